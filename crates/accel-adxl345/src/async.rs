@@ -1,9 +1,13 @@
-use ef_driver_common::mode::Async;
+use ef_driver_common::{
+    identify::{AsyncIdentify, DeviceIdentity},
+    mode::Async,
+    sync::AsyncStart,
+};
 use embedded_hal_async::i2c::I2c;
 
 use crate::{
-    Adxl345, BWRate, DataFormat, DataRate, FifoControl, FifoMode, FifoStatus, GRange, PowerControl,
-    register,
+    Adxl345, Adxl345Error, BWRate, DataFormat, DataRate, FifoControl, FifoMode, FifoStatus, GRange,
+    LowPowerRate, PowerControl, register,
 };
 
 impl<I2C: I2c> Adxl345<I2C, Async> {
@@ -32,6 +36,22 @@ impl<I2C: I2c> Adxl345<I2C, Async> {
         Ok((x, y, z))
     }
 
+    /// Get the acceleration data for X, Y, and Z axes, in mg.
+    ///
+    /// Values are scaled using the current range and resolution,
+    /// then corrected with the [`TempCompensation`](crate::TempCompensation)
+    /// at the current temperature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn get_acceleration_mg(&mut self) -> Result<(i16, i16, i16), I2C::Error> {
+        // `DATA_FORMAT` is directly followed by the data registers
+        let mut buf = [0u8; 7];
+        self.read_register(register::ADXL345_DATA_FORMAT, &mut buf).await?;
+        Ok(self.compensated_mg(buf))
+    }
+
     /// Get the offset values for X, Y, and Z axes
     ///
     /// # Errors
@@ -46,6 +66,11 @@ impl<I2C: I2c> Adxl345<I2C, Async> {
 
     /// Set the offset values for X, Y, and Z axes
     ///
+    /// After calibrating the offsets, use
+    /// [`Adxl345::record_calibration_temperature`] to make the calibration
+    /// temperature the reference of the
+    /// [`TempCompensation`](crate::TempCompensation).
+    ///
     /// # Errors
     ///
     /// Returns an error if the I2C communication fails
@@ -71,10 +96,34 @@ impl<I2C: I2c> Adxl345<I2C, Async> {
 
     /// Set the device's low power mode state.
     ///
+    /// Low power mode only supports data rates from 12.5 Hz to 400 Hz,
+    /// see [`DataRate::supports_low_power`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current data rate is not supported in low
+    /// power mode, or if the I2C communication fails
+    pub async fn set_low_power_mode(
+        &mut self,
+        low_power: bool,
+    ) -> Result<(), Adxl345Error<I2C::Error>> {
+        let mut buf = [0u8; 1];
+        self.read_register(register::ADXL345_BW_RATE, &mut buf).await.map_err(Adxl345Error::I2C)?;
+        let mut bwrate = BWRate::from_bits_truncate(buf[0]);
+        bwrate.set(BWRate::LOW_POWER, low_power);
+        let bwrate = bwrate.validate()?;
+        self.write_register(register::ADXL345_BW_RATE, bwrate.bits())
+            .await
+            .map_err(Adxl345Error::I2C)
+    }
+
+    /// Set the device's low power mode state,
+    /// even if the current data rate is not supported in low power mode.
+    ///
     /// # Errors
     ///
     /// Returns an error if the I2C communication fails
-    pub async fn set_low_power_mode(&mut self, low_power: bool) -> Result<(), I2C::Error> {
+    pub async fn force_low_power_mode(&mut self, low_power: bool) -> Result<(), I2C::Error> {
         let mut buf = [0u8; 1];
         self.read_register(register::ADXL345_BW_RATE, &mut buf).await?;
         let mut bwrate = BWRate::from_bits_truncate(buf[0]);
@@ -82,6 +131,16 @@ impl<I2C: I2c> Adxl345<I2C, Async> {
         self.write_register(register::ADXL345_BW_RATE, bwrate.bits()).await
     }
 
+    /// Enter low power mode at a supported data rate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn set_low_power_rate(&mut self, rate: LowPowerRate) -> Result<(), I2C::Error> {
+        let bwrate = BWRate::LOW_POWER | BWRate::from_bits_truncate(rate as u8);
+        self.write_register(register::ADXL345_BW_RATE, bwrate.bits()).await
+    }
+
     /// Get the device's data rate.
     ///
     /// # Errors
@@ -97,8 +156,27 @@ impl<I2C: I2c> Adxl345<I2C, Async> {
     ///
     /// # Errors
     ///
+    /// Returns an error if the device is in low power mode and the data rate
+    /// is not supported in low power mode, or if the I2C communication fails
+    pub async fn set_data_rate(&mut self, rate: DataRate) -> Result<(), Adxl345Error<I2C::Error>> {
+        let mut buf = [0u8; 1];
+        self.read_register(register::ADXL345_BW_RATE, &mut buf).await.map_err(Adxl345Error::I2C)?;
+        let mut bwrate = BWRate::from_bits_truncate(buf[0]);
+        bwrate.remove(BWRate::RATE_MASK);
+        bwrate.insert(BWRate::from_bits_truncate(rate as u8));
+        let bwrate = bwrate.validate()?;
+        self.write_register(register::ADXL345_BW_RATE, bwrate.bits())
+            .await
+            .map_err(Adxl345Error::I2C)
+    }
+
+    /// Set the device's data rate,
+    /// even if it is not supported in the current power mode.
+    ///
+    /// # Errors
+    ///
     /// Returns an error if the I2C communication fails
-    pub async fn set_data_rate(&mut self, rate: DataRate) -> Result<(), I2C::Error> {
+    pub async fn force_data_rate(&mut self, rate: DataRate) -> Result<(), I2C::Error> {
         let mut buf = [0u8; 1];
         self.read_register(register::ADXL345_BW_RATE, &mut buf).await?;
         let mut bwrate = BWRate::from_bits_truncate(buf[0]);
@@ -186,6 +264,49 @@ impl<I2C: I2c> Adxl345<I2C, Async> {
         self.write_register(register::ADXL345_POWER_CONTROL, power_ctrl.bits()).await
     }
 
+    /// Arm a capture, entering standby mode so that
+    /// [`Adxl345::start_capture`] only has to set the `MEASURE` bit.
+    ///
+    /// Configure the data rate, range and FIFO before starting the capture.
+    /// The link and auto sleep settings are kept from when the capture was
+    /// armed, and sleep mode is cleared so that measuring starts at the
+    /// configured data rate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn arm_capture(&mut self) -> Result<(), I2C::Error> {
+        let mut buf = [0u8; 1];
+        self.read_register(register::ADXL345_POWER_CONTROL, &mut buf).await?;
+        let mut power_ctrl = PowerControl::from_bits_truncate(buf[0]);
+        power_ctrl.remove(PowerControl::MEASURE | PowerControl::SLEEP);
+        self.write_register(register::ADXL345_POWER_CONTROL, power_ctrl.bits()).await?;
+        self.armed = Some(power_ctrl);
+        Ok(())
+    }
+
+    /// Start a capture armed with [`Adxl345::arm_capture`],
+    /// leaving standby mode with a single register write.
+    ///
+    /// If no capture was armed, the power control register is read first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn start_capture(&mut self) -> Result<(), I2C::Error> {
+        let power_ctrl = if let Some(power_ctrl) = self.armed {
+            power_ctrl
+        } else {
+            let mut buf = [0u8; 1];
+            self.read_register(register::ADXL345_POWER_CONTROL, &mut buf).await?;
+            PowerControl::from_bits_truncate(buf[0])
+        };
+        let power_ctrl = power_ctrl | PowerControl::MEASURE;
+        self.write_register(register::ADXL345_POWER_CONTROL, power_ctrl.bits()).await?;
+        self.armed = None;
+        Ok(())
+    }
+
     /// Get whether the device is in full resolution mode.
     ///
     /// When `true`, the output resolution changes based on the selected
@@ -267,7 +388,7 @@ impl<I2C: I2c> Adxl345<I2C, Async> {
         self.read_register(register::ADXL345_FIFO_CONTROL, &mut buf).await?;
         let mut control = FifoControl::from_bits_truncate(buf[0]);
         control.remove(FifoControl::FIFO_MASK);
-        control.insert(FifoControl::from_bits_truncate(mode as u8));
+        control.insert(FifoControl::from_bits_truncate((mode as u8) << 6));
         self.write_register(register::ADXL345_FIFO_CONTROL, control.bits()).await
     }
 
@@ -321,12 +442,38 @@ impl<I2C: I2c> Adxl345<I2C, Async> {
     }
 
     /// Read data from a register
-    async fn read_register(&mut self, register: u8, buf: &mut [u8]) -> Result<(), I2C::Error> {
+    pub(crate) async fn read_register(
+        &mut self,
+        register: u8,
+        buf: &mut [u8],
+    ) -> Result<(), I2C::Error> {
         self.i2c.write_read(self.address, core::slice::from_ref(&register), buf).await
     }
 
     // Write data to a register
-    async fn write_register(&mut self, register: u8, value: u8) -> Result<(), I2C::Error> {
+    pub(crate) async fn write_register(
+        &mut self,
+        register: u8,
+        value: u8,
+    ) -> Result<(), I2C::Error> {
         self.i2c.write(self.address, [register, value].as_slice()).await
     }
 }
+
+impl<I2C: I2c> AsyncStart for Adxl345<I2C, Async> {
+    type Error = I2C::Error;
+
+    /// Start a capture, see [`Adxl345::start_capture`].
+    #[inline]
+    async fn start(&mut self) -> Result<(), Self::Error> { self.start_capture().await }
+}
+
+impl<I2C: I2c> AsyncIdentify for Adxl345<I2C, Async> {
+    type Error = I2C::Error;
+
+    /// Read the device ID, see [`Adxl345::get_device_id`].
+    async fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> {
+        let id = self.get_device_id().await?;
+        Ok(DeviceIdentity::new("ADXL345", &[id], None))
+    }
+}
@@ -1,9 +1,13 @@
-use ef_driver_common::mode::Blocking;
+use ef_driver_common::{
+    identify::{DeviceIdentity, Identify},
+    mode::Blocking,
+    sync::Start,
+};
 use embedded_hal::i2c::I2c;
 
 use crate::{
-    Adxl345, BWRate, DataFormat, DataRate, FifoControl, FifoMode, FifoStatus, GRange, PowerControl,
-    register,
+    Adxl345, Adxl345Error, BWRate, DataFormat, DataRate, FifoControl, FifoMode, FifoStatus, GRange,
+    LowPowerRate, PowerControl, register,
 };
 
 impl<I2C: I2c> Adxl345<I2C, Blocking> {
@@ -32,6 +36,48 @@ impl<I2C: I2c> Adxl345<I2C, Blocking> {
         Ok((x, y, z))
     }
 
+    /// Get the acceleration data for X, Y, and Z axes, in mg.
+    ///
+    /// Values are scaled using the current range and resolution,
+    /// then corrected with the [`TempCompensation`](crate::TempCompensation)
+    /// at the current temperature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_adxl345::{Adxl345, TempCompensation, mode::Blocking};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_adxl345::test_util::Registers;
+    ///
+    /// // Full resolution at ±16 g, reading (256, 0, -128) LSB
+    /// let mut registers = [0u8; 64];
+    /// registers[0x31..0x38].copy_from_slice(&[0b0000_1011, 0x00, 0x01, 0x00, 0x00, 0x80, 0xFF]);
+    /// let mut accel = Adxl345::<_, Blocking>::new(Registers::new(registers), 0x53);
+    /// assert_eq!(accel.get_acceleration(), Ok((256, 0, -128)));
+    /// assert_eq!(accel.get_acceleration_mg(), Ok((998, 0, -499)));
+    ///
+    /// // Calibrated at 25 °C, with the X axis drifting by +2 mg/°C
+    /// accel.set_current_temperature(25_00);
+    /// accel.set_temperature_compensation(TempCompensation::new([2_000, 0, 0], 0));
+    /// accel.record_calibration_temperature();
+    ///
+    /// accel.set_current_temperature(35_00);
+    /// assert_eq!(accel.get_acceleration_mg(), Ok((978, 0, -499)));
+    ///
+    /// // Raw values are never compensated
+    /// assert_eq!(accel.get_acceleration(), Ok((256, 0, -128)));
+    /// ```
+    pub fn get_acceleration_mg(&mut self) -> Result<(i16, i16, i16), I2C::Error> {
+        // `DATA_FORMAT` is directly followed by the data registers
+        let mut buf = [0u8; 7];
+        self.read_register(register::ADXL345_DATA_FORMAT, &mut buf)?;
+        Ok(self.compensated_mg(buf))
+    }
+
     /// Get the offset values for X, Y, and Z axes
     ///
     /// # Errors
@@ -46,6 +92,11 @@ impl<I2C: I2c> Adxl345<I2C, Blocking> {
 
     /// Set the offset values for X, Y, and Z axes
     ///
+    /// After calibrating the offsets, use
+    /// [`Adxl345::record_calibration_temperature`] to make the calibration
+    /// temperature the reference of the
+    /// [`TempCompensation`](crate::TempCompensation).
+    ///
     /// # Errors
     ///
     /// Returns an error if the I2C communication fails
@@ -71,10 +122,81 @@ impl<I2C: I2c> Adxl345<I2C, Blocking> {
 
     /// Set the device's low power mode state.
     ///
+    /// Low power mode only supports data rates from 12.5 Hz to 400 Hz,
+    /// see [`DataRate::supports_low_power`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current data rate is not supported in low
+    /// power mode, or if the I2C communication fails
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_adxl345::{Adxl345, Adxl345Error, DataRate, LowPowerRate, mode::Blocking};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_adxl345::test_util::Registers;
+    ///
+    /// let mut accel = Adxl345::<_, Blocking>::new(Registers::default(), 0x53);
+    ///
+    /// // Low power mode supports 12.5 Hz to 400 Hz
+    /// const LOW_POWER: [DataRate; 6] = [
+    ///     DataRate::Hz12_5,
+    ///     DataRate::Hz25,
+    ///     DataRate::Hz50,
+    ///     DataRate::Hz100,
+    ///     DataRate::Hz200,
+    ///     DataRate::Hz400,
+    /// ];
+    ///
+    /// for byte in 0..16 {
+    ///     let rate = DataRate::from_byte(byte);
+    ///     let valid = LOW_POWER.contains(&rate);
+    ///     assert_eq!(rate.supports_low_power(), valid);
+    ///     assert_eq!(LowPowerRate::try_from(rate).is_ok(), valid);
+    ///
+    ///     // Every rate is supported in normal mode
+    ///     accel.force_low_power_mode(false)?;
+    ///     accel.set_data_rate(rate)?;
+    ///
+    ///     // Entering low power mode is rejected at unsupported rates
+    ///     let result = accel.set_low_power_mode(true);
+    ///     if valid {
+    ///         assert_eq!(result, Ok(()));
+    ///     } else {
+    ///         assert_eq!(result, Err(Adxl345Error::InvalidCombination(rate)));
+    ///     }
+    ///     assert_eq!(accel.get_low_power_mode()?, valid);
+    ///
+    ///     // Unsupported rates are rejected in low power mode, keeping the register
+    ///     accel.set_low_power_rate(LowPowerRate::Hz50)?;
+    ///     let result = accel.set_data_rate(rate);
+    ///     assert_eq!(result.is_ok(), valid);
+    ///     assert_eq!(accel.get_data_rate()?, if valid { rate } else { DataRate::Hz50 });
+    ///
+    ///     // Unless forced
+    ///     accel.force_data_rate(rate)?;
+    ///     assert_eq!(accel.get_data_rate()?, rate);
+    ///     assert!(accel.get_low_power_mode()?);
+    /// }
+    /// # Ok::<(), Adxl345Error<ErrorKind>>(())
+    /// ```
+    pub fn set_low_power_mode(&mut self, low_power: bool) -> Result<(), Adxl345Error<I2C::Error>> {
+        let mut buf = [0u8; 1];
+        self.read_register(register::ADXL345_BW_RATE, &mut buf).map_err(Adxl345Error::I2C)?;
+        let mut bwrate = BWRate::from_bits_truncate(buf[0]);
+        bwrate.set(BWRate::LOW_POWER, low_power);
+        let bwrate = bwrate.validate()?;
+        self.write_register(register::ADXL345_BW_RATE, bwrate.bits()).map_err(Adxl345Error::I2C)
+    }
+
+    /// Set the device's low power mode state,
+    /// even if the current data rate is not supported in low power mode.
+    ///
     /// # Errors
     ///
     /// Returns an error if the I2C communication fails
-    pub fn set_low_power_mode(&mut self, low_power: bool) -> Result<(), I2C::Error> {
+    pub fn force_low_power_mode(&mut self, low_power: bool) -> Result<(), I2C::Error> {
         let mut buf = [0u8; 1];
         self.read_register(register::ADXL345_BW_RATE, &mut buf)?;
         let mut bwrate = BWRate::from_bits_truncate(buf[0]);
@@ -82,6 +204,16 @@ impl<I2C: I2c> Adxl345<I2C, Blocking> {
         self.write_register(register::ADXL345_BW_RATE, bwrate.bits())
     }
 
+    /// Enter low power mode at a supported data rate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn set_low_power_rate(&mut self, rate: LowPowerRate) -> Result<(), I2C::Error> {
+        let bwrate = BWRate::LOW_POWER | BWRate::from_bits_truncate(rate as u8);
+        self.write_register(register::ADXL345_BW_RATE, bwrate.bits())
+    }
+
     /// Get the device's data rate.
     ///
     /// # Errors
@@ -97,8 +229,25 @@ impl<I2C: I2c> Adxl345<I2C, Blocking> {
     ///
     /// # Errors
     ///
+    /// Returns an error if the device is in low power mode and the data rate
+    /// is not supported in low power mode, or if the I2C communication fails
+    pub fn set_data_rate(&mut self, rate: DataRate) -> Result<(), Adxl345Error<I2C::Error>> {
+        let mut buf = [0u8; 1];
+        self.read_register(register::ADXL345_BW_RATE, &mut buf).map_err(Adxl345Error::I2C)?;
+        let mut bwrate = BWRate::from_bits_truncate(buf[0]);
+        bwrate.remove(BWRate::RATE_MASK);
+        bwrate.insert(BWRate::from_bits_truncate(rate as u8));
+        let bwrate = bwrate.validate()?;
+        self.write_register(register::ADXL345_BW_RATE, bwrate.bits()).map_err(Adxl345Error::I2C)
+    }
+
+    /// Set the device's data rate,
+    /// even if it is not supported in the current power mode.
+    ///
+    /// # Errors
+    ///
     /// Returns an error if the I2C communication fails
-    pub fn set_data_rate(&mut self, rate: DataRate) -> Result<(), I2C::Error> {
+    pub fn force_data_rate(&mut self, rate: DataRate) -> Result<(), I2C::Error> {
         let mut buf = [0u8; 1];
         self.read_register(register::ADXL345_BW_RATE, &mut buf)?;
         let mut bwrate = BWRate::from_bits_truncate(buf[0]);
@@ -186,6 +335,82 @@ impl<I2C: I2c> Adxl345<I2C, Blocking> {
         self.write_register(register::ADXL345_POWER_CONTROL, power_ctrl.bits())
     }
 
+    /// Arm a capture, entering standby mode so that
+    /// [`Adxl345::start_capture`] only has to set the `MEASURE` bit.
+    ///
+    /// Configure the data rate, range and FIFO before starting the capture.
+    /// The link and auto sleep settings are kept from when the capture was
+    /// armed, and sleep mode is cleared so that measuring starts at the
+    /// configured data rate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_adxl345::{Adxl345, mode::Blocking};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_adxl345::test_util::Registers;
+    ///
+    /// // Measuring in link mode, asleep
+    /// let mut registers = [0u8; 64];
+    /// registers[0x2D] = 0b0010_1100;
+    /// let mut accel = Adxl345::<_, Blocking>::new(Registers::new(registers), 0x53);
+    ///
+    /// // Arming enters standby and wakes the device, keeping link mode
+    /// accel.arm_capture()?;
+    /// assert!(accel.is_armed());
+    /// assert_eq!(accel.i2c().registers[0x2D], 0b0010_0000);
+    ///
+    /// // Starting is a single write
+    /// accel.i2c_mut().clear();
+    /// accel.start_capture()?;
+    /// assert!(!accel.is_armed());
+    /// assert!(accel.i2c().reads.is_empty());
+    /// assert_eq!(accel.i2c().writes, [(0x2D, 0b0010_1000)]);
+    ///
+    /// // Without arming, the register is read first
+    /// accel.set_standby_mode(true)?;
+    /// accel.i2c_mut().clear();
+    /// accel.start_capture()?;
+    /// assert_eq!(accel.i2c().reads, [(0x2D, 1)]);
+    /// assert_eq!(accel.i2c().writes, [(0x2D, 0b0010_1000)]);
+    /// # Ok::<(), ErrorKind>(())
+    /// ```
+    pub fn arm_capture(&mut self) -> Result<(), I2C::Error> {
+        let mut buf = [0u8; 1];
+        self.read_register(register::ADXL345_POWER_CONTROL, &mut buf)?;
+        let mut power_ctrl = PowerControl::from_bits_truncate(buf[0]);
+        power_ctrl.remove(PowerControl::MEASURE | PowerControl::SLEEP);
+        self.write_register(register::ADXL345_POWER_CONTROL, power_ctrl.bits())?;
+        self.armed = Some(power_ctrl);
+        Ok(())
+    }
+
+    /// Start a capture armed with [`Adxl345::arm_capture`],
+    /// leaving standby mode with a single register write.
+    ///
+    /// If no capture was armed, the power control register is read first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn start_capture(&mut self) -> Result<(), I2C::Error> {
+        let power_ctrl = if let Some(power_ctrl) = self.armed {
+            power_ctrl
+        } else {
+            let mut buf = [0u8; 1];
+            self.read_register(register::ADXL345_POWER_CONTROL, &mut buf)?;
+            PowerControl::from_bits_truncate(buf[0])
+        };
+        let power_ctrl = power_ctrl | PowerControl::MEASURE;
+        self.write_register(register::ADXL345_POWER_CONTROL, power_ctrl.bits())?;
+        self.armed = None;
+        Ok(())
+    }
+
     /// Get whether the device is in full resolution mode.
     ///
     /// When `true`, the output resolution changes based on the selected
@@ -267,7 +492,7 @@ impl<I2C: I2c> Adxl345<I2C, Blocking> {
         self.read_register(register::ADXL345_FIFO_CONTROL, &mut buf)?;
         let mut control = FifoControl::from_bits_truncate(buf[0]);
         control.remove(FifoControl::FIFO_MASK);
-        control.insert(FifoControl::from_bits_truncate(mode as u8));
+        control.insert(FifoControl::from_bits_truncate((mode as u8) << 6));
         self.write_register(register::ADXL345_FIFO_CONTROL, control.bits())
     }
 
@@ -321,12 +546,65 @@ impl<I2C: I2c> Adxl345<I2C, Blocking> {
     }
 
     /// Read data from a register
-    fn read_register(&mut self, register: u8, buf: &mut [u8]) -> Result<(), I2C::Error> {
+    pub(crate) fn read_register(&mut self, register: u8, buf: &mut [u8]) -> Result<(), I2C::Error> {
         self.i2c.write_read(self.address, core::slice::from_ref(&register), buf)
     }
 
     // Write data to a register
-    fn write_register(&mut self, register: u8, value: u8) -> Result<(), I2C::Error> {
+    pub(crate) fn write_register(&mut self, register: u8, value: u8) -> Result<(), I2C::Error> {
         self.i2c.write(self.address, [register, value].as_slice())
     }
 }
+
+impl<I2C: I2c> Start for Adxl345<I2C, Blocking> {
+    type Error = I2C::Error;
+
+    /// Start a capture, see [`Adxl345::start_capture`].
+    #[inline]
+    fn start(&mut self) -> Result<(), Self::Error> { self.start_capture() }
+}
+
+impl<I2C: I2c> Identify for Adxl345<I2C, Blocking> {
+    type Error = I2C::Error;
+
+    /// Read the device ID, see [`Adxl345::get_device_id`].
+    ///
+    /// An ADXL345 reports `0xE5`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_adxl345::{Adxl345, mode::Blocking};
+    /// use ef_driver_common::identify::Identify;
+    /// use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
+    ///
+    /// /// A bus with an ADXL345 at `0x53`.
+    /// struct Bus;
+    ///
+    /// impl ErrorType for Bus {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl I2c for Bus {
+    ///     fn transaction(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+    ///         assert_eq!(address, 0x53);
+    ///         match ops {
+    ///             [Operation::Write([0x00]), Operation::Read([id])] => *id = 0xE5,
+    ///             _ => unreachable!(),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let identity = Adxl345::<_, Blocking>::new(Bus, 0x53).identify()?;
+    /// assert_eq!(
+    ///     (identity.name(), identity.id(), identity.version()),
+    ///     ("ADXL345", &[0xE5][..], None)
+    /// );
+    /// # Ok::<(), ErrorKind>(())
+    /// ```
+    fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> {
+        let id = self.get_device_id()?;
+        Ok(DeviceIdentity::new("ADXL345", &[id], None))
+    }
+}
@@ -0,0 +1,362 @@
+//! Capturing the samples leading up to the device coming to rest.
+//!
+//! The FIFO is put into trigger mode, with the inactivity interrupt as the
+//! trigger event. While the device is moving the FIFO keeps the most recent
+//! samples, and once the device is at rest for the configured time the
+//! samples from before the trigger are kept for reading.
+
+use ef_driver_common::mode::{Async, Blocking, DriverMode};
+use embedded_hal::i2c::I2c;
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use crate::{ActivityControl, Adxl345, Axes, InterruptPin, Interrupts, PowerControl, register};
+
+/// The configuration of a [`PreEventCapture`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CaptureConfig {
+    /// The inactivity threshold, in 62.5 mg steps.
+    pub inactivity_threshold: u8,
+    /// How long the device must be inactive before triggering, in seconds.
+    pub inactivity_time: u8,
+    /// The axes used for activity and inactivity detection.
+    pub axes: Axes,
+    /// Whether inactivity detection is AC-coupled.
+    pub ac_coupled: bool,
+    /// The activity threshold, in 62.5 mg steps.
+    ///
+    /// When set, link mode is enabled and inactivity is only detected after
+    /// activity, so the device must move before a capture is triggered.
+    pub activity_threshold: Option<u8>,
+    /// The number of samples from before the trigger to keep (0-31).
+    ///
+    /// The remainder of the 32-sample FIFO is filled after the trigger.
+    pub samples: u8,
+    /// The interrupt pin that the inactivity interrupt is mapped to.
+    ///
+    /// The FIFO is triggered by events on the same pin.
+    pub pin: InterruptPin,
+}
+
+impl CaptureConfig {
+    /// Trigger after 2 seconds below 250 mg, keeping 31 samples.
+    pub const DEFAULT: Self = Self {
+        inactivity_threshold: 4,
+        inactivity_time: 2,
+        axes: Axes::all(),
+        ac_coupled: true,
+        activity_threshold: Some(16),
+        samples: 31,
+        pin: InterruptPin::Int1,
+    };
+
+    /// The value of the `FIFO_CTL` register in trigger mode.
+    const fn fifo_control(self) -> u8 {
+        let pin = match self.pin {
+            InterruptPin::Int1 => 0,
+            InterruptPin::Int2 => 0b0010_0000,
+        };
+        0b1100_0000 | pin | (self.samples & 0b0001_1111)
+    }
+
+    /// Update the `ACT_INACT_CTL` register.
+    const fn activity_control(self, control: ActivityControl) -> ActivityControl {
+        let mut bits = control.bits() & !ActivityControl::INACTIVITY_AXES_MASK.bits();
+        bits &= !ActivityControl::INACTIVITY_AC.bits();
+        bits |= self.axes.bits();
+        if self.ac_coupled {
+            bits |= ActivityControl::INACTIVITY_AC.bits();
+        }
+        if self.activity_threshold.is_some() {
+            bits &= !ActivityControl::ACTIVITY_AXES_MASK.bits();
+            bits |= self.axes.bits() << 4;
+        }
+        ActivityControl::from_bits_truncate(bits)
+    }
+
+    /// The interrupts used by the capture.
+    const fn interrupts(self) -> Interrupts {
+        if self.activity_threshold.is_some() {
+            Interrupts::INACTIVITY.union(Interrupts::ACTIVITY)
+        } else {
+            Interrupts::INACTIVITY
+        }
+    }
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// A helper for capturing the samples leading up to inactivity.
+///
+/// # Notes
+///
+/// - Auto sleep is disabled while armed, as sleep mode lowers the data rate and
+///   would leave the FIFO with sparse samples.
+/// - The trigger bit of `FIFO_CTL` selects the interrupt *pin*, so the
+///   inactivity interrupt must be mapped to the same pin. Other interrupts
+///   mapped to that pin also trigger the FIFO.
+/// - In link mode, inactivity is only detected after activity. After a capture
+///   the device must move again before the next trigger.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_adxl345::{Adxl345, CaptureConfig, PreEventCapture, mode::Blocking};
+/// use embedded_hal::i2c::ErrorKind;
+/// # use ef_adxl345::test_util::Registers;
+///
+/// let mut registers = [0u8; 64];
+/// registers[0x2D] = 0b0001_1000; // Measuring, with auto sleep
+/// let mut accel = Adxl345::<_, Blocking>::new(Registers::new(registers), 0x53);
+///
+/// let mut capture = PreEventCapture::new(&mut accel, CaptureConfig::DEFAULT);
+/// capture.arm()?;
+/// assert!(!capture.is_captured()?);
+///
+/// assert_eq!(
+///     accel.i2c().writes,
+///     [
+///         (0x2D, 0b0010_0000), // Standby, link mode, no auto sleep
+///         (0x38, 0b0000_0000), // Clear the FIFO
+///         (0x25, 4),           // Inactivity threshold
+///         (0x26, 2),           // Inactivity time
+///         (0x24, 16),          // Activity threshold
+///         (0x27, 0b0111_1111), // Activity and AC-coupled inactivity on all axes
+///         (0x2F, 0b0000_0000), // Map (in)activity to INT1
+///         (0x2E, 0b0001_1000), // Enable (in)activity
+///         (0x38, 0b1101_1111), // Trigger mode on INT1, keep 31 samples
+///         (0x2D, 0b0010_1000), // Start measuring
+///     ]
+/// );
+///
+/// // Simulate the device coming to rest with a full FIFO
+/// accel.i2c_mut().registers[0x39] = 0b1010_0000;
+/// accel.i2c_mut().registers[0x32..0x38].copy_from_slice(&[0x10, 0, 0xF0, 0xFF, 0x00, 0x01]);
+///
+/// let mut capture = PreEventCapture::new(&mut accel, CaptureConfig::DEFAULT);
+/// assert!(capture.is_captured()?);
+///
+/// // Read the samples, oldest first, and look for the last high-g sample
+/// let mut samples = [(0, 0, 0); 32];
+/// let count = capture.read(&mut samples)?;
+/// assert_eq!(count, 32);
+/// assert_eq!(samples[0], (16, -16, 256));
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct PreEventCapture<'a, I2C, MODE: DriverMode> {
+    accel: &'a mut Adxl345<I2C, MODE>,
+    config: CaptureConfig,
+}
+
+impl<'a, I2C, MODE: DriverMode> PreEventCapture<'a, I2C, MODE> {
+    /// Create a new [`PreEventCapture`].
+    ///
+    /// The device is not configured until [`PreEventCapture::arm`] is called.
+    #[inline]
+    #[must_use]
+    pub const fn new(accel: &'a mut Adxl345<I2C, MODE>, config: CaptureConfig) -> Self {
+        Self { accel, config }
+    }
+
+    /// Get the [`CaptureConfig`].
+    #[inline]
+    #[must_use]
+    pub const fn config(&self) -> &CaptureConfig { &self.config }
+
+    /// The value of the `POWER_CTL` register while configuring.
+    const fn standby_power(&self, power: PowerControl) -> PowerControl {
+        let mut bits = power.bits() & !PowerControl::MEASURE.bits();
+        bits &= !PowerControl::AUTO_SLEEP.bits();
+        if self.config.activity_threshold.is_some() {
+            bits |= PowerControl::LINK.bits();
+        } else {
+            bits &= !PowerControl::LINK.bits();
+        }
+        PowerControl::from_bits_truncate(bits)
+    }
+
+    /// The value of the `INT_MAP` register.
+    const fn interrupt_map(&self, map: Interrupts) -> Interrupts {
+        match self.config.pin {
+            InterruptPin::Int1 => map.difference(self.config.interrupts()),
+            InterruptPin::Int2 => map.union(self.config.interrupts()),
+        }
+    }
+}
+
+impl<I2C: I2c> PreEventCapture<'_, I2C, Blocking> {
+    /// Configure the device and start capturing.
+    ///
+    /// The device is configured in standby mode and starts measuring once
+    /// everything is set up.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn arm(&mut self) -> Result<(), I2C::Error> {
+        let config = self.config;
+        let mut buf = [0u8; 1];
+
+        // Configure in standby mode
+        self.accel.read_register(register::ADXL345_POWER_CONTROL, &mut buf)?;
+        let power = self.standby_power(PowerControl::from_bits_truncate(buf[0]));
+        self.accel.write_register(register::ADXL345_POWER_CONTROL, power.bits())?;
+
+        // Clear the FIFO
+        self.accel.write_register(register::ADXL345_FIFO_CONTROL, 0)?;
+
+        // Configure (in)activity detection
+        self.accel
+            .write_register(register::ADXL345_INACTIVITY_THRESHOLD, config.inactivity_threshold)?;
+        self.accel.write_register(register::ADXL345_INACTIVITY_TIME, config.inactivity_time)?;
+        if let Some(threshold) = config.activity_threshold {
+            self.accel.write_register(register::ADXL345_ACTIVITY_THRESHOLD, threshold)?;
+        }
+        self.accel.read_register(register::ADXL345_INACTIVITY_ENABLE, &mut buf)?;
+        let control = config.activity_control(ActivityControl::from_bits_truncate(buf[0]));
+        self.accel.write_register(register::ADXL345_INACTIVITY_ENABLE, control.bits())?;
+
+        // Route the interrupts to the trigger pin
+        self.accel.read_register(register::ADXL345_INTERRUPT_MAP, &mut buf)?;
+        let map = self.interrupt_map(Interrupts::from_bits_truncate(buf[0]));
+        self.accel.write_register(register::ADXL345_INTERRUPT_MAP, map.bits())?;
+        self.accel.read_register(register::ADXL345_INTERRUPT_ENABLE, &mut buf)?;
+        let enable = Interrupts::from_bits_truncate(buf[0]).union(config.interrupts());
+        self.accel.write_register(register::ADXL345_INTERRUPT_ENABLE, enable.bits())?;
+
+        // Enter trigger mode and clear any stale interrupts
+        self.accel.write_register(register::ADXL345_FIFO_CONTROL, config.fifo_control())?;
+        self.accel.read_register(register::ADXL345_INTERRUPT_SOURCE, &mut buf)?;
+
+        // Start measuring
+        let power = power.union(PowerControl::MEASURE);
+        self.accel.write_register(register::ADXL345_POWER_CONTROL, power.bits())
+    }
+
+    /// Returns `true` if a capture has been triggered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    #[inline]
+    pub fn is_captured(&mut self) -> Result<bool, I2C::Error> { self.accel.get_fifo_trigger() }
+
+    /// Read the captured samples into `out`, oldest first.
+    ///
+    /// Returns the number of samples read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn read(&mut self, out: &mut [(i16, i16, i16)]) -> Result<usize, I2C::Error> {
+        let entries = usize::from(self.accel.get_fifo_entries()?).min(out.len());
+        for sample in &mut out[..entries] {
+            *sample = self.accel.get_acceleration()?;
+        }
+        Ok(entries)
+    }
+
+    /// Discard the FIFO contents and wait for the next trigger.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn rearm(&mut self) -> Result<(), I2C::Error> {
+        let mut buf = [0u8; 1];
+        self.accel.read_register(register::ADXL345_INTERRUPT_SOURCE, &mut buf)?;
+        self.accel.write_register(register::ADXL345_FIFO_CONTROL, 0)?;
+        self.accel.write_register(register::ADXL345_FIFO_CONTROL, self.config.fifo_control())
+    }
+}
+
+impl<I2C: AsyncI2c> PreEventCapture<'_, I2C, Async> {
+    /// Configure the device and start capturing.
+    ///
+    /// The device is configured in standby mode and starts measuring once
+    /// everything is set up.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn arm(&mut self) -> Result<(), I2C::Error> {
+        let config = self.config;
+        let mut buf = [0u8; 1];
+
+        // Configure in standby mode
+        self.accel.read_register(register::ADXL345_POWER_CONTROL, &mut buf).await?;
+        let power = self.standby_power(PowerControl::from_bits_truncate(buf[0]));
+        self.accel.write_register(register::ADXL345_POWER_CONTROL, power.bits()).await?;
+
+        // Clear the FIFO
+        self.accel.write_register(register::ADXL345_FIFO_CONTROL, 0).await?;
+
+        // Configure (in)activity detection
+        self.accel
+            .write_register(register::ADXL345_INACTIVITY_THRESHOLD, config.inactivity_threshold)
+            .await?;
+        self.accel
+            .write_register(register::ADXL345_INACTIVITY_TIME, config.inactivity_time)
+            .await?;
+        if let Some(threshold) = config.activity_threshold {
+            self.accel.write_register(register::ADXL345_ACTIVITY_THRESHOLD, threshold).await?;
+        }
+        self.accel.read_register(register::ADXL345_INACTIVITY_ENABLE, &mut buf).await?;
+        let control = config.activity_control(ActivityControl::from_bits_truncate(buf[0]));
+        self.accel.write_register(register::ADXL345_INACTIVITY_ENABLE, control.bits()).await?;
+
+        // Route the interrupts to the trigger pin
+        self.accel.read_register(register::ADXL345_INTERRUPT_MAP, &mut buf).await?;
+        let map = self.interrupt_map(Interrupts::from_bits_truncate(buf[0]));
+        self.accel.write_register(register::ADXL345_INTERRUPT_MAP, map.bits()).await?;
+        self.accel.read_register(register::ADXL345_INTERRUPT_ENABLE, &mut buf).await?;
+        let enable = Interrupts::from_bits_truncate(buf[0]).union(config.interrupts());
+        self.accel.write_register(register::ADXL345_INTERRUPT_ENABLE, enable.bits()).await?;
+
+        // Enter trigger mode and clear any stale interrupts
+        self.accel.write_register(register::ADXL345_FIFO_CONTROL, config.fifo_control()).await?;
+        self.accel.read_register(register::ADXL345_INTERRUPT_SOURCE, &mut buf).await?;
+
+        // Start measuring
+        let power = power.union(PowerControl::MEASURE);
+        self.accel.write_register(register::ADXL345_POWER_CONTROL, power.bits()).await
+    }
+
+    /// Returns `true` if a capture has been triggered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    #[inline]
+    pub async fn is_captured(&mut self) -> Result<bool, I2C::Error> {
+        self.accel.get_fifo_trigger().await
+    }
+
+    /// Read the captured samples into `out`, oldest first.
+    ///
+    /// Returns the number of samples read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn read(&mut self, out: &mut [(i16, i16, i16)]) -> Result<usize, I2C::Error> {
+        let entries = usize::from(self.accel.get_fifo_entries().await?).min(out.len());
+        for sample in &mut out[..entries] {
+            *sample = self.accel.get_acceleration().await?;
+        }
+        Ok(entries)
+    }
+
+    /// Discard the FIFO contents and wait for the next trigger.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn rearm(&mut self) -> Result<(), I2C::Error> {
+        let mut buf = [0u8; 1];
+        self.accel.read_register(register::ADXL345_INTERRUPT_SOURCE, &mut buf).await?;
+        self.accel.write_register(register::ADXL345_FIFO_CONTROL, 0).await?;
+        self.accel.write_register(register::ADXL345_FIFO_CONTROL, self.config.fifo_control()).await
+    }
+}
@@ -0,0 +1,125 @@
+//! Temperature compensation of the offset drift.
+
+use crate::{DataFormat, GRange};
+
+/// A linear, per-axis correction of the offset drift over temperature.
+///
+/// The ADXL345 does not measure its own temperature, so the current
+/// temperature must be provided by another sensor with
+/// [`Adxl345::set_current_temperature`](crate::Adxl345::set_current_temperature).
+///
+/// All math is done in fixed point and saturates instead of overflowing.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_adxl345::TempCompensation;
+///
+/// // The X axis drifts by +1 mg/°C, the Z axis by -0.5 mg/°C
+/// let compensation = TempCompensation::new([1_000, 0, -500], 25_00);
+///
+/// // No correction at the reference temperature
+/// assert_eq!(compensation.apply(25_00, (100, 200, 1000)), (100, 200, 1000));
+///
+/// // At the extremes of the operating range
+/// assert_eq!(compensation.apply(60_00, (135, 200, 982)), (100, 200, 1000));
+/// assert_eq!(compensation.apply(-20_00, (55, 200, 1023)), (100, 200, 1000));
+///
+/// // Fractions of a degree are rounded to the nearest mg
+/// assert_eq!(compensation.correction(25_50), (1, 0, 0));
+/// assert_eq!(compensation.correction(24_49), (-1, 0, 0));
+///
+/// // The output saturates at the limits of `i16`
+/// let compensation = TempCompensation::new([i16::MAX, 0, i16::MIN], 0);
+/// assert_eq!(compensation.apply(i16::MIN, (i16::MAX, 0, i16::MIN)), (i16::MAX, 0, i16::MIN));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TempCompensation {
+    /// The drift of each axis, in µg/°C (thousandths of a mg/°C).
+    pub slope: [i16; 3],
+    /// The temperature at which the offsets were calibrated, in centi-°C.
+    pub reference: i16,
+}
+
+impl TempCompensation {
+    /// No compensation.
+    pub const NONE: Self = Self { slope: [0; 3], reference: 0 };
+
+    /// Create a new [`TempCompensation`].
+    #[inline]
+    #[must_use]
+    pub const fn new(slope: [i16; 3], reference: i16) -> Self { Self { slope, reference } }
+
+    /// Set the reference temperature, in centi-°C.
+    #[inline]
+    #[must_use]
+    pub const fn with_reference(mut self, reference: i16) -> Self {
+        self.reference = reference;
+        self
+    }
+
+    /// Get the drift of each axis at a temperature, in mg.
+    ///
+    /// Results are rounded to the nearest mg, away from zero on ties.
+    #[must_use]
+    pub const fn correction(self, temperature: i16) -> (i16, i16, i16) {
+        let delta = temperature as i64 - self.reference as i64;
+        (
+            Self::axis_correction(self.slope[0], delta),
+            Self::axis_correction(self.slope[1], delta),
+            Self::axis_correction(self.slope[2], delta),
+        )
+    }
+
+    /// Remove the drift at a temperature from acceleration values in mg.
+    #[must_use]
+    pub const fn apply(self, temperature: i16, (x, y, z): (i16, i16, i16)) -> (i16, i16, i16) {
+        let (dx, dy, dz) = self.correction(temperature);
+        (x.saturating_sub(dx), y.saturating_sub(dy), z.saturating_sub(dz))
+    }
+
+    /// Get the drift of a single axis, in mg.
+    #[expect(clippy::cast_possible_truncation, reason = "Clamped")]
+    const fn axis_correction(slope: i16, delta: i64) -> i16 {
+        // µg/°C * centi-°C = 1/100_000 mg
+        const SCALE: i64 = 100_000;
+
+        let product = slope as i64 * delta;
+        let rounded =
+            if product < 0 { (product - SCALE / 2) / SCALE } else { (product + SCALE / 2) / SCALE };
+
+        if rounded > i16::MAX as i64 {
+            i16::MAX
+        } else if rounded < i16::MIN as i64 {
+            i16::MIN
+        } else {
+            rounded as i16
+        }
+    }
+}
+
+/// Scale a raw acceleration value to mg, using the `DATA_FORMAT` register.
+#[expect(clippy::cast_possible_truncation, reason = "Clamped")]
+pub(crate) const fn scale_to_mg(format: DataFormat, raw: i16) -> i16 {
+    let range = GRange::from_byte(format.bits()) as u32;
+
+    // Full resolution keeps 3.9 mg/LSB by adding a bit per range,
+    // 10-bit mode doubles the scale instead.
+    let (bits, shift) =
+        if format.contains(DataFormat::FULL_RESOLUTION) { (10 + range, 0) } else { (10, range) };
+
+    // Left-justified values keep their most significant bit in bit 15.
+    let raw = if format.contains(DataFormat::JUSTIFY) { raw >> (16 - bits) } else { raw };
+
+    // 3.9 mg/LSB, rounded to the nearest mg
+    let tenths = (raw as i32 * 39) << shift;
+    let mg = if tenths < 0 { (tenths - 5) / 10 } else { (tenths + 5) / 10 };
+    if mg > i16::MAX as i32 {
+        i16::MAX
+    } else if mg < i16::MIN as i32 {
+        i16::MIN
+    } else {
+        mg as i16
+    }
+}
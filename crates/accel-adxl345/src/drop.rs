@@ -0,0 +1,407 @@
+//! Detecting drops, a free-fall followed by an impact.
+//!
+//! Neither interrupt is enough on its own: free-fall is also seen when the
+//! device is thrown or swung, and activity when it is knocked while at rest.
+//! The [`DropDetector`] reports a drop when the activity interrupt follows
+//! the free-fall interrupt within a configured gap.
+
+use ef_driver_common::mode::{Async, Blocking};
+use embedded_hal::i2c::I2c;
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use crate::{ActivityControl, Adxl345, Axes, InterruptPin, Interrupts, PowerControl, register};
+
+/// The configuration of a [`DropDetector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DropConfig {
+    /// The free-fall threshold, in 62.5 mg steps.
+    ///
+    /// All axes must be below the threshold to detect free-fall.
+    pub freefall_threshold: u8,
+    /// How long all axes must be below the threshold, in 5 ms steps.
+    pub freefall_time: u8,
+    /// The impact threshold, in 62.5 mg steps.
+    pub impact_threshold: u8,
+    /// The axes used for impact detection.
+    pub impact_axes: Axes,
+    /// The longest time between the last free-fall and the impact,
+    /// in milliseconds.
+    pub max_gap_ms: u32,
+    /// The interrupt pin that both interrupts are mapped to.
+    pub pin: InterruptPin,
+}
+
+impl DropConfig {
+    /// Detect free-fall below 437.5 mg for 100 ms, followed within 200 ms by
+    /// an impact above 2 g on any axis.
+    ///
+    /// The free-fall values are within the ranges recommended by the
+    /// *AN-1023* application note.
+    pub const DEFAULT: Self = Self {
+        freefall_threshold: 7,
+        freefall_time: 20,
+        impact_threshold: 32,
+        impact_axes: Axes::all(),
+        max_gap_ms: 200,
+        pin: InterruptPin::Int1,
+    };
+    /// The interrupts used by the detector.
+    const INTERRUPTS: Interrupts = Interrupts::FREE_FALL.union(Interrupts::ACTIVITY);
+
+    /// The free-fall time, in milliseconds.
+    const fn freefall_ms(self) -> u32 { self.freefall_time as u32 * 5 }
+
+    /// Update the `ACT_INACT_CTL` register for DC-coupled activity.
+    const fn activity_control(self, control: ActivityControl) -> ActivityControl {
+        let mut bits = control.bits() & !ActivityControl::ACTIVITY_AXES_MASK.bits();
+        bits &= !ActivityControl::ACTIVITY_AC.bits();
+        bits |= self.impact_axes.bits() << 4;
+        ActivityControl::from_bits_truncate(bits)
+    }
+
+    /// The value of the `INT_MAP` register.
+    const fn interrupt_map(self, map: Interrupts) -> Interrupts {
+        match self.pin {
+            InterruptPin::Int1 => map.difference(Self::INTERRUPTS),
+            InterruptPin::Int2 => map.union(Self::INTERRUPTS),
+        }
+    }
+}
+
+impl Default for DropConfig {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// A detected drop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DropEvent {
+    /// How long the device was falling, in milliseconds.
+    ///
+    /// The free-fall interrupt is only raised after the configured
+    /// free-fall time, which is included.
+    pub fall_duration_ms: u32,
+    /// The largest acceleration of any axis seen during the fall and the
+    /// impact, in mg.
+    ///
+    /// `None` if no samples were recorded.
+    pub impact_peak: Option<u16>,
+}
+
+/// The state of a [`DropDetector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+enum DropState {
+    /// Waiting for free-fall.
+    Idle,
+    /// Free-fall was seen, waiting for an impact.
+    Falling { start: u32, last: u32, peak: Option<u16> },
+}
+
+/// A state machine correlating the free-fall and activity interrupts.
+///
+/// Feed it the interrupt sources with [`DropDetector::update`], or let it
+/// read them from the device with [`DropDetector::poll`]. Timestamps are in
+/// milliseconds and may wrap.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_adxl345::{DropConfig, DropDetector, DropEvent, Interrupts};
+///
+/// let mut detector = DropDetector::new(DropConfig::DEFAULT);
+///
+/// // A drop, with the free-fall interrupt raised while falling
+/// assert_eq!(detector.update(Interrupts::FREE_FALL, 1_000), None);
+/// assert_eq!(detector.update(Interrupts::FREE_FALL, 1_150), None);
+/// detector.record_sample((-120, 2_900, 4_100));
+/// let event = detector.update(Interrupts::ACTIVITY, 1_250);
+/// assert_eq!(event, Some(DropEvent { fall_duration_ms: 250, impact_peak: Some(4_100) }));
+/// assert!(!detector.is_falling());
+///
+/// // Free-fall without an impact times out
+/// assert_eq!(detector.update(Interrupts::FREE_FALL, 5_000), None);
+/// assert!(detector.is_falling());
+/// assert_eq!(detector.update(Interrupts::DATA_READY, 5_201), None);
+/// assert!(!detector.is_falling());
+/// assert_eq!(detector.update(Interrupts::ACTIVITY, 5_300), None);
+///
+/// // An impact without free-fall is only a knock
+/// assert_eq!(detector.update(Interrupts::ACTIVITY, 9_000), None);
+///
+/// // Both interrupts in the same read, with a wrapping timestamp
+/// let event = detector.update(Interrupts::FREE_FALL | Interrupts::ACTIVITY, u32::MAX);
+/// assert_eq!(event, Some(DropEvent { fall_duration_ms: 100, impact_peak: None }));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DropDetector {
+    config: DropConfig,
+    state: DropState,
+}
+
+impl DropDetector {
+    /// Create a new [`DropDetector`].
+    ///
+    /// The device is not configured until [`DropDetector::install`] is
+    /// called.
+    #[inline]
+    #[must_use]
+    pub const fn new(config: DropConfig) -> Self { Self { config, state: DropState::Idle } }
+
+    /// Get the [`DropConfig`].
+    #[inline]
+    #[must_use]
+    pub const fn config(&self) -> &DropConfig { &self.config }
+
+    /// Returns `true` if free-fall was seen and an impact is expected.
+    #[inline]
+    #[must_use]
+    pub const fn is_falling(&self) -> bool { matches!(self.state, DropState::Falling { .. }) }
+
+    /// Forget any free-fall in progress.
+    #[inline]
+    pub const fn reset(&mut self) { self.state = DropState::Idle; }
+
+    /// Record an acceleration sample in mg, for [`DropEvent::impact_peak`].
+    ///
+    /// Samples are ignored unless free-fall was seen.
+    pub fn record_sample(&mut self, (x, y, z): (i16, i16, i16)) {
+        if let DropState::Falling { peak, .. } = &mut self.state {
+            let max = x.unsigned_abs().max(y.unsigned_abs()).max(z.unsigned_abs());
+            *peak = Some(peak.map_or(max, |peak| peak.max(max)));
+        }
+    }
+
+    /// Update the state machine with the interrupt sources read at
+    /// `timestamp_ms`.
+    ///
+    /// Returns a [`DropEvent`] when an impact follows free-fall.
+    pub fn update(&mut self, source: Interrupts, timestamp_ms: u32) -> Option<DropEvent> {
+        if let DropState::Falling { last, .. } = self.state
+            && timestamp_ms.wrapping_sub(last) > self.config.max_gap_ms
+        {
+            self.state = DropState::Idle;
+        }
+
+        if source.contains(Interrupts::FREE_FALL) {
+            match &mut self.state {
+                DropState::Idle => {
+                    self.state =
+                        DropState::Falling { start: timestamp_ms, last: timestamp_ms, peak: None };
+                }
+                DropState::Falling { last, .. } => *last = timestamp_ms,
+            }
+        }
+
+        match self.state {
+            DropState::Falling { start, last, peak } if source.contains(Interrupts::ACTIVITY) => {
+                self.state = DropState::Idle;
+                let fall_duration_ms = last.wrapping_sub(start) + self.config.freefall_ms();
+                Some(DropEvent { fall_duration_ms, impact_peak: peak })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl DropDetector {
+    /// Configure free-fall and activity detection on the device.
+    ///
+    /// The device is configured in standby mode and starts measuring once
+    /// everything is set up. Any free-fall in progress is forgotten.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_adxl345::{Adxl345, DropConfig, DropDetector, DropEvent, mode::Blocking};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_adxl345::test_util::Registers;
+    ///
+    /// let mut registers = [0u8; 64];
+    /// registers[0x2D] = 0b0000_1000; // Measuring
+    /// registers[0x27] = 0b1000_1111; // AC-coupled activity, inactivity on all axes
+    /// let mut accel = Adxl345::<_, Blocking>::new(Registers::new(registers), 0x53);
+    ///
+    /// let mut detector = DropDetector::new(DropConfig::DEFAULT);
+    /// detector.install(&mut accel)?;
+    /// assert_eq!(
+    ///     accel.i2c().writes,
+    ///     [
+    ///         (0x2D, 0b0000_0000), // Standby
+    ///         (0x28, 7),           // Free-fall threshold
+    ///         (0x29, 20),          // Free-fall time
+    ///         (0x24, 32),          // Impact threshold
+    ///         (0x27, 0b0111_1111), // DC-coupled activity on all axes
+    ///         (0x2F, 0b0000_0000), // Map free-fall and activity to INT1
+    ///         (0x2E, 0b0001_0100), // Enable free-fall and activity
+    ///         (0x2D, 0b0000_1000), // Start measuring
+    ///     ]
+    /// );
+    ///
+    /// // Falling, then hitting the floor
+    /// accel.i2c_mut().registers[0x30] = 0b0000_0100;
+    /// assert_eq!(detector.poll(&mut accel, 100)?, None);
+    /// accel.i2c_mut().registers[0x30] = 0b0001_0000;
+    /// // 512 LSB at 3.9 mg/LSB on the Y axis
+    /// accel.i2c_mut().registers[0x31..0x38].copy_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x02, 0, 0]);
+    /// let event = detector.poll(&mut accel, 160)?;
+    /// assert_eq!(event, Some(DropEvent { fall_duration_ms: 100, impact_peak: Some(1_997) }));
+    /// # Ok::<(), ErrorKind>(())
+    /// ```
+    pub fn install<I2C: I2c>(
+        &mut self,
+        accel: &mut Adxl345<I2C, Blocking>,
+    ) -> Result<(), I2C::Error> {
+        let config = self.config;
+        let mut buf = [0u8; 1];
+        self.reset();
+
+        // Configure in standby mode
+        accel.read_register(register::ADXL345_POWER_CONTROL, &mut buf)?;
+        let power = PowerControl::from_bits_truncate(buf[0]);
+        accel.write_register(
+            register::ADXL345_POWER_CONTROL,
+            power.difference(PowerControl::MEASURE).bits(),
+        )?;
+
+        // Configure free-fall and activity detection
+        accel.write_register(register::ADXL345_FREEFALL_THRESHOLD, config.freefall_threshold)?;
+        accel.write_register(register::ADXL345_FREEFALL_TIME, config.freefall_time)?;
+        accel.write_register(register::ADXL345_ACTIVITY_THRESHOLD, config.impact_threshold)?;
+        accel.read_register(register::ADXL345_INACTIVITY_ENABLE, &mut buf)?;
+        let control = config.activity_control(ActivityControl::from_bits_truncate(buf[0]));
+        accel.write_register(register::ADXL345_INACTIVITY_ENABLE, control.bits())?;
+
+        // Route and enable the interrupts, clearing any stale interrupts
+        accel.read_register(register::ADXL345_INTERRUPT_MAP, &mut buf)?;
+        let map = config.interrupt_map(Interrupts::from_bits_truncate(buf[0]));
+        accel.write_register(register::ADXL345_INTERRUPT_MAP, map.bits())?;
+        accel.read_register(register::ADXL345_INTERRUPT_ENABLE, &mut buf)?;
+        let enable = Interrupts::from_bits_truncate(buf[0]).union(DropConfig::INTERRUPTS);
+        accel.write_register(register::ADXL345_INTERRUPT_ENABLE, enable.bits())?;
+        accel.read_register(register::ADXL345_INTERRUPT_SOURCE, &mut buf)?;
+
+        // Start measuring
+        let power = power.union(PowerControl::MEASURE);
+        accel.write_register(register::ADXL345_POWER_CONTROL, power.bits())
+    }
+
+    /// Read and clear the interrupt sources, then update the state machine.
+    ///
+    /// While falling, the acceleration is also read and recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn poll<I2C: I2c>(
+        &mut self,
+        accel: &mut Adxl345<I2C, Blocking>,
+        timestamp_ms: u32,
+    ) -> Result<Option<DropEvent>, I2C::Error> {
+        let mut buf = [0u8; 1];
+        accel.read_register(register::ADXL345_INTERRUPT_SOURCE, &mut buf)?;
+        let source = Interrupts::from_bits_truncate(buf[0]);
+        if self.is_falling() || source.contains(Interrupts::FREE_FALL) {
+            let sample = accel.get_acceleration_mg()?;
+            self.record_falling(source, timestamp_ms, sample);
+        }
+        Ok(self.update(source, timestamp_ms))
+    }
+
+    /// Configure free-fall and activity detection on the device.
+    ///
+    /// The device is configured in standby mode and starts measuring once
+    /// everything is set up. Any free-fall in progress is forgotten.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the device may be left in standby mode,
+    /// but the configuration can be written again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn install_async<I2C: AsyncI2c>(
+        &mut self,
+        accel: &mut Adxl345<I2C, Async>,
+    ) -> Result<(), I2C::Error> {
+        let config = self.config;
+        let mut buf = [0u8; 1];
+        self.reset();
+
+        // Configure in standby mode
+        accel.read_register(register::ADXL345_POWER_CONTROL, &mut buf).await?;
+        let power = PowerControl::from_bits_truncate(buf[0]);
+        accel
+            .write_register(
+                register::ADXL345_POWER_CONTROL,
+                power.difference(PowerControl::MEASURE).bits(),
+            )
+            .await?;
+
+        // Configure free-fall and activity detection
+        accel
+            .write_register(register::ADXL345_FREEFALL_THRESHOLD, config.freefall_threshold)
+            .await?;
+        accel.write_register(register::ADXL345_FREEFALL_TIME, config.freefall_time).await?;
+        accel.write_register(register::ADXL345_ACTIVITY_THRESHOLD, config.impact_threshold).await?;
+        accel.read_register(register::ADXL345_INACTIVITY_ENABLE, &mut buf).await?;
+        let control = config.activity_control(ActivityControl::from_bits_truncate(buf[0]));
+        accel.write_register(register::ADXL345_INACTIVITY_ENABLE, control.bits()).await?;
+
+        // Route and enable the interrupts, clearing any stale interrupts
+        accel.read_register(register::ADXL345_INTERRUPT_MAP, &mut buf).await?;
+        let map = config.interrupt_map(Interrupts::from_bits_truncate(buf[0]));
+        accel.write_register(register::ADXL345_INTERRUPT_MAP, map.bits()).await?;
+        accel.read_register(register::ADXL345_INTERRUPT_ENABLE, &mut buf).await?;
+        let enable = Interrupts::from_bits_truncate(buf[0]).union(DropConfig::INTERRUPTS);
+        accel.write_register(register::ADXL345_INTERRUPT_ENABLE, enable.bits()).await?;
+        accel.read_register(register::ADXL345_INTERRUPT_SOURCE, &mut buf).await?;
+
+        // Start measuring
+        let power = power.union(PowerControl::MEASURE);
+        accel.write_register(register::ADXL345_POWER_CONTROL, power.bits()).await
+    }
+
+    /// Read and clear the interrupt sources, then update the state machine.
+    ///
+    /// While falling, the acceleration is also read and recorded.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: reading the sources clears the interrupts,
+    /// so a cancelled poll can miss a free-fall or an impact.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn poll_async<I2C: AsyncI2c>(
+        &mut self,
+        accel: &mut Adxl345<I2C, Async>,
+        timestamp_ms: u32,
+    ) -> Result<Option<DropEvent>, I2C::Error> {
+        let mut buf = [0u8; 1];
+        accel.read_register(register::ADXL345_INTERRUPT_SOURCE, &mut buf).await?;
+        let source = Interrupts::from_bits_truncate(buf[0]);
+        if self.is_falling() || source.contains(Interrupts::FREE_FALL) {
+            let sample = accel.get_acceleration_mg().await?;
+            self.record_falling(source, timestamp_ms, sample);
+        }
+        Ok(self.update(source, timestamp_ms))
+    }
+
+    /// Record a sample read by a poll, once the free-fall it belongs to has
+    /// started.
+    fn record_falling(&mut self, source: Interrupts, timestamp_ms: u32, sample: (i16, i16, i16)) {
+        if !self.is_falling() {
+            self.update(source.intersection(Interrupts::FREE_FALL), timestamp_ms);
+        }
+        self.record_sample(sample);
+    }
+}
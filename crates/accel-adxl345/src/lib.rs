@@ -1,6 +1,9 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
 
+#[cfg(feature = "test-util")]
+extern crate alloc;
+
 use core::marker::PhantomData;
 
 #[cfg(not(feature = "defmt"))]
@@ -12,12 +15,31 @@ use ef_driver_common::mode::DriverMode;
 
 mod r#async;
 mod blocking;
+mod capture;
+mod compensation;
+mod drop;
+mod noise;
+#[cfg(feature = "pedometer")]
+pub mod pedometer;
+mod raw;
 mod register;
+#[cfg(feature = "test-util")]
+#[doc(hidden)]
+pub mod test_util;
+
+pub use capture::{CaptureConfig, PreEventCapture};
+pub use compensation::TempCompensation;
+pub use drop::{DropConfig, DropDetector, DropEvent};
+pub use noise::{AxisNoise, MAX_NOISE_SAMPLES, NoiseAccumulator, NoiseStats};
+pub use raw::{Access, RawRegisterError, RawRegisters, Register};
 
 /// A driver for an ADXL345 accelerometer.
 pub struct Adxl345<I2C, MODE: DriverMode> {
     i2c: I2C,
     address: u8,
+    compensation: TempCompensation,
+    temperature: i16,
+    armed: Option<PowerControl>,
     _mode: PhantomData<MODE>,
 }
 
@@ -25,7 +47,16 @@ impl<I2C, MODE: DriverMode> Adxl345<I2C, MODE> {
     /// Create a new [`Adxl345`] driver instance.
     #[inline]
     #[must_use]
-    pub const fn new(i2c: I2C, address: u8) -> Self { Self { i2c, address, _mode: PhantomData } }
+    pub const fn new(i2c: I2C, address: u8) -> Self {
+        Self {
+            i2c,
+            address,
+            compensation: TempCompensation::NONE,
+            temperature: 0,
+            armed: None,
+            _mode: PhantomData,
+        }
+    }
 
     /// Get the I2C address of the [`Adxl345`] device.
     #[inline]
@@ -46,10 +77,83 @@ impl<I2C, MODE: DriverMode> Adxl345<I2C, MODE> {
     #[inline]
     #[must_use]
     pub fn release(self) -> I2C { self.i2c }
+
+    /// Get the [`TempCompensation`] applied to scaled outputs.
+    #[inline]
+    #[must_use]
+    pub const fn temperature_compensation(&self) -> TempCompensation { self.compensation }
+
+    /// Set the [`TempCompensation`] applied to scaled outputs.
+    ///
+    /// Raw outputs are never compensated.
+    #[inline]
+    pub const fn set_temperature_compensation(&mut self, compensation: TempCompensation) {
+        self.compensation = compensation;
+    }
+
+    /// Get the current temperature, in centi-°C.
+    #[inline]
+    #[must_use]
+    pub const fn current_temperature(&self) -> i16 { self.temperature }
+
+    /// Set the current temperature, in centi-°C.
+    ///
+    /// The ADXL345 has no temperature sensor,
+    /// so this must be updated from another sensor.
+    #[inline]
+    pub const fn set_current_temperature(&mut self, centi_celsius: i16) {
+        self.temperature = centi_celsius;
+    }
+
+    /// Use the current temperature as the compensation reference.
+    ///
+    /// Call this after calibrating the axis offsets,
+    /// so the compensation is relative to the calibration temperature.
+    #[inline]
+    pub const fn record_calibration_temperature(&mut self) {
+        self.compensation.reference = self.temperature;
+    }
+
+    /// Returns `true` if a capture was armed and is waiting to be started.
+    ///
+    /// See `Adxl345::arm_capture`.
+    #[inline]
+    #[must_use]
+    pub const fn is_armed(&self) -> bool { self.armed.is_some() }
+
+    /// Get raw access to the registers,
+    /// for settings without a typed API.
+    #[inline]
+    #[must_use]
+    pub const fn registers(&mut self) -> RawRegisters<'_, I2C, MODE> { RawRegisters::new(self) }
+
+    /// Scale and compensate a burst read of `DATA_FORMAT` and the data
+    /// registers.
+    const fn compensated_mg(&self, buf: [u8; 7]) -> (i16, i16, i16) {
+        let format = DataFormat::from_bits_truncate(buf[0]);
+        let x = compensation::scale_to_mg(format, i16::from_le_bytes([buf[1], buf[2]]));
+        let y = compensation::scale_to_mg(format, i16::from_le_bytes([buf[3], buf[4]]));
+        let z = compensation::scale_to_mg(format, i16::from_le_bytes([buf[5], buf[6]]));
+        self.compensation.apply(self.temperature, (x, y, z))
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
 
+/// An error that can occur when configuring the [`Adxl345`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Adxl345Error<E> {
+    /// The data rate is not supported in low power mode.
+    InvalidCombination(DataRate),
+    /// I2C bus error.
+    I2C(E),
+}
+
+impl<E> From<E> for Adxl345Error<E> {
+    fn from(error: E) -> Self { Adxl345Error::I2C(error) }
+}
+
 bitflags! {
     #[cfg_attr(not(feature = "defmt"), derive(Debug, Clone, Copy, PartialEq, Eq))]
     struct BWRate: u8 {
@@ -106,6 +210,69 @@ impl DataRate {
             _ => unreachable!(),
         }
     }
+
+    /// Returns `true` if the data rate is supported in low power mode,
+    /// from 12.5 Hz to 400 Hz.
+    #[inline]
+    #[must_use]
+    pub const fn supports_low_power(self) -> bool {
+        matches!(
+            self,
+            DataRate::Hz12_5
+                | DataRate::Hz25
+                | DataRate::Hz50
+                | DataRate::Hz100
+                | DataRate::Hz200
+                | DataRate::Hz400
+        )
+    }
+}
+
+/// A [`DataRate`] supported in low power mode.
+#[repr(u8)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[expect(missing_docs, reason = "Self explanatory")]
+pub enum LowPowerRate {
+    Hz12_5 = DataRate::Hz12_5 as u8,
+    Hz25 = DataRate::Hz25 as u8,
+    Hz50 = DataRate::Hz50 as u8,
+    #[default]
+    Hz100 = DataRate::Hz100 as u8,
+    Hz200 = DataRate::Hz200 as u8,
+    Hz400 = DataRate::Hz400 as u8,
+}
+
+impl From<LowPowerRate> for DataRate {
+    fn from(rate: LowPowerRate) -> Self { DataRate::from_byte(rate as u8) }
+}
+
+impl TryFrom<DataRate> for LowPowerRate {
+    type Error = DataRate;
+
+    fn try_from(rate: DataRate) -> Result<Self, Self::Error> {
+        match rate {
+            DataRate::Hz12_5 => Ok(LowPowerRate::Hz12_5),
+            DataRate::Hz25 => Ok(LowPowerRate::Hz25),
+            DataRate::Hz50 => Ok(LowPowerRate::Hz50),
+            DataRate::Hz100 => Ok(LowPowerRate::Hz100),
+            DataRate::Hz200 => Ok(LowPowerRate::Hz200),
+            DataRate::Hz400 => Ok(LowPowerRate::Hz400),
+            _ => Err(rate),
+        }
+    }
+}
+
+impl BWRate {
+    /// Check that the data rate is supported in the power mode.
+    const fn validate<E>(self) -> Result<Self, Adxl345Error<E>> {
+        let rate = DataRate::from_byte(self.bits());
+        if self.contains(BWRate::LOW_POWER) && !rate.supports_low_power() {
+            Err(Adxl345Error::InvalidCombination(rate))
+        } else {
+            Ok(self)
+        }
+    }
 }
 
 bitflags! {
@@ -175,6 +342,7 @@ bitflags! {
 }
 
 /// FIFO operation modes
+#[repr(u8)]
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FifoMode {
@@ -196,7 +364,7 @@ impl FifoMode {
     /// Create a [`FifoMode`] from a byte value.
     #[must_use]
     pub const fn from_byte(byte: u8) -> Self {
-        match byte & FifoControl::FIFO_MASK.bits() {
+        match (byte & FifoControl::FIFO_MASK.bits()) >> 6 {
             0b00 => FifoMode::Bypass,
             0b01 => FifoMode::Fifo,
             0b10 => FifoMode::Stream,
@@ -205,3 +373,60 @@ impl FifoMode {
         }
     }
 }
+
+bitflags! {
+    /// Interrupt sources, used for enabling, mapping, and reading interrupts.
+    #[cfg_attr(not(feature = "defmt"), derive(Debug, Clone, Copy, PartialEq, Eq))]
+    pub struct Interrupts: u8 {
+        /// New data is available.
+        const DATA_READY = 0b1000_0000;
+        /// A single tap was detected.
+        const SINGLE_TAP = 0b0100_0000;
+        /// A double tap was detected.
+        const DOUBLE_TAP = 0b0010_0000;
+        /// Activity was detected.
+        const ACTIVITY = 0b0001_0000;
+        /// Inactivity was detected.
+        const INACTIVITY = 0b0000_1000;
+        /// Free fall was detected.
+        const FREE_FALL = 0b0000_0100;
+        /// The FIFO reached the configured number of samples.
+        const WATERMARK = 0b0000_0010;
+        /// Unread data was overwritten.
+        const OVERRUN = 0b0000_0001;
+    }
+}
+
+/// An interrupt output pin.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InterruptPin {
+    /// The `INT1` pin.
+    #[default]
+    Int1,
+    /// The `INT2` pin.
+    Int2,
+}
+
+bitflags! {
+    /// A set of measurement axes.
+    #[cfg_attr(not(feature = "defmt"), derive(Debug, Clone, Copy, PartialEq, Eq))]
+    pub struct Axes: u8 {
+        /// The X axis.
+        const X = 0b100;
+        /// The Y axis.
+        const Y = 0b010;
+        /// The Z axis.
+        const Z = 0b001;
+    }
+}
+
+bitflags! {
+    #[cfg_attr(not(feature = "defmt"), derive(Debug, Clone, Copy, PartialEq, Eq))]
+    struct ActivityControl: u8 {
+        const ACTIVITY_AC = 0b1000_0000;
+        const ACTIVITY_AXES_MASK = 0b0111_0000;
+        const INACTIVITY_AC = 0b0000_1000;
+        const INACTIVITY_AXES_MASK = 0b0000_0111;
+    }
+}
@@ -0,0 +1,392 @@
+//! Measuring the output noise of the device, for qualification.
+//!
+//! The datasheet specifies the noise at 100 Hz, in full resolution at ±2 g,
+//! with the device at rest. [`Adxl345::measure_noise`] configures these
+//! conditions, collects samples through the FIFO and restores the previous
+//! configuration afterwards.
+//!
+//! All statistics are computed in fixed point, without floating-point math.
+
+use ef_driver_common::mode::{Async, Blocking};
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, i2c::I2c as AsyncI2c};
+
+use crate::{Adxl345, DataFormat, PowerControl, register};
+
+/// The maximum number of samples collected by [`Adxl345::measure_noise`].
+pub const MAX_NOISE_SAMPLES: u16 = 4096;
+
+/// The `BW_RATE` value for 100 Hz in normal power mode.
+const NOISE_BW_RATE: u8 = 0b0000_1010;
+/// The `FIFO_CTL` value for FIFO mode, stopping when full.
+const NOISE_FIFO_CONTROL: u8 = 0b0100_0000;
+/// The time between samples at 100 Hz, in milliseconds.
+const NOISE_SAMPLE_PERIOD_MS: u32 = 10;
+/// The scale in full resolution, in µg/LSB.
+const MICRO_G_PER_LSB: u64 = 3_900;
+
+/// The noise of a single axis, see [`NoiseStats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AxisNoise {
+    /// The mean value, in LSB, rounded to the nearest LSB.
+    pub mean: i16,
+    /// The RMS noise (standard deviation) in thousandths of an LSB.
+    pub rms: u32,
+    /// The RMS noise (standard deviation) in µg, at 3.9 mg/LSB.
+    pub rms_ug: u32,
+    /// The difference between the largest and smallest value, in LSB.
+    pub peak_to_peak: u16,
+}
+
+/// The noise of each axis over a number of samples.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoiseStats {
+    /// The number of samples.
+    pub samples: u16,
+    /// The noise of the X, Y, and Z axes.
+    pub axes: [AxisNoise; 3],
+}
+
+/// Accumulates samples into [`NoiseStats`].
+///
+/// Sums are kept in 64 bits and saturate, so any number of full-scale
+/// samples up to `u16::MAX` can be accumulated without overflowing.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_adxl345::{AxisNoise, NoiseAccumulator};
+///
+/// // X alternates around 5 LSB, Y is constant, Z steps through -2..=2
+/// let mut noise = NoiseAccumulator::new();
+/// for i in 0..1000i16 {
+///     noise.push((if i % 2 == 0 { 4 } else { 6 }, -256, i % 5 - 2));
+/// }
+/// let stats = noise.stats();
+/// assert_eq!(stats.samples, 1000);
+/// assert_eq!(stats.axes[0], AxisNoise { mean: 5, rms: 1000, rms_ug: 3900, peak_to_peak: 2 });
+/// assert_eq!(stats.axes[1], AxisNoise { mean: -256, rms: 0, rms_ug: 0, peak_to_peak: 0 });
+/// // The standard deviation of -2..=2 is √2 LSB
+/// assert_eq!(stats.axes[2], AxisNoise { mean: 0, rms: 1414, rms_ug: 5514, peak_to_peak: 4 });
+///
+/// // Full-scale samples do not overflow
+/// let mut noise = NoiseAccumulator::new();
+/// for i in 0..4096 {
+///     noise.push(if i % 2 == 0 {
+///         (i16::MIN, i16::MAX, i16::MAX)
+///     } else {
+///         (i16::MAX, 0, i16::MAX)
+///     });
+/// }
+/// let stats = noise.stats();
+/// assert_eq!(
+///     stats.axes[0],
+///     AxisNoise { mean: -1, rms: 32_767_500, rms_ug: 127_793_250, peak_to_peak: u16::MAX }
+/// );
+/// assert_eq!(stats.axes[1].mean, 16_384);
+/// assert_eq!(stats.axes[2], AxisNoise { mean: i16::MAX, rms: 0, rms_ug: 0, peak_to_peak: 0 });
+///
+/// // Without samples, everything is zero
+/// assert_eq!(NoiseAccumulator::new().stats().axes, [AxisNoise::default(); 3]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoiseAccumulator {
+    samples: u16,
+    sum: [i64; 3],
+    sum_squares: [u64; 3],
+    min: [i16; 3],
+    max: [i16; 3],
+}
+
+impl NoiseAccumulator {
+    /// Create a new, empty [`NoiseAccumulator`].
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            samples: 0,
+            sum: [0; 3],
+            sum_squares: [0; 3],
+            min: [i16::MAX; 3],
+            max: [i16::MIN; 3],
+        }
+    }
+
+    /// Get the number of samples accumulated so far.
+    #[inline]
+    #[must_use]
+    pub const fn samples(&self) -> u16 { self.samples }
+
+    /// Add a sample of the X, Y, and Z axes, in LSB.
+    ///
+    /// Samples past `u16::MAX` are ignored.
+    pub fn push(&mut self, (x, y, z): (i16, i16, i16)) {
+        let Some(samples) = self.samples.checked_add(1) else { return };
+        self.samples = samples;
+
+        for (axis, value) in [x, y, z].into_iter().enumerate() {
+            self.sum[axis] = self.sum[axis].saturating_add(i64::from(value));
+            let square = u64::from(value.unsigned_abs()).pow(2);
+            self.sum_squares[axis] = self.sum_squares[axis].saturating_add(square);
+            self.min[axis] = self.min[axis].min(value);
+            self.max[axis] = self.max[axis].max(value);
+        }
+    }
+
+    /// Compute the [`NoiseStats`] of the samples so far.
+    #[must_use]
+    pub fn stats(&self) -> NoiseStats {
+        let mut axes = [AxisNoise::default(); 3];
+        if self.samples == 0 {
+            return NoiseStats { samples: 0, axes };
+        }
+
+        for (axis, noise) in axes.iter_mut().enumerate() {
+            *noise = self.axis(axis);
+        }
+        NoiseStats { samples: self.samples, axes }
+    }
+
+    /// Compute the [`AxisNoise`] of a single axis.
+    #[expect(clippy::cast_possible_truncation, reason = "Clamped")]
+    fn axis(&self, axis: usize) -> AxisNoise {
+        let count = i128::from(self.samples);
+        let sum = i128::from(self.sum[axis]);
+
+        // Round the mean to the nearest LSB, away from zero on ties
+        let mean = if sum < 0 { (sum - count / 2) / count } else { (sum + count / 2) / count };
+        let mean = mean.clamp(i16::MIN.into(), i16::MAX.into()) as i16;
+
+        // Var = (n·Σx² - (Σx)²) / n², scaled to thousandths of an LSB squared
+        let spread = (count * i128::from(self.sum_squares[axis]) - sum * sum).max(0).unsigned_abs();
+        let variance = spread * 1_000_000 / (count * count).unsigned_abs();
+        let rms = variance.isqrt().min(u32::MAX.into()) as u32;
+        let rms_ug = (u64::from(rms) * MICRO_G_PER_LSB / 1_000).min(u32::MAX.into()) as u32;
+
+        let peak_to_peak = self.max[axis].abs_diff(self.min[axis]);
+        AxisNoise { mean, rms, rms_ug, peak_to_peak }
+    }
+}
+
+impl Default for NoiseAccumulator {
+    fn default() -> Self { Self::new() }
+}
+
+/// The registers changed by a noise measurement.
+#[derive(Debug, Clone, Copy)]
+struct SavedConfig {
+    bw_rate: u8,
+    power: u8,
+    format: u8,
+    fifo: u8,
+}
+
+impl SavedConfig {
+    /// The `DATA_FORMAT` value for full resolution at ±2 g,
+    /// keeping the interface settings.
+    const fn noise_format(self) -> u8 {
+        let keep = DataFormat::SPI_MODE.union(DataFormat::INTERRUPT_INVERT);
+        self.format & keep.bits() | DataFormat::FULL_RESOLUTION.bits()
+    }
+
+    /// The `POWER_CTL` value to restore, after entering standby mode.
+    const fn standby_power(self) -> u8 { self.power & !PowerControl::MEASURE.bits() }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl<I2C: I2c> Adxl345<I2C, Blocking> {
+    /// Measure the output noise over `samples` samples, with the device at
+    /// rest.
+    ///
+    /// The device is configured for 100 Hz, full resolution at ±2 g, and
+    /// samples are collected through the FIFO, waiting with `delay` while it
+    /// is empty. The data rate, data format, FIFO and power configuration are
+    /// restored afterwards, even if the measurement fails.
+    ///
+    /// At most [`MAX_NOISE_SAMPLES`] are collected, and at least one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_adxl345::{Adxl345, mode::Blocking};
+    /// use embedded_hal::{delay::DelayNs, i2c::ErrorKind};
+    /// # use ef_adxl345::test_util::Registers;
+    ///
+    /// struct NoDelay;
+    ///
+    /// impl DelayNs for NoDelay {
+    ///     fn delay_ns(&mut self, _: u32) {}
+    /// }
+    ///
+    /// // Measuring at 800 Hz in 10-bit mode at ±16 g, with auto sleep
+    /// let mut registers = [0u8; 64];
+    /// registers[0x2C] = 0b0000_1101;
+    /// registers[0x2D] = 0b0001_1000;
+    /// registers[0x31] = 0b0000_0011;
+    /// registers[0x38] = 0b1000_0000;
+    ///
+    /// // A device at rest, with the Z axis reading 1 g
+    /// let samples = (0..100i16).map(|i| (i % 3 - 1, 0, 256 + i % 2)).collect();
+    ///
+    /// let mut accel = Adxl345::<_, Blocking>::new(Registers::new(registers).with_fifo(samples), 0x53);
+    /// let stats = accel.measure_noise(&mut NoDelay, 100)?;
+    /// assert_eq!(stats.samples, 100);
+    /// assert_eq!((stats.axes[0].mean, stats.axes[0].peak_to_peak), (0, 2));
+    /// assert_eq!(stats.axes[1].rms, 0);
+    /// assert_eq!((stats.axes[2].mean, stats.axes[2].rms), (257, 500));
+    ///
+    /// // The previous configuration is restored
+    /// let accel = accel.release();
+    /// assert_eq!(accel.registers, registers);
+    /// assert!(accel.fifo.is_empty());
+    /// # Ok::<(), ErrorKind>(())
+    /// ```
+    pub fn measure_noise<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+        samples: u16,
+    ) -> Result<NoiseStats, I2C::Error> {
+        let saved = self.configure_noise()?;
+        let result = self.collect_noise(delay, samples.clamp(1, MAX_NOISE_SAMPLES));
+        let restored = self.restore_noise(saved);
+        let stats = result?;
+        restored?;
+        Ok(stats)
+    }
+
+    /// Save the current configuration and configure the noise test.
+    fn configure_noise(&mut self) -> Result<SavedConfig, I2C::Error> {
+        let mut buf = [0u8; 2];
+        self.read_register(register::ADXL345_BW_RATE, &mut buf)?;
+        let [bw_rate, power] = buf;
+        self.read_register(register::ADXL345_DATA_FORMAT, &mut buf[..1])?;
+        let format = buf[0];
+        self.read_register(register::ADXL345_FIFO_CONTROL, &mut buf[..1])?;
+        let saved = SavedConfig { bw_rate, power, format, fifo: buf[0] };
+
+        // Configure in standby mode, clearing the FIFO
+        self.write_register(register::ADXL345_POWER_CONTROL, 0)?;
+        self.write_register(register::ADXL345_BW_RATE, NOISE_BW_RATE)?;
+        self.write_register(register::ADXL345_DATA_FORMAT, saved.noise_format())?;
+        self.write_register(register::ADXL345_FIFO_CONTROL, 0)?;
+        self.write_register(register::ADXL345_FIFO_CONTROL, NOISE_FIFO_CONTROL)?;
+        self.write_register(register::ADXL345_POWER_CONTROL, PowerControl::MEASURE.bits())?;
+        Ok(saved)
+    }
+
+    /// Collect samples from the FIFO.
+    fn collect_noise<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+        samples: u16,
+    ) -> Result<NoiseStats, I2C::Error> {
+        let mut noise = NoiseAccumulator::new();
+        while noise.samples() < samples {
+            let entries = u16::from(self.get_fifo_entries()?);
+            if entries == 0 {
+                delay.delay_ms(NOISE_SAMPLE_PERIOD_MS);
+                continue;
+            }
+
+            for _ in 0..entries.min(samples - noise.samples()) {
+                noise.push(self.get_acceleration()?);
+            }
+        }
+        Ok(noise.stats())
+    }
+
+    /// Restore the configuration saved before the noise test.
+    fn restore_noise(&mut self, saved: SavedConfig) -> Result<(), I2C::Error> {
+        self.write_register(register::ADXL345_POWER_CONTROL, saved.standby_power())?;
+        self.write_register(register::ADXL345_BW_RATE, saved.bw_rate)?;
+        self.write_register(register::ADXL345_DATA_FORMAT, saved.format)?;
+        self.write_register(register::ADXL345_FIFO_CONTROL, saved.fifo)?;
+        self.write_register(register::ADXL345_POWER_CONTROL, saved.power)
+    }
+}
+
+impl<I2C: AsyncI2c> Adxl345<I2C, Async> {
+    /// Measure the output noise over `samples` samples, with the device at
+    /// rest.
+    ///
+    /// The device is configured for 100 Hz, full resolution at ±2 g, and
+    /// samples are collected through the FIFO, waiting with `delay` while it
+    /// is empty. The data rate, data format, FIFO and power configuration are
+    /// restored afterwards, even if the measurement fails.
+    ///
+    /// At most [`MAX_NOISE_SAMPLES`] are collected, and at least one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn measure_noise<D: AsyncDelayNs>(
+        &mut self,
+        delay: &mut D,
+        samples: u16,
+    ) -> Result<NoiseStats, I2C::Error> {
+        let saved = self.configure_noise().await?;
+        let result = self.collect_noise(delay, samples.clamp(1, MAX_NOISE_SAMPLES)).await;
+        let restored = self.restore_noise(saved).await;
+        let stats = result?;
+        restored?;
+        Ok(stats)
+    }
+
+    /// Save the current configuration and configure the noise test.
+    async fn configure_noise(&mut self) -> Result<SavedConfig, I2C::Error> {
+        let mut buf = [0u8; 2];
+        self.read_register(register::ADXL345_BW_RATE, &mut buf).await?;
+        let [bw_rate, power] = buf;
+        self.read_register(register::ADXL345_DATA_FORMAT, &mut buf[..1]).await?;
+        let format = buf[0];
+        self.read_register(register::ADXL345_FIFO_CONTROL, &mut buf[..1]).await?;
+        let saved = SavedConfig { bw_rate, power, format, fifo: buf[0] };
+
+        // Configure in standby mode, clearing the FIFO
+        self.write_register(register::ADXL345_POWER_CONTROL, 0).await?;
+        self.write_register(register::ADXL345_BW_RATE, NOISE_BW_RATE).await?;
+        self.write_register(register::ADXL345_DATA_FORMAT, saved.noise_format()).await?;
+        self.write_register(register::ADXL345_FIFO_CONTROL, 0).await?;
+        self.write_register(register::ADXL345_FIFO_CONTROL, NOISE_FIFO_CONTROL).await?;
+        self.write_register(register::ADXL345_POWER_CONTROL, PowerControl::MEASURE.bits()).await?;
+        Ok(saved)
+    }
+
+    /// Collect samples from the FIFO.
+    async fn collect_noise<D: AsyncDelayNs>(
+        &mut self,
+        delay: &mut D,
+        samples: u16,
+    ) -> Result<NoiseStats, I2C::Error> {
+        let mut noise = NoiseAccumulator::new();
+        while noise.samples() < samples {
+            let entries = u16::from(self.get_fifo_entries().await?);
+            if entries == 0 {
+                delay.delay_ms(NOISE_SAMPLE_PERIOD_MS).await;
+                continue;
+            }
+
+            for _ in 0..entries.min(samples - noise.samples()) {
+                noise.push(self.get_acceleration().await?);
+            }
+        }
+        Ok(noise.stats())
+    }
+
+    /// Restore the configuration saved before the noise test.
+    async fn restore_noise(&mut self, saved: SavedConfig) -> Result<(), I2C::Error> {
+        self.write_register(register::ADXL345_POWER_CONTROL, saved.standby_power()).await?;
+        self.write_register(register::ADXL345_BW_RATE, saved.bw_rate).await?;
+        self.write_register(register::ADXL345_DATA_FORMAT, saved.format).await?;
+        self.write_register(register::ADXL345_FIFO_CONTROL, saved.fifo).await?;
+        self.write_register(register::ADXL345_POWER_CONTROL, saved.power).await
+    }
+}
@@ -0,0 +1,352 @@
+//! Counting steps from acceleration samples.
+//!
+//! The [`Pedometer`] does not access the device, it only consumes the
+//! samples it is given, in mg, at the configured output data rate. Every
+//! sample goes through four stages, using only integer math:
+//!
+//! 1. **Magnitude**: the length of the acceleration vector, so the count does
+//!    not depend on how the device is worn.
+//! 2. **Band-pass**: two first-order IIR low-pass filters, one at ~4 Hz to
+//!    remove vibration and one at ~0.3 Hz following gravity. Their difference
+//!    keeps the 1-3 Hz band of walking and running.
+//! 3. **Peak detection**: a peak starts when the filtered signal rises above a
+//!    threshold and ends when it falls below zero. The threshold adapts to half
+//!    the recent peak height, but never drops below
+//!    [`PedometerConfig::min_threshold_mg`]. Peaks within the refractory period
+//!    of the previous step are ignored.
+//! 4. **Validation**: steps are only counted once
+//!    [`PedometerConfig::validation_steps`] peaks in a row were found at a
+//!    regular pace, which rejects bumps and short bursts of shaking. The
+//!    validated steps are then counted at once, and every following step is
+//!    counted on its own until no step is found for
+//!    [`PedometerConfig::max_interval_ms`].
+//!
+//! Filter coefficients are computed once for the data rate, which can be
+//! between 25 and 100 Hz, so updates do not divide.
+//!
+//! # Example
+//!
+//! Replaying the synthetic traces in `tests/data`, sampled at 50 Hz. They
+//! model each case closely enough to exercise every stage, but are not
+//! recordings, so the counts below are not a measure of accuracy on a real
+//! device.
+//!
+//! ```rust
+//! use ef_adxl345::pedometer::{Pedometer, PedometerConfig};
+//!
+//! fn replay(trace: &str) -> u32 {
+//!     let mut pedometer = Pedometer::new(PedometerConfig::DEFAULT);
+//!     for line in trace.lines() {
+//!         let mut axes = line.split(',').map(|axis| axis.parse::<i16>().unwrap());
+//!         let sample = (axes.next().unwrap(), axes.next().unwrap(), axes.next().unwrap());
+//!         pedometer.update(sample);
+//!     }
+//!     pedometer.step_count()
+//! }
+//!
+//! macro_rules! trace {
+//!     ($name:literal) => {
+//!         include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/", $name))
+//!     };
+//! }
+//!
+//! // 100 steps of walking, with a pause, are all counted
+//! assert_eq!(replay(trace!("walking.csv")), 100);
+//!
+//! // Bumps while driving and sensor noise while idle are rejected
+//! assert_eq!(replay(trace!("driving.csv")), 0);
+//! assert_eq!(replay(trace!("idle.csv")), 0);
+//! ```
+
+/// The configuration of a [`Pedometer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PedometerConfig {
+    /// The rate samples are given at, in Hz.
+    ///
+    /// Clamped to 25 to 100 Hz.
+    pub rate_hz: u16,
+    /// The lowest peak height counted as a step, in mg.
+    ///
+    /// Lower values are more sensitive, and count softer steps as well as
+    /// more spurious ones.
+    pub min_threshold_mg: u16,
+    /// The shortest time between two steps, in milliseconds.
+    pub refractory_ms: u16,
+    /// The longest time between two steps, in milliseconds,
+    /// after which steps must be validated again.
+    pub max_interval_ms: u16,
+    /// The number of regular steps needed before steps are counted.
+    ///
+    /// A value of `0` is treated as `1`, which counts every step.
+    pub validation_steps: u8,
+}
+
+impl PedometerConfig {
+    /// A configuration for walking and running at 50 Hz.
+    ///
+    /// Steps are at most 4 per second and at least one every 2 seconds,
+    /// and 4 steps validate a window.
+    pub const DEFAULT: Self = Self {
+        rate_hz: 50,
+        min_threshold_mg: 70,
+        refractory_ms: 250,
+        max_interval_ms: 2_000,
+        validation_steps: 4,
+    };
+
+    /// Set the rate samples are given at, in Hz.
+    #[inline]
+    #[must_use]
+    pub const fn with_rate_hz(mut self, rate_hz: u16) -> Self {
+        self.rate_hz = rate_hz;
+        self
+    }
+
+    /// Set the lowest peak height counted as a step, in mg.
+    #[inline]
+    #[must_use]
+    pub const fn with_min_threshold_mg(mut self, min_threshold_mg: u16) -> Self {
+        self.min_threshold_mg = min_threshold_mg;
+        self
+    }
+
+    /// Get the rate samples are given at, clamped to 25 to 100 Hz.
+    const fn clamped_rate(self) -> u32 {
+        if self.rate_hz < 25 {
+            25
+        } else if self.rate_hz > 100 {
+            100
+        } else {
+            self.rate_hz as u32
+        }
+    }
+
+    /// Convert a duration in milliseconds to a number of samples.
+    const fn samples(self, ms: u16) -> u32 { ms as u32 * self.clamped_rate() / 1000 }
+
+    /// Get the Q16 coefficient of a first-order low-pass filter,
+    /// from its angular cutoff frequency in thousandths of a rad/s.
+    ///
+    /// Uses `ω / (fs + ω)`, which is close to the exact `1 - e^(-ω/fs)`
+    /// below a quarter of the data rate.
+    const fn alpha(self, omega_milli: u32) -> i64 {
+        ((omega_milli as i64) << 16) / (self.clamped_rate() as i64 * 1000 + omega_milli as i64)
+    }
+}
+
+impl Default for PedometerConfig {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// Steps counted by a [`Pedometer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StepEvent {
+    /// The number of steps counted by this event.
+    ///
+    /// Once validated, the steps of the validation window are counted at
+    /// once, then every step is counted on its own.
+    pub steps: u8,
+    /// The total number of steps counted.
+    pub total: u32,
+}
+
+/// A step counter for walking and running.
+///
+/// See the [module documentation](self) for the algorithm.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_adxl345::pedometer::{Pedometer, PedometerConfig, StepEvent};
+///
+/// // Walking at 2 steps per second, sampled at 100 Hz
+/// let config = PedometerConfig::DEFAULT.with_rate_hz(100);
+/// let mut pedometer = Pedometer::new(config);
+///
+/// let mut events = Vec::new();
+/// for t in 0..500 {
+///     let phase = (t % 50) as f32 / 50.0 * core::f32::consts::TAU;
+///     let z = 1000 + (250.0 * phase.sin()) as i16;
+///     events.extend(pedometer.update((0, 0, z)));
+/// }
+///
+/// // The first 4 steps are counted once validated
+/// assert_eq!(events[0], StepEvent { steps: 4, total: 4 });
+/// assert_eq!(events[1], StepEvent { steps: 1, total: 5 });
+/// assert_eq!(pedometer.step_count(), 10);
+/// assert!(pedometer.is_walking());
+///
+/// // Standing still ends the walk after 2 seconds
+/// for _ in 0..300 {
+///     assert_eq!(pedometer.update((0, 0, 1000)), None);
+/// }
+/// assert!(!pedometer.is_walking());
+/// assert_eq!(pedometer.step_count(), 10);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Pedometer {
+    config: PedometerConfig,
+    /// The Q16 coefficient of the vibration filter.
+    fast_alpha: i64,
+    /// The Q16 coefficient of the gravity filter.
+    slow_alpha: i64,
+    /// The output of the vibration filter, in Q8 mg.
+    fast: i32,
+    /// The output of the gravity filter, in Q8 mg.
+    slow: i32,
+    /// Whether the filters were started from a sample.
+    primed: bool,
+    /// The height of the peak in progress, in mg.
+    peak: Option<i32>,
+    /// The average height of recent peaks, in mg.
+    envelope: i32,
+    /// The number of samples since the last step, saturating.
+    since_step: u32,
+    /// The number of samples between the last two steps.
+    last_interval: u32,
+    /// The number of steps found in the validation window.
+    pending: u8,
+    /// Whether the validation window was passed.
+    validated: bool,
+    /// The total number of steps counted.
+    count: u32,
+}
+
+impl Pedometer {
+    /// The angular cutoff frequency of the vibration filter,
+    /// `2π × 4 Hz`, in thousandths of a rad/s.
+    const FAST_OMEGA: u32 = 25_133;
+    /// The angular cutoff frequency of the gravity filter,
+    /// `2π × 0.3 Hz`, in thousandths of a rad/s.
+    const SLOW_OMEGA: u32 = 1_885;
+
+    /// Create a new [`Pedometer`].
+    #[must_use]
+    pub const fn new(config: PedometerConfig) -> Self {
+        Self {
+            config,
+            fast_alpha: config.alpha(Self::FAST_OMEGA),
+            slow_alpha: config.alpha(Self::SLOW_OMEGA),
+            fast: 0,
+            slow: 0,
+            primed: false,
+            peak: None,
+            envelope: 0,
+            since_step: u32::MAX,
+            last_interval: 0,
+            pending: 0,
+            validated: false,
+            count: 0,
+        }
+    }
+
+    /// Get the [`PedometerConfig`].
+    #[inline]
+    #[must_use]
+    pub const fn config(&self) -> &PedometerConfig { &self.config }
+
+    /// Get the total number of steps counted.
+    #[inline]
+    #[must_use]
+    pub const fn step_count(&self) -> u32 { self.count }
+
+    /// Returns `true` if steps were validated and are being counted.
+    #[inline]
+    #[must_use]
+    pub const fn is_walking(&self) -> bool { self.validated }
+
+    /// Restart the filters and the validation, keeping the step count.
+    ///
+    /// Call this after a gap in the samples, such as a FIFO overrun.
+    #[inline]
+    pub const fn restart(&mut self) {
+        *self = Self { count: self.count, ..Self::new(self.config) };
+    }
+
+    /// Restart the pedometer, and reset the step count to `0`.
+    #[inline]
+    pub const fn reset(&mut self) { *self = Self::new(self.config); }
+
+    /// Update the pedometer with an acceleration sample in mg.
+    ///
+    /// Returns a [`StepEvent`] when steps are counted.
+    pub fn update(&mut self, sample: (i16, i16, i16)) -> Option<StepEvent> {
+        let magnitude = magnitude(sample) << 8;
+        if !self.primed {
+            (self.fast, self.slow, self.primed) = (magnitude, magnitude, true);
+            return None;
+        }
+        self.fast += low_pass(self.fast, magnitude, self.fast_alpha);
+        self.slow += low_pass(self.slow, magnitude, self.slow_alpha);
+        let signal = (self.fast - self.slow) >> 8;
+
+        // Stop counting once steps are too far apart
+        self.since_step = self.since_step.saturating_add(1);
+        if self.since_step > self.config.samples(self.config.max_interval_ms) {
+            (self.pending, self.validated, self.envelope) = (0, false, 0);
+        }
+
+        let threshold = i32::from(self.config.min_threshold_mg).max(self.envelope / 2);
+        match self.peak {
+            None if signal > threshold => self.peak = Some(signal),
+            Some(peak) if signal > peak => self.peak = Some(signal),
+            Some(peak) if signal < 0 => {
+                self.peak = None;
+                return self.step(peak);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Record a peak, counting steps if they are validated.
+    fn step(&mut self, peak: i32) -> Option<StepEvent> {
+        if self.since_step < self.config.samples(self.config.refractory_ms) {
+            return None;
+        }
+        let interval = core::mem::replace(&mut self.since_step, 0);
+        self.envelope = if self.envelope == 0 { peak } else { (3 * self.envelope + peak) / 4 };
+
+        if self.validated {
+            self.count = self.count.saturating_add(1);
+            self.last_interval = interval;
+            return Some(StepEvent { steps: 1, total: self.count });
+        }
+
+        // Restart the window from the last two steps if the pace changed by
+        // more than half, the first two steps set the pace
+        let regular = interval <= self.last_interval.saturating_mul(2)
+            && interval.saturating_mul(2) >= self.last_interval;
+        self.pending = match self.pending {
+            0 | 1 => self.pending + 1,
+            _ if regular => self.pending + 1,
+            _ => 2,
+        };
+        self.last_interval = interval;
+
+        if self.pending >= self.config.validation_steps.max(1) {
+            let steps = core::mem::replace(&mut self.pending, 0);
+            self.validated = true;
+            self.count = self.count.saturating_add(u32::from(steps));
+            Some(StepEvent { steps, total: self.count })
+        } else {
+            None
+        }
+    }
+}
+
+/// Get the magnitude of an acceleration sample.
+#[expect(clippy::cast_possible_truncation, reason = "The magnitude is at most 56_756")]
+const fn magnitude((x, y, z): (i16, i16, i16)) -> i32 {
+    let (x, y, z) = (x.unsigned_abs() as u64, y.unsigned_abs() as u64, z.unsigned_abs() as u64);
+    (x * x + y * y + z * z).isqrt() as i32
+}
+
+/// Get the change of a first-order low-pass filter towards `input`.
+#[expect(clippy::cast_possible_truncation, reason = "The change is at most the difference")]
+const fn low_pass(output: i32, input: i32, alpha: i64) -> i32 {
+    (((input - output) as i64 * alpha) >> 16) as i32
+}
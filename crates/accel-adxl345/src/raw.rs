@@ -0,0 +1,222 @@
+//! Raw access to the register map, for registers without a typed API.
+
+use ef_driver_common::mode::{Async, Blocking, DriverMode};
+use embedded_hal::i2c::I2c;
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use crate::{Adxl345, register};
+
+macro_rules! registers {
+    ($($variant:ident: $register:ident, $access:ident,)+) => {
+        /// A register of the ADXL345.
+        #[repr(u8)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        #[expect(missing_docs, reason = "Register names")]
+        pub enum Register {
+            $($variant = register::$register,)+
+        }
+
+        impl Register {
+            /// Every register, in address order.
+            pub const ALL: &'static [Self] = &[$(Self::$variant,)+];
+
+            /// Get the [`Access`] allowed to the register.
+            #[must_use]
+            pub const fn access(self) -> Access {
+                match self {
+                    $(Self::$variant => Access::$access,)+
+                }
+            }
+        }
+    };
+}
+
+registers! {
+    DeviceId: ADXL345_DEVICE_ID, ReadOnly,
+    TapThreshold: ADXL345_TAP_THRESHOLD, ReadWrite,
+    OffsetX: ADXL345_OFFSET_X, ReadWrite,
+    OffsetY: ADXL345_OFFSET_Y, ReadWrite,
+    OffsetZ: ADXL345_OFFSET_Z, ReadWrite,
+    TapDuration: ADXL345_TAP_DURATION, ReadWrite,
+    TapLatency: ADXL345_TAP_LATENCY, ReadWrite,
+    TapWindow: ADXL345_TAP_WINDOW, ReadWrite,
+    ActivityThreshold: ADXL345_ACTIVITY_THRESHOLD, ReadWrite,
+    InactivityThreshold: ADXL345_INACTIVITY_THRESHOLD, ReadWrite,
+    InactivityTime: ADXL345_INACTIVITY_TIME, ReadWrite,
+    InactivityEnable: ADXL345_INACTIVITY_ENABLE, ReadWrite,
+    FreefallThreshold: ADXL345_FREEFALL_THRESHOLD, ReadWrite,
+    FreefallTime: ADXL345_FREEFALL_TIME, ReadWrite,
+    TapAxes: ADXL345_TAP_AXES, ReadWrite,
+    TapStatus: ADXL345_TAP_STATUS, ReadOnly,
+    BwRate: ADXL345_BW_RATE, ReadWrite,
+    PowerControl: ADXL345_POWER_CONTROL, ReadWrite,
+    InterruptEnable: ADXL345_INTERRUPT_ENABLE, ReadWrite,
+    InterruptMap: ADXL345_INTERRUPT_MAP, ReadWrite,
+    InterruptSource: ADXL345_INTERRUPT_SOURCE, ReadOnly,
+    DataFormat: ADXL345_DATA_FORMAT, ReadWrite,
+    DataXLsb: ADXL345_DATA_X_LSB, ReadOnly,
+    DataXMsb: ADXL345_DATA_X_MSB, ReadOnly,
+    DataYLsb: ADXL345_DATA_Y_LSB, ReadOnly,
+    DataYMsb: ADXL345_DATA_Y_MSB, ReadOnly,
+    DataZLsb: ADXL345_DATA_Z_LSB, ReadOnly,
+    DataZMsb: ADXL345_DATA_Z_MSB, ReadOnly,
+    FifoControl: ADXL345_FIFO_CONTROL, ReadWrite,
+    FifoStatus: ADXL345_FIFO_STATUS, ReadOnly,
+}
+
+impl Register {
+    /// Get the address of the register.
+    #[inline]
+    #[must_use]
+    pub const fn address(self) -> u8 { self as u8 }
+
+    /// Returns `true` if the register can be written.
+    #[inline]
+    #[must_use]
+    pub const fn is_writable(self) -> bool { matches!(self.access(), Access::ReadWrite) }
+}
+
+/// The access allowed to a [`Register`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Access {
+    /// The register can only be read.
+    ReadOnly,
+    /// The register can be read and written.
+    ReadWrite,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Raw access to the registers of an [`Adxl345`],
+/// created with [`Adxl345::registers`].
+///
+/// The driver reads `DATA_FORMAT` and `BW_RATE` whenever it needs them
+/// instead of caching them, so changes made here are always seen by the
+/// typed API.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_adxl345::{Adxl345, GRange, RawRegisterError, Register, mode::Blocking};
+/// use embedded_hal::i2c::ErrorKind;
+/// # use ef_adxl345::test_util::Registers;
+///
+/// let mut registers = [0u8; 64];
+/// registers[0x00] = 0xE5;
+/// let mut accel = Adxl345::<_, Blocking>::new(Registers::new(registers), 0x53);
+///
+/// // Tweak the tap threshold, which has no typed API
+/// accel.registers().write(Register::TapThreshold, 0x30)?;
+/// assert_eq!(accel.registers().read(Register::DeviceId)?, 0xE5);
+///
+/// // Changes to `DATA_FORMAT` are seen by the typed API
+/// accel.registers().write(Register::DataFormat, 0b0000_0011)?;
+/// assert_eq!(accel.get_grange(), Ok(GRange::Sixteen));
+///
+/// // Read-only registers are never written
+/// assert_eq!(
+///     accel.registers().write(Register::DeviceId, 0x00),
+///     Err(RawRegisterError::ReadOnly(Register::DeviceId))
+/// );
+/// assert_eq!(accel.i2c().writes, [(0x1D, 0x30), (0x31, 0b0000_0011)]);
+///
+/// // The map covers every register, with its access from the datasheet
+/// assert_eq!(Register::ALL.len(), 30);
+/// assert!(Register::ALL.windows(2).all(|pair| pair[0].address() < pair[1].address()));
+/// assert_eq!(Register::ALL.iter().filter(|register| !register.is_writable()).count(), 10);
+/// # Ok::<(), RawRegisterError<ErrorKind>>(())
+/// ```
+pub struct RawRegisters<'a, I2C, MODE: DriverMode> {
+    accel: &'a mut Adxl345<I2C, MODE>,
+}
+
+impl<'a, I2C, MODE: DriverMode> RawRegisters<'a, I2C, MODE> {
+    /// Create a new [`RawRegisters`] view.
+    #[inline]
+    #[must_use]
+    pub(crate) const fn new(accel: &'a mut Adxl345<I2C, MODE>) -> Self { Self { accel } }
+
+    /// Check that a register can be written.
+    const fn check_writable<E>(register: Register) -> Result<(), RawRegisterError<E>> {
+        if register.is_writable() { Ok(()) } else { Err(RawRegisterError::ReadOnly(register)) }
+    }
+}
+
+impl<I2C: I2c> RawRegisters<'_, I2C, Blocking> {
+    /// Read a register.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn read(&mut self, register: Register) -> Result<u8, RawRegisterError<I2C::Error>> {
+        let mut buf = [0u8; 1];
+        self.accel.read_register(register.address(), &mut buf).map_err(RawRegisterError::I2C)?;
+        Ok(buf[0])
+    }
+
+    /// Write a register.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the register is read-only,
+    /// or if the I2C communication fails.
+    pub fn write(
+        &mut self,
+        register: Register,
+        value: u8,
+    ) -> Result<(), RawRegisterError<I2C::Error>> {
+        Self::check_writable(register)?;
+        self.accel.write_register(register.address(), value).map_err(RawRegisterError::I2C)
+    }
+}
+
+impl<I2C: AsyncI2c> RawRegisters<'_, I2C, Async> {
+    /// Read a register.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: reading a register has no side effects,
+    /// other than clearing interrupts when reading `INT_SOURCE`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn read(&mut self, register: Register) -> Result<u8, RawRegisterError<I2C::Error>> {
+        let mut buf = [0u8; 1];
+        let result = self.accel.read_register(register.address(), &mut buf).await;
+        result.map_err(RawRegisterError::I2C)?;
+        Ok(buf[0])
+    }
+
+    /// Write a register.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the register is written in a single transfer,
+    /// so it can be written again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the register is read-only,
+    /// or if the I2C communication fails.
+    pub async fn write(
+        &mut self,
+        register: Register,
+        value: u8,
+    ) -> Result<(), RawRegisterError<I2C::Error>> {
+        Self::check_writable(register)?;
+        self.accel.write_register(register.address(), value).await.map_err(RawRegisterError::I2C)
+    }
+}
+
+/// An error that can occur when accessing a [`Register`] directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RawRegisterError<E> {
+    /// The register is read-only.
+    ReadOnly(Register),
+    /// I2C bus error.
+    I2C(E),
+}
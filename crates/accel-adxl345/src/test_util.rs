@@ -0,0 +1,113 @@
+//! Mock buses for examples and tests, without a device.
+//!
+//! Enabled with the `test-util` feature.
+
+use alloc::vec::Vec;
+
+use embedded_hal::i2c::{self, ErrorKind, ErrorType, Operation};
+use embedded_hal_async::i2c as i2c_async;
+
+/// A bus holding the registers of a single device.
+///
+/// Reading `INT_SOURCE` clears the interrupts, as on the device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registers {
+    /// The registers, indexed by address.
+    pub registers: [u8; 64],
+    /// Every register write, as a register and its value.
+    pub writes: Vec<(u8, u8)>,
+    /// The first register and length of every read.
+    pub reads: Vec<(u8, usize)>,
+    /// The samples left in the FIFO, as X, Y and Z in LSB.
+    ///
+    /// While the FIFO holds samples, reads from `DATAX0` take the next one,
+    /// and `FIFO_STATUS` reports how many are left.
+    pub fifo: Vec<(i16, i16, i16)>,
+    pointer: u8,
+}
+
+impl Registers {
+    /// The `DATAX0` register.
+    const DATAX0: u8 = 0x32;
+    /// The `FIFO_STATUS` register.
+    const FIFO_STATUS: u8 = 0x39;
+    /// The `INT_SOURCE` register.
+    const INT_SOURCE: u8 = 0x30;
+
+    /// Create a new [`Registers`] with the given register values.
+    #[must_use]
+    pub const fn new(registers: [u8; 64]) -> Self {
+        Self { registers, writes: Vec::new(), reads: Vec::new(), fifo: Vec::new(), pointer: 0 }
+    }
+
+    /// Fill the FIFO with `samples`.
+    #[must_use]
+    pub fn with_fifo(mut self, samples: Vec<(i16, i16, i16)>) -> Self {
+        self.fifo = samples;
+        self
+    }
+
+    /// Forget every recorded write and read.
+    pub fn clear(&mut self) {
+        self.writes.clear();
+        self.reads.clear();
+    }
+
+    /// Apply the operations of a transaction.
+    fn apply(&mut self, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        for op in ops {
+            match op {
+                Operation::Write([register]) => self.pointer = *register,
+                Operation::Write([register, value]) => {
+                    self.registers[usize::from(*register)] = *value;
+                    self.writes.push((*register, *value));
+                }
+                Operation::Read(buf) => {
+                    self.read(buf);
+                    self.reads.push((self.pointer, buf.len()));
+                }
+                Operation::Write(_) => return Err(ErrorKind::Other),
+            }
+        }
+        Ok(())
+    }
+
+    /// Read from the current register on.
+    fn read(&mut self, buf: &mut [u8]) {
+        match self.pointer {
+            Self::DATAX0 if !self.fifo.is_empty() => {
+                let (x, y, z) = self.fifo.remove(0);
+                buf.copy_from_slice(&[x.to_le_bytes(), y.to_le_bytes(), z.to_le_bytes()].concat());
+            }
+            #[expect(clippy::cast_possible_truncation, reason = "At most 32 entries")]
+            Self::FIFO_STATUS if !self.fifo.is_empty() => buf[0] = self.fifo.len().min(32) as u8,
+            register => {
+                let start = usize::from(register);
+                buf.copy_from_slice(&self.registers[start..start + buf.len()]);
+                if register == Self::INT_SOURCE {
+                    self.registers[usize::from(Self::INT_SOURCE)] = 0;
+                }
+            }
+        }
+    }
+}
+
+impl Default for Registers {
+    fn default() -> Self { Self::new([0; 64]) }
+}
+
+impl ErrorType for Registers {
+    type Error = ErrorKind;
+}
+
+impl i2c::I2c for Registers {
+    fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.apply(ops)
+    }
+}
+
+impl i2c_async::I2c for Registers {
+    async fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.apply(ops)
+    }
+}
@@ -0,0 +1,353 @@
+use ef_driver_common::{
+    identify::{AsyncIdentify, DeviceIdentity},
+    mode::Async,
+    sync::AsyncStart,
+};
+use embedded_hal_async::i2c::I2c;
+
+use crate::{
+    ADDRESSES, CLOCK_GATE_ALL, CLOCK_RUN_ALL, Channel, ChannelPair, ClockStatus, DmicControl,
+    Es7210, Es7210Error, GainTrim, InputType, MicGain, PAIR_POWER_DOWN, PAIR_POWER_UP, PGA_ENABLE,
+    ProbeResult, ProbeStatus, TrimmedGain, register,
+};
+
+/// Probe every ES7210 address, reading the chip ID of each device found.
+///
+/// # Errors
+///
+/// Returns an error if the I2C communication fails,
+/// other than addresses that are not acknowledged.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_es7210::{ProbeStatus, probe_all_async};
+/// use embedded_hal_async::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+/// # use ef_es7210::test_util::block_on;
+///
+/// /// A bus with an ES7210 at `0x40` and an ES7243 at `0x42`.
+/// #[derive(Default)]
+/// struct Bus(u8);
+///
+/// impl ErrorType for Bus {
+///     type Error = ErrorKind;
+/// }
+///
+/// impl I2c for Bus {
+///     async fn transaction(
+///         &mut self,
+///         address: u8,
+///         ops: &mut [Operation<'_>],
+///     ) -> Result<(), ErrorKind> {
+///         let id: u16 = match address {
+///             0x40 => 0x7210,
+///             0x42 => 0x7243,
+///             _ => return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+///         };
+///         for op in ops {
+///             match op {
+///                 Operation::Write([register]) => self.0 = *register,
+///                 Operation::Read([value]) => {
+///                     *value = id.to_be_bytes()[usize::from(self.0 == 0x3E)]
+///                 }
+///                 _ => unreachable!(),
+///             }
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let result = block_on(probe_all_async(&mut Bus::default()))?;
+/// assert_eq!(result.status(0x40), Some(ProbeStatus::Found));
+/// assert_eq!(result.status(0x41), Some(ProbeStatus::NoResponse));
+/// assert_eq!(result.status(0x42), Some(ProbeStatus::WrongId(0x7243)));
+/// assert_eq!(result.status(0x44), None);
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub async fn probe_all_async<I2C: I2c>(i2c: &mut I2C) -> Result<ProbeResult, I2C::Error> {
+    let mut statuses = [ProbeStatus::NoResponse; 4];
+    for (status, address) in statuses.iter_mut().zip(ADDRESSES) {
+        let id = Es7210::<_, Async>::new(&mut *i2c, address).chip_id().await;
+        *status = ProbeStatus::from_chip_id(id)?;
+    }
+    Ok(ProbeResult { statuses })
+}
+
+impl<I2C: I2c> Es7210<I2C, Async> {
+    /// Read the chip ID.
+    ///
+    /// This should be [`Es7210::CHIP_ID`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn chip_id(&mut self) -> Result<u16, Es7210Error<I2C::Error>> {
+        let mut buf = [0u8; 2];
+        self.read_register(register::ES7210_CHIP_ID1, &mut buf[..1])
+            .await
+            .map_err(Es7210Error::I2C)?;
+        self.read_register(register::ES7210_CHIP_ID0, &mut buf[1..])
+            .await
+            .map_err(Es7210Error::I2C)?;
+        Ok(u16::from_be_bytes(buf))
+    }
+
+    /// Arm the device, gating its clocks so that no samples are produced
+    /// until [`Es7210::start`].
+    ///
+    /// Configure the inputs, gains and serial port before starting. The
+    /// clocks that are running when the device is first armed are released
+    /// again when it starts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn arm(&mut self) -> Result<(), Es7210Error<I2C::Error>> {
+        if self.armed.is_none() {
+            let mut buf = [0u8; 1];
+            self.read_register(register::ES7210_CLOCK_OFF, &mut buf)
+                .await
+                .map_err(Es7210Error::I2C)?;
+            self.armed = Some(buf[0]);
+        }
+        self.write_register(register::ES7210_CLOCK_OFF, CLOCK_GATE_ALL)
+            .await
+            .map_err(Es7210Error::I2C)
+    }
+
+    /// Start the device armed with [`Es7210::arm`],
+    /// releasing its clocks with a single register write.
+    ///
+    /// If the device was not armed, every clock is released.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn start(&mut self) -> Result<(), Es7210Error<I2C::Error>> {
+        let clocks = self.armed.unwrap_or(CLOCK_RUN_ALL);
+        self.write_register(register::ES7210_CLOCK_OFF, clocks).await.map_err(Es7210Error::I2C)?;
+        self.armed = None;
+        Ok(())
+    }
+
+    /// Set the type of microphones connected to a [`ChannelPair`].
+    ///
+    /// For PDM microphones the analog PGAs and modulators of the pair are
+    /// powered down. Switching back to analog powers them up before the
+    /// digital microphone interface is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pair does not support the input type,
+    /// or if the I2C communication fails.
+    pub async fn set_input_type(
+        &mut self,
+        pair: ChannelPair,
+        input: InputType,
+    ) -> Result<(), Es7210Error<I2C::Error>> {
+        match input {
+            InputType::Pdm { .. } => {
+                if !pair.supports_pdm() {
+                    return Err(Es7210Error::UnsupportedInput(pair));
+                }
+
+                self.update_dmic(input).await?;
+                self.set_analog_power(pair, false).await.map_err(Es7210Error::I2C)
+            }
+            InputType::Analog => {
+                self.set_analog_power(pair, true).await.map_err(Es7210Error::I2C)?;
+                if pair.supports_pdm() {
+                    self.update_dmic(input).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Get the type of microphones connected to a [`ChannelPair`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn input_type(
+        &mut self,
+        pair: ChannelPair,
+    ) -> Result<InputType, Es7210Error<I2C::Error>> {
+        if !pair.supports_pdm() {
+            return Ok(InputType::Analog);
+        }
+
+        let mut buf = [0u8; 1];
+        self.read_register(register::ES7210_DMIC_CONTROL, &mut buf)
+            .await
+            .map_err(Es7210Error::I2C)?;
+        Ok(DmicControl::from_bits_truncate(buf[0]).input())
+    }
+
+    /// Set the nominal PGA gain of a channel.
+    ///
+    /// The channel's [`GainTrim`] offset is added on top, see
+    /// [`Es7210::apply_trim`]. Returns `true` if the trimmed gain was clamped
+    /// to the range of the PGA.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn set_mic_gain(
+        &mut self,
+        channel: Channel,
+        gain: MicGain,
+    ) -> Result<bool, Es7210Error<I2C::Error>> {
+        self.nominal[channel as usize] = Some(gain);
+        self.write_gain(channel).await.map_err(Es7210Error::I2C)
+    }
+
+    /// Apply a per-unit [`GainTrim`] to every channel.
+    ///
+    /// The trim is kept and added to the nominal gain of every later
+    /// [`Es7210::set_mic_gain`]. Channels whose nominal gain has not been set
+    /// take it from their gain register. The PGA is set to the largest step
+    /// not above the trimmed gain and the ADC's digital volume makes up the
+    /// rest, overwriting any digital volume set before.
+    ///
+    /// Returns which channels were clamped to the range of the PGA.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn apply_trim(
+        &mut self,
+        trim: &GainTrim,
+    ) -> Result<[bool; 4], Es7210Error<I2C::Error>> {
+        self.trim = *trim;
+
+        let mut clamped = [false; 4];
+        for channel in Channel::ALL {
+            clamped[channel as usize] = self.write_gain(channel).await.map_err(Es7210Error::I2C)?;
+        }
+        Ok(clamped)
+    }
+
+    /// Write the nominal gain plus the trim of a channel,
+    /// returning `true` if it was clamped.
+    async fn write_gain(&mut self, channel: Channel) -> Result<bool, I2C::Error> {
+        let mut buf = [0u8; 1];
+        self.read_register(channel.gain_register(), &mut buf).await?;
+        let nominal = *self.nominal[channel as usize].get_or_insert(MicGain::from_register(buf[0]));
+
+        let gain = TrimmedGain::new(nominal, self.trim.offset(channel));
+        self.write_register(channel.gain_register(), gain.pga.to_register(buf[0])).await?;
+        self.write_register(channel.volume_register(), gain.volume).await?;
+        Ok(gain.clamped)
+    }
+
+    /// Read the clock and power registers in a single transaction.
+    ///
+    /// See [`ClockWatchdog`](crate::ClockWatchdog) for monitoring them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn clock_status(&mut self) -> Result<ClockStatus, Es7210Error<I2C::Error>> {
+        let mut buf = [0u8; ClockStatus::LEN];
+        self.read_register(register::ES7210_CLOCK_OFF, &mut buf).await.map_err(Es7210Error::I2C)?;
+        Ok(ClockStatus::from_bytes(buf))
+    }
+
+    /// Write back the clock and power registers of a [`ClockStatus`].
+    ///
+    /// The dividers and power are restored before the clocks are released,
+    /// so the clocks start with the expected configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn restore_clocks(
+        &mut self,
+        status: &ClockStatus,
+    ) -> Result<(), Es7210Error<I2C::Error>> {
+        let [clock_off, registers @ ..] = status.to_bytes();
+        for (register, value) in (register::ES7210_MAIN_CLOCK..).zip(registers) {
+            self.write_register(register, value).await.map_err(Es7210Error::I2C)?;
+        }
+        self.write_register(register::ES7210_CLOCK_OFF, clock_off).await.map_err(Es7210Error::I2C)
+    }
+
+    /// Update the digital microphone interface.
+    async fn update_dmic(&mut self, input: InputType) -> Result<(), Es7210Error<I2C::Error>> {
+        let mut buf = [0u8; 1];
+        self.read_register(register::ES7210_DMIC_CONTROL, &mut buf)
+            .await
+            .map_err(Es7210Error::I2C)?;
+        let control = DmicControl::from_bits_truncate(buf[0]).with_input(input);
+        self.write_register(register::ES7210_DMIC_CONTROL, control.bits())
+            .await
+            .map_err(Es7210Error::I2C)
+    }
+
+    /// Power the analog PGAs and modulators of a pair up or down.
+    ///
+    /// Powering up enables the pair before its PGAs, powering down disables
+    /// the PGAs before the pair.
+    async fn set_analog_power(
+        &mut self,
+        pair: ChannelPair,
+        enable: bool,
+    ) -> Result<(), I2C::Error> {
+        if enable {
+            self.write_register(pair.power_register(), PAIR_POWER_UP).await?;
+        }
+
+        for register in pair.gain_registers() {
+            let mut buf = [0u8; 1];
+            self.read_register(register, &mut buf).await?;
+            let gain = if enable { buf[0] | PGA_ENABLE } else { buf[0] & !PGA_ENABLE };
+            self.write_register(register, gain).await?;
+        }
+
+        if !enable {
+            self.write_register(pair.power_register(), PAIR_POWER_DOWN).await?;
+        }
+        Ok(())
+    }
+
+    /// Read from a register.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    async fn read_register(&mut self, register: u8, buf: &mut [u8]) -> Result<(), I2C::Error> {
+        self.i2c.write_read(self.address, &[register], buf).await
+    }
+
+    /// Write to a register.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    async fn write_register(&mut self, register: u8, value: u8) -> Result<(), I2C::Error> {
+        self.i2c.write(self.address, &[register, value]).await
+    }
+}
+
+impl<I2C: I2c> AsyncStart for Es7210<I2C, Async> {
+    type Error = Es7210Error<I2C::Error>;
+
+    /// Start the device, see [`Es7210::start`].
+    #[inline]
+    async fn start(&mut self) -> Result<(), Self::Error> { self.start().await }
+}
+
+impl<I2C: I2c> AsyncIdentify for Es7210<I2C, Async> {
+    type Error = Es7210Error<I2C::Error>;
+
+    /// Read the chip ID and version, see [`Es7210::chip_id`].
+    async fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> {
+        let id = self.chip_id().await?;
+        let mut version = [0u8; 1];
+        self.read_register(register::ES7210_CHIP_VERSION, &mut version)
+            .await
+            .map_err(Es7210Error::I2C)?;
+        Ok(DeviceIdentity::new("ES7210", &id.to_be_bytes(), Some(u16::from(version[0]))))
+    }
+}
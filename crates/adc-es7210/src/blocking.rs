@@ -0,0 +1,479 @@
+use ef_driver_common::{
+    identify::{DeviceIdentity, Identify},
+    mode::Blocking,
+    sync::Start,
+};
+use embedded_hal::i2c::I2c;
+
+use crate::{
+    ADDRESSES, CLOCK_GATE_ALL, CLOCK_RUN_ALL, Channel, ChannelPair, ClockStatus, DmicControl,
+    Es7210, Es7210Error, GainTrim, InputType, MicGain, PAIR_POWER_DOWN, PAIR_POWER_UP, PGA_ENABLE,
+    ProbeResult, ProbeStatus, TrimmedGain, register,
+};
+
+/// Probe every ES7210 address, reading the chip ID of each device found.
+///
+/// # Errors
+///
+/// Returns an error if the I2C communication fails,
+/// other than addresses that are not acknowledged.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_es7210::{ProbeStatus, probe_all};
+/// use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+///
+/// /// A bus with an ES7210 at `0x40` and an ES7243 at `0x42`.
+/// #[derive(Default)]
+/// struct Bus(u8);
+///
+/// impl ErrorType for Bus {
+///     type Error = ErrorKind;
+/// }
+///
+/// impl I2c for Bus {
+///     fn transaction(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+///         let id: u16 = match address {
+///             0x40 => 0x7210,
+///             0x42 => 0x7243,
+///             _ => return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+///         };
+///         for op in ops {
+///             match op {
+///                 Operation::Write([register]) => self.0 = *register,
+///                 Operation::Read([value]) => {
+///                     *value = id.to_be_bytes()[usize::from(self.0 == 0x3E)]
+///                 }
+///                 _ => unreachable!(),
+///             }
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let result = probe_all(&mut Bus::default())?;
+/// assert_eq!(
+///     result.iter().collect::<Vec<_>>(),
+///     [
+///         (0x40, ProbeStatus::Found),
+///         (0x41, ProbeStatus::NoResponse),
+///         (0x42, ProbeStatus::WrongId(0x7243)),
+///         (0x43, ProbeStatus::NoResponse),
+///     ]
+/// );
+/// assert_eq!(result.found().collect::<Vec<_>>(), [0x40]);
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub fn probe_all<I2C: I2c>(i2c: &mut I2C) -> Result<ProbeResult, I2C::Error> {
+    let mut statuses = [ProbeStatus::NoResponse; 4];
+    for (status, address) in statuses.iter_mut().zip(ADDRESSES) {
+        let id = Es7210::<_, Blocking>::new(&mut *i2c, address).chip_id();
+        *status = ProbeStatus::from_chip_id(id)?;
+    }
+    Ok(ProbeResult { statuses })
+}
+
+impl<I2C: I2c> Es7210<I2C, Blocking> {
+    /// Read the chip ID.
+    ///
+    /// This should be [`Es7210::CHIP_ID`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn chip_id(&mut self) -> Result<u16, Es7210Error<I2C::Error>> {
+        let mut buf = [0u8; 2];
+        self.read_register(register::ES7210_CHIP_ID1, &mut buf[..1]).map_err(Es7210Error::I2C)?;
+        self.read_register(register::ES7210_CHIP_ID0, &mut buf[1..]).map_err(Es7210Error::I2C)?;
+        Ok(u16::from_be_bytes(buf))
+    }
+
+    /// Arm the device, gating its clocks so that no samples are produced
+    /// until [`Es7210::start`].
+    ///
+    /// Configure the inputs, gains and serial port before starting. The
+    /// clocks that are running when the device is first armed are released
+    /// again when it starts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_es7210::{Es7210, mode::Blocking};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_es7210::test_util::Registers;
+    ///
+    /// // Running with the clocks of unused inputs gated
+    /// let mut registers = [0u8; 256];
+    /// registers[0x01] = 0b0001_0100;
+    /// let mut adc = Es7210::<_, Blocking>::new(Registers::new(registers), 0x40);
+    ///
+    /// // Arming gates every clock, arming again keeps the running clocks
+    /// adc.arm()?;
+    /// adc.arm()?;
+    /// assert!(adc.is_armed());
+    /// assert_eq!(adc.i2c().registers[0x01], 0x7F);
+    ///
+    /// // Starting is a single write, restoring the clocks
+    /// adc.i2c_mut().clear();
+    /// adc.start()?;
+    /// assert!(!adc.is_armed());
+    /// assert!(adc.i2c().reads.is_empty());
+    /// assert_eq!(adc.i2c().writes, [(0x01, 0b0001_0100)]);
+    /// # Ok::<(), ef_es7210::Es7210Error<ErrorKind>>(())
+    /// ```
+    pub fn arm(&mut self) -> Result<(), Es7210Error<I2C::Error>> {
+        if self.armed.is_none() {
+            let mut buf = [0u8; 1];
+            self.read_register(register::ES7210_CLOCK_OFF, &mut buf).map_err(Es7210Error::I2C)?;
+            self.armed = Some(buf[0]);
+        }
+        self.write_register(register::ES7210_CLOCK_OFF, CLOCK_GATE_ALL).map_err(Es7210Error::I2C)
+    }
+
+    /// Start the device armed with [`Es7210::arm`],
+    /// releasing its clocks with a single register write.
+    ///
+    /// If the device was not armed, every clock is released.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn start(&mut self) -> Result<(), Es7210Error<I2C::Error>> {
+        let clocks = self.armed.unwrap_or(CLOCK_RUN_ALL);
+        self.write_register(register::ES7210_CLOCK_OFF, clocks).map_err(Es7210Error::I2C)?;
+        self.armed = None;
+        Ok(())
+    }
+
+    /// Set the type of microphones connected to a [`ChannelPair`].
+    ///
+    /// For PDM microphones the analog PGAs and modulators of the pair are
+    /// powered down. Switching back to analog powers them up before the
+    /// digital microphone interface is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pair does not support the input type,
+    /// or if the I2C communication fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_es7210::{ChannelPair, Es7210, InputType, PdmClockRatio, mode::Blocking};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_es7210::test_util::Registers;
+    ///
+    /// // Analog microphones on all inputs, with a gain of 30 dB
+    /// let mut registers = [0u8; 256];
+    /// registers[0x43..=0x46].fill(0x1A);
+    /// let mut adc = Es7210::<_, Blocking>::new(Registers::new(registers), 0x40);
+    ///
+    /// // PDM microphones on MIC1/MIC2, analog microphones on MIC3/MIC4
+    /// let pdm = InputType::Pdm { clock_ratio: PdmClockRatio::X128 };
+    /// adc.set_input_type(ChannelPair::Mic12, pdm)?;
+    /// adc.set_input_type(ChannelPair::Mic34, InputType::Analog)?;
+    /// assert_eq!(
+    ///     adc.i2c().writes,
+    ///     [
+    ///         // Enable the PDM interface, then power down the analog inputs
+    ///         (0x10, 0b1000_0001),
+    ///         (0x43, 0x0A),
+    ///         (0x44, 0x0A),
+    ///         (0x4B, 0xFF),
+    ///         // Power up the analog inputs, then leave the PDM interface alone
+    ///         (0x4C, 0x00),
+    ///         (0x45, 0x1A),
+    ///         (0x46, 0x1A),
+    ///     ]
+    /// );
+    /// assert_eq!(adc.input_type(ChannelPair::Mic12)?, pdm);
+    /// assert_eq!(adc.input_type(ChannelPair::Mic34)?, InputType::Analog);
+    ///
+    /// // MIC3/MIC4 cannot take PDM microphones
+    /// assert!(adc.set_input_type(ChannelPair::Mic34, pdm).is_err());
+    ///
+    /// // Switching back restores the analog path before disabling PDM
+    /// adc.i2c_mut().writes.clear();
+    /// adc.set_input_type(ChannelPair::Mic12, InputType::Analog)?;
+    /// assert_eq!(adc.i2c().writes, [(0x4B, 0x00), (0x43, 0x1A), (0x44, 0x1A), (0x10, 0b1000_0000)]);
+    /// # Ok::<(), ef_es7210::Es7210Error<ErrorKind>>(())
+    /// ```
+    pub fn set_input_type(
+        &mut self,
+        pair: ChannelPair,
+        input: InputType,
+    ) -> Result<(), Es7210Error<I2C::Error>> {
+        match input {
+            InputType::Pdm { .. } => {
+                if !pair.supports_pdm() {
+                    return Err(Es7210Error::UnsupportedInput(pair));
+                }
+
+                self.update_dmic(input)?;
+                self.set_analog_power(pair, false).map_err(Es7210Error::I2C)
+            }
+            InputType::Analog => {
+                self.set_analog_power(pair, true).map_err(Es7210Error::I2C)?;
+                if pair.supports_pdm() {
+                    self.update_dmic(input)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Get the type of microphones connected to a [`ChannelPair`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn input_type(&mut self, pair: ChannelPair) -> Result<InputType, Es7210Error<I2C::Error>> {
+        if !pair.supports_pdm() {
+            return Ok(InputType::Analog);
+        }
+
+        let mut buf = [0u8; 1];
+        self.read_register(register::ES7210_DMIC_CONTROL, &mut buf).map_err(Es7210Error::I2C)?;
+        Ok(DmicControl::from_bits_truncate(buf[0]).input())
+    }
+
+    /// Set the nominal PGA gain of a channel.
+    ///
+    /// The channel's [`GainTrim`] offset is added on top,
+    /// see [`Es7210::apply_trim`]. Returns `true` if the trimmed gain was
+    /// clamped to the range of the PGA.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn set_mic_gain(
+        &mut self,
+        channel: Channel,
+        gain: MicGain,
+    ) -> Result<bool, Es7210Error<I2C::Error>> {
+        self.nominal[channel as usize] = Some(gain);
+        self.write_gain(channel).map_err(Es7210Error::I2C)
+    }
+
+    /// Apply a per-unit [`GainTrim`] to every channel.
+    ///
+    /// The trim is kept and added to the nominal gain of every later
+    /// [`Es7210::set_mic_gain`]. Channels whose nominal gain has not been set
+    /// take it from their gain register. The PGA is set to the largest step
+    /// not above the trimmed gain and the ADC's digital volume makes up the
+    /// rest, overwriting any digital volume set before.
+    ///
+    /// Returns which channels were clamped to the range of the PGA.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_es7210::{Channel, Es7210, GainTrim, MicGain, mode::Blocking};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_es7210::test_util::Registers;
+    ///
+    /// /// The PGA gain and digital volume of MIC1.
+    /// fn mic1(adc: &Es7210<Registers, Blocking>) -> (u8, u8) {
+    ///     (adc.i2c().registers[0x43], adc.i2c().registers[0x1E])
+    /// }
+    ///
+    /// // Every PGA enabled, MIC1 and MIC2 at 30 dB, MIC3 and MIC4 at 0 dB
+    /// let mut registers = [0u8; 256];
+    /// registers[0x43..=0x44].fill(0x1A);
+    /// registers[0x45..=0x46].fill(0x10);
+    /// let mut adc = Es7210::<_, Blocking>::new(Registers::new(registers), 0x40);
+    ///
+    /// // Load the trim measured at the end of line
+    /// let stored = GainTrim::new([3, 0, -2, 0]).to_bytes();
+    /// let trim = GainTrim::from_bytes(&stored).expect("Valid trim");
+    ///
+    /// // MIC1 is raised by 1.5 dB with the digital volume, MIC3 cannot go below 0 dB
+    /// assert_eq!(adc.apply_trim(&trim)?, [false, false, true, false]);
+    /// assert_eq!(adc.mic_gain(Channel::Mic1), Some(MicGain::Db30));
+    /// assert_eq!(mic1(&adc), (0x1A, 0xBF + 3));
+    /// assert_eq!(adc.i2c().registers[0x45], 0x10);
+    ///
+    /// // Changing the nominal gain keeps the trim, 33 dB + 1.5 dB is a PGA step
+    /// assert!(!adc.set_mic_gain(Channel::Mic1, MicGain::Db33)?);
+    /// assert_eq!(mic1(&adc), (0x1C, 0xBF));
+    ///
+    /// // The trim is clamped at the top of the range
+    /// assert!(adc.set_mic_gain(Channel::Mic1, MicGain::Db37_5)?);
+    /// assert_eq!(mic1(&adc), (0x1E, 0xBF));
+    ///
+    /// // Removing the trim restores the nominal gains
+    /// assert_eq!(adc.apply_trim(&GainTrim::ZERO)?, [false; 4]);
+    /// assert_eq!(mic1(&adc), (0x1E, 0xBF));
+    /// assert_eq!(adc.i2c().registers[0x45], 0x10);
+    /// # Ok::<(), ef_es7210::Es7210Error<ErrorKind>>(())
+    /// ```
+    pub fn apply_trim(&mut self, trim: &GainTrim) -> Result<[bool; 4], Es7210Error<I2C::Error>> {
+        self.trim = *trim;
+
+        let mut clamped = [false; 4];
+        for channel in Channel::ALL {
+            clamped[channel as usize] = self.write_gain(channel).map_err(Es7210Error::I2C)?;
+        }
+        Ok(clamped)
+    }
+
+    /// Write the nominal gain plus the trim of a channel,
+    /// returning `true` if it was clamped.
+    fn write_gain(&mut self, channel: Channel) -> Result<bool, I2C::Error> {
+        let mut buf = [0u8; 1];
+        self.read_register(channel.gain_register(), &mut buf)?;
+        let nominal = *self.nominal[channel as usize].get_or_insert(MicGain::from_register(buf[0]));
+
+        let gain = TrimmedGain::new(nominal, self.trim.offset(channel));
+        self.write_register(channel.gain_register(), gain.pga.to_register(buf[0]))?;
+        self.write_register(channel.volume_register(), gain.volume)?;
+        Ok(gain.clamped)
+    }
+
+    /// Read the clock and power registers in a single transaction.
+    ///
+    /// See [`ClockWatchdog`](crate::ClockWatchdog) for monitoring them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn clock_status(&mut self) -> Result<ClockStatus, Es7210Error<I2C::Error>> {
+        let mut buf = [0u8; ClockStatus::LEN];
+        self.read_register(register::ES7210_CLOCK_OFF, &mut buf).map_err(Es7210Error::I2C)?;
+        Ok(ClockStatus::from_bytes(buf))
+    }
+
+    /// Write back the clock and power registers of a [`ClockStatus`].
+    ///
+    /// The dividers and power are restored before the clocks are released,
+    /// so the clocks start with the expected configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn restore_clocks(&mut self, status: &ClockStatus) -> Result<(), Es7210Error<I2C::Error>> {
+        let [clock_off, registers @ ..] = status.to_bytes();
+        for (register, value) in (register::ES7210_MAIN_CLOCK..).zip(registers) {
+            self.write_register(register, value).map_err(Es7210Error::I2C)?;
+        }
+        self.write_register(register::ES7210_CLOCK_OFF, clock_off).map_err(Es7210Error::I2C)
+    }
+
+    /// Update the digital microphone interface.
+    fn update_dmic(&mut self, input: InputType) -> Result<(), Es7210Error<I2C::Error>> {
+        let mut buf = [0u8; 1];
+        self.read_register(register::ES7210_DMIC_CONTROL, &mut buf).map_err(Es7210Error::I2C)?;
+        let control = DmicControl::from_bits_truncate(buf[0]).with_input(input);
+        self.write_register(register::ES7210_DMIC_CONTROL, control.bits()).map_err(Es7210Error::I2C)
+    }
+
+    /// Power the analog PGAs and modulators of a pair up or down.
+    ///
+    /// Powering up enables the pair before its PGAs, powering down disables
+    /// the PGAs before the pair.
+    fn set_analog_power(&mut self, pair: ChannelPair, enable: bool) -> Result<(), I2C::Error> {
+        if enable {
+            self.write_register(pair.power_register(), PAIR_POWER_UP)?;
+        }
+
+        for register in pair.gain_registers() {
+            let mut buf = [0u8; 1];
+            self.read_register(register, &mut buf)?;
+            let gain = if enable { buf[0] | PGA_ENABLE } else { buf[0] & !PGA_ENABLE };
+            self.write_register(register, gain)?;
+        }
+
+        if !enable {
+            self.write_register(pair.power_register(), PAIR_POWER_DOWN)?;
+        }
+        Ok(())
+    }
+
+    /// Read from a register.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    fn read_register(&mut self, register: u8, buf: &mut [u8]) -> Result<(), I2C::Error> {
+        self.i2c.write_read(self.address, &[register], buf)
+    }
+
+    /// Write to a register.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    fn write_register(&mut self, register: u8, value: u8) -> Result<(), I2C::Error> {
+        self.i2c.write(self.address, &[register, value])
+    }
+}
+
+impl<I2C: I2c> Start for Es7210<I2C, Blocking> {
+    type Error = Es7210Error<I2C::Error>;
+
+    /// Start the device, see [`Es7210::start`].
+    #[inline]
+    fn start(&mut self) -> Result<(), Self::Error> { self.start() }
+}
+
+impl<I2C: I2c> Identify for Es7210<I2C, Blocking> {
+    type Error = Es7210Error<I2C::Error>;
+
+    /// Read the chip ID and version, see [`Es7210::chip_id`].
+    ///
+    /// An ES7210 reports `[0x72, 0x10]`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_driver_common::identify::Identify;
+    /// use ef_es7210::{Es7210, Es7210Error, mode::Blocking};
+    /// use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+    ///
+    /// /// A bus with an ES7210 at `0x40`.
+    /// struct Bus;
+    ///
+    /// impl ErrorType for Bus {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl I2c for Bus {
+    ///     fn transaction(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+    ///         if address != 0x40 {
+    ///             return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+    ///         }
+    ///         match ops {
+    ///             [Operation::Write([register]), Operation::Read([value])] => {
+    ///                 *value = [0x72, 0x10, 0x01][usize::from(*register - 0x3D)];
+    ///             }
+    ///             _ => unreachable!(),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let identity = Es7210::<_, Blocking>::new(Bus, 0x40).identify()?;
+    /// assert_eq!((identity.name(), identity.id()), ("ES7210", &[0x72, 0x10][..]));
+    /// assert_eq!(identity.version(), Some(0x01));
+    ///
+    /// let result = Es7210::<_, Blocking>::new(Bus, 0x41).identify();
+    /// assert!(matches!(result, Err(Es7210Error::I2C(ErrorKind::NoAcknowledge(_)))));
+    /// # Ok::<(), Es7210Error<ErrorKind>>(())
+    /// ```
+    fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> {
+        let id = self.chip_id()?;
+        let mut version = [0u8; 1];
+        self.read_register(register::ES7210_CHIP_VERSION, &mut version)
+            .map_err(Es7210Error::I2C)?;
+        Ok(DeviceIdentity::new("ES7210", &id.to_be_bytes(), Some(u16::from(version[0]))))
+    }
+}
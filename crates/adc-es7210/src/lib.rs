@@ -1,10 +1,428 @@
 #![doc = include_str!("../README.md")]
-#![expect(unused_imports, reason = "WIP")]
 #![no_std]
 
+#[cfg(feature = "test-util")]
+extern crate alloc;
+
+use core::marker::PhantomData;
+
 #[cfg(not(feature = "defmt"))]
 use bitflags::bitflags;
 #[cfg(feature = "defmt")]
 use defmt::bitflags;
 pub use ef_driver_common::mode;
 use ef_driver_common::mode::DriverMode;
+use embedded_hal::i2c::{Error, ErrorKind};
+
+mod r#async;
+mod blocking;
+mod register;
+#[cfg(feature = "test-util")]
+#[doc(hidden)]
+pub mod test_util;
+mod trim;
+mod watchdog;
+
+pub use r#async::probe_all_async;
+pub use blocking::probe_all;
+pub use trim::GainTrim;
+use trim::TrimmedGain;
+pub use watchdog::{ClockStatus, ClockWatchdog, Es7210Fault, WatchdogConfig, WatchdogEvent};
+
+/// The I2C addresses an ES7210 can be strapped to, indexed by `AD1:AD0`.
+pub const ADDRESSES: [u8; 4] =
+    [address(false, false), address(false, true), address(true, false), address(true, true)];
+
+/// Get the I2C address of an ES7210 from the levels of its `AD1` and `AD0`
+/// strap pins.
+///
+/// ```rust
+/// use ef_es7210::{Es7210, address, mode::Blocking};
+///
+/// assert_eq!(address(false, false), Es7210::<(), Blocking>::DEFAULT_ADDRESS);
+/// assert_eq!(address(true, false), 0x42);
+/// ```
+#[must_use]
+pub const fn address(ad1: bool, ad0: bool) -> u8 { 0x40 | (ad1 as u8) << 1 | ad0 as u8 }
+
+/// A driver for an ES7210 audio ADC.
+pub struct Es7210<I2C, MODE: DriverMode> {
+    i2c: I2C,
+    address: u8,
+    armed: Option<u8>,
+    nominal: [Option<MicGain>; 4],
+    trim: GainTrim,
+    _mode: PhantomData<MODE>,
+}
+
+impl<I2C, MODE: DriverMode> Es7210<I2C, MODE> {
+    /// The expected value of the chip ID registers.
+    pub const CHIP_ID: u16 = 0x7210;
+    /// The address of an ES7210 with both address pins pulled low.
+    pub const DEFAULT_ADDRESS: u8 = 0x40;
+
+    /// Create a new [`Es7210`] driver instance.
+    #[inline]
+    #[must_use]
+    pub const fn new(i2c: I2C, address: u8) -> Self {
+        Self {
+            i2c,
+            address,
+            armed: None,
+            nominal: [None; 4],
+            trim: GainTrim::ZERO,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Get the I2C address of the [`Es7210`] device.
+    #[inline]
+    #[must_use]
+    pub const fn address(&self) -> u8 { self.address }
+
+    /// Returns `true` if the device was armed and is waiting to be started.
+    ///
+    /// See `Es7210::arm`.
+    #[inline]
+    #[must_use]
+    pub const fn is_armed(&self) -> bool { self.armed.is_some() }
+
+    /// Get the nominal gain of a channel, before its trim is applied.
+    ///
+    /// Returns `None` until the gain is set with `Es7210::set_mic_gain`
+    /// or read by `Es7210::apply_trim`.
+    #[inline]
+    #[must_use]
+    pub const fn mic_gain(&self, channel: Channel) -> Option<MicGain> {
+        self.nominal[channel as usize]
+    }
+
+    /// Get the [`GainTrim`] applied on top of the nominal gains.
+    ///
+    /// See `Es7210::apply_trim`.
+    #[inline]
+    #[must_use]
+    pub const fn trim(&self) -> &GainTrim { &self.trim }
+
+    /// Get a reference to the I2C bus.
+    #[inline]
+    #[must_use]
+    pub const fn i2c(&self) -> &I2C { &self.i2c }
+
+    /// Get a mutable reference to the I2C bus.
+    #[inline]
+    #[must_use]
+    pub const fn i2c_mut(&mut self) -> &mut I2C { &mut self.i2c }
+
+    /// Release the I2C bus.
+    #[inline]
+    #[must_use]
+    pub fn release(self) -> I2C { self.i2c }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// An error that can occur when using the ES7210 driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Es7210Error<E> {
+    /// The input type is not supported by the channel pair.
+    UnsupportedInput(ChannelPair),
+    /// I2C bus error.
+    I2C(E),
+}
+
+/// The result of probing every ES7210 address with [`probe_all`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ProbeResult {
+    statuses: [ProbeStatus; 4],
+}
+
+impl ProbeResult {
+    /// Get the [`ProbeStatus`] of an address.
+    ///
+    /// Returns `None` if the address is not one of [`ADDRESSES`].
+    #[must_use]
+    pub const fn status(&self, address: u8) -> Option<ProbeStatus> {
+        match address {
+            0x40..=0x43 => Some(self.statuses[(address - 0x40) as usize]),
+            _ => None,
+        }
+    }
+
+    /// Iterate over every address and its [`ProbeStatus`].
+    pub fn iter(&self) -> impl Iterator<Item = (u8, ProbeStatus)> + '_ {
+        ADDRESSES.into_iter().zip(self.statuses)
+    }
+
+    /// Iterate over the addresses of every ES7210 found.
+    pub fn found(&self) -> impl Iterator<Item = u8> + '_ {
+        self.iter()
+            .filter_map(|(address, status)| (status == ProbeStatus::Found).then_some(address))
+    }
+}
+
+/// The status of a single address probed by [`probe_all`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ProbeStatus {
+    /// An ES7210 responded with [`Es7210::CHIP_ID`].
+    Found,
+    /// No device acknowledged the address.
+    NoResponse,
+    /// A device responded with a different chip ID,
+    /// such as an ES7243 populated in place of an ES7210.
+    WrongId(u16),
+}
+
+impl ProbeStatus {
+    /// Classify the result of reading the chip ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I2C error other than a missing acknowledgement.
+    fn from_chip_id<E: Error>(result: Result<u16, Es7210Error<E>>) -> Result<Self, E> {
+        match result {
+            Ok(Es7210::<(), mode::Blocking>::CHIP_ID) => Ok(Self::Found),
+            Ok(id) => Ok(Self::WrongId(id)),
+            Err(Es7210Error::I2C(err)) if matches!(err.kind(), ErrorKind::NoAcknowledge(_)) => {
+                Ok(Self::NoResponse)
+            }
+            Err(Es7210Error::I2C(err)) => Err(err),
+            Err(Es7210Error::UnsupportedInput(_)) => {
+                unreachable!("Not returned when reading the chip ID")
+            }
+        }
+    }
+}
+
+/// A pair of microphone inputs that share a bias and power domain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChannelPair {
+    /// Inputs `MIC1` and `MIC2`.
+    Mic12,
+    /// Inputs `MIC3` and `MIC4`.
+    Mic34,
+}
+
+impl ChannelPair {
+    /// Returns `true` if the pair can take a PDM microphone.
+    ///
+    /// Only `MIC1` and `MIC2` can be driven by the digital microphone
+    /// interface, with one microphone on each edge of the PDM clock.
+    #[inline]
+    #[must_use]
+    pub const fn supports_pdm(self) -> bool { matches!(self, Self::Mic12) }
+
+    /// The gain registers of both inputs.
+    const fn gain_registers(self) -> [u8; 2] {
+        match self {
+            Self::Mic12 => [register::ES7210_MIC1_GAIN, register::ES7210_MIC2_GAIN],
+            Self::Mic34 => [register::ES7210_MIC3_GAIN, register::ES7210_MIC4_GAIN],
+        }
+    }
+
+    /// The analog power register of the pair.
+    const fn power_register(self) -> u8 {
+        match self {
+            Self::Mic12 => register::ES7210_MIC12_POWER,
+            Self::Mic34 => register::ES7210_MIC34_POWER,
+        }
+    }
+}
+
+/// A single microphone input and its ADC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Channel {
+    /// Input `MIC1`.
+    Mic1,
+    /// Input `MIC2`.
+    Mic2,
+    /// Input `MIC3`.
+    Mic3,
+    /// Input `MIC4`.
+    Mic4,
+}
+
+impl Channel {
+    /// Every channel, in order.
+    pub const ALL: [Self; 4] = [Self::Mic1, Self::Mic2, Self::Mic3, Self::Mic4];
+
+    /// The gain register of the input.
+    const fn gain_register(self) -> u8 {
+        match self {
+            Self::Mic1 => register::ES7210_MIC1_GAIN,
+            Self::Mic2 => register::ES7210_MIC2_GAIN,
+            Self::Mic3 => register::ES7210_MIC3_GAIN,
+            Self::Mic4 => register::ES7210_MIC4_GAIN,
+        }
+    }
+
+    /// The digital volume register of the ADC.
+    const fn volume_register(self) -> u8 {
+        match self {
+            Self::Mic1 => register::ES7210_ADC1_VOLUME,
+            Self::Mic2 => register::ES7210_ADC2_VOLUME,
+            Self::Mic3 => register::ES7210_ADC3_VOLUME,
+            Self::Mic4 => register::ES7210_ADC4_VOLUME,
+        }
+    }
+}
+
+/// The gain of a microphone PGA.
+#[repr(u8)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MicGain {
+    /// 0 dB.
+    #[default]
+    Db0 = 0x0,
+    /// 3 dB.
+    Db3 = 0x1,
+    /// 6 dB.
+    Db6 = 0x2,
+    /// 9 dB.
+    Db9 = 0x3,
+    /// 12 dB.
+    Db12 = 0x4,
+    /// 15 dB.
+    Db15 = 0x5,
+    /// 18 dB.
+    Db18 = 0x6,
+    /// 21 dB.
+    Db21 = 0x7,
+    /// 24 dB.
+    Db24 = 0x8,
+    /// 27 dB.
+    Db27 = 0x9,
+    /// 30 dB.
+    Db30 = 0xA,
+    /// 33 dB.
+    Db33 = 0xB,
+    /// 34.5 dB.
+    Db34_5 = 0xC,
+    /// 36 dB.
+    Db36 = 0xD,
+    /// 37.5 dB.
+    Db37_5 = 0xE,
+}
+
+impl MicGain {
+    /// Every gain, from lowest to highest.
+    pub const ALL: [Self; 15] = [
+        Self::Db0,
+        Self::Db3,
+        Self::Db6,
+        Self::Db9,
+        Self::Db12,
+        Self::Db15,
+        Self::Db18,
+        Self::Db21,
+        Self::Db24,
+        Self::Db27,
+        Self::Db30,
+        Self::Db33,
+        Self::Db34_5,
+        Self::Db36,
+        Self::Db37_5,
+    ];
+    /// The gain bits of a `MICx_GAIN` register.
+    const MASK: u8 = 0x0F;
+
+    /// Get the gain in 0.5 dB steps.
+    #[must_use]
+    pub const fn half_db(self) -> u8 {
+        match self {
+            Self::Db34_5 => 69,
+            Self::Db36 => 72,
+            Self::Db37_5 => 75,
+            _ => self as u8 * 6,
+        }
+    }
+
+    /// Read the gain from a `MICx_GAIN` register,
+    /// saturating reserved values to the highest gain.
+    const fn from_register(value: u8) -> Self {
+        let code = (value & Self::MASK) as usize;
+        if code < Self::ALL.len() { Self::ALL[code] } else { Self::Db37_5 }
+    }
+
+    /// Update a `MICx_GAIN` register with this gain, keeping the other bits.
+    const fn to_register(self, current: u8) -> u8 { current & !Self::MASK | self as u8 }
+}
+
+/// The type of microphone connected to a [`ChannelPair`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InputType {
+    /// Analog microphones, amplified by the PGAs.
+    #[default]
+    Analog,
+    /// PDM digital microphones.
+    Pdm {
+        /// The PDM clock rate, relative to the sample rate.
+        clock_ratio: PdmClockRatio,
+    },
+}
+
+/// The PDM clock rate, relative to the sample rate (`LRCK`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PdmClockRatio {
+    /// A PDM clock of 64 times the sample rate.
+    #[default]
+    X64,
+    /// A PDM clock of 128 times the sample rate.
+    X128,
+}
+
+bitflags! {
+    /// The `DMIC_CONTROL` register.
+    #[cfg_attr(not(feature = "defmt"), derive(Debug, Clone, Copy, PartialEq, Eq))]
+    struct DmicControl: u8 {
+        /// Use a PDM clock of 128 times the sample rate.
+        const CLOCK_RATIO_128 = 0b1000_0000;
+        /// Take `ADC1` and `ADC2` from the PDM input.
+        const DMIC12_ENABLE = 0b0000_0001;
+    }
+}
+
+impl DmicControl {
+    /// Update the register for the input type of `MIC1` and `MIC2`.
+    const fn with_input(self, input: InputType) -> Self {
+        match input {
+            InputType::Analog => self.difference(Self::DMIC12_ENABLE),
+            InputType::Pdm { clock_ratio: PdmClockRatio::X64 } => {
+                self.union(Self::DMIC12_ENABLE).difference(Self::CLOCK_RATIO_128)
+            }
+            InputType::Pdm { clock_ratio: PdmClockRatio::X128 } => {
+                self.union(Self::DMIC12_ENABLE).union(Self::CLOCK_RATIO_128)
+            }
+        }
+    }
+
+    /// Get the input type of `MIC1` and `MIC2`.
+    const fn input(self) -> InputType {
+        if !self.contains(Self::DMIC12_ENABLE) {
+            InputType::Analog
+        } else if self.contains(Self::CLOCK_RATIO_128) {
+            InputType::Pdm { clock_ratio: PdmClockRatio::X128 }
+        } else {
+            InputType::Pdm { clock_ratio: PdmClockRatio::X64 }
+        }
+    }
+}
+
+/// Enables the PGA in a `MICx_GAIN` register.
+const PGA_ENABLE: u8 = 0b0001_0000;
+/// Powers up both inputs in a `MICxx_POWER` register.
+const PAIR_POWER_UP: u8 = 0x00;
+/// Powers down both inputs in a `MICxx_POWER` register.
+const PAIR_POWER_DOWN: u8 = 0xFF;
+/// Gates every clock in the `CLOCK_OFF` register.
+const CLOCK_GATE_ALL: u8 = 0x7F;
+/// Runs every clock in the `CLOCK_OFF` register.
+const CLOCK_RUN_ALL: u8 = 0x00;
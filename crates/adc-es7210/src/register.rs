@@ -0,0 +1,51 @@
+//! All documented registers from the ES7210 datasheet.
+#![expect(dead_code, reason = "Not all register values are used")]
+
+macro_rules! register {
+    ($($ident:ident: $addr:expr,)+) => {
+        $(pub(super) const $ident: u8 = $addr;)+
+    };
+}
+
+register! {
+    ES7210_RESET: 0x00,
+    ES7210_CLOCK_OFF: 0x01,
+    ES7210_MAIN_CLOCK: 0x02,
+    ES7210_MASTER_CLOCK: 0x03,
+    ES7210_LRCK_DIVIDER_HIGH: 0x04,
+    ES7210_LRCK_DIVIDER_LOW: 0x05,
+    ES7210_POWER_DOWN: 0x06,
+    ES7210_OSR: 0x07,
+    ES7210_MODE_CONFIG: 0x08,
+    ES7210_TIME_CONTROL0: 0x09,
+    ES7210_TIME_CONTROL1: 0x0A,
+    ES7210_DMIC_CONTROL: 0x10,
+    ES7210_SDP_INTERFACE1: 0x11,
+    ES7210_SDP_INTERFACE2: 0x12,
+    ES7210_ADC_AUTOMUTE: 0x13,
+    ES7210_ADC34_MUTE_RANGE: 0x14,
+    ES7210_ADC4_VOLUME: 0x1B,
+    ES7210_ADC3_VOLUME: 0x1C,
+    ES7210_ADC2_VOLUME: 0x1D,
+    ES7210_ADC1_VOLUME: 0x1E,
+    ES7210_ADC34_HPF2: 0x20,
+    ES7210_ADC34_HPF1: 0x21,
+    ES7210_ADC12_HPF1: 0x22,
+    ES7210_ADC12_HPF2: 0x23,
+    ES7210_CHIP_ID1: 0x3D,
+    ES7210_CHIP_ID0: 0x3E,
+    ES7210_CHIP_VERSION: 0x3F,
+    ES7210_ANALOG: 0x40,
+    ES7210_MIC12_BIAS: 0x41,
+    ES7210_MIC34_BIAS: 0x42,
+    ES7210_MIC1_GAIN: 0x43,
+    ES7210_MIC2_GAIN: 0x44,
+    ES7210_MIC3_GAIN: 0x45,
+    ES7210_MIC4_GAIN: 0x46,
+    ES7210_MIC1_POWER: 0x47,
+    ES7210_MIC2_POWER: 0x48,
+    ES7210_MIC3_POWER: 0x49,
+    ES7210_MIC4_POWER: 0x4A,
+    ES7210_MIC12_POWER: 0x4B,
+    ES7210_MIC34_POWER: 0x4C,
+}
@@ -0,0 +1,98 @@
+//! Mock buses for examples and tests, without a device.
+//!
+//! Enabled with the `test-util` feature.
+
+use alloc::vec::Vec;
+use core::{
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use embedded_hal::i2c::{self, ErrorKind, ErrorType, Operation};
+use embedded_hal_async::i2c as i2c_async;
+
+/// A bus holding the registers of a single device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registers {
+    /// The registers, indexed by address.
+    pub registers: [u8; 256],
+    /// Every register write, as a register and its value.
+    pub writes: Vec<(u8, u8)>,
+    /// The first register and length of every read.
+    pub reads: Vec<(u8, usize)>,
+    /// The value `CLOCK_OFF` is stuck at, overriding every write,
+    /// as after a clock fault the device cannot recover from.
+    pub clock_off: Option<u8>,
+    pointer: u8,
+}
+
+impl Registers {
+    /// The `CLOCK_OFF` register.
+    const CLOCK_OFF: usize = 0x01;
+
+    /// Create a new [`Registers`] with the given register values.
+    #[must_use]
+    pub const fn new(registers: [u8; 256]) -> Self {
+        Self { registers, writes: Vec::new(), reads: Vec::new(), clock_off: None, pointer: 0 }
+    }
+
+    /// Forget every recorded write and read.
+    pub fn clear(&mut self) {
+        self.writes.clear();
+        self.reads.clear();
+    }
+
+    /// Apply the operations of a transaction.
+    fn apply(&mut self, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        for op in ops {
+            match op {
+                Operation::Write([register]) => self.pointer = *register,
+                Operation::Write([register, value]) => {
+                    self.registers[usize::from(*register)] = *value;
+                    self.writes.push((*register, *value));
+                }
+                Operation::Read(buf) => {
+                    if let Some(clock_off) = self.clock_off {
+                        self.registers[Self::CLOCK_OFF] = clock_off;
+                    }
+                    let start = usize::from(self.pointer);
+                    buf.copy_from_slice(&self.registers[start..start + buf.len()]);
+                    self.reads.push((self.pointer, buf.len()));
+                }
+                Operation::Write(_) => return Err(ErrorKind::Other),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Registers {
+    fn default() -> Self { Self::new([0; 256]) }
+}
+
+impl ErrorType for Registers {
+    type Error = ErrorKind;
+}
+
+impl i2c::I2c for Registers {
+    fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.apply(ops)
+    }
+}
+
+impl i2c_async::I2c for Registers {
+    async fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.apply(ops)
+    }
+}
+
+/// Run a future to completion, polling it until it is ready.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
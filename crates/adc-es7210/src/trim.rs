@@ -0,0 +1,137 @@
+//! Per-unit microphone gain trims.
+
+use crate::{Channel, MicGain};
+
+/// Per-channel gain offsets, measured at the end of line to compensate for
+/// the sensitivity spread of the microphones.
+///
+/// Offsets are in 0.5 dB steps and are added to the nominal gain set with
+/// `Es7210::set_mic_gain`. The trim can be stored with
+/// [`GainTrim::to_bytes`] and loaded again at boot with
+/// [`GainTrim::from_bytes`].
+///
+/// # Example
+///
+/// ```rust
+/// use ef_es7210::{Channel, GainTrim};
+///
+/// // MIC1 is 1.5 dB too quiet, MIC3 is 1 dB too loud
+/// let trim = GainTrim::new([3, 0, -2, 0]);
+/// assert_eq!(trim.offset(Channel::Mic1), 3);
+///
+/// let bytes = trim.to_bytes();
+/// assert_eq!(GainTrim::from_bytes(&bytes), Some(trim));
+///
+/// // Corrupted or erased storage is rejected
+/// let mut corrupted = bytes;
+/// corrupted[2] ^= 0x01;
+/// assert_eq!(GainTrim::from_bytes(&corrupted), None);
+/// assert_eq!(GainTrim::from_bytes(&[0xFF; GainTrim::BYTES]), None);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GainTrim {
+    offsets: [i8; 4],
+}
+
+impl GainTrim {
+    /// The length of a serialized [`GainTrim`].
+    pub const BYTES: usize = 6;
+    /// The version of the serialized format.
+    const VERSION: u8 = 1;
+    /// A trim that leaves every channel at its nominal gain.
+    pub const ZERO: Self = Self { offsets: [0; 4] };
+
+    /// Create a [`GainTrim`] from the offsets of `MIC1` to `MIC4`,
+    /// in 0.5 dB steps.
+    #[inline]
+    #[must_use]
+    pub const fn new(offsets: [i8; 4]) -> Self { Self { offsets } }
+
+    /// Get the offset of a channel, in 0.5 dB steps.
+    #[inline]
+    #[must_use]
+    pub const fn offset(&self, channel: Channel) -> i8 { self.offsets[channel as usize] }
+
+    /// Get the offsets of `MIC1` to `MIC4`, in 0.5 dB steps.
+    #[inline]
+    #[must_use]
+    pub const fn offsets(&self) -> [i8; 4] { self.offsets }
+
+    /// Serialize the trim, followed by a checksum.
+    #[must_use]
+    pub const fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let [a, b, c, d] = self.offsets;
+        let mut bytes = [
+            Self::VERSION,
+            a.cast_unsigned(),
+            b.cast_unsigned(),
+            c.cast_unsigned(),
+            d.cast_unsigned(),
+            0,
+        ];
+        bytes[Self::BYTES - 1] = crc8(&bytes, Self::BYTES - 1);
+        bytes
+    }
+
+    /// Deserialize a trim written by [`GainTrim::to_bytes`].
+    ///
+    /// Returns `None` if the version or checksum does not match,
+    /// such as for erased or corrupted storage.
+    #[must_use]
+    pub const fn from_bytes(bytes: &[u8; Self::BYTES]) -> Option<Self> {
+        if bytes[0] != Self::VERSION || crc8(bytes, Self::BYTES - 1) != bytes[Self::BYTES - 1] {
+            return None;
+        }
+
+        let [_, a, b, c, d, _] = *bytes;
+        Some(Self::new([a.cast_signed(), b.cast_signed(), c.cast_signed(), d.cast_signed()]))
+    }
+}
+
+/// A CRC-8 over the first `len` bytes, with the polynomial `0x07`.
+const fn crc8(bytes: &[u8], len: usize) -> u8 {
+    let mut crc = 0u8;
+    let mut index = 0;
+    while index < len {
+        crc ^= bytes[index];
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x80 != 0 { crc << 1 ^ 0x07 } else { crc << 1 };
+            bit += 1;
+        }
+        index += 1;
+    }
+    crc
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// The register values that apply a nominal gain plus a trim to a channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TrimmedGain {
+    /// The PGA gain, the largest step not above the total gain.
+    pub(crate) pga: MicGain,
+    /// The digital volume, making up the rest of the total gain.
+    pub(crate) volume: u8,
+    /// Whether the total gain was clamped to the PGA range.
+    pub(crate) clamped: bool,
+}
+
+impl TrimmedGain {
+    /// The digital volume register value for 0 dB.
+    const VOLUME_0DB: u8 = 0xBF;
+
+    /// Split `nominal` plus `offset` half-dB steps into a PGA gain and a
+    /// digital volume.
+    pub(crate) fn new(nominal: MicGain, offset: i8) -> Self {
+        let max = MicGain::Db37_5.half_db();
+        let total = i16::from(nominal.half_db()) + i16::from(offset);
+        let clamped = !(0..=i16::from(max)).contains(&total);
+        let total = u8::try_from(total.clamp(0, i16::from(max))).unwrap_or(max);
+
+        let pga =
+            MicGain::ALL.into_iter().rfind(|gain| gain.half_db() <= total).unwrap_or(MicGain::Db0);
+        Self { pga, volume: Self::VOLUME_0DB + (total - pga.half_db()), clamped }
+    }
+}
@@ -0,0 +1,491 @@
+//! Monitoring the clock registers of an ES7210.
+//!
+//! An ES7210 that loses its clocks, or is reset by a brown-out, stops
+//! producing samples while the I2S bus keeps running. A [`ClockWatchdog`]
+//! reads the clock and power registers at a fixed interval, compares them
+//! against the configuration the device was started with, and restores
+//! them when they change.
+
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+
+use crate::{Es7210, mode::Async};
+
+/// The clock and power registers of an ES7210, from `0x01` to `0x06`.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_es7210::{ClockStatus, Es7210Fault};
+///
+/// let expected = ClockStatus::from_bytes([0x20, 0xC1, 0x00, 0x01, 0x00, 0x00]);
+/// assert_eq!(expected.lrck_divider, 0x0100);
+/// assert_eq!(expected.to_bytes(), [0x20, 0xC1, 0x00, 0x01, 0x00, 0x00]);
+/// assert_eq!(expected.check(&expected), None);
+///
+/// // The device was reset, restoring the register defaults.
+/// let reset = ClockStatus::from_bytes([0x3F, 0x00, 0x00, 0x00, 0x00, 0x00]);
+/// assert_eq!(reset.check(&expected), Some(Es7210Fault::ConfigLost));
+///
+/// // The ADC clocks were gated.
+/// let gated = ClockStatus { clock_off: 0x7F, ..expected };
+/// assert_eq!(gated.check(&expected), Some(Es7210Fault::ClocksGated { clocks: 0x5F }));
+///
+/// // The analog circuits were powered down.
+/// let powered_down = ClockStatus { power_down: 0x07, ..expected };
+/// assert_eq!(powered_down.check(&expected), Some(Es7210Fault::PoweredDown));
+///
+/// // Releasing more clocks than expected is not a fault.
+/// let running = ClockStatus { clock_off: 0x00, ..expected };
+/// assert_eq!(running.check(&expected), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClockStatus {
+    /// The clock gating register, where a set bit stops a clock.
+    pub clock_off: u8,
+    /// The main clock divider register.
+    pub main_clock: u8,
+    /// The master clock register.
+    pub master_clock: u8,
+    /// The LRCK divider, from its high and low registers.
+    pub lrck_divider: u16,
+    /// The power down register.
+    pub power_down: u8,
+}
+
+impl ClockStatus {
+    /// The number of registers in a [`ClockStatus`].
+    pub const LEN: usize = 6;
+
+    /// Create a [`ClockStatus`] from the registers `0x01` to `0x06`.
+    #[must_use]
+    pub const fn from_bytes(bytes: [u8; Self::LEN]) -> Self {
+        let [clock_off, main_clock, master_clock, lrck_high, lrck_low, power_down] = bytes;
+        Self {
+            clock_off,
+            main_clock,
+            master_clock,
+            lrck_divider: u16::from_be_bytes([lrck_high, lrck_low]),
+            power_down,
+        }
+    }
+
+    /// Get the registers `0x01` to `0x06` of this [`ClockStatus`].
+    #[must_use]
+    pub const fn to_bytes(&self) -> [u8; Self::LEN] {
+        let [lrck_high, lrck_low] = self.lrck_divider.to_be_bytes();
+        [self.clock_off, self.main_clock, self.master_clock, lrck_high, lrck_low, self.power_down]
+    }
+
+    /// Compare this [`ClockStatus`] against the expected one.
+    ///
+    /// Changed dividers mean the configuration was lost, usually by a reset,
+    /// and take priority over gated clocks and power.
+    #[must_use]
+    pub const fn check(&self, expected: &Self) -> Option<Es7210Fault> {
+        let gated = self.clock_off & !expected.clock_off;
+        if self.main_clock != expected.main_clock
+            || self.master_clock != expected.master_clock
+            || self.lrck_divider != expected.lrck_divider
+        {
+            Some(Es7210Fault::ConfigLost)
+        } else if gated != 0 {
+            Some(Es7210Fault::ClocksGated { clocks: gated })
+        } else if self.power_down != expected.power_down {
+            Some(Es7210Fault::PoweredDown)
+        } else {
+            None
+        }
+    }
+}
+
+/// A fault detected by a [`ClockWatchdog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Es7210Fault {
+    /// The device did not respond to the status read.
+    NoResponse,
+    /// The clock dividers changed, usually because the device was reset.
+    ConfigLost,
+    /// Clocks that should be running were gated.
+    ClocksGated {
+        /// The clocks that were gated, as bits of the clock gating register.
+        clocks: u8,
+    },
+    /// The power down register changed.
+    PoweredDown,
+}
+
+/// An event reported by a [`ClockWatchdog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WatchdogEvent {
+    /// A fault was seen on enough consecutive reads to be confirmed.
+    ///
+    /// The watchdog restores the registers after reporting it.
+    Fault(Es7210Fault),
+    /// The registers matched again after a confirmed fault.
+    Recovered {
+        /// The number of times the registers were restored.
+        attempts: u8,
+    },
+    /// The fault remained after every recovery attempt.
+    ///
+    /// The watchdog stops monitoring until it is reset.
+    RecoveryFailed(Es7210Fault),
+}
+
+/// The configuration of a [`ClockWatchdog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WatchdogConfig {
+    /// The time between two status reads, in milliseconds.
+    pub interval_ms: u32,
+    /// The number of consecutive faulty reads needed to confirm a fault.
+    ///
+    /// A value of `0` is treated as `1`, which confirms every fault.
+    pub debounce: u8,
+    /// The number of times the registers are restored before giving up.
+    pub max_recoveries: u8,
+}
+
+impl WatchdogConfig {
+    /// A configuration reading every 100 ms, confirming a fault after 3 reads
+    /// and restoring the registers up to 3 times.
+    pub const DEFAULT: Self = Self { interval_ms: 100, debounce: 3, max_recoveries: 3 };
+
+    /// Set the time between two status reads, in milliseconds.
+    #[inline]
+    #[must_use]
+    pub const fn with_interval_ms(mut self, interval_ms: u32) -> Self {
+        self.interval_ms = interval_ms;
+        self
+    }
+
+    /// Set the number of consecutive faulty reads needed to confirm a fault.
+    #[inline]
+    #[must_use]
+    pub const fn with_debounce(mut self, debounce: u8) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Set the number of times the registers are restored before giving up.
+    #[inline]
+    #[must_use]
+    pub const fn with_max_recoveries(mut self, max_recoveries: u8) -> Self {
+        self.max_recoveries = max_recoveries;
+        self
+    }
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// The state of a [`ClockWatchdog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+enum WatchdogState {
+    /// The registers match.
+    Healthy,
+    /// A fault was seen on `count` consecutive reads.
+    Suspect { count: u8 },
+    /// A confirmed fault, with the registers restored `attempts` times.
+    Recovering { attempts: u8 },
+    /// Every recovery attempt failed.
+    Failed(Es7210Fault),
+}
+
+/// What a [`ClockWatchdog`] does after a status read.
+enum Step {
+    /// Wait for the next read.
+    Wait,
+    /// Restore the registers.
+    Recover,
+}
+
+/// A watchdog over the clock and power registers of an ES7210.
+///
+/// Each tick reads the registers with a single [`Es7210::clock_status`] and
+/// compares them against the expected [`ClockStatus`]. A fault must be seen
+/// on [`WatchdogConfig::debounce`] consecutive ticks to be confirmed, after
+/// which the registers are restored with [`Es7210::restore_clocks`] on every
+/// tick until they match, up to [`WatchdogConfig::max_recoveries`] times.
+///
+/// Without an expected status, the first tick reads it from the device,
+/// so only start the watchdog after the device is configured and started.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_es7210::{
+///     ClockWatchdog, Es7210, Es7210Fault, WatchdogConfig, WatchdogEvent, mode::Async,
+/// };
+/// use embedded_hal_async::i2c::ErrorKind;
+/// # use ef_es7210::test_util::{Registers, block_on};
+///
+/// # block_on(async {
+/// let mut registers = [0u8; 256];
+/// registers[0x01..=0x06].copy_from_slice(&[0x20, 0xC1, 0x00, 0x01, 0x00, 0x00]);
+/// let mut es7210 = Es7210::<_, Async>::new(Registers::new(registers), 0x40);
+///
+/// let mut events = Vec::new();
+/// let mut watchdog = ClockWatchdog::new(WatchdogConfig::DEFAULT);
+///
+/// // The first tick reads the expected status.
+/// assert_eq!(watchdog.tick(&mut es7210, |event| events.push(event)).await, None);
+/// assert_eq!(watchdog.expected().unwrap().clock_off, 0x20);
+///
+/// // A transient fault is not reported.
+/// es7210.i2c_mut().registers[0x01] = 0x7F;
+/// watchdog.tick(&mut es7210, |event| events.push(event)).await;
+/// es7210.i2c_mut().registers[0x01] = 0x20;
+/// watchdog.tick(&mut es7210, |event| events.push(event)).await;
+/// assert!(events.is_empty());
+///
+/// // A fault seen on three consecutive reads is reported and recovered.
+/// es7210.i2c_mut().registers[0x01] = 0x7F;
+/// for _ in 0..4 {
+///     watchdog.tick(&mut es7210, |event| events.push(event)).await;
+/// }
+/// assert_eq!(
+///     events,
+///     [
+///         WatchdogEvent::Fault(Es7210Fault::ClocksGated { clocks: 0x5F }),
+///         WatchdogEvent::Recovered { attempts: 1 },
+///     ]
+/// );
+/// assert_eq!(es7210.i2c_mut().registers[0x01], 0x20);
+///
+/// // A fault that returns after every recovery is permanent.
+/// events.clear();
+/// es7210.i2c_mut().clock_off = Some(0x7F);
+/// let fault = loop {
+///     if let Some(fault) = watchdog.tick(&mut es7210, |event| events.push(event)).await {
+///         break fault;
+///     }
+/// };
+/// assert_eq!(fault, Es7210Fault::ClocksGated { clocks: 0x5F });
+/// assert_eq!(
+///     events,
+///     [
+///         WatchdogEvent::Fault(Es7210Fault::ClocksGated { clocks: 0x5F }),
+///         WatchdogEvent::RecoveryFailed(Es7210Fault::ClocksGated { clocks: 0x5F }),
+///     ]
+/// );
+/// assert_eq!(watchdog.failed(), Some(fault));
+/// # });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClockWatchdog {
+    config: WatchdogConfig,
+    expected: Option<ClockStatus>,
+    state: WatchdogState,
+}
+
+impl ClockWatchdog {
+    /// Create a new [`ClockWatchdog`] that reads the expected status
+    /// on its first tick.
+    #[must_use]
+    pub const fn new(config: WatchdogConfig) -> Self {
+        Self { config, expected: None, state: WatchdogState::Healthy }
+    }
+
+    /// Create a new [`ClockWatchdog`] with an expected status.
+    #[must_use]
+    pub const fn with_expected(config: WatchdogConfig, expected: ClockStatus) -> Self {
+        Self { config, expected: Some(expected), state: WatchdogState::Healthy }
+    }
+
+    /// Get the [`WatchdogConfig`].
+    #[inline]
+    #[must_use]
+    pub const fn config(&self) -> &WatchdogConfig { &self.config }
+
+    /// Get the expected status, if it is known.
+    #[inline]
+    #[must_use]
+    pub const fn expected(&self) -> Option<&ClockStatus> { self.expected.as_ref() }
+
+    /// Set the expected status.
+    ///
+    /// Call this after reconfiguring the device, or pass `None` to read
+    /// the expected status again on the next tick.
+    #[inline]
+    pub const fn set_expected(&mut self, expected: Option<ClockStatus>) {
+        self.expected = expected;
+    }
+
+    /// Get the permanent fault, if every recovery attempt failed.
+    #[must_use]
+    pub const fn failed(&self) -> Option<Es7210Fault> {
+        match self.state {
+            WatchdogState::Failed(fault) => Some(fault),
+            _ => None,
+        }
+    }
+
+    /// Reset the watchdog, resuming monitoring after a permanent fault
+    /// with the same expected status.
+    #[inline]
+    pub const fn reset(&mut self) { self.state = WatchdogState::Healthy; }
+
+    /// Read the status once, reporting confirmed faults and recoveries
+    /// to `on_event`, and restore the registers if needed.
+    ///
+    /// Returns the fault once every recovery attempt has failed,
+    /// without reading the status again until [`ClockWatchdog::reset`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancelling while the registers are restored may leave them partially
+    /// written, which the next tick detects and restores again.
+    pub async fn tick<I2C: I2c>(
+        &mut self,
+        es7210: &mut Es7210<I2C, Async>,
+        mut on_event: impl FnMut(WatchdogEvent),
+    ) -> Option<Es7210Fault> {
+        if let WatchdogState::Failed(fault) = self.state {
+            return Some(fault);
+        }
+
+        let fault = match (es7210.clock_status().await, &self.expected) {
+            (Ok(status), Some(expected)) => status.check(expected),
+            (Ok(status), None) => {
+                self.expected = Some(status);
+                None
+            }
+            (Err(_), _) => Some(Es7210Fault::NoResponse),
+        };
+
+        if let Step::Recover = self.update(fault, &mut on_event)
+            && let Some(expected) = self.expected
+        {
+            // A failed write shows up as a fault on the next tick.
+            let _ = es7210.restore_clocks(&expected).await;
+        }
+        self.failed()
+    }
+
+    /// Monitor the device until every recovery attempt has failed,
+    /// waiting [`WatchdogConfig::interval_ms`] between ticks.
+    ///
+    /// Returns the permanent fault, see [`ClockWatchdog::tick`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_es7210::{ClockWatchdog, Es7210, Es7210Fault, WatchdogConfig, mode::Async};
+    /// use embedded_hal_async::{
+    ///     delay::DelayNs,
+    ///     i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation},
+    /// };
+    /// # use ef_es7210::test_util::block_on;
+    ///
+    /// /// A bus where the device never responds.
+    /// struct Bus;
+    ///
+    /// impl ErrorType for Bus {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl I2c for Bus {
+    ///     async fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+    ///         Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+    ///     }
+    /// }
+    ///
+    /// /// A delay that records the total time waited.
+    /// struct Delay(u32);
+    ///
+    /// impl DelayNs for Delay {
+    ///     async fn delay_ns(&mut self, ns: u32) { self.0 += ns / 1_000_000; }
+    /// }
+    ///
+    /// # block_on(async {
+    /// let mut es7210 = Es7210::<_, Async>::new(Bus, 0x40);
+    /// let mut watchdog = ClockWatchdog::new(WatchdogConfig::DEFAULT.with_max_recoveries(2));
+    ///
+    /// let mut delay = Delay(0);
+    /// let mut faults = 0;
+    /// let fault = watchdog.run(&mut es7210, &mut delay, |_| faults += 1).await;
+    ///
+    /// // Three reads confirm the fault, then one read follows each recovery.
+    /// assert_eq!(fault, Es7210Fault::NoResponse);
+    /// assert_eq!(faults, 2);
+    /// assert_eq!(delay.0, 400);
+    /// # });
+    /// ```
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe, see [`ClockWatchdog::tick`].
+    pub async fn run<I2C: I2c, D: DelayNs>(
+        &mut self,
+        es7210: &mut Es7210<I2C, Async>,
+        delay: &mut D,
+        mut on_event: impl FnMut(WatchdogEvent),
+    ) -> Es7210Fault {
+        loop {
+            if let Some(fault) = self.tick(es7210, &mut on_event).await {
+                return fault;
+            }
+            delay.delay_ms(self.config.interval_ms).await;
+        }
+    }
+
+    /// Advance the state with the fault seen by a status read.
+    fn update(
+        &mut self,
+        fault: Option<Es7210Fault>,
+        on_event: &mut impl FnMut(WatchdogEvent),
+    ) -> Step {
+        let debounce = self.config.debounce.max(1);
+        match (self.state, fault) {
+            (WatchdogState::Healthy | WatchdogState::Suspect { .. }, None) => {
+                self.state = WatchdogState::Healthy;
+                Step::Wait
+            }
+            (WatchdogState::Recovering { attempts }, None) => {
+                self.state = WatchdogState::Healthy;
+                on_event(WatchdogEvent::Recovered { attempts });
+                Step::Wait
+            }
+            (WatchdogState::Healthy, Some(_)) if debounce > 1 => {
+                self.state = WatchdogState::Suspect { count: 1 };
+                Step::Wait
+            }
+            (WatchdogState::Suspect { count }, Some(_)) if count + 1 < debounce => {
+                self.state = WatchdogState::Suspect { count: count + 1 };
+                Step::Wait
+            }
+            (WatchdogState::Healthy | WatchdogState::Suspect { .. }, Some(fault)) => {
+                on_event(WatchdogEvent::Fault(fault));
+                self.recover(fault, 0, on_event)
+            }
+            (WatchdogState::Recovering { attempts }, Some(fault)) => {
+                self.recover(fault, attempts, on_event)
+            }
+            (WatchdogState::Failed(_), _) => Step::Wait,
+        }
+    }
+
+    /// Restore the registers again, or give up after the last attempt.
+    fn recover(
+        &mut self,
+        fault: Es7210Fault,
+        attempts: u8,
+        on_event: &mut impl FnMut(WatchdogEvent),
+    ) -> Step {
+        if attempts < self.config.max_recoveries {
+            self.state = WatchdogState::Recovering { attempts: attempts + 1 };
+            Step::Recover
+        } else {
+            self.state = WatchdogState::Failed(fault);
+            on_event(WatchdogEvent::RecoveryFailed(fault));
+            Step::Wait
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! Benchmarks of formatting commands and data into 9-bit words.
+//!
+//! Run with `cargo +nightly bench -p ef-st7701s --features nightly`.
+#![feature(test)]
+
+extern crate test;
+
+use core::hint::black_box;
+
+use ef_st7701s::{format_command, format_data};
+use test::Bencher;
+
+/// A staging buffer large enough for a few lines of pixels.
+const BUFFER: usize = 4096;
+
+/// `MADCTL` with its single parameter.
+const MADCTL: [u8; 2] = [0x36, 0x00];
+/// `CASET` with a column range of `0..480`.
+const CASET: [u8; 5] = [0x2A, 0x00, 0x00, 0x01, 0xDF];
+/// A short run of pixel data.
+const PIXELS: [u8; 32] = [0xA5; 32];
+
+#[bench]
+fn command_small(b: &mut Bencher) {
+    let mut buffer = [0u8; BUFFER];
+    b.iter(|| {
+        let _ = black_box(format_command(black_box(MADCTL).into_iter(), &mut buffer));
+    });
+}
+
+#[bench]
+fn command_window(b: &mut Bencher) {
+    let mut buffer = [0u8; BUFFER];
+    b.iter(|| {
+        let _ = black_box(format_command(black_box(CASET).into_iter(), &mut buffer));
+    });
+}
+
+#[bench]
+fn data_small(b: &mut Bencher) {
+    let mut buffer = [0u8; BUFFER];
+    b.bytes = PIXELS.len() as u64;
+    b.iter(|| {
+        let _ = black_box(format_data(black_box(PIXELS).into_iter(), &mut buffer));
+    });
+}
+
+#[bench]
+fn data_full(b: &mut Bencher) {
+    // Eight words fill nine bytes
+    let pixels = [0xA5; BUFFER / 9 * 8];
+    let mut buffer = [0u8; BUFFER];
+    b.bytes = pixels.len() as u64;
+    b.iter(|| {
+        let _ = black_box(format_data(black_box(pixels).into_iter(), &mut buffer));
+    });
+}
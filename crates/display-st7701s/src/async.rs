@@ -1,9 +1,18 @@
 use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
-use ef_driver_common::{color::DisplayColor, mode::Async};
-use embedded_hal_async::delay::DelayNs;
+use ef_driver_common::{
+    color::DisplayColor,
+    identify::{AsyncIdentify, DeviceIdentity},
+    mode::Async,
+};
+use embedded_hal_async::{delay::DelayNs, digital::Wait};
 
 use crate::{
-    AddressMode, ColorFormat, CommandDataShifter, St7701s, command, format_command, format_data,
+    Bank, BrightnessConfig, ColorFormat, CommandBatch, CommandDataShifter, DisplayConfig,
+    GammaCurve, INIT_DELAY, InitCommand, InitConfig, Orientation, PanelConfig, St7701s,
+    St7701sError, StateChange, TearingEffect, Words, command, display_command, idle_command,
+    init::InitStep,
+    inversion_command,
+    read::{AsyncReadDataCommand, DisplayStatus, READ_BUFFER, ReadCommand},
 };
 
 impl<C: DisplayColor + ColorFormat, SPI: AsyncWriteOnlyDataCommand, const N: usize>
@@ -11,85 +20,1083 @@ impl<C: DisplayColor + ColorFormat, SPI: AsyncWriteOnlyDataCommand, const N: usi
 {
     /// Initialize the display.
     ///
+    /// Waits the delays of the [`TimingProfile`](crate::TimingProfile) in
+    /// `config` between commands, and keeps it for `sleep` and `wake`.
+    ///
+    /// This is [`St7701s::init_with`] with the default [`InitConfig`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: initialization starts with a software reset,
+    /// so it can be run again from the start.
+    ///
     /// # Errors
     ///
     /// Returns an error if communication with the display fails.
+    #[inline]
     pub async fn init<D: DelayNs>(
         &mut self,
-        madctl: AddressMode,
+        config: DisplayConfig,
         delay: &mut D,
-    ) -> Result<(), DisplayError> {
-        // Software reset
-        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_SOFT_RESET])).await?;
-        delay.delay_ms(150).await; // 150 ms
+    ) -> Result<(), St7701sError> {
+        self.init_with(&InitConfig::new(config), delay).await
+    }
+
+    /// Initialize the display with the delays and command order of an
+    /// [`InitConfig`].
+    ///
+    /// Keeps the [`DisplayConfig`] of `config`, including its timing for
+    /// `sleep` and `wake`.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: initialization starts with a software reset,
+    /// so it can be run again from the start.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a delay of the extra command sequence is longer
+    /// than four bytes, or if communication with the display fails.
+    pub async fn init_with<D: DelayNs>(
+        &mut self,
+        config: &InitConfig<'_>,
+        delay: &mut D,
+    ) -> Result<(), St7701sError> {
+        for step in config.steps() {
+            match step {
+                InitStep::Command(cmd, ms) => {
+                    self.spi.send_commands(DataFormat::U8(&[cmd])).await?;
+                    delay.delay_ms(ms).await;
+                }
+                InitStep::Extra(sequence) => self.run_init_sequence(sequence, delay).await?,
+                InitStep::Configure(ms) => {
+                    let mut batch = self.spi.batch();
+                    for (bytes, len) in config.commands::<C>() {
+                        batch.command_async(&bytes[..len]).await?;
+                    }
+                    batch.flush_async().await?;
+                    delay.delay_ms(ms).await;
+                }
+            }
+        }
+
+        self.state.apply(StateChange::Initialized(*config.display_config()));
+        Ok(())
+    }
+
+    /// Enter sleep mode, turning off the panel's DC/DC converter and
+    /// oscillator.
+    ///
+    /// Waits the `sleep_in` delay of the
+    /// [`TimingProfile`](crate::TimingProfile) before the next command, 5
+    /// ms by default. The panel must stay asleep for 120 ms before
+    /// [`St7701s::wake`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the panel may enter sleep mode before the driver
+    /// records it, and sending the command again is harmless.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn sleep<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_SLEEP_ENTER])).await?;
+        delay.delay_ms(self.state.config.timing.sleep_in).await;
+        self.state.apply(StateChange::Asleep(true));
+        Ok(())
+    }
 
-        // Exit sleep mode
+    /// Exit sleep mode.
+    ///
+    /// Waits the `wake` delay of the [`TimingProfile`](crate::TimingProfile)
+    /// for the panel to stabilize, 120 ms by default.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the panel may wake before the driver records it,
+    /// and the wake wait is repeated by the next call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn wake<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), St7701sError> {
         self.spi.send_commands(DataFormat::U8(&[command::ST7701S_SLEEP_EXIT])).await?;
-        delay.delay_ms(150).await; // 150 ms
+        delay.delay_ms(self.state.config.timing.wake).await;
+        self.state.apply(StateChange::Asleep(false));
+        Ok(())
+    }
+
+    /// Turn off the display, blanking the panel without clearing its memory.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the display may be off before the driver records it,
+    /// and turning it off again has no visible effect.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn display_off(&mut self) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_DISPLAY_OFF])).await?;
+        self.state.apply(StateChange::DisplayOn(false));
+        Ok(())
+    }
+
+    /// Turn on the display, showing the contents of its memory.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the display may be on before the driver records it,
+    /// and turning it on again has no visible effect.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn display_on(&mut self) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_DISPLAY_ON])).await?;
+        self.state.apply(StateChange::DisplayOn(true));
+        Ok(())
+    }
+
+    /// Blank the display, keeping the image in its memory.
+    ///
+    /// Only the display is turned off, the panel stays awake and keeps its
+    /// configuration, so [`St7701s::unblank`] shows the image again at once.
+    /// Pixels written while blanked are drawn into memory and appear when
+    /// unblanked.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: see [`St7701s::display_off`], which this calls.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    #[inline]
+    pub async fn blank(&mut self) -> Result<(), St7701sError> { self.display_off().await }
+
+    /// Unblank the display, showing the contents of its memory.
+    ///
+    /// The panel must not be asleep, see [`St7701s::wake`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: see [`St7701s::display_on`], which this calls.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    #[inline]
+    pub async fn unblank(&mut self) -> Result<(), St7701sError> { self.display_on().await }
+
+    /// Blank the display if it is not already, then enter sleep mode.
+    ///
+    /// The display is never turned on, so a blanked image is not shown
+    /// again. The display stays blanked after [`St7701s::wake`] until
+    /// [`St7701s::unblank`], allowing the image to be redrawn first.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the display may be blanked but awake,
+    /// and calling this again finishes entering sleep mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn power_down<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), St7701sError> {
+        if self.state.display_on {
+            self.display_off().await?;
+        }
+        self.sleep(delay).await
+    }
 
-        // Set the address mode
+    /// Set whether the colors of the display are inverted.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the colors may be inverted before the driver records
+    /// it, and the command sets the inversion rather than toggling it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn set_inverted(&mut self, inverted: bool) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[inversion_command(inverted)])).await?;
+        self.state.apply(StateChange::Inverted(inverted));
+        Ok(())
+    }
+
+    /// Turn all pixels on, showing white regardless of the display's memory.
+    ///
+    /// The display's memory is kept, and is shown again once the display
+    /// leaves this mode.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: every pixel may already be on, and the command has
+    /// no parameters, so it can be sent again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn all_pixels_on(&mut self) -> Result<(), St7701sError> {
+        Ok(self.spi.send_commands(DataFormat::U8(&[command::ST7701S_ALL_PIXEL_ON])).await?)
+    }
+
+    /// Turn all pixels off, showing black regardless of the display's memory.
+    ///
+    /// The display's memory is kept, and is shown again once the display
+    /// leaves this mode.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: every pixel may already be off, and the driver
+    /// keeps no state for the mode, so it can be sent again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn all_pixels_off(&mut self) -> Result<(), St7701sError> {
+        Ok(self.spi.send_commands(DataFormat::U8(&[command::ST7701S_ALL_PIXEL_OFF])).await?)
+    }
+
+    /// Enter partial mode, only showing the rows from `start_row` to
+    /// `end_row` (inclusive).
+    ///
+    /// Rows outside of the partial area are not refreshed,
+    /// which reduces the power used by the panel.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the commands are padded to a byte boundary,
+    /// so they can be sent again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rows do not fit the panel,
+    /// or if communication with the display fails.
+    pub async fn enter_partial_mode(
+        &mut self,
+        start_row: u16,
+        end_row: u16,
+    ) -> Result<(), St7701sError> {
+        let area = self.partial_area_command(start_row, end_row)?;
+        self.spi.send_commands(DataFormat::U8(&area)).await?;
+        Ok(self.spi.send_commands(DataFormat::U8(&[command::ST7701S_PARTIAL_MODE])).await?)
+    }
+
+    /// Exit partial mode, showing the whole panel again.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the commands are padded to a byte boundary,
+    /// so they can be sent again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn exit_partial_mode(&mut self) -> Result<(), St7701sError> {
+        Ok(self.spi.send_commands(DataFormat::U8(&[command::ST7701S_NORMAL_MODE])).await?)
+    }
+
+    /// Define the vertical scrolling layout of the panel: `top_fixed` rows
+    /// that never scroll, followed by `scroll_area` rows that scroll and
+    /// `bottom_fixed` rows that never scroll.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the command is padded to a byte boundary,
+    /// so it can be sent again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rows do not add up to the panel height,
+    /// or if communication with the display fails.
+    pub async fn define_vertical_scroll(
+        &mut self,
+        top_fixed: u16,
+        scroll_area: u16,
+        bottom_fixed: u16,
+    ) -> Result<(), St7701sError> {
+        let area = self.scroll_area_command(top_fixed, scroll_area, bottom_fixed)?;
+        Ok(self.spi.send_commands(DataFormat::U8(&area)).await?)
+    }
+
+    /// Set the row of the display's memory shown at the top of the scrolling
+    /// area, see [`St7701s::define_vertical_scroll`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the command is padded to a byte boundary,
+    /// so it can be sent again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the row does not fit the panel,
+    /// or if communication with the display fails.
+    pub async fn set_scroll_offset(&mut self, offset: u16) -> Result<(), St7701sError> {
+        let start = self.scroll_start_command(offset)?;
+        Ok(self.spi.send_commands(DataFormat::U8(&start)).await?)
+    }
+
+    /// Enter or exit idle mode.
+    ///
+    /// In idle mode the panel only shows 8 colors, using the most significant
+    /// bit of each channel, and uses less power. The pixel format and the
+    /// display's memory are kept, so exiting idle mode shows the full colors
+    /// again without initializing the display.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: idle mode may change before the driver records it,
+    /// and the command sets the mode rather than toggling it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn set_idle_mode(&mut self, enabled: bool) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[idle_command(enabled)])).await?;
+        self.state.apply(StateChange::Idle(enabled));
+        Ok(())
+    }
+
+    /// Rotate the panel, keeping the color order and refresh directions of
+    /// its address mode.
+    ///
+    /// Coordinates and [`dimensions`](St7701s::dimensions) follow the new
+    /// orientation, the contents of the display's memory are not moved.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the command is padded to a byte boundary,
+    /// so it can be sent again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn set_orientation(&mut self, orientation: Orientation) -> Result<(), St7701sError> {
+        let address_mode = orientation.apply(self.state.config.address_mode);
+        let madctl = [command::ST7701S_SET_ADDRESS_MODE, address_mode.to_byte()];
+        self.spi.send_commands(DataFormat::U8(&madctl)).await?;
+        self.state.apply(StateChange::AddressMode(address_mode));
+        Ok(())
+    }
+
+    /// Set the signal output on the tearing effect (TE) pin.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the command is padded to a byte boundary,
+    /// so it can be sent again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn set_tearing_effect(&mut self, mode: TearingEffect) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(mode.command())).await?;
+        self.state.apply(StateChange::TearingEffect(mode));
+        Ok(())
+    }
+
+    /// Wait for the start of the next vertical blanking period,
+    /// signalled on the tearing effect (TE) pin connected to `te`.
+    ///
+    /// Writing pixels right after this returns does not show a partially
+    /// updated frame, as long as the write finishes before the panel
+    /// starts reading the rows that are written.
+    ///
+    /// The tearing effect output must be set to
+    /// [`TearingEffect::VBlankOnly`], otherwise the pin either never rises
+    /// or also rises for every row.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe, as long as the pin's wait is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if waiting on the pin fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+    /// use ef_st7701s::{St7701s, TearingEffect, color::Rgb565, mode::Async};
+    /// use embedded_hal::digital::{ErrorKind, ErrorType};
+    /// use embedded_hal_async::digital::Wait;
+    /// # use ef_st7701s::test_util::block_on;
+    ///
+    /// struct Interface;
+    ///
+    /// impl AsyncWriteOnlyDataCommand for Interface {
+    ///     async fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+    ///
+    ///     async fn send_data(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+    /// }
+    ///
+    /// /// A TE pin that counts the rising edges waited for.
+    /// #[derive(Default)]
+    /// struct TePin(usize);
+    ///
+    /// impl ErrorType for TePin {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl Wait for TePin {
+    ///     async fn wait_for_high(&mut self) -> Result<(), ErrorKind> { unimplemented!() }
+    ///
+    ///     async fn wait_for_low(&mut self) -> Result<(), ErrorKind> { unimplemented!() }
+    ///
+    ///     async fn wait_for_rising_edge(&mut self) -> Result<(), ErrorKind> {
+    ///         self.0 += 1;
+    ///         Ok(())
+    ///     }
+    ///
+    ///     async fn wait_for_falling_edge(&mut self) -> Result<(), ErrorKind> { unimplemented!() }
+    ///
+    ///     async fn wait_for_any_edge(&mut self) -> Result<(), ErrorKind> { unimplemented!() }
+    /// }
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Async, 16>::new(Interface);
+    /// let mut te = TePin::default();
+    ///
+    /// block_on(async {
+    ///     display.set_tearing_effect(TearingEffect::VBlankOnly).await.unwrap();
+    ///
+    ///     // Draw each frame during vertical blanking
+    ///     for _ in 0..3 {
+    ///         display.wait_for_vsync(&mut te).await.unwrap();
+    ///         display.write_pixels(&[0x00; 6]).await.unwrap();
+    ///     }
+    /// });
+    /// assert_eq!(te.0, 3);
+    /// ```
+    pub async fn wait_for_vsync<TE: Wait>(&self, te: &mut TE) -> Result<(), TE::Error> {
+        debug_assert!(
+            self.state.tearing_effect == TearingEffect::VBlankOnly,
+            "the tearing effect output must only signal vertical blanking"
+        );
+        te.wait_for_rising_edge().await
+    }
+
+    /// Send a command with its parameters.
+    ///
+    /// This allows sending commands the driver does not support directly,
+    /// such as those of a panel vendor's initialization sequence.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the command is padded to a byte boundary,
+    /// so it can be sent again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn send_command(&mut self, cmd: u8, params: &[u8]) -> Result<(), St7701sError> {
+        let mut bytes = core::iter::once(cmd).chain(params.iter().copied());
+        Ok(self.spi.send_commands(DataFormat::U8Iter(&mut bytes)).await?)
+    }
+
+    /// Send a sequence of commands with their parameters,
+    /// such as a panel vendor's initialization sequence.
+    ///
+    /// Entries with the [`INIT_DELAY`] command and parameters wait for their
+    /// parameters in milliseconds, as a big-endian number of up to four bytes.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: every command is padded to a byte boundary,
+    /// so the sequence can be run again from the start.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a delay is longer than four bytes,
+    /// or if communication with the display fails.
+    pub async fn run_init_sequence<D: DelayNs>(
+        &mut self,
+        seq: &[(u8, &[u8])],
+        delay: &mut D,
+    ) -> Result<(), St7701sError> {
+        for &(cmd, params) in seq {
+            match cmd {
+                INIT_DELAY => match Self::init_delay(params)? {
+                    Some(ms) => delay.delay_ms(ms).await,
+                    None => self.send_command(cmd, params).await?,
+                },
+                _ => self.send_command(cmd, params).await?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Send a sequence of [`InitCommand`]s, such as a panel vendor's
+    /// initialization sequence with delays between commands.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: every command is padded to a byte boundary,
+    /// so the sequence can be run again from the start.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn run_init_commands<D: DelayNs>(
+        &mut self,
+        seq: &[InitCommand],
+        delay: &mut D,
+    ) -> Result<(), St7701sError> {
+        for command in seq {
+            match *command {
+                InitCommand::Command { cmd, params } => self.send_command(cmd, params).await?,
+                InitCommand::DelayMs(ms) => delay.delay_ms(u32::from(ms)).await,
+                InitCommand::DelayUs(us) => delay.delay_us(u32::from(us)).await,
+            }
+        }
+        Ok(())
+    }
+
+    /// Select the [`Bank`] that following commands are written to.
+    ///
+    /// Commands other than Command2 registers need [`Bank::Command1`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the bank may be selected before the call returns,
+    /// and selecting it again has no other effect.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn select_bank(&mut self, bank: Bank) -> Result<(), St7701sError> {
+        Ok(self.spi.send_commands(DataFormat::U8(&bank.select_command())).await?)
+    }
+
+    /// Write the panel-specific configuration to the Command2 banks,
+    /// returning to [`Bank::Command1`] afterwards.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: every register is written in full, so the
+    /// configuration can be applied again. Until then, a Command2 bank may
+    /// still be selected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn apply_panel_config(&mut self, config: &PanelConfig) -> Result<(), St7701sError> {
+        let (timing, gamma) = config.bk0_commands();
+
+        self.spi.send_commands(DataFormat::U8(&Bank::Command2Bk0.select_command())).await?;
+        for cmd in timing {
+            self.spi.send_commands(DataFormat::U8(&cmd)).await?;
+        }
+        for cmd in gamma {
+            self.spi.send_commands(DataFormat::U8(&cmd)).await?;
+        }
+
+        self.spi.send_commands(DataFormat::U8(&Bank::Command2Bk1.select_command())).await?;
+        for cmd in config.bk1_commands() {
+            self.spi.send_commands(DataFormat::U8(&cmd)).await?;
+        }
+
+        self.spi.send_commands(DataFormat::U8(&Bank::Command1.select_command())).await?;
+        let (positive, negative) = (config.positive_gamma, config.negative_gamma);
+        self.state.apply(StateChange::Gamma(GammaCurve::new(positive), GammaCurve::new(negative)));
+        Ok(())
+    }
+
+    /// Write the positive and negative voltage gamma tables to Command2 BK0,
+    /// returning to [`Bank::Command1`] afterwards.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: every register is written in full, so the tables
+    /// can be written again. Until then, a Command2 bank may still be
+    /// selected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn set_gamma(
+        &mut self,
+        positive: &GammaCurve,
+        negative: &GammaCurve,
+    ) -> Result<(), St7701sError> {
+        let change = StateChange::Gamma(*positive, *negative);
+        let positive = positive.command(command::ST7701S_BK0_PVGAMCTRL);
+        let negative = negative.command(command::ST7701S_BK0_NVGAMCTRL);
+
+        self.spi.send_commands(DataFormat::U8(&Bank::Command2Bk0.select_command())).await?;
+        self.spi.send_commands(DataFormat::U8(&positive)).await?;
+        self.spi.send_commands(DataFormat::U8(&negative)).await?;
+        self.spi.send_commands(DataFormat::U8(&Bank::Command1.select_command())).await?;
+        self.state.apply(change);
+        Ok(())
+    }
+
+    /// Set the display brightness, from `0` (darkest) to `255` (brightest).
+    ///
+    /// Brightness values are ignored unless brightness control is enabled
+    /// with [`St7701s::set_brightness_config`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the brightness may change before the driver records
+    /// it, and the value replaces the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn set_brightness(&mut self, value: u8) -> Result<(), St7701sError> {
         self.spi
-            .send_commands(DataFormat::U8(&[command::ST7701S_SET_ADDRESS_MODE, madctl.to_byte()]))
+            .send_commands(DataFormat::U8(&[command::ST7701S_DISPLAY_BRIGHTNESS, value]))
             .await?;
+        self.state.apply(StateChange::Brightness(value));
+        Ok(())
+    }
 
-        // Turn off color inversion
-        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_INVERSION_OFF])).await?;
+    /// Configure the display's brightness control block.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the control block may change before the driver
+    /// records it, and the whole block is written again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn set_brightness_config(
+        &mut self,
+        config: BrightnessConfig,
+    ) -> Result<(), St7701sError> {
+        self.spi
+            .send_commands(DataFormat::U8(&[command::ST7701S_WRITE_CTRL_DISPLAY, config.to_byte()]))
+            .await?;
+        self.state.apply(StateChange::BrightnessConfig(config));
+        Ok(())
+    }
 
-        // Set the pixel format
+    /// Set the window that following pixel writes fill,
+    /// from `(x0, y0)` to `(x1, y1)` inclusive.
+    ///
+    /// The window is clipped to the panel.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the column range may be set without the page range,
+    /// and both are set again by the next call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window does not overlap the panel,
+    /// or if communication with the display fails.
+    pub async fn set_address_window(
+        &mut self,
+        x0: u16,
+        y0: u16,
+        x1: u16,
+        y1: u16,
+    ) -> Result<(), St7701sError> {
+        let [columns, pages] = self.window_commands(x0, y0, x1, y1)?;
+        self.spi.send_commands(DataFormat::U8(&columns)).await?;
+        Ok(self.spi.send_commands(DataFormat::U8(&pages)).await?)
+    }
+
+    /// Start writing pixel data into the current address window,
+    /// without sending any pixels.
+    ///
+    /// Pixel data can then be formatted with [`St7701s::format_frame_chunk`]
+    /// and transferred outside of the `SPI` interface, such as by an SPI DMA
+    /// engine. The output must be realigned afterwards, with
+    /// [`CommandDataShifter::format_realignment`] or
+    /// [`CommandDataShifter::realign_async`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the command only starts a memory write, and
+    /// starting it again discards nothing that was drawn.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn begin_memory_write(&mut self) -> Result<(), St7701sError> {
+        Ok(self.spi.send_commands(DataFormat::U8(&[command::ST7701S_MEMORY_WRITE])).await?)
+    }
+
+    /// Write raw pixel data into the current address window.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: pixels already written stay on the panel and the
+    /// write position is lost, so the address window must be set again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn write_pixels(&mut self, data: &[u8]) -> Result<(), St7701sError> {
+        self.send_pixels(data).await?;
+        self.refresh_if_due().await
+    }
+
+    /// Write raw pixel data into the current address window,
+    /// without counting it towards a periodic refresh.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub(crate) async fn send_pixels(&mut self, data: &[u8]) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_MEMORY_WRITE])).await?;
+        self.spi.send_data(DataFormat::U8(data)).await?;
+        self.spi.realign_async().await
+    }
+
+    /// Write raw pixel data into the current address window,
+    /// formatting it in chunks that fit into the internal buffer.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: pixels already written stay on the panel and the
+    /// write position is lost, so the address window must be set again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn write_pixels_iter(
+        &mut self,
+        data: impl IntoIterator<Item = u8>,
+    ) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_MEMORY_WRITE])).await?;
+        self.spi.send_data(DataFormat::U8Iter(&mut data.into_iter())).await?;
+        self.spi.realign_async().await?;
+        self.refresh_if_due().await
+    }
+
+    /// Re-send the cached configuration to the display.
+    ///
+    /// See [`St7701s::refresh_critical_config`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: every command is idempotent,
+    /// so the configuration can be sent again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn refresh_critical_config(&mut self) -> Result<(), St7701sError> {
+        let config = self.state.config;
+        self.spi
+            .send_commands(DataFormat::U8(&[
+                command::ST7701S_SET_ADDRESS_MODE,
+                config.address_mode.to_byte(),
+            ]))
+            .await?;
         self.spi
             .send_commands(DataFormat::U8(&[command::ST7701S_PIXEL_FORMAT, C::FORMAT_BYTE]))
             .await?;
-        delay.delay_ms(10).await; // 10 ms
+        self.spi.send_commands(DataFormat::U8(&[inversion_command(config.inverted)])).await?;
+        self.spi
+            .send_commands(DataFormat::U8(&[
+                command::ST7701S_DISPLAY_BRIGHTNESS,
+                config.brightness,
+            ]))
+            .await?;
+        if let Some(brightness) = self.state.brightness_config {
+            self.spi
+                .send_commands(DataFormat::U8(&[
+                    command::ST7701S_WRITE_CTRL_DISPLAY,
+                    brightness.to_byte(),
+                ]))
+                .await?;
+        }
+        Ok(self
+            .spi
+            .send_commands(DataFormat::U8(&[display_command(self.state.display_on)]))
+            .await?)
+    }
+
+    /// Refresh the cached configuration if a periodic refresh is due.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub(crate) async fn refresh_if_due(&mut self) -> Result<(), St7701sError> {
+        if self.refresh_due() { self.refresh_critical_config().await } else { Ok(()) }
+    }
+}
 
-        // Enter normal mode
-        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_NORMAL_MODE])).await?;
-        delay.delay_ms(10).await; // 10 ms
+impl<
+    C: DisplayColor + ColorFormat,
+    SPI: AsyncWriteOnlyDataCommand + AsyncReadDataCommand,
+    const N: usize,
+> St7701s<C, SPI, Async, N>
+{
+    /// Read the ID of the display, with `READ_ID_1`, `READ_ID_2` and
+    /// `READ_ID_3`.
+    ///
+    /// Reading the ID checks that a panel is attached and responding.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: reads do not change the state of the display.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn read_id(&mut self) -> Result<[u8; 3], St7701sError> {
+        let mut id = [0u8; 3];
+        for (byte, command) in id.iter_mut().zip(ReadCommand::ID) {
+            [*byte] = self.read(command).await?;
+        }
+        Ok(id)
+    }
 
-        // Exit idle mode
-        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_IDLE_OFF])).await?;
-        delay.delay_ms(10).await; // 10 ms
+    /// Read the [`DisplayStatus`], with `READ_DISPLAY_STATUS`.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: reads do not change the state of the display.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn read_display_status(&mut self) -> Result<DisplayStatus, St7701sError> {
+        self.read(ReadCommand::DISPLAY_STATUS).await.map(DisplayStatus::from_bytes)
+    }
 
-        // Turn on the display
-        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_DISPLAY_ON])).await?;
-        delay.delay_ms(150).await; // 150 ms
+    /// Read the result of the display's self-diagnostic,
+    /// with `READ_SELF_DIAGNOSTIC`.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: reads do not change the state of the display.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn read_self_diagnostic(&mut self) -> Result<u8, St7701sError> {
+        self.read(ReadCommand::SELF_DIAGNOSTIC).await.map(|[result]| result)
+    }
 
-        Ok(())
+    /// Send a read command and extract its response.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    async fn read<const M: usize>(
+        &mut self,
+        command: ReadCommand,
+    ) -> Result<[u8; M], St7701sError> {
+        // Send any carried over bits before the read command
+        self.spi.realign_async().await?;
+
+        let length = command.len::<M>();
+        let mut read = [0u8; READ_BUFFER];
+        self.spi.spi_mut().transfer(&mut read[..length], &command.request()[..length]).await?;
+        Ok(command.response(read))
+    }
+}
+
+impl<
+    C: DisplayColor + ColorFormat,
+    SPI: AsyncWriteOnlyDataCommand + AsyncReadDataCommand,
+    const N: usize,
+> AsyncIdentify for St7701s<C, SPI, Async, N>
+{
+    type Error = St7701sError;
+
+    /// Read the ID of the display, see [`St7701s::read_id`].
+    async fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> {
+        let id = self.read_id().await?;
+        Ok(DeviceIdentity::new("ST7701S", &id, None))
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 
-impl<SPI: AsyncWriteOnlyDataCommand, const N: usize> AsyncWriteOnlyDataCommand
-    for CommandDataShifter<SPI, N>
-{
-    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
-        match cmd {
-            DataFormat::U8(slice) => {
-                // Calculate the number of bytes that can be sent at once.
-                // Formatting adds 1 additional byte for every 8 bytes.
-                let chunk_size = N * 8 / 9;
+impl<SPI: AsyncWriteOnlyDataCommand, const N: usize> CommandDataShifter<SPI, N> {
+    /// Realign the output to a byte boundary,
+    /// sending any carried over bits followed by NOP commands.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable. Carried bits are only kept for chunks that have
+    /// been handed to the interface, so a cancelled transfer never breaks the
+    /// framing of the next one. This assumes the interface sends a chunk once
+    /// it has started, as a DMA transfer does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::{
+    ///     future::poll_fn,
+    ///     pin::pin,
+    ///     task::{Context, Poll, Waker},
+    /// };
+    ///
+    /// use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+    /// use ef_st7701s::{CommandDataShifter, test_util::words};
+    /// # use ef_st7701s::test_util::block_on;
+    ///
+    /// /// Starts every transfer immediately, then waits for it to finish.
+    /// #[derive(Default)]
+    /// struct Recorder(Vec<u8>);
+    ///
+    /// impl AsyncWriteOnlyDataCommand for Recorder {
+    ///     async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+    ///         self.send_data(cmd).await
+    ///     }
+    ///
+    ///     async fn send_data(&mut self, dat: DataFormat<'_>) -> Result<(), DisplayError> {
+    ///         let DataFormat::U8(bytes) = dat else { unreachable!() };
+    ///         self.0.extend_from_slice(bytes);
+    ///
+    ///         let mut waited = false;
+    ///         poll_fn(|_| {
+    ///             if std::mem::replace(&mut waited, true) { Poll::Ready(()) } else { Poll::Pending }
+    ///         })
+    ///         .await;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// // Cancel a `MEMORY_WRITE` at every await point
+    /// for polls in 0.. {
+    ///     let mut shifter = CommandDataShifter::new(Recorder::default(), [0u8; 16]);
+    ///     block_on(shifter.send_commands(DataFormat::U8(&[0x2C])))?;
+    ///
+    ///     let completed = {
+    ///         let mut cx = Context::from_waker(Waker::noop());
+    ///         let mut future = pin!(shifter.send_data(DataFormat::U8(&[0xFF; 50])));
+    ///         (0..polls).any(|_| future.as_mut().poll(&mut cx).is_ready())
+    ///     };
+    ///
+    ///     // The next command still starts on a word boundary
+    ///     block_on(shifter.send_commands(DataFormat::U8(&[0x29])))?;
+    ///     assert!(shifter.is_aligned());
+    ///
+    ///     let words = words(&shifter.release().0);
+    ///
+    ///     // Only the pixel data is cut short
+    ///     assert_eq!(words.first(), Some(&(false, 0x2C)));
+    ///     assert!(words[1..words.len() - 1].iter().all(|&word| word == (true, 0xFF)));
+    ///     assert_eq!(words.last(), Some(&(false, 0x29)));
+    ///
+    ///     if completed {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub async fn realign_async(&mut self) -> Result<(), St7701sError> {
+        if self.is_aligned() {
+            return Ok(());
+        }
 
-                let mut iter = slice.chunks(chunk_size);
+        let length = self.format_chunk(&mut Words::command(core::iter::empty()), true)?;
+        Ok(self.spi.send_commands(DataFormat::U8(&self.buffer[..length])).await?)
+    }
 
-                // Initial chunk includes the command byte.
-                if let Some(cmd_chunk) = iter.next() {
-                    let cmd = format_command(cmd_chunk.iter().copied(), self.1.as_mut_slice());
-                    self.0.send_commands(DataFormat::U8(cmd)).await?;
-                }
+    /// Format and send words in chunks that fit into the internal buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    async fn send_words_async<I: Iterator<Item = u8>>(
+        &mut self,
+        mut words: Words<I>,
+        command: bool,
+    ) -> Result<(), DisplayError> {
+        // Initial chunk includes the command byte.
+        let mut is_command = command;
 
-                // Subsequent chunks are data only.
-                for chunk in iter {
-                    let data = format_data(chunk.iter().copied(), self.1.as_mut_slice());
-                    self.0.send_data(DataFormat::U8(data)).await?;
+        loop {
+            let length = self.format_chunk(&mut words, command)?;
+            if length != 0 {
+                let chunk = DataFormat::U8(&self.buffer[..length]);
+                if is_command {
+                    self.spi.send_commands(chunk).await?;
+                } else {
+                    // Subsequent chunks are data only.
+                    self.spi.send_data(chunk).await?;
                 }
+            }
 
-                Ok(())
+            is_command = false;
+            if words.is_empty() {
+                return Ok(());
             }
-            DataFormat::U8Iter(iter) => {
-                self.0
-                    .send_commands(DataFormat::U8(format_command(iter, self.1.as_mut_slice())))
-                    .await
+        }
+    }
+}
+
+impl<SPI: AsyncWriteOnlyDataCommand, const N: usize> CommandBatch<'_, SPI, N> {
+    /// Add a command and its parameters to the batch.
+    ///
+    /// If the command does not fit into the rest of the buffer,
+    /// the commands so far are sent first.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: the commands so far may or may not have been
+    /// sent, so the batch must be built again from its first command.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`St7701sError::BufferTooSmall`] if the command does not fit
+    /// into the whole buffer with
+    /// [`Realignment::Nop`](crate::Realignment::Nop), which would end it
+    /// early, or an error if communication with the display fails.
+    pub async fn command_async(&mut self, command: &[u8]) -> Result<(), St7701sError> {
+        if self.push(command) {
+            return Ok(());
+        }
+
+        self.flush_async().await?;
+        if self.push(command) {
+            return Ok(());
+        }
+
+        // Too long for the buffer, send it on its own
+        self.check_split(command)?;
+        Ok(self.shifter.send_commands(DataFormat::U8(command)).await?)
+    }
+
+    /// Send every command in the batch as a single transfer,
+    /// realigned to a byte boundary.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: the commands may or may not have been sent,
+    /// so the batch must be built again from its first command.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn flush_async(&mut self) -> Result<(), St7701sError> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let length = self.finish();
+        Ok(self.shifter.spi.send_commands(DataFormat::U8(&self.shifter.buffer[..length])).await?)
+    }
+}
+
+impl<SPI: AsyncWriteOnlyDataCommand, const N: usize> AsyncWriteOnlyDataCommand
+    for CommandDataShifter<SPI, N>
+{
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        match cmd {
+            DataFormat::U8(slice) => {
+                self.send_words_async(Words::command(slice.iter().copied()), true).await
             }
+            DataFormat::U8Iter(iter) => self.send_words_async(Words::command(iter), true).await,
             _ => Err(DisplayError::InvalidFormatError),
         }
     }
@@ -97,19 +1104,22 @@ impl<SPI: AsyncWriteOnlyDataCommand, const N: usize> AsyncWriteOnlyDataCommand
     async fn send_data(&mut self, dat: DataFormat<'_>) -> Result<(), DisplayError> {
         match dat {
             DataFormat::U8(slice) => {
-                // Calculate the number of bytes that can be sent at once.
-                // Formatting adds 1 additional byte for every 8 bytes.
-                let chunk_size = N * 8 / 9;
-
-                for chunk in slice.chunks(chunk_size) {
-                    let data = format_data(chunk.iter().copied(), self.1.as_mut_slice());
-                    self.0.send_data(DataFormat::U8(data)).await?;
-                }
-
-                Ok(())
+                self.send_words_async(Words::data(slice.iter().copied()), false).await
+            }
+            DataFormat::U8Iter(iter) => self.send_words_async(Words::data(iter), false).await,
+            DataFormat::U16BE(slice) => {
+                let bytes = slice.iter().flat_map(|w| w.to_be_bytes());
+                self.send_words_async(Words::data(bytes), false).await
+            }
+            DataFormat::U16LE(slice) => {
+                let bytes = slice.iter().flat_map(|w| w.to_le_bytes());
+                self.send_words_async(Words::data(bytes), false).await
+            }
+            DataFormat::U16BEIter(iter) => {
+                self.send_words_async(Words::data(iter.flat_map(u16::to_be_bytes)), false).await
             }
-            DataFormat::U8Iter(iter) => {
-                self.0.send_data(DataFormat::U8(format_data(iter, self.1.as_mut_slice()))).await
+            DataFormat::U16LEIter(iter) => {
+                self.send_words_async(Words::data(iter.flat_map(u16::to_le_bytes)), false).await
             }
             _ => Err(DisplayError::InvalidFormatError),
         }
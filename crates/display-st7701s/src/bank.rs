@@ -0,0 +1,281 @@
+//! Command2 banks and the panel configuration stored in them.
+
+use crate::command;
+
+/// A command bank of the ST7701S, selected with `CMD_BANK_SELECT`.
+///
+/// Command2 registers share their addresses between banks,
+/// so the bank must be selected before writing them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Bank {
+    /// The standard command set.
+    #[default]
+    Command1,
+    /// Command2 BK0, holding the display timing and gamma registers.
+    Command2Bk0,
+    /// Command2 BK1, holding the power control registers.
+    Command2Bk1,
+}
+
+impl Bank {
+    /// Get the `CMD_BANK_SELECT` command that selects this bank.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_st7701s::Bank;
+    ///
+    /// assert_eq!(Bank::Command1.select_command(), [0xFF, 0x77, 0x01, 0x00, 0x00, 0x00]);
+    /// assert_eq!(Bank::Command2Bk0.select_command(), [0xFF, 0x77, 0x01, 0x00, 0x00, 0x10]);
+    /// assert_eq!(Bank::Command2Bk1.select_command(), [0xFF, 0x77, 0x01, 0x00, 0x00, 0x11]);
+    /// ```
+    #[must_use]
+    pub const fn select_command(self) -> [u8; 6] {
+        let bank = match self {
+            Bank::Command1 => 0x00,
+            Bank::Command2Bk0 => 0x10,
+            Bank::Command2Bk1 => 0x11,
+        };
+        [command::ST7701S_CMD_BANK_SELECT, 0x77, 0x01, 0x00, 0x00, bank]
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// The panel-specific configuration stored in the Command2 banks.
+///
+/// These values depend on the glass and are usually given by the panel vendor
+/// as part of an initialization sequence.
+/// [`PanelConfig::DEFAULT`] holds values used by many 480x480 modules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PanelConfig {
+    /// The number of display lines, a multiple of 8 (`LNESET`).
+    pub lines: u16,
+    /// The vertical back porch, in lines (`PORCTRL`).
+    pub back_porch: u8,
+    /// The vertical front porch, in lines (`PORCTRL`).
+    pub front_porch: u8,
+    /// The inversion type and minimum clocks per line (`INVSET`).
+    pub inversion: [u8; 2],
+    /// The VOP amplitude (`VRHS`).
+    pub vop: u8,
+    /// The VCOM voltage (`VCOMS`).
+    pub vcom: u8,
+    /// The positive voltage gamma table (`PVGAMCTRL`).
+    pub positive_gamma: [u8; 16],
+    /// The negative voltage gamma table (`NVGAMCTRL`).
+    pub negative_gamma: [u8; 16],
+}
+
+impl PanelConfig {
+    /// The configuration used by many 480x480 modules.
+    pub const DEFAULT: Self = Self {
+        lines: 480,
+        back_porch: 0x0D,
+        front_porch: 0x02,
+        inversion: [0x31, 0x05],
+        vop: 0x60,
+        vcom: 0x32,
+        positive_gamma: [
+            0x00, 0x11, 0x18, 0x0E, 0x11, 0x06, 0x07, 0x08, 0x07, 0x22, 0x04, 0x12, 0x0F, 0xAA,
+            0x31, 0x18,
+        ],
+        negative_gamma: [
+            0x00, 0x11, 0x19, 0x0E, 0x12, 0x07, 0x08, 0x08, 0x08, 0x22, 0x04, 0x11, 0x11, 0xA9,
+            0x32, 0x18,
+        ],
+    };
+
+    /// Create a new [`PanelConfig`] with the default values.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self { Self::DEFAULT }
+
+    /// Set the number of display lines.
+    ///
+    /// Lines are rounded down to a multiple of 8, from 8 to 1024 lines.
+    #[inline]
+    #[must_use]
+    pub const fn with_lines(mut self, lines: u16) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    /// Set the vertical back and front porches, in lines.
+    #[inline]
+    #[must_use]
+    pub const fn with_porch(mut self, back_porch: u8, front_porch: u8) -> Self {
+        self.back_porch = back_porch;
+        self.front_porch = front_porch;
+        self
+    }
+
+    /// Set the inversion type and minimum clocks per line.
+    #[inline]
+    #[must_use]
+    pub const fn with_inversion(mut self, inversion: [u8; 2]) -> Self {
+        self.inversion = inversion;
+        self
+    }
+
+    /// Set the VOP amplitude.
+    #[inline]
+    #[must_use]
+    pub const fn with_vop(mut self, vop: u8) -> Self {
+        self.vop = vop;
+        self
+    }
+
+    /// Set the VCOM voltage.
+    #[inline]
+    #[must_use]
+    pub const fn with_vcom(mut self, vcom: u8) -> Self {
+        self.vcom = vcom;
+        self
+    }
+
+    /// Set the positive and negative voltage gamma tables.
+    #[inline]
+    #[must_use]
+    pub const fn with_gamma(mut self, positive: [u8; 16], negative: [u8; 16]) -> Self {
+        self.positive_gamma = positive;
+        self.negative_gamma = negative;
+        self
+    }
+
+    /// Create the timing and gamma commands written to Command2 BK0.
+    pub(crate) const fn bk0_commands(&self) -> ([[u8; 3]; 3], [[u8; 17]; 2]) {
+        // Lines are set in units of 8, starting from 8 lines
+        #[expect(clippy::cast_possible_truncation, reason = "Clamped")]
+        let lines = match self.lines / 8 {
+            0 => 0,
+            units @ 1..=128 => (units - 1) as u8,
+            _ => 127,
+        };
+
+        let mut positive = [command::ST7701S_BK0_PVGAMCTRL; 17];
+        let mut negative = [command::ST7701S_BK0_NVGAMCTRL; 17];
+        let mut index = 0;
+        while index < 16 {
+            positive[index + 1] = self.positive_gamma[index];
+            negative[index + 1] = self.negative_gamma[index];
+            index += 1;
+        }
+
+        (
+            [
+                [command::ST7701S_BK0_LNESET, lines, 0x00],
+                [command::ST7701S_BK0_PORCTRL, self.back_porch, self.front_porch],
+                [command::ST7701S_BK0_INVSET, self.inversion[0], self.inversion[1]],
+            ],
+            [positive, negative],
+        )
+    }
+
+    /// Create the commands written to Command2 BK1.
+    pub(crate) const fn bk1_commands(&self) -> [[u8; 2]; 2] {
+        [[command::ST7701S_BK1_VRHS, self.vop], [command::ST7701S_BK1_VCOMS, self.vcom]]
+    }
+}
+
+impl Default for PanelConfig {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A voltage gamma table, the 16 parameters of `PVGAMCTRL` or `NVGAMCTRL`.
+///
+/// Panel vendors give one table per polarity, written with
+/// [`St7701s::set_gamma`](crate::St7701s::set_gamma).
+///
+/// Bits that are reserved in the datasheet are cleared, so tables copied from
+/// vendor initialization code cannot set them.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_st7701s::GammaCurve;
+///
+/// let vendor = [
+///     0x40, 0xC9, 0x91, 0x0D, 0x12, 0x07, 0x02, 0x09, 0x09, 0x1F, 0x04, 0x50, 0x0F, 0xE4, 0x29,
+///     0xDF,
+/// ];
+///
+/// // Valid tables are kept as they are
+/// let curve = GammaCurve::try_new(vendor).unwrap();
+/// assert_eq!(curve.to_bytes(), vendor);
+/// assert_eq!(GammaCurve::new(vendor), curve);
+///
+/// // Reserved bits are cleared, or rejected
+/// let mut invalid = vendor;
+/// invalid[3] |= 0xE0;
+/// invalid[5] |= 0xF0;
+/// assert_eq!(GammaCurve::try_new(invalid), None);
+/// assert_eq!(GammaCurve::new(invalid), curve);
+///
+/// // Serializing and parsing any table returns the same curve
+/// for seed in 0..=255u8 {
+///     let params = core::array::from_fn(|i| {
+///         seed.wrapping_mul(31).wrapping_add((i as u8).wrapping_mul(73))
+///     });
+///     let curve = GammaCurve::new(params);
+///     assert_eq!(GammaCurve::try_new(curve.to_bytes()), Some(curve));
+///     assert_eq!(GammaCurve::new(curve.to_bytes()), curve);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GammaCurve([u8; 16]);
+
+impl GammaCurve {
+    /// The bits of each parameter that are not reserved.
+    const MASK: [u8; 16] = [
+        0xCF, 0xFF, 0xFF, 0x1F, 0xDF, 0x0F, 0x3F, 0x0F, 0x0F, 0x3F, 0x0F, 0xDF, 0x1F, 0xFF, 0x3F,
+        0xDF,
+    ];
+
+    /// Create a new [`GammaCurve`], clearing any reserved bits.
+    #[must_use]
+    pub const fn new(mut params: [u8; 16]) -> Self {
+        let mut index = 0;
+        while index < 16 {
+            params[index] &= Self::MASK[index];
+            index += 1;
+        }
+        Self(params)
+    }
+
+    /// Create a new [`GammaCurve`].
+    ///
+    /// Returns `None` if any reserved bits are set.
+    #[must_use]
+    pub const fn try_new(params: [u8; 16]) -> Option<Self> {
+        let mut index = 0;
+        while index < 16 {
+            if params[index] & !Self::MASK[index] != 0 {
+                return None;
+            }
+            index += 1;
+        }
+        Some(Self(params))
+    }
+
+    /// Get the 16 parameter bytes of the [`GammaCurve`].
+    #[inline]
+    #[must_use]
+    pub const fn to_bytes(self) -> [u8; 16] { self.0 }
+
+    /// Create the command writing this curve with `cmd`.
+    pub(crate) const fn command(self, cmd: u8) -> [u8; 17] {
+        let mut command = [cmd; 17];
+        let mut index = 0;
+        while index < 16 {
+            command[index + 1] = self.0[index];
+            index += 1;
+        }
+        command
+    }
+}
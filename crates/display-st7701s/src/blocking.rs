@@ -1,9 +1,18 @@
 use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
-use ef_driver_common::{color::DisplayColor, mode::Blocking};
+use ef_driver_common::{
+    color::DisplayColor,
+    identify::{DeviceIdentity, Identify},
+    mode::Blocking,
+};
 use embedded_hal::delay::DelayNs;
 
 use crate::{
-    AddressMode, ColorFormat, CommandDataShifter, St7701s, command, format_command, format_data,
+    Bank, BrightnessConfig, ColorFormat, CommandBatch, CommandDataShifter, DisplayConfig,
+    GammaCurve, INIT_DELAY, InitCommand, InitConfig, Orientation, PanelConfig, St7701s,
+    St7701sError, StateChange, TearingEffect, Words, command, display_command, idle_command,
+    init::InitStep,
+    inversion_command,
+    read::{DisplayStatus, READ_BUFFER, ReadCommand, ReadDataCommand},
 };
 
 impl<C: DisplayColor + ColorFormat, SPI: WriteOnlyDataCommand, const N: usize>
@@ -11,102 +20,1423 @@ impl<C: DisplayColor + ColorFormat, SPI: WriteOnlyDataCommand, const N: usize>
 {
     /// Initialize the display.
     ///
+    /// Waits the delays of the [`TimingProfile`](crate::TimingProfile) in
+    /// `config` between commands, and keeps it for `sleep` and `wake`.
+    ///
+    /// This is [`St7701s::init_with`] with the default [`InitConfig`].
+    ///
     /// # Errors
     ///
     /// Returns an error if communication with the display fails.
+    #[inline]
     pub fn init<D: DelayNs>(
         &mut self,
-        madctl: AddressMode,
+        config: DisplayConfig,
         delay: &mut D,
-    ) -> Result<(), DisplayError> {
-        // Software reset
-        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_SOFT_RESET]))?;
-        delay.delay_ms(150); // 150 ms
+    ) -> Result<(), St7701sError> {
+        self.init_with(&InitConfig::new(config), delay)
+    }
 
-        // Exit sleep mode
+    /// Initialize the display with the delays and command order of an
+    /// [`InitConfig`].
+    ///
+    /// Keeps the [`DisplayConfig`] of `config`, including its timing for
+    /// `sleep` and `wake`. See [`InitConfig`] for an example.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a delay of the extra command sequence is longer
+    /// than four bytes, or if communication with the display fails.
+    pub fn init_with<D: DelayNs>(
+        &mut self,
+        config: &InitConfig<'_>,
+        delay: &mut D,
+    ) -> Result<(), St7701sError> {
+        for step in config.steps() {
+            match step {
+                InitStep::Command(cmd, ms) => {
+                    self.spi.send_commands(DataFormat::U8(&[cmd]))?;
+                    delay.delay_ms(ms);
+                }
+                InitStep::Extra(sequence) => self.run_init_sequence(sequence, delay)?,
+                InitStep::Configure(ms) => {
+                    let mut batch = self.spi.batch();
+                    for (bytes, len) in config.commands::<C>() {
+                        batch.command(&bytes[..len])?;
+                    }
+                    batch.flush()?;
+                    delay.delay_ms(ms);
+                }
+            }
+        }
+
+        self.state.apply(StateChange::Initialized(*config.display_config()));
+        Ok(())
+    }
+
+    /// Enter sleep mode, turning off the panel's DC/DC converter and
+    /// oscillator.
+    ///
+    /// Waits the `sleep_in` delay of the
+    /// [`TimingProfile`](crate::TimingProfile) before the next command, 5
+    /// ms by default. The panel must stay asleep for 120 ms before
+    /// [`St7701s::wake`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn sleep<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_SLEEP_ENTER]))?;
+        delay.delay_ms(self.state.config.timing.sleep_in);
+        self.state.apply(StateChange::Asleep(true));
+        Ok(())
+    }
+
+    /// Exit sleep mode.
+    ///
+    /// Waits the `wake` delay of the [`TimingProfile`](crate::TimingProfile)
+    /// for the panel to stabilize, 120 ms by default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{
+    ///     St7701s,
+    ///     color::Rgb565,
+    ///     mode::Blocking,
+    ///     test_util::{Clock, Recorder},
+    /// };
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// let mut clock = Clock::default();
+    ///
+    /// // Blank and sleep the panel, then bring it back
+    /// display.display_off()?;
+    /// display.sleep(&mut clock)?;
+    /// display.wake(&mut clock)?;
+    /// display.display_on()?;
+    /// assert_eq!(clock.0, [5, 120]);
+    ///
+    /// let words = display.release().words();
+    /// assert_eq!(words, [(false, 0x28), (false, 0x10), (false, 0x11), (false, 0x29)]);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn wake<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), St7701sError> {
         self.spi.send_commands(DataFormat::U8(&[command::ST7701S_SLEEP_EXIT]))?;
-        delay.delay_ms(150); // 150 ms
+        delay.delay_ms(self.state.config.timing.wake);
+        self.state.apply(StateChange::Asleep(false));
+        Ok(())
+    }
+
+    /// Turn off the display, blanking the panel without clearing its memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn display_off(&mut self) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_DISPLAY_OFF]))?;
+        self.state.apply(StateChange::DisplayOn(false));
+        Ok(())
+    }
+
+    /// Turn on the display, showing the contents of its memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn display_on(&mut self) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_DISPLAY_ON]))?;
+        self.state.apply(StateChange::DisplayOn(true));
+        Ok(())
+    }
+
+    /// Blank the display, keeping the image in its memory.
+    ///
+    /// Only the display is turned off, the panel stays awake and keeps its
+    /// configuration, so [`St7701s::unblank`] shows the image again at once.
+    /// Pixels written while blanked are drawn into memory and appear when
+    /// unblanked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{
+    ///     DisplayConfig, St7701s,
+    ///     color::Rgb565,
+    ///     mode::Blocking,
+    ///     test_util::{Clock, Recorder},
+    /// };
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// let mut clock = Clock::default();
+    /// display.init(DisplayConfig::default(), &mut clock)?;
+    /// display.spi_mut().take_words();
+    ///
+    /// // Drawing while blanked only writes memory
+    /// display.blank()?;
+    /// display.write_pixels(&[0xF8, 0x1F])?;
+    /// assert!(display.is_blanked());
+    /// assert_eq!(
+    ///     display.spi_mut().take_words(),
+    ///     [(false, 0x28), (false, 0x2C), (true, 0xF8), (true, 0x1F)]
+    /// );
+    ///
+    /// // Unblanking reveals the new image at once
+    /// display.unblank()?;
+    /// assert!(!display.is_blanked());
+    /// assert_eq!(display.spi_mut().take_words(), [(false, 0x29)]);
+    ///
+    /// // Powering down while blanked never turns the display back on
+    /// display.blank()?;
+    /// display.power_down(&mut clock)?;
+    /// assert!(display.is_blanked() && display.is_asleep());
+    /// assert_eq!(display.spi_mut().take_words(), [(false, 0x28), (false, 0x10)]);
+    ///
+    /// // Waking keeps the display blank until the image is redrawn
+    /// display.wake(&mut clock)?;
+    /// assert!(display.is_blanked() && !display.is_asleep());
+    /// display.write_pixels(&[0x07, 0xE0])?;
+    /// display.unblank()?;
+    /// assert_eq!(
+    ///     display.spi_mut().take_words(),
+    ///     [(false, 0x11), (false, 0x2C), (true, 0x07), (true, 0xE0), (false, 0x29)]
+    /// );
+    ///
+    /// // Powering down a visible display blanks it first
+    /// display.power_down(&mut clock)?;
+    /// assert_eq!(display.spi_mut().take_words(), [(false, 0x28), (false, 0x10)]);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    #[inline]
+    pub fn blank(&mut self) -> Result<(), St7701sError> { self.display_off() }
+
+    /// Unblank the display, showing the contents of its memory.
+    ///
+    /// The panel must not be asleep, see [`St7701s::wake`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    #[inline]
+    pub fn unblank(&mut self) -> Result<(), St7701sError> { self.display_on() }
+
+    /// Blank the display if it is not already, then enter sleep mode.
+    ///
+    /// The display is never turned on, so a blanked image is not shown
+    /// again. The display stays blanked after [`St7701s::wake`] until
+    /// [`St7701s::unblank`], allowing the image to be redrawn first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn power_down<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), St7701sError> {
+        if self.state.display_on {
+            self.display_off()?;
+        }
+        self.sleep(delay)
+    }
+
+    /// Set whether the colors of the display are inverted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{St7701s, color::Rgb565, mode::Blocking, test_util::Recorder};
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// display.set_inverted(true)?;
+    /// display.all_pixels_on()?;
+    /// display.all_pixels_off()?;
+    /// display.set_inverted(false)?;
+    ///
+    /// let words = display.release().words();
+    ///
+    /// assert_eq!(words, [(false, 0x21), (false, 0x23), (false, 0x22), (false, 0x20)]);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn set_inverted(&mut self, inverted: bool) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[inversion_command(inverted)]))?;
+        self.state.apply(StateChange::Inverted(inverted));
+        Ok(())
+    }
+
+    /// Turn all pixels on, showing white regardless of the display's memory.
+    ///
+    /// The display's memory is kept, and is shown again once the display
+    /// leaves this mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn all_pixels_on(&mut self) -> Result<(), St7701sError> {
+        Ok(self.spi.send_commands(DataFormat::U8(&[command::ST7701S_ALL_PIXEL_ON]))?)
+    }
+
+    /// Turn all pixels off, showing black regardless of the display's memory.
+    ///
+    /// The display's memory is kept, and is shown again once the display
+    /// leaves this mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn all_pixels_off(&mut self) -> Result<(), St7701sError> {
+        Ok(self.spi.send_commands(DataFormat::U8(&[command::ST7701S_ALL_PIXEL_OFF]))?)
+    }
+
+    /// Enter partial mode, only showing the rows from `start_row` to
+    /// `end_row` (inclusive).
+    ///
+    /// Rows outside of the partial area are not refreshed,
+    /// which reduces the power used by the panel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rows do not fit the panel,
+    /// or if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{St7701s, color::Rgb565, mode::Blocking, test_util::Recorder};
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    ///
+    /// // Only refresh a status strip at the bottom of the panel
+    /// display.enter_partial_mode(448, 479)?;
+    /// display.exit_partial_mode()?;
+    ///
+    /// // Empty areas and rows past the panel are rejected
+    /// assert!(display.enter_partial_mode(100, 99).is_err());
+    /// assert!(display.enter_partial_mode(448, 480).is_err());
+    ///
+    /// let words = display.release().words();
+    ///
+    /// assert_eq!(
+    ///     words,
+    ///     [
+    ///         (false, 0x30),
+    ///         (true, 0x01),
+    ///         (true, 0xC0),
+    ///         (true, 0x01),
+    ///         (true, 0xDF),
+    ///         (false, 0x12),
+    ///         (false, 0x13),
+    ///     ]
+    /// );
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn enter_partial_mode(&mut self, start_row: u16, end_row: u16) -> Result<(), St7701sError> {
+        let area = self.partial_area_command(start_row, end_row)?;
+        self.spi.send_commands(DataFormat::U8(&area))?;
+        Ok(self.spi.send_commands(DataFormat::U8(&[command::ST7701S_PARTIAL_MODE]))?)
+    }
+
+    /// Exit partial mode, showing the whole panel again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn exit_partial_mode(&mut self) -> Result<(), St7701sError> {
+        Ok(self.spi.send_commands(DataFormat::U8(&[command::ST7701S_NORMAL_MODE]))?)
+    }
+
+    /// Define the vertical scrolling layout of the panel: `top_fixed` rows
+    /// that never scroll, followed by `scroll_area` rows that scroll and
+    /// `bottom_fixed` rows that never scroll.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rows do not add up to the panel height,
+    /// or if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{St7701s, color::Rgb565, mode::Blocking, test_util::Recorder};
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    ///
+    /// // A log view between a 32-row header and a 16-row footer
+    /// display.define_vertical_scroll(32, 432, 16)?;
+    /// display.set_scroll_offset(300)?;
+    ///
+    /// // Layouts that do not cover the panel exactly are rejected
+    /// assert!(display.define_vertical_scroll(32, 432, 0).is_err());
+    /// assert!(display.define_vertical_scroll(u16::MAX, 1, 0).is_err());
+    /// assert!(display.set_scroll_offset(480).is_err());
+    ///
+    /// let words = display.release().words();
+    ///
+    /// assert_eq!(
+    ///     words,
+    ///     [
+    ///         (false, 0x33),
+    ///         (true, 0x00),
+    ///         (true, 0x20),
+    ///         (true, 0x01),
+    ///         (true, 0xB0),
+    ///         (true, 0x00),
+    ///         (true, 0x10),
+    ///         (false, 0x37),
+    ///         (true, 0x01),
+    ///         (true, 0x2C),
+    ///     ]
+    /// );
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn define_vertical_scroll(
+        &mut self,
+        top_fixed: u16,
+        scroll_area: u16,
+        bottom_fixed: u16,
+    ) -> Result<(), St7701sError> {
+        let area = self.scroll_area_command(top_fixed, scroll_area, bottom_fixed)?;
+        Ok(self.spi.send_commands(DataFormat::U8(&area))?)
+    }
+
+    /// Set the row of the display's memory shown at the top of the scrolling
+    /// area, see [`St7701s::define_vertical_scroll`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the row does not fit the panel,
+    /// or if communication with the display fails.
+    pub fn set_scroll_offset(&mut self, offset: u16) -> Result<(), St7701sError> {
+        let start = self.scroll_start_command(offset)?;
+        Ok(self.spi.send_commands(DataFormat::U8(&start))?)
+    }
+
+    /// Enter or exit idle mode.
+    ///
+    /// In idle mode the panel only shows 8 colors, using the most significant
+    /// bit of each channel, and uses less power. The pixel format and the
+    /// display's memory are kept, so exiting idle mode shows the full colors
+    /// again without initializing the display.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{St7701s, color::Rgb565, mode::Blocking, test_util::Recorder};
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// display.set_idle_mode(true)?;
+    /// display.set_idle_mode(false)?;
+    ///
+    /// let words = display.release().words();
+    ///
+    /// // The pixel format is not sent again
+    /// assert_eq!(words, [(false, 0x39), (false, 0x38)]);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn set_idle_mode(&mut self, enabled: bool) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[idle_command(enabled)]))?;
+        self.state.apply(StateChange::Idle(enabled));
+        Ok(())
+    }
+
+    /// Rotate the panel, keeping the color order and refresh directions of
+    /// its address mode.
+    ///
+    /// Coordinates and [`dimensions`](St7701s::dimensions) follow the new
+    /// orientation, the contents of the display's memory are not moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{Orientation, St7701s, color::Rgb565, mode::Blocking, test_util::Recorder};
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// assert_eq!(display.orientation(), Some(Orientation::Portrait));
+    ///
+    /// // The display maps the top-left corner, no transform is needed
+    /// display.set_orientation(Orientation::Landscape)?;
+    /// assert_eq!(display.orientation(), Some(Orientation::Landscape));
+    /// display.set_address_window(0, 0, 1, 0)?;
+    ///
+    /// let words = display.release().words();
+    ///
+    /// assert_eq!(
+    ///     words,
+    ///     [
+    ///         // `MX` and `MV`
+    ///         (false, 0x36),
+    ///         (true, 0x60),
+    ///         // Columns 0 to 1, row 0
+    ///         (false, 0x2A),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x01),
+    ///         (false, 0x2B),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///     ]
+    /// );
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn set_orientation(&mut self, orientation: Orientation) -> Result<(), St7701sError> {
+        let address_mode = orientation.apply(self.state.config.address_mode);
+        let madctl = [command::ST7701S_SET_ADDRESS_MODE, address_mode.to_byte()];
+        self.spi.send_commands(DataFormat::U8(&madctl))?;
+        self.state.apply(StateChange::AddressMode(address_mode));
+        Ok(())
+    }
+
+    /// Set the signal output on the tearing effect (TE) pin.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{St7701s, TearingEffect, color::Rgb565, mode::Blocking, test_util::Recorder};
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// display.set_tearing_effect(TearingEffect::VBlankOnly)?;
+    /// display.set_tearing_effect(TearingEffect::VAndHBlank)?;
+    /// assert_eq!(display.tearing_effect(), TearingEffect::VAndHBlank);
+    /// display.set_tearing_effect(TearingEffect::Off)?;
+    ///
+    /// let words = display.release().words();
+    ///
+    /// assert_eq!(words, [(false, 0x35), (true, 0x00), (false, 0x35), (true, 0x01), (false, 0x34)]);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn set_tearing_effect(&mut self, mode: TearingEffect) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(mode.command()))?;
+        self.state.apply(StateChange::TearingEffect(mode));
+        Ok(())
+    }
+
+    /// Send a command with its parameters.
+    ///
+    /// This allows sending commands the driver does not support directly,
+    /// such as those of a panel vendor's initialization sequence.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn send_command(&mut self, cmd: u8, params: &[u8]) -> Result<(), St7701sError> {
+        let mut bytes = core::iter::once(cmd).chain(params.iter().copied());
+        Ok(self.spi.send_commands(DataFormat::U8Iter(&mut bytes))?)
+    }
+
+    /// Send a sequence of commands with their parameters,
+    /// such as a panel vendor's initialization sequence.
+    ///
+    /// Entries with the [`INIT_DELAY`] command and parameters wait for their
+    /// parameters in milliseconds, as a big-endian number of up to four bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a delay is longer than four bytes,
+    /// or if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{
+    ///     INIT_DELAY, St7701s,
+    ///     color::Rgb565,
+    ///     mode::Blocking,
+    ///     test_util::{Clock, Recorder},
+    /// };
+    ///
+    /// // Part of a vendor initialization sequence
+    /// const SEQUENCE: &[(u8, &[u8])] = &[
+    ///     (0xFF, &[0x77, 0x01, 0x00, 0x00, 0x10]),
+    ///     (0xC0, &[0x3B, 0x00]),
+    ///     (0xFF, &[0x77, 0x01, 0x00, 0x00, 0x00]),
+    ///     (0x11, &[]),
+    ///     (INIT_DELAY, &[120]),
+    ///     (0x29, &[]),
+    ///     (INIT_DELAY, &[0x01, 0x2C]),
+    /// ];
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// let mut clock = Clock::default();
+    /// display.run_init_sequence(SEQUENCE, &mut clock)?;
+    /// assert_eq!(clock.0, [120, 300]);
+    ///
+    /// // Delays longer than four bytes are rejected
+    /// assert!(display.run_init_sequence(&[(INIT_DELAY, &[0; 5])], &mut clock).is_err());
+    ///
+    /// let words = display.release().words();
+    /// // Every command is sent with its parameters marked as data
+    /// let expected: Vec<(bool, u8)> = SEQUENCE
+    ///     .iter()
+    ///     .filter(|(cmd, _)| *cmd != INIT_DELAY)
+    ///     .flat_map(|&(cmd, params)| {
+    ///         core::iter::once((false, cmd)).chain(params.iter().map(|&param| (true, param)))
+    ///     })
+    ///     .collect();
+    /// assert_eq!(words, expected);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn run_init_sequence<D: DelayNs>(
+        &mut self,
+        seq: &[(u8, &[u8])],
+        delay: &mut D,
+    ) -> Result<(), St7701sError> {
+        for &(cmd, params) in seq {
+            match cmd {
+                INIT_DELAY => match Self::init_delay(params)? {
+                    Some(ms) => delay.delay_ms(ms),
+                    None => self.send_command(cmd, params)?,
+                },
+                _ => self.send_command(cmd, params)?,
+            }
+        }
+        Ok(())
+    }
 
-        // Set the address mode
+    /// Send a sequence of [`InitCommand`]s, such as a panel vendor's
+    /// initialization sequence with delays between commands.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// See [`vendor`](crate::vendor) for an example.
+    pub fn run_init_commands<D: DelayNs>(
+        &mut self,
+        seq: &[InitCommand],
+        delay: &mut D,
+    ) -> Result<(), St7701sError> {
+        for command in seq {
+            match *command {
+                InitCommand::Command { cmd, params } => self.send_command(cmd, params)?,
+                InitCommand::DelayMs(ms) => delay.delay_ms(u32::from(ms)),
+                InitCommand::DelayUs(us) => delay.delay_us(u32::from(us)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Select the [`Bank`] that following commands are written to.
+    ///
+    /// Commands other than Command2 registers need [`Bank::Command1`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn select_bank(&mut self, bank: Bank) -> Result<(), St7701sError> {
+        Ok(self.spi.send_commands(DataFormat::U8(&bank.select_command()))?)
+    }
+
+    /// Write the panel-specific configuration to the Command2 banks,
+    /// returning to [`Bank::Command1`] afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{PanelConfig, St7701s, color::Rgb565, mode::Blocking, test_util::Recorder};
+    ///
+    /// let gamma = core::array::from_fn(|i| i as u8);
+    /// let config = PanelConfig::new().with_porch(0x10, 0x0C).with_vcom(0x40).with_gamma(gamma, gamma);
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// display.apply_panel_config(&config)?;
+    ///
+    /// let words = display.release().words();
+    ///
+    /// // Rebuild the expected commands, with parameters marked as data
+    /// let mut expected = Vec::new();
+    /// let mut command = |cmd: u8, params: &[u8]| {
+    ///     expected.push((false, cmd));
+    ///     expected.extend(params.iter().map(|&param| (true, param)));
+    /// };
+    /// command(0xFF, &[0x77, 0x01, 0x00, 0x00, 0x10]);
+    /// command(0xC0, &[0x3B, 0x00]);
+    /// command(0xC1, &[0x10, 0x0C]);
+    /// command(0xC2, &[0x31, 0x05]);
+    /// command(0xB0, &gamma);
+    /// command(0xB1, &gamma);
+    /// command(0xFF, &[0x77, 0x01, 0x00, 0x00, 0x11]);
+    /// command(0xB0, &[0x60]);
+    /// command(0xB1, &[0x40]);
+    /// command(0xFF, &[0x77, 0x01, 0x00, 0x00, 0x00]);
+    /// assert_eq!(words, expected);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn apply_panel_config(&mut self, config: &PanelConfig) -> Result<(), St7701sError> {
+        let (timing, gamma) = config.bk0_commands();
+
+        self.spi.send_commands(DataFormat::U8(&Bank::Command2Bk0.select_command()))?;
+        for cmd in timing {
+            self.spi.send_commands(DataFormat::U8(&cmd))?;
+        }
+        for cmd in gamma {
+            self.spi.send_commands(DataFormat::U8(&cmd))?;
+        }
+
+        self.spi.send_commands(DataFormat::U8(&Bank::Command2Bk1.select_command()))?;
+        for cmd in config.bk1_commands() {
+            self.spi.send_commands(DataFormat::U8(&cmd))?;
+        }
+
+        self.spi.send_commands(DataFormat::U8(&Bank::Command1.select_command()))?;
+        let (positive, negative) = (config.positive_gamma, config.negative_gamma);
+        self.state.apply(StateChange::Gamma(GammaCurve::new(positive), GammaCurve::new(negative)));
+        Ok(())
+    }
+
+    /// Write the positive and negative voltage gamma tables to Command2 BK0,
+    /// returning to [`Bank::Command1`] afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{GammaCurve, St7701s, color::Rgb565, mode::Blocking, test_util::Recorder};
+    ///
+    /// let positive = GammaCurve::new([
+    ///     0x40, 0xC9, 0x91, 0x0D, 0x12, 0x07, 0x02, 0x09, 0x09, 0x1F, 0x04, 0x50, 0x0F, 0xE4, 0x29,
+    ///     0xDF,
+    /// ]);
+    /// let negative = GammaCurve::new([
+    ///     0x40, 0xCB, 0xD0, 0x11, 0x92, 0x07, 0x00, 0x08, 0x07, 0x1C, 0x06, 0x53, 0x12, 0x63, 0xEB,
+    ///     0xDF,
+    /// ]);
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// display.set_gamma(&positive, &negative)?;
+    ///
+    /// let words = display.release().words();
+    ///
+    /// // Rebuild the expected commands, with parameters marked as data
+    /// let mut expected = Vec::new();
+    /// let mut command = |cmd: u8, params: &[u8]| {
+    ///     expected.push((false, cmd));
+    ///     expected.extend(params.iter().map(|&param| (true, param)));
+    /// };
+    /// command(0xFF, &[0x77, 0x01, 0x00, 0x00, 0x10]);
+    /// command(0xB0, &positive.to_bytes());
+    /// command(0xB1, &negative.to_bytes());
+    /// command(0xFF, &[0x77, 0x01, 0x00, 0x00, 0x00]);
+    /// assert_eq!(words, expected);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn set_gamma(
+        &mut self,
+        positive: &GammaCurve,
+        negative: &GammaCurve,
+    ) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&Bank::Command2Bk0.select_command()))?;
+        self.spi
+            .send_commands(DataFormat::U8(&positive.command(command::ST7701S_BK0_PVGAMCTRL)))?;
+        self.spi
+            .send_commands(DataFormat::U8(&negative.command(command::ST7701S_BK0_NVGAMCTRL)))?;
+        self.spi.send_commands(DataFormat::U8(&Bank::Command1.select_command()))?;
+        self.state.apply(StateChange::Gamma(*positive, *negative));
+        Ok(())
+    }
+
+    /// Set the display brightness, from `0` (darkest) to `255` (brightest).
+    ///
+    /// Brightness values are ignored unless brightness control is enabled
+    /// with [`St7701s::set_brightness_config`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{
+    ///     BrightnessConfig, St7701s, color::Rgb565, mode::Blocking, test_util::Recorder,
+    /// };
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    ///
+    /// // Enable brightness control with dimming, then fade in
+    /// display.set_brightness_config(BrightnessConfig::new().with_dimming(true))?;
+    /// for value in [0x40, 0x80, 0xFF] {
+    ///     display.set_brightness(value)?;
+    /// }
+    /// assert_eq!(display.brightness(), 0xFF);
+    ///
+    /// let words = display.release().words();
+    ///
+    /// assert_eq!(
+    ///     words,
+    ///     [
+    ///         (false, 0x53),
+    ///         (true, 0b0010_1100),
+    ///         (false, 0x51),
+    ///         (true, 0x40),
+    ///         (false, 0x51),
+    ///         (true, 0x80),
+    ///         (false, 0x51),
+    ///         (true, 0xFF),
+    ///     ]
+    /// );
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn set_brightness(&mut self, value: u8) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_DISPLAY_BRIGHTNESS, value]))?;
+        self.state.apply(StateChange::Brightness(value));
+        Ok(())
+    }
+
+    /// Configure the display's brightness control block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn set_brightness_config(&mut self, config: BrightnessConfig) -> Result<(), St7701sError> {
         self.spi.send_commands(DataFormat::U8(&[
-            command::ST7701S_SET_ADDRESS_MODE,
-            madctl.to_byte(),
+            command::ST7701S_WRITE_CTRL_DISPLAY,
+            config.to_byte(),
         ]))?;
+        self.state.apply(StateChange::BrightnessConfig(config));
+        Ok(())
+    }
+
+    /// Set the window that following pixel writes fill,
+    /// from `(x0, y0)` to `(x1, y1)` inclusive.
+    ///
+    /// The window is clipped to the panel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window does not overlap the panel,
+    /// or if communication with the display fails.
+    pub fn set_address_window(
+        &mut self,
+        x0: u16,
+        y0: u16,
+        x1: u16,
+        y1: u16,
+    ) -> Result<(), St7701sError> {
+        let [columns, pages] = self.window_commands(x0, y0, x1, y1)?;
+        self.spi.send_commands(DataFormat::U8(&columns))?;
+        Ok(self.spi.send_commands(DataFormat::U8(&pages))?)
+    }
+
+    /// Start writing pixel data into the current address window,
+    /// without sending any pixels.
+    ///
+    /// Pixel data can then be formatted with [`St7701s::format_frame_chunk`]
+    /// and transferred outside of the `SPI` interface, such as by an SPI DMA
+    /// engine. The output must be realigned afterwards, with
+    /// [`CommandDataShifter::format_realignment`] or
+    /// [`CommandDataShifter::realign`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{St7701s, color::Rgb565, mode::Blocking, test_util::Recorder};
+    ///
+    /// let pixels: Vec<u8> = (0..=255).collect();
+    ///
+    /// let mut expected = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// expected.write_pixels(&pixels)?;
+    ///
+    /// // Stream the pixels into a separate "DMA" buffer
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// display.begin_memory_write()?;
+    /// let mut dma = Vec::new();
+    /// let mut iter = pixels.iter().copied();
+    /// loop {
+    ///     let chunk = display.format_frame_chunk(&mut iter);
+    ///     if chunk.is_empty() {
+    ///         break;
+    ///     }
+    ///     dma.extend_from_slice(chunk);
+    /// }
+    /// dma.extend_from_slice(display.shifter_mut().format_realignment());
+    ///
+    /// let mut output = display.release().0;
+    /// output.extend(dma);
+    /// assert_eq!(output, expected.release().0);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn begin_memory_write(&mut self) -> Result<(), St7701sError> {
+        Ok(self.spi.send_commands(DataFormat::U8(&[command::ST7701S_MEMORY_WRITE]))?)
+    }
+
+    /// Write raw pixel data into the current address window.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{St7701s, color::Rgb565, mode::Blocking, test_util::Recorder};
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    ///
+    /// // Write a single pixel at (300, 10), clipping the window to the panel
+    /// display.set_address_window(300, 10, 300, 10)?;
+    /// display.write_pixels(&[0xF8, 0x1F])?;
+    /// assert!(display.set_address_window(480, 0, 500, 0).is_err());
+    ///
+    /// let words = display.release().words();
+    ///
+    /// assert_eq!(
+    ///     words,
+    ///     [
+    ///         // Columns 300 to 300
+    ///         (false, 0x2A),
+    ///         (true, 0x01),
+    ///         (true, 0x2C),
+    ///         (true, 0x01),
+    ///         (true, 0x2C),
+    ///         // Pages 10 to 10
+    ///         (false, 0x2B),
+    ///         (true, 0x00),
+    ///         (true, 0x0A),
+    ///         (true, 0x00),
+    ///         (true, 0x0A),
+    ///         // The pixel data
+    ///         (false, 0x2C),
+    ///         (true, 0xF8),
+    ///         (true, 0x1F),
+    ///     ]
+    /// );
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn write_pixels(&mut self, data: &[u8]) -> Result<(), St7701sError> {
+        self.send_pixels(data)?;
+        self.refresh_if_due()
+    }
+
+    /// Write raw pixel data into the current address window,
+    /// without counting it towards a periodic refresh.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub(crate) fn send_pixels(&mut self, data: &[u8]) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_MEMORY_WRITE]))?;
+        self.spi.send_data(DataFormat::U8(data))?;
+        self.spi.realign()
+    }
 
-        // Turn off color inversion
-        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_INVERSION_OFF]))?;
+    /// Write raw pixel data into the current address window,
+    /// formatting it in chunks that fit into the internal buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn write_pixels_iter(
+        &mut self,
+        data: impl IntoIterator<Item = u8>,
+    ) -> Result<(), St7701sError> {
+        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_MEMORY_WRITE]))?;
+        self.spi.send_data(DataFormat::U8Iter(&mut data.into_iter()))?;
+        self.spi.realign()?;
+        self.refresh_if_due()
+    }
 
-        // Set the pixel format
+    /// Re-send the cached configuration to the display.
+    ///
+    /// Only commands that do not change what is shown are replayed:
+    /// the address mode, pixel format, color inversion, brightness, the
+    /// brightness control block if it was configured, and whether the
+    /// display is on. Commands with side effects, such as
+    /// resets or leaving sleep mode, are never sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{
+    ///     AddressMode, BrightnessConfig, DisplayConfig, St7701s,
+    ///     color::Rgb565,
+    ///     mode::Blocking,
+    ///     test_util::{Clock, Recorder},
+    /// };
+    ///
+    /// let mut display =
+    ///     St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default()).with_periodic_refresh(2);
+    /// let config = DisplayConfig::new(AddressMode::DEFAULT).with_inverted(true).with_brightness(0x80);
+    /// display.init(config, &mut Clock::default())?;
+    /// display.set_brightness_config(BrightnessConfig::DEFAULT)?;
+    /// display.spi_mut().0.clear();
+    ///
+    /// // The configuration is replayed after every second flush
+    /// display.write_pixels(&[0xF8, 0x1F])?;
+    /// assert_eq!(display.spi().words(), [(false, 0x2C), (true, 0xF8), (true, 0x1F)]);
+    /// display.write_pixels(&[0xF8, 0x1F])?;
+    /// let refresh = [
+    ///     (false, 0x36),
+    ///     (true, 0x00),
+    ///     (false, 0x3A),
+    ///     (true, 0x50),
+    ///     (false, 0x21),
+    ///     (false, 0x51),
+    ///     (true, 0x80),
+    ///     (false, 0x53),
+    ///     (true, 0x24),
+    ///     (false, 0x29),
+    /// ];
+    /// assert_eq!(display.spi().words()[6..], refresh);
+    ///
+    /// // A blanked display stays blank
+    /// display.display_off()?;
+    /// display.spi_mut().0.clear();
+    /// display.refresh_critical_config()?;
+    /// assert_eq!(display.spi().words()[..9], refresh[..9]);
+    /// assert_eq!(display.spi().words()[9..], [(false, 0x28)]);
+    ///
+    /// // Resets, sleep and mode changes are never replayed
+    /// let commands = display.release().words();
+    /// assert!(commands.iter().all(|word| ![0x01, 0x10, 0x11, 0x13, 0x38, 0x39].contains(&word.1)));
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn refresh_critical_config(&mut self) -> Result<(), St7701sError> {
+        let config = self.state.config;
+        self.spi.send_commands(DataFormat::U8(&[
+            command::ST7701S_SET_ADDRESS_MODE,
+            config.address_mode.to_byte(),
+        ]))?;
         self.spi.send_commands(DataFormat::U8(&[command::ST7701S_PIXEL_FORMAT, C::FORMAT_BYTE]))?;
-        delay.delay_ms(10); // 10 ms
+        self.spi.send_commands(DataFormat::U8(&[inversion_command(config.inverted)]))?;
+        self.spi.send_commands(DataFormat::U8(&[
+            command::ST7701S_DISPLAY_BRIGHTNESS,
+            config.brightness,
+        ]))?;
+        if let Some(brightness) = self.state.brightness_config {
+            self.spi.send_commands(DataFormat::U8(&[
+                command::ST7701S_WRITE_CTRL_DISPLAY,
+                brightness.to_byte(),
+            ]))?;
+        }
+        Ok(self.spi.send_commands(DataFormat::U8(&[display_command(self.state.display_on)]))?)
+    }
 
-        // Enter normal mode
-        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_NORMAL_MODE]))?;
-        delay.delay_ms(10); // 10 ms
+    /// Refresh the cached configuration if a periodic refresh is due.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub(crate) fn refresh_if_due(&mut self) -> Result<(), St7701sError> {
+        if self.refresh_due() { self.refresh_critical_config() } else { Ok(()) }
+    }
+}
 
-        // Exit idle mode
-        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_IDLE_OFF]))?;
-        delay.delay_ms(10); // 10 ms
+impl<C: DisplayColor + ColorFormat, SPI: WriteOnlyDataCommand + ReadDataCommand, const N: usize>
+    St7701s<C, SPI, Blocking, N>
+{
+    /// Read the ID of the display, with `READ_ID_1`, `READ_ID_2` and
+    /// `READ_ID_3`.
+    ///
+    /// Reading the ID checks that a panel is attached and responding.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+    /// use ef_st7701s::{ColorFormat, ReadDataCommand, St7701s, color::Rgb565, mode::Blocking};
+    ///
+    /// /// A panel that is awake, with inverted colors.
+    /// struct Panel;
+    ///
+    /// impl WriteOnlyDataCommand for Panel {
+    ///     fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+    ///
+    ///     fn send_data(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+    /// }
+    ///
+    /// impl ReadDataCommand for Panel {
+    ///     fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), DisplayError> {
+    ///         // Decode the 9-bit command word
+    ///         assert_eq!(write[0] & 0x80, 0x00);
+    ///         let (dummy, response): (usize, &[u8]) = match write[0] << 1 | write[1] >> 7 {
+    ///             0xDA => (0, &[0x88]),
+    ///             0xDB => (0, &[0x02]),
+    ///             0xDC => (0, &[0x01]),
+    ///             0x09 => (1, &[0x80, 0x53, 0x24, 0x00]),
+    ///             _ => return Err(DisplayError::InvalidFormatError),
+    ///         };
+    ///
+    ///         // Respond after the command word and any dummy clock,
+    ///         // which reads as a `1` here to show that it is skipped
+    ///         let mut bits = vec![false; 9];
+    ///         bits.extend(std::iter::repeat_n(true, dummy));
+    ///         bits.extend(response.iter().flat_map(|b| (0..8).rev().map(move |i| b >> i & 1 == 1)));
+    ///         assert_eq!(bits.len().div_ceil(8), read.len());
+    ///
+    ///         bits.resize(read.len() * 8, false);
+    ///         for (byte, chunk) in read.iter_mut().zip(bits.chunks(8)) {
+    ///             *byte = chunk.iter().fold(0, |acc, &bit| acc << 1 | u8::from(bit));
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Panel);
+    /// assert_eq!(display.read_id()?, [0x88, 0x02, 0x01]);
+    ///
+    /// let status = display.read_display_status()?;
+    /// assert!(status.is_booster_on() && status.is_normal() && status.is_display_on());
+    /// assert!(status.is_inverted() && !status.is_sleeping() && !status.is_idle());
+    /// assert_eq!(status.pixel_format(), Rgb565::FORMAT_BYTE);
+    ///
+    /// // Errors from the interface are passed through
+    /// assert!(display.read_self_diagnostic().is_err());
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn read_id(&mut self) -> Result<[u8; 3], St7701sError> {
+        let mut id = [0u8; 3];
+        for (byte, command) in id.iter_mut().zip(ReadCommand::ID) {
+            [*byte] = self.read(command)?;
+        }
+        Ok(id)
+    }
 
-        // Turn on the display
-        self.spi.send_commands(DataFormat::U8(&[command::ST7701S_DISPLAY_ON]))?;
-        delay.delay_ms(150); // 150 ms
+    /// Read the [`DisplayStatus`], with `READ_DISPLAY_STATUS`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn read_display_status(&mut self) -> Result<DisplayStatus, St7701sError> {
+        self.read(ReadCommand::DISPLAY_STATUS).map(DisplayStatus::from_bytes)
+    }
 
-        Ok(())
+    /// Read the result of the display's self-diagnostic,
+    /// with `READ_SELF_DIAGNOSTIC`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn read_self_diagnostic(&mut self) -> Result<u8, St7701sError> {
+        self.read(ReadCommand::SELF_DIAGNOSTIC).map(|[result]| result)
+    }
+
+    /// Send a read command and extract its response.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    fn read<const M: usize>(&mut self, command: ReadCommand) -> Result<[u8; M], St7701sError> {
+        // Send any carried over bits before the read command
+        self.spi.realign()?;
+
+        let length = command.len::<M>();
+        let mut read = [0u8; READ_BUFFER];
+        self.spi.spi_mut().transfer(&mut read[..length], &command.request()[..length])?;
+        Ok(command.response(read))
+    }
+}
+
+impl<C: DisplayColor + ColorFormat, SPI: WriteOnlyDataCommand + ReadDataCommand, const N: usize>
+    Identify for St7701s<C, SPI, Blocking, N>
+{
+    type Error = St7701sError;
+
+    /// Read the ID of the display, see [`St7701s::read_id`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+    /// use ef_driver_common::identify::Identify;
+    /// use ef_st7701s::{ReadDataCommand, St7701s, color::Rgb565, mode::Blocking};
+    ///
+    /// /// A panel that answers each read ID command with one byte.
+    /// struct Panel;
+    ///
+    /// impl WriteOnlyDataCommand for Panel {
+    ///     fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+    ///
+    ///     fn send_data(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+    /// }
+    ///
+    /// impl ReadDataCommand for Panel {
+    ///     fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), DisplayError> {
+    ///         // The response follows the 9-bit command word, in 24 clocks
+    ///         let response: u32 = match write[0] << 1 | write[1] >> 7 {
+    ///             0xDA => 0x88,
+    ///             0xDB => 0x02,
+    ///             0xDC => 0x01,
+    ///             _ => return Err(DisplayError::InvalidFormatError),
+    ///         };
+    ///         read.copy_from_slice(&(response << 7).to_be_bytes()[1..]);
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let identity = St7701s::<Rgb565, _, Blocking, 16>::new(Panel).identify()?;
+    /// assert_eq!((identity.name(), identity.id()), ("ST7701S", &[0x88, 0x02, 0x01][..]));
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> {
+        let id = self.read_id()?;
+        Ok(DeviceIdentity::new("ST7701S", &id, None))
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 
-impl<SPI: WriteOnlyDataCommand, const N: usize> WriteOnlyDataCommand
-    for CommandDataShifter<SPI, N>
-{
-    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
-        match cmd {
-            DataFormat::U8(slice) => {
-                // Calculate the number of bytes that can be sent at once.
-                // Formatting adds 1 additional byte for every 8 bytes.
-                let chunk_size = N * 8 / 9;
+impl<SPI: WriteOnlyDataCommand, const N: usize> CommandDataShifter<SPI, N> {
+    /// Realign the output to a byte boundary,
+    /// sending any carried over bits followed by NOP commands.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// With [`Realignment::Carry`](crate::Realignment::Carry), data sent in
+    /// any number of chunks through any buffer size only realigns once,
+    /// giving the same output as formatting the data in one go.
+    ///
+    /// ```rust
+    /// use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+    /// use ef_st7701s::{CommandDataShifter, format_data, test_util::Recorder};
+    ///
+    /// /// Send the payload in chunks, then realign.
+    /// fn stream<const N: usize>(payload: &[u8], chunk: usize) -> Vec<u8> {
+    ///     let mut shifter = CommandDataShifter::new(Recorder::default(), [0u8; N]);
+    ///     for chunk in payload.chunks(chunk) {
+    ///         shifter.send_data(DataFormat::U8(chunk)).unwrap();
+    ///     }
+    ///     shifter.realign().unwrap();
+    ///     shifter.release().0
+    /// }
+    ///
+    /// let payload: Vec<u8> = (0..20).map(|i| i * 13).collect();
+    /// let mut buffer = [0u8; 32];
+    /// let expected = format_data(payload.iter().copied(), &mut buffer).unwrap();
+    ///
+    /// for chunk in [1, 3, 7, 8, 9, 20] {
+    ///     assert_eq!(stream::<9>(&payload, chunk), expected);
+    ///     assert_eq!(stream::<10>(&payload, chunk), expected);
+    ///     assert_eq!(stream::<16>(&payload, chunk), expected);
+    ///     assert_eq!(stream::<64>(&payload, chunk), expected);
+    /// }
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn realign(&mut self) -> Result<(), St7701sError> {
+        if self.is_aligned() {
+            return Ok(());
+        }
 
-                let mut iter = slice.chunks(chunk_size);
+        let length = self.format_chunk(&mut Words::command(core::iter::empty()), true)?;
+        Ok(self.spi.send_commands(DataFormat::U8(&self.buffer[..length]))?)
+    }
 
-                // Initial chunk includes the command byte.
-                if let Some(cmd_chunk) = iter.next() {
-                    let cmd = format_command(cmd_chunk.iter().copied(), self.1.as_mut_slice());
-                    self.0.send_commands(DataFormat::U8(cmd))?;
-                }
+    /// Format and send words in chunks that fit into the internal buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    fn send_words<I: Iterator<Item = u8>>(
+        &mut self,
+        mut words: Words<I>,
+        command: bool,
+    ) -> Result<(), DisplayError> {
+        // Initial chunk includes the command byte.
+        let mut is_command = command;
 
-                // Subsequent chunks are data only.
-                for chunk in iter {
-                    let data = format_data(chunk.iter().copied(), self.1.as_mut_slice());
-                    self.0.send_data(DataFormat::U8(data))?;
+        loop {
+            let length = self.format_chunk(&mut words, command)?;
+            if length != 0 {
+                let chunk = DataFormat::U8(&self.buffer[..length]);
+                if is_command {
+                    self.spi.send_commands(chunk)?;
+                } else {
+                    // Subsequent chunks are data only.
+                    self.spi.send_data(chunk)?;
                 }
-
-                Ok(())
             }
-            DataFormat::U8Iter(iter) => {
-                self.0.send_commands(DataFormat::U8(format_command(iter, self.1.as_mut_slice())))
+
+            is_command = false;
+            if words.is_empty() {
+                return Ok(());
             }
+        }
+    }
+}
+
+impl<SPI: WriteOnlyDataCommand, const N: usize> CommandBatch<'_, SPI, N> {
+    /// Add a command and its parameters to the batch.
+    ///
+    /// If the command does not fit into the rest of the buffer,
+    /// the commands so far are sent first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`St7701sError::BufferTooSmall`] if the command does not fit
+    /// into the whole buffer with [`Realignment::Nop`], which would end it
+    /// early, or an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_st7701s::{CommandDataShifter, Realignment, St7701sError, test_util::Recorder};
+    ///
+    /// // A gamma table takes 17 words, or 27 bytes with the NOPs after it
+    /// let gamma: Vec<u8> = core::iter::once(0xB0).chain(1..=16).collect();
+    ///
+    /// // With NOPs after every transfer it cannot be split
+    /// let mut shifter =
+    ///     CommandDataShifter::new(Recorder::default(), [0u8; 16]).with_realignment(Realignment::Nop);
+    /// let mut batch = shifter.batch();
+    /// batch.command(&[0x11])?;
+    /// let error = batch.command(&gamma).unwrap_err();
+    /// assert!(matches!(error, St7701sError::BufferTooSmall { needed: 27, available: 16 }));
+    ///
+    /// // Only the commands before it were sent
+    /// assert_eq!(shifter.release().words(), [(false, 0x11)]);
+    ///
+    /// // Carried bits let it continue in the next transfer
+    /// let mut shifter = CommandDataShifter::new(Recorder::default(), [0u8; 16]);
+    /// let mut batch = shifter.batch();
+    /// batch.command(&gamma)?;
+    /// batch.flush()?;
+    /// assert_eq!(shifter.release().words().len(), 17);
+    /// # Ok::<(), St7701sError>(())
+    /// ```
+    pub fn command(&mut self, command: &[u8]) -> Result<(), St7701sError> {
+        if self.push(command) {
+            return Ok(());
+        }
+
+        self.flush()?;
+        if self.push(command) {
+            return Ok(());
+        }
+
+        // Too long for the buffer, send it on its own
+        self.check_split(command)?;
+        Ok(self.shifter.send_commands(DataFormat::U8(command))?)
+    }
+
+    /// Send every command in the batch as a single transfer,
+    /// realigned to a byte boundary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn flush(&mut self) -> Result<(), St7701sError> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let length = self.finish();
+        Ok(self.shifter.spi.send_commands(DataFormat::U8(&self.shifter.buffer[..length]))?)
+    }
+}
+
+/// Data can also be sent as 16-bit words, which are split into bytes in the
+/// given byte order.
+///
+/// # Example
+///
+/// ```rust
+/// use display_interface::{DataFormat, WriteOnlyDataCommand};
+/// use ef_st7701s::{CommandDataShifter, test_util::Recorder};
+///
+/// /// Send the data, then realign.
+/// fn send(data: DataFormat<'_>) -> Vec<u8> {
+///     let mut shifter = CommandDataShifter::new(Recorder::default(), [0u8; 16]);
+///     shifter.send_data(data).unwrap();
+///     shifter.realign().unwrap();
+///     shifter.release().0
+/// }
+///
+/// let words: Vec<u16> = (0..37).map(|i| i * 1721).collect();
+/// let be: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+/// let le: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+/// let expected = (send(DataFormat::U8(&be)), send(DataFormat::U8(&le)));
+///
+/// assert_eq!(send(DataFormat::U16BE(&mut words.clone())), expected.0);
+/// assert_eq!(send(DataFormat::U16LE(&mut words.clone())), expected.1);
+/// assert_eq!(send(DataFormat::U16BEIter(&mut words.iter().copied())), expected.0);
+/// assert_eq!(send(DataFormat::U16LEIter(&mut words.iter().copied())), expected.1);
+/// ```
+impl<SPI: WriteOnlyDataCommand, const N: usize> WriteOnlyDataCommand
+    for CommandDataShifter<SPI, N>
+{
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        match cmd {
+            DataFormat::U8(slice) => self.send_words(Words::command(slice.iter().copied()), true),
+            DataFormat::U8Iter(iter) => self.send_words(Words::command(iter), true),
             _ => Err(DisplayError::InvalidFormatError),
         }
     }
 
     fn send_data(&mut self, dat: DataFormat<'_>) -> Result<(), DisplayError> {
         match dat {
-            DataFormat::U8(slice) => {
-                // Calculate the number of bytes that can be sent at once.
-                // Formatting adds 1 additional byte for every 8 bytes.
-                let chunk_size = N * 8 / 9;
-
-                for chunk in slice.chunks(chunk_size) {
-                    let data = format_data(chunk.iter().copied(), self.1.as_mut_slice());
-                    self.0.send_data(DataFormat::U8(data))?;
-                }
-
-                Ok(())
+            DataFormat::U8(slice) => self.send_words(Words::data(slice.iter().copied()), false),
+            DataFormat::U8Iter(iter) => self.send_words(Words::data(iter), false),
+            DataFormat::U16BE(slice) => {
+                self.send_words(Words::data(slice.iter().flat_map(|w| w.to_be_bytes())), false)
+            }
+            DataFormat::U16LE(slice) => {
+                self.send_words(Words::data(slice.iter().flat_map(|w| w.to_le_bytes())), false)
+            }
+            DataFormat::U16BEIter(iter) => {
+                self.send_words(Words::data(iter.flat_map(u16::to_be_bytes)), false)
             }
-            DataFormat::U8Iter(iter) => {
-                self.0.send_data(DataFormat::U8(format_data(iter, self.1.as_mut_slice())))
+            DataFormat::U16LEIter(iter) => {
+                self.send_words(Words::data(iter.flat_map(u16::to_le_bytes)), false)
             }
             _ => Err(DisplayError::InvalidFormatError),
         }
@@ -10,9 +10,9 @@ macro_rules! command {
 command! {
     ST7701S_NOP: 0x00,
     ST7701S_SOFT_RESET: 0x01,
-    ST7701S_READ_ID_1: 0x04,
-    ST7701S_READ_ID_2: 0x05,
-    ST7701S_READ_ID_3: 0x06,
+    ST7701S_READ_DISPLAY_ID: 0x04,
+    ST7701S_READ_DISPLAY_STATUS: 0x09,
+    ST7701S_READ_SELF_DIAGNOSTIC: 0x0F,
     ST7701S_SLEEP_ENTER: 0x10,
     ST7701S_SLEEP_EXIT: 0x11,
     ST7701S_PARTIAL_MODE: 0x12,
@@ -27,13 +27,30 @@ command! {
     ST7701S_SET_PAGE_ADDR: 0x2B,
     ST7701S_MEMORY_WRITE: 0x2C,
     ST7701S_MEMORY_READ: 0x2E,
+    ST7701S_PARTIAL_AREA: 0x30,
+    ST7701S_SCROLL_AREA: 0x33,
+    ST7701S_TEARING_EFFECT_OFF: 0x34,
+    ST7701S_TEARING_EFFECT_ON: 0x35,
     ST7701S_SET_ADDRESS_MODE: 0x36,
+    ST7701S_SCROLL_START: 0x37,
     ST7701S_IDLE_OFF: 0x38,
     ST7701S_IDLE_ON: 0x39,
     ST7701S_PIXEL_FORMAT: 0x3A,
     ST7701S_DISPLAY_BRIGHTNESS: 0x51,
+    ST7701S_WRITE_CTRL_DISPLAY: 0x53,
+    ST7701S_READ_ID_1: 0xDA,
+    ST7701S_READ_ID_2: 0xDB,
+    ST7701S_READ_ID_3: 0xDC,
     // Command2 BK0
+    ST7701S_BK0_PVGAMCTRL: 0xB0,
+    ST7701S_BK0_NVGAMCTRL: 0xB1,
+    ST7701S_BK0_LNESET: 0xC0,
+    ST7701S_BK0_PORCTRL: 0xC1,
+    ST7701S_BK0_INVSET: 0xC2,
     ST7701S_COLOR_CONTROL: 0xCD,
+    // Command2 BK1
+    ST7701S_BK1_VRHS: 0xB0,
+    ST7701S_BK1_VCOMS: 0xB1,
 
     ST7701S_CMD_BANK_SELECT: 0xFF,
 }
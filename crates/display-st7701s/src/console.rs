@@ -0,0 +1,267 @@
+//! A buffered text console.
+//!
+//! Text is written into a character grid using [`core::fmt::Write`],
+//! and only the lines that changed are redrawn on [`Console::flush`].
+
+use core::fmt;
+
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyle, MonoTextStyleBuilder},
+    prelude::*,
+    text::{Baseline, Text},
+};
+
+/// A text console with `COLS` columns and `ROWS` rows.
+///
+/// Lines wrap at the last column, `\n` moves to the start of the next line
+/// and `\r` moves to the start of the current line. Writing past the last
+/// line scrolls the console up by one line.
+///
+/// Non-ASCII characters are replaced with `?` and other control characters
+/// are ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use core::fmt::Write;
+///
+/// use ef_st7701s::{color::Rgb565, console::Console};
+/// use embedded_graphics::{mono_font::ascii::FONT_6X10, pixelcolor::RgbColor};
+///
+/// let mut console = Console::<Rgb565, 8, 3>::new(&FONT_6X10, Rgb565::WHITE, Rgb565::BLACK);
+///
+/// // Long lines wrap onto the next line
+/// write!(console, "Hello, World!")?;
+/// assert_eq!(console.line(0), Some("Hello, W"));
+/// assert_eq!(console.line(1), Some("orld!   "));
+/// assert_eq!(console.cursor(), (5, 1));
+///
+/// // A carriage return overwrites the current line
+/// write!(console, "\rEarth\n")?;
+/// assert_eq!(console.line(1), Some("Earth   "));
+/// assert_eq!(console.cursor(), (0, 2));
+///
+/// // Filling the last line exactly does not scroll until more text is written
+/// write!(console, "12345678")?;
+/// assert_eq!(console.line(0), Some("Hello, W"));
+/// assert_eq!(console.cursor(), (8, 2));
+///
+/// // Writing past the last line scrolls everything up
+/// write!(console, "\nDone")?;
+/// assert_eq!(console.line(0), Some("Earth   "));
+/// assert_eq!(console.line(1), Some("12345678"));
+/// assert_eq!(console.line(2), Some("Done    "));
+/// assert_eq!(console.line(3), None);
+/// # Ok::<(), core::fmt::Error>(())
+/// ```
+pub struct Console<'a, C, const COLS: usize, const ROWS: usize> {
+    style: MonoTextStyle<'a, C>,
+    /// The lines of the console, starting at `top`.
+    lines: [[u8; COLS]; ROWS],
+    /// The index of the first visible line in `lines`.
+    top: usize,
+    /// The cursor column, which may be `COLS` until the next character.
+    column: usize,
+    /// The cursor row.
+    row: usize,
+    /// Whether each visible line needs to be redrawn.
+    dirty: [bool; ROWS],
+}
+
+impl<'a, C: PixelColor, const COLS: usize, const ROWS: usize> Console<'a, C, COLS, ROWS> {
+    /// Create a new, empty [`Console`].
+    ///
+    /// Every line starts out dirty, so the first [`Console::flush`]
+    /// clears the area covered by the console.
+    #[must_use]
+    pub fn new(font: &'a MonoFont<'a>, foreground: C, background: C) -> Self {
+        const { assert!(COLS > 0 && ROWS > 0, "Console must have at least one cell") };
+
+        let style = MonoTextStyleBuilder::new()
+            .font(font)
+            .text_color(foreground)
+            .background_color(background);
+        Self {
+            style: style.build(),
+            lines: [[b' '; COLS]; ROWS],
+            top: 0,
+            column: 0,
+            row: 0,
+            dirty: [true; ROWS],
+        }
+    }
+
+    /// Get the cursor position as `(column, row)`.
+    #[inline]
+    #[must_use]
+    pub const fn cursor(&self) -> (usize, usize) { (self.column, self.row) }
+
+    /// Get the text of a visible line, including trailing spaces.
+    ///
+    /// Returns `None` if the row is out of bounds.
+    #[must_use]
+    pub fn line(&self, row: usize) -> Option<&str> {
+        let line = self.lines.get(self.index(row)?)?;
+        // Lines only ever contain ASCII characters.
+        core::str::from_utf8(line).ok()
+    }
+
+    /// Returns `true` if a visible line needs to be redrawn.
+    #[inline]
+    #[must_use]
+    pub fn is_dirty(&self, row: usize) -> bool { self.dirty.get(row).copied().unwrap_or(false) }
+
+    /// Mark every line as dirty, redrawing the whole console on the next flush.
+    pub fn invalidate(&mut self) { self.dirty = [true; ROWS]; }
+
+    /// Clear the console and move the cursor to the top-left corner.
+    pub fn clear(&mut self) {
+        self.lines = [[b' '; COLS]; ROWS];
+        self.top = 0;
+        self.column = 0;
+        self.row = 0;
+        self.invalidate();
+    }
+
+    /// Write a single character to the console.
+    pub fn write_char(&mut self, c: char) {
+        match c {
+            '\n' => self.newline(),
+            '\r' => self.column = 0,
+            c if c.is_ascii_control() => {}
+            c => {
+                if self.column == COLS {
+                    self.newline();
+                }
+
+                let byte = if c.is_ascii() { c as u8 } else { b'?' };
+                let index = (self.top + self.row) % ROWS;
+                self.lines[index][self.column] = byte;
+                self.dirty[self.row] = true;
+                self.column += 1;
+            }
+        }
+    }
+
+    /// Redraw all dirty lines.
+    ///
+    /// Each line is drawn across all `COLS` columns, including the
+    /// background, so nothing needs to be cleared beforehand.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if drawing to the target fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use core::{convert::Infallible, fmt::Write};
+    ///
+    /// use ef_st7701s::{color::Rgb565, console::Console};
+    /// use embedded_graphics::{mono_font::ascii::FONT_6X10, prelude::*};
+    ///
+    /// /// A display that records the rows of every pixel drawn.
+    /// #[derive(Default)]
+    /// struct Recorder(Vec<i32>);
+    ///
+    /// impl OriginDimensions for Recorder {
+    ///     fn size(&self) -> Size { Size::new_equal(480) }
+    /// }
+    ///
+    /// impl DrawTarget for Recorder {
+    ///     type Color = Rgb565;
+    ///     type Error = Infallible;
+    ///
+    ///     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    ///     where
+    ///         I: IntoIterator<Item = Pixel<Self::Color>>,
+    ///     {
+    ///         self.0.extend(pixels.into_iter().map(|Pixel(point, _)| point.y));
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut console = Console::<Rgb565, 80, 48>::new(&FONT_6X10, Rgb565::WHITE, Rgb565::BLACK);
+    /// let mut display = Recorder::default();
+    ///
+    /// // The first flush draws every line
+    /// console.flush(&mut display)?;
+    /// assert_eq!(display.0.len(), 480 * 480);
+    ///
+    /// // Afterwards, only lines that changed are redrawn
+    /// display.0.clear();
+    /// write!(console, "\nBooting...")?;
+    /// console.flush(&mut display)?;
+    /// assert_eq!(display.0.len(), 480 * 10);
+    /// assert!(display.0.iter().all(|y| (10..20).contains(y)));
+    ///
+    /// // Nothing is drawn if nothing changed
+    /// display.0.clear();
+    /// console.flush(&mut display)?;
+    /// assert!(display.0.is_empty());
+    ///
+    /// // Scrolling moves every line
+    /// for _ in 0..48 {
+    ///     writeln!(console)?;
+    /// }
+    /// console.flush(&mut display)?;
+    /// assert_eq!(display.0.len(), 480 * 480);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn flush<D: DrawTarget<Color = C>>(&mut self, display: &mut D) -> Result<(), D::Error> {
+        for row in 0..ROWS {
+            if self.dirty[row] {
+                self.draw_line(row, display)?;
+                self.dirty[row] = false;
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw a single visible line.
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        reason = "Within bounds"
+    )]
+    fn draw_line<D: DrawTarget<Color = C>>(
+        &self,
+        row: usize,
+        display: &mut D,
+    ) -> Result<(), D::Error> {
+        let text = self.line(row).unwrap_or_default();
+        let y = self.style.font.character_size.height as i32 * row as i32;
+        Text::with_baseline(text, Point::new(0, y), self.style, Baseline::Top).draw(display)?;
+        Ok(())
+    }
+
+    /// Move the cursor to the start of the next line, scrolling if needed.
+    fn newline(&mut self) {
+        self.column = 0;
+        if self.row + 1 < ROWS {
+            self.row += 1;
+        } else {
+            // The first line becomes the new last line.
+            self.lines[self.top] = [b' '; COLS];
+            self.top = (self.top + 1) % ROWS;
+            self.invalidate();
+        }
+    }
+
+    /// Get the index in `lines` of a visible row.
+    fn index(&self, row: usize) -> Option<usize> { (row < ROWS).then_some((self.top + row) % ROWS) }
+}
+
+impl<C: PixelColor, const COLS: usize, const ROWS: usize> fmt::Write
+    for Console<'_, C, COLS, ROWS>
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.chars().for_each(|c| self.write_char(c));
+        Ok(())
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        Console::write_char(self, c);
+        Ok(())
+    }
+}
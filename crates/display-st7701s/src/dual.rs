@@ -0,0 +1,408 @@
+//! Two panels side by side, drawn as one logical surface.
+
+use display_interface::AsyncWriteOnlyDataCommand;
+use ef_driver_common::{color::DisplayColor, mode::Async};
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+
+use crate::{PixelSerialize, St7701s, St7701sError};
+
+/// Two panels side by side, such as the halves of a clamshell device,
+/// drawn as one logical surface.
+///
+/// The left panel shows the left part of the surface and the right panel
+/// the rest, with x-coordinates translated to each panel. The right panel
+/// can be mirrored horizontally, for panels mounted facing each other.
+///
+/// Fills that span the seam are split into one fill per panel.
+/// Contiguous fills are passed on as-is when they fit a single panel that
+/// is not mirrored, and are drawn pixel by pixel otherwise.
+///
+/// # Example
+///
+/// ```rust
+/// use core::convert::Infallible;
+///
+/// use ef_st7701s::DualDisplay;
+/// use embedded_graphics_core::{pixelcolor::BinaryColor, prelude::*, primitives::Rectangle};
+///
+/// /// A 4x2 panel that records filled areas and drawn pixels.
+/// #[derive(Default)]
+/// struct Panel(Vec<Rectangle>, Vec<Point>);
+///
+/// impl OriginDimensions for Panel {
+///     fn size(&self) -> Size { Size::new(4, 2) }
+/// }
+///
+/// impl DrawTarget for Panel {
+///     type Color = BinaryColor;
+///     type Error = Infallible;
+///
+///     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Infallible>
+///     where
+///         I: IntoIterator<Item = Pixel<BinaryColor>>,
+///     {
+///         self.1.extend(pixels.into_iter().map(|Pixel(point, _)| point));
+///         Ok(())
+///     }
+///
+///     fn fill_solid(&mut self, area: &Rectangle, _: BinaryColor) -> Result<(), Infallible> {
+///         self.0.push(*area);
+///         Ok(())
+///     }
+/// }
+///
+/// let mut dual = DualDisplay::new(Panel::default(), Panel::default()).with_mirrored(true);
+/// assert_eq!(dual.size(), Size::new(8, 2));
+///
+/// // A fill across the seam becomes one fill per panel
+/// dual.fill_solid(&Rectangle::new(Point::new(1, 0), Size::new(4, 2)), BinaryColor::On)?;
+/// assert_eq!(dual.left().0, [Rectangle::new(Point::new(1, 0), Size::new(3, 2))]);
+/// // The right panel is mirrored, so its first column is on the far side
+/// assert_eq!(dual.right().0, [Rectangle::new(Point::new(3, 0), Size::new(1, 2))]);
+///
+/// // Pixels are routed to their panel, skipping those outside the surface
+/// let pixels = [Point::new(0, 1), Point::new(3, 0), Point::new(4, 0), Point::new(8, 0)];
+/// dual.draw_iter(pixels.map(|point| Pixel(point, BinaryColor::On)))?;
+/// assert_eq!(dual.left().1, [Point::new(0, 1), Point::new(3, 0)]);
+/// assert_eq!(dual.right().1, [Point::new(3, 0)]);
+///
+/// // Contiguous fills across the seam keep their row-major order
+/// let area = Rectangle::new(Point::new(3, 1), Size::new(3, 1));
+/// dual.fill_contiguous(&area, [BinaryColor::On; 3])?;
+/// assert_eq!(dual.left().1[2..], [Point::new(3, 1)]);
+/// assert_eq!(dual.right().1[1..], [Point::new(3, 1), Point::new(2, 1)]);
+/// # Ok::<(), Infallible>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DualDisplay<L, R> {
+    left: L,
+    right: R,
+    mirrored: bool,
+}
+
+impl<L, R> DualDisplay<L, R> {
+    /// Create a new [`DualDisplay`] from a left and a right panel.
+    #[inline]
+    #[must_use]
+    pub const fn new(left: L, right: R) -> Self { Self { left, right, mirrored: false } }
+
+    /// Set whether the right panel is mirrored horizontally.
+    #[inline]
+    #[must_use]
+    pub const fn with_mirrored(mut self, mirrored: bool) -> Self {
+        self.mirrored = mirrored;
+        self
+    }
+
+    /// Returns `true` if the right panel is mirrored horizontally.
+    #[inline]
+    #[must_use]
+    pub const fn is_mirrored(&self) -> bool { self.mirrored }
+
+    /// Get a reference to the left panel.
+    #[inline]
+    #[must_use]
+    pub const fn left(&self) -> &L { &self.left }
+
+    /// Get a mutable reference to the left panel.
+    #[inline]
+    #[must_use]
+    pub const fn left_mut(&mut self) -> &mut L { &mut self.left }
+
+    /// Get a reference to the right panel.
+    #[inline]
+    #[must_use]
+    pub const fn right(&self) -> &R { &self.right }
+
+    /// Get a mutable reference to the right panel.
+    #[inline]
+    #[must_use]
+    pub const fn right_mut(&mut self) -> &mut R { &mut self.right }
+
+    /// Release both panels.
+    #[inline]
+    #[must_use]
+    pub fn release(self) -> (L, R) { (self.left, self.right) }
+}
+
+impl<L: OriginDimensions, R: OriginDimensions> DualDisplay<L, R> {
+    /// Get the area of the surface shown by the left panel.
+    fn left_area(&self) -> Rectangle {
+        Rectangle::new(Point::zero(), Size::new(self.left.size().width, self.size().height))
+    }
+
+    /// Get the area of the surface shown by the right panel.
+    #[expect(clippy::cast_possible_wrap, reason = "Within bounds")]
+    fn right_area(&self) -> Rectangle {
+        let top_left = Point::new(self.left.size().width as i32, 0);
+        Rectangle::new(top_left, Size::new(self.right.size().width, self.size().height))
+    }
+
+    /// Translate an area of the surface inside the right panel
+    /// into the coordinates of the right panel.
+    #[expect(clippy::cast_possible_wrap, reason = "Within bounds")]
+    fn to_right(&self, area: Rectangle) -> Rectangle {
+        let mut top_left = area.top_left - self.right_area().top_left;
+        if self.mirrored {
+            top_left.x = (self.right.size().width as i32) - top_left.x - (area.size.width as i32);
+        }
+        Rectangle::new(top_left, area.size)
+    }
+
+    /// Route a point of the surface to a panel,
+    /// returning `None` if it is outside the surface.
+    fn route(&self, point: Point) -> Option<Side> {
+        let single = |top_left| Rectangle::new(top_left, Size::new(1, 1));
+        if self.left_area().contains(point) {
+            Some(Side::Left(point))
+        } else if self.right_area().contains(point) {
+            Some(Side::Right(self.to_right(single(point)).top_left))
+        } else {
+            None
+        }
+    }
+
+    /// Split an area of the surface into the areas of each panel,
+    /// in the coordinates of each panel.
+    fn split(&self, area: &Rectangle) -> (Rectangle, Rectangle) {
+        let left = area.intersection(&self.left_area());
+        let right = self.to_right(area.intersection(&self.right_area()));
+        (left, right)
+    }
+
+    /// Route a contiguous fill to the only panel it covers,
+    /// if that panel can take its colors in row-major order.
+    fn contiguous(&self, area: &Rectangle) -> Option<Side> {
+        if area.is_zero_sized() {
+            None
+        } else if self.left_area().intersection(area) == *area {
+            Some(Side::Left(area.top_left))
+        } else if !self.mirrored && self.right_area().intersection(area) == *area {
+            Some(Side::Right(self.to_right(*area).top_left))
+        } else {
+            None
+        }
+    }
+}
+
+/// A point or area routed to one of the panels,
+/// in the coordinates of that panel.
+#[derive(Clone, Copy)]
+enum Side {
+    Left(Point),
+    Right(Point),
+}
+
+impl<L: OriginDimensions, R: OriginDimensions> OriginDimensions for DualDisplay<L, R> {
+    /// The combined width of both panels,
+    /// by the height of the shorter panel.
+    fn size(&self) -> Size {
+        let (left, right) = (self.left.size(), self.right.size());
+        Size::new(left.width + right.width, left.height.min(right.height))
+    }
+}
+
+impl<L, R> DrawTarget for DualDisplay<L, R>
+where
+    L: DrawTarget + OriginDimensions,
+    R: DrawTarget<Color = L::Color, Error = L::Error> + OriginDimensions,
+{
+    type Color = L::Color;
+    type Error = L::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            match self.route(point) {
+                Some(Side::Left(point)) => self.left.draw_iter([Pixel(point, color)])?,
+                Some(Side::Right(point)) => self.right.draw_iter([Pixel(point, color)])?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        match self.contiguous(area) {
+            Some(Side::Left(top_left)) => {
+                self.left.fill_contiguous(&Rectangle::new(top_left, area.size), colors)
+            }
+            Some(Side::Right(top_left)) => {
+                self.right.fill_contiguous(&Rectangle::new(top_left, area.size), colors)
+            }
+            None => {
+                let pixels = area.points().zip(colors).map(|(point, color)| Pixel(point, color));
+                self.draw_iter(pixels)
+            }
+        }
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let (left, right) = self.split(area);
+        if !left.is_zero_sized() {
+            self.left.fill_solid(&left, color)?;
+        }
+        if !right.is_zero_sized() {
+            self.right.fill_solid(&right, color)?;
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.left.clear(color)?;
+        self.right.clear(color)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Asynchronous versions of the [`DrawTarget`] methods,
+/// drawing to the left panel and then to the right panel.
+impl<
+    C: DisplayColor + PixelSerialize,
+    LSPI: AsyncWriteOnlyDataCommand,
+    RSPI: AsyncWriteOnlyDataCommand,
+    const LN: usize,
+    const RN: usize,
+> DualDisplay<St7701s<C, LSPI, Async, LN>, St7701s<C, RSPI, Async, RN>>
+{
+    /// Draw individual pixels, skipping pixels outside the surface.
+    ///
+    /// See [`DrawTarget::draw_iter`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: every pixel sets its own address window,
+    /// so the pixels can be drawn again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with either display fails.
+    pub async fn draw_iter<I>(&mut self, pixels: I) -> Result<(), St7701sError>
+    where
+        I: IntoIterator<Item = Pixel<C>>,
+    {
+        for Pixel(point, color) in pixels {
+            match self.route(point) {
+                Some(Side::Left(point)) => self.left.draw_iter([Pixel(point, color)]).await?,
+                Some(Side::Right(point)) => self.right.draw_iter([Pixel(point, color)]).await?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill an area with one color per pixel, in row-major order.
+    ///
+    /// See [`DrawTarget::fill_contiguous`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: one display may be filled and the other not, and
+    /// the next call sets the windows of both again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with either display fails.
+    pub async fn fill_contiguous<I>(
+        &mut self,
+        area: &Rectangle,
+        colors: I,
+    ) -> Result<(), St7701sError>
+    where
+        I: IntoIterator<Item = C>,
+    {
+        match self.contiguous(area) {
+            Some(Side::Left(top_left)) => {
+                self.left.fill_contiguous(&Rectangle::new(top_left, area.size), colors).await
+            }
+            Some(Side::Right(top_left)) => {
+                self.right.fill_contiguous(&Rectangle::new(top_left, area.size), colors).await
+            }
+            None => {
+                let pixels = area.points().zip(colors).map(|(point, color)| Pixel(point, color));
+                self.draw_iter(pixels).await
+            }
+        }
+    }
+
+    /// Fill an area with a single color,
+    /// splitting areas that span the seam into one fill per panel.
+    ///
+    /// See [`DrawTarget::fill_solid`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: every fill sets its own address window,
+    /// so the area can be filled again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with either display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+    /// use ef_st7701s::{DualDisplay, St7701s, color::Rgb565, mode::Async};
+    /// use embedded_graphics_core::{pixelcolor::RgbColor, prelude::*, primitives::Rectangle};
+    /// # use ef_st7701s::test_util::block_on;
+    ///
+    /// /// Records the number of bytes of every data transfer.
+    /// #[derive(Default)]
+    /// struct Recorder(usize);
+    ///
+    /// impl AsyncWriteOnlyDataCommand for Recorder {
+    ///     async fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+    ///
+    ///     async fn send_data(&mut self, dat: DataFormat<'_>) -> Result<(), DisplayError> {
+    ///         let DataFormat::U8(bytes) = dat else { unreachable!() };
+    ///         self.0 += bytes.len();
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// type Panel = St7701s<Rgb565, Recorder, Async, 512>;
+    /// let mut dual = DualDisplay::new(Panel::new(Recorder(0)), Panel::new(Recorder(0)));
+    /// assert_eq!(dual.size(), Size::new(960, 480));
+    ///
+    /// // 10 columns on either side of the seam, of two 9-bit words per pixel
+    /// let area = Rectangle::new(Point::new(470, 0), Size::new(20, 10));
+    /// block_on(dual.fill_solid(&area, Rgb565::BLUE))?;
+    /// assert_eq!(dual.left().spi().0, 10 * 10 * 2 * 9 / 8);
+    /// assert_eq!(dual.right().spi().0, 10 * 10 * 2 * 9 / 8);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub async fn fill_solid(&mut self, area: &Rectangle, color: C) -> Result<(), St7701sError> {
+        let (left, right) = self.split(area);
+        if !left.is_zero_sized() {
+            self.left.fill_solid(&left, color).await?;
+        }
+        if !right.is_zero_sized() {
+            self.right.fill_solid(&right, color).await?;
+        }
+        Ok(())
+    }
+
+    /// Fill both panels with a single color.
+    ///
+    /// See [`DrawTarget::clear`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: every fill sets its own address window,
+    /// so the panels can be cleared again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with either display fails.
+    pub async fn clear(&mut self, color: C) -> Result<(), St7701sError> {
+        self.left.clear(color).await?;
+        self.right.clear(color).await
+    }
+}
@@ -0,0 +1,298 @@
+//! Drawing into a framebuffer in RAM, and flushing only what changed.
+
+use core::{convert::Infallible, ops::Range};
+
+use display_interface::{AsyncWriteOnlyDataCommand, WriteOnlyDataCommand};
+use ef_driver_common::{
+    color::DisplayColor,
+    mode::{Async, Blocking, DriverMode},
+};
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+
+use crate::{ColorFormat, MAX_BYTES_PER_PIXEL, PixelSerialize, St7701s, St7701sError};
+
+/// A [`St7701s`] drawn through a framebuffer in RAM.
+///
+/// Drawing only writes into the framebuffer and never fails, while the
+/// smallest rectangle containing every change is tracked. [`flush`] then
+/// sends that rectangle to the display in a single memory write, instead of
+/// setting an address window for every pixel.
+///
+/// The framebuffer holds every pixel of the panel in row-major order, in
+/// the layout written by [`PixelSerialize`], and must be at least
+/// [`buffer_len`] bytes long. Its size is taken from the panel size and
+/// orientation when created, so changing either afterwards through
+/// [`display_mut`] requires creating a new [`FramebufferedSt7701s`].
+///
+/// [`flush`]: FramebufferedSt7701s::flush
+/// [`buffer_len`]: FramebufferedSt7701s::buffer_len
+/// [`display_mut`]: FramebufferedSt7701s::display_mut
+///
+/// # Example
+///
+/// ```rust
+/// use display_interface::DisplayError;
+/// use ef_st7701s::{
+///     FramebufferedSt7701s, St7701s, color::Rgb565, mode::Blocking, test_util::Recorder,
+/// };
+/// use embedded_graphics_core::{pixelcolor::RgbColor, prelude::*, primitives::Rectangle};
+///
+/// type Display = St7701s<Rgb565, Recorder, Blocking, 64>;
+///
+/// let display = Display::new(Recorder::default()).with_panel_size(8, 4);
+/// let mut buffer = [0u8; 8 * 4 * 2];
+/// let Ok(mut framebuffer) = FramebufferedSt7701s::new(display, &mut buffer) else { panic!() };
+/// assert_eq!(framebuffer.size(), Size::new(8, 4));
+///
+/// // Drawing only changes the framebuffer, and cannot fail
+/// let area = Rectangle::new(Point::new(1, 1), Size::new(2, 2));
+/// let Ok(()) = framebuffer.fill_solid(&area, Rgb565::RED);
+/// let pixels = [Pixel(Point::new(4, 2), Rgb565::BLUE), Pixel(Point::new(9, 0), Rgb565::BLUE)];
+/// let Ok(()) = framebuffer.draw_iter(pixels);
+/// assert!(framebuffer.display().spi().0.is_empty());
+/// assert_eq!(framebuffer.dirty_area(), Some(Rectangle::new(Point::new(1, 1), Size::new(4, 2))));
+///
+/// // Flushing sends the dirty rectangle in a single memory write
+/// framebuffer.flush()?;
+/// assert_eq!(framebuffer.dirty_area(), None);
+///
+/// let mut expected = Display::new(Recorder::default()).with_panel_size(8, 4);
+/// expected.set_address_window(1, 1, 4, 2)?;
+/// let (red, blue, black) = ([0xF8, 0x00], [0x00, 0x1F], [0x00, 0x00]);
+/// let rows = [[red, red, black, black], [red, red, black, blue]];
+/// expected.write_pixels(rows.as_flattened().as_flattened())?;
+///
+/// let (display, _) = framebuffer.release();
+/// assert_eq!(display.release().0, expected.release().0);
+///
+/// // Nothing is sent without changes
+/// let display = Display::new(Recorder::default()).with_panel_size(8, 4);
+/// let Ok(mut framebuffer) = FramebufferedSt7701s::new(display, &mut buffer) else { panic!() };
+/// framebuffer.flush()?;
+/// assert!(framebuffer.display().spi().0.is_empty());
+///
+/// // Framebuffers that are too small are rejected
+/// let display = Display::new(Recorder::default()).with_panel_size(8, 4);
+/// assert!(FramebufferedSt7701s::new(display, &mut buffer[1..]).is_err());
+/// # Ok::<(), DisplayError>(())
+/// ```
+pub struct FramebufferedSt7701s<
+    'a,
+    C: DisplayColor + ColorFormat,
+    SPI,
+    MODE: DriverMode,
+    const N: usize,
+> {
+    display: St7701s<C, SPI, MODE, N>,
+    buffer: &'a mut [u8],
+    size: (u16, u16),
+    dirty: Option<[u16; 4]>,
+}
+
+impl<'a, C: DisplayColor + ColorFormat, SPI, MODE: DriverMode, const N: usize>
+    FramebufferedSt7701s<'a, C, SPI, MODE, N>
+{
+    /// Get the number of bytes needed for the framebuffer of a panel of
+    /// `width` by `height` pixels.
+    #[inline]
+    #[must_use]
+    pub const fn buffer_len(width: u16, height: u16) -> usize {
+        width as usize * height as usize * C::BYTES_PER_PIXEL
+    }
+
+    /// Create a new [`FramebufferedSt7701s`], drawing into `buffer`.
+    ///
+    /// The framebuffer is not cleared, call [`mark_all_dirty`] to send its
+    /// initial contents with the next flush.
+    ///
+    /// [`mark_all_dirty`]: FramebufferedSt7701s::mark_all_dirty
+    ///
+    /// # Errors
+    ///
+    /// Returns the display if `buffer` is shorter than
+    /// [`FramebufferedSt7701s::buffer_len`] for its current dimensions.
+    pub fn new(
+        display: St7701s<C, SPI, MODE, N>,
+        buffer: &'a mut [u8],
+    ) -> Result<Self, St7701s<C, SPI, MODE, N>> {
+        let (width, height) = display.dimensions();
+        if buffer.len() < Self::buffer_len(width, height) {
+            return Err(display);
+        }
+
+        Ok(Self { display, buffer, size: (width, height), dirty: None })
+    }
+
+    /// Get a reference to the display.
+    #[inline]
+    #[must_use]
+    pub const fn display(&self) -> &St7701s<C, SPI, MODE, N> { &self.display }
+
+    /// Get a mutable reference to the display.
+    ///
+    /// Pixels written to the display directly are not tracked,
+    /// and are overwritten by flushes of the same area.
+    #[inline]
+    #[must_use]
+    pub const fn display_mut(&mut self) -> &mut St7701s<C, SPI, MODE, N> { &mut self.display }
+
+    /// Get the contents of the framebuffer.
+    #[inline]
+    #[must_use]
+    pub fn buffer(&self) -> &[u8] { &self.buffer[..Self::buffer_len(self.size.0, self.size.1)] }
+
+    /// Release the display and the framebuffer.
+    #[inline]
+    #[must_use]
+    pub fn release(self) -> (St7701s<C, SPI, MODE, N>, &'a mut [u8]) { (self.display, self.buffer) }
+
+    /// Get the area changed since the last flush, if any.
+    #[must_use]
+    pub fn dirty_area(&self) -> Option<Rectangle> {
+        let [x0, y0, x1, y1] = self.dirty?;
+        let top_left = Point::new(i32::from(x0), i32::from(y0));
+        let bottom_right = Point::new(i32::from(x1), i32::from(y1));
+        Some(Rectangle::with_corners(top_left, bottom_right))
+    }
+
+    /// Mark the entire framebuffer as changed,
+    /// so the next flush sends every pixel.
+    pub const fn mark_all_dirty(&mut self) {
+        if self.size.0 != 0 && self.size.1 != 0 {
+            self.dirty = Some([0, 0, self.size.0 - 1, self.size.1 - 1]);
+        }
+    }
+
+    /// Grow the dirty rectangle to contain the given corners.
+    fn mark_dirty(&mut self, [x0, y0, x1, y1]: [u16; 4]) {
+        self.dirty = Some(match self.dirty {
+            Some([dx0, dy0, dx1, dy1]) => [dx0.min(x0), dy0.min(y0), dx1.max(x1), dy1.max(y1)],
+            None => [x0, y0, x1, y1],
+        });
+    }
+
+    /// Get the range of bytes of row `y`, from column `x0` to `x1` inclusive.
+    const fn row_range(&self, y: u16, x0: u16, x1: u16) -> Range<usize> {
+        row_range::<C>(self.size.0, y, x0, x1)
+    }
+}
+
+/// Get the range of bytes of row `y`, from column `x0` to `x1` inclusive,
+/// in a framebuffer `width` pixels wide.
+const fn row_range<C: ColorFormat>(width: u16, y: u16, x0: u16, x1: u16) -> Range<usize> {
+    let row = y as usize * width as usize;
+    (row + x0 as usize) * C::BYTES_PER_PIXEL..(row + x1 as usize + 1) * C::BYTES_PER_PIXEL
+}
+
+/// Get the bytes of an area of a framebuffer `width` pixels wide, row by row.
+fn area_bytes<C: ColorFormat>(
+    buffer: &[u8],
+    width: u16,
+    [x0, y0, x1, y1]: [u16; 4],
+) -> impl Iterator<Item = u8> {
+    (y0..=y1).flat_map(move |y| buffer[row_range::<C>(width, y, x0, x1)].iter().copied())
+}
+
+impl<C: DisplayColor + ColorFormat, SPI, MODE: DriverMode, const N: usize> OriginDimensions
+    for FramebufferedSt7701s<'_, C, SPI, MODE, N>
+{
+    fn size(&self) -> Size { Size::new(u32::from(self.size.0), u32::from(self.size.1)) }
+}
+
+impl<C: DisplayColor + PixelSerialize, SPI, MODE: DriverMode, const N: usize> DrawTarget
+    for FramebufferedSt7701s<'_, C, SPI, MODE, N>
+{
+    type Color = C;
+    type Error = Infallible;
+
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "Within bounds")]
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = self.bounding_box();
+        for Pixel(point, color) in pixels {
+            // Skip pixels that are out of bounds
+            if !bounds.contains(point) {
+                continue;
+            }
+
+            let (x, y) = (point.x as u16, point.y as u16);
+            let range = self.row_range(y, x, x);
+            if C::write_pixel(color, &mut self.buffer[range]).is_ok() {
+                self.mark_dirty([x, y, x, y]);
+            }
+        }
+        Ok(())
+    }
+
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "Within bounds")]
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let clipped = area.intersection(&self.bounding_box());
+        let Some(bottom_right) = clipped.bottom_right() else { return Ok(()) };
+        let (x0, y0) = (clipped.top_left.x as u16, clipped.top_left.y as u16);
+        let (x1, y1) = (bottom_right.x as u16, bottom_right.y as u16);
+
+        // Serialize the color once and repeat it for every pixel
+        let mut pixel = [0u8; MAX_BYTES_PER_PIXEL];
+        let Ok(len) = C::write_pixel(color, &mut pixel) else { return Ok(()) };
+        for y in y0..=y1 {
+            let range = self.row_range(y, x0, x1);
+            for chunk in self.buffer[range].chunks_exact_mut(len) {
+                chunk.copy_from_slice(&pixel[..len]);
+            }
+        }
+
+        self.mark_dirty([x0, y0, x1, y1]);
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl<C: DisplayColor + PixelSerialize, SPI: WriteOnlyDataCommand, const N: usize>
+    FramebufferedSt7701s<'_, C, SPI, Blocking, N>
+{
+    /// Send the area changed since the last flush to the display.
+    ///
+    /// Does nothing if nothing changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    /// The area stays dirty, so it is sent again by the next flush.
+    pub fn flush(&mut self) -> Result<(), St7701sError> {
+        let Some(dirty @ [x0, y0, x1, y1]) = self.dirty else { return Ok(()) };
+
+        self.display.set_address_window(x0, y0, x1, y1)?;
+        self.display.write_pixels_iter(area_bytes::<C>(self.buffer, self.size.0, dirty))?;
+        self.dirty = None;
+        Ok(())
+    }
+}
+
+impl<C: DisplayColor + PixelSerialize, SPI: AsyncWriteOnlyDataCommand, const N: usize>
+    FramebufferedSt7701s<'_, C, SPI, Async, N>
+{
+    /// Send the area changed since the last flush to the display.
+    ///
+    /// Does nothing if nothing changed.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the area is only marked clean once it was sent,
+    /// so the next flush sends it again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    /// The area stays dirty, so it is sent again by the next flush.
+    pub async fn flush_async(&mut self) -> Result<(), St7701sError> {
+        let Some(dirty @ [x0, y0, x1, y1]) = self.dirty else { return Ok(()) };
+
+        self.display.set_address_window(x0, y0, x1, y1).await?;
+        self.display.write_pixels_iter(area_bytes::<C>(self.buffer, self.size.0, dirty)).await?;
+        self.dirty = None;
+        Ok(())
+    }
+}
@@ -1,41 +1,287 @@
-use display_interface::{DisplayError, WriteOnlyDataCommand};
-use ef_driver_common::{color::DisplayColor, mode::Blocking};
-use embedded_graphics_core::{pixelcolor::raw::ToBytes, prelude::*};
+use display_interface::{AsyncWriteOnlyDataCommand, WriteOnlyDataCommand};
+use ef_driver_common::{
+    color::DisplayColor,
+    mode::{Async, Blocking, DriverMode},
+};
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
 
-use crate::{ColorFormat, St7701s};
+use crate::{ColorFormat, MAX_BYTES_PER_PIXEL, PixelSerialize, St7701s, St7701sError, pixel_bytes};
 
-impl<C: DisplayColor + ColorFormat, SPI: WriteOnlyDataCommand, const N: usize> OriginDimensions
-    for St7701s<C, SPI, Blocking, N>
+impl<C: DisplayColor + ColorFormat, SPI, MODE: DriverMode, const N: usize> OriginDimensions
+    for St7701s<C, SPI, MODE, N>
 {
-    fn size(&self) -> Size { Size::new_equal(480) }
+    fn size(&self) -> Size {
+        let (width, height) = self.dimensions();
+        Size::new(u32::from(width), u32::from(height))
+    }
 }
 
-impl<
-    C: DisplayColor + ColorFormat + ToBytes<Bytes = B>,
-    B: AsRef<[u8]>,
-    SPI: WriteOnlyDataCommand,
-    const N: usize,
-> DrawTarget for St7701s<C, SPI, Blocking, N>
+impl<C: DisplayColor + PixelSerialize, SPI: WriteOnlyDataCommand, const N: usize> DrawTarget
+    for St7701s<C, SPI, Blocking, N>
 {
     type Color = C;
-    type Error = DisplayError;
+    type Error = St7701sError;
 
-    #[expect(unused_variables, reason = "WIP")]
     #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "Within bounds")]
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
-        for pixel in pixels {
+        let bounds = self.bounding_box();
+        let mut drawn = false;
+        for Pixel(point, color) in pixels {
+            // Skip pixels that are out of bounds
+            if !bounds.contains(point) {
+                continue;
+            }
+
+            let (x, y) = (point.x as u16, point.y as u16);
+            let mut buffer = [0u8; MAX_BYTES_PER_PIXEL];
+            let len = C::write_pixel(color, &mut buffer)?;
+            self.set_address_window(x, y, x, y)?;
+            self.send_pixels(&buffer[..len])?;
+            drawn = true;
+        }
+
+        // Count the whole call as a single write
+        if drawn { self.refresh_if_due() } else { Ok(()) }
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let clipped = area.intersection(&self.bounding_box());
+        let Some([x0, y0, x1, y1]) = window(&clipped) else { return Ok(()) };
+        self.set_address_window(x0, y0, x1, y1)?;
+
+        // Only stream the colors of pixels inside the window
+        let colors = area.points().zip(colors).filter(|(point, _)| clipped.contains(*point));
+        self.write_pixels_iter(colors.flat_map(|(_, color)| pixel_bytes(color)))
+    }
+
+    /// Fill an area with a single color, streaming the color through the
+    /// internal buffer of the
+    /// [`CommandDataShifter`](crate::CommandDataShifter).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+    /// use ef_st7701s::{St7701s, color::Rgb565, mode::Blocking};
+    /// use embedded_graphics_core::{pixelcolor::RgbColor, prelude::*, primitives::Rectangle};
+    ///
+    /// /// Records the number of command transfers and the size of every data transfer.
+    /// #[derive(Default)]
+    /// struct Recorder(usize, Vec<usize>);
+    ///
+    /// impl WriteOnlyDataCommand for Recorder {
+    ///     fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+    ///         self.0 += 1;
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn send_data(&mut self, dat: DataFormat<'_>) -> Result<(), DisplayError> {
+    ///         let DataFormat::U8(bytes) = dat else { unreachable!() };
+    ///         self.1.push(bytes.len());
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 512>::new(Recorder::default());
+    ///
+    /// // Clearing the screen sets the window once and streams full buffers
+    /// display.clear(Rgb565::BLUE)?;
+    /// let Recorder(commands, data) = display.spi();
+    /// assert_eq!(*commands, 3);
+    /// // Every transfer fills the buffer, apart from bits carried into the next
+    /// assert!(data[..data.len() - 1].iter().all(|&len| len >= 511));
+    /// // 480 * 480 pixels of two 9-bit words each
+    /// assert_eq!(data.iter().sum::<usize>(), 480 * 480 * 2 * 9 / 8);
+    ///
+    /// // Areas outside the panel are skipped entirely
+    /// *display.spi_mut() = Recorder::default();
+    /// display.fill_solid(&Rectangle::new(Point::new(480, 0), Size::new(10, 10)), Rgb565::RED)?;
+    /// assert_eq!(display.spi().0, 0);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let clipped = area.intersection(&self.bounding_box());
+        let Some([x0, y0, x1, y1]) = window(&clipped) else { return Ok(()) };
+        self.set_address_window(x0, y0, x1, y1)?;
+
+        // Repeat the color bytes for every pixel in the window
+        let mut buffer = [0u8; MAX_BYTES_PER_PIXEL];
+        let len = C::write_pixel(color, &mut buffer)?;
+        let bytes = &buffer[..len];
+        let pixels = clipped.size.width as usize * clipped.size.height as usize;
+        self.write_pixels_iter(bytes.iter().copied().cycle().take(bytes.len() * pixels))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Asynchronous versions of the [`DrawTarget`] methods.
+impl<C: DisplayColor + PixelSerialize, SPI: AsyncWriteOnlyDataCommand, const N: usize>
+    St7701s<C, SPI, Async, N>
+{
+    /// Draw individual pixels, skipping pixels outside the panel.
+    ///
+    /// See [`DrawTarget::draw_iter`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: each pixel gets its own one-pixel window, so the
+    /// pixels drawn before the cancellation are complete and drawing them
+    /// again only repeats them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "Within bounds")]
+    pub async fn draw_iter<I>(&mut self, pixels: I) -> Result<(), St7701sError>
+    where
+        I: IntoIterator<Item = Pixel<C>>,
+    {
+        let bounds = self.bounding_box();
+        let mut drawn = false;
+        for Pixel(point, color) in pixels {
             // Skip pixels that are out of bounds
-            if pixel.0 < Point::zero() || pixel.0 > Point::new_equal(480) {
+            if !bounds.contains(point) {
                 continue;
             }
 
-            let (x, y) = (pixel.0.x as u16, pixel.0.y as u16);
-            let data: B = <C as ToBytes>::to_be_bytes(pixel.1);
-            // self.write_to_address_window(x, y, x, y, data.as_ref())?;
+            let (x, y) = (point.x as u16, point.y as u16);
+            let mut buffer = [0u8; MAX_BYTES_PER_PIXEL];
+            let len = C::write_pixel(color, &mut buffer)?;
+            self.set_address_window(x, y, x, y).await?;
+            self.send_pixels(&buffer[..len]).await?;
+            drawn = true;
         }
-        Ok(())
+
+        // Count the whole call as a single write
+        if drawn { self.refresh_if_due().await } else { Ok(()) }
     }
+
+    /// Fill an area with one color per pixel, in row-major order.
+    ///
+    /// See [`DrawTarget::fill_contiguous`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the window may be partly filled, and the next call
+    /// sets it again before streaming its colors from the first pixel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn fill_contiguous<I>(
+        &mut self,
+        area: &Rectangle,
+        colors: I,
+    ) -> Result<(), St7701sError>
+    where
+        I: IntoIterator<Item = C>,
+    {
+        let clipped = area.intersection(&self.bounding_box());
+        let Some([x0, y0, x1, y1]) = window(&clipped) else { return Ok(()) };
+        self.set_address_window(x0, y0, x1, y1).await?;
+
+        // Only stream the colors of pixels inside the window
+        let colors = area.points().zip(colors).filter(|(point, _)| clipped.contains(*point));
+        self.write_pixels_iter(colors.flat_map(|(_, color)| pixel_bytes(color))).await
+    }
+
+    /// Fill an area with a single color.
+    ///
+    /// See [`DrawTarget::fill_solid`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the area may be partly filled with the color, and
+    /// filling it again covers the whole area.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+    /// use ef_st7701s::{St7701s, color::Rgb565, mode::Async};
+    /// use embedded_graphics_core::{pixelcolor::RgbColor, prelude::*, primitives::Rectangle};
+    /// # use ef_st7701s::test_util::block_on;
+    ///
+    /// /// Records the number of command transfers and the size of every data transfer.
+    /// #[derive(Default)]
+    /// struct Recorder(usize, Vec<usize>);
+    ///
+    /// impl AsyncWriteOnlyDataCommand for Recorder {
+    ///     async fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+    ///         self.0 += 1;
+    ///         Ok(())
+    ///     }
+    ///
+    ///     async fn send_data(&mut self, dat: DataFormat<'_>) -> Result<(), DisplayError> {
+    ///         let DataFormat::U8(bytes) = dat else { unreachable!() };
+    ///         self.1.push(bytes.len());
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Async, 512>::new(Recorder::default());
+    ///
+    /// // Filling a rectangle sets the window once and streams full buffers
+    /// let area = Rectangle::new(Point::new(400, 0), Size::new(100, 100));
+    /// block_on(display.fill_solid(&area, Rgb565::BLUE))?;
+    /// let Recorder(commands, data) = display.spi();
+    /// assert_eq!(*commands, 3);
+    /// assert!(data[..data.len() - 1].iter().all(|&len| len >= 511));
+    /// // Clipped to 80 * 100 pixels of two 9-bit words each
+    /// assert_eq!(data.iter().sum::<usize>(), 80 * 100 * 2 * 9 / 8);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub async fn fill_solid(&mut self, area: &Rectangle, color: C) -> Result<(), St7701sError> {
+        let clipped = area.intersection(&self.bounding_box());
+        let Some([x0, y0, x1, y1]) = window(&clipped) else { return Ok(()) };
+        self.set_address_window(x0, y0, x1, y1).await?;
+
+        // Repeat the color bytes for every pixel in the window
+        let mut buffer = [0u8; MAX_BYTES_PER_PIXEL];
+        let len = C::write_pixel(color, &mut buffer)?;
+        let bytes = &buffer[..len];
+        let pixels = clipped.size.width as usize * clipped.size.height as usize;
+        self.write_pixels_iter(bytes.iter().copied().cycle().take(bytes.len() * pixels)).await
+    }
+
+    /// Fill the entire panel with a single color.
+    ///
+    /// See [`DrawTarget::clear`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the panel may be partly cleared, and clearing it
+    /// again starts over from the top-left corner.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn clear(&mut self, color: C) -> Result<(), St7701sError> {
+        self.fill_solid(&self.bounding_box(), color).await
+    }
+}
+
+/// Get the corners of an area inside the panel as `[x0, y0, x1, y1]`.
+///
+/// Returns `None` if the area is empty.
+#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "Within bounds")]
+fn window(area: &Rectangle) -> Option<[u16; 4]> {
+    let bottom_right = area.bottom_right()?;
+    Some([
+        area.top_left.x as u16,
+        area.top_left.y as u16,
+        bottom_right.x as u16,
+        bottom_right.y as u16,
+    ])
 }
@@ -0,0 +1,253 @@
+//! The command sequence sent by `init`.
+
+use crate::{
+    ColorFormat, DisplayConfig, command, display_command, idle_command, inversion_command,
+};
+
+/// A command of the configuration step of `init`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigCommand {
+    /// `MADCTL`, the [`AddressMode`](crate::AddressMode).
+    AddressMode,
+    /// `INVON` or `INVOFF`, the color inversion.
+    Inversion,
+    /// `WRDISBV`, the display brightness.
+    Brightness,
+    /// `COLMOD`, the pixel format of the [`ColorFormat`].
+    PixelFormat,
+}
+
+impl ConfigCommand {
+    /// The order used by [`InitConfig::new`].
+    pub const DEFAULT_ORDER: [Self; 4] =
+        [Self::AddressMode, Self::Inversion, Self::Brightness, Self::PixelFormat];
+
+    /// Get the command and its parameter, and the number of bytes used.
+    const fn bytes<C: ColorFormat>(self, config: &DisplayConfig) -> ([u8; 2], usize) {
+        match self {
+            Self::AddressMode => {
+                ([command::ST7701S_SET_ADDRESS_MODE, config.address_mode.to_byte()], 2)
+            }
+            Self::Inversion => ([inversion_command(config.inverted), 0], 1),
+            Self::Brightness => ([command::ST7701S_DISPLAY_BRIGHTNESS, config.brightness], 2),
+            Self::PixelFormat => ([command::ST7701S_PIXEL_FORMAT, C::FORMAT_BYTE], 2),
+        }
+    }
+}
+
+/// The configuration of `init_with`, for panels that need different delays
+/// or a different command order than `init`.
+///
+/// The display is initialized in these steps:
+///
+/// 1. A software reset, followed by the `reset` delay.
+/// 2. Exiting sleep mode, followed by the `sleep_out` delay.
+/// 3. The extra command sequence, if any, see
+///    [`InitConfig::with_extra_commands`].
+/// 4. The [`ConfigCommand`]s in a single transfer, followed by the `command`
+///    delay.
+/// 5. Entering normal mode and exiting idle mode, each followed by the
+///    `command` delay.
+/// 6. Turning on the display, followed by the `display_on` delay.
+///
+/// # Example
+///
+/// ```rust
+/// use display_interface::DisplayError;
+/// use ef_st7701s::{
+///     ConfigCommand, DisplayConfig, INIT_DELAY, InitConfig, St7701s,
+///     color::Rgb565,
+///     mode::Blocking,
+///     test_util::{Clock, Recorder, words},
+/// };
+///
+/// // A panel that needs the pixel format first, only 120 ms after a reset,
+/// // and a vendor command with a delay after it
+/// const EXTRA: &[(u8, &[u8])] = &[(0xC0, &[0x3B, 0x00]), (INIT_DELAY, &[20])];
+/// let config = InitConfig::new(DisplayConfig::default())
+///     .with_reset_delay(120)
+///     .with_inverted(true)
+///     .with_order([
+///         ConfigCommand::PixelFormat,
+///         ConfigCommand::AddressMode,
+///         ConfigCommand::Inversion,
+///         ConfigCommand::Brightness,
+///     ])
+///     .with_extra_commands(EXTRA);
+///
+/// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+/// let mut clock = Clock::default();
+/// display.init_with(&config, &mut clock)?;
+/// assert!(display.config().inverted);
+/// assert_eq!(clock.0, [120, 150, 20, 10, 10, 10, 150]);
+///
+/// assert_eq!(
+///     words(&display.release().0),
+///     [
+///         (false, 0x01),
+///         (false, 0x11),
+///         (false, 0xC0),
+///         (true, 0x3B),
+///         (true, 0x00),
+///         (false, 0x3A),
+///         (true, 0x50),
+///         (false, 0x36),
+///         (true, 0x00),
+///         (false, 0x21),
+///         (false, 0x51),
+///         (true, 0x00),
+///         (false, 0x13),
+///         (false, 0x38),
+///         (false, 0x29),
+///     ]
+/// );
+///
+/// // `init` uses the default order without extra commands
+/// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+/// let mut clock = Clock::default();
+/// display.init(DisplayConfig::default(), &mut clock)?;
+/// assert_eq!(clock.0, [150, 150, 10, 10, 10, 150]);
+/// # Ok::<(), DisplayError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InitConfig<'a> {
+    display: DisplayConfig,
+    order: [ConfigCommand; 4],
+    extra: &'a [(u8, &'a [u8])],
+}
+
+impl<'a> InitConfig<'a> {
+    /// Create a new [`InitConfig`] from a [`DisplayConfig`],
+    /// in the default order and without extra commands.
+    #[inline]
+    #[must_use]
+    pub const fn new(display: DisplayConfig) -> Self {
+        Self { display, order: ConfigCommand::DEFAULT_ORDER, extra: &[] }
+    }
+
+    /// Set the delay after the software reset, in milliseconds.
+    #[inline]
+    #[must_use]
+    pub const fn with_reset_delay(mut self, ms: u32) -> Self {
+        self.display.timing.reset = ms;
+        self
+    }
+
+    /// Set the delay after exiting sleep mode, in milliseconds.
+    #[inline]
+    #[must_use]
+    pub const fn with_sleep_out_delay(mut self, ms: u32) -> Self {
+        self.display.timing.sleep_out = ms;
+        self
+    }
+
+    /// Set whether colors are inverted.
+    #[inline]
+    #[must_use]
+    pub const fn with_inverted(mut self, inverted: bool) -> Self {
+        self.display.inverted = inverted;
+        self
+    }
+
+    /// Set the order of the configuration commands.
+    ///
+    /// A command may be repeated in place of another, leaving that one out.
+    #[inline]
+    #[must_use]
+    pub const fn with_order(mut self, order: [ConfigCommand; 4]) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Set a sequence of commands sent after exiting sleep mode,
+    /// before the configuration commands.
+    ///
+    /// The sequence is sent like `St7701s::run_init_sequence`, so entries
+    /// with the [`INIT_DELAY`](crate::INIT_DELAY) command wait instead.
+    #[inline]
+    #[must_use]
+    pub const fn with_extra_commands(mut self, commands: &'a [(u8, &'a [u8])]) -> Self {
+        self.extra = commands;
+        self
+    }
+
+    /// Get the [`DisplayConfig`] applied by `init_with`.
+    #[inline]
+    #[must_use]
+    pub const fn display_config(&self) -> &DisplayConfig { &self.display }
+
+    /// Get the order of the configuration commands.
+    #[inline]
+    #[must_use]
+    pub const fn order(&self) -> [ConfigCommand; 4] { self.order }
+
+    /// Get the steps of the initialization.
+    pub(crate) const fn steps(&self) -> [InitStep<'a>; 7] {
+        let timing = &self.display.timing;
+        [
+            InitStep::Command(command::ST7701S_SOFT_RESET, timing.reset),
+            InitStep::Command(command::ST7701S_SLEEP_EXIT, timing.sleep_out),
+            InitStep::Extra(self.extra),
+            InitStep::Configure(timing.command),
+            InitStep::Command(command::ST7701S_NORMAL_MODE, timing.command),
+            InitStep::Command(idle_command(false), timing.command),
+            InitStep::Command(display_command(true), timing.display_on),
+        ]
+    }
+
+    /// Get the configuration commands, in order.
+    pub(crate) fn commands<C: ColorFormat>(&self) -> impl Iterator<Item = ([u8; 2], usize)> {
+        self.order.into_iter().map(|command| command.bytes::<C>(&self.display))
+    }
+}
+
+impl Default for InitConfig<'_> {
+    fn default() -> Self { Self::new(DisplayConfig::default()) }
+}
+
+impl From<DisplayConfig> for InitConfig<'_> {
+    fn from(display: DisplayConfig) -> Self { Self::new(display) }
+}
+
+/// An entry of a vendor initialization sequence, sent by
+/// `St7701s::run_init_commands`.
+///
+/// Unlike the [`INIT_DELAY`](crate::INIT_DELAY) entries of
+/// `St7701s::run_init_sequence`, delays are separate entries and may be
+/// given in microseconds. See [`vendor`](crate::vendor) for complete
+/// sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InitCommand {
+    /// Send a command with its parameters.
+    Command {
+        /// The command byte.
+        cmd: u8,
+        /// The parameters, sent as data.
+        params: &'static [u8],
+    },
+    /// Wait for a number of milliseconds.
+    DelayMs(u16),
+    /// Wait for a number of microseconds.
+    DelayUs(u16),
+}
+
+impl InitCommand {
+    /// Create an [`InitCommand::Command`].
+    #[inline]
+    #[must_use]
+    pub const fn command(cmd: u8, params: &'static [u8]) -> Self { Self::Command { cmd, params } }
+}
+
+/// A step of the initialization, shared by both driver modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InitStep<'a> {
+    /// Send a command without parameters, then wait.
+    Command(u8, u32),
+    /// Send the extra command sequence.
+    Extra(&'a [(u8, &'a [u8])]),
+    /// Send the configuration commands in a single transfer, then wait.
+    Configure(u32),
+}
@@ -1,20 +1,146 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
 
-use core::marker::PhantomData;
+#[cfg(feature = "test-util")]
+extern crate alloc;
 
-pub use ef_driver_common::color;
-use ef_driver_common::{color::DisplayColor, mode::DriverMode};
+use core::{iter::Peekable, marker::PhantomData};
+
+use display_interface::DisplayError;
+pub use ef_driver_common::{color, mode};
+use ef_driver_common::{
+    color::{DisplayColor, RgbColor},
+    mode::{Async, Blocking, DriverMode},
+};
 
 mod r#async;
+mod bank;
 mod blocking;
 mod command;
+#[cfg(feature = "console")]
+pub mod console;
+#[cfg(feature = "embedded-graphics")]
+mod dual;
+#[cfg(feature = "framebuffer")]
+mod framebuffer;
 #[cfg(feature = "embedded-graphics")]
 mod graphics;
+mod init;
+#[cfg(feature = "mipidsi")]
+mod model;
+mod pattern;
+mod pipeline;
+mod read;
+#[cfg(feature = "splash")]
+pub mod splash;
+mod state;
+#[cfg(feature = "test-util")]
+#[doc(hidden)]
+pub mod test_util;
+mod timing;
+pub mod vendor;
+
+pub use bank::{Bank, GammaCurve, PanelConfig};
+#[cfg(feature = "embedded-graphics")]
+pub use dual::DualDisplay;
+#[cfg(feature = "framebuffer")]
+pub use framebuffer::FramebufferedSt7701s;
+pub use init::{ConfigCommand, InitCommand, InitConfig};
+#[cfg(feature = "mipidsi")]
+pub use model::St7701sModel;
+pub use pattern::TestPattern;
+pub use pipeline::{AsyncOwnedWrite, PipelinedSt7701s};
+pub use read::{AsyncReadDataCommand, DisplayStatus, ReadDataCommand};
+pub use state::DisplayState;
+use state::StateChange;
+pub use timing::TimingProfile;
+
+/// The command that marks a delay in an initialization sequence,
+/// see `St7701s::run_init_sequence`.
+///
+/// This is the `NOP` command, which never takes parameters.
+pub const INIT_DELAY: u8 = command::ST7701S_NOP;
+
+/// The smallest buffer a [`CommandDataShifter`] can use, in bytes.
+///
+/// A command followed by the NOPs that realign it takes up to eight
+/// 9-bit words, which is nine bytes.
+///
+/// # Example
+///
+/// Buffers of any size from the minimum up format commands and data of
+/// any length without panicking, giving the same output as formatting
+/// them in one go.
+///
+/// ```rust
+/// use display_interface::{DataFormat, WriteOnlyDataCommand};
+/// use ef_st7701s::{
+///     CommandDataShifter, Realignment, format_command, format_data, test_util::Recorder,
+/// };
+///
+/// fn check<const N: usize>() {
+///     let mut buffer = [0u8; 64];
+///     for len in 0..32u8 {
+///         let bytes: Vec<u8> = (0..len).map(|i| i.wrapping_mul(37) | 1).collect();
+///
+///         // A command with `len - 1` parameters
+///         let mut shifter = CommandDataShifter::new(Recorder::default(), [0u8; N]);
+///         shifter.send_commands(DataFormat::U8(&bytes)).unwrap();
+///         let expected = format_command(bytes.iter().copied(), &mut buffer).unwrap();
+///         assert_eq!(shifter.release().0, expected, "N = {N}, len = {len}");
+///
+///         // `len` bytes of data
+///         let mut shifter = CommandDataShifter::new(Recorder::default(), [0u8; N]);
+///         shifter.send_data(DataFormat::U8(&bytes)).unwrap();
+///         shifter.realign().unwrap();
+///         let expected = format_data(bytes.iter().copied(), &mut buffer).unwrap();
+///         assert_eq!(shifter.release().0, expected, "N = {N}, len = {len}");
+///
+///         // `len` bytes of data, realigned after every transfer
+///         let mut shifter = CommandDataShifter::new(Recorder::default(), [0u8; N])
+///             .with_realignment(Realignment::Nop);
+///         shifter.send_data(DataFormat::U8(&bytes)).unwrap();
+///         assert!(shifter.is_aligned());
+///     }
+/// }
+///
+/// check::<9>();
+/// check::<10>();
+/// check::<11>();
+/// check::<12>();
+/// check::<13>();
+/// check::<16>();
+/// check::<17>();
+/// check::<18>();
+/// ```
+///
+/// Smaller buffers are rejected at compile time.
+///
+/// ```rust,compile_fail
+/// use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+/// use ef_st7701s::{St7701s, color::Rgb565, mode::Blocking};
+///
+/// struct Interface;
+///
+/// impl WriteOnlyDataCommand for Interface {
+///     fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+///
+///     fn send_data(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+/// }
+///
+/// let display = St7701s::<Rgb565, _, Blocking, 8>::new(Interface);
+/// ```
+pub const MIN_BUFFER_SIZE: usize = 9;
 
 /// A driver for a ST7701S display.
+///
+/// The buffer of `N` bytes must be at least [`MIN_BUFFER_SIZE`] bytes.
 pub struct St7701s<C: DisplayColor + ColorFormat, SPI, MODE: DriverMode, const N: usize> {
     spi: CommandDataShifter<SPI, N>,
+    state: DisplayState,
+    refresh: PeriodicRefresh,
+    size: (u16, u16),
+    offset: (u16, u16),
     _color: PhantomData<C>,
     _mode: PhantomData<MODE>,
 }
@@ -22,6 +148,15 @@ pub struct St7701s<C: DisplayColor + ColorFormat, SPI, MODE: DriverMode, const N
 impl<C: DisplayColor + ColorFormat, SPI, MODE: DriverMode, const N: usize>
     St7701s<C, SPI, MODE, N>
 {
+    /// The default height of the panel, in pixels.
+    ///
+    /// See [`St7701s::with_panel_size`] for panels of other sizes.
+    pub const HEIGHT: u16 = 480;
+    /// The default width of the panel, in pixels.
+    ///
+    /// See [`St7701s::with_panel_size`] for panels of other sizes.
+    pub const WIDTH: u16 = 480;
+
     /// Create a new [`St7701s`] driver instance.
     #[inline]
     #[must_use]
@@ -31,55 +166,1091 @@ impl<C: DisplayColor + ColorFormat, SPI, MODE: DriverMode, const N: usize>
     #[inline]
     #[must_use]
     pub const fn new_with_buffer(spi: SPI, buffer: [u8; N]) -> Self {
-        Self { spi: CommandDataShifter(spi, buffer), _color: PhantomData, _mode: PhantomData }
+        Self {
+            spi: CommandDataShifter::new(spi, buffer),
+            state: DisplayState::new(DisplayConfig::new(AddressMode::DEFAULT)),
+            refresh: PeriodicRefresh { every: 0, count: 0 },
+            size: (Self::WIDTH, Self::HEIGHT),
+            offset: (0, 0),
+            _color: PhantomData,
+            _mode: PhantomData,
+        }
     }
 
     /// Get a reference to the SPI interface.
     #[inline]
     #[must_use]
-    pub const fn spi(&self) -> &SPI { &self.spi.0 }
+    pub const fn spi(&self) -> &SPI { self.spi.spi() }
 
     /// Get a mutable reference to the SPI interface.
     #[inline]
     #[must_use]
-    pub const fn spi_mut(&mut self) -> &mut SPI { &mut self.spi.0 }
+    pub const fn spi_mut(&mut self) -> &mut SPI { self.spi.spi_mut() }
+
+    /// Get a reference to the [`CommandDataShifter`].
+    #[inline]
+    #[must_use]
+    pub const fn shifter(&self) -> &CommandDataShifter<SPI, N> { &self.spi }
+
+    /// Get a mutable reference to the [`CommandDataShifter`].
+    #[inline]
+    #[must_use]
+    pub const fn shifter_mut(&mut self) -> &mut CommandDataShifter<SPI, N> { &mut self.spi }
 
     /// Release the SPI interface.
     #[inline]
     #[must_use]
-    pub fn release(self) -> SPI { self.spi.0 }
+    pub fn release(self) -> SPI { self.spi.release() }
+
+    /// Get the number of whole pixels that fit into every transfer of a
+    /// frame, see [`CommandDataShifter::payload_capacity`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+    /// use ef_st7701s::{
+    ///     Realignment, St7701s,
+    ///     color::{Rgb565, Rgb666, Rgb888},
+    ///     mode::Blocking,
+    /// };
+    ///
+    /// struct Interface;
+    ///
+    /// impl WriteOnlyDataCommand for Interface {
+    ///     fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+    ///
+    ///     fn send_data(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+    /// }
+    ///
+    /// assert_eq!(St7701s::<Rgb565, _, Blocking, 64>::new(Interface).max_chunk_pixels(), 28);
+    /// assert_eq!(St7701s::<Rgb666, _, Blocking, 64>::new(Interface).max_chunk_pixels(), 18);
+    /// assert_eq!(St7701s::<Rgb888, _, Blocking, 512>::new(Interface).max_chunk_pixels(), 151);
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Interface);
+    /// assert_eq!(display.max_chunk_pixels(), 7);
+    /// display.shifter_mut().set_realignment(Realignment::Nop);
+    /// assert_eq!(display.max_chunk_pixels(), 4);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn max_chunk_pixels(&self) -> usize {
+        self.spi.payload_capacity() / C::BYTES_PER_PIXEL
+    }
+
+    /// Format as much pixel data as fits into the internal buffer,
+    /// returning the bytes to transfer.
+    ///
+    /// This allows streaming a frame through a transfer that does not go
+    /// through the `SPI` interface, such as an SPI DMA engine, after starting
+    /// it with `begin_memory_write`. See
+    /// [`CommandDataShifter::format_data_chunk`] for details.
+    #[inline]
+    pub fn format_frame_chunk(&mut self, pixels: &mut impl Iterator<Item = u8>) -> &[u8] {
+        self.spi.format_data_chunk(pixels)
+    }
+
+    /// Create a [`St7701s`] driver instance for a display that is already
+    /// initialized, restoring its cached state from
+    /// [`DisplayConfig::to_bytes`] without sending anything to the panel.
+    ///
+    /// This allows a bootloader to hand the display over to an application,
+    /// possibly switching between [`Blocking`] and [`Async`]:
+    ///
+    /// 1. The bootloader initializes the display and shows a splash screen.
+    /// 2. The bootloader stores [`St7701s::config`] as bytes in a memory region
+    ///    that is retained across the jump.
+    /// 3. The application resumes the driver from those bytes and continues
+    ///    from the cached state, without initializing the display again.
+    ///
+    /// # Errors
+    ///
+    /// Returns the SPI interface if the bytes are not a valid state,
+    /// such as uninitialized memory after a power cycle.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{
+    ///     DisplayConfig, St7701s,
+    ///     color::Rgb565,
+    ///     mode::{Async, Blocking},
+    ///     test_util::Recorder,
+    /// };
+    ///
+    /// // The bootloader configures the display and stashes its state
+    /// let mut bootloader = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// bootloader.set_inverted(true)?;
+    /// bootloader.set_brightness(0x80)?;
+    /// let retained: [u8; DisplayConfig::BYTES] = bootloader.config().to_bytes();
+    ///
+    /// // The application resumes without touching the panel
+    /// type App = St7701s<Rgb565, Recorder, Async, 16>;
+    /// let Ok(app) = App::resume_from_state(Recorder::default(), retained) else {
+    ///     panic!("Invalid display state");
+    /// };
+    /// assert_eq!(app.config(), bootloader.config());
+    /// assert_eq!(app.brightness(), 0x80);
+    /// assert!(app.spi().0.is_empty());
+    ///
+    /// // Switching modes keeps the cached state as well
+    /// let app = app.into_blocking();
+    /// assert_eq!(app.config(), bootloader.into_async().config());
+    ///
+    /// // Memory that was never written is rejected
+    /// assert!(
+    ///     St7701s::<Rgb565, _, Async, 16>::resume_from_state(Recorder::default(), [0; 3]).is_err()
+    /// );
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn resume_from_state(spi: SPI, bytes: [u8; DisplayConfig::BYTES]) -> Result<Self, SPI> {
+        match DisplayConfig::from_bytes(bytes) {
+            Some(config) => {
+                let mut display = Self::new(spi);
+                display.state.apply(StateChange::Initialized(config));
+                Ok(display)
+            }
+            None => Err(spi),
+        }
+    }
+
+    /// Get the cached state of the display.
+    ///
+    /// The interface is write-only, so this is the state last written by the
+    /// driver, starting from the reset values of the display.
+    #[inline]
+    #[must_use]
+    pub const fn config(&self) -> DisplayConfig { self.state.config }
+
+    /// Get the cached runtime state of the display.
+    ///
+    /// The interface is write-only, so this is the state last written by the
+    /// driver. See [`DisplayState`] for persisting it.
+    #[inline]
+    #[must_use]
+    pub const fn display_state(&self) -> &DisplayState { &self.state }
+
+    /// Get the display brightness.
+    ///
+    /// The interface is write-only, so this is the last value written with
+    /// `set_brightness`, starting from the reset value of `0`.
+    #[inline]
+    #[must_use]
+    pub const fn brightness(&self) -> u8 { self.state.config.brightness }
+
+    /// Returns `true` if the display is turned off, such as with `blank`.
+    ///
+    /// The interface is write-only, so this is the state last written by the
+    /// driver, starting from the reset state of the display being off.
+    #[inline]
+    #[must_use]
+    pub const fn is_blanked(&self) -> bool { !self.state.display_on }
+
+    /// Returns `true` if the display is in sleep mode.
+    ///
+    /// The interface is write-only, so this is the state last written with
+    /// `sleep`, `wake` or `power_down`.
+    #[inline]
+    #[must_use]
+    pub const fn is_asleep(&self) -> bool { self.state.asleep }
+
+    /// Get the mode of the tearing effect output.
+    ///
+    /// The interface is write-only, so this is the last mode written with
+    /// `set_tearing_effect`, starting from the reset value of
+    /// [`TearingEffect::Off`].
+    #[inline]
+    #[must_use]
+    pub const fn tearing_effect(&self) -> TearingEffect { self.state.tearing_effect }
+
+    /// Get the width and height of the panel in its current orientation,
+    /// in pixels.
+    ///
+    /// Rows and columns are exchanged in the landscape orientations.
+    #[inline]
+    #[must_use]
+    pub const fn dimensions(&self) -> (u16, u16) {
+        let (width, height) = self.size;
+        if self.state.config.address_mode.exchange_xy { (height, width) } else { (width, height) }
+    }
+
+    /// Set the size of the panel in its default orientation, in pixels.
+    ///
+    /// See [`St7701s::set_panel_size`].
+    #[inline]
+    #[must_use]
+    pub const fn with_panel_size(mut self, width: u16, height: u16) -> Self {
+        self.set_panel_size(width, height);
+        self
+    }
+
+    /// Get the size of the panel in its default orientation, in pixels.
+    ///
+    /// Defaults to [`St7701s::WIDTH`] by [`St7701s::HEIGHT`].
+    #[inline]
+    #[must_use]
+    pub const fn panel_size(&self) -> (u16, u16) { self.size }
+
+    /// Set the size of the panel in its default orientation, in pixels.
+    ///
+    /// The ST7701S drives panels of up to 480 columns and 864 lines, so
+    /// modules that are not square, such as 480x854 panels, need their size
+    /// set for windows to be clipped correctly and for scrolling to cover
+    /// every line.
+    ///
+    /// The number of lines must also be set in the [`PanelConfig`] used to
+    /// initialize the panel.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{Orientation, St7701s, color::Rgb565, mode::Blocking, test_util::Recorder};
+    ///
+    /// type Display = St7701s<Rgb565, Recorder, Blocking, 16>;
+    ///
+    /// /// Get the columns and pages of the last address window that was set.
+    /// fn last_window(display: &mut Display) -> [u16; 4] {
+    ///     let words = display.spi_mut().take_words();
+    ///     let param = |command: u8, index: usize| {
+    ///         let start = words.iter().position(|&word| word == (false, command)).unwrap();
+    ///         let start = start + 1 + 2 * index;
+    ///         u16::from_be_bytes([words[start].1, words[start + 1].1])
+    ///     };
+    ///     [param(0x2A, 0), param(0x2A, 1), param(0x2B, 0), param(0x2B, 1)]
+    /// }
+    ///
+    /// let mut display = Display::new(Recorder::default()).with_panel_size(480, 854);
+    /// assert_eq!(display.dimensions(), (480, 854));
+    ///
+    /// // Draw a pixel at each extreme corner of the panel
+    /// for (x, y) in [(0, 0), (479, 0), (0, 853), (479, 853)] {
+    ///     display.set_address_window(x, y, x, y)?;
+    ///     assert_eq!(last_window(&mut display), [x, x, y, y]);
+    /// }
+    ///
+    /// // Windows are clipped to the panel, not to a square
+    /// display.set_address_window(0, 0, 1000, 1000)?;
+    /// assert_eq!(last_window(&mut display), [0, 479, 0, 853]);
+    /// assert!(display.set_address_window(0, 854, 0, 854).is_err());
+    ///
+    /// // Rows and columns are exchanged in the landscape orientations
+    /// display.set_orientation(Orientation::Landscape)?;
+    /// assert_eq!(display.dimensions(), (854, 480));
+    /// display.set_address_window(853, 479, 2000, 2000)?;
+    /// assert_eq!(last_window(&mut display), [853, 853, 479, 479]);
+    ///
+    /// // A panel offset in the display RAM is added to every window
+    /// display.set_orientation(Orientation::Portrait)?;
+    /// display.set_panel_offset(0, 10);
+    /// display.set_address_window(479, 853, 479, 853)?;
+    /// assert_eq!(last_window(&mut display), [479, 479, 863, 863]);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    #[inline]
+    pub const fn set_panel_size(&mut self, width: u16, height: u16) { self.size = (width, height); }
+
+    /// Set the offset of the panel in the display RAM, in pixels.
+    ///
+    /// See [`St7701s::set_panel_offset`].
+    #[inline]
+    #[must_use]
+    pub const fn with_panel_offset(mut self, x: u16, y: u16) -> Self {
+        self.set_panel_offset(x, y);
+        self
+    }
+
+    /// Get the offset of the panel in the display RAM, in pixels.
+    ///
+    /// Defaults to `(0, 0)`.
+    #[inline]
+    #[must_use]
+    pub const fn panel_offset(&self) -> (u16, u16) { self.offset }
+
+    /// Set the offset of the panel in the display RAM, in pixels.
+    ///
+    /// Some modules connect a panel that is smaller than the display RAM,
+    /// so the visible area does not start at column and page `0`.
+    /// The offset is added to every address window, and is exchanged
+    /// together with the axes in the landscape orientations.
+    #[inline]
+    pub const fn set_panel_offset(&mut self, x: u16, y: u16) { self.offset = (x, y); }
+
+    /// Get the offset of the panel in its current orientation, in pixels.
+    const fn oriented_offset(&self) -> (u16, u16) {
+        let (x, y) = self.offset;
+        if self.state.config.address_mode.exchange_xy { (y, x) } else { (x, y) }
+    }
+
+    /// Get the orientation of the panel.
+    ///
+    /// Returns `None` if the cached address mode mirrors the panel
+    /// in a way that is not a rotation.
+    #[inline]
+    #[must_use]
+    pub const fn orientation(&self) -> Option<Orientation> { self.state.orientation() }
+
+    /// Re-send the cached configuration every `every_n_flushes` flushes.
+    ///
+    /// See [`St7701s::set_periodic_refresh`].
+    #[inline]
+    #[must_use]
+    pub const fn with_periodic_refresh(mut self, every_n_flushes: u32) -> Self {
+        self.set_periodic_refresh(every_n_flushes);
+        self
+    }
+
+    /// Get the number of flushes between configuration refreshes,
+    /// or `0` if periodic refreshes are disabled.
+    #[inline]
+    #[must_use]
+    pub const fn periodic_refresh(&self) -> u32 { self.refresh.every }
+
+    /// Re-send the cached configuration every `every_n_flushes` flushes,
+    /// with `refresh_critical_config`.
+    ///
+    /// A flush is a single `write_pixels` or `write_pixels_iter` call, a
+    /// single `DrawTarget` call however many pixels it draws, or a
+    /// framebuffer flush.
+    ///
+    /// This recovers a panel whose configuration was corrupted by noise on
+    /// the bus, such as on a long FPC cable. A value of `0` disables
+    /// periodic refreshes, which is the default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{St7701s, color::Rgb565, mode::Blocking, test_util::Recorder};
+    /// use embedded_graphics_core::{pixelcolor::RgbColor, prelude::*, primitives::Rectangle};
+    ///
+    /// let mut display =
+    ///     St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default()).with_periodic_refresh(2);
+    ///
+    /// // Drawing two pixels is the first flush, nothing is replayed
+    /// let pixels = [Point::new(0, 0), Point::new(1, 0)].map(|point| Pixel(point, Rgb565::RED));
+    /// display.draw_iter(pixels)?;
+    /// assert_eq!(
+    ///     display.spi_mut().take_words(),
+    ///     [
+    ///         (false, 0x2A),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (false, 0x2B),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (false, 0x2C),
+    ///         (true, 0xF8),
+    ///         (true, 0x00),
+    ///         (false, 0x2A),
+    ///         (true, 0x00),
+    ///         (true, 0x01),
+    ///         (true, 0x00),
+    ///         (true, 0x01),
+    ///         (false, 0x2B),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (false, 0x2C),
+    ///         (true, 0xF8),
+    ///         (true, 0x00),
+    ///     ]
+    /// );
+    ///
+    /// // Filling an area is the second flush, followed by the replay
+    /// display.fill_solid(&Rectangle::new(Point::zero(), Size::new(2, 1)), Rgb565::BLUE)?;
+    /// assert_eq!(
+    ///     display.spi_mut().take_words(),
+    ///     [
+    ///         (false, 0x2A),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x01),
+    ///         (false, 0x2B),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (true, 0x00),
+    ///         (false, 0x2C),
+    ///         (true, 0x00),
+    ///         (true, 0x1F),
+    ///         (true, 0x00),
+    ///         (true, 0x1F),
+    ///         // The replayed configuration
+    ///         (false, 0x36),
+    ///         (true, 0x00),
+    ///         (false, 0x3A),
+    ///         (true, 0x50),
+    ///         (false, 0x20),
+    ///         (false, 0x51),
+    ///         (true, 0x00),
+    ///         (false, 0x28),
+    ///     ]
+    /// );
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    #[inline]
+    pub const fn set_periodic_refresh(&mut self, every_n_flushes: u32) {
+        self.refresh = PeriodicRefresh { every: every_n_flushes, count: 0 };
+    }
+
+    /// Count a flush, returning `true` if the configuration should be
+    /// refreshed after it.
+    const fn refresh_due(&mut self) -> bool {
+        if self.refresh.every == 0 {
+            return false;
+        }
+
+        self.refresh.count += 1;
+        if self.refresh.count < self.refresh.every {
+            return false;
+        }
+        self.refresh.count = 0;
+        true
+    }
+
+    /// Change the [`DriverMode`] of the driver, keeping its buffer,
+    /// any carried bits and its cached state.
+    fn into_mode<NEW: DriverMode>(self) -> St7701s<C, SPI, NEW, N> {
+        St7701s {
+            spi: self.spi,
+            state: self.state,
+            refresh: self.refresh,
+            size: self.size,
+            offset: self.offset,
+            _color: PhantomData,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Create the `SET_COLUMN_ADDR` and `SET_PAGE_ADDR` commands for a window,
+    /// clipping the end coordinates to the panel in its current orientation
+    /// and adding the panel offset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window does not overlap the panel.
+    fn window_commands(
+        &self,
+        x0: u16,
+        y0: u16,
+        x1: u16,
+        y1: u16,
+    ) -> Result<[[u8; 5]; 2], St7701sError> {
+        let (width, height) = self.dimensions();
+        if x0 >= width || y0 >= height || x0 > x1 || y0 > y1 {
+            return Err(DisplayError::OutOfBoundsError.into());
+        }
+        let (x1, y1) = (x1.min(width - 1), y1.min(height - 1));
+
+        let (x_offset, y_offset) = self.oriented_offset();
+        let offset = |value: u16, offset: u16| {
+            value.checked_add(offset).ok_or(DisplayError::OutOfBoundsError)
+        };
+        let (x0, x1) = (offset(x0, x_offset)?, offset(x1, x_offset)?);
+        let (y0, y1) = (offset(y0, y_offset)?, offset(y1, y_offset)?);
+
+        let ([x0h, x0l], [x1h, x1l]) = (x0.to_be_bytes(), x1.to_be_bytes());
+        let ([y0h, y0l], [y1h, y1l]) = (y0.to_be_bytes(), y1.to_be_bytes());
+        Ok([
+            [command::ST7701S_SET_COLUMN_ADDR, x0h, x0l, x1h, x1l],
+            [command::ST7701S_SET_PAGE_ADDR, y0h, y0l, y1h, y1l],
+        ])
+    }
+
+    /// Get the delay of an [`INIT_DELAY`] entry, in milliseconds.
+    ///
+    /// Returns `None` for a `NOP` command without parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the delay is longer than four bytes.
+    fn init_delay(params: &[u8]) -> Result<Option<u32>, St7701sError> {
+        match params.len() {
+            0 => Ok(None),
+            1..=4 => Ok(Some(params.iter().fold(0, |acc, &byte| acc << 8 | u32::from(byte)))),
+            _ => Err(DisplayError::InvalidFormatError.into()),
+        }
+    }
+
+    /// Create the `PARTIAL_AREA` command for a range of rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the range is empty or does not fit the panel.
+    fn partial_area_command(&self, start_row: u16, end_row: u16) -> Result<[u8; 5], St7701sError> {
+        if start_row > end_row || end_row >= self.size.1 {
+            return Err(DisplayError::OutOfBoundsError.into());
+        }
+
+        let ([sh, sl], [eh, el]) = (start_row.to_be_bytes(), end_row.to_be_bytes());
+        Ok([command::ST7701S_PARTIAL_AREA, sh, sl, eh, el])
+    }
+
+    /// Create the `SCROLL_AREA` command for a vertical scrolling layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the areas do not add up to the panel height.
+    fn scroll_area_command(
+        &self,
+        top_fixed: u16,
+        scroll_area: u16,
+        bottom_fixed: u16,
+    ) -> Result<[u8; 7], St7701sError> {
+        let total = u32::from(top_fixed) + u32::from(scroll_area) + u32::from(bottom_fixed);
+        if total != u32::from(self.size.1) {
+            return Err(DisplayError::OutOfBoundsError.into());
+        }
+
+        let ([th, tl], [sh, sl]) = (top_fixed.to_be_bytes(), scroll_area.to_be_bytes());
+        let [bh, bl] = bottom_fixed.to_be_bytes();
+        Ok([command::ST7701S_SCROLL_AREA, th, tl, sh, sl, bh, bl])
+    }
+
+    /// Create the `SCROLL_START` command for a scroll offset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the offset does not fit the panel.
+    fn scroll_start_command(&self, offset: u16) -> Result<[u8; 3], St7701sError> {
+        if offset >= self.size.1 {
+            return Err(DisplayError::OutOfBoundsError.into());
+        }
+
+        let [oh, ol] = offset.to_be_bytes();
+        Ok([command::ST7701S_SCROLL_START, oh, ol])
+    }
+}
+
+impl<C: DisplayColor + ColorFormat, SPI, const N: usize> St7701s<C, SPI, Blocking, N> {
+    /// Convert the driver to [`Async`] mode, without sending anything to
+    /// the panel.
+    #[inline]
+    #[must_use]
+    pub fn into_async(self) -> St7701s<C, SPI, Async, N> { self.into_mode() }
+}
+
+impl<C: DisplayColor + ColorFormat, SPI, const N: usize> St7701s<C, SPI, Async, N> {
+    /// Convert the driver to [`Blocking`] mode, without sending anything to
+    /// the panel.
+    #[inline]
+    #[must_use]
+    pub fn into_blocking(self) -> St7701s<C, SPI, Blocking, N> { self.into_mode() }
+}
+
+/// The state of the periodic configuration refresh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PeriodicRefresh {
+    /// The number of flushes between refreshes, or `0` if disabled.
+    every: u32,
+    /// The number of flushes since the last refresh.
+    count: u32,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// An error that can occur when using the [`St7701s`].
+///
+/// Converts to and from a [`DisplayError`], so it can be used with `?` in
+/// code written against the `display-interface` traits.
+///
+/// # Example
+///
+/// ```rust
+/// use display_interface::DisplayError;
+/// use ef_st7701s::{CommandDataShifter, St7701sError, format_data};
+///
+/// // 8 data bytes take 72 bits, or 9 bytes
+/// let mut buffer = [0u8; 8];
+/// let error = format_data([0xFF; 8].into_iter(), &mut buffer).unwrap_err();
+/// assert!(matches!(error, St7701sError::BufferTooSmall { needed: 9, available: 8 }));
+///
+/// // A command with 15 parameters does not fit into a 16-byte buffer
+/// let mut shifter = CommandDataShifter::new((), [0u8; 16]);
+/// let error = shifter.format_command_chunk(&[0xB0; 16]).unwrap_err();
+/// assert!(matches!(error, St7701sError::BufferTooSmall { needed: 18, available: 16 }));
+///
+/// // Both convert into a `DisplayError`
+/// assert!(matches!(DisplayError::from(error), DisplayError::OutOfBoundsError));
+/// let error = St7701sError::from(DisplayError::BusWriteError);
+/// assert!(matches!(DisplayError::from(error), DisplayError::BusWriteError));
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum St7701sError {
+    /// The formatted output does not fit into a buffer.
+    BufferTooSmall {
+        /// The number of bytes needed.
+        needed: usize,
+        /// The number of bytes available.
+        available: usize,
+    },
+    /// The interface failed, or an argument was rejected.
+    Display(DisplayError),
+}
+
+impl From<DisplayError> for St7701sError {
+    fn from(error: DisplayError) -> Self { Self::Display(error) }
+}
+
+impl From<St7701sError> for DisplayError {
+    fn from(error: St7701sError) -> Self {
+        match error {
+            St7701sError::BufferTooSmall { .. } => DisplayError::OutOfBoundsError,
+            St7701sError::Display(error) => error,
+        }
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
 
-/// The addressing mode of the display.
+/// The configuration applied when initializing the display,
+/// which the driver keeps up to date as its state changes.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DisplayConfig {
+    /// The addressing mode of the display.
+    pub address_mode: AddressMode,
+    /// Whether colors are inverted, as needed by many IPS panels.
+    pub inverted: bool,
+    /// The display brightness, from `0` (darkest) to `255` (brightest).
+    pub brightness: u8,
+    /// The delays waited between commands.
+    ///
+    /// This is not part of the byte-representation,
+    /// so it is reset to [`TimingProfile::DEFAULT`] by
+    /// [`DisplayConfig::from_bytes`].
+    pub timing: TimingProfile,
+}
+
+impl DisplayConfig {
+    /// The length of the byte-representation of a [`DisplayConfig`].
+    pub const BYTES: usize = 3;
+    /// The color order is [`ColorOrder::BGR`].
+    const FLAG_BGR: u8 = 0b0000_0001;
+    /// The rows and columns are exchanged.
+    const FLAG_EXCHANGE_XY: u8 = 0b0010_0000;
+    /// The display refreshes columns backward.
+    const FLAG_HORIZONTAL_REFRESH: u8 = 0b0100_0000;
+    /// The colors are inverted.
+    const FLAG_INVERTED: u8 = 0b0000_0100;
+    /// The columns are mirrored.
+    const FLAG_MIRROR_X: u8 = 0b0000_1000;
+    /// The rows are mirrored.
+    const FLAG_MIRROR_Y: u8 = 0b0001_0000;
+    /// The display refreshes rows backward.
+    const FLAG_REFRESH_DIRECTION: u8 = 0b0000_0010;
+    /// Marks the bytes as a valid [`DisplayConfig`].
+    const MAGIC: u8 = 0x77;
+
+    /// Create a new [`DisplayConfig`] without color inversion,
+    /// at the reset brightness of `0` and with the datasheet delays.
+    #[inline]
+    #[must_use]
+    pub const fn new(address_mode: AddressMode) -> Self {
+        Self { address_mode, inverted: false, brightness: 0, timing: TimingProfile::DEFAULT }
+    }
+
+    /// Set whether colors are inverted.
+    #[inline]
+    #[must_use]
+    pub const fn with_inverted(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// Set the display brightness.
+    #[inline]
+    #[must_use]
+    pub const fn with_brightness(mut self, brightness: u8) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    /// Set the delays waited between commands.
+    ///
+    /// See [`TimingProfile`] for an example.
+    #[inline]
+    #[must_use]
+    pub const fn with_timing(mut self, timing: TimingProfile) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Get the byte-representation of the [`DisplayConfig`].
+    ///
+    /// The layout is fixed: a marker byte, a byte of flags and the
+    /// brightness. The [`TimingProfile`] is not included.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_st7701s::{AddressMode, ColorOrder, DisplayConfig};
+    ///
+    /// for bits in 0..128u8 {
+    ///     for brightness in [0x00, 0x80, 0xFF] {
+    ///         let address_mode = AddressMode {
+    ///             color_order: if bits & 1 == 0 { ColorOrder::RGB } else { ColorOrder::BGR },
+    ///             refresh_direction: bits & 2 != 0,
+    ///             mirror_x: bits & 4 != 0,
+    ///             mirror_y: bits & 8 != 0,
+    ///             exchange_xy: bits & 16 != 0,
+    ///             horizontal_refresh: bits & 32 != 0,
+    ///         };
+    ///         let config = DisplayConfig::new(address_mode)
+    ///             .with_inverted(bits & 64 != 0)
+    ///             .with_brightness(brightness);
+    ///         assert_eq!(DisplayConfig::from_bytes(config.to_bytes()), Some(config));
+    ///     }
+    /// }
+    ///
+    /// let config = DisplayConfig::default().with_inverted(true).with_brightness(0x40);
+    /// assert_eq!(config.to_bytes(), [0x77, 0b0000_0100, 0x40]);
+    ///
+    /// // Missing markers and unknown flags are rejected
+    /// assert_eq!(DisplayConfig::from_bytes([0x00, 0b0000_0100, 0x40]), None);
+    /// assert_eq!(DisplayConfig::from_bytes([0x77, 0b1000_0100, 0x40]), None);
+    /// ```
+    #[must_use]
+    pub const fn to_bytes(self) -> [u8; Self::BYTES] {
+        let mut flags = 0u8;
+        if matches!(self.address_mode.color_order, ColorOrder::BGR) {
+            flags |= Self::FLAG_BGR;
+        }
+        if self.address_mode.refresh_direction {
+            flags |= Self::FLAG_REFRESH_DIRECTION;
+        }
+        if self.address_mode.mirror_x {
+            flags |= Self::FLAG_MIRROR_X;
+        }
+        if self.address_mode.mirror_y {
+            flags |= Self::FLAG_MIRROR_Y;
+        }
+        if self.address_mode.exchange_xy {
+            flags |= Self::FLAG_EXCHANGE_XY;
+        }
+        if self.address_mode.horizontal_refresh {
+            flags |= Self::FLAG_HORIZONTAL_REFRESH;
+        }
+        if self.inverted {
+            flags |= Self::FLAG_INVERTED;
+        }
+        [Self::MAGIC, flags, self.brightness]
+    }
+
+    /// Create a [`DisplayConfig`] from its byte-representation.
+    ///
+    /// Returns `None` if the bytes were not created by
+    /// [`DisplayConfig::to_bytes`].
+    #[must_use]
+    pub const fn from_bytes([magic, flags, brightness]: [u8; Self::BYTES]) -> Option<Self> {
+        const KNOWN: u8 = DisplayConfig::FLAG_BGR
+            | DisplayConfig::FLAG_REFRESH_DIRECTION
+            | DisplayConfig::FLAG_INVERTED
+            | DisplayConfig::FLAG_MIRROR_X
+            | DisplayConfig::FLAG_MIRROR_Y
+            | DisplayConfig::FLAG_EXCHANGE_XY
+            | DisplayConfig::FLAG_HORIZONTAL_REFRESH;
+        if magic != Self::MAGIC || flags & !KNOWN != 0 {
+            return None;
+        }
+
+        let color_order =
+            if flags & Self::FLAG_BGR == 0 { ColorOrder::RGB } else { ColorOrder::BGR };
+        let address_mode = AddressMode {
+            color_order,
+            refresh_direction: flags & Self::FLAG_REFRESH_DIRECTION != 0,
+            mirror_x: flags & Self::FLAG_MIRROR_X != 0,
+            mirror_y: flags & Self::FLAG_MIRROR_Y != 0,
+            exchange_xy: flags & Self::FLAG_EXCHANGE_XY != 0,
+            horizontal_refresh: flags & Self::FLAG_HORIZONTAL_REFRESH != 0,
+        };
+        let inverted = flags & Self::FLAG_INVERTED != 0;
+        Some(Self { address_mode, inverted, brightness, timing: TimingProfile::DEFAULT })
+    }
+}
+
+impl From<AddressMode> for DisplayConfig {
+    fn from(address_mode: AddressMode) -> Self { Self::new(address_mode) }
+}
+
+/// The addressing mode of the display, as set by the `MADCTL` command.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[expect(clippy::struct_excessive_bools, reason = "Each field is a bit of the register")]
 pub struct AddressMode {
     /// The color order of the display.
     pub color_order: ColorOrder,
-    /// Whether the display refreshes forward (false) or backward (true).
+    /// Whether the display refreshes rows top-to-bottom (false) or
+    /// bottom-to-top (true).
     pub refresh_direction: bool,
+    /// Whether columns are mirrored, writing the rightmost column first.
+    pub mirror_x: bool,
+    /// Whether rows are mirrored, writing the bottom row first.
+    pub mirror_y: bool,
+    /// Whether rows and columns are exchanged.
+    pub exchange_xy: bool,
+    /// Whether the display refreshes columns left-to-right (false) or
+    /// right-to-left (true).
+    pub horizontal_refresh: bool,
 }
 
 impl AddressMode {
-    /// Get the byte-representation of the [`AddressMode`].
+    /// RGB/BGR order (`BGR`).
+    const BIT_BGR: u8 = 0b0000_1000;
+    /// Row/column exchange (`MV`).
+    const BIT_EXCHANGE_XY: u8 = 0b0010_0000;
+    /// Horizontal refresh order (`MH`).
+    const BIT_HORIZONTAL_REFRESH: u8 = 0b0000_0100;
+    /// Column address order (`MX`).
+    const BIT_MIRROR_X: u8 = 0b0100_0000;
+    /// Row address order (`MY`).
+    const BIT_MIRROR_Y: u8 = 0b1000_0000;
+    /// Vertical refresh order (`ML`).
+    const BIT_REFRESH_DIRECTION: u8 = 0b0001_0000;
+    /// The default [`AddressMode`].
+    pub const DEFAULT: Self = Self {
+        color_order: ColorOrder::RGB,
+        refresh_direction: false,
+        mirror_x: false,
+        mirror_y: false,
+        exchange_xy: false,
+        horizontal_refresh: false,
+    };
+
+    /// Get the byte-representation of the [`AddressMode`],
+    /// the parameter of the `MADCTL` command.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_st7701s::{AddressMode, ColorOrder};
+    ///
+    /// // The reset value of the display
+    /// assert_eq!(AddressMode::DEFAULT.to_byte(), 0x00);
+    ///
+    /// // Rotated by 180 degrees
+    /// let rotated = AddressMode { mirror_x: true, mirror_y: true, ..AddressMode::DEFAULT };
+    /// assert_eq!(rotated.to_byte(), 0b1100_0000);
+    ///
+    /// // Every combination sets exactly the datasheet bits of its fields
+    /// for bits in 0..64u8 {
+    ///     let mode = AddressMode {
+    ///         color_order: if bits & 1 == 0 { ColorOrder::RGB } else { ColorOrder::BGR },
+    ///         refresh_direction: bits & 2 != 0,
+    ///         mirror_x: bits & 4 != 0,
+    ///         mirror_y: bits & 8 != 0,
+    ///         exchange_xy: bits & 16 != 0,
+    ///         horizontal_refresh: bits & 32 != 0,
+    ///     };
+    ///
+    ///     let expected = [
+    ///         (mode.mirror_y, 0x80),
+    ///         (mode.mirror_x, 0x40),
+    ///         (mode.exchange_xy, 0x20),
+    ///         (mode.refresh_direction, 0x10),
+    ///         (mode.color_order == ColorOrder::BGR, 0x08),
+    ///         (mode.horizontal_refresh, 0x04),
+    ///     ];
+    ///     let expected = expected.iter().filter(|(set, _)| *set).fold(0, |acc, (_, bit)| acc | bit);
+    ///     assert_eq!(mode.to_byte(), expected, "{mode:?}");
+    /// }
+    /// ```
     #[must_use]
     pub const fn to_byte(self) -> u8 {
         let mut byte = 0u8;
-
-        match self.color_order {
-            ColorOrder::RGB => byte &= 0b1111_1011,
-            ColorOrder::BGR => byte |= 0b0000_0100,
+        if self.mirror_y {
+            byte |= Self::BIT_MIRROR_Y;
+        }
+        if self.mirror_x {
+            byte |= Self::BIT_MIRROR_X;
+        }
+        if self.exchange_xy {
+            byte |= Self::BIT_EXCHANGE_XY;
         }
         if self.refresh_direction {
-            byte &= 0b1111_0111;
-        } else {
-            byte |= 0b0000_1000;
+            byte |= Self::BIT_REFRESH_DIRECTION;
+        }
+        if matches!(self.color_order, ColorOrder::BGR) {
+            byte |= Self::BIT_BGR;
         }
+        if self.horizontal_refresh {
+            byte |= Self::BIT_HORIZONTAL_REFRESH;
+        }
+        byte
+    }
+}
+
+/// The orientation of the panel, set with the mirror and exchange bits of
+/// the [`AddressMode`].
+///
+/// The display maps coordinates to the panel, so drawing at `(0, 0)` always
+/// draws in the top-left corner as seen by the user.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Orientation {
+    /// The panel's native orientation.
+    #[default]
+    Portrait,
+    /// Rotated by 180 degrees.
+    PortraitFlipped,
+    /// Rotated by 90 degrees clockwise.
+    Landscape,
+    /// Rotated by 270 degrees clockwise.
+    LandscapeFlipped,
+}
+
+impl Orientation {
+    /// Set the mirror and exchange bits of an [`AddressMode`] for this
+    /// orientation, keeping the color order and refresh directions.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_st7701s::{AddressMode, Orientation};
+    ///
+    /// for orientation in [
+    ///     Orientation::Portrait,
+    ///     Orientation::PortraitFlipped,
+    ///     Orientation::Landscape,
+    ///     Orientation::LandscapeFlipped,
+    /// ] {
+    ///     let mode = orientation.apply(AddressMode::DEFAULT);
+    ///     assert_eq!(Orientation::from_address_mode(mode), Some(orientation));
+    /// }
+    ///
+    /// // The `MX` and `MV` bits of `MADCTL`
+    /// assert_eq!(Orientation::Landscape.apply(AddressMode::DEFAULT).to_byte(), 0b0110_0000);
+    ///
+    /// // Mirroring a single axis is not a rotation
+    /// let mirrored = AddressMode { mirror_x: true, ..AddressMode::DEFAULT };
+    /// assert_eq!(Orientation::from_address_mode(mirrored), None);
+    /// ```
+    #[must_use]
+    pub const fn apply(self, mut mode: AddressMode) -> AddressMode {
+        (mode.mirror_x, mode.mirror_y, mode.exchange_xy) = match self {
+            Orientation::Portrait => (false, false, false),
+            Orientation::PortraitFlipped => (true, true, false),
+            Orientation::Landscape => (true, false, true),
+            Orientation::LandscapeFlipped => (false, true, true),
+        };
+        mode
+    }
+
+    /// Get the orientation set by an [`AddressMode`].
+    ///
+    /// Returns `None` if the address mode mirrors the panel in a way that is
+    /// not a rotation.
+    #[must_use]
+    pub const fn from_address_mode(mode: AddressMode) -> Option<Self> {
+        match (mode.mirror_x, mode.mirror_y, mode.exchange_xy) {
+            (false, false, false) => Some(Orientation::Portrait),
+            (true, true, false) => Some(Orientation::PortraitFlipped),
+            (true, false, true) => Some(Orientation::Landscape),
+            (false, true, true) => Some(Orientation::LandscapeFlipped),
+            _ => None,
+        }
+    }
+}
+
+/// The configuration of the display's brightness control block.
+///
+/// Brightness values are ignored unless brightness control is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BrightnessConfig {
+    /// Whether the brightness control block is enabled.
+    pub brightness_control: bool,
+    /// Whether brightness changes are dimmed in gradually.
+    pub dimming: bool,
+    /// Whether the backlight control output is enabled.
+    pub backlight: bool,
+}
+
+impl BrightnessConfig {
+    /// Brightness control and backlight enabled, without dimming.
+    pub const DEFAULT: Self = Self { brightness_control: true, dimming: false, backlight: true };
+    /// The backlight control output is enabled.
+    const FLAG_BACKLIGHT: u8 = 0b0000_0100;
+    /// The brightness control block is enabled.
+    const FLAG_BRIGHTNESS_CONTROL: u8 = 0b0010_0000;
+    /// Brightness changes are dimmed in gradually.
+    const FLAG_DIMMING: u8 = 0b0000_1000;
+
+    /// Create a new [`BrightnessConfig`] with the default settings.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self { Self::DEFAULT }
+
+    /// Set whether the brightness control block is enabled.
+    #[inline]
+    #[must_use]
+    pub const fn with_brightness_control(mut self, enabled: bool) -> Self {
+        self.brightness_control = enabled;
+        self
+    }
+
+    /// Set whether brightness changes are dimmed in gradually.
+    #[inline]
+    #[must_use]
+    pub const fn with_dimming(mut self, dimming: bool) -> Self {
+        self.dimming = dimming;
+        self
+    }
+
+    /// Set whether the backlight control output is enabled.
+    #[inline]
+    #[must_use]
+    pub const fn with_backlight(mut self, backlight: bool) -> Self {
+        self.backlight = backlight;
+        self
+    }
 
+    /// Get the byte-representation of the [`BrightnessConfig`].
+    #[must_use]
+    pub const fn to_byte(self) -> u8 {
+        let mut byte = 0u8;
+        if self.brightness_control {
+            byte |= Self::FLAG_BRIGHTNESS_CONTROL;
+        }
+        if self.dimming {
+            byte |= Self::FLAG_DIMMING;
+        }
+        if self.backlight {
+            byte |= Self::FLAG_BACKLIGHT;
+        }
         byte
     }
+
+    /// Create a [`BrightnessConfig`] from its byte-representation.
+    ///
+    /// Returns `None` if any other bits are set.
+    #[must_use]
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        const KNOWN: u8 = BrightnessConfig::FLAG_BRIGHTNESS_CONTROL
+            | BrightnessConfig::FLAG_DIMMING
+            | BrightnessConfig::FLAG_BACKLIGHT;
+        if byte & !KNOWN != 0 {
+            return None;
+        }
+        Some(Self {
+            brightness_control: byte & Self::FLAG_BRIGHTNESS_CONTROL != 0,
+            dimming: byte & Self::FLAG_DIMMING != 0,
+            backlight: byte & Self::FLAG_BACKLIGHT != 0,
+        })
+    }
+}
+
+impl Default for BrightnessConfig {
+    fn default() -> Self { Self::DEFAULT }
 }
 
 /// The color order of the display.
@@ -93,89 +1264,803 @@ pub enum ColorOrder {
     BGR,
 }
 
+/// The signal output on the tearing effect (TE) pin of the display.
+///
+/// The pin is high while the panel is not reading from the display's memory,
+/// so writing pixels then does not show a partially updated frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TearingEffect {
+    /// The pin is held low.
+    #[default]
+    Off,
+    /// The pin is high during vertical blanking only.
+    VBlankOnly,
+    /// The pin is high during both vertical and horizontal blanking.
+    VAndHBlank,
+}
+
+impl TearingEffect {
+    /// Get the command that sets the tearing effect output to this mode.
+    const fn command(self) -> &'static [u8] {
+        match self {
+            TearingEffect::Off => &[command::ST7701S_TEARING_EFFECT_OFF],
+            TearingEffect::VBlankOnly => &[command::ST7701S_TEARING_EFFECT_ON, 0x00],
+            TearingEffect::VAndHBlank => &[command::ST7701S_TEARING_EFFECT_ON, 0x01],
+        }
+    }
+}
+
+/// Get the command that turns color inversion on or off.
+const fn inversion_command(inverted: bool) -> u8 {
+    if inverted { command::ST7701S_INVERSION_ON } else { command::ST7701S_INVERSION_OFF }
+}
+
+/// Get the command that turns the display on or off.
+const fn display_command(on: bool) -> u8 {
+    if on { command::ST7701S_DISPLAY_ON } else { command::ST7701S_DISPLAY_OFF }
+}
+
+/// Get the command that enters or exits idle mode.
+const fn idle_command(enabled: bool) -> u8 {
+    if enabled { command::ST7701S_IDLE_ON } else { command::ST7701S_IDLE_OFF }
+}
+
 /// A trait for color formats supported by the [`St7701s`] driver.
 pub trait ColorFormat {
     /// The format byte for the color format.
     const FORMAT_BYTE: u8;
+    /// The number of bytes written for each pixel.
+    const BYTES_PER_PIXEL: usize;
 }
 
 impl ColorFormat for color::Rgb565 {
+    const BYTES_PER_PIXEL: usize = 2;
     const FORMAT_BYTE: u8 = 0b0101_0000;
 }
 impl ColorFormat for color::Rgb666 {
+    const BYTES_PER_PIXEL: usize = 3;
     const FORMAT_BYTE: u8 = 0b0110_0000;
 }
 impl ColorFormat for color::Rgb888 {
+    const BYTES_PER_PIXEL: usize = 3;
     const FORMAT_BYTE: u8 = 0b0111_0000;
 }
 
+/// A trait for writing colors in the pixel layout of the [`St7701s`].
+///
+/// The panel expects every color channel in the most significant bits of a
+/// byte, except for [`Rgb565`](color::Rgb565) which packs a pixel into two
+/// bytes. This differs from the packed layout used by `embedded-graphics`
+/// for [`Rgb666`](color::Rgb666).
+///
+/// # Example
+///
+/// ```rust
+/// use ef_st7701s::{
+///     PixelSerialize, St7701sError,
+///     color::{Rgb565, Rgb666, Rgb888},
+/// };
+///
+/// let mut buffer = [0u8; 3];
+///
+/// // `RRRRRGGG GGGBBBBB`
+/// assert_eq!(Rgb565::write_pixel(Rgb565::new(0b10101, 0b110011, 0b01010), &mut buffer)?, 2);
+/// assert_eq!(buffer[..2], [0b1010_1110, 0b0110_1010]);
+///
+/// // `RRRRRR-- GGGGGG-- BBBBBB--`
+/// assert_eq!(Rgb666::write_pixel(Rgb666::new(0b11_1111, 0b10_0001, 0b01_0101), &mut buffer)?, 3);
+/// assert_eq!(buffer, [0b1111_1100, 0b1000_0100, 0b0101_0100]);
+///
+/// // `RRRRRRRR GGGGGGGG BBBBBBBB`
+/// assert_eq!(Rgb888::write_pixel(Rgb888::new(0x12, 0x34, 0x56), &mut buffer)?, 3);
+/// assert_eq!(buffer, [0x12, 0x34, 0x56]);
+///
+/// // A buffer that is too short is left untouched
+/// let error = Rgb888::write_pixel(Rgb888::new(0xFF, 0xFF, 0xFF), &mut buffer[..2]).unwrap_err();
+/// assert!(matches!(error, St7701sError::BufferTooSmall { needed: 3, available: 2 }));
+/// assert_eq!(buffer, [0x12, 0x34, 0x56]);
+/// # Ok::<(), St7701sError>(())
+/// ```
+pub trait PixelSerialize: ColorFormat + Sized {
+    /// Write `color` to the start of `buffer`,
+    /// returning the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`St7701sError::BufferTooSmall`] if `buffer` is shorter than
+    /// [`ColorFormat::BYTES_PER_PIXEL`].
+    fn write_pixel(color: Self, buffer: &mut [u8]) -> Result<usize, St7701sError>;
+}
+
+impl PixelSerialize for color::Rgb565 {
+    fn write_pixel(color: Self, buffer: &mut [u8]) -> Result<usize, St7701sError> {
+        let raw = u16::from(color.r()) << 11 | u16::from(color.g()) << 5 | u16::from(color.b());
+        pixel_slot::<Self>(buffer)?.copy_from_slice(&raw.to_be_bytes());
+        Ok(2)
+    }
+}
+impl PixelSerialize for color::Rgb666 {
+    fn write_pixel(color: Self, buffer: &mut [u8]) -> Result<usize, St7701sError> {
+        pixel_slot::<Self>(buffer)?.copy_from_slice(&[
+            color.r() << 2,
+            color.g() << 2,
+            color.b() << 2,
+        ]);
+        Ok(3)
+    }
+}
+impl PixelSerialize for color::Rgb888 {
+    fn write_pixel(color: Self, buffer: &mut [u8]) -> Result<usize, St7701sError> {
+        pixel_slot::<Self>(buffer)?.copy_from_slice(&[color.r(), color.g(), color.b()]);
+        Ok(3)
+    }
+}
+
+/// Get the start of `buffer` that holds one pixel of `C`.
+fn pixel_slot<C: ColorFormat>(buffer: &mut [u8]) -> Result<&mut [u8], St7701sError> {
+    let available = buffer.len();
+    buffer
+        .get_mut(..C::BYTES_PER_PIXEL)
+        .ok_or(St7701sError::BufferTooSmall { needed: C::BYTES_PER_PIXEL, available })
+}
+
+/// The largest number of bytes written for a pixel of any [`ColorFormat`].
+pub(crate) const MAX_BYTES_PER_PIXEL: usize = 3;
+
+/// Get the bytes of a pixel, in the layout expected by the panel.
+pub(crate) fn pixel_bytes<C: PixelSerialize>(color: C) -> impl Iterator<Item = u8> + Clone {
+    let mut buffer = [0u8; MAX_BYTES_PER_PIXEL];
+    // Every format fits into the buffer
+    let len = C::write_pixel(color, &mut buffer).unwrap_or_default();
+    buffer.into_iter().take(len)
+}
+
 // -------------------------------------------------------------------------------------------------
 
 /// A wrapper around an SPI interface that prefixes each byte
 /// with either a `0` bit for a command or a `1` bit for data,
 /// shifting bits across byte boundaries as needed.
 ///
-/// When writing unaligned data (non-groups of 8 bytes),
-/// additional NOP commands (`0x00`) are appended to realign the data.
+/// Every nine bytes of output hold eight 9-bit words, so transfers that are
+/// not a multiple of eight words leave bits over at the end. How those bits
+/// are handled is controlled by the [`Realignment`] strategy.
+///
+/// # Example
+///
+/// A `MEMORY_WRITE` split across several chunks does not contain any command
+/// bits after the initial command byte.
+///
+/// ```rust
+/// use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+/// use ef_st7701s::{
+///     CommandDataShifter,
+///     test_util::{Recorder, words},
+/// };
+///
+/// let mut shifter = CommandDataShifter::new(Recorder::default(), [0u8; 16]);
+///
+/// // `MEMORY_WRITE` followed by 100 bytes of pixel data
+/// shifter.send_commands(DataFormat::U8(&[0x2C]))?;
+/// shifter.send_data(DataFormat::U8(&[0xFF; 100]))?;
+/// shifter.realign()?;
+///
+/// // The command is padded with NOPs to the first nine bytes
+/// let bytes = shifter.release().0;
+/// assert_eq!(words(&bytes[..9]), [(false, 0x2C)]);
+/// // The data is one continuous stream
+/// assert_eq!(words(&bytes[9..]), [(true, 0xFF); 100]);
+/// // Only the final realignment appends NOPs, up to 112 words
+/// assert_eq!(bytes.len(), 112 * 9 / 8);
+/// # Ok::<(), DisplayError>(())
+/// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct CommandDataShifter<SPI, const N: usize>(pub SPI, pub [u8; N]);
+pub struct CommandDataShifter<SPI, const N: usize> {
+    spi: SPI,
+    buffer: [u8; N],
+    carry: BitCarry,
+    realignment: Realignment,
+}
 
-/// Format command bytes by properly shifting bits and adding bit prefixes.
+impl<SPI, const N: usize> CommandDataShifter<SPI, N> {
+    /// Fails to compile if the buffer is smaller than [`MIN_BUFFER_SIZE`].
+    const BUFFER_OK: () = assert!(N >= MIN_BUFFER_SIZE, "The buffer must be at least 9 bytes");
+
+    /// Create a new [`CommandDataShifter`] using the default [`Realignment`].
+    ///
+    /// Buffers smaller than [`MIN_BUFFER_SIZE`] fail to compile.
+    #[inline]
+    #[must_use]
+    pub const fn new(spi: SPI, buffer: [u8; N]) -> Self {
+        let () = Self::BUFFER_OK;
+        Self { spi, buffer, carry: BitCarry::EMPTY, realignment: Realignment::Carry }
+    }
+
+    /// Set the [`Realignment`] strategy.
+    #[inline]
+    #[must_use]
+    pub const fn with_realignment(mut self, realignment: Realignment) -> Self {
+        self.realignment = realignment;
+        self
+    }
+
+    /// Get the current [`Realignment`] strategy.
+    #[inline]
+    #[must_use]
+    pub const fn realignment(&self) -> Realignment { self.realignment }
+
+    /// Set the [`Realignment`] strategy.
+    ///
+    /// Any bits carried over from a previous transfer are kept
+    /// and sent with the next transfer.
+    #[inline]
+    pub const fn set_realignment(&mut self, realignment: Realignment) {
+        self.realignment = realignment;
+    }
+
+    /// Returns `true` if no bits are waiting to be sent.
+    #[inline]
+    #[must_use]
+    pub const fn is_aligned(&self) -> bool { self.carry.len == 0 }
+
+    /// Get the number of data bytes that fit into every transfer of a
+    /// stream, using the current [`Realignment`].
+    ///
+    /// Data always follows a command, which realigns the output. With
+    /// [`Realignment::Carry`], up to seven bits are carried into each
+    /// following transfer, leaving room for `N * 8 / 9` bytes. With
+    /// [`Realignment::Nop`], every transfer is padded to whole groups of eight
+    /// words, leaving room for `N / 9 * 8` bytes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_st7701s::{CommandDataShifter, Realignment};
+    ///
+    /// fn capacity<const N: usize>(realignment: Realignment) -> usize {
+    ///     let shifter = CommandDataShifter::new((), [0u8; N]).with_realignment(realignment);
+    ///     let capacity = shifter.payload_capacity();
+    ///
+    ///     // Every chunk after a command holds at least that many bytes
+    ///     let mut shifter = shifter;
+    ///     shifter.format_command_chunk(&[0x2C]).unwrap();
+    ///     let mut pixels = core::iter::repeat_n(0xA5, 1000);
+    ///     while pixels.len() > capacity {
+    ///         let before = pixels.len();
+    ///         assert!(!shifter.format_data_chunk(&mut pixels).is_empty());
+    ///         assert!(before - pixels.len() >= capacity);
+    ///     }
+    ///
+    ///     capacity
+    /// }
+    ///
+    /// assert_eq!(capacity::<9>(Realignment::Carry), 8);
+    /// assert_eq!(capacity::<9>(Realignment::Nop), 8);
+    /// assert_eq!(capacity::<16>(Realignment::Carry), 14);
+    /// assert_eq!(capacity::<16>(Realignment::Nop), 8);
+    /// assert_eq!(capacity::<64>(Realignment::Carry), 56);
+    /// assert_eq!(capacity::<64>(Realignment::Nop), 56);
+    /// assert_eq!(capacity::<100>(Realignment::Carry), 88);
+    /// assert_eq!(capacity::<100>(Realignment::Nop), 88);
+    /// assert_eq!(capacity::<512>(Realignment::Carry), 455);
+    /// assert_eq!(capacity::<512>(Realignment::Nop), 448);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn payload_capacity(&self) -> usize {
+        match self.realignment {
+            Realignment::Carry => N * 8 / 9,
+            Realignment::Nop => N / 9 * 8,
+        }
+    }
+
+    /// Get a reference to the SPI interface.
+    #[inline]
+    #[must_use]
+    pub const fn spi(&self) -> &SPI { &self.spi }
+
+    /// Get a mutable reference to the SPI interface.
+    #[inline]
+    #[must_use]
+    pub const fn spi_mut(&mut self) -> &mut SPI { &mut self.spi }
+
+    /// Release the SPI interface.
+    ///
+    /// Any bits carried over from a previous transfer are discarded.
+    #[inline]
+    #[must_use]
+    pub fn release(self) -> SPI { self.spi }
+
+    /// Start a [`CommandBatch`], packing commands into as few transfers as
+    /// the internal buffer allows.
+    #[inline]
+    pub const fn batch(&mut self) -> CommandBatch<'_, SPI, N> {
+        let carry = self.carry;
+        CommandBatch { shifter: self, length: 0, carry }
+    }
+
+    /// Format as many words as fit into the internal buffer,
+    /// returning the number of bytes to send.
+    ///
+    /// Commands are always realigned once all words have been formatted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer is too small to hold a single word.
+    fn format_chunk<I: Iterator<Item = u8>>(
+        &mut self,
+        words: &mut Words<I>,
+        command: bool,
+    ) -> Result<usize, St7701sError> {
+        let realign = command || matches!(self.realignment, Realignment::Nop);
+        let mut writer = WordWriter::new(self.buffer.as_mut_slice(), self.carry);
+
+        let mut formatted = false;
+        while writer.fits(1, realign) {
+            let Some(word) = words.next() else { break };
+            writer.push(word);
+            formatted = true;
+        }
+
+        // Make sure the transfer always makes progress.
+        if !formatted && !words.is_empty() {
+            return Err(writer.too_small(1, realign));
+        }
+
+        if realign && (words.is_empty() || matches!(self.realignment, Realignment::Nop)) {
+            writer.realign();
+        }
+
+        let (length, carry) = writer.finish();
+        self.carry = carry;
+        Ok(length)
+    }
+
+    /// Format a command and its parameters into the internal buffer,
+    /// returning the bytes to transfer.
+    ///
+    /// Together with [`CommandDataShifter::format_data_chunk`] and
+    /// [`CommandDataShifter::format_realignment`], this formats the same
+    /// output as sending through the `SPI` interface, for transfers that do
+    /// not go through it, such as an SPI DMA engine. The output is realigned
+    /// with NOPs, after any bits carried over from a previous transfer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command does not fit into the buffer.
+    pub fn format_command_chunk(&mut self, command: &[u8]) -> Result<&[u8], St7701sError> {
+        let mut writer = WordWriter::new(self.buffer.as_mut_slice(), self.carry);
+        if !writer.fits(command.len(), true) {
+            return Err(writer.too_small(command.len(), true));
+        }
+
+        for word in Words::command(command.iter().copied()) {
+            writer.push(word);
+        }
+        writer.realign();
+
+        let (length, carry) = writer.finish();
+        self.carry = carry;
+        Ok(&self.buffer[..length])
+    }
+
+    /// Format as much data as fits into the internal buffer,
+    /// returning the bytes to transfer.
+    ///
+    /// Only the bytes that fit are taken from `data`, so it can be passed
+    /// again until an empty chunk is returned. The returned bytes stay valid
+    /// until the shifter is used again, so a DMA transfer must complete
+    /// before the next chunk is formatted.
+    ///
+    /// With [`Realignment::Carry`], leftover bits are kept for the next
+    /// chunk until [`CommandDataShifter::format_realignment`] is called.
+    ///
+    /// # Example
+    ///
+    /// Streaming a frame through a DMA engine sends the same bytes as
+    /// sending it through the `SPI` interface, which is not needed at all.
+    ///
+    /// ```rust
+    /// use display_interface::{DataFormat, WriteOnlyDataCommand};
+    /// use ef_st7701s::{CommandDataShifter, test_util::Recorder};
+    ///
+    /// /// Send a `MEMORY_WRITE` through the interface.
+    /// fn send<const N: usize>(pixels: &[u8]) -> Vec<u8> {
+    ///     let mut shifter = CommandDataShifter::new(Recorder::default(), [0u8; N]);
+    ///     shifter.send_commands(DataFormat::U8(&[0x2C])).unwrap();
+    ///     shifter.send_data(DataFormat::U8(pixels)).unwrap();
+    ///     shifter.realign().unwrap();
+    ///     shifter.release().0
+    /// }
+    ///
+    /// /// Format a `MEMORY_WRITE` for a DMA engine, without an interface.
+    /// fn dma<const N: usize>(pixels: &[u8]) -> Vec<u8> {
+    ///     let mut shifter = CommandDataShifter::new((), [0u8; N]);
+    ///     let mut transfers = Vec::new();
+    ///
+    ///     transfers.extend_from_slice(shifter.format_command_chunk(&[0x2C]).unwrap());
+    ///     let mut pixels = pixels.iter().copied();
+    ///     loop {
+    ///         let chunk = shifter.format_data_chunk(&mut pixels);
+    ///         if chunk.is_empty() {
+    ///             break;
+    ///         }
+    ///         assert!(chunk.len() <= N);
+    ///         transfers.extend_from_slice(chunk);
+    ///     }
+    ///     transfers.extend_from_slice(shifter.format_realignment());
+    ///
+    ///     assert!(shifter.is_aligned());
+    ///     transfers
+    /// }
+    ///
+    /// for length in [0, 1, 7, 8, 9, 100, 961] {
+    ///     let pixels: Vec<u8> = (0..length).map(|i| (i * 37) as u8).collect();
+    ///     assert_eq!(dma::<9>(&pixels), send::<9>(&pixels));
+    ///     assert_eq!(dma::<16>(&pixels), send::<16>(&pixels));
+    ///     assert_eq!(dma::<64>(&pixels), send::<64>(&pixels));
+    /// }
+    /// ```
+    pub fn format_data_chunk(&mut self, data: &mut impl Iterator<Item = u8>) -> &[u8] {
+        let (length, carry) =
+            Self::format_data_into(data, self.buffer.as_mut_slice(), self.carry, self.realignment);
+        self.carry = carry;
+        &self.buffer[..length]
+    }
+
+    /// Format as much data as fits into `buffer` after the `carry` bits,
+    /// returning the length and the bits carried after it.
+    ///
+    /// The carried bits must only be kept once the bytes are handed to the
+    /// interface.
+    fn format_data_into(
+        data: &mut impl Iterator<Item = u8>,
+        buffer: &mut [u8],
+        carry: BitCarry,
+        realignment: Realignment,
+    ) -> (usize, BitCarry) {
+        let realign = matches!(realignment, Realignment::Nop);
+        let mut writer = WordWriter::new(buffer, carry);
+
+        let mut formatted = false;
+        while writer.fits(1, realign) {
+            let Some(byte) = data.next() else { break };
+            writer.push(DATA_PREFIX | u16::from(byte));
+            formatted = true;
+        }
+        if realign && formatted {
+            writer.realign();
+        }
+
+        writer.finish()
+    }
+
+    /// Format any carried over bits followed by NOP commands,
+    /// returning the bytes to transfer.
+    ///
+    /// Returns an empty slice if the output is already aligned.
+    pub fn format_realignment(&mut self) -> &[u8] {
+        let mut writer = WordWriter::new(self.buffer.as_mut_slice(), self.carry);
+        writer.realign();
+
+        let (length, carry) = writer.finish();
+        self.carry = carry;
+        &self.buffer[..length]
+    }
+}
+
+/// Packs several commands into a single transfer of a
+/// [`CommandDataShifter`].
+///
+/// Commands sent one by one are each realigned with NOPs and sent as a
+/// separate transfer. A batch writes the 9-bit words of every command directly
+/// after each other, so command boundaries can fall inside a byte, and only
+/// realigns once the batch is flushed.
+///
+/// When the next command does not fit into the internal buffer, the commands
+/// so far are sent first. Commands that do not fit into an empty buffer are
+/// sent on their own.
+///
+/// Commands that were not flushed when the batch is dropped are never sent.
 ///
 /// # Example
 ///
 /// ```rust
-/// use ef_st7701s::format_command;
+/// use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+/// use ef_st7701s::{CommandDataShifter, test_util::words};
 ///
-/// let mut buffer = [0u8; 10];
+/// /// Records every transfer separately.
+/// #[derive(Default)]
+/// struct Recorder(Vec<Vec<u8>>);
 ///
-/// // `[0b00010010, 0b00110100, 0b01010110]`
-/// let input = [0x12, 0x34, 0x56];
-/// //     v- CMD       v- DATA      v- DATA      v- (CMD + NOP) ...
-/// // `[0b00001001, 0b01001101, 0b00101010, 0b11010000, 0b00100000, ...]`
-/// let output = format_command(input.into_iter(), &mut buffer);
-/// assert_eq!(output, &[0x09, 0x4D, 0x2A, 0xD0, 0x08, 0x04, 0x02, 0x01]);
+/// impl WriteOnlyDataCommand for Recorder {
+///     fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+///         self.send_data(cmd)
+///     }
+///
+///     fn send_data(&mut self, dat: DataFormat<'_>) -> Result<(), DisplayError> {
+///         let DataFormat::U8(bytes) = dat else { unreachable!() };
+///         self.0.push(bytes.to_vec());
+///         Ok(())
+///     }
+/// }
+///
+/// /// Send the commands after some pixel data, one by one or as a batch.
+/// fn send<const N: usize>(commands: &[&[u8]], batch: bool) -> Vec<Vec<u8>> {
+///     let mut shifter = CommandDataShifter::new(Recorder::default(), [0u8; N]);
+///     shifter.send_data(DataFormat::U8(&[0xAB; 3])).unwrap();
+///     if batch {
+///         let mut batch = shifter.batch();
+///         for command in commands {
+///             batch.command(command).unwrap();
+///         }
+///         batch.flush().unwrap();
+///     } else {
+///         for command in commands {
+///             shifter.send_commands(DataFormat::U8(command)).unwrap();
+///         }
+///     }
+///     assert!(shifter.is_aligned());
+///     shifter.release().0
+/// }
+///
+/// let gamma: Vec<u8> = core::iter::once(0xB0).chain(1..=16).collect();
+/// let commands: [&[u8]; 7] =
+///     [&[0x11], &[0x36, 0x00], &[0x21], &[0x51, 0x80], &gamma, &[0x3A, 0x50], &[0x29]];
+///
+/// // The same words are sent in fewer transfers, through any buffer size
+/// for (single, batched) in [
+///     (send::<9>(&commands, false), send::<9>(&commands, true)),
+///     (send::<16>(&commands, false), send::<16>(&commands, true)),
+///     (send::<64>(&commands, false), send::<64>(&commands, true)),
+/// ] {
+///     assert_eq!(words(&single.concat()), words(&batched.concat()));
+///     assert!(batched.len() < single.len());
+/// }
+///
+/// // Everything fits into a 64-byte buffer, after the carried data bits
+/// assert_eq!(send::<64>(&commands, true).len(), 2);
+/// # Ok::<(), DisplayError>(())
 /// ```
-#[doc(hidden)]
-pub fn format_command(mut iter: impl Iterator<Item = u8>, buffer: &mut [u8]) -> &[u8] {
-    buffer.fill(0);
+#[must_use = "commands are only sent once the batch is flushed"]
+pub struct CommandBatch<'a, SPI, const N: usize> {
+    shifter: &'a mut CommandDataShifter<SPI, N>,
+    /// The number of bytes formatted into the buffer.
+    length: usize,
+    /// The bits formatted after the last full byte.
+    carry: BitCarry,
+}
 
-    let mut bit_carry;
-    let mut byte_index = 1usize;
+impl<SPI, const N: usize> CommandBatch<'_, SPI, N> {
+    /// Returns `true` if no commands are waiting to be sent.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool { self.length == 0 }
+
+    /// Append a command and its parameters to the buffer,
+    /// returning `false` if it does not fit.
+    ///
+    /// Every non-empty command fills at least one byte, so the batch is only
+    /// empty until the first command.
+    fn push(&mut self, command: &[u8]) -> bool {
+        let mut writer = WordWriter::new(&mut self.shifter.buffer[self.length..], self.carry);
+        if !writer.fits(command.len(), true) {
+            return false;
+        }
 
-    // The first byte is always prefixed with a `0` bit and shifted
-    if let Some(cmd) = iter.next() {
-        buffer[0] |= cmd >> 1;
-        bit_carry = cmd << 7;
-    } else {
-        return &buffer[..0];
+        for word in Words::command(command.iter().copied()) {
+            writer.push(word);
+        }
+
+        let (length, carry) = writer.finish();
+        self.length += length;
+        self.carry = carry;
+        true
     }
 
-    // Remaining bytes are prefixed with a `1` bit and shifted
-    for byte in iter {
-        let shift = byte_index % 8;
-        buffer[byte_index] |= bit_carry >> shift | byte >> (shift + 1) | 1u8 << (7 - shift);
-        bit_carry = byte << (7 - shift);
-        byte_index += 1;
+    /// Check that a command that does not fit into the buffer
+    /// can be split across transfers.
+    ///
+    /// With [`Realignment::Nop`] every transfer ends with NOPs,
+    /// which would end the command early.
+    fn check_split(&mut self, command: &[u8]) -> Result<(), St7701sError> {
+        match self.shifter.realignment {
+            Realignment::Carry => Ok(()),
+            Realignment::Nop => {
+                let writer = WordWriter::new(&mut self.shifter.buffer, self.carry);
+                Err(writer.too_small(command.len(), true))
+            }
+        }
     }
 
-    if !byte_index.is_multiple_of(8) {
-        // Append the final byte carry
-        buffer[byte_index] = bit_carry | 1u8 << (7 - (byte_index % 8));
-        byte_index += 1;
+    /// Realign the buffer, returning the number of bytes to send.
+    ///
+    /// The batch and the [`CommandDataShifter`] are left empty.
+    fn finish(&mut self) -> usize {
+        let mut writer = WordWriter::new(&mut self.shifter.buffer[self.length..], self.carry);
+        writer.realign();
+        let (length, _) = writer.finish();
+
+        let total = self.length + length;
+        self.length = 0;
+        self.carry = BitCarry::EMPTY;
+        self.shifter.carry = BitCarry::EMPTY;
+        total
+    }
+}
+
+/// How the [`CommandDataShifter`] realigns its output to a byte boundary.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Realignment {
+    /// Append NOP commands (`0x00`) to every transfer.
+    ///
+    /// Every transfer is self-contained, but a NOP ends an ongoing
+    /// `MEMORY_WRITE`, so pixel data must fit in a single transfer.
+    Nop,
+    /// Carry leftover data bits over into the next transfer.
+    ///
+    /// Commands are still followed by NOPs, but data transfers keep their
+    /// trailing bits until the next transfer or until
+    /// [`CommandDataShifter::realign`] is called. This requires the
+    /// underlying interface to keep the display selected between transfers.
+    #[default]
+    Carry,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// The prefix of a 9-bit data word.
+const DATA_PREFIX: u16 = 0x100;
+
+/// Bits left over after the last full byte of a transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BitCarry {
+    /// The leftover bits, right-aligned.
+    bits: u8,
+    /// The number of leftover bits, always less than 8.
+    len: u8,
+}
+
+impl BitCarry {
+    const EMPTY: Self = Self { bits: 0, len: 0 };
+}
+
+/// An iterator over 9-bit words, prefixing the first byte as either
+/// a command or data and every following byte as data.
+struct Words<I: Iterator<Item = u8>> {
+    iter: Peekable<I>,
+    prefix: u16,
+}
+
+impl<I: Iterator<Item = u8>> Words<I> {
+    /// Create an iterator over a command followed by its parameters.
+    fn command(iter: I) -> Self { Self { iter: iter.peekable(), prefix: 0 } }
+
+    /// Create an iterator over data.
+    fn data(iter: I) -> Self { Self { iter: iter.peekable(), prefix: DATA_PREFIX } }
+
+    /// Returns `true` if there are no words left.
+    fn is_empty(&mut self) -> bool { self.iter.peek().is_none() }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Words<I> {
+    type Item = u16;
 
-        // Realign to the next byte-group boundary with NOP commands
-        while !byte_index.is_multiple_of(8) {
-            buffer[byte_index] = 1u8 << (7 - (byte_index % 8));
-            byte_index += 1;
+    fn next(&mut self) -> Option<Self::Item> {
+        let byte = self.iter.next()?;
+        let word = self.prefix | u16::from(byte);
+        self.prefix = DATA_PREFIX;
+        Some(word)
+    }
+}
+
+/// Packs 9-bit words into a byte buffer.
+struct WordWriter<'a> {
+    buffer: &'a mut [u8],
+    index: usize,
+    carry: BitCarry,
+}
+
+impl<'a> WordWriter<'a> {
+    /// Create a new [`WordWriter`], starting with the given carry.
+    const fn new(buffer: &'a mut [u8], carry: BitCarry) -> Self { Self { buffer, index: 0, carry } }
+
+    /// Returns `true` if `words` more words fit into the buffer,
+    /// optionally including the NOPs needed to realign afterwards.
+    const fn fits(&self, words: usize, realign: bool) -> bool {
+        self.needed(words, realign) <= self.buffer.len()
+    }
+
+    /// Get the length of the buffer needed for `words` more words,
+    /// optionally including the NOPs needed to realign afterwards.
+    const fn needed(&self, words: usize, realign: bool) -> usize {
+        let mut bits = self.carry.len as usize + words * 9;
+        if realign {
+            bits += (8 - bits % 8) % 8 * 9;
+        }
+        self.index + bits / 8
+    }
+
+    /// Get a [`St7701sError::BufferTooSmall`] for `words` more words,
+    /// optionally including the NOPs needed to realign afterwards.
+    const fn too_small(&self, words: usize, realign: bool) -> St7701sError {
+        St7701sError::BufferTooSmall {
+            needed: self.needed(words, realign),
+            available: self.buffer.len(),
+        }
+    }
+
+    /// Append a 9-bit word.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the word does not fit into the buffer.
+    #[expect(clippy::cast_possible_truncation, reason = "Only the lowest byte is wanted")]
+    fn push(&mut self, word: u16) {
+        let bits = u32::from(self.carry.bits) << 9 | u32::from(word & 0x1FF);
+        let mut len = self.carry.len + 9;
+
+        while len >= 8 {
+            len -= 8;
+            self.buffer[self.index] = (bits >> len) as u8;
+            self.index += 1;
+        }
+
+        self.carry = BitCarry { bits: (bits & ((1 << len) - 1)) as u8, len };
+    }
+
+    /// Append NOP commands until the output ends on a byte boundary.
+    fn realign(&mut self) {
+        while self.carry.len != 0 {
+            self.push(u16::from(command::ST7701S_NOP));
         }
     }
 
-    &buffer[..byte_index]
+    /// Returns the number of bytes written and the leftover bits.
+    const fn finish(self) -> (usize, BitCarry) { (self.index, self.carry) }
+}
+
+/// Format command bytes by properly shifting bits and adding bit prefixes.
+///
+/// The output is realigned to a byte boundary with NOP commands (`0x00`).
+///
+/// # Errors
+///
+/// Returns [`St7701sError::BufferTooSmall`] if the formatted output does not
+/// fit into the buffer.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_st7701s::format_command;
+///
+/// let mut buffer = [0u8; 10];
+///
+/// // `[0b00010010, 0b00110100, 0b01010110]`
+/// let input = [0x12, 0x34, 0x56];
+/// //     v- CMD       v- DATA      v- DATA      v- (CMD + NOP) ...
+/// // `[0b00001001, 0b01001101, 0b00101010, 0b11000000, 0b00000000, ...]`
+/// let output = format_command(input.into_iter(), &mut buffer).unwrap();
+/// assert_eq!(output, &[0x09, 0x4D, 0x2A, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00]);
+/// ```
+#[doc(hidden)]
+pub fn format_command(
+    iter: impl Iterator<Item = u8>,
+    buffer: &mut [u8],
+) -> Result<&[u8], St7701sError> {
+    format_words(Words::command(iter), buffer)
 }
 
 /// Format data bytes by properly shifting bits and adding byte prefixes.
 ///
+/// The output is realigned to a byte boundary with NOP commands (`0x00`).
+///
+/// # Errors
+///
+/// Returns [`St7701sError::BufferTooSmall`] if the formatted output does not
+/// fit into the buffer.
+///
 /// # Example
 ///
 /// ```rust
@@ -186,44 +2071,82 @@ pub fn format_command(mut iter: impl Iterator<Item = u8>, buffer: &mut [u8]) ->
 /// // `[0b00010010, 0b00110100, 0b01010110]`
 /// let input = [0x12, 0x34, 0x56];
 /// //     v- DATA      v- DATA      v- DATA      v- (CMD + NOP) ...
-/// // `[0b10001001, 0b01001101, 0b00101010, 0b11010000, 0b00100000, ...]`
-/// let output = format_data(input.into_iter(), &mut buffer);
-/// assert_eq!(output, &[0x89, 0x4D, 0x2A, 0xD0, 0x08, 0x04, 0x02, 0x01]);
+/// // `[0b10001001, 0b01001101, 0b00101010, 0b11000000, 0b00000000, ...]`
+/// let output = format_data(input.into_iter(), &mut buffer).unwrap();
+/// assert_eq!(output, &[0x89, 0x4D, 0x2A, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00]);
+/// ```
+///
+/// Every output byte is written whole, so the buffer does not need to be
+/// cleared beforehand.
+///
+/// ```rust
+/// use ef_st7701s::{format_command, format_data};
+///
+/// /// Pack words bit by bit, realigning with NOPs.
+/// fn reference(bytes: &[u8], command: bool) -> Vec<u8> {
+///     let mut bits = Vec::new();
+///     for (index, byte) in bytes.iter().enumerate() {
+///         bits.push(!(command && index == 0));
+///         bits.extend((0..8).rev().map(|i| byte >> i & 1 == 1));
+///     }
+///     while bits.len() % 8 != 0 {
+///         bits.extend([false; 9]);
+///     }
+///     bits.chunks(8).map(|b| b.iter().fold(0u8, |acc, &bit| acc << 1 | u8::from(bit))).collect()
+/// }
+///
+/// // Pseudo-random payloads of every length modulo 8, into dirty buffers
+/// let mut seed = 0x2545_F491u32;
+/// for len in 0..48 {
+///     let payload: Vec<u8> = (0..len)
+///         .map(|_| {
+///             seed ^= seed << 13;
+///             seed ^= seed >> 17;
+///             seed ^= seed << 5;
+///             seed.to_le_bytes()[0]
+///         })
+///         .collect();
+///
+///     for fill in [0x00, 0xA5, 0xFF] {
+///         let mut buffer = [fill; 128];
+///         let output = format_command(payload.iter().copied(), &mut buffer).unwrap();
+///         assert_eq!(output, reference(&payload, true), "command of {len} bytes");
+///
+///         let mut buffer = [fill; 128];
+///         let output = format_data(payload.iter().copied(), &mut buffer).unwrap();
+///         assert_eq!(output, reference(&payload, false), "data of {len} bytes");
+///     }
+/// }
 /// ```
 #[doc(hidden)]
-pub fn format_data(mut iter: impl Iterator<Item = u8>, buffer: &mut [u8]) -> &[u8] {
-    buffer.fill(0);
-
-    let mut bit_carry;
-    let mut byte_index = 1usize;
-
-    // The first byte is always prefixed with a `1` bit and shifted
-    if let Some(cmd) = iter.next() {
-        buffer[0] |= (cmd >> 1) | 0x80;
-        bit_carry = cmd << 7;
-    } else {
-        return &buffer[..0];
-    }
-
-    // Remaining bytes are prefixed with a `1` bit and shifted
-    for byte in iter {
-        let shift = byte_index % 8;
-        buffer[byte_index] |= bit_carry >> shift | byte >> (shift + 1) | 1u8 << (7 - shift);
-        bit_carry = byte << (7 - shift);
-        byte_index += 1;
-    }
-
-    if !byte_index.is_multiple_of(8) {
-        // Append the final byte carry
-        buffer[byte_index] = bit_carry | 1u8 << (7 - (byte_index % 8));
-        byte_index += 1;
+pub fn format_data(
+    iter: impl Iterator<Item = u8>,
+    buffer: &mut [u8],
+) -> Result<&[u8], St7701sError> {
+    format_words(Words::data(iter), buffer)
+}
 
-        // Realign to the next byte-group boundary with NOP commands
-        while !byte_index.is_multiple_of(8) {
-            buffer[byte_index] = 1u8 << (7 - (byte_index % 8));
-            byte_index += 1;
+/// Format words into the buffer and realign the output.
+///
+/// # Errors
+///
+/// Returns an error if the output does not fit into the buffer.
+fn format_words<I: Iterator<Item = u8>>(
+    mut words: Words<I>,
+    buffer: &mut [u8],
+) -> Result<&[u8], St7701sError> {
+    let mut writer = WordWriter::new(buffer, BitCarry::EMPTY);
+    while let Some(word) = words.next() {
+        if !writer.fits(1, false) {
+            return Err(writer.too_small(1 + words.count(), true));
         }
+        writer.push(word);
+    }
+    if !writer.fits(0, true) {
+        return Err(writer.too_small(0, true));
     }
+    writer.realign();
 
-    &buffer[..byte_index]
+    let (length, _) = writer.finish();
+    Ok(&buffer[..length])
 }
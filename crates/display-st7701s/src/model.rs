@@ -0,0 +1,209 @@
+//! A [`mipidsi`] model for the ST7701S.
+
+use core::marker::PhantomData;
+
+use ef_driver_common::color::{Rgb565, RgbColor};
+use embedded_hal::delay::DelayNs;
+use mipidsi::{
+    ConfigurationError,
+    dcs::{InterfaceExt, SetAddressMode, SetInvertMode},
+    interface::{Interface, InterfaceKind},
+    models::{Model, ModelInitError},
+    options::ModelOptions,
+};
+
+use crate::{Bank, ColorFormat, PanelConfig, TimingProfile, command};
+
+/// The ST7701S as a [`mipidsi`] [`Model`], for drivers that share an
+/// interface with other `mipidsi` displays.
+///
+/// [`Model::init`] writes the [`PanelConfig`] to the Command2 banks,
+/// then applies the orientation, color order and inversion of the
+/// [`ModelOptions`]. Delays are taken from the [`TimingProfile`].
+///
+/// # Example
+///
+/// ```rust
+/// use core::convert::Infallible;
+///
+/// use ef_st7701s::{PanelConfig, St7701sModel, color::Rgb666, test_util::Clock};
+/// use mipidsi::{
+///     interface::{Interface, InterfaceKind},
+///     models::Model,
+///     options::{ColorInversion, ColorOrder, ModelOptions, Orientation, Rotation},
+/// };
+///
+/// /// An interface that records every command and its parameters.
+/// #[derive(Default)]
+/// struct Recorder(Vec<(u8, Vec<u8>)>);
+///
+/// impl Interface for Recorder {
+///     type Error = Infallible;
+///     type Word = u8;
+///
+///     const KIND: InterfaceKind = InterfaceKind::Serial4Line;
+///
+///     fn send_command(&mut self, command: u8, args: &[u8]) -> Result<(), Infallible> {
+///         self.0.push((command, args.to_vec()));
+///         Ok(())
+///     }
+///
+///     fn send_pixels<const N: usize>(
+///         &mut self,
+///         _: impl IntoIterator<Item = [u8; N]>,
+///     ) -> Result<(), Infallible> {
+///         Ok(())
+///     }
+///
+///     fn send_repeated_pixel<const N: usize>(
+///         &mut self,
+///         _: [u8; N],
+///         _: u32,
+///     ) -> Result<(), Infallible> {
+///         Ok(())
+///     }
+/// }
+///
+/// let config = PanelConfig::new().with_lines(854).with_vcom(0x40);
+/// let mut model = St7701sModel::<Rgb666>::with_config(config);
+///
+/// let mut options = ModelOptions::full_size::<St7701sModel<Rgb666>>();
+/// options.orientation = Orientation::new().rotate(Rotation::Deg90);
+/// options.color_order = ColorOrder::Bgr;
+/// options.invert_colors = ColorInversion::Inverted;
+///
+/// let (mut interface, mut clock) = (Recorder::default(), Clock::default());
+/// let madctl = model.init(&mut interface, &mut clock, &options).unwrap();
+/// assert_eq!(clock.0.iter().sum::<u32>(), 480);
+///
+/// // The Command2 banks hold the panel configuration
+/// let commands = interface.0;
+/// assert_eq!(commands[0], (0x11, vec![]));
+/// assert_eq!(commands[1], (0xFF, vec![0x77, 0x01, 0x00, 0x00, 0x10]));
+/// assert_eq!(commands[2], (0xC0, vec![0x69, 0x00]));
+/// assert_eq!(commands[7], (0xFF, vec![0x77, 0x01, 0x00, 0x00, 0x11]));
+/// assert_eq!(commands[9], (0xB1, vec![0x40]));
+/// assert_eq!(commands[10], (0xFF, vec![0x77, 0x01, 0x00, 0x00, 0x00]));
+///
+/// // Followed by the options and the pixel format
+/// let mut bytes = [0];
+/// assert_eq!(mipidsi::dcs::DcsCommand::fill_params_buf(&madctl, &mut bytes), 1);
+/// assert_eq!(bytes[0] & 0x08, 0x08);
+/// assert_eq!(commands[11], (0x36, bytes.to_vec()));
+/// assert_eq!(
+///     commands[12..],
+///     [(0x21, vec![]), (0x3A, vec![0x60]), (0x13, vec![]), (0x38, vec![]), (0x29, vec![])]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct St7701sModel<C = Rgb565> {
+    /// The panel configuration written to the Command2 banks.
+    pub config: PanelConfig,
+    /// The delays waited during initialization.
+    pub timing: TimingProfile,
+    _color: PhantomData<C>,
+}
+
+impl<C> St7701sModel<C> {
+    /// Create a new [`St7701sModel`] with the default [`PanelConfig`].
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self { Self::with_config(PanelConfig::DEFAULT) }
+
+    /// Create a new [`St7701sModel`] with a [`PanelConfig`].
+    #[inline]
+    #[must_use]
+    pub const fn with_config(config: PanelConfig) -> Self {
+        Self { config, timing: TimingProfile::DEFAULT, _color: PhantomData }
+    }
+
+    /// Set the delays waited during initialization.
+    #[inline]
+    #[must_use]
+    pub const fn with_timing(mut self, timing: TimingProfile) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Write the [`PanelConfig`] to the Command2 banks,
+    /// returning to [`Bank::Command1`] afterwards.
+    fn write_banks<DI: Interface>(&self, di: &mut DI) -> Result<(), DI::Error> {
+        let (timing, gamma) = self.config.bk0_commands();
+
+        Self::select_bank(di, Bank::Command2Bk0)?;
+        for [cmd, params @ ..] in timing {
+            di.write_raw(cmd, &params)?;
+        }
+        for [cmd, params @ ..] in gamma {
+            di.write_raw(cmd, &params)?;
+        }
+
+        Self::select_bank(di, Bank::Command2Bk1)?;
+        for [cmd, params @ ..] in self.config.bk1_commands() {
+            di.write_raw(cmd, &params)?;
+        }
+
+        Self::select_bank(di, Bank::Command1)
+    }
+
+    /// Select the [`Bank`] that following commands are written to.
+    fn select_bank<DI: Interface>(di: &mut DI, bank: Bank) -> Result<(), DI::Error> {
+        let [cmd, params @ ..] = bank.select_command();
+        di.write_raw(cmd, &params)
+    }
+}
+
+impl<C> Default for St7701sModel<C> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<C: RgbColor + ColorFormat> Model for St7701sModel<C> {
+    type ColorFormat = C;
+
+    /// The ST7701S drives up to 480 columns and 864 lines.
+    const FRAMEBUFFER_SIZE: (u16, u16) = (480, 864);
+
+    fn init<DELAY, DI>(
+        &mut self,
+        di: &mut DI,
+        delay: &mut DELAY,
+        options: &ModelOptions,
+    ) -> Result<SetAddressMode, ModelInitError<DI::Error>>
+    where
+        DELAY: DelayNs,
+        DI: Interface,
+    {
+        if !matches!(
+            DI::KIND,
+            InterfaceKind::Serial4Line | InterfaceKind::Parallel8Bit | InterfaceKind::Parallel16Bit
+        ) {
+            return Err(ModelInitError::InvalidConfiguration(
+                ConfigurationError::UnsupportedInterface,
+            ));
+        }
+
+        let madctl = SetAddressMode::from(options);
+        delay.delay_ms(self.timing.reset);
+
+        di.write_raw(command::ST7701S_SLEEP_EXIT, &[])?;
+        delay.delay_ms(self.timing.sleep_out);
+
+        self.write_banks(di)?;
+
+        di.write_command(madctl)?;
+        di.write_command(SetInvertMode::new(options.invert_colors))?;
+        di.write_raw(command::ST7701S_PIXEL_FORMAT, &[C::FORMAT_BYTE])?;
+        delay.delay_ms(self.timing.command);
+
+        for cmd in [command::ST7701S_NORMAL_MODE, command::ST7701S_IDLE_OFF] {
+            di.write_raw(cmd, &[])?;
+            delay.delay_ms(self.timing.command);
+        }
+
+        di.write_raw(command::ST7701S_DISPLAY_ON, &[])?;
+        delay.delay_ms(self.timing.display_on);
+
+        Ok(madctl)
+    }
+}
@@ -0,0 +1,187 @@
+//! Test patterns for bringing up a panel, without `embedded-graphics`.
+
+use display_interface::{AsyncWriteOnlyDataCommand, WriteOnlyDataCommand};
+use ef_driver_common::{
+    color::{DisplayColor, Rgb888},
+    mode::{Async, Blocking},
+};
+
+use crate::{PixelSerialize, St7701s, St7701sError, pixel_bytes};
+
+/// A test pattern drawn by `St7701s::draw_test_pattern`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TestPattern {
+    /// Eight vertical bars of equal width: white, yellow, cyan, green,
+    /// magenta, red, blue and black.
+    ColorBars,
+    /// A horizontal ramp from black on the left to white on the right.
+    Gradient,
+    /// White and black squares of the given size in pixels,
+    /// starting with white in the top left corner.
+    Checkerboard(u16),
+}
+
+impl TestPattern {
+    /// Get the color of the pattern at `(x, y)` on a panel `width` pixels
+    /// wide.
+    ///
+    /// ```rust
+    /// use ef_st7701s::{
+    ///     TestPattern,
+    ///     color::{Rgb565, RgbColor},
+    /// };
+    ///
+    /// let bars = TestPattern::ColorBars;
+    /// assert_eq!(bars.color_at::<Rgb565>(0, 0, 480), Rgb565::WHITE);
+    /// assert_eq!(bars.color_at::<Rgb565>(60, 479, 480), Rgb565::YELLOW);
+    /// assert_eq!(bars.color_at::<Rgb565>(479, 0, 480), Rgb565::BLACK);
+    ///
+    /// let gradient = TestPattern::Gradient;
+    /// assert_eq!(gradient.color_at::<Rgb565>(0, 0, 480), Rgb565::BLACK);
+    /// assert_eq!(gradient.color_at::<Rgb565>(479, 0, 480), Rgb565::WHITE);
+    ///
+    /// let checkerboard = TestPattern::Checkerboard(10);
+    /// assert_eq!(checkerboard.color_at::<Rgb565>(9, 9, 480), Rgb565::WHITE);
+    /// assert_eq!(checkerboard.color_at::<Rgb565>(10, 9, 480), Rgb565::BLACK);
+    /// assert_eq!(checkerboard.color_at::<Rgb565>(10, 10, 480), Rgb565::WHITE);
+    /// ```
+    #[must_use]
+    #[expect(clippy::manual_is_multiple_of, reason = "Alternates by the parity of the cell")]
+    pub fn color_at<C: DisplayColor + From<Rgb888>>(self, x: u16, y: u16, width: u16) -> C {
+        match self {
+            Self::ColorBars => {
+                const BARS: usize = 8;
+                let bar = usize::from(x) * BARS / usize::from(width.max(1));
+                [C::WHITE, C::YELLOW, C::CYAN, C::GREEN, C::MAGENTA, C::RED, C::BLUE, C::BLACK]
+                    [bar.min(BARS - 1)]
+            }
+            Self::Gradient => {
+                let last = u32::from(width.saturating_sub(1).max(1));
+                let level = u8::try_from(u32::from(x).min(last) * 255 / last).unwrap_or(u8::MAX);
+                C::from(Rgb888::new(level, level, level))
+            }
+            Self::Checkerboard(size) => {
+                let size = size.max(1);
+                if (x / size + y / size) % 2 == 0 { C::WHITE } else { C::BLACK }
+            }
+        }
+    }
+
+    /// Iterate over the bytes of every pixel of the pattern, in row-major
+    /// order.
+    fn bytes<C: DisplayColor + PixelSerialize + From<Rgb888>>(
+        self,
+        width: u16,
+        height: u16,
+    ) -> impl Iterator<Item = u8> {
+        (0..height)
+            .flat_map(move |y| (0..width).map(move |x| self.color_at::<C>(x, y, width)))
+            .flat_map(pixel_bytes)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl<C: DisplayColor + PixelSerialize, SPI: WriteOnlyDataCommand, const N: usize>
+    St7701s<C, SPI, Blocking, N>
+{
+    /// Fill the whole panel with a single color.
+    ///
+    /// The pixels are formatted in chunks that fit into the internal buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub fn fill_screen(&mut self, color: C) -> Result<(), St7701sError> {
+        let (width, height) = self.dimensions();
+        self.set_address_window(0, 0, width.saturating_sub(1), height.saturating_sub(1))?;
+
+        let pixels = usize::from(width) * usize::from(height);
+        self.write_pixels_iter(pixel_bytes(color).cycle().take(C::BYTES_PER_PIXEL * pixels))
+    }
+
+    /// Draw a [`TestPattern`] over the whole panel.
+    ///
+    /// The pixels are formatted in chunks that fit into the internal buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{St7701s, TestPattern, color::Rgb565, mode::Blocking, test_util::Recorder};
+    ///
+    /// // A small panel streamed through the smallest buffer
+    /// let mut display =
+    ///     St7701s::<Rgb565, _, Blocking, 9>::new(Recorder::default()).with_panel_size(16, 4);
+    /// display.draw_test_pattern(TestPattern::Checkerboard(2))?;
+    ///
+    /// let words = display.release().words();
+    /// // The window covers the whole panel
+    /// assert_eq!(words[..5], [(false, 0x2A), (true, 0), (true, 0), (true, 0), (true, 15)]);
+    /// assert_eq!(words[5..10], [(false, 0x2B), (true, 0), (true, 0), (true, 0), (true, 3)]);
+    /// // Two white pixels, then black pixels
+    /// assert_eq!(
+    ///     words[10..16],
+    ///     [(false, 0x2C), (true, 0xFF), (true, 0xFF), (true, 0xFF), (true, 0xFF), (true, 0x00)]
+    /// );
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn draw_test_pattern(&mut self, pattern: TestPattern) -> Result<(), St7701sError>
+    where
+        C: From<Rgb888>,
+    {
+        let (width, height) = self.dimensions();
+        self.set_address_window(0, 0, width.saturating_sub(1), height.saturating_sub(1))?;
+        self.write_pixels_iter(pattern.bytes::<C>(width, height))
+    }
+}
+
+impl<C: DisplayColor + PixelSerialize, SPI: AsyncWriteOnlyDataCommand, const N: usize>
+    St7701s<C, SPI, Async, N>
+{
+    /// Fill the whole panel with a single color.
+    ///
+    /// The pixels are formatted in chunks that fit into the internal buffer.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the address window is set again by every call,
+    /// so the panel can be filled again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn fill_screen(&mut self, color: C) -> Result<(), St7701sError> {
+        let (width, height) = self.dimensions();
+        self.set_address_window(0, 0, width.saturating_sub(1), height.saturating_sub(1)).await?;
+
+        let pixels = usize::from(width) * usize::from(height);
+        self.write_pixels_iter(pixel_bytes(color).cycle().take(C::BYTES_PER_PIXEL * pixels)).await
+    }
+
+    /// Draw a [`TestPattern`] over the whole panel.
+    ///
+    /// The pixels are formatted in chunks that fit into the internal buffer.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the address window is set again by every call,
+    /// so the pattern can be drawn again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    pub async fn draw_test_pattern(&mut self, pattern: TestPattern) -> Result<(), St7701sError>
+    where
+        C: From<Rgb888>,
+    {
+        let (width, height) = self.dimensions();
+        self.set_address_window(0, 0, width.saturating_sub(1), height.saturating_sub(1)).await?;
+        self.write_pixels_iter(pattern.bytes::<C>(width, height)).await
+    }
+}
@@ -0,0 +1,335 @@
+//! Sending pixel data from owned buffers, formatting the next chunk while
+//! the previous one is transferred.
+
+use display_interface::{AsyncWriteOnlyDataCommand, DisplayError};
+use ef_driver_common::{color::DisplayColor, mode::Async};
+
+use crate::{ColorFormat, CommandDataShifter, Realignment, St7701s, St7701sError, WordWriter};
+
+/// An asynchronous interface that sends data from owned buffers,
+/// such as an SPI DMA engine.
+///
+/// In the style of `embedded-dma`, the interface takes ownership of a
+/// buffer for the duration of a transfer. Buffers are `'static`, so they
+/// can never be freed or reused while a transfer still reads from them,
+/// even if the future waiting for the transfer is dropped.
+///
+/// This is the bound required by [`PipelinedSt7701s`], in addition to
+/// [`AsyncWriteOnlyDataCommand`] for commands.
+#[expect(async_fn_in_trait, reason = "Matches the `embedded-hal-async` traits")]
+pub trait AsyncOwnedWrite<B: AsMut<[u8]> + 'static> {
+    /// Start sending the first `len` bytes of `buffer` as data,
+    /// without waiting for the transfer to complete.
+    ///
+    /// Only one transfer is started at a time, after the previous one was
+    /// returned by [`AsyncOwnedWrite::finish_data`].
+    fn start_data(&mut self, buffer: B, len: usize);
+
+    /// Wait for the transfer started by [`AsyncOwnedWrite::start_data`] to
+    /// complete, returning its buffer and whether it succeeded.
+    ///
+    /// # Cancel safety
+    ///
+    /// Must be cancel-resumable: when cancelled the transfer keeps its
+    /// buffer, and the next call waits for the same transfer.
+    async fn finish_data(&mut self) -> (B, Result<(), DisplayError>);
+}
+
+impl<B: AsMut<[u8]> + 'static, T: AsyncOwnedWrite<B> + ?Sized> AsyncOwnedWrite<B> for &mut T {
+    #[inline]
+    fn start_data(&mut self, buffer: B, len: usize) { T::start_data(self, buffer, len) }
+
+    #[inline]
+    async fn finish_data(&mut self) -> (B, Result<(), DisplayError>) { T::finish_data(self).await }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A [`St7701s`] that sends pixel data from owned buffers.
+///
+/// With two buffers, the next chunk of pixel data is formatted into the
+/// idle buffer while the other is being transferred, instead of waiting for
+/// every transfer before formatting the next. With a single buffer, created
+/// with [`St7701s::into_pipelined_single`], every chunk is formatted after
+/// the previous transfer completed, in the same order as the buffered path
+/// through [`AsyncWriteOnlyDataCommand`].
+///
+/// Commands are still sent through the internal buffer of the driver.
+///
+/// # Example
+///
+/// ```rust
+/// use core::cell::RefCell;
+/// use std::{
+///     future::poll_fn,
+///     pin::pin,
+///     task::{Context, Poll, Waker},
+/// };
+///
+/// use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+/// use ef_st7701s::{AsyncOwnedWrite, St7701s, color::Rgb565, mode::Async};
+/// # use ef_st7701s::test_util::block_on;
+///
+/// /// A numbered DMA buffer.
+/// struct Buffer(u8, [u8; 16]);
+///
+/// impl AsMut<[u8]> for Buffer {
+///     fn as_mut(&mut self) -> &mut [u8] { &mut self.1 }
+/// }
+///
+/// /// Records the output, and when transfers start and finish.
+/// struct Dma<'a> {
+///     output: Vec<u8>,
+///     log: &'a RefCell<Vec<String>>,
+///     in_flight: Option<Buffer>,
+/// }
+///
+/// impl AsyncWriteOnlyDataCommand for Dma<'_> {
+///     async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+///         self.send_data(cmd).await
+///     }
+///
+///     async fn send_data(&mut self, dat: DataFormat<'_>) -> Result<(), DisplayError> {
+///         let DataFormat::U8(bytes) = dat else { unreachable!() };
+///         self.output.extend_from_slice(bytes);
+///         Ok(())
+///     }
+/// }
+///
+/// impl AsyncOwnedWrite<Buffer> for Dma<'_> {
+///     fn start_data(&mut self, buffer: Buffer, len: usize) {
+///         assert!(self.in_flight.is_none());
+///         self.output.extend_from_slice(&buffer.1[..len]);
+///         self.log.borrow_mut().push(format!("start {}", buffer.0));
+///         self.in_flight = Some(buffer);
+///     }
+///
+///     async fn finish_data(&mut self) -> (Buffer, Result<(), DisplayError>) {
+///         // The transfer takes a moment
+///         let mut waited = false;
+///         poll_fn(|_| {
+///             if std::mem::replace(&mut waited, true) { Poll::Ready(()) } else { Poll::Pending }
+///         })
+///         .await;
+///
+///         let buffer = self.in_flight.take().unwrap();
+///         self.log.borrow_mut().push(format!("finish {}", buffer.0));
+///         (buffer, Ok(()))
+///     }
+/// }
+///
+/// let log = RefCell::new(Vec::new());
+/// let pixels: Vec<u8> = (0..40).collect();
+///
+/// // Log when pixels are formatted, once per chunk
+/// let formatted = pixels.iter().copied().inspect(|_| {
+///     if log.borrow().last().is_none_or(|event| event != "format") {
+///         log.borrow_mut().push(String::from("format"));
+///     }
+/// });
+///
+/// let dma = Dma { output: Vec::new(), log: &log, in_flight: None };
+/// let mut display = St7701s::<Rgb565, _, Async, 16>::new(dma)
+///     .into_pipelined([Buffer(0, [0; 16]), Buffer(1, [0; 16])]);
+///
+/// block_on(async {
+///     display.display_mut().begin_memory_write().await?;
+///     display.send_data_pipelined(formatted).await?;
+///     display.display_mut().shifter_mut().realign_async().await
+/// })?;
+///
+/// // The buffers alternate, formatting while the other is transferred
+/// assert_eq!(
+///     *log.borrow(),
+///     [
+///         "format", "start 0", "format", "finish 0", "start 1", "format", "finish 1", "start 0",
+///         "finish 0",
+///     ]
+/// );
+///
+/// // The same bytes are sent as through the internal buffer
+/// let dma = Dma { output: Vec::new(), log: &RefCell::default(), in_flight: None };
+/// let mut expected = St7701s::<Rgb565, _, Async, 16>::new(dma);
+/// block_on(expected.write_pixels(&pixels))?;
+/// assert_eq!(display.display().spi().output, expected.spi().output);
+///
+/// // Cancelling leaves the transfer in flight, owning its buffer
+/// {
+///     let mut cx = Context::from_waker(Waker::noop());
+///     let mut future = pin!(display.send_data_pipelined(pixels.iter().copied()));
+///     assert!(future.as_mut().poll(&mut cx).is_pending());
+/// }
+/// assert!(display.is_in_flight());
+/// assert!(display.display().spi().in_flight.is_some());
+///
+/// // Until it is waited for and returned
+/// block_on(display.finish())?;
+/// assert!(!display.is_in_flight());
+/// let (_, buffers) = display.release();
+/// assert!(buffers.iter().all(Option::is_some));
+/// # Ok::<(), DisplayError>(())
+/// ```
+pub struct PipelinedSt7701s<C: DisplayColor + ColorFormat, SPI, B, const N: usize> {
+    display: St7701s<C, SPI, Async, N>,
+    buffers: [Option<B>; 2],
+    in_flight: bool,
+}
+
+impl<C: DisplayColor + ColorFormat, SPI, const N: usize> St7701s<C, SPI, Async, N> {
+    /// Send pixel data from two owned buffers, formatting into one while
+    /// the other is transferred.
+    ///
+    /// See [`PipelinedSt7701s`].
+    #[inline]
+    #[must_use]
+    pub fn into_pipelined<B: AsMut<[u8]> + 'static>(
+        self,
+        bufs: [B; 2],
+    ) -> PipelinedSt7701s<C, SPI, B, N> {
+        let [first, second] = bufs;
+        PipelinedSt7701s { display: self, buffers: [Some(first), Some(second)], in_flight: false }
+    }
+
+    /// Send pixel data from a single owned buffer,
+    /// formatting every chunk after the previous transfer completed.
+    ///
+    /// See [`PipelinedSt7701s`].
+    #[inline]
+    #[must_use]
+    pub fn into_pipelined_single<B: AsMut<[u8]> + 'static>(
+        self,
+        buf: B,
+    ) -> PipelinedSt7701s<C, SPI, B, N> {
+        PipelinedSt7701s { display: self, buffers: [Some(buf), None], in_flight: false }
+    }
+}
+
+impl<C: DisplayColor + ColorFormat, SPI, B, const N: usize> PipelinedSt7701s<C, SPI, B, N> {
+    /// Get a reference to the display.
+    #[inline]
+    #[must_use]
+    pub const fn display(&self) -> &St7701s<C, SPI, Async, N> { &self.display }
+
+    /// Get a mutable reference to the display.
+    ///
+    /// If a call to [`PipelinedSt7701s::send_data_pipelined`] was cancelled,
+    /// wait for [`PipelinedSt7701s::finish`] before sending any commands.
+    #[inline]
+    #[must_use]
+    pub const fn display_mut(&mut self) -> &mut St7701s<C, SPI, Async, N> { &mut self.display }
+
+    /// Returns `true` if a transfer is still in flight,
+    /// after a call to [`PipelinedSt7701s::send_data_pipelined`] was cancelled.
+    #[inline]
+    #[must_use]
+    pub const fn is_in_flight(&self) -> bool { self.in_flight }
+
+    /// Release the display and the buffers.
+    ///
+    /// A buffer still in flight is owned by the interface,
+    /// wait for [`PipelinedSt7701s::finish`] first to get it back.
+    #[inline]
+    #[must_use]
+    pub fn release(self) -> (St7701s<C, SPI, Async, N>, [Option<B>; 2]) {
+        (self.display, self.buffers)
+    }
+}
+
+impl<
+    C: DisplayColor + ColorFormat,
+    SPI: AsyncWriteOnlyDataCommand + AsyncOwnedWrite<B>,
+    B: AsMut<[u8]> + 'static,
+    const N: usize,
+> PipelinedSt7701s<C, SPI, B, N>
+{
+    /// Send raw pixel data, formatting it into the idle buffer while the
+    /// other buffer is transferred.
+    ///
+    /// Like [`AsyncWriteOnlyDataCommand::send_data`], this only sends the
+    /// data. Start with [`St7701s::begin_memory_write`] and end with
+    /// [`CommandDataShifter::realign_async`] through
+    /// [`PipelinedSt7701s::display_mut`].
+    ///
+    /// Returns once every transfer completed.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: pixels already written stay on the panel and the
+    /// write position is lost, so the address window must be set again.
+    /// The transfer in flight keeps its buffer and is waited for by the next
+    /// call, or by [`PipelinedSt7701s::finish`]. Carried bits are only kept
+    /// for chunks that were started, so the framing of the next command is
+    /// never broken.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a buffer cannot hold a single pixel byte,
+    /// or if communication with the display fails.
+    pub async fn send_data_pipelined(
+        &mut self,
+        pixels: impl Iterator<Item = u8>,
+    ) -> Result<(), St7701sError> {
+        let mut pixels = pixels.peekable();
+        loop {
+            // Format into an idle buffer, or wait for the only buffer to return
+            let idle = self
+                .buffers
+                .iter_mut()
+                .enumerate()
+                .find_map(|(slot, buffer)| buffer.as_mut().map(|buffer| (slot, buffer)));
+            let Some((slot, buffer)) = idle else {
+                self.finish().await?;
+                continue;
+            };
+            let shifter = &self.display.spi;
+            let (length, carry) = CommandDataShifter::<SPI, N>::format_data_into(
+                &mut pixels,
+                buffer.as_mut(),
+                shifter.carry,
+                shifter.realignment,
+            );
+
+            if length == 0 {
+                // Make sure the transfer always makes progress.
+                if pixels.peek().is_some() {
+                    let realign = matches!(shifter.realignment, Realignment::Nop);
+                    return Err(
+                        WordWriter::new(buffer.as_mut(), shifter.carry).too_small(1, realign)
+                    );
+                }
+                return self.finish().await;
+            }
+
+            // Keep the order, waiting for the previous transfer first
+            self.finish().await?;
+            if let Some(buffer) = self.buffers[slot].take() {
+                self.display.spi.carry = carry;
+                self.display.spi.spi.start_data(buffer, length);
+                self.in_flight = true;
+            }
+        }
+    }
+
+    /// Wait for the transfer in flight to complete, if any,
+    /// and take its buffer back.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable if [`AsyncOwnedWrite::finish_data`] is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transfer failed.
+    pub async fn finish(&mut self) -> Result<(), St7701sError> {
+        if !self.in_flight {
+            return Ok(());
+        }
+
+        let (buffer, result) = self.display.spi.spi.finish_data().await;
+        self.in_flight = false;
+        if let Some(slot) = self.buffers.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(buffer);
+        }
+        Ok(result?)
+    }
+}
@@ -0,0 +1,158 @@
+//! Reading identification and status registers from the display.
+
+use display_interface::DisplayError;
+
+use crate::command;
+
+/// An interface that can read from the display,
+/// in addition to writing with `WriteOnlyDataCommand`.
+///
+/// Reads are full-duplex transfers of raw bits, during which the display
+/// stays selected. The driver writes a 9-bit command word into `write`,
+/// and extracts the response from the bits clocked into `read` after it,
+/// skipping any dummy clock.
+///
+/// On a 3-wire bus, the interface must release the data line once the
+/// command word has been sent.
+pub trait ReadDataCommand {
+    /// Clock out `write` while clocking in the same number of bytes into
+    /// `read`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), DisplayError>;
+}
+
+/// An asynchronous interface that can read from the display,
+/// in addition to writing with `AsyncWriteOnlyDataCommand`.
+///
+/// See [`ReadDataCommand`] for how reads are transferred.
+#[expect(async_fn_in_trait, reason = "Matches `AsyncWriteOnlyDataCommand`")]
+pub trait AsyncReadDataCommand {
+    /// Clock out `write` while clocking in the same number of bytes into
+    /// `read`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the display fails.
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), DisplayError>;
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// The display status, as reported by `READ_DISPLAY_STATUS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DisplayStatus([u8; 4]);
+
+impl DisplayStatus {
+    /// Create a [`DisplayStatus`] from the bytes read from the display.
+    #[inline]
+    #[must_use]
+    pub const fn from_bytes(bytes: [u8; 4]) -> Self { Self(bytes) }
+
+    /// Get the bytes read from the display.
+    #[inline]
+    #[must_use]
+    pub const fn to_bytes(self) -> [u8; 4] { self.0 }
+
+    /// Returns `true` if the booster voltage is on.
+    #[inline]
+    #[must_use]
+    pub const fn is_booster_on(self) -> bool { self.0[0] & 0b1000_0000 != 0 }
+
+    /// Get the interface pixel format,
+    /// in the same layout as
+    /// [`ColorFormat::FORMAT_BYTE`](crate::ColorFormat::FORMAT_BYTE).
+    #[inline]
+    #[must_use]
+    pub const fn pixel_format(self) -> u8 { self.0[1] & 0b0111_0000 }
+
+    /// Returns `true` if idle mode is on.
+    #[inline]
+    #[must_use]
+    pub const fn is_idle(self) -> bool { self.0[1] & 0b0000_1000 != 0 }
+
+    /// Returns `true` if partial mode is on.
+    #[inline]
+    #[must_use]
+    pub const fn is_partial(self) -> bool { self.0[1] & 0b0000_0100 != 0 }
+
+    /// Returns `true` if the display is in sleep mode.
+    #[inline]
+    #[must_use]
+    pub const fn is_sleeping(self) -> bool { self.0[1] & 0b0000_0010 == 0 }
+
+    /// Returns `true` if normal mode is on.
+    #[inline]
+    #[must_use]
+    pub const fn is_normal(self) -> bool { self.0[1] & 0b0000_0001 != 0 }
+
+    /// Returns `true` if color inversion is on.
+    #[inline]
+    #[must_use]
+    pub const fn is_inverted(self) -> bool { self.0[2] & 0b0010_0000 != 0 }
+
+    /// Returns `true` if the display is on.
+    #[inline]
+    #[must_use]
+    pub const fn is_display_on(self) -> bool { self.0[2] & 0b0000_0100 != 0 }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// The length of the largest read transfer, `READ_DISPLAY_STATUS`.
+pub(crate) const READ_BUFFER: usize = 6;
+
+/// A command that reads a response from the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ReadCommand {
+    command: u8,
+    /// Whether a dummy clock comes before the response.
+    dummy: bool,
+}
+
+impl ReadCommand {
+    /// The `READ_DISPLAY_STATUS` command.
+    pub(crate) const DISPLAY_STATUS: Self =
+        Self { command: command::ST7701S_READ_DISPLAY_STATUS, dummy: true };
+    /// The `READ_ID_1`, `READ_ID_2` and `READ_ID_3` commands.
+    pub(crate) const ID: [Self; 3] = [
+        Self { command: command::ST7701S_READ_ID_1, dummy: false },
+        Self { command: command::ST7701S_READ_ID_2, dummy: false },
+        Self { command: command::ST7701S_READ_ID_3, dummy: false },
+    ];
+    /// The `READ_SELF_DIAGNOSTIC` command.
+    pub(crate) const SELF_DIAGNOSTIC: Self =
+        Self { command: command::ST7701S_READ_SELF_DIAGNOSTIC, dummy: false };
+
+    /// Get the number of bits before the response.
+    const fn offset(self) -> usize { 9 + self.dummy as usize }
+
+    /// Get the number of bytes transferred to read a response of `M` bytes.
+    pub(crate) const fn len<const M: usize>(self) -> usize { (self.offset() + M * 8).div_ceil(8) }
+
+    /// Get the bytes to write, starting with the 9-bit command word.
+    pub(crate) const fn request(self) -> [u8; READ_BUFFER] {
+        let mut request = [0u8; READ_BUFFER];
+        request[0] = self.command >> 1;
+        request[1] = self.command << 7;
+        request
+    }
+
+    /// Extract a response of `M` bytes from the bytes read.
+    pub(crate) const fn response<const M: usize>(self, read: [u8; READ_BUFFER]) -> [u8; M] {
+        let (start, shift) = (self.offset() / 8, self.offset() % 8);
+
+        let mut response = [0u8; M];
+        let mut index = 0;
+        while index < M {
+            let high = read[start + index] << shift;
+            let low = if shift == 0 { 0 } else { read[start + index + 1] >> (8 - shift) };
+            response[index] = high | low;
+            index += 1;
+        }
+        response
+    }
+}
@@ -0,0 +1,556 @@
+//! Run-length encoded images, streamed to the display without a
+//! framebuffer.
+//!
+//! An image is a sequence of runs, each starting with a control byte:
+//!
+//! - `0b1nnn_nnnn`: a repeat run, followed by one pixel that is repeated `n +
+//!   1` times.
+//! - `0b0nnn_nnnn`: a literal run, followed by `n + 1` pixels.
+//!
+//! Pixels are stored in the byte layout of the display's pixel format, so a
+//! run of the background color of a boot logo costs a control byte and a
+//! single pixel.
+//!
+//! Images can be compressed at compile time with
+//! [`rle_image!`](crate::rle_image), or with [`encode_into`] from a build
+//! script for images that are too large to compress in a constant.
+
+use display_interface::{AsyncWriteOnlyDataCommand, DisplayError, WriteOnlyDataCommand};
+use ef_driver_common::{
+    color::DisplayColor,
+    mode::{Async, Blocking, DriverMode},
+};
+
+use crate::{ColorFormat, St7701s, St7701sError};
+
+/// The control bit of a repeat run.
+const REPEAT: u8 = 0b1000_0000;
+/// The longest run, in pixels.
+const MAX_RUN: usize = 128;
+
+/// A source of raw pixel data for a rectangular area,
+/// in the byte layout of the display's pixel format.
+pub trait PixelSource {
+    /// An iterator over the bytes of every pixel, in row-major order.
+    type Bytes<'a>: Iterator<Item = u8>
+    where
+        Self: 'a;
+
+    /// The width of the area, in pixels.
+    fn width(&self) -> u16;
+    /// The height of the area, in pixels.
+    fn height(&self) -> u16;
+    /// The number of bytes per pixel.
+    fn bytes_per_pixel(&self) -> usize;
+    /// Get the bytes of every pixel, in row-major order.
+    fn pixel_bytes(&self) -> Self::Bytes<'_>;
+}
+
+/// A run-length encoded image.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_st7701s::splash::{PixelSource, RleImage};
+///
+/// // A 4x2 image of two-byte pixels: three red, four blue and one green
+/// const IMAGE: RleImage<'static> =
+///     match RleImage::new(4, 2, 2, &[0x82, 0xF8, 0x00, 0x83, 0x00, 0x1F, 0x00, 0x07, 0xE0]) {
+///         Ok(image) => image,
+///         Err(_) => panic!("invalid image"),
+///     };
+///
+/// let decoded: Vec<u8> = IMAGE.pixel_bytes().collect();
+/// assert_eq!(
+///     decoded,
+///     [
+///         0xF8, 0x00, 0xF8, 0x00, 0xF8, 0x00, 0x00, 0x1F, //
+///         0x00, 0x1F, 0x00, 0x1F, 0x00, 0x1F, 0x07, 0xE0,
+///     ]
+/// );
+///
+/// // Truncated data and the wrong number of pixels are rejected
+/// assert!(RleImage::new(4, 2, 2, &[0x82, 0xF8, 0x00, 0x83, 0x00, 0x1F, 0x00, 0x07]).is_err());
+/// assert!(
+///     RleImage::new(4, 3, 2, &[0x82, 0xF8, 0x00, 0x83, 0x00, 0x1F, 0x00, 0x07, 0xE0]).is_err()
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RleImage<'a> {
+    width: u16,
+    height: u16,
+    bytes_per_pixel: usize,
+    data: &'a [u8],
+}
+
+impl<'a> RleImage<'a> {
+    /// Create an [`RleImage`] from encoded data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is truncated, or does not decode to
+    /// exactly `width * height` pixels.
+    pub const fn new(
+        width: u16,
+        height: u16,
+        bytes_per_pixel: usize,
+        data: &'a [u8],
+    ) -> Result<Self, RleError> {
+        if bytes_per_pixel == 0 {
+            return Err(RleError::InvalidPixelSize);
+        }
+
+        let mut pixels = 0;
+        let mut index = 0;
+        while index < data.len() {
+            let (run, stored) = run(data[index]);
+            index += 1 + stored * bytes_per_pixel;
+            pixels += run;
+        }
+
+        if index > data.len() {
+            Err(RleError::Truncated)
+        } else if pixels != width as usize * height as usize {
+            Err(RleError::PixelCount)
+        } else {
+            Ok(Self { width, height, bytes_per_pixel, data })
+        }
+    }
+
+    /// Get the encoded data.
+    #[inline]
+    #[must_use]
+    pub const fn data(&self) -> &'a [u8] { self.data }
+}
+
+impl PixelSource for RleImage<'_> {
+    type Bytes<'b>
+        = RleBytes<'b>
+    where
+        Self: 'b;
+
+    fn width(&self) -> u16 { self.width }
+
+    fn height(&self) -> u16 { self.height }
+
+    fn bytes_per_pixel(&self) -> usize { self.bytes_per_pixel }
+
+    fn pixel_bytes(&self) -> Self::Bytes<'_> {
+        RleBytes { data: self.data, bytes_per_pixel: self.bytes_per_pixel, ..RleBytes::EMPTY }
+    }
+}
+
+/// An iterator decoding the pixel bytes of an [`RleImage`].
+#[derive(Debug, Clone)]
+pub struct RleBytes<'a> {
+    /// The data, starting at the current pixel of the current run.
+    data: &'a [u8],
+    bytes_per_pixel: usize,
+    /// The byte within the current pixel.
+    byte: usize,
+    /// The pixels left in the current run, including the current pixel.
+    left: usize,
+    /// Whether the current run repeats a single pixel.
+    repeat: bool,
+}
+
+impl RleBytes<'_> {
+    const EMPTY: Self = Self { data: &[], bytes_per_pixel: 1, byte: 0, left: 0, repeat: false };
+}
+
+impl Iterator for RleBytes<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.left == 0 {
+            let (&control, rest) = self.data.split_first()?;
+            (self.left, _) = run(control);
+            self.repeat = control & REPEAT != 0;
+            self.data = rest;
+        }
+
+        let byte = *self.data.get(self.byte)?;
+        self.byte += 1;
+        if self.byte == self.bytes_per_pixel {
+            self.byte = 0;
+            self.left -= 1;
+            if !self.repeat || self.left == 0 {
+                self.data = self.data.get(self.bytes_per_pixel..).unwrap_or_default();
+            }
+        }
+        Some(byte)
+    }
+}
+
+/// Get the number of pixels in a run and the number of pixels stored for it.
+const fn run(control: u8) -> (usize, usize) {
+    let count = (control & !REPEAT) as usize + 1;
+    if control & REPEAT == 0 { (count, count) } else { (count, 1) }
+}
+
+/// An error that can occur when encoding or decoding an [`RleImage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RleError {
+    /// The number of bytes per pixel is zero.
+    InvalidPixelSize,
+    /// The data ends in the middle of a run,
+    /// or the raw pixels end in the middle of a pixel.
+    Truncated,
+    /// The data does not hold exactly `width * height` pixels.
+    PixelCount,
+    /// The output buffer is too small to hold the encoded image.
+    BufferTooSmall,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Get the length of the encoded form of `raw` pixels.
+///
+/// Returns `0` if `raw` is not a whole number of pixels.
+#[must_use]
+pub const fn encoded_len(raw: &[u8], bytes_per_pixel: usize) -> usize {
+    match encode_into(raw, bytes_per_pixel, &mut []) {
+        Ok(len) => len,
+        Err(_) => 0,
+    }
+}
+
+/// Encode `raw` pixels into an array of exactly [`encoded_len`] bytes.
+///
+/// # Panics
+///
+/// Panics if `N` is not the encoded length of `raw`.
+#[must_use]
+pub const fn encode<const N: usize>(raw: &[u8], bytes_per_pixel: usize) -> [u8; N] {
+    let mut output = [0u8; N];
+    match encode_into(raw, bytes_per_pixel, &mut output) {
+        Ok(len) if len == N => output,
+        _ => panic!("`N` must be the encoded length of the image"),
+    }
+}
+
+/// Encode `raw` pixels into `output`, returning the encoded length.
+///
+/// Runs of two or more identical pixels become repeat runs, all other
+/// pixels are grouped into literal runs. With an empty `output` nothing is
+/// written and only the encoded length is returned, which is useful in a
+/// build script.
+///
+/// # Errors
+///
+/// Returns an error if `raw` is not a whole number of pixels,
+/// or if `output` is not empty and too small.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_st7701s::splash::{self, PixelSource, RleImage};
+///
+/// // A 480x480 boot logo: a white square on black, above a 16-step gradient bar
+/// let mut raw = Vec::new();
+/// for y in 0..480u16 {
+///     for x in 0..480u16 {
+///         let pixel: u16 = match (x, y) {
+///             (160..320, 120..280) => 0xFFFF,
+///             (80..400, 360..376) => (x - 80) / 20 * 0x0841,
+///             _ => 0x0000,
+///         };
+///         raw.extend(pixel.to_be_bytes());
+///     }
+/// }
+///
+/// let len = splash::encode_into(&raw, 2, &mut [])?;
+/// let mut encoded = vec![0u8; len];
+/// splash::encode_into(&raw, 2, &mut encoded)?;
+///
+/// // 450 KiB of pixels compress to under 7 KiB
+/// assert_eq!(raw.len(), 460_800);
+/// assert_eq!(encoded.len(), 6_639);
+///
+/// // Decoding gives back every pixel
+/// let image = RleImage::new(480, 480, 2, &encoded)?;
+/// assert!(image.pixel_bytes().eq(raw.iter().copied()));
+///
+/// // Mismatched sizes are rejected
+/// assert!(splash::encode_into(&raw[1..], 2, &mut []).is_err());
+/// assert!(splash::encode_into(&raw, 2, &mut encoded[..len - 1]).is_err());
+/// # Ok::<(), ef_st7701s::splash::RleError>(())
+/// ```
+#[expect(clippy::cast_possible_truncation, reason = "Runs are at most `MAX_RUN` pixels")]
+#[expect(clippy::manual_is_multiple_of, reason = "The remainder matches the division below")]
+pub const fn encode_into(
+    raw: &[u8],
+    bytes_per_pixel: usize,
+    output: &mut [u8],
+) -> Result<usize, RleError> {
+    if bytes_per_pixel == 0 {
+        return Err(RleError::InvalidPixelSize);
+    } else if raw.len() % bytes_per_pixel != 0 {
+        return Err(RleError::Truncated);
+    }
+
+    let pixels = raw.len() / bytes_per_pixel;
+    let measure = output.is_empty();
+    let mut len = 0;
+    let mut pixel = 0;
+    while pixel < pixels {
+        let repeats = same_pixels(raw, bytes_per_pixel, pixel);
+        let (control, stored, count) = if repeats >= 2 {
+            (REPEAT | (repeats - 1) as u8, 1, repeats)
+        } else {
+            // Collect pixels until the next repeat run starts.
+            let mut count = 1;
+            while pixel + count < pixels
+                && count < MAX_RUN
+                && same_pixels(raw, bytes_per_pixel, pixel + count) < 2
+            {
+                count += 1;
+            }
+            ((count - 1) as u8, count, count)
+        };
+
+        let bytes = stored * bytes_per_pixel;
+        if !measure {
+            if len + 1 + bytes > output.len() {
+                return Err(RleError::BufferTooSmall);
+            }
+            output[len] = control;
+            let mut index = 0;
+            while index < bytes {
+                output[len + 1 + index] = raw[pixel * bytes_per_pixel + index];
+                index += 1;
+            }
+        }
+
+        len += 1 + bytes;
+        pixel += count;
+    }
+
+    Ok(len)
+}
+
+/// Count the identical pixels starting at `pixel`, up to [`MAX_RUN`].
+const fn same_pixels(raw: &[u8], bytes_per_pixel: usize, pixel: usize) -> usize {
+    let start = pixel * bytes_per_pixel;
+    let mut count = 1;
+    'pixels: while count < MAX_RUN && start + (count + 1) * bytes_per_pixel <= raw.len() {
+        let mut index = 0;
+        while index < bytes_per_pixel {
+            if raw[start + index] != raw[start + count * bytes_per_pixel + index] {
+                break 'pixels;
+            }
+            index += 1;
+        }
+        count += 1;
+    }
+    count
+}
+
+/// Compress a file of raw pixels into an [`RleImage`] at compile time.
+///
+/// The file must hold `width * height` pixels of `bytes_per_pixel` bytes
+/// each, in row-major order and the byte layout of the display's pixel
+/// format, such as big-endian RGB565.
+///
+/// Compressing large images in a constant can be slow to compile,
+/// [`splash::encode_into`](crate::splash::encode_into) can be used from a
+/// build script instead.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_st7701s::{
+///     rle_image,
+///     splash::{PixelSource, RleImage},
+/// };
+///
+/// // An 8x4 image, two red rows above two rows of blue and white stripes
+/// static LOGO: RleImage<'static> =
+///     rle_image!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/logo.raw"), 8, 4, 2);
+///
+/// // The red rows are compressed into a single run
+/// assert_eq!(LOGO.data()[..3], [0x8F, 0xF8, 0x00]);
+///
+/// // Decoding restores every pixel
+/// let decoded: Vec<u8> = LOGO.pixel_bytes().collect();
+/// let pixels: Vec<[u8; 2]> = decoded.chunks(2).map(|pixel| [pixel[0], pixel[1]]).collect();
+/// assert_eq!(pixels.len(), 8 * 4);
+/// assert!(pixels[..16].iter().all(|&pixel| pixel == [0xF8, 0x00]));
+/// assert_eq!(pixels[16..20], [[0x00, 0x1F], [0xFF, 0xFF], [0x00, 0x1F], [0xFF, 0xFF]]);
+/// assert_eq!(pixels[24..28], [[0xFF, 0xFF], [0x00, 0x1F], [0xFF, 0xFF], [0x00, 0x1F]]);
+/// ```
+#[macro_export]
+macro_rules! rle_image {
+    ($path:expr, $width:expr, $height:expr, $bytes_per_pixel:expr) => {{
+        const RAW: &[u8] = include_bytes!($path);
+        const LEN: usize = $crate::splash::encoded_len(RAW, $bytes_per_pixel);
+        const DATA: [u8; LEN] = $crate::splash::encode(RAW, $bytes_per_pixel);
+        const IMAGE: $crate::splash::RleImage<'static> =
+            match $crate::splash::RleImage::new($width, $height, $bytes_per_pixel, &DATA) {
+                Ok(image) => image,
+                Err(_) => panic!("the image does not match its size"),
+            };
+        IMAGE
+    }};
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl<C: DisplayColor + ColorFormat, SPI, MODE: DriverMode, const N: usize>
+    St7701s<C, SPI, MODE, N>
+{
+    /// Get the bottom-right corner of a [`PixelSource`] drawn at `(x, y)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is empty, does not fit on the panel,
+    /// or is not in the display's pixel format.
+    fn source_window<S: PixelSource>(
+        &self,
+        x: u16,
+        y: u16,
+        source: &S,
+    ) -> Result<[u16; 2], DisplayError> {
+        if source.bytes_per_pixel() != C::BYTES_PER_PIXEL {
+            return Err(DisplayError::InvalidFormatError);
+        }
+
+        let (width, height) = (source.width(), source.height());
+        let x1 = x.checked_add(width).and_then(|end| end.checked_sub(1));
+        let y1 = y.checked_add(height).and_then(|end| end.checked_sub(1));
+        let (panel_width, panel_height) = self.dimensions();
+        match (x1, y1) {
+            (Some(x1), Some(y1)) if x1 < panel_width && y1 < panel_height => Ok([x1, y1]),
+            _ => Err(DisplayError::OutOfBoundsError),
+        }
+    }
+}
+
+impl<C: DisplayColor + ColorFormat, SPI: WriteOnlyDataCommand, const N: usize>
+    St7701s<C, SPI, Blocking, N>
+{
+    /// Draw a [`PixelSource`] with its top-left corner at `(x, y)`,
+    /// streaming its pixels without a framebuffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source does not fit on the panel, if its
+    /// pixels are not in the display's pixel format, or if communication
+    /// with the display fails.
+    pub fn draw_source<S: PixelSource>(
+        &mut self,
+        x: u16,
+        y: u16,
+        source: &S,
+    ) -> Result<(), St7701sError> {
+        let [x1, y1] = self.source_window(x, y, source)?;
+        self.set_address_window(x, y, x1, y1)?;
+        self.write_pixels_iter(source.pixel_bytes())
+    }
+
+    /// Draw an [`RleImage`] with its top-left corner at `(x, y)`,
+    /// decoding it while it is streamed to the display.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image does not fit on the panel, if its
+    /// pixels are not in the display's pixel format, or if communication
+    /// with the display fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use display_interface::DisplayError;
+    /// use ef_st7701s::{
+    ///     St7701s,
+    ///     color::Rgb565,
+    ///     mode::Blocking,
+    ///     splash::{self, RleImage},
+    ///     test_util::Recorder,
+    /// };
+    ///
+    /// // A 3x1 image compressed at compile time
+    /// const RAW: [u8; 6] = [0xF8, 0x00, 0xF8, 0x00, 0x07, 0xE0];
+    /// const DATA: [u8; 6] = splash::encode(&RAW, 2);
+    /// let image = RleImage::new(3, 1, 2, &DATA).unwrap();
+    ///
+    /// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Recorder::default());
+    /// display.draw_rle_image(477, 479, &image)?;
+    ///
+    /// // Images must fit on the panel, in the display's pixel format
+    /// assert!(display.draw_rle_image(478, 0, &image).is_err());
+    /// let rgb888 = RleImage::new(1, 1, 3, &[0x80, 0xFF, 0x00, 0x00]).unwrap();
+    /// assert!(display.draw_rle_image(0, 0, &rgb888).is_err());
+    ///
+    /// let words = display.release().words();
+    ///
+    /// let pixels: Vec<u8> = words
+    ///     .iter()
+    ///     .skip_while(|&&word| word != (false, 0x2C))
+    ///     .skip(1)
+    ///     .map(|&(_, byte)| byte)
+    ///     .collect();
+    /// assert_eq!(pixels, RAW);
+    /// # Ok::<(), DisplayError>(())
+    /// ```
+    pub fn draw_rle_image(
+        &mut self,
+        x: u16,
+        y: u16,
+        image: &RleImage<'_>,
+    ) -> Result<(), St7701sError> {
+        self.draw_source(x, y, image)
+    }
+}
+
+impl<C: DisplayColor + ColorFormat, SPI: AsyncWriteOnlyDataCommand, const N: usize>
+    St7701s<C, SPI, Async, N>
+{
+    /// Draw a [`PixelSource`] with its top-left corner at `(x, y)`,
+    /// streaming its pixels without a framebuffer.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the source is only borrowed, so it can be drawn
+    /// again from its first pixel into a window that is set again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source does not fit on the panel, if its
+    /// pixels are not in the display's pixel format, or if communication
+    /// with the display fails.
+    pub async fn draw_source<S: PixelSource>(
+        &mut self,
+        x: u16,
+        y: u16,
+        source: &S,
+    ) -> Result<(), St7701sError> {
+        let [x1, y1] = self.source_window(x, y, source)?;
+        self.set_address_window(x, y, x1, y1).await?;
+        self.write_pixels_iter(source.pixel_bytes()).await
+    }
+
+    /// Draw an [`RleImage`] with its top-left corner at `(x, y)`,
+    /// decoding it while it is streamed to the display.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the image is decoded from its start on every call,
+    /// so a partly drawn image is overdrawn by the next one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image does not fit on the panel, if its
+    /// pixels are not in the display's pixel format, or if communication
+    /// with the display fails.
+    pub async fn draw_rle_image(
+        &mut self,
+        x: u16,
+        y: u16,
+        image: &RleImage<'_>,
+    ) -> Result<(), St7701sError> {
+        self.draw_source(x, y, image).await
+    }
+}
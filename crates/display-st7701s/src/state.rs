@@ -0,0 +1,329 @@
+//! The runtime state cached by the driver.
+
+use crate::{AddressMode, BrightnessConfig, DisplayConfig, GammaCurve, Orientation, TearingEffect};
+
+/// The runtime state of the display, as cached by the driver.
+///
+/// The interface is write-only, so this is the state last written by the
+/// driver, starting from the reset values of the display. It is returned by
+/// `St7701s::display_state`, and every command that changes the state
+/// updates it once the command was sent.
+///
+/// The byte-representation allows persisting user preferences, such as on
+/// a settings screen, and restoring them with the setters on the next boot.
+///
+/// # Example
+///
+/// ```rust
+/// use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+/// use ef_st7701s::{
+///     BrightnessConfig, DisplayState, GammaCurve, Orientation, St7701s, TearingEffect,
+///     color::Rgb565, mode::Blocking,
+/// };
+///
+/// struct Interface;
+///
+/// impl WriteOnlyDataCommand for Interface {
+///     fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+///
+///     fn send_data(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+/// }
+///
+/// let curve = GammaCurve::new([0x0F; 16]);
+///
+/// // Every setter updates the cached state
+/// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Interface);
+/// display.set_brightness(0xA0)?;
+/// display.set_brightness_config(BrightnessConfig::new().with_dimming(true))?;
+/// display.set_inverted(true)?;
+/// display.set_orientation(Orientation::Landscape)?;
+/// display.set_idle_mode(true)?;
+/// display.set_tearing_effect(TearingEffect::VBlankOnly)?;
+/// display.set_gamma(&curve, &curve)?;
+///
+/// let state = *display.display_state();
+/// assert_eq!((state.config.brightness, state.config.inverted), (0xA0, true));
+/// assert_eq!(state.orientation(), Some(Orientation::Landscape));
+/// assert!(state.idle && !state.display_on);
+/// assert_eq!(state.gamma, Some((curve, curve)));
+///
+/// // Persist the state, and restore it with the setters on the next boot
+/// let saved = DisplayState::from_bytes(state.to_bytes()).unwrap();
+/// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Interface);
+/// display.set_brightness(saved.config.brightness)?;
+/// if let Some(config) = saved.brightness_config {
+///     display.set_brightness_config(config)?;
+/// }
+/// display.set_inverted(saved.config.inverted)?;
+/// display.set_orientation(saved.orientation().unwrap())?;
+/// display.set_idle_mode(saved.idle)?;
+/// display.set_tearing_effect(saved.tearing_effect)?;
+/// if let Some((positive, negative)) = saved.gamma {
+///     display.set_gamma(&positive, &negative)?;
+/// }
+/// assert_eq!(*display.display_state(), state);
+/// # Ok::<(), ef_st7701s::St7701sError>(())
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DisplayState {
+    /// The address mode, inversion and brightness of the display.
+    pub config: DisplayConfig,
+    /// Whether the display is turned on.
+    pub display_on: bool,
+    /// Whether the display is in sleep mode.
+    pub asleep: bool,
+    /// Whether the display is in idle mode, with reduced colors.
+    pub idle: bool,
+    /// The mode of the tearing effect output.
+    pub tearing_effect: TearingEffect,
+    /// The positive and negative gamma curves last written,
+    /// or `None` for the defaults of the display.
+    pub gamma: Option<(GammaCurve, GammaCurve)>,
+    /// The configuration of the brightness control block last written,
+    /// or `None` for the defaults of the display.
+    pub brightness_config: Option<BrightnessConfig>,
+}
+
+impl DisplayState {
+    /// The length of the byte-representation of a [`DisplayState`].
+    pub const BYTES: usize = 8 + 2 * 16;
+    /// The display is in sleep mode.
+    const FLAG_ASLEEP: u8 = 0b0000_0010;
+    /// The brightness configuration follows the gamma curves.
+    const FLAG_BRIGHTNESS_CONFIG: u8 = 0b0001_0000;
+    /// The display is turned on.
+    const FLAG_DISPLAY_ON: u8 = 0b0000_0001;
+    /// Gamma curves follow the flags.
+    const FLAG_GAMMA: u8 = 0b0000_1000;
+    /// The display is in idle mode.
+    const FLAG_IDLE: u8 = 0b0000_0100;
+    /// Marks the bytes as a [`DisplayState`].
+    const MAGIC: u8 = 0x53;
+    /// The version of the byte-representation.
+    pub const VERSION: u8 = 2;
+
+    /// Create a new [`DisplayState`] from a [`DisplayConfig`], with the
+    /// display turned off and the remaining state at its reset values.
+    #[inline]
+    #[must_use]
+    pub const fn new(config: DisplayConfig) -> Self {
+        Self {
+            config,
+            display_on: false,
+            asleep: false,
+            idle: false,
+            tearing_effect: TearingEffect::Off,
+            gamma: None,
+            brightness_config: None,
+        }
+    }
+
+    /// Get the [`Orientation`] set by the address mode.
+    ///
+    /// Returns `None` if the address mode mirrors the panel in a way that is
+    /// not a rotation.
+    #[inline]
+    #[must_use]
+    pub const fn orientation(&self) -> Option<Orientation> {
+        Orientation::from_address_mode(self.config.address_mode)
+    }
+
+    /// Get the byte-representation of the [`DisplayState`].
+    ///
+    /// The layout is fixed: a marker byte, the [`DisplayState::VERSION`],
+    /// the bytes of the [`DisplayConfig`], a byte of flags, the tearing
+    /// effect mode, both gamma curves and the [`BrightnessConfig`], which
+    /// are zero if unset.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_st7701s::{BrightnessConfig, DisplayConfig, DisplayState, GammaCurve, TearingEffect};
+    ///
+    /// let curves = [None, Some((GammaCurve::new([0xFF; 16]), GammaCurve::new([0x01; 16])))];
+    /// for bits in 0..16u8 {
+    ///     for tearing_effect in
+    ///         [TearingEffect::Off, TearingEffect::VBlankOnly, TearingEffect::VAndHBlank]
+    ///     {
+    ///         for gamma in curves {
+    ///             let state = DisplayState {
+    ///                 display_on: bits & 1 != 0,
+    ///                 asleep: bits & 2 != 0,
+    ///                 idle: bits & 4 != 0,
+    ///                 tearing_effect,
+    ///                 gamma,
+    ///                 brightness_config: (bits & 8 != 0)
+    ///                     .then(|| BrightnessConfig::new().with_backlight(bits & 1 != 0)),
+    ///                 ..DisplayState::new(DisplayConfig::default().with_brightness(bits))
+    ///             };
+    ///             assert_eq!(DisplayState::from_bytes(state.to_bytes()), Some(state));
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let state = DisplayState { display_on: true, ..DisplayState::default() };
+    /// assert_eq!(state.to_bytes()[..7], [0x53, 0x02, 0x77, 0x00, 0x00, 0x01, 0x00]);
+    ///
+    /// // Other versions, unknown flags and invalid modes are rejected
+    /// let mut bytes = state.to_bytes();
+    /// bytes[1] = 1;
+    /// assert_eq!(DisplayState::from_bytes(bytes), None);
+    /// let mut bytes = state.to_bytes();
+    /// bytes[5] = 0x20;
+    /// assert_eq!(DisplayState::from_bytes(bytes), None);
+    /// let mut bytes = state.to_bytes();
+    /// (bytes[5], bytes[DisplayState::BYTES - 1]) = (0x10, 0xFF);
+    /// assert_eq!(DisplayState::from_bytes(bytes), None);
+    /// let mut bytes = state.to_bytes();
+    /// bytes[6] = 3;
+    /// assert_eq!(DisplayState::from_bytes(bytes), None);
+    /// assert_eq!(DisplayState::from_bytes([0; DisplayState::BYTES]), None);
+    /// ```
+    #[must_use]
+    pub const fn to_bytes(self) -> [u8; Self::BYTES] {
+        let mut bytes = [0u8; Self::BYTES];
+        let [magic, flags, brightness] = self.config.to_bytes();
+        (bytes[0], bytes[1]) = (Self::MAGIC, Self::VERSION);
+        (bytes[2], bytes[3], bytes[4]) = (magic, flags, brightness);
+
+        let mut flags = 0u8;
+        if self.display_on {
+            flags |= Self::FLAG_DISPLAY_ON;
+        }
+        if self.asleep {
+            flags |= Self::FLAG_ASLEEP;
+        }
+        if self.idle {
+            flags |= Self::FLAG_IDLE;
+        }
+        if let Some((positive, negative)) = self.gamma {
+            flags |= Self::FLAG_GAMMA;
+            let (positive, negative) = (positive.to_bytes(), negative.to_bytes());
+            let mut index = 0;
+            while index < 16 {
+                bytes[7 + index] = positive[index];
+                bytes[7 + 16 + index] = negative[index];
+                index += 1;
+            }
+        }
+        if let Some(config) = self.brightness_config {
+            flags |= Self::FLAG_BRIGHTNESS_CONFIG;
+            bytes[7 + 2 * 16] = config.to_byte();
+        }
+        bytes[5] = flags;
+        bytes[6] = match self.tearing_effect {
+            TearingEffect::Off => 0,
+            TearingEffect::VBlankOnly => 1,
+            TearingEffect::VAndHBlank => 2,
+        };
+        bytes
+    }
+
+    /// Create a [`DisplayState`] from its byte-representation.
+    ///
+    /// Returns `None` if the bytes were not created by
+    /// [`DisplayState::to_bytes`] of the same [`DisplayState::VERSION`].
+    /// The [`TimingProfile`](crate::TimingProfile) is reset to its default.
+    #[must_use]
+    pub const fn from_bytes(bytes: [u8; Self::BYTES]) -> Option<Self> {
+        const KNOWN: u8 = DisplayState::FLAG_DISPLAY_ON
+            | DisplayState::FLAG_ASLEEP
+            | DisplayState::FLAG_IDLE
+            | DisplayState::FLAG_GAMMA
+            | DisplayState::FLAG_BRIGHTNESS_CONFIG;
+        if bytes[0] != Self::MAGIC || bytes[1] != Self::VERSION || bytes[5] & !KNOWN != 0 {
+            return None;
+        }
+        let Some(config) = DisplayConfig::from_bytes([bytes[2], bytes[3], bytes[4]]) else {
+            return None;
+        };
+        let tearing_effect = match bytes[6] {
+            0 => TearingEffect::Off,
+            1 => TearingEffect::VBlankOnly,
+            2 => TearingEffect::VAndHBlank,
+            _ => return None,
+        };
+
+        let flags = bytes[5];
+        let gamma = if flags & Self::FLAG_GAMMA == 0 {
+            None
+        } else {
+            let (mut positive, mut negative) = ([0u8; 16], [0u8; 16]);
+            let mut index = 0;
+            while index < 16 {
+                positive[index] = bytes[7 + index];
+                negative[index] = bytes[7 + 16 + index];
+                index += 1;
+            }
+            match (GammaCurve::try_new(positive), GammaCurve::try_new(negative)) {
+                (Some(positive), Some(negative)) => Some((positive, negative)),
+                _ => return None,
+            }
+        };
+        let brightness_config = if flags & Self::FLAG_BRIGHTNESS_CONFIG == 0 {
+            None
+        } else {
+            match BrightnessConfig::from_byte(bytes[7 + 2 * 16]) {
+                Some(config) => Some(config),
+                None => return None,
+            }
+        };
+
+        Some(Self {
+            config,
+            display_on: flags & Self::FLAG_DISPLAY_ON != 0,
+            asleep: flags & Self::FLAG_ASLEEP != 0,
+            idle: flags & Self::FLAG_IDLE != 0,
+            tearing_effect,
+            gamma,
+            brightness_config,
+        })
+    }
+
+    /// Record a change to the state.
+    ///
+    /// This is the only place the driver changes its cached state.
+    pub(crate) const fn apply(&mut self, change: StateChange) {
+        match change {
+            StateChange::Initialized(config) => {
+                *self = Self { display_on: true, ..Self::new(config) };
+            }
+            StateChange::DisplayOn(on) => self.display_on = on,
+            StateChange::Asleep(asleep) => self.asleep = asleep,
+            StateChange::Idle(idle) => self.idle = idle,
+            StateChange::Inverted(inverted) => self.config.inverted = inverted,
+            StateChange::Brightness(brightness) => self.config.brightness = brightness,
+            StateChange::BrightnessConfig(config) => self.brightness_config = Some(config),
+            StateChange::AddressMode(mode) => self.config.address_mode = mode,
+            StateChange::TearingEffect(mode) => self.tearing_effect = mode,
+            StateChange::Gamma(positive, negative) => self.gamma = Some((positive, negative)),
+        }
+    }
+}
+
+/// A change to the [`DisplayState`], made by a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StateChange {
+    /// The display was reset and initialized with a configuration,
+    /// either by the driver or before the driver was resumed.
+    Initialized(DisplayConfig),
+    /// The display was turned on or off.
+    DisplayOn(bool),
+    /// The display entered or exited sleep mode.
+    Asleep(bool),
+    /// The display entered or exited idle mode.
+    Idle(bool),
+    /// Color inversion was turned on or off.
+    Inverted(bool),
+    /// The brightness was set.
+    Brightness(u8),
+    /// The brightness control block was configured.
+    BrightnessConfig(BrightnessConfig),
+    /// The address mode was set.
+    AddressMode(AddressMode),
+    /// The tearing effect output was set.
+    TearingEffect(TearingEffect),
+    /// The positive and negative gamma curves were set.
+    Gamma(GammaCurve, GammaCurve),
+}
@@ -0,0 +1,111 @@
+//! Mock interfaces for examples and tests, without a display.
+//!
+//! Enabled with the `test-util` feature.
+
+use alloc::vec::Vec;
+use core::{
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use display_interface::{
+    AsyncWriteOnlyDataCommand, DataFormat, DisplayError, WriteOnlyDataCommand,
+};
+
+/// An interface that records every byte sent through it.
+///
+/// Commands and data are recorded alike, as the 9-bit words are already
+/// told apart by their prefix bit. Only [`DataFormat::U8`] and
+/// [`DataFormat::U8Iter`] are accepted, as sent by the
+/// [`CommandDataShifter`](crate::CommandDataShifter).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Recorder(pub Vec<u8>);
+
+impl Recorder {
+    /// Split the recorded bytes into 9-bit words, skipping NOPs.
+    #[must_use]
+    pub fn words(&self) -> Vec<(bool, u8)> { words(&self.0) }
+
+    /// Take the recorded bytes as 9-bit words, skipping NOPs,
+    /// and start recording again.
+    pub fn take_words(&mut self) -> Vec<(bool, u8)> { words(&core::mem::take(&mut self.0)) }
+
+    /// Record the bytes of a transfer.
+    fn record(&mut self, format: DataFormat<'_>) -> Result<(), DisplayError> {
+        match format {
+            DataFormat::U8(bytes) => self.0.extend_from_slice(bytes),
+            DataFormat::U8Iter(bytes) => self.0.extend(bytes),
+            _ => return Err(DisplayError::InvalidFormatError),
+        }
+        Ok(())
+    }
+}
+
+impl WriteOnlyDataCommand for Recorder {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(cmd)
+    }
+
+    fn send_data(&mut self, dat: DataFormat<'_>) -> Result<(), DisplayError> { self.record(dat) }
+}
+
+impl AsyncWriteOnlyDataCommand for Recorder {
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(cmd)
+    }
+
+    async fn send_data(&mut self, dat: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(dat)
+    }
+}
+
+/// Split bytes into 9-bit words, skipping NOPs.
+///
+/// Each word is returned as its prefix bit, `true` for data, and its byte.
+#[must_use]
+pub fn words(bytes: &[u8]) -> Vec<(bool, u8)> {
+    let mut words = Vec::new();
+    let (mut bits, mut len) = (0u32, 0);
+    for &byte in bytes {
+        bits = bits << 8 | u32::from(byte);
+        len += 8;
+        if len >= 9 {
+            len -= 9;
+            let word = bits >> len & 0x1FF;
+            #[expect(clippy::cast_possible_truncation, reason = "Only the lowest byte is wanted")]
+            let word = (word & 0x100 != 0, word as u8);
+            if word != (false, 0x00) {
+                words.push(word);
+            }
+        }
+        bits &= (1 << len) - 1;
+    }
+    words
+}
+
+/// A delay that records every wait, in milliseconds.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Clock(pub Vec<u32>);
+
+impl embedded_hal::delay::DelayNs for Clock {
+    fn delay_ns(&mut self, ns: u32) { self.0.push(ns / 1_000_000); }
+
+    fn delay_ms(&mut self, ms: u32) { self.0.push(ms); }
+}
+
+impl embedded_hal_async::delay::DelayNs for Clock {
+    async fn delay_ns(&mut self, ns: u32) { self.0.push(ns / 1_000_000); }
+
+    async fn delay_ms(&mut self, ms: u32) { self.0.push(ms); }
+}
+
+/// Run a future to completion, polling it until it is ready.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
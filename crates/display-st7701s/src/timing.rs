@@ -0,0 +1,95 @@
+//! The delays waited between commands.
+
+/// The delays waited by `init`, `sleep` and `wake`, in milliseconds.
+///
+/// [`TimingProfile::DEFAULT`] holds the datasheet values. Clone controllers
+/// on some panels need longer delays, and ignore commands sent too soon
+/// after a reset or after exiting sleep mode.
+///
+/// # Example
+///
+/// ```rust
+/// use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+/// use ef_st7701s::{
+///     DisplayConfig, St7701s, TimingProfile, color::Rgb565, mode::Blocking, test_util::Clock,
+/// };
+///
+/// struct Interface;
+///
+/// impl WriteOnlyDataCommand for Interface {
+///     fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+///
+///     fn send_data(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> { Ok(()) }
+/// }
+///
+/// for timing in [TimingProfile::DEFAULT, TimingProfile::conservative()] {
+///     let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Interface);
+///     let mut clock = Clock::default();
+///
+///     display.init(DisplayConfig::default().with_timing(timing), &mut clock)?;
+///     display.sleep(&mut clock)?;
+///     display.wake(&mut clock)?;
+///
+///     assert_eq!(
+///         clock.0,
+///         [
+///             timing.reset,
+///             timing.sleep_out,
+///             timing.command,
+///             timing.command,
+///             timing.command,
+///             timing.display_on,
+///             timing.sleep_in,
+///             timing.wake,
+///         ]
+///     );
+/// }
+///
+/// // A clone that needs 210 ms after exiting sleep mode
+/// let timing = TimingProfile { sleep_out: 210, ..TimingProfile::DEFAULT };
+/// let mut display = St7701s::<Rgb565, _, Blocking, 16>::new(Interface);
+/// let mut clock = Clock::default();
+/// display.init(DisplayConfig::default().with_timing(timing), &mut clock)?;
+/// assert_eq!(clock.0, [150, 210, 10, 10, 10, 150]);
+/// # Ok::<(), DisplayError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimingProfile {
+    /// The delay after a software reset.
+    pub reset: u32,
+    /// The delay after exiting sleep mode during `init`.
+    pub sleep_out: u32,
+    /// The delay after each configuration step of `init`.
+    pub command: u32,
+    /// The delay after turning on the display during `init`.
+    pub display_on: u32,
+    /// The delay after entering sleep mode.
+    pub sleep_in: u32,
+    /// The delay after exiting sleep mode with `wake`.
+    pub wake: u32,
+}
+
+impl TimingProfile {
+    /// The delays given in the datasheet.
+    pub const DEFAULT: Self =
+        Self { reset: 150, sleep_out: 150, command: 10, display_on: 150, sleep_in: 5, wake: 120 };
+
+    /// Delays with generous margins, for clone controllers that do not
+    /// work with the datasheet values.
+    ///
+    /// ```rust
+    /// use ef_st7701s::TimingProfile;
+    ///
+    /// let (default, conservative) = (TimingProfile::DEFAULT, TimingProfile::conservative());
+    /// assert!(conservative.reset >= default.reset && conservative.sleep_out >= 210);
+    /// ```
+    #[must_use]
+    pub const fn conservative() -> Self {
+        Self { reset: 300, sleep_out: 300, command: 20, display_on: 200, sleep_in: 20, wake: 300 }
+    }
+}
+
+impl Default for TimingProfile {
+    fn default() -> Self { Self::DEFAULT }
+}
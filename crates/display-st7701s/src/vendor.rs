@@ -0,0 +1,142 @@
+//! Vendor initialization sequences for common panels, sent with
+//! `St7701s::run_init_commands`.
+//!
+//! Each sequence is complete: it configures the Command2 banks, the
+//! inversion and pixel format, exits sleep mode and turns on the display.
+//! Send it after a hardware reset, instead of `init`.
+
+use crate::InitCommand::{self, Command, DelayMs};
+
+/// The sequence of 2.1" round 480x480 panels, such as the `TL021WVC02`.
+///
+/// Transcribed from the vendor script, in order:
+///
+/// | Entries | Vendor script                                                 |
+/// |---------|---------------------------------------------------------------|
+/// | 0       | `FF 77 01 00 00 10`, select Command2 bank 0                   |
+/// | 1-4     | `C0`-`C2` and `CD`, display line, porch and inversion setting |
+/// | 5-6     | `B0`-`B1`, positive and negative gamma                        |
+/// | 7       | `FF 77 01 00 00 11`, select Command2 bank 1                   |
+/// | 8-16    | `B0`-`C2`, Vop, VCOM, VGH, VGL and AVDD power settings        |
+/// | 17-28   | `E0`-`ED`, gate-in-panel (GIP) timing                         |
+/// | 29-30   | `FF 77 01 00 00 13` and `E5 E4`, VAP and VAN                  |
+/// | 31      | `FF 77 01 00 00 00`, return to Command1                       |
+/// | 32-33   | `21` and `3A 60`, inversion on (IPS) and 18-bit RGB666        |
+/// | 34-36   | `11`, `delay 120`, `29`, sleep out and display on             |
+///
+/// # Example
+///
+/// ```rust
+/// use display_interface::DisplayError;
+/// use ef_st7701s::{
+///     St7701s,
+///     color::Rgb666,
+///     mode::Blocking,
+///     test_util::{Clock, Recorder},
+///     vendor,
+/// };
+///
+/// let mut display = St7701s::<Rgb666, _, Blocking, 16>::new(Recorder::default());
+/// let mut clock = Clock::default();
+/// display.run_init_commands(vendor::ROUND_2_1_480X480, &mut clock)?;
+/// assert_eq!(clock.0, [120]);
+///
+/// let words = display.release().words();
+/// assert_eq!(words.len(), 201);
+/// assert_eq!(words.iter().filter(|(data, _)| !data).count(), 36);
+/// // The first and last lines of the vendor script
+/// assert_eq!(
+///     words[..6],
+///     [(false, 0xFF), (true, 0x77), (true, 0x01), (true, 0x00), (true, 0x00), (true, 0x10)]
+/// );
+/// assert_eq!(
+///     words[words.len() - 4..],
+///     [(false, 0x3A), (true, 0x60), (false, 0x11), (false, 0x29)]
+/// );
+/// // VCOM in Command2 bank 1
+/// let select =
+///     [(false, 0xFF), (true, 0x77), (true, 0x01), (true, 0x00), (true, 0x00), (true, 0x11)];
+/// let bank1 = words.windows(6).position(|w| w == select).unwrap();
+/// assert_eq!(words[bank1 + 8..bank1 + 10], [(false, 0xB1), (true, 0x32)]);
+/// # Ok::<(), DisplayError>(())
+/// ```
+pub const ROUND_2_1_480X480: &[InitCommand] = &[
+    // Command2 bank 0
+    Command { cmd: 0xFF, params: &[0x77, 0x01, 0x00, 0x00, 0x10] },
+    Command { cmd: 0xC0, params: &[0x3B, 0x00] },
+    Command { cmd: 0xC1, params: &[0x0D, 0x02] },
+    Command { cmd: 0xC2, params: &[0x31, 0x05] },
+    Command { cmd: 0xCD, params: &[0x08] },
+    Command {
+        cmd: 0xB0,
+        params: &[
+            0x00, 0x11, 0x18, 0x0E, 0x11, 0x06, 0x07, 0x08, 0x07, 0x22, 0x04, 0x12, 0x0F, 0xAA,
+            0x31, 0x18,
+        ],
+    },
+    Command {
+        cmd: 0xB1,
+        params: &[
+            0x00, 0x11, 0x19, 0x0E, 0x12, 0x07, 0x08, 0x08, 0x08, 0x22, 0x04, 0x11, 0x11, 0xA9,
+            0x32, 0x18,
+        ],
+    },
+    // Command2 bank 1
+    Command { cmd: 0xFF, params: &[0x77, 0x01, 0x00, 0x00, 0x11] },
+    Command { cmd: 0xB0, params: &[0x60] },
+    Command { cmd: 0xB1, params: &[0x32] },
+    Command { cmd: 0xB2, params: &[0x07] },
+    Command { cmd: 0xB3, params: &[0x80] },
+    Command { cmd: 0xB5, params: &[0x49] },
+    Command { cmd: 0xB7, params: &[0x85] },
+    Command { cmd: 0xB8, params: &[0x21] },
+    Command { cmd: 0xC1, params: &[0x78] },
+    Command { cmd: 0xC2, params: &[0x78] },
+    // Gate-in-panel timing
+    Command { cmd: 0xE0, params: &[0x00, 0x1B, 0x02] },
+    Command {
+        cmd: 0xE1,
+        params: &[0x08, 0xA0, 0x00, 0x00, 0x07, 0xA0, 0x00, 0x00, 0x00, 0x44, 0x44],
+    },
+    Command {
+        cmd: 0xE2,
+        params: &[0x11, 0x11, 0x44, 0x44, 0xED, 0xA0, 0x00, 0x00, 0xEC, 0xA0, 0x00, 0x00],
+    },
+    Command { cmd: 0xE3, params: &[0x00, 0x00, 0x11, 0x11] },
+    Command { cmd: 0xE4, params: &[0x44, 0x44] },
+    Command {
+        cmd: 0xE5,
+        params: &[
+            0x0A, 0xE9, 0xD8, 0xA0, 0x0C, 0xEB, 0xD8, 0xA0, 0x0E, 0xED, 0xD8, 0xA0, 0x10, 0xEF,
+            0xD8, 0xA0,
+        ],
+    },
+    Command { cmd: 0xE6, params: &[0x00, 0x00, 0x11, 0x11] },
+    Command { cmd: 0xE7, params: &[0x44, 0x44] },
+    Command {
+        cmd: 0xE8,
+        params: &[
+            0x09, 0xE8, 0xD8, 0xA0, 0x0B, 0xEA, 0xD8, 0xA0, 0x0D, 0xEC, 0xD8, 0xA0, 0x0F, 0xEE,
+            0xD8, 0xA0,
+        ],
+    },
+    Command { cmd: 0xEB, params: &[0x02, 0x00, 0xE4, 0xE4, 0x88, 0x00, 0x40] },
+    Command { cmd: 0xEC, params: &[0x3C, 0x00] },
+    Command {
+        cmd: 0xED,
+        params: &[
+            0xAB, 0x89, 0x76, 0x54, 0x02, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x20, 0x45, 0x67,
+            0x98, 0xBA,
+        ],
+    },
+    // Command2 bank 3, VAP and VAN
+    Command { cmd: 0xFF, params: &[0x77, 0x01, 0x00, 0x00, 0x13] },
+    Command { cmd: 0xE5, params: &[0xE4] },
+    // Command1
+    Command { cmd: 0xFF, params: &[0x77, 0x01, 0x00, 0x00, 0x00] },
+    Command { cmd: 0x21, params: &[] },
+    Command { cmd: 0x3A, params: &[0x60] },
+    Command { cmd: 0x11, params: &[] },
+    DelayMs(120),
+    Command { cmd: 0x29, params: &[] },
+];
@@ -0,0 +1,62 @@
+//! Check the workspace with the minimum supported Rust version.
+
+use std::{fs, path::Path, process::Command};
+
+/// The feature sets checked.
+const FEATURES: &[&[&str]] = &[&["--no-default-features"], &[], &["--features", "defmt"]];
+
+#[test]
+#[ignore = "needs the minimum supported toolchain, run with `just msrv`"]
+fn builds_with_msrv() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let msrv = workspace_msrv(&root);
+    assert!(
+        toolchain_installed(&msrv),
+        "Rust {msrv} is not installed, install it with `rustup toolchain install {msrv}`"
+    );
+
+    let failures: Vec<String> = FEATURES
+        .iter()
+        .filter(|features| !check(&root, &msrv, features))
+        .map(|features| format!("[{}]", features.join(" ")))
+        .collect();
+
+    assert!(failures.is_empty(), "failed to build with Rust {msrv}:\n{}", failures.join("\n"));
+}
+
+/// Read the `rust-version` of the workspace manifest.
+fn workspace_msrv(root: &Path) -> String {
+    let manifest =
+        fs::read_to_string(root.join("Cargo.toml")).expect("failed to read the workspace manifest");
+    manifest
+        .lines()
+        .find_map(|line| line.strip_prefix("rust-version = \""))
+        .map(|version| version.trim_end().trim_end_matches('"').to_owned())
+        .expect("the workspace manifest has no `rust-version`")
+}
+
+/// Returns `true` if the `toolchain` is installed through `rustup`.
+fn toolchain_installed(toolchain: &str) -> bool {
+    Command::new("rustup")
+        .args(["run", toolchain, "rustc", "--version"])
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Check every target of the workspace with `toolchain`.
+///
+/// Uses a separate target directory, so the check does not wait for the
+/// lock held by the `cargo test` running it, nor mixes artifacts of
+/// different compilers.
+fn check(root: &Path, toolchain: &str, features: &[&str]) -> bool {
+    let status = Command::new("rustup")
+        .current_dir(root)
+        .env("CARGO_TARGET_DIR", root.join("target/msrv"))
+        .env_remove("RUSTC")
+        .env_remove("RUSTDOC")
+        .args(["run", toolchain, "cargo", "check", "--quiet", "--workspace", "--all-targets"])
+        .args(features)
+        .status()
+        .expect("failed to run cargo");
+    status.success()
+}
@@ -0,0 +1,135 @@
+//! Check every crate in the workspace for a target without `std`.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The target checked by default, a Cortex-M4F without `std`.
+const DEFAULT_TARGET: &str = "thumbv7em-none-eabihf";
+
+/// Crates with more features than this are checked with each feature on its
+/// own and with all features, instead of every combination.
+const MAX_POWERSET: usize = 5;
+
+/// A crate in the workspace and the features declared in its manifest.
+#[derive(Debug)]
+struct Package {
+    name: String,
+    features: Vec<String>,
+}
+
+#[test]
+#[ignore = "needs a target without `std`, run with `just no-std`"]
+fn builds_without_std() {
+    let target = env::var("ENSURE_NO_STD_TARGET").unwrap_or_else(|_| DEFAULT_TARGET.into());
+    assert!(
+        target_installed(&target),
+        "`{target}` is not installed, install it with `rustup target add {target}`"
+    );
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let failures: Vec<String> = workspace_packages(&root)
+        .iter()
+        .flat_map(|package| {
+            feature_matrix(&package.features).into_iter().map(move |set| (package, set))
+        })
+        .filter(|(package, features)| !check(&root, &target, &package.name, features))
+        .map(|(package, features)| format!("{} [{}]", package.name, features.join(", ")))
+        .collect();
+
+    assert!(failures.is_empty(), "failed to build for `{target}`:\n{}", failures.join("\n"));
+}
+
+/// Returns `true` if the standard library of `target` is installed.
+fn target_installed(target: &str) -> bool {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    let Ok(output) = Command::new(rustc).args(["--print", "sysroot"]).output() else {
+        return false;
+    };
+
+    let sysroot = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    sysroot.join("lib/rustlib").join(target).join("lib").is_dir()
+}
+
+/// Read every crate in the workspace, except this one.
+fn workspace_packages(root: &Path) -> Vec<Package> {
+    let crates = fs::read_dir(root.join("crates")).expect("failed to read the crates directory");
+    let mut manifests: Vec<PathBuf> = crates
+        .map(|entry| entry.expect("failed to read a crate").path().join("Cargo.toml"))
+        .filter(|manifest| manifest.is_file())
+        .collect();
+    manifests.push(root.join("Cargo.toml"));
+    manifests.sort();
+
+    manifests
+        .iter()
+        .map(|manifest| fs::read_to_string(manifest).expect("failed to read a manifest"))
+        .filter_map(|manifest| parse_manifest(&manifest))
+        .filter(|package| package.name != env!("CARGO_PKG_NAME"))
+        .collect()
+}
+
+/// Read the package name and the features of a manifest,
+/// without the `default` feature.
+fn parse_manifest(manifest: &str) -> Option<Package> {
+    let mut name = None;
+    let mut features = Vec::new();
+
+    let mut section = "";
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            section = line;
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let key = key.trim();
+        match section {
+            "[package]" if key == "name" => name = Some(value.trim().trim_matches('"').to_owned()),
+            "[features]" if key != "default" && is_key(key) => features.push(key.to_owned()),
+            _ => {}
+        }
+    }
+
+    Some(Package { name: name?, features })
+}
+
+/// Returns `true` if `key` is a bare TOML key.
+fn is_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Get the feature sets to check, always including no features.
+fn feature_matrix(features: &[String]) -> Vec<Vec<&str>> {
+    if features.len() <= MAX_POWERSET {
+        return (0..1usize << features.len())
+            .map(|mask| {
+                let enabled = features.iter().enumerate().filter(|(i, _)| mask >> i & 1 == 1);
+                enabled.map(|(_, feature)| feature.as_str()).collect()
+            })
+            .collect();
+    }
+
+    let mut matrix = vec![Vec::new()];
+    matrix.extend(features.iter().map(|feature| vec![feature.as_str()]));
+    matrix.push(features.iter().map(String::as_str).collect());
+    matrix
+}
+
+/// Check a crate for `target` with only `features` enabled.
+///
+/// Uses a separate target directory, so the check does not wait for the
+/// lock held by the `cargo test` running it.
+fn check(root: &Path, target: &str, package: &str, features: &[&str]) -> bool {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    let status = Command::new(cargo)
+        .current_dir(root)
+        .env("CARGO_TARGET_DIR", root.join("target/ensure-no-std"))
+        .args(["check", "--quiet", "--package", package, "--target", target])
+        .args(["--no-default-features", "--features", &features.join(",")])
+        .status()
+        .expect("failed to run cargo");
+    status.success()
+}
@@ -0,0 +1,254 @@
+use ef_driver_common::{
+    identify::{AsyncIdentify, DeviceIdentity},
+    mode::Async,
+};
+use embedded_hal::digital::PinState;
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+
+use crate::{
+    Direction, OutputFaults, Pins, PortState, SequenceError, SequenceStep, Tca9554, register,
+};
+
+/// Reset all devices on the bus using the I2C general call.
+///
+/// This resets **every** device on the bus that honors the general call
+/// software reset, not only TCA9554 devices. It is the only way to recover a
+/// TCA9554 that holds SDA low in the middle of a transaction.
+///
+/// After the reset, call [`Tca9554::after_external_reset`] on every driver
+/// to restore its configuration.
+///
+/// # Errors
+///
+/// Returns an error if the I2C communication fails
+///
+/// # Example
+///
+/// ```rust
+/// use ef_tca9554::{general_call_reset_async, test_util::Recorder};
+/// use embedded_hal_async::i2c::ErrorKind;
+/// # use ef_tca9554::test_util::block_on;
+///
+/// let mut i2c = Recorder::default();
+/// block_on(general_call_reset_async(&mut i2c))?;
+/// assert_eq!(i2c.writes, [(0x00, vec![0x06])]);
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub async fn general_call_reset_async<I2C: I2c>(i2c: &mut I2C) -> Result<(), I2C::Error> {
+    i2c.write(register::GENERAL_CALL_ADDRESS, &[register::GENERAL_CALL_RESET]).await
+}
+
+impl<I2C: I2c> Tca9554<I2C, Async> {
+    /// Read the levels of all pins.
+    ///
+    /// Inputs with polarity inversion enabled are reported inverted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn read_inputs(&mut self) -> Result<Pins, I2C::Error> {
+        let mut buf = [0u8; 1];
+        self.read_register(register::TCA9554_INPUT_PORT, &mut buf).await?;
+        Ok(Pins::from_bits_truncate(buf[0]))
+    }
+
+    /// Set the output levels of all pins.
+    ///
+    /// Levels of pins configured as inputs are stored,
+    /// but have no effect until the pins become outputs.
+    ///
+    /// Runs an automatic output check if one is due,
+    /// see [`Tca9554::set_output_check`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn write_outputs(&mut self, output: Pins) -> Result<(), I2C::Error> {
+        self.write_register(register::TCA9554_OUTPUT_PORT, output.bits()).await?;
+        self.state.output = output;
+
+        if self.output_check_due() {
+            let faults = self.check_outputs().await?;
+            self.faults = self.faults.union(faults);
+        }
+        Ok(())
+    }
+
+    /// Compare the levels driven on the output pins against the levels
+    /// read back from the input register.
+    ///
+    /// See [`OutputFaults`] for which faults can be detected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn check_outputs(&mut self) -> Result<OutputFaults, I2C::Error> {
+        let input = self.read_inputs().await?;
+        Ok(OutputFaults::compare(&self.state, input))
+    }
+
+    /// Drive the given pins high.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    #[inline]
+    pub async fn set_high(&mut self, pins: Pins) -> Result<(), I2C::Error> {
+        self.write_outputs(self.state.output.union(pins)).await
+    }
+
+    /// Drive the given pins low.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    #[inline]
+    pub async fn set_low(&mut self, pins: Pins) -> Result<(), I2C::Error> {
+        self.write_outputs(self.state.output.difference(pins)).await
+    }
+
+    /// Toggle the given pins.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    #[inline]
+    pub async fn toggle(&mut self, pins: Pins) -> Result<(), I2C::Error> {
+        self.write_outputs(self.state.output.symmetric_difference(pins)).await
+    }
+
+    /// Set which inputs are reported inverted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn set_polarity_inversion(&mut self, inverted: Pins) -> Result<(), I2C::Error> {
+        self.write_register(register::TCA9554_POLARITY_INVERSION, inverted.bits()).await?;
+        self.state.polarity = inverted;
+        Ok(())
+    }
+
+    /// Set the [`Direction`] of the given pins.
+    ///
+    /// Pins that become outputs immediately drive their stored output level,
+    /// set it with [`Tca9554::write_outputs`] beforehand to avoid glitches.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn set_direction(
+        &mut self,
+        pins: Pins,
+        direction: Direction,
+    ) -> Result<(), I2C::Error> {
+        let mut inputs = self.state.inputs;
+        inputs.set(pins, direction == Direction::Input);
+        self.write_register(register::TCA9554_CONFIGURATION, inputs.bits()).await?;
+        self.state.inputs = inputs;
+        Ok(())
+    }
+
+    /// Write the cached [`PortState`] to the device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn reinitialize(&mut self) -> Result<(), I2C::Error> {
+        for (register, value) in self.state.register_writes() {
+            self.write_register(register, value).await?;
+        }
+        Ok(())
+    }
+
+    /// Restore the cached [`PortState`] after the device has been reset,
+    /// for example by
+    /// [`general_call_reset_async`](crate::general_call_reset_async).
+    ///
+    /// Only registers that differ from their power-on defaults are written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn after_external_reset(&mut self) -> Result<(), I2C::Error> {
+        let defaults = PortState::POWER_ON.register_writes();
+        for (write, default) in self.state.register_writes().into_iter().zip(defaults) {
+            if write != default {
+                self.write_register(write.0, write.1).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Run a sequence of [`SequenceStep`]s in order, stopping at the first
+    /// step that fails.
+    ///
+    /// `callback` is called with the identifier of every
+    /// [`SequenceStep::Callback`], and can fail the sequence by returning an
+    /// error.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: the sequence stops part way through,
+    /// with the steps already run left in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns the index of the failed step if the I2C communication fails
+    /// or if the callback returns an error.
+    pub async fn run_sequence<D: DelayNs, C>(
+        &mut self,
+        steps: &[SequenceStep],
+        delay: &mut D,
+        mut callback: impl AsyncFnMut(u8) -> Result<(), C>,
+    ) -> Result<(), SequenceError<I2C::Error, C>> {
+        for (step, &action) in steps.iter().enumerate() {
+            match action {
+                SequenceStep::SetPin { pin, level } => {
+                    let result = self.drive(pin, level).await;
+                    result.map_err(|error| SequenceError::I2C { step, error })?;
+                }
+                SequenceStep::DelayMs(ms) => delay.delay_ms(ms).await,
+                SequenceStep::Callback(id) => {
+                    callback(id).await.map_err(|error| SequenceError::Callback { step, error })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Drive pins to a level, then make them outputs if they are not already.
+    async fn drive(&mut self, pins: Pins, level: PinState) -> Result<(), I2C::Error> {
+        match level {
+            PinState::High => self.set_high(pins).await?,
+            PinState::Low => self.set_low(pins).await?,
+        }
+        if !self.state.outputs().contains(pins) {
+            self.set_direction(pins, Direction::Output).await?;
+        }
+        Ok(())
+    }
+
+    /// Read data from a register
+    async fn read_register(&mut self, register: u8, buf: &mut [u8]) -> Result<(), I2C::Error> {
+        self.i2c.write_read(self.address, core::slice::from_ref(&register), buf).await
+    }
+
+    // Write data to a register
+    async fn write_register(&mut self, register: u8, value: u8) -> Result<(), I2C::Error> {
+        self.i2c.write(self.address, [register, value].as_slice()).await
+    }
+}
+
+impl<I2C: I2c> AsyncIdentify for Tca9554<I2C, Async> {
+    type Error = I2C::Error;
+
+    /// Read back the output, polarity inversion and configuration registers
+    /// as the ID, see [`Identify`](ef_driver_common::identify::Identify).
+    async fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> {
+        let mut registers = [0u8; 3];
+        for (value, register) in registers.iter_mut().zip(register::TCA9554_OUTPUT_PORT..) {
+            self.read_register(register, core::slice::from_mut(value)).await?;
+        }
+        Ok(DeviceIdentity::new("TCA9554", &registers, None))
+    }
+}
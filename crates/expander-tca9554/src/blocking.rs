@@ -0,0 +1,395 @@
+use ef_driver_common::{
+    identify::{DeviceIdentity, Identify},
+    mode::Blocking,
+};
+use embedded_hal::{delay::DelayNs, digital::PinState, i2c::I2c};
+
+use crate::{
+    Direction, OutputFaults, Pins, PortState, SequenceError, SequenceStep, Tca9554, register,
+};
+
+/// Reset all devices on the bus using the I2C general call.
+///
+/// This resets **every** device on the bus that honors the general call
+/// software reset, not only TCA9554 devices. It is the only way to recover a
+/// TCA9554 that holds SDA low in the middle of a transaction.
+///
+/// After the reset, call [`Tca9554::after_external_reset`] on every driver
+/// to restore its configuration.
+///
+/// # Errors
+///
+/// Returns an error if the I2C communication fails
+///
+/// # Example
+///
+/// ```rust
+/// use ef_tca9554::{general_call_reset, test_util::Recorder};
+/// use embedded_hal::i2c::ErrorKind;
+///
+/// let mut i2c = Recorder::default();
+/// general_call_reset(&mut i2c)?;
+/// assert_eq!(i2c.writes, [(0x00, vec![0x06])]);
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub fn general_call_reset<I2C: I2c>(i2c: &mut I2C) -> Result<(), I2C::Error> {
+    i2c.write(register::GENERAL_CALL_ADDRESS, &[register::GENERAL_CALL_RESET])
+}
+
+impl<I2C: I2c> Tca9554<I2C, Blocking> {
+    /// Read the levels of all pins.
+    ///
+    /// Inputs with polarity inversion enabled are reported inverted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn read_inputs(&mut self) -> Result<Pins, I2C::Error> {
+        let mut buf = [0u8; 1];
+        self.read_register(register::TCA9554_INPUT_PORT, &mut buf)?;
+        Ok(Pins::from_bits_truncate(buf[0]))
+    }
+
+    /// Set the output levels of all pins.
+    ///
+    /// Levels of pins configured as inputs are stored,
+    /// but have no effect until the pins become outputs.
+    ///
+    /// Runs an automatic output check if one is due,
+    /// see [`Tca9554::set_output_check`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn write_outputs(&mut self, output: Pins) -> Result<(), I2C::Error> {
+        self.write_register(register::TCA9554_OUTPUT_PORT, output.bits())?;
+        self.state.output = output;
+
+        if self.output_check_due() {
+            let faults = self.check_outputs()?;
+            self.faults = self.faults.union(faults);
+        }
+        Ok(())
+    }
+
+    /// Compare the levels driven on the output pins against the levels
+    /// read back from the input register.
+    ///
+    /// See [`OutputFaults`] for which faults can be detected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_tca9554::{OutputFault, Pins, Tca9554, mode::Blocking, test_util::Registers};
+    /// use embedded_hal::i2c::ErrorKind;
+    ///
+    /// let mut expander = Tca9554::<_, Blocking>::new(Registers::DEFAULT, 0x20).with_output_check(2);
+    /// expander.set_direction(Pins::P0 | Pins::P1, ef_tca9554::Direction::Output)?;
+    ///
+    /// // The outputs read back as driven
+    /// expander.set_low(Pins::P1)?;
+    /// assert!(expander.check_outputs()?.is_empty());
+    ///
+    /// // P0 is shorted to ground while driven high
+    /// expander.i2c_mut().shorted = Pins::P0.bits();
+    /// let faults = expander.check_outputs()?;
+    /// assert_eq!(faults.fault(0), Some(OutputFault::DrivenHighReadsLow));
+    /// assert_eq!(faults.fault(1), None);
+    ///
+    /// // The automatic check runs on every second output write
+    /// assert!(expander.take_output_faults().is_empty());
+    /// expander.set_high(Pins::P1)?;
+    /// assert_eq!(expander.take_output_faults().pins(), Pins::P0);
+    /// # Ok::<(), ErrorKind>(())
+    /// ```
+    pub fn check_outputs(&mut self) -> Result<OutputFaults, I2C::Error> {
+        let input = self.read_inputs()?;
+        Ok(OutputFaults::compare(&self.state, input))
+    }
+
+    /// Drive the given pins high.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    #[inline]
+    pub fn set_high(&mut self, pins: Pins) -> Result<(), I2C::Error> {
+        self.write_outputs(self.state.output.union(pins))
+    }
+
+    /// Drive the given pins low.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    #[inline]
+    pub fn set_low(&mut self, pins: Pins) -> Result<(), I2C::Error> {
+        self.write_outputs(self.state.output.difference(pins))
+    }
+
+    /// Toggle the given pins.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    #[inline]
+    pub fn toggle(&mut self, pins: Pins) -> Result<(), I2C::Error> {
+        self.write_outputs(self.state.output.symmetric_difference(pins))
+    }
+
+    /// Set which inputs are reported inverted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn set_polarity_inversion(&mut self, inverted: Pins) -> Result<(), I2C::Error> {
+        self.write_register(register::TCA9554_POLARITY_INVERSION, inverted.bits())?;
+        self.state.polarity = inverted;
+        Ok(())
+    }
+
+    /// Set the [`Direction`] of the given pins.
+    ///
+    /// Pins that become outputs immediately drive their stored output level,
+    /// set it with [`Tca9554::write_outputs`] beforehand to avoid glitches.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn set_direction(&mut self, pins: Pins, direction: Direction) -> Result<(), I2C::Error> {
+        let mut inputs = self.state.inputs;
+        inputs.set(pins, direction == Direction::Input);
+        self.write_register(register::TCA9554_CONFIGURATION, inputs.bits())?;
+        self.state.inputs = inputs;
+        Ok(())
+    }
+
+    /// Write the cached [`PortState`] to the device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn reinitialize(&mut self) -> Result<(), I2C::Error> {
+        for (register, value) in self.state.register_writes() {
+            self.write_register(register, value)?;
+        }
+        Ok(())
+    }
+
+    /// Restore the cached [`PortState`] after the device has been reset,
+    /// for example by [`general_call_reset`](crate::general_call_reset).
+    ///
+    /// Only registers that differ from their power-on defaults are written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn after_external_reset(&mut self) -> Result<(), I2C::Error> {
+        let defaults = PortState::POWER_ON.register_writes();
+        for (write, default) in self.state.register_writes().into_iter().zip(defaults) {
+            if write != default {
+                self.write_register(write.0, write.1)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Run a sequence of [`SequenceStep`]s in order, stopping at the first
+    /// step that fails.
+    ///
+    /// `callback` is called with the identifier of every
+    /// [`SequenceStep::Callback`], and can fail the sequence by returning an
+    /// error.
+    ///
+    /// # Errors
+    ///
+    /// Returns the index of the failed step if the I2C communication fails
+    /// or if the callback returns an error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use core::cell::RefCell;
+    ///
+    /// use ef_tca9554::{Pins, SequenceError, SequenceStep, Tca9554, mode::Blocking};
+    /// use embedded_hal::{
+    ///     delay::DelayNs,
+    ///     digital::PinState,
+    ///     i2c::{ErrorKind, ErrorType, I2c, Operation},
+    /// };
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Event {
+    ///     Write(u8, u8),
+    ///     DelayMs(u32),
+    ///     Callback(u8),
+    /// }
+    ///
+    /// /// A device that records register writes.
+    /// struct Recorder<'a>(&'a RefCell<Vec<Event>>);
+    ///
+    /// impl ErrorType for Recorder<'_> {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl I2c for Recorder<'_> {
+    ///     fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+    ///         for op in ops {
+    ///             if let Operation::Write([register, value]) = op {
+    ///                 self.0.borrow_mut().push(Event::Write(*register, *value));
+    ///             }
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// /// A delay that records the time waited.
+    /// struct Clock<'a>(&'a RefCell<Vec<Event>>);
+    ///
+    /// impl DelayNs for Clock<'_> {
+    ///     fn delay_ns(&mut self, _: u32) { unreachable!() }
+    ///
+    ///     fn delay_ms(&mut self, ms: u32) { self.0.borrow_mut().push(Event::DelayMs(ms)); }
+    /// }
+    ///
+    /// const DISPLAY: u8 = 0;
+    /// const TOUCH: u8 = 1;
+    ///
+    /// // Power the display, release it from reset, then strap the touch
+    /// // controller's address with its interrupt pin while releasing its reset
+    /// let sequence = [
+    ///     SequenceStep::SetPin { pin: Pins::P0, level: PinState::High },
+    ///     SequenceStep::DelayMs(10),
+    ///     SequenceStep::SetPin { pin: Pins::P1, level: PinState::High },
+    ///     SequenceStep::DelayMs(120),
+    ///     SequenceStep::Callback(DISPLAY),
+    ///     SequenceStep::SetPin { pin: Pins::P3, level: PinState::Low },
+    ///     SequenceStep::SetPin { pin: Pins::P2, level: PinState::High },
+    ///     SequenceStep::DelayMs(5),
+    ///     SequenceStep::Callback(TOUCH),
+    /// ];
+    ///
+    /// let events = RefCell::new(Vec::new());
+    /// let mut expander = Tca9554::<_, Blocking>::new(Recorder(&events), 0x20);
+    /// let mut clock = Clock(&events);
+    ///
+    /// expander.run_sequence(&sequence, &mut clock, |id| {
+    ///     events.borrow_mut().push(Event::Callback(id));
+    ///     Ok::<(), ()>(())
+    /// })?;
+    /// assert_eq!(
+    ///     events.take(),
+    ///     [
+    ///         // Outputs are written before the configuration
+    ///         Event::Write(0x01, 0xFF),
+    ///         Event::Write(0x03, 0b1111_1110),
+    ///         Event::DelayMs(10),
+    ///         Event::Write(0x01, 0xFF),
+    ///         Event::Write(0x03, 0b1111_1100),
+    ///         Event::DelayMs(120),
+    ///         Event::Callback(DISPLAY),
+    ///         Event::Write(0x01, 0b1111_0111),
+    ///         Event::Write(0x03, 0b1111_0100),
+    ///         Event::Write(0x01, 0b1111_0111),
+    ///         Event::Write(0x03, 0b1111_0000),
+    ///         Event::DelayMs(5),
+    ///         Event::Callback(TOUCH),
+    ///     ]
+    /// );
+    ///
+    /// // Errors report the step that failed
+    /// let result = expander.run_sequence(&sequence, &mut clock, |id| match id {
+    ///     TOUCH => Err("Touch controller not found"),
+    ///     _ => Ok(()),
+    /// });
+    /// assert_eq!(
+    ///     result,
+    ///     Err(SequenceError::Callback { step: 8, error: "Touch controller not found" })
+    /// );
+    /// # Ok::<(), SequenceError<ErrorKind, ()>>(())
+    /// ```
+    pub fn run_sequence<D: DelayNs, C>(
+        &mut self,
+        steps: &[SequenceStep],
+        delay: &mut D,
+        mut callback: impl FnMut(u8) -> Result<(), C>,
+    ) -> Result<(), SequenceError<I2C::Error, C>> {
+        for (step, &action) in steps.iter().enumerate() {
+            match action {
+                SequenceStep::SetPin { pin, level } => {
+                    let result = self.drive(pin, level);
+                    result.map_err(|error| SequenceError::I2C { step, error })?;
+                }
+                SequenceStep::DelayMs(ms) => delay.delay_ms(ms),
+                SequenceStep::Callback(id) => {
+                    callback(id).map_err(|error| SequenceError::Callback { step, error })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Drive pins to a level, then make them outputs if they are not already.
+    fn drive(&mut self, pins: Pins, level: PinState) -> Result<(), I2C::Error> {
+        match level {
+            PinState::High => self.set_high(pins)?,
+            PinState::Low => self.set_low(pins)?,
+        }
+        if !self.state.outputs().contains(pins) {
+            self.set_direction(pins, Direction::Output)?;
+        }
+        Ok(())
+    }
+
+    /// Read data from a register
+    fn read_register(&mut self, register: u8, buf: &mut [u8]) -> Result<(), I2C::Error> {
+        self.i2c.write_read(self.address, core::slice::from_ref(&register), buf)
+    }
+
+    // Write data to a register
+    fn write_register(&mut self, register: u8, value: u8) -> Result<(), I2C::Error> {
+        self.i2c.write(self.address, [register, value].as_slice())
+    }
+}
+
+impl<I2C: I2c> Identify for Tca9554<I2C, Blocking> {
+    type Error = I2C::Error;
+
+    /// Read back the output, polarity inversion and configuration registers
+    /// as the ID.
+    ///
+    /// The TCA9554 has no ID register, so this is only a weak check: after
+    /// power-on the registers read `[0xFF, 0x00, 0xFF]`, and afterwards they
+    /// should match the cached [`PortState`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_driver_common::identify::Identify;
+    /// use ef_tca9554::{Pins, Tca9554, mode::Blocking, test_util::Registers};
+    /// use embedded_hal::i2c::ErrorKind;
+    ///
+    /// let mut expander = Tca9554::<_, Blocking>::new(Registers::DEFAULT, 0x20);
+    /// let identity = expander.identify()?;
+    /// assert_eq!((identity.name(), identity.id()), ("TCA9554", &[0xFF, 0x00, 0xFF][..]));
+    ///
+    /// // The registers read back as the cached state
+    /// expander.set_low(Pins::P0)?;
+    /// let state = expander.state();
+    /// let expected = [state.output.bits(), state.polarity.bits(), state.inputs.bits()];
+    /// assert_eq!(expander.identify()?.id(), expected);
+    /// # Ok::<(), ErrorKind>(())
+    /// ```
+    fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> {
+        // Reads do not auto-increment, so each register is read separately
+        let mut registers = [0u8; 3];
+        for (value, register) in registers.iter_mut().zip(register::TCA9554_OUTPUT_PORT..) {
+            self.read_register(register, core::slice::from_mut(value))?;
+        }
+        Ok(DeviceIdentity::new("TCA9554", &registers, None))
+    }
+}
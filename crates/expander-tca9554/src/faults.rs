@@ -0,0 +1,118 @@
+//! Comparing the driven output levels against the pin levels read back.
+
+use crate::{Pins, PortState};
+
+/// A fault on a pin configured as an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OutputFault {
+    /// The pin is driven high, but reads low.
+    DrivenHighReadsLow,
+    /// The pin is driven low, but reads high.
+    DrivenLowReadsHigh,
+}
+
+/// Output pins whose level read back differs from the level driven.
+///
+/// The input register reflects the voltage on every pin, including pins
+/// configured as outputs. An output reads the opposite of its driven level
+/// only when something overpowers the output driver, such as a load shorted
+/// to ground or to the supply, or a pin shorted to a neighbour driven the
+/// other way.
+///
+/// Open loads cannot be detected: a broken solder joint or a burnt out LED
+/// leaves the pin at the level it is driven to. Loads that only pull the
+/// pin partway, such as a relay coil drawing more than the output is rated
+/// for, are detected only once the pin crosses the input threshold.
+///
+/// Returned by `Tca9554::check_outputs`.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_tca9554::{OutputFault, OutputFaults, Pins, PortState};
+///
+/// // P0 and P1 are outputs driven high and low, the rest are inputs
+/// let state =
+///     PortState { output: Pins::P0, inputs: !(Pins::P0 | Pins::P1), ..PortState::POWER_ON };
+///
+/// // Matching levels, inputs are ignored
+/// let faults = OutputFaults::compare(&state, Pins::P0 | Pins::P7);
+/// assert!(faults.is_empty());
+///
+/// // Both outputs read the opposite level
+/// let faults = OutputFaults::compare(&state, Pins::P1);
+/// assert_eq!(faults.pins(), Pins::P0 | Pins::P1);
+/// assert_eq!(faults.fault(0), Some(OutputFault::DrivenHighReadsLow));
+/// assert_eq!(faults.fault(1), Some(OutputFault::DrivenLowReadsHigh));
+/// assert_eq!(faults.fault(2), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OutputFaults {
+    /// Output pins driven high that read low.
+    pub driven_high_reads_low: Pins,
+    /// Output pins driven low that read high.
+    pub driven_low_reads_high: Pins,
+}
+
+impl OutputFaults {
+    /// No faults.
+    pub const NONE: Self =
+        Self { driven_high_reads_low: Pins::empty(), driven_low_reads_high: Pins::empty() };
+
+    /// Compare the output levels of a [`PortState`]
+    /// against the levels read from the input register.
+    ///
+    /// Polarity inversion only applies to pins configured as inputs,
+    /// so the levels of outputs are compared as read.
+    #[must_use]
+    pub const fn compare(state: &PortState, input: Pins) -> Self {
+        let outputs = state.outputs();
+        let high = state.output.intersection(outputs);
+        let low = state.output.complement().intersection(outputs);
+        Self {
+            driven_high_reads_low: high.difference(input),
+            driven_low_reads_high: low.intersection(input),
+        }
+    }
+
+    /// Get all pins with a fault.
+    #[inline]
+    #[must_use]
+    pub const fn pins(&self) -> Pins {
+        self.driven_high_reads_low.union(self.driven_low_reads_high)
+    }
+
+    /// Returns `true` if no pin has a fault.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool { self.pins().is_empty() }
+
+    /// Get the [`OutputFault`] of a pin by its index, if it has one.
+    #[must_use]
+    pub const fn fault(&self, index: u8) -> Option<OutputFault> {
+        let Some(pin) = Pins::from_index(index) else { return None };
+        if self.driven_high_reads_low.contains(pin) {
+            Some(OutputFault::DrivenHighReadsLow)
+        } else if self.driven_low_reads_high.contains(pin) {
+            Some(OutputFault::DrivenLowReadsHigh)
+        } else {
+            None
+        }
+    }
+
+    /// Combine the faults of two checks.
+    #[inline]
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self {
+            driven_high_reads_low: self.driven_high_reads_low.union(other.driven_high_reads_low),
+            driven_low_reads_high: self.driven_low_reads_high.union(other.driven_low_reads_high),
+        }
+    }
+}
+
+impl Default for OutputFaults {
+    fn default() -> Self { Self::NONE }
+}
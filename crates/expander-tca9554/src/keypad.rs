@@ -0,0 +1,463 @@
+//! Scanning a key matrix connected to the expander.
+
+use ef_driver_common::mode::{Async, Blocking};
+
+use crate::{Direction, Pins, Tca9554};
+
+/// A scanner for a key matrix of `R` rows and `C` columns.
+///
+/// Rows are outputs held low between scans, columns are inputs pulled high
+/// by the expander, so a pressed key pulls its column low. Keys should not
+/// share a row and a column with other pressed keys, see
+/// [`KeyStates::is_ghosting`].
+///
+/// Each scan first reads the columns once. If no key is pressed or being
+/// released, the scan ends there, otherwise every row is driven low on its
+/// own and the columns read, for `2 * R + 2` transactions.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_tca9554::{Key, KeyEvent, MatrixScanner, Pins, Tca9554, mode::Blocking};
+/// use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
+///
+/// /// A 4x4 keypad on `P0-P3` (rows) and `P4-P7` (columns),
+/// /// counting transactions.
+/// struct Keypad {
+///     registers: [u8; 4],
+///     pointer: usize,
+///     pressed: Vec<(u8, u8)>,
+///     transactions: usize,
+/// }
+///
+/// impl Keypad {
+///     /// Get the lines pulled low through pressed keys, the way current
+///     /// flows through a matrix without diodes.
+///     fn pulled_low(&self, mut low: u8) -> u8 {
+///         loop {
+///             let next = self.pressed.iter().fold(low, |low, &(row, column)| {
+///                 let key = 1 << row | 1 << (column + 4);
+///                 if low & key != 0 { low | key } else { low }
+///             });
+///             if next == low {
+///                 return low;
+///             }
+///             low = next;
+///         }
+///     }
+/// }
+///
+/// impl ErrorType for Keypad {
+///     type Error = ErrorKind;
+/// }
+///
+/// impl I2c for Keypad {
+///     fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+///         self.transactions += 1;
+///         for op in ops {
+///             match op {
+///                 Operation::Write([register]) => self.pointer = usize::from(*register),
+///                 Operation::Write([register, value]) => {
+///                     self.registers[usize::from(*register)] = *value;
+///                 }
+///                 Operation::Read([value]) => {
+///                     assert_eq!(self.pointer, 0);
+///                     let (output, inputs) = (self.registers[1], self.registers[3]);
+///                     *value = !self.pulled_low(!output & !inputs);
+///                 }
+///                 _ => unreachable!(),
+///             }
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// // The expander starts with every pin an input
+/// let keypad =
+///     Keypad { registers: [0, 0xFF, 0, 0xFF], pointer: 0, pressed: vec![], transactions: 0 };
+/// let mut tca = Tca9554::<_, Blocking>::new(keypad, 0x20);
+/// let rows = Pins::P0 | Pins::P1 | Pins::P2 | Pins::P3;
+/// let columns = Pins::P4 | Pins::P5 | Pins::P6 | Pins::P7;
+/// let mut keypad = MatrixScanner::<4, 4>::new(rows, columns).with_debounce(2);
+/// keypad.init(&mut tca)?;
+///
+/// // An idle scan takes a single transaction
+/// tca.i2c_mut().transactions = 0;
+/// assert!(keypad.scan(&mut tca)?.events().next().is_none());
+/// assert_eq!(tca.i2c().transactions, 1);
+///
+/// // A press is reported after two scans
+/// tca.i2c_mut().pressed = vec![(1, 2)];
+/// assert!(keypad.scan(&mut tca)?.events().next().is_none());
+/// let states = keypad.scan(&mut tca)?;
+/// let key = Key { row: 1, column: 2 };
+/// assert_eq!(states.events().collect::<Vec<_>>(), [KeyEvent::Pressed(key)]);
+/// assert!(states.is_pressed(key));
+/// assert_eq!(tca.i2c().transactions, 1 + 2 * 10);
+///
+/// // Several keys on different rows and columns
+/// tca.i2c_mut().pressed = vec![(1, 2), (0, 0), (3, 3)];
+/// keypad.scan(&mut tca)?;
+/// let states = keypad.scan(&mut tca)?;
+/// let pressed = [Key { row: 0, column: 0 }, key, Key { row: 3, column: 3 }];
+/// assert_eq!(states.pressed().collect::<Vec<_>>(), pressed);
+///
+/// // Three corners of a rectangle make the fourth look pressed
+/// tca.i2c_mut().pressed = vec![(1, 2), (0, 0), (3, 3), (0, 2)];
+/// let states = keypad.scan(&mut tca)?;
+/// assert!(states.is_ghosting());
+/// assert!(!states.is_pressed(Key { row: 1, column: 0 }));
+/// assert_eq!(states.pressed().collect::<Vec<_>>(), pressed);
+///
+/// // Releasing every key
+/// tca.i2c_mut().pressed.clear();
+/// keypad.scan(&mut tca)?;
+/// let states = keypad.scan(&mut tca)?;
+/// assert_eq!(states.events().filter(|e| matches!(e, KeyEvent::Released(_))).count(), 3);
+/// assert_eq!(states.pressed().count(), 0);
+/// # Ok::<(), ErrorKind>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MatrixScanner<const R: usize, const C: usize> {
+    rows: [Pins; R],
+    columns: [Pins; C],
+    debounce: u8,
+    state: [u8; R],
+    counters: [[u8; C]; R],
+}
+
+impl<const R: usize, const C: usize> MatrixScanner<R, C> {
+    /// Create a [`MatrixScanner`] from the row and column pins,
+    /// in order of their pin index.
+    ///
+    /// Keys are reported as soon as a single scan sees them change,
+    /// see [`MatrixScanner::with_debounce`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of row or column pins does not match `R` or `C`,
+    /// or if a pin is both a row and a column.
+    #[must_use]
+    pub const fn new(rows: Pins, columns: Pins) -> Self {
+        assert!(rows.bits().count_ones() as usize == R, "`rows` must contain `R` pins");
+        assert!(columns.bits().count_ones() as usize == C, "`columns` must contain `C` pins");
+        assert!(rows.intersection(columns).is_empty(), "a pin cannot be a row and a column");
+
+        Self {
+            rows: split(rows),
+            columns: split(columns),
+            debounce: 1,
+            state: [0; R],
+            counters: [[0; C]; R],
+        }
+    }
+
+    /// Set the number of consecutive scans a key must be seen in its new
+    /// state before the change is reported, a value of `0` is treated as `1`.
+    #[inline]
+    #[must_use]
+    pub const fn with_debounce(mut self, scans: u8) -> Self {
+        self.debounce = if scans == 0 { 1 } else { scans };
+        self
+    }
+
+    /// Get the row pins.
+    #[must_use]
+    pub const fn rows(&self) -> Pins { union(&self.rows) }
+
+    /// Get the column pins.
+    #[must_use]
+    pub const fn columns(&self) -> Pins { union(&self.columns) }
+
+    /// Get the bits of the pressed columns from the input levels.
+    fn pressed_columns(&self, inputs: Pins) -> u8 {
+        self.columns
+            .iter()
+            .enumerate()
+            .filter(|(_, column)| !inputs.intersects(**column))
+            .fold(0, |bits, (index, _)| bits | 1 << index)
+    }
+
+    /// Debounce the keys seen by a scan, unless the scan is ambiguous.
+    fn update(&mut self, raw: [u8; R]) -> KeyStates<R> {
+        let previous = self.state;
+        let ghosting = is_ghosting(&raw);
+        if !ghosting {
+            for (row, (state, counters)) in
+                self.state.iter_mut().zip(&mut self.counters).enumerate()
+            {
+                for (column, counter) in counters.iter_mut().enumerate() {
+                    let bit = 1 << column;
+                    if (raw[row] ^ *state) & bit == 0 {
+                        *counter = 0;
+                    } else {
+                        *counter += 1;
+                        if *counter >= self.debounce {
+                            *state ^= bit;
+                            *counter = 0;
+                        }
+                    }
+                }
+            }
+        }
+        KeyStates { pressed: self.state, previous, ghosting }
+    }
+
+    /// Returns `true` if a scan has to drive the rows, because a key is
+    /// pressed or may be changing.
+    fn is_busy(&self, raw: u8) -> bool { raw != 0 || self.state.iter().any(|&row| row != 0) }
+}
+
+impl<const R: usize, const C: usize> MatrixScanner<R, C> {
+    /// Configure the rows as outputs driven low and the columns as inputs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn init<I2C: embedded_hal::i2c::I2c>(
+        &self,
+        tca: &mut Tca9554<I2C, Blocking>,
+    ) -> Result<(), I2C::Error> {
+        tca.set_low(self.rows())?;
+        tca.set_direction(self.rows(), Direction::Output)?;
+        tca.set_direction(self.columns(), Direction::Input)
+    }
+
+    /// Scan the matrix once, returning the debounced [`KeyStates`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub fn scan<I2C: embedded_hal::i2c::I2c>(
+        &mut self,
+        tca: &mut Tca9554<I2C, Blocking>,
+    ) -> Result<KeyStates<R>, I2C::Error> {
+        let mut raw = [0u8; R];
+        let idle = self.pressed_columns(tca.read_inputs()?);
+        if self.is_busy(idle) {
+            let rows = self.rows();
+            for (bits, row) in raw.iter_mut().zip(self.rows) {
+                tca.write_outputs(tca.state().output.union(rows).difference(row))?;
+                *bits = self.pressed_columns(tca.read_inputs()?);
+            }
+            tca.set_low(rows)?;
+        }
+        Ok(self.update(raw))
+    }
+
+    /// Configure the rows as outputs driven low and the columns as inputs.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the configuration can be written again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn init_async<I2C: embedded_hal_async::i2c::I2c>(
+        &self,
+        tca: &mut Tca9554<I2C, Async>,
+    ) -> Result<(), I2C::Error> {
+        tca.set_low(self.rows()).await?;
+        tca.set_direction(self.rows(), Direction::Output).await?;
+        tca.set_direction(self.columns(), Direction::Input).await
+    }
+
+    /// Scan the matrix once, returning the debounced [`KeyStates`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_tca9554::{Key, KeyEvent, MatrixScanner, Pins, Tca9554, mode::Async};
+    /// use embedded_hal_async::i2c::{ErrorKind, ErrorType, I2c, Operation};
+    /// # use ef_tca9554::test_util::block_on;
+    ///
+    /// /// A 2x2 keypad on `P0-P1` (rows) and `P2-P3` (columns),
+    /// /// with a single key pressed.
+    /// struct Keypad {
+    ///     registers: [u8; 4],
+    ///     pointer: usize,
+    ///     pressed: Option<(u8, u8)>,
+    /// }
+    ///
+    /// impl ErrorType for Keypad {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl I2c for Keypad {
+    ///     async fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+    ///         for op in ops {
+    ///             match op {
+    ///                 Operation::Write([register]) => self.pointer = usize::from(*register),
+    ///                 Operation::Write([register, value]) => {
+    ///                     self.registers[usize::from(*register)] = *value;
+    ///                 }
+    ///                 Operation::Read([value]) => {
+    ///                     let low = !self.registers[1] & !self.registers[3];
+    ///                     *value = match self.pressed {
+    ///                         Some((row, column)) if low & 1 << row != 0 => !(1 << (column + 2)),
+    ///                         _ => 0xFF,
+    ///                     } & !low;
+    ///                 }
+    ///                 _ => unreachable!(),
+    ///             }
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let keypad = Keypad { registers: [0, 0xFF, 0, 0xFF], pointer: 0, pressed: None };
+    /// let mut tca = Tca9554::<_, Async>::new(keypad, 0x20);
+    /// let mut keypad = MatrixScanner::<2, 2>::new(Pins::P0 | Pins::P1, Pins::P2 | Pins::P3);
+    /// block_on(keypad.init_async(&mut tca))?;
+    ///
+    /// tca.i2c_mut().pressed = Some((1, 0));
+    /// let states = block_on(keypad.scan_async(&mut tca))?;
+    /// let key = Key { row: 1, column: 0 };
+    /// assert_eq!(states.events().collect::<Vec<_>>(), [KeyEvent::Pressed(key)]);
+    ///
+    /// tca.i2c_mut().pressed = None;
+    /// let states = block_on(keypad.scan_async(&mut tca))?;
+    /// assert_eq!(states.events().collect::<Vec<_>>(), [KeyEvent::Released(key)]);
+    /// // Every row is driven low again
+    /// assert_eq!(tca.i2c().registers[1] & 0b11, 0);
+    /// # Ok::<(), ErrorKind>(())
+    /// ```
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: a row may be left driven on its own, which the next
+    /// scan corrects. The debounced state is only updated once the scan
+    /// completes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C communication fails
+    pub async fn scan_async<I2C: embedded_hal_async::i2c::I2c>(
+        &mut self,
+        tca: &mut Tca9554<I2C, Async>,
+    ) -> Result<KeyStates<R>, I2C::Error> {
+        let mut raw = [0u8; R];
+        let idle = self.pressed_columns(tca.read_inputs().await?);
+        if self.is_busy(idle) {
+            let rows = self.rows();
+            for (bits, row) in raw.iter_mut().zip(self.rows) {
+                tca.write_outputs(tca.state().output.union(rows).difference(row)).await?;
+                *bits = self.pressed_columns(tca.read_inputs().await?);
+            }
+            tca.set_low(rows).await?;
+        }
+        Ok(self.update(raw))
+    }
+}
+
+/// Split pins into single pins, in order of their index.
+const fn split<const N: usize>(pins: Pins) -> [Pins; N] {
+    let mut split = [Pins::empty(); N];
+    let (mut bits, mut index) = (pins.bits(), 0);
+    while bits != 0 {
+        let lowest = bits & bits.wrapping_neg();
+        split[index] = Pins::from_bits_truncate(lowest);
+        bits ^= lowest;
+        index += 1;
+    }
+    split
+}
+
+/// Join single pins.
+const fn union<const N: usize>(pins: &[Pins; N]) -> Pins {
+    let (mut union, mut index) = (Pins::empty(), 0);
+    while index < N {
+        union = union.union(pins[index]);
+        index += 1;
+    }
+    union
+}
+
+/// Returns `true` if two rows share two pressed columns, so a fourth key
+/// may be seen pressed through the other three.
+fn is_ghosting(raw: &[u8]) -> bool {
+    raw.iter()
+        .enumerate()
+        .any(|(index, a)| raw[index + 1..].iter().any(|b| (a & b).count_ones() >= 2))
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A key of a matrix, by its row and column index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Key {
+    /// The row index.
+    pub row: u8,
+    /// The column index.
+    pub column: u8,
+}
+
+/// A change of a key's debounced state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum KeyEvent {
+    /// The key was pressed.
+    Pressed(Key),
+    /// The key was released.
+    Released(Key),
+}
+
+/// The debounced keys of a matrix after a scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct KeyStates<const R: usize> {
+    pressed: [u8; R],
+    previous: [u8; R],
+    ghosting: bool,
+}
+
+impl<const R: usize> KeyStates<R> {
+    /// Returns `true` if the key is pressed.
+    #[must_use]
+    pub fn is_pressed(&self, key: Key) -> bool {
+        self.pressed.get(usize::from(key.row)).is_some_and(|row| row >> key.column & 1 != 0)
+    }
+
+    /// Iterate over the pressed keys, by row then column.
+    pub fn pressed(&self) -> impl Iterator<Item = Key> + '_ { keys(&self.pressed) }
+
+    /// Iterate over the keys that changed in this scan,
+    /// by row then column.
+    pub fn events(&self) -> impl Iterator<Item = KeyEvent> + '_ {
+        keys(&self.pressed)
+            .filter(|key| !is_set(&self.previous, *key))
+            .map(KeyEvent::Pressed)
+            .chain(
+                keys(&self.previous)
+                    .filter(|key| !is_set(&self.pressed, *key))
+                    .map(KeyEvent::Released),
+            )
+    }
+
+    /// Returns `true` if the scan was ambiguous, because two rows shared two
+    /// pressed columns.
+    ///
+    /// Without a diode per key the fourth corner of such a rectangle also
+    /// reads as pressed, so the scan is ignored and the previous states are
+    /// kept.
+    #[inline]
+    #[must_use]
+    pub const fn is_ghosting(&self) -> bool { self.ghosting }
+}
+
+/// Iterate over the keys set in a row bitmap.
+fn keys(rows: &[u8]) -> impl Iterator<Item = Key> + '_ {
+    rows.iter().zip(0u8..).flat_map(|(&bits, row)| {
+        (0u8..8)
+            .filter(move |column| bits >> column & 1 != 0)
+            .map(move |column| Key { row, column })
+    })
+}
+
+/// Returns `true` if a key is set in a row bitmap.
+fn is_set(rows: &[u8], key: Key) -> bool {
+    rows.get(usize::from(key.row)).is_some_and(|row| row >> key.column & 1 != 0)
+}
@@ -0,0 +1,234 @@
+#![doc = include_str!("../README.md")]
+#![no_std]
+
+#[cfg(feature = "test-util")]
+extern crate alloc;
+
+use core::marker::PhantomData;
+
+#[cfg(not(feature = "defmt"))]
+use bitflags::bitflags;
+#[cfg(feature = "defmt")]
+use defmt::bitflags;
+pub use ef_driver_common::mode;
+use ef_driver_common::mode::DriverMode;
+
+mod r#async;
+mod blocking;
+mod faults;
+mod keypad;
+pub mod pin;
+mod register;
+mod sequence;
+#[cfg(feature = "test-util")]
+#[doc(hidden)]
+pub mod test_util;
+
+pub use r#async::general_call_reset_async;
+pub use blocking::general_call_reset;
+pub use faults::{OutputFault, OutputFaults};
+pub use keypad::{Key, KeyEvent, KeyStates, MatrixScanner};
+pub use sequence::{SequenceError, SequenceStep};
+
+/// A driver for a TCA9554 I/O expander.
+///
+/// The driver caches the output, polarity inversion, and configuration
+/// registers, so pins can be changed without reading the device first.
+pub struct Tca9554<I2C, MODE: DriverMode> {
+    i2c: I2C,
+    address: u8,
+    state: PortState,
+    check_interval: u8,
+    writes_since_check: u8,
+    faults: OutputFaults,
+    _mode: PhantomData<MODE>,
+}
+
+impl<I2C, MODE: DriverMode> Tca9554<I2C, MODE> {
+    /// The address of a TCA9554 with all address pins pulled low.
+    pub const DEFAULT_ADDRESS: u8 = 0x20;
+
+    /// Create a new [`Tca9554`] driver instance.
+    ///
+    /// The cached state is assumed to match the power-on defaults.
+    #[inline]
+    #[must_use]
+    pub const fn new(i2c: I2C, address: u8) -> Self {
+        Self {
+            i2c,
+            address,
+            state: PortState::POWER_ON,
+            check_interval: 0,
+            writes_since_check: 0,
+            faults: OutputFaults::NONE,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Get the address of a TCA9554 from its address pins.
+    #[must_use]
+    pub const fn address_from_pins(a2: bool, a1: bool, a0: bool) -> u8 {
+        Self::DEFAULT_ADDRESS | (a2 as u8) << 2 | (a1 as u8) << 1 | a0 as u8
+    }
+
+    /// Get the I2C address of the [`Tca9554`] device.
+    #[inline]
+    #[must_use]
+    pub const fn address(&self) -> u8 { self.address }
+
+    /// Get the cached [`PortState`].
+    #[inline]
+    #[must_use]
+    pub const fn state(&self) -> &PortState { &self.state }
+
+    /// Check the outputs automatically every `writes` output writes.
+    ///
+    /// See [`Tca9554::set_output_check`].
+    #[inline]
+    #[must_use]
+    pub const fn with_output_check(mut self, writes: u8) -> Self {
+        self.set_output_check(writes);
+        self
+    }
+
+    /// Get how many output writes pass between automatic output checks,
+    /// `0` if they are disabled.
+    #[inline]
+    #[must_use]
+    pub const fn output_check(&self) -> u8 { self.check_interval }
+
+    /// Check the outputs automatically every `writes` output writes,
+    /// or never if `writes` is `0`, which is the default.
+    ///
+    /// The check reads the input register after the output register was
+    /// written, see [`OutputFaults`] for the faults it can detect. Faults
+    /// found are collected until [`Tca9554::take_output_faults`] is called.
+    #[inline]
+    pub const fn set_output_check(&mut self, writes: u8) {
+        self.check_interval = writes;
+        self.writes_since_check = 0;
+    }
+
+    /// Take the [`OutputFaults`] found by automatic output checks
+    /// since the last call.
+    #[inline]
+    #[must_use]
+    pub const fn take_output_faults(&mut self) -> OutputFaults {
+        core::mem::replace(&mut self.faults, OutputFaults::NONE)
+    }
+
+    /// Count an output write, returning `true` if an automatic output check
+    /// is due.
+    const fn output_check_due(&mut self) -> bool {
+        if self.check_interval == 0 {
+            return false;
+        }
+        self.writes_since_check += 1;
+        if self.writes_since_check >= self.check_interval {
+            self.writes_since_check = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Get a reference to the I2C bus.
+    #[inline]
+    #[must_use]
+    pub const fn i2c(&self) -> &I2C { &self.i2c }
+
+    /// Get a mutable reference to the I2C bus.
+    #[inline]
+    #[must_use]
+    pub const fn i2c_mut(&mut self) -> &mut I2C { &mut self.i2c }
+
+    /// Release the I2C bus.
+    #[inline]
+    #[must_use]
+    pub fn release(self) -> I2C { self.i2c }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+bitflags! {
+    /// A set of TCA9554 pins.
+    #[cfg_attr(not(feature = "defmt"), derive(Debug, Clone, Copy, PartialEq, Eq, Hash))]
+    pub struct Pins: u8 {
+        /// Pin `P0`.
+        const P0 = 0b0000_0001;
+        /// Pin `P1`.
+        const P1 = 0b0000_0010;
+        /// Pin `P2`.
+        const P2 = 0b0000_0100;
+        /// Pin `P3`.
+        const P3 = 0b0000_1000;
+        /// Pin `P4`.
+        const P4 = 0b0001_0000;
+        /// Pin `P5`.
+        const P5 = 0b0010_0000;
+        /// Pin `P6`.
+        const P6 = 0b0100_0000;
+        /// Pin `P7`.
+        const P7 = 0b1000_0000;
+    }
+}
+
+impl Pins {
+    /// Get a single pin by its index.
+    ///
+    /// Returns `None` if the index is greater than 7.
+    #[must_use]
+    pub const fn from_index(index: u8) -> Option<Self> {
+        if index < 8 { Some(Self::from_bits_truncate(1 << index)) } else { None }
+    }
+}
+
+/// The direction of a pin.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    /// The pin is an input.
+    #[default]
+    Input,
+    /// The pin is an output.
+    Output,
+}
+
+/// The state of the writable TCA9554 registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PortState {
+    /// The output levels, `1` for high.
+    pub output: Pins,
+    /// The inverted inputs, `1` for inverted.
+    pub polarity: Pins,
+    /// The pins configured as inputs, `1` for input.
+    pub inputs: Pins,
+}
+
+impl PortState {
+    /// The register state after power-on or reset.
+    pub const POWER_ON: Self =
+        Self { output: Pins::all(), polarity: Pins::empty(), inputs: Pins::all() };
+
+    /// Get the pins configured as outputs.
+    #[inline]
+    #[must_use]
+    pub const fn outputs(&self) -> Pins { self.inputs.complement() }
+
+    /// The register writes needed to restore this state.
+    ///
+    /// Outputs are written before the configuration,
+    /// so pins never glitch when they become outputs.
+    const fn register_writes(self) -> [(u8, u8); 3] {
+        [
+            (register::TCA9554_OUTPUT_PORT, self.output.bits()),
+            (register::TCA9554_POLARITY_INVERSION, self.polarity.bits()),
+            (register::TCA9554_CONFIGURATION, self.inputs.bits()),
+        ]
+    }
+}
+
+impl Default for PortState {
+    fn default() -> Self { Self::POWER_ON }
+}
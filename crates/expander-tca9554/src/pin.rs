@@ -0,0 +1,275 @@
+//! Individual pins, with their direction tracked in the type.
+//!
+//! Pins are split off a shared [`Tca9554`] with [`Tca9554::split`] and start
+//! out [`Unconfigured`]. Converting a pin into an [`Output`] or an [`Input`]
+//! writes its configuration, after which it implements the matching
+//! `embedded-hal` traits.
+//!
+//! Only pins configured as outputs can be driven.
+//!
+//! ```rust,compile_fail
+//! use core::cell::RefCell;
+//!
+//! use ef_tca9554::{Tca9554, mode::Blocking};
+//! use embedded_hal::{
+//!     digital::OutputPin,
+//!     i2c::{ErrorKind, ErrorType, I2c, Operation},
+//! };
+//!
+//! struct Bus;
+//!
+//! impl ErrorType for Bus {
+//!     type Error = ErrorKind;
+//! }
+//!
+//! impl I2c for Bus {
+//!     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> { Ok(()) }
+//! }
+//!
+//! let expander = RefCell::new(Tca9554::<_, Blocking>::new(Bus, 0x20));
+//! let [p0, ..] = Tca9554::split(&expander);
+//!
+//! let mut input = p0.into_input().map_err(|(_, err)| err).unwrap();
+//! input.set_high().unwrap();
+//! ```
+//!
+//! Neither can pins that have not been configured.
+//!
+//! ```rust,compile_fail
+//! use core::cell::RefCell;
+//!
+//! use ef_tca9554::{Tca9554, mode::Blocking};
+//! use embedded_hal::{
+//!     digital::OutputPin,
+//!     i2c::{ErrorKind, ErrorType, I2c, Operation},
+//! };
+//!
+//! struct Bus;
+//!
+//! impl ErrorType for Bus {
+//!     type Error = ErrorKind;
+//! }
+//!
+//! impl I2c for Bus {
+//!     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> { Ok(()) }
+//! }
+//!
+//! let expander = RefCell::new(Tca9554::<_, Blocking>::new(Bus, 0x20));
+//! let [mut p0, ..] = Tca9554::split(&expander);
+//! p0.set_high().unwrap();
+//! ```
+
+use core::{cell::RefCell, fmt::Debug, marker::PhantomData};
+
+use ef_driver_common::mode::Blocking;
+use embedded_hal::{
+    digital::{self, ErrorKind, ErrorType, InputPin, OutputPin, PinState, StatefulOutputPin},
+    i2c::I2c,
+};
+
+use crate::{Direction, Pins, Tca9554};
+
+impl<I2C> Tca9554<I2C, Blocking> {
+    /// Split a shared [`Tca9554`] into its eight [`Unconfigured`] pins,
+    /// ordered from `P0` to `P7`.
+    ///
+    /// Pins borrow the driver for every operation, so the driver itself
+    /// must not stay borrowed while a pin is used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use core::cell::RefCell;
+    ///
+    /// use ef_tca9554::{Tca9554, mode::Blocking, test_util::Recorder};
+    /// use embedded_hal::{
+    ///     digital::{InputPin, OutputPin, PinState},
+    ///     i2c::ErrorKind,
+    /// };
+    ///
+    /// let i2c = Recorder::with_input(0b0000_0010);
+    /// let expander = RefCell::new(Tca9554::<_, Blocking>::new(i2c, 0x20));
+    /// let [reset, interrupt, ..] = Tca9554::split(&expander);
+    ///
+    /// // The output level is set before the pin becomes an output
+    /// let mut reset = reset.into_output(PinState::Low).map_err(|(_, err)| err)?;
+    /// reset.set_high()?;
+    /// assert_eq!(
+    ///     expander.borrow().i2c().registers(),
+    ///     [(0x01, 0b1111_1110), (0x03, 0b1111_1110), (0x01, 0xFF)]
+    /// );
+    ///
+    /// // Inputs read the input port
+    /// let mut interrupt = interrupt.into_input().map_err(|(_, err)| err)?;
+    /// assert!(interrupt.is_high()?);
+    ///
+    /// // Converting back reconfigures the pin
+    /// let reset = reset.into_input().map_err(|(_, err)| err)?;
+    /// assert_eq!(expander.borrow().i2c().registers().last(), Some(&(0x03, 0xFF)));
+    /// # Ok::<(), ef_tca9554::pin::PinError<ErrorKind>>(())
+    /// ```
+    #[must_use]
+    pub fn split(driver: &RefCell<Self>) -> [Pin<'_, I2C>; 8] {
+        core::array::from_fn(|index| {
+            #[expect(clippy::cast_possible_truncation, reason = "Within bounds")]
+            let pin = Pins::from_bits_truncate(1 << index as u8);
+            Pin { driver, mask: pin, _role: PhantomData }
+        })
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A single pin of a [`Tca9554`], split off with [`Tca9554::split`].
+pub struct Pin<'a, I2C, ROLE: PinRole = Unconfigured> {
+    driver: &'a RefCell<Tca9554<I2C, Blocking>>,
+    mask: Pins,
+    _role: PhantomData<ROLE>,
+}
+
+impl<'a, I2C, ROLE: PinRole> Pin<'a, I2C, ROLE> {
+    /// Get the [`Pins`] flag of this pin.
+    #[inline]
+    #[must_use]
+    pub const fn pins(&self) -> Pins { self.mask }
+
+    /// Forget the role of this pin, without changing its configuration.
+    #[inline]
+    #[must_use]
+    pub fn into_unconfigured(self) -> Pin<'a, I2C> { self.into_role() }
+
+    /// Change the role of this pin, without changing its configuration.
+    fn into_role<NEW: PinRole>(self) -> Pin<'a, I2C, NEW> {
+        Pin { driver: self.driver, mask: self.mask, _role: PhantomData }
+    }
+}
+
+impl<'a, I2C: I2c, ROLE: PinRole> Pin<'a, I2C, ROLE> {
+    /// Configure this pin as an [`Output`], driving the `initial` level.
+    ///
+    /// The level is written before the direction, so the pin never glitches.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`Unconfigured`] pin if the driver is already borrowed,
+    /// or if the I2C communication fails.
+    #[expect(clippy::type_complexity, reason = "Returning the pin on failure")]
+    pub fn into_output(
+        self,
+        initial: PinState,
+    ) -> Result<Pin<'a, I2C, Output>, (Pin<'a, I2C>, PinError<I2C::Error>)> {
+        let result = self.with_driver(|driver| {
+            match initial {
+                PinState::High => driver.set_high(self.mask)?,
+                PinState::Low => driver.set_low(self.mask)?,
+            }
+            driver.set_direction(self.mask, Direction::Output)
+        });
+
+        match result {
+            Ok(()) => Ok(self.into_role()),
+            Err(err) => Err((self.into_role(), err)),
+        }
+    }
+
+    /// Configure this pin as an [`Input`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`Unconfigured`] pin if the driver is already borrowed,
+    /// or if the I2C communication fails.
+    #[expect(clippy::type_complexity, reason = "Returning the pin on failure")]
+    pub fn into_input(self) -> Result<Pin<'a, I2C, Input>, (Pin<'a, I2C>, PinError<I2C::Error>)> {
+        match self.with_driver(|driver| driver.set_direction(self.mask, Direction::Input)) {
+            Ok(()) => Ok(self.into_role()),
+            Err(err) => Err((self.into_role(), err)),
+        }
+    }
+
+    /// Run a function with the borrowed driver.
+    fn with_driver<T>(
+        &self,
+        f: impl FnOnce(&mut Tca9554<I2C, Blocking>) -> Result<T, I2C::Error>,
+    ) -> Result<T, PinError<I2C::Error>> {
+        let mut driver = self.driver.try_borrow_mut().map_err(|_| PinError::Busy)?;
+        f(&mut driver).map_err(PinError::I2C)
+    }
+}
+
+impl<I2C: I2c, ROLE: PinRole> ErrorType for Pin<'_, I2C, ROLE> {
+    type Error = PinError<I2C::Error>;
+}
+
+impl<I2C: I2c> OutputPin for Pin<'_, I2C, Output> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.with_driver(|driver| driver.set_low(self.mask))
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.with_driver(|driver| driver.set_high(self.mask))
+    }
+}
+
+impl<I2C: I2c> StatefulOutputPin for Pin<'_, I2C, Output> {
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        self.with_driver(|driver| Ok(driver.state().output.contains(self.mask)))
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> { self.is_set_high().map(|high| !high) }
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.with_driver(|driver| driver.toggle(self.mask))
+    }
+}
+
+impl<I2C: I2c> InputPin for Pin<'_, I2C, Input> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.with_driver(|driver| Ok(driver.read_inputs()?.contains(self.mask)))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> { self.is_high().map(|high| !high) }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// An error that can occur when using a [`Pin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PinError<E> {
+    /// The driver is already borrowed.
+    Busy,
+    /// I2C bus error.
+    I2C(E),
+}
+
+impl<E: Debug> digital::Error for PinError<E> {
+    fn kind(&self) -> ErrorKind { ErrorKind::Other }
+}
+
+/// A marker struct for pins that have not been configured.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Unconfigured;
+
+/// A marker struct for pins configured as inputs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Input;
+
+/// A marker struct for pins configured as outputs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Output;
+
+/// A marker trait for the role of a [`Pin`].
+pub trait PinRole: sealed::Sealed {}
+impl PinRole for Unconfigured {}
+impl PinRole for Input {}
+impl PinRole for Output {}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for super::Unconfigured {}
+    impl Sealed for super::Input {}
+    impl Sealed for super::Output {}
+}
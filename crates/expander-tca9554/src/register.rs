@@ -0,0 +1,19 @@
+//! All documented registers from the TCA9554 datasheet.
+
+macro_rules! register {
+    ($($ident:ident: $addr:expr,)+) => {
+        $(pub(super) const $ident: u8 = $addr;)+
+    };
+}
+
+register! {
+    TCA9554_INPUT_PORT: 0x00,
+    TCA9554_OUTPUT_PORT: 0x01,
+    TCA9554_POLARITY_INVERSION: 0x02,
+    TCA9554_CONFIGURATION: 0x03,
+}
+
+/// The I2C general call address.
+pub(super) const GENERAL_CALL_ADDRESS: u8 = 0x00;
+/// The software reset byte sent to the general call address.
+pub(super) const GENERAL_CALL_RESET: u8 = 0x06;
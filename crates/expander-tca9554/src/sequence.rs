@@ -0,0 +1,57 @@
+//! Declarative sequences of pin changes and delays,
+//! such as the power and reset sequence of a board.
+
+use embedded_hal::digital::PinState;
+
+use crate::Pins;
+
+/// A single step of a sequence run by `Tca9554::run_sequence`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SequenceStep {
+    /// Drive pins to a level, making them outputs if they are not already.
+    ///
+    /// The level is written before the direction, so the pins never glitch.
+    SetPin {
+        /// The pins to drive.
+        pin: Pins,
+        /// The level to drive.
+        level: PinState,
+    },
+    /// Wait for a number of milliseconds.
+    DelayMs(u32),
+    /// Call the sequence's callback with an identifier,
+    /// for example to initialize a device once it is out of reset.
+    Callback(u8),
+}
+
+/// An error that can occur when running a sequence,
+/// along with the index of the step that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SequenceError<E, C> {
+    /// I2C bus error.
+    I2C {
+        /// The index of the step that failed.
+        step: usize,
+        /// The I2C error.
+        error: E,
+    },
+    /// The callback returned an error.
+    Callback {
+        /// The index of the step that failed.
+        step: usize,
+        /// The callback error.
+        error: C,
+    },
+}
+
+impl<E, C> SequenceError<E, C> {
+    /// Get the index of the step that failed.
+    #[must_use]
+    pub const fn step(&self) -> usize {
+        match self {
+            SequenceError::I2C { step, .. } | SequenceError::Callback { step, .. } => *step,
+        }
+    }
+}
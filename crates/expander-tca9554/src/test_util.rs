@@ -0,0 +1,142 @@
+//! Mock buses for examples and tests, without a device.
+//!
+//! Enabled with the `test-util` feature.
+
+use alloc::vec::Vec;
+use core::{
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use embedded_hal::i2c::{self, ErrorKind, ErrorType, Operation};
+use embedded_hal_async::i2c as i2c_async;
+
+/// A bus that records every write and reads back a fixed value.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Recorder {
+    /// The address and bytes of every write.
+    pub writes: Vec<(u8, Vec<u8>)>,
+    /// The value returned by every read.
+    pub input: u8,
+}
+
+impl Recorder {
+    /// Create a new [`Recorder`] that reads back `input`.
+    #[must_use]
+    pub const fn with_input(input: u8) -> Self { Self { writes: Vec::new(), input } }
+
+    /// Get every register write, as a register and its value.
+    #[must_use]
+    pub fn registers(&self) -> Vec<(u8, u8)> {
+        self.writes
+            .iter()
+            .filter_map(|(_, bytes)| match bytes.as_slice() {
+                [register, value] => Some((*register, *value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Record the writes and answer the reads of a transaction.
+    fn record(&mut self, address: u8, ops: &mut [Operation<'_>]) {
+        for op in ops {
+            match op {
+                Operation::Write(bytes) => self.writes.push((address, bytes.to_vec())),
+                Operation::Read(buf) => buf.fill(self.input),
+            }
+        }
+    }
+}
+
+impl ErrorType for Recorder {
+    type Error = ErrorKind;
+}
+
+impl i2c::I2c for Recorder {
+    fn transaction(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.record(address, ops);
+        Ok(())
+    }
+}
+
+impl i2c_async::I2c for Recorder {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        ops: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        self.record(address, ops);
+        Ok(())
+    }
+}
+
+/// A bus holding the four registers of a single device.
+///
+/// The input port reads back the output port, with the pins in
+/// [`Registers::shorted`] pulled low.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registers {
+    /// The input, output, polarity inversion and configuration registers.
+    pub registers: [u8; 4],
+    /// The pins shorted to ground.
+    pub shorted: u8,
+    pointer: u8,
+}
+
+impl Registers {
+    /// The registers after power-on.
+    pub const DEFAULT: Self = Self::new([0xFF, 0xFF, 0x00, 0xFF]);
+
+    /// Create a new [`Registers`] with the given register values.
+    #[must_use]
+    pub const fn new(registers: [u8; 4]) -> Self { Self { registers, shorted: 0, pointer: 0 } }
+
+    /// Apply the operations of a transaction.
+    fn apply(&mut self, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        for op in ops {
+            match op {
+                Operation::Write([register]) if *register < 4 => self.pointer = *register,
+                Operation::Write([register, value]) if *register < 4 => {
+                    self.registers[usize::from(*register)] = *value;
+                }
+                Operation::Read([value]) if self.pointer == 0 => {
+                    *value = self.registers[1] & !self.shorted;
+                }
+                Operation::Read([value]) => *value = self.registers[usize::from(self.pointer)],
+                _ => return Err(ErrorKind::Other),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Registers {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+impl ErrorType for Registers {
+    type Error = ErrorKind;
+}
+
+impl i2c::I2c for Registers {
+    fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.apply(ops)
+    }
+}
+
+impl i2c_async::I2c for Registers {
+    async fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.apply(ops)
+    }
+}
+
+/// Run a future to completion, polling it until it is ready.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! Benchmarks of parsing NMEA sentences.
+//!
+//! Run with `cargo +nightly bench -p ef-generic-gps --features nightly`.
+#![feature(test)]
+
+extern crate test;
+
+use core::hint::black_box;
+
+use ef_generic_gps::nmea::parse_sentence;
+use test::Bencher;
+
+/// A `GGA` sentence with every field filled in.
+const GGA: &[u8] = b"$GNGGA,174530.000,3746.4940,N,12225.4000,W,2,11,0.8,12.6,M,-29.9,M,,*48\r\n";
+/// A `RMC` sentence with every field filled in.
+const RMC: &[u8] = b"$GPRMC,235959.990,A,4807.0380,N,01131.0000,E,022.4,084.4,311223,,*0C\r\n";
+/// A synthetic drive, sending `GGA` and `RMC` sentences.
+const DRIVING: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/driving.nmea"));
+
+#[bench]
+fn parse_gga(b: &mut Bencher) { b.iter(|| parse_sentence::<()>(black_box(GGA))) }
+
+#[bench]
+fn parse_rmc(b: &mut Bencher) { b.iter(|| parse_sentence::<()>(black_box(RMC))) }
+
+#[bench]
+fn parse_recording(b: &mut Bencher) {
+    b.bytes = DRIVING.len() as u64;
+    b.iter(|| {
+        for line in black_box(DRIVING).split_inclusive(|&byte| byte == b'\n') {
+            let _ = black_box(parse_sentence::<()>(line));
+        }
+    });
+}
@@ -1,9 +1,10 @@
 use ef_driver_common::mode::Async;
-use embedded_io_async::Read;
+use embedded_io_async::{Read, Write};
 
 use crate::{
-    BufferGuard, GenericGps,
-    nmea::{NmeaError, NmeaSentence, parse_sentence},
+    ACK_TIMEOUT_BYTES, BufferGuard, CommandError, GenericGps, OutputMask, TimedSentence,
+    nmea::{NmeaError, NmeaSentence},
+    vendor::{Ack, AckScanner, COMMAND_BUFFER, Command},
 };
 
 impl<UART: Read, const N: usize> GenericGps<UART, Async, N> {
@@ -11,28 +12,39 @@ impl<UART: Read, const N: usize> GenericGps<UART, Async, N> {
     ///
     /// Returns `None` if a complete message has not yet been received.
     ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe, as long as the UART read is. Bytes are only added to the
+    /// buffer once the read completes.
+    ///
     /// # Errors
     ///
     /// Returns an error if the UART read operation fails.
     pub async fn receive_raw(&mut self) -> Result<Option<BufferGuard<'_>>, UART::Error> {
-        let buffer = &mut self.buffer[self.index..];
-        let received = self.uart.read(buffer).await?;
-        self.index += received;
+        self.fill_buffer().await?;
+        Ok(self.take_message())
+    }
 
-        // Search for a newline, signaling the end of a message.
-        for (index, byte) in buffer[..self.index].iter().enumerate() {
-            if *byte == b'\n' {
-                self.index = 0;
-                return Ok(Some(BufferGuard::new(self.buffer.as_mut_slice(), index)));
-            }
+    /// Read more bytes into the buffer, unless it already holds a message.
+    async fn fill_buffer(&mut self) -> Result<(), UART::Error> {
+        // Return messages left over from a previous read first.
+        if !self.has_message() {
+            let received = self.uart.read(&mut self.buffer[self.index..]).await?;
+            self.record_received(self.index..self.index + received);
+            self.index += received;
         }
-
-        Ok(None)
+        Ok(())
     }
 
     /// Attempt to read a single NMEA sentence from the GPS module.
     ///
     /// Returns `None` if a complete sentence has not yet been received.
+    /// After a corrupted sentence the driver resynchronizes on the next `$`,
+    /// so a sentence following the corruption is still received.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe, as long as the UART read is.
     ///
     /// # Errors
     ///
@@ -41,8 +53,8 @@ impl<UART: Read, const N: usize> GenericGps<UART, Async, N> {
     pub async fn try_receive_sentence(
         &mut self,
     ) -> Result<Option<NmeaSentence>, NmeaError<UART::Error>> {
-        let sentence = self.receive_raw().await.map_err(NmeaError::Other)?;
-        sentence.map_or(Ok(None), |buffer| parse_sentence(buffer.as_slice()).map(Some))
+        self.fill_buffer().await.map_err(NmeaError::Other)?;
+        self.take_sentence()
     }
 
     /// Read a single NMEA message from the GPS module.
@@ -50,10 +62,85 @@ impl<UART: Read, const N: usize> GenericGps<UART, Async, N> {
     /// Repeatedly calls [`GenericGps::try_receive_sentence`] until a complete
     /// sentence is received.
     ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe, as long as the UART read is. Partially received
+    /// sentences stay in the buffer and are completed by the next call.
+    ///
     /// # Errors
     ///
     /// Returns an error if the UART read operation fails or if the sentence
     /// is malformed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::{
+    ///     future::poll_fn,
+    ///     pin::pin,
+    ///     task::{Context, Poll, Waker},
+    /// };
+    ///
+    /// use ef_generic_gps::{
+    ///     GenericGps,
+    ///     mode::Async,
+    ///     nmea::{NmeaError, parse_sentence},
+    /// };
+    /// use embedded_io_async::{ErrorKind, ErrorType, Read};
+    /// # use ef_generic_gps::replay::block_on;
+    ///
+    /// const STREAM: &[u8] =
+    ///     b"$GPGGA,123519.000,4807.0380,N,01131.0000,E,1,08,0.9,545.4,M,,M,,*4C\r\n\
+    ///     $GNRMC,123519.000,A,4807.0380,N,01131.0000,E,22.4,84.4,230394,,*11\r\n";
+    ///
+    /// /// A UART that waits before every read, then returns up to 5 bytes.
+    /// struct Uart(&'static [u8]);
+    ///
+    /// impl ErrorType for Uart {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl Read for Uart {
+    ///     async fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+    ///         let mut waited = false;
+    ///         poll_fn(|_| {
+    ///             if std::mem::replace(&mut waited, true) { Poll::Ready(()) } else { Poll::Pending }
+    ///         })
+    ///         .await;
+    ///
+    ///         let length = buf.len().min(self.0.len()).min(5);
+    ///         buf[..length].copy_from_slice(&self.0[..length]);
+    ///         self.0 = &self.0[length..];
+    ///         Ok(length)
+    ///     }
+    /// }
+    ///
+    /// let (first, _) = STREAM.split_at(STREAM.iter().position(|&b| b == b'\n').unwrap() + 1);
+    /// let first = parse_sentence::<ErrorKind>(first)?;
+    ///
+    /// // Cancel the first call at every await point
+    /// for polls in 0.. {
+    ///     let mut gps = GenericGps::<_, Async, 128>::new(Uart(STREAM));
+    ///
+    ///     let completed = {
+    ///         let mut cx = Context::from_waker(Waker::noop());
+    ///         let mut future = pin!(gps.receive_sentence());
+    ///         (0..polls).any(|_| future.as_mut().poll(&mut cx).is_ready())
+    ///     };
+    ///
+    ///     // No bytes are lost, and both sentences are still received in order
+    ///     if !completed {
+    ///         assert_eq!(block_on(gps.receive_sentence())?, first);
+    ///     }
+    ///     let second = block_on(gps.receive_sentence())?;
+    ///     assert_eq!(second.talker, ['G', 'N']);
+    ///
+    ///     if completed {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok::<(), NmeaError<ErrorKind>>(())
+    /// ```
     pub async fn receive_sentence(&mut self) -> Result<NmeaSentence, NmeaError<UART::Error>> {
         let mut sentence = None;
         while sentence.is_none() {
@@ -63,4 +150,165 @@ impl<UART: Read, const N: usize> GenericGps<UART, Async, N> {
         // SAFETY: `message` is guaranteed to be `Some`
         Ok(unsafe { sentence.unwrap_unchecked() })
     }
+
+    /// Attempt to read a single NMEA sentence from the GPS module,
+    /// along with the clock time its newline was received.
+    ///
+    /// See [`GenericGps::with_clock`] and
+    /// [`GenericGps::try_receive_sentence`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe, as long as the UART read is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the UART read operation fails or if the sentence
+    /// is malformed.
+    pub async fn try_receive_timed_sentence(
+        &mut self,
+    ) -> Result<Option<TimedSentence>, NmeaError<UART::Error>> {
+        self.fill_buffer().await.map_err(NmeaError::Other)?;
+        let received_at_us = self.received_at_us;
+        let sentence = self.take_sentence()?;
+        Ok(sentence.map(|sentence| TimedSentence { sentence, received_at_us }))
+    }
+
+    /// Read a single NMEA sentence from the GPS module,
+    /// along with the clock time its newline was received.
+    ///
+    /// Repeatedly calls [`GenericGps::try_receive_timed_sentence`] until a
+    /// complete sentence is received.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe, as long as the UART read is. Partially received
+    /// sentences stay in the buffer and are completed by the next call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the UART read operation fails or if the sentence
+    /// is malformed.
+    pub async fn receive_timed_sentence(
+        &mut self,
+    ) -> Result<TimedSentence, NmeaError<UART::Error>> {
+        loop {
+            if let Some(sentence) = self.try_receive_timed_sentence().await? {
+                return Ok(sentence);
+            }
+        }
+    }
+}
+
+impl<UART: Read + Write, const N: usize> GenericGps<UART, Async, N> {
+    /// Save the current settings of the GPS module, so that they survive a
+    /// power cycle.
+    ///
+    /// Sentences received while waiting for the acknowledgment are discarded.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: the save command may be cut off partway, or sent
+    /// without its acknowledgment being read, so it is unknown whether the
+    /// settings were saved and the call must be made again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the [`Vendor`](crate::Vendor) cannot save its
+    /// settings, if the module rejects or does not acknowledge the command,
+    /// or if the UART operation fails.
+    pub async fn persist_settings(&mut self) -> Result<(), CommandError<UART::Error>> {
+        let commands = self.vendor.persist_commands().ok_or(CommandError::Unsupported)?;
+        for &command in commands {
+            self.send_command(command).await?;
+        }
+        Ok(())
+    }
+
+    /// Restore the default settings of the GPS module.
+    ///
+    /// Sentences received while waiting for the acknowledgment are discarded.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: only some of the restore commands may have been
+    /// applied, leaving the module between its current and default settings
+    /// until the call is made again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the [`Vendor`](crate::Vendor) cannot restore its
+    /// settings, if the module rejects or does not acknowledge a command,
+    /// or if the UART operation fails.
+    pub async fn restore_defaults(&mut self) -> Result<(), CommandError<UART::Error>> {
+        let commands = self.vendor.restore_commands().ok_or(CommandError::Unsupported)?;
+        for &command in commands {
+            self.send_command(command).await?;
+        }
+        Ok(())
+    }
+
+    /// Configure the GPS module to only send the sentences in `mask`.
+    ///
+    /// MediaTek modules acknowledge a single `PMTK314` sentence. u-blox
+    /// modules receive a `PUBX,40` sentence for every
+    /// [`SentenceType`](crate::SentenceType), which they do not answer.
+    ///
+    /// Sentences received while waiting for the acknowledgment are discarded.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: on u-blox modules only some sentence types may have
+    /// been switched, so the module sends a mix of the old and new mask
+    /// until the call is made again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the [`Vendor`](crate::Vendor) cannot configure its
+    /// sentences, if the module rejects or does not acknowledge the command,
+    /// or if the UART operation fails.
+    pub async fn apply_output_mask(
+        &mut self,
+        mask: OutputMask,
+    ) -> Result<(), CommandError<UART::Error>> {
+        let commands = self.vendor.output_mask_commands(mask).ok_or(CommandError::Unsupported)?;
+        for command in commands {
+            self.send_command(command).await?;
+        }
+        Ok(())
+    }
+
+    /// Send a command and wait for its acknowledgment, if it has one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command is rejected or not acknowledged,
+    /// or if the UART operation fails.
+    async fn send_command(&mut self, command: Command) -> Result<(), CommandError<UART::Error>> {
+        let mut buffer = [0u8; COMMAND_BUFFER];
+        let bytes = command.write(&mut buffer).map_err(CommandError::Build)?;
+        self.uart.write_all(bytes).await.map_err(CommandError::Other)?;
+        self.uart.flush().await.map_err(CommandError::Other)?;
+
+        let Some(expected) = command.expected() else { return Ok(()) };
+        let mut scanner = AckScanner::new(expected);
+        let mut received = 0;
+        while received < ACK_TIMEOUT_BYTES {
+            self.index = 0;
+            let read = self.uart.read(&mut self.buffer).await.map_err(CommandError::Other)?;
+            if read == 0 {
+                break;
+            }
+
+            received += read;
+            match self.scan_ack(&mut scanner, read) {
+                Some(Ack::Ack) => return Ok(()),
+                Some(Ack::Nak) => return Err(CommandError::Nak),
+                None => {}
+            }
+        }
+
+        self.index = 0;
+        Err(CommandError::Timeout)
+    }
 }
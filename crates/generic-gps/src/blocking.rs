@@ -1,9 +1,10 @@
 use ef_driver_common::mode::Blocking;
-use embedded_io::Read;
+use embedded_io::{Read, Write};
 
 use crate::{
-    BufferGuard, GenericGps,
-    nmea::{NmeaError, NmeaSentence, parse_sentence},
+    ACK_TIMEOUT_BYTES, BufferGuard, CommandError, GenericGps, OutputMask, TimedSentence,
+    nmea::{NmeaError, NmeaSentence},
+    vendor::{Ack, AckScanner, COMMAND_BUFFER, Command},
 };
 
 impl<UART: Read, const N: usize> GenericGps<UART, Blocking, N> {
@@ -15,32 +16,78 @@ impl<UART: Read, const N: usize> GenericGps<UART, Blocking, N> {
     ///
     /// Returns an error if the UART read operation fails.
     pub fn receive_raw(&mut self) -> Result<Option<BufferGuard<'_>>, UART::Error> {
-        let buffer = &mut self.buffer[self.index..];
-        let received = self.uart.read(buffer)?;
-        self.index += received;
+        self.fill_buffer()?;
+        Ok(self.take_message())
+    }
 
-        // Search for a newline, signaling the end of a message.
-        for (index, byte) in buffer[..self.index].iter().enumerate() {
-            if *byte == b'\n' {
-                self.index = 0;
-                return Ok(Some(BufferGuard::new(self.buffer.as_mut_slice(), index)));
-            }
+    /// Read more bytes into the buffer, unless it already holds a message.
+    fn fill_buffer(&mut self) -> Result<(), UART::Error> {
+        // Return messages left over from a previous read first.
+        if !self.has_message() {
+            let received = self.uart.read(&mut self.buffer[self.index..])?;
+            self.record_received(self.index..self.index + received);
+            self.index += received;
         }
-
-        Ok(None)
+        Ok(())
     }
 
     /// Attempt to read a single NMEA sentence from the GPS module.
     ///
     /// Returns `None` if a complete sentence has not yet been received.
+    /// After a corrupted sentence the driver resynchronizes on the next `$`,
+    /// so a sentence following the corruption is still received.
     ///
     /// # Errors
     ///
     /// Returns an error if the UART read operation fails or if the sentence
     /// is malformed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_generic_gps::{GenericGps, mode::Blocking};
+    ///
+    /// const CLEAN: &[u8] = b"$GPGGA,123519.000,4807.0380,N,01131.0000,E,1,08,0.9,545.4,M,,M,,*4C\r\n";
+    ///
+    /// let mut stream = CLEAN.to_vec();
+    /// // A single flipped bit
+    /// let mut flipped = CLEAN.to_vec();
+    /// flipped[20] ^= 0x04;
+    /// stream.extend_from_slice(&flipped);
+    /// stream.extend_from_slice(CLEAN);
+    /// // An inserted byte
+    /// let mut inserted = CLEAN.to_vec();
+    /// inserted.insert(30, b'7');
+    /// stream.extend_from_slice(&inserted);
+    /// stream.extend_from_slice(CLEAN);
+    /// // Deleted bytes, including the newline
+    /// stream.extend_from_slice(&CLEAN[..40]);
+    /// stream.extend_from_slice(CLEAN);
+    /// // Noise before the `$`
+    /// stream.extend_from_slice(&[0x00, 0xFF]);
+    /// stream.extend_from_slice(CLEAN);
+    /// // Noise without a newline
+    /// stream.extend_from_slice(&[0x55; 100]);
+    /// stream.extend_from_slice(CLEAN);
+    ///
+    /// let mut gps = GenericGps::<_, Blocking, 128>::new(stream.as_slice());
+    /// let (mut parsed, mut errors) = (0, 0);
+    /// for _ in 0..32 {
+    ///     match gps.try_receive_sentence() {
+    ///         Ok(Some(_)) => parsed += 1,
+    ///         Ok(None) => {}
+    ///         Err(_) => errors += 1,
+    ///     }
+    /// }
+    ///
+    /// // Every clean sentence was received
+    /// assert_eq!((parsed, errors), (6, 3));
+    /// #[cfg(feature = "stats")]
+    /// assert_eq!(gps.resyncs(), 3);
+    /// ```
     pub fn try_receive_sentence(&mut self) -> Result<Option<NmeaSentence>, NmeaError<UART::Error>> {
-        let sentence = self.receive_raw().map_err(NmeaError::Other)?;
-        sentence.map_or(Ok(None), |buffer| parse_sentence(buffer.as_slice()).map(Some))
+        self.fill_buffer().map_err(NmeaError::Other)?;
+        self.take_sentence()
     }
 
     /// Read a single NMEA message from the GPS module.
@@ -61,4 +108,380 @@ impl<UART: Read, const N: usize> GenericGps<UART, Blocking, N> {
         // SAFETY: `message` is guaranteed to be `Some`
         Ok(unsafe { sentence.unwrap_unchecked() })
     }
+
+    /// Attempt to read a single NMEA sentence from the GPS module,
+    /// along with the clock time its newline was received.
+    ///
+    /// See [`GenericGps::with_clock`] and
+    /// [`GenericGps::try_receive_sentence`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the UART read operation fails or if the sentence
+    /// is malformed.
+    pub fn try_receive_timed_sentence(
+        &mut self,
+    ) -> Result<Option<TimedSentence>, NmeaError<UART::Error>> {
+        self.fill_buffer().map_err(NmeaError::Other)?;
+        let received_at_us = self.received_at_us;
+        let sentence = self.take_sentence()?;
+        Ok(sentence.map(|sentence| TimedSentence { sentence, received_at_us }))
+    }
+
+    /// Read a single NMEA sentence from the GPS module,
+    /// along with the clock time its newline was received.
+    ///
+    /// Repeatedly calls [`GenericGps::try_receive_timed_sentence`] until a
+    /// complete sentence is received.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the UART read operation fails or if the sentence
+    /// is malformed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    ///
+    /// use ef_generic_gps::{GenericGps, mode::Blocking};
+    /// use embedded_io::{ErrorKind, ErrorType, Read};
+    ///
+    /// const GGA: &[u8] = b"$GPGGA,123519.000,4807.0380,N,01131.0000,E,1,08,0.9,545.4,M,,M,,*4C\r\n";
+    /// const RMC: &[u8] = b"$GPRMC,235959.99,A,4807.0380,N,01131.0000,E,22.4,84.4,311223,,*3C\r\n";
+    ///
+    /// /// A clock that advances by 1 ms on every UART read,
+    /// /// counting microseconds since the Unix epoch.
+    /// static NOW: AtomicU64 = AtomicU64::new(1_704_067_200_239_000);
+    /// fn clock() -> u64 { NOW.load(Ordering::Relaxed) }
+    ///
+    /// /// A UART that returns everything it has on every read.
+    /// struct Uart(Vec<u8>);
+    ///
+    /// impl ErrorType for Uart {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl Read for Uart {
+    ///     fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+    ///         NOW.fetch_add(1_000, Ordering::Relaxed);
+    ///         let len = buf.len().min(self.0.len());
+    ///         buf[..len].copy_from_slice(&self.0[..len]);
+    ///         self.0.drain(..len);
+    ///         Ok(len)
+    ///     }
+    /// }
+    ///
+    /// let uart = Uart([GGA, RMC].concat());
+    /// let mut gps = GenericGps::<_, Blocking, 256>::new(uart).with_clock(clock);
+    ///
+    /// // Both sentences arrive with the first read
+    /// let gga = gps.receive_timed_sentence()?;
+    /// assert_eq!(gga.received_at_us, 1_704_067_200_240_000);
+    /// // GGA sentences carry no date
+    /// assert_eq!(gga.fix_latency_us(), None);
+    ///
+    /// // The time is taken from the read, not from when the sentence is parsed
+    /// NOW.fetch_add(10_000, Ordering::Relaxed);
+    /// let rmc = gps.receive_timed_sentence()?;
+    /// assert_eq!(rmc.received_at_us, 1_704_067_200_240_000);
+    /// // The fix was valid at 23:59:59.99 on 2023-12-31
+    /// assert_eq!(rmc.fix_latency_us(), Some(250_000));
+    ///
+    /// // Later sentences are timed by the read that completes them
+    /// gps.uart_mut().0.extend_from_slice(RMC);
+    /// let rmc = gps.receive_timed_sentence()?;
+    /// assert_eq!(rmc.received_at_us, 1_704_067_200_251_000);
+    /// # Ok::<(), ef_generic_gps::nmea::NmeaError<ErrorKind>>(())
+    /// ```
+    pub fn receive_timed_sentence(&mut self) -> Result<TimedSentence, NmeaError<UART::Error>> {
+        loop {
+            if let Some(sentence) = self.try_receive_timed_sentence()? {
+                return Ok(sentence);
+            }
+        }
+    }
+}
+
+impl<UART: Read + Write, const N: usize> GenericGps<UART, Blocking, N> {
+    /// Save the current settings of the GPS module, so that they survive a
+    /// power cycle.
+    ///
+    /// Sentences received while waiting for the acknowledgment are discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the [`Vendor`](crate::Vendor) cannot save its
+    /// settings, if the module rejects or does not acknowledge the command,
+    /// or if the UART operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::collections::VecDeque;
+    ///
+    /// use ef_generic_gps::{CommandError, GenericGps, Vendor, mode::Blocking, ubx};
+    /// use embedded_io::{ErrorKind, ErrorType, Read, Write};
+    ///
+    /// const GGA: &[u8] = b"$GPGGA,123519.000,4807.0380,N,01131.0000,E,1,08,0.9,545.4,M,,M,,*4C\r\n";
+    ///
+    /// /// A module that records commands and replays replies,
+    /// /// then only sends `GGA` sentences.
+    /// #[derive(Default)]
+    /// struct Module(Vec<u8>, VecDeque<Vec<u8>>);
+    ///
+    /// impl ErrorType for Module {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl Read for Module {
+    ///     fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+    ///         let reply = self.1.pop_front().unwrap_or_else(|| GGA.to_vec());
+    ///         buf[..reply.len()].copy_from_slice(&reply);
+    ///         Ok(reply.len())
+    ///     }
+    /// }
+    ///
+    /// impl Write for Module {
+    ///     fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorKind> {
+    ///         self.0.extend_from_slice(buf);
+    ///         Ok(buf.len())
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> Result<(), ErrorKind> { Ok(()) }
+    /// }
+    ///
+    /// fn ubx(id: u8, payload: &[u8]) -> Vec<u8> {
+    ///     ubx::write_frame(0x05, id, payload, &mut [0u8; 16]).unwrap().to_vec()
+    /// }
+    ///
+    /// // A u-blox module acknowledges `CFG-CFG` between two sentences
+    /// let mut module = Module::default();
+    /// module.1.push_back([GGA, &ubx(0x01, &[0x06, 0x09]), &GGA[..20]].concat());
+    /// module.1.push_back(GGA[20..].to_vec());
+    /// let mut gps = GenericGps::<_, Blocking, 128>::new(module).with_vendor(Vendor::Ublox);
+    /// gps.persist_settings()?;
+    ///
+    /// // Every section is saved to every device
+    /// assert_eq!(
+    ///     gps.uart().0,
+    ///     [
+    ///         0xB5, 0x62, 0x06, 0x09, 0x0D, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+    ///         0x00, 0x00, 0x00, 0x00, 0x17, 0x31, 0xBF
+    ///     ]
+    /// );
+    /// // The sentence after the acknowledgment is kept
+    /// assert_eq!(gps.receive_sentence().unwrap().talker, ['G', 'P']);
+    ///
+    /// // Rejections and acknowledgments of other messages are told apart
+    /// gps.uart_mut().1.push_back(ubx(0x00, &[0x06, 0x09]));
+    /// assert_eq!(gps.persist_settings(), Err(CommandError::Nak));
+    /// gps.uart_mut().1.push_back(ubx(0x01, &[0x06, 0x01]));
+    /// assert_eq!(gps.persist_settings(), Err(CommandError::Timeout));
+    ///
+    /// // Modules that cannot save their settings are reported
+    /// let mut gps = GenericGps::<_, Blocking, 128>::new(Module::default());
+    /// assert_eq!(gps.persist_settings(), Err(CommandError::Unsupported));
+    /// gps = gps.with_vendor(Vendor::MediaTek);
+    /// assert_eq!(gps.persist_settings(), Err(CommandError::Unsupported));
+    /// assert!(gps.uart().0.is_empty());
+    /// # Ok::<(), CommandError<ErrorKind>>(())
+    /// ```
+    pub fn persist_settings(&mut self) -> Result<(), CommandError<UART::Error>> {
+        let commands = self.vendor.persist_commands().ok_or(CommandError::Unsupported)?;
+        commands.iter().try_for_each(|&command| self.send_command(command))
+    }
+
+    /// Restore the default settings of the GPS module.
+    ///
+    /// Sentences received while waiting for the acknowledgment are discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the [`Vendor`](crate::Vendor) cannot restore its
+    /// settings, if the module rejects or does not acknowledge a command,
+    /// or if the UART operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::collections::VecDeque;
+    ///
+    /// use ef_generic_gps::{CommandError, GenericGps, Vendor, mode::Blocking};
+    /// use embedded_io::{ErrorKind, ErrorType, Read, Write};
+    ///
+    /// /// A module that records commands and replays replies.
+    /// #[derive(Default)]
+    /// struct Module(Vec<u8>, VecDeque<&'static [u8]>);
+    ///
+    /// impl ErrorType for Module {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl Read for Module {
+    ///     fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+    ///         let reply = self.1.pop_front().unwrap_or_default();
+    ///         buf[..reply.len()].copy_from_slice(reply);
+    ///         Ok(reply.len())
+    ///     }
+    /// }
+    ///
+    /// impl Write for Module {
+    ///     fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorKind> {
+    ///         self.0.extend_from_slice(buf);
+    ///         Ok(buf.len())
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> Result<(), ErrorKind> { Ok(()) }
+    /// }
+    ///
+    /// // A MediaTek module acknowledges both commands
+    /// let mut module = Module::default();
+    /// module.1.extend([&b"$PMTK001,314,3*36\r\n"[..], b"$PMTK001,220,3*30\r\n"]);
+    /// let mut gps = GenericGps::<_, Blocking, 64>::new(module).with_vendor(Vendor::MediaTek);
+    /// gps.restore_defaults()?;
+    /// assert_eq!(gps.uart().0, b"$PMTK314,-1*04\r\n$PMTK220,1000*1F\r\n");
+    ///
+    /// // Unsupported commands and corrupted acknowledgments
+    /// gps.uart_mut().1.push_back(b"$PMTK001,314,1*34\r\n");
+    /// assert_eq!(gps.restore_defaults(), Err(CommandError::Nak));
+    /// gps.uart_mut().1.push_back(b"$PMTK001,314,3*37\r\n");
+    /// assert_eq!(gps.restore_defaults(), Err(CommandError::Timeout));
+    /// # Ok::<(), CommandError<ErrorKind>>(())
+    /// ```
+    pub fn restore_defaults(&mut self) -> Result<(), CommandError<UART::Error>> {
+        let commands = self.vendor.restore_commands().ok_or(CommandError::Unsupported)?;
+        commands.iter().try_for_each(|&command| self.send_command(command))
+    }
+
+    /// Configure the GPS module to only send the sentences in `mask`.
+    ///
+    /// MediaTek modules receive a single `PMTK314` sentence and acknowledge
+    /// it. u-blox modules receive a `PUBX,40` sentence for every
+    /// [`SentenceType`](crate::SentenceType), setting its rate on every port.
+    /// u-blox modules do not answer `PUBX` sentences, so the only
+    /// confirmation is the output of the module.
+    ///
+    /// Sentences received while waiting for the acknowledgment are discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the [`Vendor`](crate::Vendor) cannot configure its
+    /// sentences, if the module rejects or does not acknowledge the command,
+    /// or if the UART operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::collections::VecDeque;
+    ///
+    /// use ef_generic_gps::{CommandError, GenericGps, OutputMask, Vendor, mode::Blocking};
+    /// use embedded_io::{ErrorKind, ErrorType, Read, Write};
+    ///
+    /// const GGA: &[u8] = b"$GPGGA,123519.000,4807.0380,N,01131.0000,E,1,08,0.9,545.4,M,,M,,*4C\r\n";
+    /// const RMC: &[u8] = b"$GPRMC,235959.99,A,4807.0380,N,01131.0000,E,22.4,84.4,311223,,*3C\r\n";
+    /// const GSV: &[u8] = b"$GPGSV,1,1,01,05,45,120,38*44\r\n";
+    ///
+    /// /// A module that records commands and replays replies.
+    /// #[derive(Default)]
+    /// struct Module(Vec<u8>, VecDeque<&'static [u8]>);
+    ///
+    /// impl ErrorType for Module {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl Read for Module {
+    ///     fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+    ///         let reply = self.1.pop_front().unwrap_or_default();
+    ///         buf[..reply.len()].copy_from_slice(reply);
+    ///         Ok(reply.len())
+    ///     }
+    /// }
+    ///
+    /// impl Write for Module {
+    ///     fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorKind> {
+    ///         self.0.extend_from_slice(buf);
+    ///         Ok(buf.len())
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> Result<(), ErrorKind> { Ok(()) }
+    /// }
+    ///
+    /// // The application only parses `GGA` and `RMC` sentences
+    /// let mut module = Module::default();
+    /// module.1.extend([GGA, RMC, GSV]);
+    /// let mut gps = GenericGps::<_, Blocking, 128>::new(module).with_vendor(Vendor::MediaTek);
+    /// for _ in 0..3 {
+    ///     let _ = gps.receive_sentence();
+    /// }
+    /// assert_eq!(gps.sentence_usage().unused(), OutputMask::GSV);
+    /// let mask = gps.recommend_output_mask();
+    /// assert_eq!(mask, OutputMask::GGA | OutputMask::RMC);
+    ///
+    /// // A MediaTek module acknowledges the mask
+    /// gps.uart_mut().1.push_back(b"$PMTK001,314,3*36\r\n");
+    /// gps.apply_output_mask(mask)?;
+    /// assert_eq!(gps.uart().0, b"$PMTK314,0,1,0,1,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0*28\r\n");
+    ///
+    /// // And rejections are reported
+    /// gps.uart_mut().1.push_back(b"$PMTK001,314,2*37\r\n");
+    /// assert_eq!(gps.apply_output_mask(mask), Err(CommandError::Nak));
+    ///
+    /// // A u-blox module receives a sentence for every type
+    /// let mut gps = GenericGps::<_, Blocking, 128>::new(Module::default()).with_vendor(Vendor::Ublox);
+    /// gps.apply_output_mask(mask)?;
+    /// assert_eq!(
+    ///     core::str::from_utf8(&gps.uart().0).unwrap(),
+    ///     "$PUBX,40,GLL,0,0,0,0,0,0*5C\r\n\
+    ///      $PUBX,40,RMC,1,1,1,1,1,0*46\r\n\
+    ///      $PUBX,40,VTG,0,0,0,0,0,0*5E\r\n\
+    ///      $PUBX,40,GGA,1,1,1,1,1,0*5B\r\n\
+    ///      $PUBX,40,GSA,0,0,0,0,0,0*4E\r\n\
+    ///      $PUBX,40,GSV,0,0,0,0,0,0*59\r\n\
+    ///      $PUBX,40,ZDA,0,0,0,0,0,0*44\r\n"
+    /// );
+    ///
+    /// // Modules that cannot configure their sentences are reported
+    /// let mut gps = GenericGps::<_, Blocking, 128>::new(Module::default());
+    /// assert_eq!(gps.apply_output_mask(mask), Err(CommandError::Unsupported));
+    /// # Ok::<(), CommandError<ErrorKind>>(())
+    /// ```
+    pub fn apply_output_mask(&mut self, mask: OutputMask) -> Result<(), CommandError<UART::Error>> {
+        let commands = self.vendor.output_mask_commands(mask).ok_or(CommandError::Unsupported)?;
+        commands.into_iter().try_for_each(|command| self.send_command(command))
+    }
+
+    /// Send a command and wait for its acknowledgment, if it has one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command is rejected or not acknowledged,
+    /// or if the UART operation fails.
+    fn send_command(&mut self, command: Command) -> Result<(), CommandError<UART::Error>> {
+        let mut buffer = [0u8; COMMAND_BUFFER];
+        let bytes = command.write(&mut buffer).map_err(CommandError::Build)?;
+        self.uart.write_all(bytes).map_err(CommandError::Other)?;
+        self.uart.flush().map_err(CommandError::Other)?;
+
+        let Some(expected) = command.expected() else { return Ok(()) };
+        let mut scanner = AckScanner::new(expected);
+        let mut received = 0;
+        while received < ACK_TIMEOUT_BYTES {
+            self.index = 0;
+            let read = self.uart.read(&mut self.buffer).map_err(CommandError::Other)?;
+            if read == 0 {
+                break;
+            }
+
+            received += read;
+            match self.scan_ack(&mut scanner, read) {
+                Some(Ack::Ack) => return Ok(()),
+                Some(Ack::Nak) => return Err(CommandError::Nak),
+                None => {}
+            }
+        }
+
+        self.index = 0;
+        Err(CommandError::Timeout)
+    }
 }
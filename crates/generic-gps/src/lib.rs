@@ -9,12 +9,33 @@ use ef_driver_common::mode::DriverMode;
 mod r#async;
 mod blocking;
 pub mod nmea;
+#[cfg(feature = "test-util")]
+pub mod replay;
+pub mod ubx;
+mod usage;
+mod vendor;
+
+use jiff::Timestamp;
+use nmea::{NmeaError, NmeaSentence, NmeaSentenceKind, parse_sentence};
+pub use usage::{OutputMask, SentenceType, SentenceUsage};
+pub use vendor::{ACK_TIMEOUT_BYTES, CommandError, Vendor};
+use vendor::{Ack, AckScanner};
+
+/// The maximum length of a NMEA sentence, including the `$` and `\r\n`.
+pub const MAX_SENTENCE_LEN: usize = 82;
 
 /// A generic driver for GPS over UART.
 pub struct GenericGps<UART, MODE: DriverMode, const N: usize> {
     uart: UART,
     index: usize,
     buffer: [u8; N],
+    vendor: Vendor,
+    max_sentence_len: usize,
+    clock: Option<fn() -> u64>,
+    received_at_us: u64,
+    usage: SentenceUsage,
+    #[cfg(feature = "stats")]
+    resyncs: u32,
     _mode: PhantomData<MODE>,
 }
 
@@ -23,9 +44,112 @@ impl<UART, MODE: DriverMode, const N: usize> GenericGps<UART, MODE, N> {
     #[inline]
     #[must_use]
     pub const fn new(uart: UART) -> Self {
-        Self { uart, index: 0, buffer: [0u8; N], _mode: PhantomData }
+        Self {
+            uart,
+            index: 0,
+            buffer: [0u8; N],
+            vendor: Vendor::Generic,
+            max_sentence_len: MAX_SENTENCE_LEN,
+            clock: None,
+            received_at_us: 0,
+            usage: SentenceUsage::NONE,
+            #[cfg(feature = "stats")]
+            resyncs: 0,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Set the [`Vendor`] of the GPS module.
+    #[inline]
+    #[must_use]
+    pub const fn with_vendor(mut self, vendor: Vendor) -> Self {
+        self.vendor = vendor;
+        self
+    }
+
+    /// Get the [`Vendor`] of the GPS module.
+    #[inline]
+    #[must_use]
+    pub const fn vendor(&self) -> Vendor { self.vendor }
+
+    /// Set the maximum length of a message, including the `$` and `\r\n`.
+    ///
+    /// Longer messages are treated as garbage and discarded up to the next
+    /// `$`, so that noise without a newline cannot fill the buffer.
+    /// Defaults to [`MAX_SENTENCE_LEN`], increase it for modules that send
+    /// longer proprietary sentences.
+    #[inline]
+    #[must_use]
+    pub const fn with_max_sentence_len(mut self, len: usize) -> Self {
+        self.max_sentence_len = len;
+        self
+    }
+
+    /// Get the maximum length of a message, including the `$` and `\r\n`.
+    #[inline]
+    #[must_use]
+    pub const fn max_sentence_len(&self) -> usize { self.max_sentence_len }
+
+    /// Set a monotonic clock in microseconds, read whenever the newline of a
+    /// message is received.
+    ///
+    /// The time is captured as soon as the bytes are read from the UART,
+    /// before any parsing, see [`GenericGps::received_at_us`] and
+    /// [`TimedSentence`].
+    #[inline]
+    #[must_use]
+    pub const fn with_clock(mut self, clock: fn() -> u64) -> Self {
+        self.clock = Some(clock);
+        self
     }
 
+    /// Get the clock time at which the newline of the messages waiting in
+    /// the buffer was received, in microseconds.
+    ///
+    /// The driver only reads from the UART once the buffer holds no complete
+    /// message, so every waiting message was completed by the same read.
+    /// Returns `0` if no clock is set, see [`GenericGps::with_clock`].
+    #[inline]
+    #[must_use]
+    pub const fn received_at_us(&self) -> u64 { self.received_at_us }
+
+    /// Get the number of times the driver resynchronized on a `$` after
+    /// receiving a corrupted or over-long message.
+    #[inline]
+    #[must_use]
+    #[cfg(feature = "stats")]
+    pub const fn resyncs(&self) -> u32 { self.resyncs }
+
+    /// Reset the resynchronization counter.
+    #[inline]
+    #[cfg(feature = "stats")]
+    pub const fn reset_resyncs(&mut self) { self.resyncs = 0; }
+
+    /// Get the [`SentenceUsage`] recorded since the driver was created,
+    /// or since [`GenericGps::reset_sentence_usage`].
+    #[inline]
+    #[must_use]
+    pub const fn sentence_usage(&self) -> &SentenceUsage { &self.usage }
+
+    /// Record that the application used a sentence.
+    ///
+    /// Sentences returned by the parsing methods are recorded automatically,
+    /// call this for sentences read with `receive_raw`.
+    #[inline]
+    pub const fn mark_consumed(&mut self, kind: SentenceType) { self.usage.mark_consumed(kind); }
+
+    /// Forget which sentences were received and used.
+    #[inline]
+    pub const fn reset_sentence_usage(&mut self) { self.usage = SentenceUsage::NONE; }
+
+    /// Get an [`OutputMask`] that disables every sentence the application
+    /// does not use, see [`SentenceUsage::recommend_output_mask`].
+    ///
+    /// Apply it with `apply_output_mask`.
+    #[inline]
+    #[must_use]
+    pub const fn recommend_output_mask(&self) -> OutputMask { self.usage.recommend_output_mask() }
+
     /// Get a reference to the internal buffer.
     #[inline]
     #[must_use]
@@ -50,28 +174,138 @@ impl<UART, MODE: DriverMode, const N: usize> GenericGps<UART, MODE, N> {
     #[inline]
     #[must_use]
     pub fn release(self) -> UART { self.uart }
+
+    /// Returns `true` if a complete message is waiting in the buffer.
+    fn has_message(&self) -> bool { self.buffer[..self.index].contains(&b'\n') }
+
+    /// Record the clock time if the bytes in `received` contain a newline.
+    fn record_received(&mut self, received: core::ops::Range<usize>) {
+        if let Some(clock) = self.clock
+            && self.buffer[received].contains(&b'\n')
+        {
+            self.received_at_us = clock();
+        }
+    }
+
+    /// Take the first complete message from the buffer.
+    ///
+    /// A message longer than the maximum sentence length is discarded up to
+    /// the next `$`, so that reading can continue.
+    fn take_message(&mut self) -> Option<BufferGuard<'_>> {
+        let limit = self.max_sentence_len.clamp(1, N.max(1));
+        loop {
+            let pending = &self.buffer[..self.index];
+            let search = &pending[..pending.len().min(limit)];
+            if let Some(end) = search.iter().position(|&byte| byte == b'\n') {
+                if let Some(kind) = SentenceType::from_message(&pending[..end]) {
+                    self.usage.record_received(kind);
+                }
+                return Some(BufferGuard::new(&mut self.buffer, &mut self.index, end));
+            }
+            if self.index < limit {
+                return None;
+            }
+
+            // Skip to the next `$`, or discard everything if there is none.
+            let skip =
+                pending[1..].iter().position(|&byte| byte == b'$').map_or(self.index, |p| p + 1);
+            self.buffer[..self.index].rotate_left(skip);
+            self.index -= skip;
+            self.record_resync();
+        }
+    }
+
+    /// Take the first complete message from the buffer and parse it as a
+    /// NMEA sentence.
+    ///
+    /// If the message is invalid but contains another `$`, only the bytes
+    /// before it are removed, so that a sentence whose newline was lost is
+    /// not discarded along with the corrupted one.
+    fn take_sentence<E>(&mut self) -> Result<Option<NmeaSentence>, NmeaError<E>> {
+        let Some(mut guard) = self.take_message() else { return Ok(None) };
+        let result = parse_sentence(guard.as_slice());
+        let resynced = result.is_err() && guard.resync();
+        drop(guard);
+
+        if resynced {
+            self.record_resync();
+        }
+        let sentence = result?;
+        if let Some(kind) = SentenceType::from_kind(&sentence.kind) {
+            self.usage.mark_consumed(kind);
+        }
+        Ok(Some(sentence))
+    }
+
+    /// Count a resynchronization, if the `stats` feature is enabled.
+    #[inline]
+    #[cfg_attr(
+        not(feature = "stats"),
+        expect(clippy::unused_self, reason = "Counted with `stats`")
+    )]
+    const fn record_resync(&mut self) {
+        #[cfg(feature = "stats")]
+        {
+            self.resyncs = self.resyncs.saturating_add(1);
+        }
+    }
+
+    /// Scan `read` bytes at the start of the buffer for an acknowledgment.
+    ///
+    /// Bytes received after the acknowledgment are kept in the buffer,
+    /// everything before it is discarded.
+    fn scan_ack(&mut self, scanner: &mut AckScanner, read: usize) -> Option<Ack> {
+        let (position, ack) = self.buffer[..read]
+            .iter()
+            .enumerate()
+            .find_map(|(position, &byte)| scanner.push(byte).map(|ack| (position, ack)))?;
+
+        self.buffer.copy_within(position + 1..read, 0);
+        self.index = read - position - 1;
+        self.record_received(0..self.index);
+        Some(ack)
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
 
 /// A guard that provides access to a portion of the internal buffer.
+///
+/// The message is removed from the buffer when the guard is dropped,
+/// a guard that is leaked leaves the message to be received again.
 #[derive(Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct BufferGuard<'a> {
     buffer: &'a mut [u8],
+    index: &'a mut usize,
     end: usize,
+    consumed: usize,
 }
 
 impl<'a> BufferGuard<'a> {
-    /// Create a new [`BufferGuard`].
+    /// Create a new [`BufferGuard`] over a message ending at the newline
+    /// at `end`, with `index` bytes of the buffer in use.
     #[inline]
     #[must_use]
-    pub(crate) const fn new(buffer: &'a mut [u8], end: usize) -> Self { Self { buffer, end } }
+    pub(crate) const fn new(buffer: &'a mut [u8], index: &'a mut usize, end: usize) -> Self {
+        Self { buffer, index, end, consumed: end + 1 }
+    }
 
     /// Get the slice of the buffer this guard provides access to.
     #[inline]
     #[must_use]
     pub fn as_slice(&self) -> &[u8] { &self.buffer[..self.end] }
+
+    /// Only remove the bytes before the next `$` in the message when the
+    /// guard is dropped, returning `false` if there is none.
+    pub(crate) fn resync(&mut self) -> bool {
+        let Some(start) = self.buffer[..self.end].iter().skip(1).position(|&byte| byte == b'$')
+        else {
+            return false;
+        };
+        self.consumed = start + 1;
+        true
+    }
 }
 
 impl Deref for BufferGuard<'_> {
@@ -82,7 +316,56 @@ impl Deref for BufferGuard<'_> {
 }
 
 impl Drop for BufferGuard<'_> {
-    // When the guard is dropped,
-    // rotate the buffer to move the unused portion to the front.
-    fn drop(&mut self) { self.buffer.rotate_left(self.end); }
+    // When the guard is dropped, rotate the message and its newline out of
+    // the buffer (or only the bytes before a resynchronization point),
+    // keeping any bytes received after it.
+    fn drop(&mut self) {
+        self.buffer[..*self.index].rotate_left(self.consumed);
+        *self.index -= self.consumed;
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A NMEA sentence, along with the clock time its newline was received.
+///
+/// See [`GenericGps::with_clock`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedSentence {
+    /// The parsed sentence.
+    pub sentence: NmeaSentence,
+    /// The clock time at which the newline was received, in microseconds.
+    pub received_at_us: u64,
+}
+
+impl TimedSentence {
+    /// Get the time at which the position of the sentence was valid.
+    ///
+    /// Returns `None` unless the sentence carries both a date and a time,
+    /// only `RMC` sentences carry a date.
+    #[must_use]
+    pub fn fix_timestamp(&self) -> Option<Timestamp> {
+        match &self.sentence.kind {
+            NmeaSentenceKind::RMC(fix)
+            | NmeaSentenceKind::GGA(fix)
+            | NmeaSentenceKind::GLL(fix)
+            | NmeaSentenceKind::GNS(fix) => fix.timestamp(),
+        }
+    }
+
+    /// Get the latency between the time of the fix and the reception of the
+    /// sentence, in microseconds.
+    ///
+    /// This is only the latency if the clock counts microseconds since the
+    /// Unix epoch, such as a clock disciplined by the PPS output. Otherwise
+    /// it also includes the offset of the clock, which is constant, so
+    /// changes in the value are still changes in latency.
+    ///
+    /// Returns `None` if the time of the fix is not known,
+    /// see [`TimedSentence::fix_timestamp`].
+    #[must_use]
+    pub fn fix_latency_us(&self) -> Option<i64> {
+        let received = i64::try_from(self.received_at_us).ok()?;
+        received.checked_sub(self.fix_timestamp()?.as_microsecond())
+    }
 }
@@ -1,8 +1,21 @@
-//! TODO
+//! Parsing and building NMEA 0183 sentences.
 
-use core::str::Utf8Error;
+use core::str::{FromStr, Utf8Error};
 
-use jiff::Timestamp;
+use jiff::{
+    Timestamp,
+    civil::{Date, Time},
+    tz::Offset,
+};
+
+mod builder;
+pub use builder::{BuildError, RateLimiter, SentenceBuilder};
+mod fields;
+use fields::FieldIter;
+mod survey;
+pub use survey::{AveragedPosition, AveragerConfig, OutlierRejection, PositionAverager};
+mod units;
+pub use units::{Feet, Kmh, Knots, Meters, MetersPerSecond};
 
 /// A generic NMEA sentence.
 #[derive(Debug, Clone, PartialEq)]
@@ -14,11 +27,41 @@ pub struct NmeaSentence {
 }
 
 /// The kind of NMEA sentence.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_generic_gps::nmea::{FixQuality, Latitude, Longitude, NmeaSentenceKind, parse_sentence};
+///
+/// // `GLL` sentences carry a position and time
+/// let sentence = parse_sentence::<()>(b"$GPGLL,4916.45,N,12311.12,W,225444,A,*1D\r\n").unwrap();
+/// let NmeaSentenceKind::GLL(fix) = sentence.kind else { panic!("Expected a GLL sentence") };
+/// assert_eq!(fix.latitude, Some(Latitude::North(49.0 + 16.45 / 60.0)));
+/// assert_eq!(fix.longitude, Some(Longitude::West(123.0 + 11.12 / 60.0)));
+/// assert_eq!(fix.time, Some(jiff::civil::time(22, 54, 44, 0)));
+/// assert_eq!(fix.quality, FixQuality::Gps);
+///
+/// // `GNS` sentences report a mode for each constellation
+/// let sentence = b"$GNGNS,014035.00,4332.69262,S,17235.48549,E,RR,13,0.9,25.63,11.24,,*70";
+/// let NmeaSentenceKind::GNS(fix) = parse_sentence::<()>(sentence).unwrap().kind else {
+///     panic!("Expected a GNS sentence")
+/// };
+/// assert_eq!(fix.quality, FixQuality::Rtk);
+/// assert_eq!(fix.satellites, Some(13));
+/// assert_eq!(fix.ellipsoidal_height().map(|height| (height.0 * 100.0).round()), Some(3687.0));
+///
+/// // Without a fix, the position is empty
+/// let sentence = parse_sentence::<()>(b"$GPGLL,,,,,092010.00,V,N").unwrap();
+/// let NmeaSentenceKind::GLL(fix) = sentence.kind else { panic!("Expected a GLL sentence") };
+/// assert_eq!((fix.latitude, fix.quality), (None, FixQuality::Invalid));
+/// ```
 #[derive(Debug, Clone, PartialEq)]
 #[expect(missing_docs, reason = "Message descriptors")]
 pub enum NmeaSentenceKind {
-    GNSS { latitude: Latitude, longitude: Longitude, timestamp: Timestamp },
-    GLSS { latitude: Latitude, longitude: Longitude, timestamp: Timestamp },
+    GNS(GpsFix),
+    GLL(GpsFix),
+    GGA(GpsFix),
+    RMC(GpsFix),
 }
 
 /// A latitude value.
@@ -41,50 +84,504 @@ pub enum Longitude {
     West(f64),
 }
 
+/// An altitude above mean sea level, as reported by `GGA` sentences.
+///
+/// Mean sea level is approximated by the geoid model of the receiver, so
+/// this is not the height above the WGS84 ellipsoid used by RTK sources.
+/// See [`GpsFix::ellipsoidal_height`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AltitudeMsl(pub Meters);
+
+/// The geoid separation, as reported by `GGA` sentences.
+///
+/// This is the height of the geoid (mean sea level) above the WGS84
+/// ellipsoid. It is negative where the geoid is below the ellipsoid,
+/// such as around -30 m along the coast of California.
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GeoidSeparation(pub Meters);
+
+/// A position fix, as reported by `GGA`, `GLL`, `GNS` and `RMC` sentences.
+///
+/// Fields that were empty in the sentence, or that the sentence
+/// does not contain, are `None`.
+///
+/// # Time
+///
+/// Times are always UTC and kept to the millisecond. Fractions are read by
+/// digit position, so receivers that send two decimals (`hhmmss.ss`) report
+/// `.50` as 500 ms. Digits past the millisecond are truncated.
+///
+/// Dates carry a two-digit year, which is read as 2000 to 2099.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct GpsFix {
+    /// The time of the fix, in UTC.
+    pub time: Option<Time>,
+    /// The date of the fix, in UTC.
+    pub date: Option<Date>,
+    /// The latitude, in degrees.
+    pub latitude: Option<Latitude>,
+    /// The longitude, in degrees.
+    pub longitude: Option<Longitude>,
+    /// The quality of the fix.
+    pub quality: FixQuality,
+    /// The number of satellites used.
+    pub satellites: Option<u8>,
+    /// The horizontal dilution of precision.
+    pub hdop: Option<f32>,
+    /// The altitude above mean sea level.
+    pub altitude: Option<AltitudeMsl>,
+    /// The height of mean sea level above the WGS84 ellipsoid.
+    pub geoid_separation: Option<GeoidSeparation>,
+    /// The speed over ground.
+    pub speed: Option<Knots>,
+    /// The course over ground, in degrees from true north.
+    pub course: Option<f32>,
+}
+
+impl GpsFix {
+    /// Get the time of the fix as a [`Timestamp`].
+    ///
+    /// Returns `None` unless both the date and time are known. Only `RMC`
+    /// sentences carry a date, `GGA` fixes must be combined with one first.
+    ///
+    /// # Example
+    ///
+    /// Timestamps survive building and parsing an `RMC` sentence unchanged.
+    ///
+    /// ```rust
+    /// use ef_generic_gps::nmea::{GpsFix, NmeaSentenceKind, SentenceBuilder, parse_sentence};
+    /// use jiff::Timestamp;
+    ///
+    /// let roundtrip = |fix: &GpsFix| {
+    ///     let mut buffer = [0u8; 82];
+    ///     let sentence = SentenceBuilder::rmc(fix).write(&mut buffer).unwrap();
+    ///     let sentence = parse_sentence::<()>(sentence).unwrap();
+    ///     let NmeaSentenceKind::RMC(fix) = sentence.kind else { unreachable!() };
+    ///     fix
+    /// };
+    ///
+    /// // Two days around midnight on 2023-12-31
+    /// let start = Timestamp::from_second(1_704_067_200 - 86_400).unwrap();
+    /// for second in (0..2 * 86_400).step_by(997).chain([86_399, 86_400]) {
+    ///     for millis in [0, 500, 999] {
+    ///         let timestamp =
+    ///             Timestamp::from_millisecond(start.as_millisecond() + second * 1_000 + millis)
+    ///                 .unwrap();
+    ///
+    ///         let fix = GpsFix::default().with_timestamp(timestamp);
+    ///         let parsed = roundtrip(&fix);
+    ///         assert_eq!(parsed.timestamp(), Some(timestamp));
+    ///         assert_eq!(roundtrip(&parsed), parsed);
+    ///     }
+    /// }
+    ///
+    /// // Two decimals are hundredths of a second
+    /// let sentence = parse_sentence::<()>(b"$GPRMC,235959.99,A,,,,,,,311223,,").unwrap();
+    /// let NmeaSentenceKind::RMC(fix) = sentence.kind else { unreachable!() };
+    /// assert_eq!(fix.timestamp(), Some("2023-12-31T23:59:59.990Z".parse().unwrap()));
+    /// ```
+    #[must_use]
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        let datetime = self.date?.to_datetime(self.time?);
+        Offset::UTC.to_timestamp(datetime).ok()
+    }
+
+    /// Get the height above the WGS84 ellipsoid, the sum of the altitude
+    /// above mean sea level and the geoid separation.
+    ///
+    /// Returns `None` unless both are known. Only `GGA` sentences carry them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_generic_gps::nmea::{
+    ///     AltitudeMsl, GeoidSeparation, Meters, NmeaSentenceKind, parse_sentence,
+    /// };
+    ///
+    /// let parse = |sentence: &[u8]| {
+    ///     let NmeaSentenceKind::GGA(fix) = parse_sentence::<()>(sentence).unwrap().kind else {
+    ///         unreachable!()
+    ///     };
+    ///     fix
+    /// };
+    ///
+    /// // The geoid is 29.9 m below the ellipsoid
+    /// let fix = parse(b"$GNGGA,174530.000,3746.4940,N,12225.4000,W,2,11,0.8,12.6,M,-29.9,M,,*48");
+    /// assert_eq!(fix.altitude, Some(AltitudeMsl(Meters(12.6))));
+    /// assert_eq!(fix.geoid_separation, Some(GeoidSeparation(Meters(-29.9))));
+    /// assert_eq!(fix.ellipsoidal_height(), Some(Meters(12.6 - 29.9)));
+    ///
+    /// // The geoid is 47.0 m above the ellipsoid
+    /// let fix = parse(b"$GPGGA,123519.000,4807.0380,N,01131.0000,E,1,08,0.9,545.4,M,47.0,M,,");
+    /// assert_eq!(fix.ellipsoidal_height(), Some(Meters(545.4 + 47.0)));
+    ///
+    /// // The geoid and the ellipsoid are the same
+    /// let fix = parse(b"$GPGGA,123519.000,4807.0380,N,01131.0000,E,1,08,0.9,545.4,M,0.0,M,,");
+    /// assert_eq!(fix.ellipsoidal_height(), Some(Meters(545.4)));
+    ///
+    /// // Receivers without a geoid model leave the separation empty
+    /// let fix = parse(b"$GPGGA,123519.000,4807.0380,N,01131.0000,E,1,08,0.9,545.4,M,,M,,");
+    /// assert_eq!(fix.altitude, Some(AltitudeMsl(Meters(545.4))));
+    /// assert_eq!(fix.geoid_separation, None);
+    /// assert_eq!(fix.ellipsoidal_height(), None);
+    ///
+    /// // Without a fix, neither is known
+    /// let fix = parse(b"$GPGGA,092010.00,,,,,0,00,99.99,,,,,,");
+    /// assert_eq!((fix.altitude, fix.geoid_separation), (None, None));
+    /// ```
+    #[must_use]
+    pub fn ellipsoidal_height(&self) -> Option<Meters> {
+        let (AltitudeMsl(altitude), GeoidSeparation(separation)) =
+            (self.altitude?, self.geoid_separation?);
+        Some(altitude + separation)
+    }
+
+    /// Set the date and time of the fix from a [`Timestamp`].
+    ///
+    /// Sentences are built with millisecond precision,
+    /// any finer precision is truncated.
+    #[must_use]
+    pub fn with_timestamp(mut self, timestamp: Timestamp) -> Self {
+        let datetime = Offset::UTC.to_datetime(timestamp);
+        self.date = Some(datetime.date());
+        self.time = Some(datetime.time());
+        self
+    }
+}
+
+/// The quality of a [`GpsFix`].
+#[repr(u8)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FixQuality {
+    /// No fix is available.
+    #[default]
+    Invalid = 0,
+    /// A standard GPS fix.
+    Gps = 1,
+    /// A differential GPS fix.
+    Dgps = 2,
+    /// A PPS fix.
+    Pps = 3,
+    /// A real-time kinematic fix.
+    Rtk = 4,
+    /// A float real-time kinematic fix.
+    FloatRtk = 5,
+    /// An estimated (dead reckoning) fix.
+    Estimated = 6,
+    /// A manually entered position.
+    Manual = 7,
+    /// A simulated fix.
+    Simulation = 8,
+}
+
+impl FixQuality {
+    /// Get the [`FixQuality`] from its `GGA` value.
+    #[must_use]
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Invalid),
+            1 => Some(Self::Gps),
+            2 => Some(Self::Dgps),
+            3 => Some(Self::Pps),
+            4 => Some(Self::Rtk),
+            5 => Some(Self::FloatRtk),
+            6 => Some(Self::Estimated),
+            7 => Some(Self::Manual),
+            8 => Some(Self::Simulation),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the fix is valid.
+    #[inline]
+    #[must_use]
+    pub const fn is_valid(self) -> bool { !matches!(self, Self::Invalid) }
+}
+
 // -------------------------------------------------------------------------------------------------
 
+/// Calculate the checksum of the data between the `$` and the `*`.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_generic_gps::nmea::checksum;
+///
+/// assert_eq!(checksum(b"GPGLL,4916.45,N,12311.12,W,225444,A"), 0x31);
+/// ```
+#[must_use]
+pub fn checksum(data: &[u8]) -> u8 { data.iter().fold(0, |acc, byte| acc ^ byte) }
+
 /// Parse a NMEA sentence from the provided buffer.
 ///
+/// The sentence may end with a `\r` or `\r\n`. If the sentence has a
+/// checksum it must be correct, sentences without one are accepted.
+///
 /// # Errors
 ///
-/// Returns an error if the sentence is malformed.
+/// Returns an error if the sentence is malformed. The sentence is read in a
+/// single pass, and a mismatched checksum is reported before any malformed
+/// field.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_generic_gps::nmea::{FixQuality, Latitude, NmeaError, NmeaSentenceKind, parse_sentence};
+///
+/// let sentence = b"$GPGGA,123519.000,4807.0380,N,01131.0000,E,1,08,0.9,545.4,M,,M,,*4C\r\n";
+/// let sentence = parse_sentence::<()>(sentence)?;
+/// assert_eq!(sentence.talker, ['G', 'P']);
+///
+/// let NmeaSentenceKind::GGA(fix) = sentence.kind else { panic!("Expected a GGA sentence") };
+/// assert_eq!(fix.latitude, Some(Latitude::North(48.0 + 7.038 / 60.0)));
+/// assert_eq!(fix.quality, FixQuality::Gps);
+/// assert_eq!(fix.satellites, Some(8));
+///
+/// // A corrupted sentence fails the checksum
+/// let sentence = b"$GPGGA,123519.000,4807.0380,N,01131.0000,E,1,09,0.9,545.4,M,,M,,*4C";
+/// assert!(matches!(parse_sentence::<()>(sentence), Err(NmeaError::Checksum)));
+///
+/// // Malformed and missing fields are reported by index, from the address field at `0`
+/// let sentence = b"$GPGGA,123519.000,4807.0380,X,01131.0000,E,1,08,0.9,545.4,M,,M,,";
+/// assert!(matches!(parse_sentence::<()>(sentence), Err(NmeaError::InvalidField(3))));
+/// let sentence = b"$GPRMC,235959.99,A,4807.0380,N";
+/// assert!(matches!(parse_sentence::<()>(sentence), Err(NmeaError::InvalidField(5))));
+/// # Ok::<(), NmeaError<()>>(())
+/// ```
 pub fn parse_sentence<T>(buffer: &[u8]) -> Result<NmeaSentence, NmeaError<T>> {
-    let buffer = core::str::from_utf8(buffer).map_err(NmeaError::Utf8)?;
-    let mut sections = buffer.split(',');
-
-    // Read the sentence identifier
-    let ident = sections.next().ok_or(NmeaError::Malformed)?;
-    let mut chars = ident.chars();
+    let mut buffer = buffer;
+    while let [rest @ .., b'\r' | b'\n'] = buffer {
+        buffer = rest;
+    }
 
     // Check for starting '$'
-    if chars.next() != Some('$') {
-        return Err(NmeaError::Malformed);
+    let [b'$', buffer @ ..] = buffer else { return Err(NmeaError::Malformed) };
+
+    // Parse the fields, then check the checksum before reporting any errors
+    let mut fields = FieldIter::new(buffer);
+    let sentence = parse_fields(&mut fields);
+    fields.finish()?;
+    sentence
+}
+
+/// Parse the fields of a sentence, starting with the address field.
+fn parse_fields<T>(fields: &mut FieldIter<'_>) -> Result<NmeaSentence, NmeaError<T>> {
+    // Read the talker ID
+    let (talker, kind) = fields.parse(|ident| {
+        let mut chars = ident.chars();
+        let talker_a = chars.next().ok_or(NmeaError::Malformed)?;
+        let talker_b = chars.next().ok_or(NmeaError::Malformed)?;
+        Ok(([talker_a, talker_b], chars.as_str()))
+    })?;
+    if talker[0] == 'P' {
+        // Proprietary sentences are not supported.
+        return Err(NmeaError::UnknownType);
     }
 
-    // // Read talker ID
-    // let talker_a = chars.next().ok_or(NmeaError::MalformedSentence)?;
-    // let talker_b = chars.next().ok_or(NmeaError::MalformedSentence)?;
-    // let talker = [talker_a, talker_b];
+    let kind = match kind {
+        "GNS" => NmeaSentenceKind::GNS(parse_gns(fields)?),
+        "GLL" => NmeaSentenceKind::GLL(parse_gll(fields)?),
+        "GGA" => NmeaSentenceKind::GGA(parse_gga(fields)?),
+        "RMC" => NmeaSentenceKind::RMC(parse_rmc(fields)?),
+        _ => return Err(NmeaError::UnknownType),
+    };
+
+    Ok(NmeaSentence { talker, kind })
+}
 
-    match &ident[chars.count()..] {
-        "GNS" => todo!(),
-        "GLL" => todo!(),
-        _ => Err(NmeaError::UnknownType),
+/// Parse the fields of a `GGA` sentence.
+fn parse_gga<T>(fields: &mut FieldIter<'_>) -> Result<GpsFix, NmeaError<T>> {
+    let time = fields.parse(|field| parse_optional(field, parse_time))?;
+    let latitude = parse_latitude(fields)?;
+    let longitude = parse_longitude(fields)?;
+    let quality = fields.parse(|field| {
+        parse_optional(field, |field| {
+            let quality = field.parse().map_err(|_| NmeaError::Malformed)?;
+            FixQuality::from_byte(quality).ok_or(NmeaError::Malformed)
+        })
+    })?;
+    let satellites = fields.parse(|field| parse_optional(field, parse_number))?;
+    let hdop = fields.parse(|field| parse_optional(field, parse_number))?;
+    let altitude = fields.parse(|field| parse_optional(field, parse_number))?.map(Meters);
+    let _altitude_unit = fields.next_field()?;
+    let geoid_separation = fields.parse(|field| parse_optional(field, parse_number))?.map(Meters);
+
+    Ok(GpsFix {
+        time,
+        latitude,
+        longitude,
+        quality: quality.unwrap_or_default(),
+        satellites,
+        hdop,
+        altitude: altitude.map(AltitudeMsl),
+        geoid_separation: geoid_separation.map(GeoidSeparation),
+        ..GpsFix::default()
+    })
+}
+
+/// Parse the fields of a `GLL` sentence.
+fn parse_gll<T>(fields: &mut FieldIter<'_>) -> Result<GpsFix, NmeaError<T>> {
+    let latitude = parse_latitude(fields)?;
+    let longitude = parse_longitude(fields)?;
+    let time = fields.parse(|field| parse_optional(field, parse_time))?;
+    let quality = fields.parse(|field| match field {
+        "A" => Ok(FixQuality::Gps),
+        "V" => Ok(FixQuality::Invalid),
+        _ => Err(NmeaError::Malformed),
+    })?;
+
+    Ok(GpsFix { time, latitude, longitude, quality, ..GpsFix::default() })
+}
+
+/// Parse the fields of a `GNS` sentence.
+fn parse_gns<T>(fields: &mut FieldIter<'_>) -> Result<GpsFix, NmeaError<T>> {
+    let time = fields.parse(|field| parse_optional(field, parse_time))?;
+    let latitude = parse_latitude(fields)?;
+    let longitude = parse_longitude(fields)?;
+    let quality = fields.parse(parse_mode)?;
+    let satellites = fields.parse(|field| parse_optional(field, parse_number))?;
+    let hdop = fields.parse(|field| parse_optional(field, parse_number))?;
+    let altitude = fields.parse(|field| parse_optional(field, parse_number))?.map(Meters);
+    let geoid_separation = fields.parse(|field| parse_optional(field, parse_number))?.map(Meters);
+
+    Ok(GpsFix {
+        time,
+        latitude,
+        longitude,
+        quality,
+        satellites,
+        hdop,
+        altitude: altitude.map(AltitudeMsl),
+        geoid_separation: geoid_separation.map(GeoidSeparation),
+        ..GpsFix::default()
+    })
+}
+
+/// Parse the mode indicators of a `GNS` sentence, one per constellation,
+/// into the quality of the first constellation with a fix.
+fn parse_mode<T>(field: &str) -> Result<FixQuality, NmeaError<T>> {
+    let mut quality = FixQuality::Invalid;
+    for mode in field.bytes() {
+        let mode = match mode {
+            b'N' => FixQuality::Invalid,
+            b'A' => FixQuality::Gps,
+            b'D' => FixQuality::Dgps,
+            b'P' => FixQuality::Pps,
+            b'R' => FixQuality::Rtk,
+            b'F' => FixQuality::FloatRtk,
+            b'E' => FixQuality::Estimated,
+            b'M' => FixQuality::Manual,
+            b'S' => FixQuality::Simulation,
+            _ => return Err(NmeaError::Malformed),
+        };
+        if !quality.is_valid() {
+            quality = mode;
+        }
     }
+    Ok(quality)
+}
+
+/// Parse the fields of a `RMC` sentence.
+fn parse_rmc<T>(fields: &mut FieldIter<'_>) -> Result<GpsFix, NmeaError<T>> {
+    let time = fields.parse(|field| parse_optional(field, parse_time))?;
+    let quality = fields.parse(|field| match field {
+        "A" => Ok(FixQuality::Gps),
+        "V" => Ok(FixQuality::Invalid),
+        _ => Err(NmeaError::Malformed),
+    })?;
+    let latitude = parse_latitude(fields)?;
+    let longitude = parse_longitude(fields)?;
+    let speed = fields.parse(|field| parse_optional(field, parse_number))?.map(Knots);
+    let course = fields.parse(|field| parse_optional(field, parse_number))?;
+    let date = fields.parse(|field| parse_optional(field, parse_date))?;
+
+    Ok(GpsFix { time, date, latitude, longitude, quality, speed, course, ..GpsFix::default() })
+}
+
+/// Parse a field, returning `None` if the field is empty.
+fn parse_optional<V, T>(
+    field: &str,
+    parse: impl FnOnce(&str) -> Result<V, NmeaError<T>>,
+) -> Result<Option<V>, NmeaError<T>> {
+    if field.is_empty() { Ok(None) } else { parse(field).map(Some) }
+}
+
+/// Parse a number from a NMEA field.
+fn parse_number<V: FromStr, T>(field: &str) -> Result<V, NmeaError<T>> {
+    field.parse().map_err(|_| NmeaError::Malformed)
 }
 
 /// Parse a latitude from two NMEA fields.
-fn _parse_latitude<T>(_degrees: &str, _direction: &str) -> Result<Latitude, NmeaError<T>> {
-    todo!()
+fn parse_latitude<T>(fields: &mut FieldIter<'_>) -> Result<Option<Latitude>, NmeaError<T>> {
+    let degrees = fields.parse(|field| parse_optional(field, parse_degrees))?;
+    fields.parse(|direction| match (degrees, direction) {
+        (None, "") => Ok(None),
+        (Some(degrees), "N") => Ok(Some(Latitude::North(degrees))),
+        (Some(degrees), "S") => Ok(Some(Latitude::South(degrees))),
+        _ => Err(NmeaError::Malformed),
+    })
 }
 
 /// Parse a longitude from two NMEA fields.
-fn _parse_longitude<T>(_degrees: &str, _direction: &str) -> Result<Longitude, NmeaError<T>> {
-    todo!()
+fn parse_longitude<T>(fields: &mut FieldIter<'_>) -> Result<Option<Longitude>, NmeaError<T>> {
+    let degrees = fields.parse(|field| parse_optional(field, parse_degrees))?;
+    fields.parse(|direction| match (degrees, direction) {
+        (None, "") => Ok(None),
+        (Some(degrees), "E") => Ok(Some(Longitude::East(degrees))),
+        (Some(degrees), "W") => Ok(Some(Longitude::West(degrees))),
+        _ => Err(NmeaError::Malformed),
+    })
+}
+
+/// Parse degrees and minutes (`dddmm.mmmm`) into degrees.
+fn parse_degrees<T>(field: &str) -> Result<f64, NmeaError<T>> {
+    let split =
+        field.find('.').unwrap_or(field.len()).checked_sub(2).ok_or(NmeaError::Malformed)?;
+    let (degrees, minutes) = field.split_at(split);
+    let degrees: u16 = if degrees.is_empty() { 0 } else { parse_number(degrees)? };
+    let minutes: f64 = parse_number(minutes)?;
+    Ok(f64::from(degrees) + minutes / 60.0)
+}
+
+/// Parse a time (`hhmmss.sss`) from a NMEA field.
+///
+/// The fraction is read as milliseconds,
+/// any digits past the third are truncated.
+fn parse_time<T>(field: &str) -> Result<Time, NmeaError<T>> {
+    let (whole, fraction) = field.split_once('.').unwrap_or((field, ""));
+    if whole.len() != 6 || !fraction.bytes().all(|digit| digit.is_ascii_digit()) {
+        return Err(NmeaError::Malformed);
+    }
+
+    let hour = parse_number(&whole[0..2])?;
+    let minute = parse_number(&whole[2..4])?;
+    let second = parse_number(&whole[4..6])?;
+
+    // Scale the fraction to milliseconds, so `.5`, `.50`, and `.500` are equal
+    let mut millis = 0i32;
+    for digit in fraction.bytes().chain(core::iter::repeat(b'0')).take(3) {
+        millis = millis * 10 + i32::from(digit - b'0');
+    }
+
+    Time::new(hour, minute, second, millis * 1_000_000).map_err(NmeaError::Time)
 }
 
-/// Parse a timestamp from a NMEA field.
-fn _parse_timestamp<T>(_timestamp: &str) -> Result<Timestamp, NmeaError<T>> { todo!() }
+/// Parse a date (`ddmmyy`) from a NMEA field.
+///
+/// Two-digit years are assumed to be in the 21st century.
+fn parse_date<T>(field: &str) -> Result<Date, NmeaError<T>> {
+    if field.len() != 6 {
+        return Err(NmeaError::Malformed);
+    }
+
+    let day = parse_number(&field[0..2])?;
+    let month = parse_number(&field[2..4])?;
+    let year: i16 = parse_number(&field[4..6])?;
+    Date::new(2000 + year, month, day).map_err(NmeaError::Time)
+}
 
 // -------------------------------------------------------------------------------------------------
 
@@ -95,6 +592,13 @@ pub enum NmeaError<Error> {
     Malformed,
     /// The sentence type was not recognized.
     UnknownType,
+    /// The sentence checksum did not match.
+    Checksum,
+    /// A field was missing or malformed.
+    ///
+    /// Fields are numbered from the address field (`GPGGA`) at `0`,
+    /// so the time of a `GGA` sentence is field `1`.
+    InvalidField(u8),
 
     /// A time error occurred.
     Time(jiff::Error),
@@ -0,0 +1,478 @@
+use core::fmt::{self, Write};
+
+use jiff::civil::{Date, Time};
+
+use super::{AltitudeMsl, GeoidSeparation, GpsFix, Knots, Latitude, Longitude, Meters, checksum};
+
+/// A builder for checksummed NMEA sentences.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_generic_gps::nmea::{
+///     AltitudeMsl, BuildError, FixQuality, GeoidSeparation, GpsFix, Knots, Latitude, Longitude,
+///     Meters, NmeaSentenceKind, SentenceBuilder, parse_sentence,
+/// };
+/// use jiff::civil::{date, time};
+///
+/// let fix = GpsFix {
+///     time: Some(time(12, 35, 19, 0)),
+///     date: Some(date(2094, 3, 23)),
+///     latitude: Some(Latitude::North(48.0 + 7.038 / 60.0)),
+///     longitude: Some(Longitude::East(11.0 + 31.0 / 60.0)),
+///     quality: FixQuality::Gps,
+///     satellites: Some(8),
+///     hdop: Some(0.9),
+///     altitude: Some(AltitudeMsl(Meters(545.4))),
+///     geoid_separation: Some(GeoidSeparation(Meters(47.0))),
+///     speed: Some(Knots(22.4)),
+///     course: Some(84.4),
+/// };
+///
+/// let mut buffer = [0u8; 82];
+/// let sentence = SentenceBuilder::gga(&fix).write(&mut buffer)?;
+/// assert_eq!(
+///     sentence,
+///     b"$GPGGA,123519.000,4807.0380,N,01131.0000,E,1,08,0.9,545.4,M,47.0,M,,*51\r\n"
+/// );
+///
+/// // Parsing the sentence returns the same fix, minus the fields `GGA` does not carry
+/// let NmeaSentenceKind::GGA(parsed) = parse_sentence::<()>(sentence).unwrap().kind else {
+///     panic!()
+/// };
+/// assert_eq!(parsed, GpsFix { date: None, speed: None, course: None, ..fix });
+///
+/// let sentence = SentenceBuilder::rmc(&fix).with_talker(['G', 'N']).write(&mut buffer)?;
+/// assert_eq!(sentence, b"$GNRMC,123519.000,A,4807.0380,N,01131.0000,E,22.4,84.4,230394,,*11\r\n");
+///
+/// let NmeaSentenceKind::RMC(parsed) = parse_sentence::<()>(sentence).unwrap().kind else {
+///     panic!()
+/// };
+/// assert_eq!(
+///     parsed,
+///     GpsFix { satellites: None, hdop: None, altitude: None, geoid_separation: None, ..fix }
+/// );
+///
+/// // Proprietary sentences carry arbitrary fields
+/// let sentence = SentenceBuilder::proprietary("PXYZ", &["DEV", "0042"]).write(&mut buffer)?;
+/// assert_eq!(sentence, b"$PXYZ,DEV,0042*5A\r\n");
+///
+/// // Sentences are never truncated or corrupted
+/// let result = SentenceBuilder::gga(&fix).write(&mut buffer[..32]);
+/// assert_eq!(result, Err(BuildError::BufferTooSmall));
+/// let result = SentenceBuilder::proprietary("PXYZ", &["DEV", "1,2"]).write(&mut buffer);
+/// assert_eq!(result, Err(BuildError::InvalidField(1)));
+/// # Ok::<(), ef_generic_gps::nmea::BuildError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SentenceBuilder<'a> {
+    talker: [char; 2],
+    kind: SentenceKind<'a>,
+}
+
+/// The kind of sentence to build.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SentenceKind<'a> {
+    Gga(&'a GpsFix),
+    Rmc(&'a GpsFix),
+    Proprietary { id: &'a str, fields: &'a [&'a str] },
+}
+
+impl<'a> SentenceBuilder<'a> {
+    /// The talker ID used unless another is set.
+    pub const DEFAULT_TALKER: [char; 2] = ['G', 'P'];
+
+    /// Build a `GGA` sentence from a [`GpsFix`].
+    #[inline]
+    #[must_use]
+    pub const fn gga(fix: &'a GpsFix) -> Self {
+        Self { talker: Self::DEFAULT_TALKER, kind: SentenceKind::Gga(fix) }
+    }
+
+    /// Build a `RMC` sentence from a [`GpsFix`].
+    ///
+    /// The status is `A` (valid) if the fix quality is valid.
+    #[inline]
+    #[must_use]
+    pub const fn rmc(fix: &'a GpsFix) -> Self {
+        Self { talker: Self::DEFAULT_TALKER, kind: SentenceKind::Rmc(fix) }
+    }
+
+    /// Build a proprietary sentence.
+    ///
+    /// The `id` must start with a `P`, followed by the manufacturer code.
+    #[inline]
+    #[must_use]
+    pub const fn proprietary(id: &'a str, fields: &'a [&'a str]) -> Self {
+        Self { talker: Self::DEFAULT_TALKER, kind: SentenceKind::Proprietary { id, fields } }
+    }
+
+    /// Set the talker ID.
+    ///
+    /// Proprietary sentences do not have a talker ID.
+    #[inline]
+    #[must_use]
+    pub const fn with_talker(mut self, talker: [char; 2]) -> Self {
+        self.talker = talker;
+        self
+    }
+
+    /// Get the identifier of the sentence, without the talker ID.
+    ///
+    /// This is the sentence type (`GGA`, `RMC`), or the full
+    /// identifier of a proprietary sentence.
+    #[must_use]
+    pub const fn id(&self) -> &'a str {
+        match self.kind {
+            SentenceKind::Gga(_) => "GGA",
+            SentenceKind::Rmc(_) => "RMC",
+            SentenceKind::Proprietary { id, .. } => id,
+        }
+    }
+
+    /// Write the sentence into `buffer`, including the checksum and `\r\n`.
+    ///
+    /// Returns the part of the buffer that was written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer is too small,
+    /// or if the identifier or a field contains reserved characters.
+    pub fn write<'b>(&self, buffer: &'b mut [u8]) -> Result<&'b [u8], BuildError> {
+        let mut writer = SentenceWriter { buffer, len: 0, checksum: 0, error: None };
+        writer.raw(b"$")?;
+
+        match self.kind {
+            SentenceKind::Gga(fix) => {
+                writer.address(self.talker, "GGA")?;
+                writer.field(Some(TimeField(fix.time)))?;
+                write_latitude(&mut writer, fix.latitude)?;
+                write_longitude(&mut writer, fix.longitude)?;
+                writer.field(Some(fix.quality as u8))?;
+                writer.field(fix.satellites.map(TwoDigits))?;
+                writer.field(fix.hdop.map(Decimal))?;
+                writer
+                    .field(fix.altitude.map(|AltitudeMsl(Meters(altitude))| Decimal(altitude)))?;
+                writer.field(Some("M"))?;
+                writer.field(
+                    fix.geoid_separation
+                        .map(|GeoidSeparation(Meters(separation))| Decimal(separation)),
+                )?;
+                writer.field(Some("M"))?;
+                // DGPS age and DGPS station
+                writer.field(None::<u8>)?;
+                writer.field(None::<u8>)?;
+            }
+            SentenceKind::Rmc(fix) => {
+                writer.address(self.talker, "RMC")?;
+                writer.field(Some(TimeField(fix.time)))?;
+                writer.field(Some(if fix.quality.is_valid() { "A" } else { "V" }))?;
+                write_latitude(&mut writer, fix.latitude)?;
+                write_longitude(&mut writer, fix.longitude)?;
+                writer.field(fix.speed.map(|Knots(speed)| Decimal(speed)))?;
+                writer.field(fix.course.map(Decimal))?;
+                writer.field(fix.date.map(DateField))?;
+                // Magnetic variation and direction
+                writer.field(None::<u8>)?;
+                writer.field(None::<u8>)?;
+            }
+            SentenceKind::Proprietary { id, fields } => {
+                if !id.starts_with('P') || !id.bytes().all(|byte| byte.is_ascii_alphanumeric()) {
+                    return Err(BuildError::InvalidAddress);
+                }
+                writer.text(id).map_err(|_| writer.take_error(BuildError::InvalidAddress))?;
+                for (index, field) in fields.iter().enumerate() {
+                    writer.raw_checked(b",")?;
+                    writer
+                        .text(field)
+                        .map_err(|_| writer.take_error(BuildError::InvalidField(index)))?;
+                }
+            }
+        }
+
+        writer.finish()
+    }
+}
+
+/// Write a latitude as `ddmm.mmmm,N`.
+fn write_latitude(
+    writer: &mut SentenceWriter<'_>,
+    latitude: Option<Latitude>,
+) -> Result<(), BuildError> {
+    let (degrees, direction) = match latitude {
+        Some(Latitude::North(degrees)) => (Some(degrees), Some("N")),
+        Some(Latitude::South(degrees)) => (Some(degrees), Some("S")),
+        None => (None, None),
+    };
+    writer.field(degrees.map(|degrees| DegreesField(degrees, 2)))?;
+    writer.field(direction)
+}
+
+/// Write a longitude as `dddmm.mmmm,E`.
+fn write_longitude(
+    writer: &mut SentenceWriter<'_>,
+    longitude: Option<Longitude>,
+) -> Result<(), BuildError> {
+    let (degrees, direction) = match longitude {
+        Some(Longitude::East(degrees)) => (Some(degrees), Some("E")),
+        Some(Longitude::West(degrees)) => (Some(degrees), Some("W")),
+        None => (None, None),
+    };
+    writer.field(degrees.map(|degrees| DegreesField(degrees, 3)))?;
+    writer.field(direction)
+}
+
+/// An error that can occur when building a sentence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BuildError {
+    /// The buffer is too small to hold the sentence.
+    BufferTooSmall,
+    /// The talker ID or sentence identifier is invalid.
+    InvalidAddress,
+    /// A field contains a reserved or non-ASCII character.
+    InvalidField(usize),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BufferTooSmall => f.write_str("buffer too small for sentence"),
+            Self::InvalidAddress => f.write_str("invalid sentence address"),
+            Self::InvalidField(index) => write!(f, "invalid sentence field {index}"),
+        }
+    }
+}
+
+impl core::error::Error for BuildError {}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Limits how often each kind of sentence is emitted.
+///
+/// Sentences are identified by [`SentenceBuilder::id`], so the same sentence
+/// type from different talkers shares a limit. Up to `N` identifiers are
+/// tracked, after which the least recently emitted one is forgotten.
+///
+/// Identifiers are compared by their first eight bytes.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_generic_gps::nmea::{GpsFix, RateLimiter, SentenceBuilder};
+///
+/// let fix = GpsFix::default();
+/// let mut limiter = RateLimiter::<4>::new(1000);
+///
+/// // Fixes arrive at 10 Hz for three seconds
+/// let (mut gga, mut rmc) = (0, 0);
+/// for now in (0..3000).step_by(100) {
+///     gga += u32::from(limiter.allow(&SentenceBuilder::gga(&fix), now));
+///     rmc += u32::from(limiter.allow(&SentenceBuilder::rmc(&fix), now));
+/// }
+///
+/// // Each sentence is emitted once per second
+/// assert_eq!((gga, rmc), (3, 3));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RateLimiter<const N: usize> {
+    interval_ms: u64,
+    slots: [Option<RateSlot>; N],
+}
+
+/// The last time a sentence was emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct RateSlot {
+    id: [u8; 8],
+    last_ms: u64,
+}
+
+impl<const N: usize> RateLimiter<N> {
+    /// Create a new [`RateLimiter`] with a minimum interval in milliseconds.
+    #[inline]
+    #[must_use]
+    pub const fn new(interval_ms: u64) -> Self { Self { interval_ms, slots: [None; N] } }
+
+    /// Get the minimum interval between sentences, in milliseconds.
+    #[inline]
+    #[must_use]
+    pub const fn interval_ms(&self) -> u64 { self.interval_ms }
+
+    /// Forget when all sentences were last emitted.
+    #[inline]
+    pub const fn reset(&mut self) { self.slots = [None; N]; }
+
+    /// Returns `true` if the sentence may be emitted at `now_ms`,
+    /// recording it as emitted.
+    pub fn allow(&mut self, sentence: &SentenceBuilder<'_>, now_ms: u64) -> bool {
+        self.allow_id(sentence.id(), now_ms)
+    }
+
+    /// Returns `true` if a sentence with the identifier may be emitted at
+    /// `now_ms`, recording it as emitted.
+    pub fn allow_id(&mut self, id: &str, now_ms: u64) -> bool {
+        let mut key = [0u8; 8];
+        let length = id.len().min(key.len());
+        key[..length].copy_from_slice(&id.as_bytes()[..length]);
+
+        if let Some(slot) = self.slots.iter_mut().flatten().find(|slot| slot.id == key) {
+            if now_ms.wrapping_sub(slot.last_ms) < self.interval_ms {
+                return false;
+            }
+            slot.last_ms = now_ms;
+            return true;
+        }
+
+        // Use an empty slot, or replace the least recently emitted sentence.
+        let slot = self.slots.iter_mut().min_by_key(|slot| slot.map(|slot| slot.last_ms));
+        if let Some(slot) = slot {
+            *slot = Some(RateSlot { id: key, last_ms: now_ms });
+        }
+        true
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Writes a sentence into a buffer while calculating its checksum.
+struct SentenceWriter<'b> {
+    buffer: &'b mut [u8],
+    len: usize,
+    checksum: u8,
+    error: Option<BuildError>,
+}
+
+impl<'b> SentenceWriter<'b> {
+    /// Write bytes that are not part of the checksum.
+    fn raw(&mut self, bytes: &[u8]) -> Result<(), BuildError> {
+        let end = self.len + bytes.len();
+        let target = self.buffer.get_mut(self.len..end).ok_or(BuildError::BufferTooSmall)?;
+        target.copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+
+    /// Write bytes that are part of the checksum, without validating them.
+    fn raw_checked(&mut self, bytes: &[u8]) -> Result<(), BuildError> {
+        self.raw(bytes)?;
+        self.checksum ^= checksum(bytes);
+        Ok(())
+    }
+
+    /// Write the talker ID and sentence type.
+    fn address(&mut self, talker: [char; 2], kind: &str) -> Result<(), BuildError> {
+        if !talker.iter().all(char::is_ascii_uppercase) {
+            return Err(BuildError::InvalidAddress);
+        }
+        let mut bytes = [0u8; 2];
+        for (byte, c) in bytes.iter_mut().zip(talker) {
+            // Checked to be ASCII above.
+            *byte = c as u8;
+        }
+        self.raw_checked(&bytes)?;
+        self.raw_checked(kind.as_bytes())
+    }
+
+    /// Write a separator followed by an optional field.
+    fn field(&mut self, value: Option<impl fmt::Display>) -> Result<(), BuildError> {
+        self.raw_checked(b",")?;
+        match value {
+            Some(value) => {
+                write!(self, "{value}").map_err(|_| self.take_error(BuildError::InvalidField(0)))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Write validated text.
+    fn text(&mut self, text: &str) -> fmt::Result { self.write_str(text) }
+
+    /// Take the error that caused a write to fail.
+    fn take_error(&mut self, default: BuildError) -> BuildError {
+        match self.error.take() {
+            Some(BuildError::BufferTooSmall) => BuildError::BufferTooSmall,
+            _ => default,
+        }
+    }
+
+    /// Write the checksum and line ending.
+    fn finish(mut self) -> Result<&'b [u8], BuildError> {
+        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+        let sum = self.checksum;
+        self.raw(&[b'*', HEX[usize::from(sum >> 4)], HEX[usize::from(sum & 0xF)], b'\r', b'\n'])?;
+        Ok(&self.buffer[..self.len])
+    }
+}
+
+impl Write for SentenceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let reserved = |byte: &u8| matches!(byte, b'$' | b'!' | b'*' | b',' | b'\\' | b'^' | b'~');
+        if s.bytes().any(|byte| !(0x20..0x7F).contains(&byte) || reserved(&byte)) {
+            self.error = Some(BuildError::InvalidField(0));
+            return Err(fmt::Error);
+        }
+
+        self.raw_checked(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+/// Formats a number with two digits.
+struct TwoDigits(u8);
+
+impl fmt::Display for TwoDigits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{:02}", self.0) }
+}
+
+/// Formats a number with a single decimal.
+struct Decimal(f32);
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{:.1}", self.0) }
+}
+
+/// Formats a time as `hhmmss.sss`.
+struct TimeField(Option<Time>);
+
+impl fmt::Display for TimeField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(time) = self.0 else { return Ok(()) };
+        let millis = time.subsec_nanosecond() / 1_000_000;
+        write!(f, "{:02}{:02}{:02}.{millis:03}", time.hour(), time.minute(), time.second())
+    }
+}
+
+/// Formats a date as `ddmmyy`.
+struct DateField(Date);
+
+impl fmt::Display for DateField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let year = self.0.year().rem_euclid(100);
+        write!(f, "{:02}{:02}{year:02}", self.0.day(), self.0.month())
+    }
+}
+
+/// Formats degrees as degrees and minutes, with the given number of degree
+/// digits.
+struct DegreesField(f64, usize);
+
+impl fmt::Display for DegreesField {
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "Clamped")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Round to 1/10000th of a minute using integers, so minutes never round up to
+        // 60.
+        let total = (self.0.clamp(0.0, 360.0) * 600_000.0 + 0.5) as u64;
+        let (degrees, minutes) = (total / 600_000, total % 600_000);
+        write!(
+            f,
+            "{degrees:0width$}{:02}.{:04}",
+            minutes / 10_000,
+            minutes % 10_000,
+            width = self.1
+        )
+    }
+}
@@ -0,0 +1,131 @@
+//! Splitting a sentence into fields in a single pass.
+
+use core::str::Utf8Error;
+
+use super::NmeaError;
+
+/// An iterator over the comma-separated fields of a NMEA sentence,
+/// calculating the checksum as it advances.
+///
+/// Fields are numbered from the address field (`GPGGA`) at index `0`,
+/// as in the NMEA 0183 standard. Iteration ends at the `*` of the checksum,
+/// which is checked by [`FieldIter::finish`] without reading the sentence
+/// again.
+pub(crate) struct FieldIter<'a> {
+    /// The bytes following the last field.
+    remaining: &'a [u8],
+    /// The index of the next field.
+    index: u8,
+    /// The checksum of the fields so far, including their separators.
+    checksum: u8,
+    /// The checksum sent with the sentence, once the `*` is reached.
+    expected: Option<&'a [u8]>,
+    /// Whether the last field was returned.
+    done: bool,
+}
+
+impl<'a> FieldIter<'a> {
+    /// Create a new [`FieldIter`] over the bytes between the `$` and the
+    /// line ending.
+    pub(crate) const fn new(sentence: &'a [u8]) -> Self {
+        Self { remaining: sentence, index: 0, checksum: 0, expected: None, done: false }
+    }
+
+    /// Get the next field.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no fields left,
+    /// or if the field is not valid UTF-8.
+    pub(crate) fn next_field<T>(&mut self) -> Result<&'a str, NmeaError<T>> {
+        let index = self.index;
+        self.next().ok_or(NmeaError::InvalidField(index))?.map_err(NmeaError::Utf8)
+    }
+
+    /// Parse the next field.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no fields left, or if the field is not
+    /// valid UTF-8. Fields that `parse` rejects as
+    /// [`Malformed`](NmeaError::Malformed) are reported with their index.
+    pub(crate) fn parse<V, T>(
+        &mut self,
+        parse: impl FnOnce(&'a str) -> Result<V, NmeaError<T>>,
+    ) -> Result<V, NmeaError<T>> {
+        let index = self.index;
+        parse(self.next_field()?).map_err(|err| match err {
+            NmeaError::Malformed => NmeaError::InvalidField(index),
+            err => err,
+        })
+    }
+
+    /// Skip the remaining fields and check the checksum, if the sentence has
+    /// one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checksum does not match or is malformed,
+    /// or if any skipped field is not valid UTF-8.
+    pub(crate) fn finish<T>(mut self) -> Result<(), NmeaError<T>> {
+        let skipped = self.by_ref().filter_map(Result::err).last();
+
+        if let Some(expected) = self.expected {
+            let expected = core::str::from_utf8(expected)
+                .ok()
+                .and_then(|sum| u8::from_str_radix(sum, 16).ok())
+                .ok_or(NmeaError::Malformed)?;
+            if self.checksum != expected {
+                return Err(NmeaError::Checksum);
+            }
+        }
+        skipped.map_or(Ok(()), |err| Err(NmeaError::Utf8(err)))
+    }
+}
+
+impl<'a> Iterator for FieldIter<'a> {
+    type Item = Result<&'a str, Utf8Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut ascii = true;
+        let mut end = self.remaining.len();
+        for (position, &byte) in self.remaining.iter().enumerate() {
+            match byte {
+                b',' => {
+                    self.checksum ^= byte;
+                    end = position;
+                    break;
+                }
+                b'*' => {
+                    self.expected = Some(&self.remaining[position + 1..]);
+                    end = position;
+                    break;
+                }
+                _ => {
+                    self.checksum ^= byte;
+                    ascii &= byte.is_ascii();
+                }
+            }
+        }
+
+        let field = &self.remaining[..end];
+        if let Some(b',') = self.remaining.get(end) {
+            self.remaining = &self.remaining[end + 1..];
+        } else {
+            self.remaining = &[];
+            self.done = true;
+        }
+        self.index = self.index.saturating_add(1);
+
+        if ascii {
+            // SAFETY: Every byte of the field is ASCII
+            Some(Ok(unsafe { core::str::from_utf8_unchecked(field) }))
+        } else {
+            Some(core::str::from_utf8(field))
+        }
+    }
+}
@@ -0,0 +1,353 @@
+//! Averaging fixes over time, for surveying the position of a base station.
+
+use super::{AltitudeMsl, GpsFix, Latitude, Longitude, Meters};
+
+/// The mean radius of the earth, in meters.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// How a [`PositionAverager`] rejects outlying fixes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OutlierRejection {
+    /// Accept every fix.
+    None,
+    /// Reject fixes further than `k` horizontal standard deviations from
+    /// the running mean.
+    Sigma(f64),
+    /// Reject fixes further than a fixed distance from the running mean.
+    Radius(Meters),
+}
+
+/// The configuration of a [`PositionAverager`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AveragerConfig {
+    /// How outlying fixes are rejected.
+    pub outliers: OutlierRejection,
+    /// The number of fixes accepted before outliers are rejected,
+    /// so the running mean and deviation settle first.
+    pub warmup: u32,
+    /// Whether fixes are weighted by `1 / HDOP²`.
+    ///
+    /// Fixes without an HDOP are weighted as an HDOP of `1`.
+    pub hdop_weighting: bool,
+}
+
+impl AveragerConfig {
+    /// Reject fixes further than 3 standard deviations from the mean after
+    /// 10 fixes, weighting fixes by their HDOP.
+    pub const DEFAULT: Self =
+        Self { outliers: OutlierRejection::Sigma(3.0), warmup: 10, hdop_weighting: true };
+}
+
+impl Default for AveragerConfig {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// The result of a [`PositionAverager`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AveragedPosition {
+    /// The mean latitude.
+    pub latitude: Latitude,
+    /// The mean longitude.
+    pub longitude: Longitude,
+    /// The mean altitude, if any accepted fix had one.
+    pub altitude: Option<AltitudeMsl>,
+    /// The number of fixes included in the mean.
+    pub accepted: u32,
+    /// The number of fixes rejected as outliers or without a position.
+    pub rejected: u32,
+    /// The horizontal standard deviation of the accepted fixes,
+    /// the root of the north and east variances.
+    pub horizontal_std_dev: Meters,
+    /// The standard deviation of the accepted altitudes.
+    pub vertical_std_dev: Option<Meters>,
+}
+
+/// A weighted running mean and variance, updated with West's incremental
+/// form of Welford's algorithm.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Welford {
+    weight: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Welford {
+    /// Add a weighted sample.
+    fn add(&mut self, value: f64, weight: f64) {
+        self.weight += weight;
+        let delta = value - self.mean;
+        self.mean += delta * weight / self.weight;
+        self.m2 += weight * delta * (value - self.mean);
+    }
+
+    /// Get the weighted variance, or `None` without samples.
+    fn variance(&self) -> Option<f64> {
+        (self.weight > 0.0).then(|| (self.m2 / self.weight).max(0.0))
+    }
+}
+
+/// Averages fixes over time into a single position, for surveying a fixed
+/// base station.
+///
+/// Positions are accumulated in meters north and east of the first
+/// accepted fix, with a weighted incremental mean and variance, so hours of
+/// fixes do not lose precision to large sums.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_generic_gps::nmea::{
+///     AveragerConfig, FixQuality, GpsFix, Latitude, Longitude, PositionAverager,
+/// };
+///
+/// /// A fix `north` and `east` meters from a surveyed point.
+/// fn fix(north: f64, east: f64, hdop: f32) -> GpsFix {
+///     let (latitude, longitude) = (48.858_370_f64, 2.294_481);
+///     let meters_per_degree = 6_371_008.8_f64.to_radians();
+///     let east_per_degree = meters_per_degree * latitude.to_radians().cos();
+///     GpsFix {
+///         latitude: Some(Latitude::North(latitude + north / meters_per_degree)),
+///         longitude: Some(Longitude::East(longitude + east / east_per_degree)),
+///         quality: FixQuality::Gps,
+///         hdop: Some(hdop),
+///         ..GpsFix::default()
+///     }
+/// }
+///
+/// /// Deterministic noise, uniform in `-1.0..1.0`.
+/// struct Noise(u64);
+///
+/// impl Noise {
+///     fn next(&mut self) -> f64 {
+///         self.0 ^= self.0 << 13;
+///         self.0 ^= self.0 >> 7;
+///         self.0 ^= self.0 << 17;
+///         (self.0 >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+///     }
+/// }
+///
+/// let mut averager = PositionAverager::new(AveragerConfig::DEFAULT);
+/// assert!(averager.result().is_none());
+///
+/// // Two hours of fixes with 3 m of noise, and a multipath jump every
+/// // thousand fixes
+/// let mut noise = Noise(0x2545_F491_4F6C_DD1D);
+/// for index in 0..7_200 {
+///     let (north, east) = (3.0 * noise.next(), 3.0 * noise.next());
+///     if index % 1_000 == 999 {
+///         assert!(!averager.add(&fix(north + 40.0, east, 0.9)));
+///     } else {
+///         assert!(averager.add(&fix(north, east, 0.9)));
+///     }
+/// }
+/// // Fixes without a position are never averaged
+/// assert!(!averager.add(&GpsFix::default()));
+///
+/// let result = averager.result().unwrap();
+/// assert_eq!((result.accepted, result.rejected), (7_193, 8));
+/// let (Latitude::North(latitude), Longitude::East(longitude)) =
+///     (result.latitude, result.longitude)
+/// else {
+///     unreachable!()
+/// };
+/// // Within 10 cm of the surveyed point
+/// assert!((latitude - 48.858_370).abs() < 1e-6);
+/// assert!((longitude - 2.294_481).abs() < 1.4e-6);
+/// // Uniform noise of ±3 m on both axes has a deviation of √6 m
+/// assert!((result.horizontal_std_dev.0 - 6f32.sqrt()).abs() < 0.1);
+///
+/// // Fixes with a worse HDOP count less
+/// let mut averager = PositionAverager::new(AveragerConfig::DEFAULT);
+/// averager.add(&fix(0.0, 0.0, 1.0));
+/// averager.add(&fix(9.0, 0.0, 2.0));
+/// let Latitude::North(latitude) = averager.result().unwrap().latitude else { unreachable!() };
+/// let north = (latitude - 48.858_370) * 6_371_008.8_f64.to_radians();
+/// assert!((north - 1.8).abs() < 1e-3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionAverager {
+    config: AveragerConfig,
+    /// The latitude and longitude of the first accepted fix, in degrees,
+    /// and the meters per degree of longitude there.
+    reference: Option<(f64, f64, f64)>,
+    north: Welford,
+    east: Welford,
+    altitude: Welford,
+    accepted: u32,
+    rejected: u32,
+}
+
+impl PositionAverager {
+    /// Create a new, empty [`PositionAverager`].
+    #[must_use]
+    pub const fn new(config: AveragerConfig) -> Self {
+        const EMPTY: Welford = Welford { weight: 0.0, mean: 0.0, m2: 0.0 };
+        Self {
+            config,
+            reference: None,
+            north: EMPTY,
+            east: EMPTY,
+            altitude: EMPTY,
+            accepted: 0,
+            rejected: 0,
+        }
+    }
+
+    /// Get the [`AveragerConfig`].
+    #[inline]
+    #[must_use]
+    pub const fn config(&self) -> &AveragerConfig { &self.config }
+
+    /// Add a fix to the average.
+    ///
+    /// Returns `true` if the fix was accepted, and `false` if it was
+    /// rejected as an outlier or had no valid position.
+    pub fn add(&mut self, fix: &GpsFix) -> bool {
+        let (Some(latitude), Some(longitude), true) =
+            (fix.latitude, fix.longitude, fix.quality.is_valid())
+        else {
+            self.rejected += 1;
+            return false;
+        };
+        let (latitude, longitude) = (signed_latitude(latitude), signed_longitude(longitude));
+
+        let (lat0, lon0, east_per_degree) = *self.reference.get_or_insert_with(|| {
+            (latitude, longitude, METERS_PER_DEGREE * cos_degrees(latitude))
+        });
+        let north = (latitude - lat0) * METERS_PER_DEGREE;
+        let east = wrap_degrees(longitude - lon0) * east_per_degree;
+
+        if self.is_outlier(north, east) {
+            self.rejected += 1;
+            return false;
+        }
+
+        let weight = match (self.config.hdop_weighting, fix.hdop) {
+            (true, Some(hdop)) if hdop > 0.0 => 1.0 / f64::from(hdop * hdop),
+            _ => 1.0,
+        };
+        self.north.add(north, weight);
+        self.east.add(east, weight);
+        if let Some(AltitudeMsl(Meters(altitude))) = fix.altitude {
+            self.altitude.add(f64::from(altitude), weight);
+        }
+        self.accepted += 1;
+        true
+    }
+
+    /// Get the averaged position, or `None` if no fix was accepted.
+    #[must_use]
+    #[expect(clippy::cast_possible_truncation, reason = "Meters are reported as `f32`")]
+    pub fn result(&self) -> Option<AveragedPosition> {
+        let (lat0, lon0, east_per_degree) = self.reference?;
+        let latitude = lat0 + self.north.mean / METERS_PER_DEGREE;
+        let longitude = wrap_degrees(lon0 + self.east.mean / east_per_degree);
+        let variance = self.north.variance()? + self.east.variance()?;
+
+        Some(AveragedPosition {
+            latitude: if latitude < 0.0 {
+                Latitude::South(-latitude)
+            } else {
+                Latitude::North(latitude)
+            },
+            longitude: if longitude < 0.0 {
+                Longitude::West(-longitude)
+            } else {
+                Longitude::East(longitude)
+            },
+            altitude: (self.altitude.weight > 0.0)
+                .then_some(AltitudeMsl(Meters(self.altitude.mean as f32))),
+            accepted: self.accepted,
+            rejected: self.rejected,
+            horizontal_std_dev: Meters(sqrt(variance) as f32),
+            vertical_std_dev: self.altitude.variance().map(|v| Meters(sqrt(v) as f32)),
+        })
+    }
+
+    /// Forget every fix, keeping the configuration.
+    pub const fn reset(&mut self) { *self = Self::new(self.config); }
+
+    /// Returns `true` if a position is too far from the running mean.
+    fn is_outlier(&self, north: f64, east: f64) -> bool {
+        if self.accepted < self.config.warmup.max(1) {
+            return false;
+        }
+        let (dn, de) = (north - self.north.mean, east - self.east.mean);
+        let distance2 = dn * dn + de * de;
+        match self.config.outliers {
+            OutlierRejection::None => false,
+            OutlierRejection::Sigma(k) => {
+                let variance =
+                    self.north.variance().unwrap_or(0.0) + self.east.variance().unwrap_or(0.0);
+                distance2 > k * k * variance
+            }
+            OutlierRejection::Radius(Meters(radius)) => {
+                distance2 > f64::from(radius) * f64::from(radius)
+            }
+        }
+    }
+}
+
+/// The meters per degree of latitude.
+const METERS_PER_DEGREE: f64 = EARTH_RADIUS * core::f64::consts::PI / 180.0;
+
+/// Get a latitude in degrees, negative to the south.
+const fn signed_latitude(latitude: Latitude) -> f64 {
+    match latitude {
+        Latitude::North(degrees) => degrees,
+        Latitude::South(degrees) => -degrees,
+    }
+}
+
+/// Get a longitude in degrees, negative to the west.
+const fn signed_longitude(longitude: Longitude) -> f64 {
+    match longitude {
+        Longitude::East(degrees) => degrees,
+        Longitude::West(degrees) => -degrees,
+    }
+}
+
+/// Wrap a longitude difference into `-180.0..180.0` degrees.
+fn wrap_degrees(degrees: f64) -> f64 {
+    if degrees >= 180.0 {
+        degrees - 360.0
+    } else if degrees < -180.0 {
+        degrees + 360.0
+    } else {
+        degrees
+    }
+}
+
+/// Get the cosine of a latitude in degrees.
+///
+/// `core` has no trigonometry, so this sums the Taylor series up to the
+/// 14th power, which is accurate to `1e-9` within `±90°`.
+fn cos_degrees(degrees: f64) -> f64 {
+    let x = degrees.clamp(-90.0, 90.0).to_radians();
+    let x2 = x * x;
+    let (mut term, mut sum) = (1.0, 1.0);
+    for n in (2..=14).step_by(2) {
+        term *= -x2 / f64::from(n * (n - 1));
+        sum += term;
+    }
+    sum
+}
+
+/// Get the square root of a non-negative number.
+///
+/// `core` has no square root, so this refines an estimate from the
+/// exponent with Newton's method.
+fn sqrt(value: f64) -> f64 {
+    if value <= 0.0 || !value.is_finite() {
+        return value.max(0.0);
+    }
+    let bits = value.to_bits();
+    let mut estimate = f64::from_bits((bits >> 1) + (1023 << 51));
+    for _ in 0..6 {
+        estimate = 0.5 * (estimate + value / estimate);
+    }
+    estimate
+}
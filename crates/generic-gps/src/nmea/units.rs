@@ -0,0 +1,171 @@
+//! Typed speeds and distances, so values in different units cannot be mixed.
+//!
+//! Formatting appends the unit symbol and respects the requested precision.
+
+use core::{
+    fmt::{self, Write},
+    ops::{Add, Div, Mul, Sub},
+};
+
+/// Meters per second in a knot, one nautical mile (1852 m) per hour.
+const METERS_PER_SECOND_PER_KNOT: f32 = 1852.0 / 3600.0;
+/// Kilometers per hour in a knot.
+const KMH_PER_KNOT: f32 = 1.852;
+/// Kilometers per hour in a meter per second.
+const KMH_PER_METER_PER_SECOND: f32 = 3.6;
+/// Meters in an international foot.
+const METERS_PER_FOOT: f32 = 0.3048;
+
+macro_rules! unit {
+    ($($(#[$meta:meta])* $ident:ident: $symbol:literal,)+) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+            #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+            pub struct $ident(pub f32);
+
+            impl $ident {
+                /// The unit symbol.
+                pub const SYMBOL: &'static str = $symbol;
+
+                /// Format the value with a number of decimals into `buffer`.
+                ///
+                /// Returns the part of the buffer that was written.
+                ///
+                /// # Errors
+                ///
+                /// Returns an error if the buffer is too small.
+                pub fn format(self, buffer: &mut [u8], decimals: usize) -> Result<&str, fmt::Error> {
+                    let mut writer = BufferWriter { buffer, len: 0 };
+                    write!(writer, "{self:.decimals$}")?;
+                    let BufferWriter { buffer, len } = writer;
+                    core::str::from_utf8(&buffer[..len]).map_err(|_| fmt::Error)
+                }
+            }
+
+            impl fmt::Display for $ident {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    match f.precision() {
+                        Some(precision) => write!(f, "{:.precision$} {}", self.0, Self::SYMBOL),
+                        None => write!(f, "{} {}", self.0, Self::SYMBOL),
+                    }
+                }
+            }
+
+            impl Add for $ident {
+                type Output = Self;
+
+                fn add(self, rhs: Self) -> Self { Self(self.0 + rhs.0) }
+            }
+
+            impl Sub for $ident {
+                type Output = Self;
+
+                fn sub(self, rhs: Self) -> Self { Self(self.0 - rhs.0) }
+            }
+
+            impl Mul<f32> for $ident {
+                type Output = Self;
+
+                fn mul(self, rhs: f32) -> Self { Self(self.0 * rhs) }
+            }
+
+            impl Div<f32> for $ident {
+                type Output = Self;
+
+                fn div(self, rhs: f32) -> Self { Self(self.0 / rhs) }
+            }
+
+            impl Div for $ident {
+                type Output = f32;
+
+                fn div(self, rhs: Self) -> f32 { self.0 / rhs.0 }
+            }
+        )+
+    };
+}
+
+macro_rules! convert {
+    ($($from:ident => $to:ident: |$value:ident| $expr:expr,)+) => {
+        $(
+            impl From<$from> for $to {
+                fn from($from($value): $from) -> Self { Self($expr) }
+            }
+        )+
+    };
+}
+
+unit! {
+    /// A speed in knots, as reported by NMEA sentences.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_generic_gps::nmea::{Feet, Kmh, Knots, Meters, MetersPerSecond};
+    ///
+    /// // Conversions use the exact definitions of each unit
+    /// assert_eq!(MetersPerSecond::from(Knots(1.0)), MetersPerSecond(1852.0 / 3600.0));
+    /// assert_eq!(Kmh::from(Knots(1.0)), Kmh(1.852));
+    /// assert_eq!(Kmh::from(MetersPerSecond(10.0)), Kmh(36.0));
+    /// assert_eq!(Meters::from(Feet(1.0)), Meters(0.3048));
+    ///
+    /// // Converting back and forth keeps the value
+    /// for knots in [0.0, 0.1, 1.0, 22.4, 100.0, 1000.0] {
+    ///     let speed = Knots(knots);
+    ///     let kmh = Kmh::from(speed);
+    ///     let mps = MetersPerSecond::from(speed);
+    ///     assert!((Knots::from(kmh) - speed).0.abs() <= knots * 1e-6);
+    ///     assert!((Knots::from(mps) - speed).0.abs() <= knots * 1e-6);
+    ///     assert!((Kmh::from(mps) - kmh).0.abs() <= knots * 1e-5);
+    /// }
+    /// let altitude = Meters(545.4);
+    /// assert!((Meters::from(Feet::from(altitude)) - altitude).0.abs() < 1e-4);
+    ///
+    /// // Arithmetic only mixes values of the same unit
+    /// assert_eq!(Knots(10.0) + Knots(2.5), Knots(12.5));
+    /// assert_eq!(Meters(10.0) * 3.0 - Meters(5.0), Meters(25.0));
+    /// assert_eq!(Kmh(90.0) / Kmh(30.0), 3.0);
+    ///
+    /// // Values format with their symbol, into a buffer without allocating
+    /// let mut buffer = [0u8; 16];
+    /// assert_eq!(Kmh::from(Knots(22.4)).format(&mut buffer, 1), Ok("41.5 km/h"));
+    /// assert_eq!(Feet::from(Meters(545.4)).format(&mut buffer, 0), Ok("1789 ft"));
+    /// assert!(MetersPerSecond(123_456.0).format(&mut buffer[..8], 2).is_err());
+    /// assert_eq!(format!("{:.2}", Knots(3.0)), "3.00 kn");
+    /// ```
+    Knots: "kn",
+    /// A speed in meters per second.
+    MetersPerSecond: "m/s",
+    /// A speed in kilometers per hour.
+    Kmh: "km/h",
+    /// A distance in meters, as reported by NMEA sentences.
+    Meters: "m",
+    /// A distance in international feet.
+    Feet: "ft",
+}
+
+convert! {
+    Knots => MetersPerSecond: |knots| knots * METERS_PER_SECOND_PER_KNOT,
+    Knots => Kmh: |knots| knots * KMH_PER_KNOT,
+    MetersPerSecond => Knots: |mps| mps / METERS_PER_SECOND_PER_KNOT,
+    MetersPerSecond => Kmh: |mps| mps * KMH_PER_METER_PER_SECOND,
+    Kmh => Knots: |kmh| kmh / KMH_PER_KNOT,
+    Kmh => MetersPerSecond: |kmh| kmh / KMH_PER_METER_PER_SECOND,
+    Meters => Feet: |meters| meters / METERS_PER_FOOT,
+    Feet => Meters: |feet| feet * METERS_PER_FOOT,
+}
+
+/// Writes formatted text into a byte buffer.
+struct BufferWriter<'b> {
+    buffer: &'b mut [u8],
+    len: usize,
+}
+
+impl Write for BufferWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.buffer.get_mut(self.len..end).ok_or(fmt::Error)?.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+//! An in-memory UART for replaying recorded NMEA streams.
+//!
+//! Recorded logs can be replayed into a [`GenericGps`](crate::GenericGps)
+//! in chunks, with errors and delays injected along the way, to test code
+//! that consumes the driver without a real GPS module.
+
+use core::{
+    fmt,
+    future::poll_fn,
+    iter::Once,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use embedded_io::{ErrorKind, ErrorType};
+
+/// A UART that replays recorded bytes, implementing both
+/// [`embedded_io::Read`] and [`embedded_io_async::Read`].
+///
+/// Every read returns bytes from a single chunk, so a stream split into
+/// chunks is read in the same pieces as it was received. Chunks can be
+/// split further with [`ReplayReader::with_chunk_size`].
+///
+/// Once every byte has been replayed, reads return
+/// [`ReplayError::Exhausted`] instead of `Ok(0)`, so that loops waiting for
+/// more data end instead of spinning forever.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_generic_gps::{
+///     GenericGps,
+///     mode::Blocking,
+///     nmea::{Knots, Longitude, NmeaError, NmeaSentenceKind},
+///     replay::{ReplayError, ReplayReader},
+/// };
+///
+/// const LOG: &[u8] =
+///     include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/driving.nmea"));
+///
+/// // Replay the log 7 bytes at a time, splitting sentences across reads
+/// let mut gps = GenericGps::<_, Blocking, 128>::new(ReplayReader::new(LOG).with_chunk_size(7));
+///
+/// let mut fixes = Vec::new();
+/// loop {
+///     match gps.receive_sentence() {
+///         Ok(sentence) => {
+///             if let NmeaSentenceKind::RMC(fix) = sentence.kind {
+///                 fixes.push(fix);
+///             }
+///         }
+///         Err(NmeaError::Other(ReplayError::Exhausted)) => break,
+///         Err(err) => panic!("Unexpected error: {err:?}"),
+///     }
+/// }
+///
+/// // One fix per second, heading west
+/// assert_eq!(fixes.len(), 5);
+/// for pair in fixes.windows(2) {
+///     let elapsed = pair[1].timestamp().unwrap() - pair[0].timestamp().unwrap();
+///     assert_eq!(elapsed.get_seconds(), 1);
+///
+///     let (Some(Longitude::West(a)), Some(Longitude::West(b))) =
+///         (pair[0].longitude, pair[1].longitude)
+///     else {
+///         panic!("Expected western longitudes");
+///     };
+///     assert!(b > a);
+/// }
+/// assert!(fixes.iter().all(|fix| fix.speed == Some(Knots(30.2)) && fix.quality.is_valid()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReplayReader<'a, I = Once<&'a [u8]>> {
+    chunks: I,
+    current: &'a [u8],
+    chunk_size: usize,
+    errors: &'a [usize],
+    position: usize,
+    pending: usize,
+}
+
+impl<'a> ReplayReader<'a> {
+    /// Create a new [`ReplayReader`] that replays a byte slice.
+    #[inline]
+    #[must_use]
+    pub fn new(bytes: &'a [u8]) -> Self { Self::from_chunks(core::iter::once(bytes)) }
+}
+
+impl<'a, I: Iterator<Item = &'a [u8]>> ReplayReader<'a, I> {
+    /// Create a new [`ReplayReader`] that replays a sequence of chunks.
+    #[must_use]
+    pub fn from_chunks(chunks: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            chunks: chunks.into_iter(),
+            current: &[],
+            chunk_size: usize::MAX,
+            errors: &[],
+            position: 0,
+            pending: 0,
+        }
+    }
+
+    /// Set the maximum number of bytes returned by a single read.
+    ///
+    /// A chunk size of `0` is treated as `1`.
+    #[inline]
+    #[must_use]
+    pub const fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = if chunk_size == 0 { 1 } else { chunk_size };
+        self
+    }
+
+    /// Set the positions in the stream at which a read fails with
+    /// [`ReplayError::Injected`].
+    ///
+    /// Positions must be in ascending order. Each position fails one read,
+    /// after which the stream continues without losing any bytes.
+    #[inline]
+    #[must_use]
+    pub const fn with_errors(mut self, positions: &'a [usize]) -> Self {
+        self.errors = positions;
+        self
+    }
+
+    /// Set the number of times an async read returns [`Poll::Pending`]
+    /// before completing, exercising every await point of the caller.
+    ///
+    /// Blocking reads are not affected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_generic_gps::{
+    ///     GenericGps,
+    ///     mode::Async,
+    ///     nmea::{FixQuality, NmeaError, NmeaSentenceKind},
+    ///     replay::{ReplayError, ReplayReader},
+    /// };
+    /// # use ef_generic_gps::replay::block_on;
+    ///
+    /// const LOG: &[u8] =
+    ///     include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/cold_start.nmea"));
+    ///
+    /// // Replay the log with a failed read in the middle of a sentence
+    /// let reader = ReplayReader::new(LOG).with_chunk_size(16).with_pending(2).with_errors(&[100]);
+    /// let mut gps = GenericGps::<_, Async, 128>::new(reader);
+    ///
+    /// let (mut qualities, mut errors, mut unknown) = (Vec::new(), 0, 0);
+    /// loop {
+    ///     match block_on(gps.receive_sentence()) {
+    ///         Ok(sentence) => match sentence.kind {
+    ///             NmeaSentenceKind::GGA(fix) => qualities.push(fix.quality),
+    ///             NmeaSentenceKind::RMC(fix) => assert_eq!(fix.quality, *qualities.last().unwrap()),
+    ///             _ => unreachable!(),
+    ///         },
+    ///         Err(NmeaError::Other(ReplayError::Injected)) => errors += 1,
+    ///         Err(NmeaError::Other(ReplayError::Exhausted)) => break,
+    ///         // Satellites in view are not parsed
+    ///         Err(NmeaError::UnknownType) => unknown += 1,
+    ///         Err(err) => panic!("Unexpected error: {err:?}"),
+    ///     }
+    /// }
+    ///
+    /// // No sentences are lost to the injected error
+    /// assert_eq!(errors, 1);
+    /// assert_eq!(unknown, 6);
+    /// assert_eq!(qualities, [[FixQuality::Invalid; 3], [FixQuality::Gps; 3]].concat());
+    /// assert_eq!(gps.uart().position(), LOG.len());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn with_pending(mut self, pending: usize) -> Self {
+        self.pending = pending;
+        self
+    }
+
+    /// Get the number of bytes replayed so far.
+    #[inline]
+    #[must_use]
+    pub const fn position(&self) -> usize { self.position }
+
+    /// Replay the next bytes into `buf`.
+    fn replay(&mut self, buf: &mut [u8]) -> Result<usize, ReplayError> {
+        if let Some((&error, rest)) = self.errors.split_first()
+            && error <= self.position
+        {
+            self.errors = rest;
+            return Err(ReplayError::Injected);
+        }
+
+        // Skip to the next chunk that has any bytes left
+        while self.current.is_empty() {
+            self.current = self.chunks.next().ok_or(ReplayError::Exhausted)?;
+        }
+
+        // Stop at the next injected error
+        let until_error = self.errors.first().map_or(usize::MAX, |&error| error - self.position);
+        let length = buf.len().min(self.current.len()).min(self.chunk_size).min(until_error);
+
+        let (bytes, rest) = self.current.split_at(length);
+        buf[..length].copy_from_slice(bytes);
+        self.current = rest;
+        self.position += length;
+        Ok(length)
+    }
+}
+
+impl<I> ErrorType for ReplayReader<'_, I> {
+    type Error = ReplayError;
+}
+
+impl<'a, I: Iterator<Item = &'a [u8]>> embedded_io::Read for ReplayReader<'a, I> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> { self.replay(buf) }
+}
+
+impl<'a, I: Iterator<Item = &'a [u8]>> embedded_io_async::Read for ReplayReader<'a, I> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let mut pending = self.pending;
+        poll_fn(|cx| {
+            if pending == 0 {
+                Poll::Ready(())
+            } else {
+                pending -= 1;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await;
+
+        self.replay(buf)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Run a future to completion, polling it until it is ready.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// An error returned by a [`ReplayReader`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReplayError {
+    /// An error injected with [`ReplayReader::with_errors`].
+    Injected,
+    /// Every byte has been replayed.
+    Exhausted,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Injected => f.write_str("injected read error"),
+            Self::Exhausted => f.write_str("every byte has been replayed"),
+        }
+    }
+}
+
+impl core::error::Error for ReplayError {}
+
+impl embedded_io::Error for ReplayError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Injected => ErrorKind::Other,
+            Self::Exhausted => ErrorKind::BrokenPipe,
+        }
+    }
+}
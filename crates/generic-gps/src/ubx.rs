@@ -0,0 +1,84 @@
+//! Framing u-blox UBX binary messages.
+//!
+//! A frame starts with two sync characters, followed by the message class,
+//! the message ID, the little-endian payload length, the payload and a
+//! two-byte Fletcher checksum over everything after the sync characters.
+
+use crate::nmea::BuildError;
+
+/// The sync characters that start every frame.
+pub const SYNC: [u8; 2] = [0xB5, 0x62];
+/// The number of bytes a frame adds around its payload.
+pub const OVERHEAD: usize = 8;
+
+/// The `ACK` message class.
+pub const CLASS_ACK: u8 = 0x05;
+/// The `ACK-NAK` message ID.
+pub const ID_ACK_NAK: u8 = 0x00;
+/// The `ACK-ACK` message ID.
+pub const ID_ACK_ACK: u8 = 0x01;
+/// The `CFG` message class.
+pub const CLASS_CFG: u8 = 0x06;
+/// The `CFG-CFG` message ID.
+pub const ID_CFG_CFG: u8 = 0x09;
+
+/// Calculate the 8-bit Fletcher checksum of the bytes between the sync
+/// characters and the checksum.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_generic_gps::ubx::checksum;
+///
+/// // `CFG-PRT` poll
+/// assert_eq!(checksum(&[0x06, 0x00, 0x00, 0x00]), [0x06, 0x18]);
+/// ```
+#[must_use]
+pub fn checksum(data: &[u8]) -> [u8; 2] {
+    data.iter().fold([0u8; 2], |[a, b], &byte| {
+        let a = a.wrapping_add(byte);
+        [a, b.wrapping_add(a)]
+    })
+}
+
+/// Write a frame into `buffer`.
+///
+/// Returns the part of the buffer that was written.
+///
+/// # Errors
+///
+/// Returns an error if the buffer is too small to hold the frame,
+/// or if the payload is longer than a frame can hold.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_generic_gps::ubx::{self, OVERHEAD};
+///
+/// let mut buffer = [0u8; 16];
+///
+/// // `ACK-ACK` for `CFG-CFG`
+/// let frame = ubx::write_frame(0x05, 0x01, &[0x06, 0x09], &mut buffer)?;
+/// assert_eq!(frame, [0xB5, 0x62, 0x05, 0x01, 0x02, 0x00, 0x06, 0x09, 0x17, 0x40]);
+/// assert_eq!(frame.len(), OVERHEAD + 2);
+///
+/// // Frames that do not fit are rejected
+/// assert!(ubx::write_frame(0x05, 0x01, &[0x06, 0x09], &mut buffer[..9]).is_err());
+/// # Ok::<(), ef_generic_gps::nmea::BuildError>(())
+/// ```
+pub fn write_frame<'b>(
+    class: u8,
+    id: u8,
+    payload: &[u8],
+    buffer: &'b mut [u8],
+) -> Result<&'b [u8], BuildError> {
+    let length = u16::try_from(payload.len()).map_err(|_| BuildError::BufferTooSmall)?;
+    let frame = buffer.get_mut(..payload.len() + OVERHEAD).ok_or(BuildError::BufferTooSmall)?;
+
+    let [lo, hi] = length.to_le_bytes();
+    frame[..6].copy_from_slice(&[SYNC[0], SYNC[1], class, id, lo, hi]);
+    frame[6..6 + payload.len()].copy_from_slice(payload);
+    let sum = checksum(&frame[2..6 + payload.len()]);
+    frame[6 + payload.len()..].copy_from_slice(&sum);
+    Ok(frame)
+}
@@ -0,0 +1,194 @@
+//! Tracking which sentence types are received and used.
+
+#[cfg(not(feature = "defmt"))]
+use bitflags::bitflags;
+#[cfg(feature = "defmt")]
+use defmt::bitflags;
+
+use crate::nmea::NmeaSentenceKind;
+
+/// A standard sentence type that GPS modules can be configured to send.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SentenceType {
+    /// Geographic position, latitude and longitude.
+    Gll,
+    /// Recommended minimum data.
+    Rmc,
+    /// Course and speed over ground.
+    Vtg,
+    /// Fix data.
+    Gga,
+    /// Dilution of precision and active satellites.
+    Gsa,
+    /// Satellites in view.
+    Gsv,
+    /// Date and time.
+    Zda,
+}
+
+impl SentenceType {
+    /// Every [`SentenceType`].
+    pub const ALL: [Self; 7] =
+        [Self::Gll, Self::Rmc, Self::Vtg, Self::Gga, Self::Gsa, Self::Gsv, Self::Zda];
+
+    /// Get the sentence identifier, without the talker ID.
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::Gll => "GLL",
+            Self::Rmc => "RMC",
+            Self::Vtg => "VTG",
+            Self::Gga => "GGA",
+            Self::Gsa => "GSA",
+            Self::Gsv => "GSV",
+            Self::Zda => "ZDA",
+        }
+    }
+
+    /// Get the [`OutputMask`] containing only this type.
+    #[inline]
+    #[must_use]
+    pub const fn mask(self) -> OutputMask { OutputMask::from_bits_truncate(1 << self as u8) }
+
+    /// Get the [`SentenceType`] of a raw message, from any talker.
+    ///
+    /// Returns `None` for proprietary sentences and unknown types.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_generic_gps::SentenceType;
+    ///
+    /// assert_eq!(SentenceType::from_message(b"$GNGSV,3,1,11,..."), Some(SentenceType::Gsv));
+    /// assert_eq!(SentenceType::from_message(b"$GPGGA,"), Some(SentenceType::Gga));
+    /// assert_eq!(SentenceType::from_message(b"$PUBX,00,..."), None);
+    /// assert_eq!(SentenceType::from_message(b"$GPTXT,01,01,02,ANTSTATUS=OK"), None);
+    /// assert_eq!(SentenceType::from_message(b"$GPGG"), None);
+    /// ```
+    #[must_use]
+    pub fn from_message(message: &[u8]) -> Option<Self> {
+        let [b'$', talker, _, id @ .., b','] = message.get(..7)? else { return None };
+        if *talker == b'P' {
+            return None;
+        }
+        Self::ALL.into_iter().find(|kind| kind.id().as_bytes() == id)
+    }
+
+    /// Get the [`SentenceType`] of a parsed sentence.
+    #[must_use]
+    pub const fn from_kind(kind: &NmeaSentenceKind) -> Option<Self> {
+        match kind {
+            NmeaSentenceKind::GGA(_) => Some(Self::Gga),
+            NmeaSentenceKind::RMC(_) => Some(Self::Rmc),
+            NmeaSentenceKind::GLL(_) => Some(Self::Gll),
+            NmeaSentenceKind::GNS(_) => None,
+        }
+    }
+}
+
+bitflags! {
+    /// A set of [`SentenceType`]s sent by a GPS module.
+    #[cfg_attr(not(feature = "defmt"), derive(Debug, Clone, Copy, PartialEq, Eq, Hash))]
+    pub struct OutputMask: u8 {
+        /// `GLL` sentences.
+        const GLL = 1 << SentenceType::Gll as u8;
+        /// `RMC` sentences.
+        const RMC = 1 << SentenceType::Rmc as u8;
+        /// `VTG` sentences.
+        const VTG = 1 << SentenceType::Vtg as u8;
+        /// `GGA` sentences.
+        const GGA = 1 << SentenceType::Gga as u8;
+        /// `GSA` sentences.
+        const GSA = 1 << SentenceType::Gsa as u8;
+        /// `GSV` sentences.
+        const GSV = 1 << SentenceType::Gsv as u8;
+        /// `ZDA` sentences.
+        const ZDA = 1 << SentenceType::Zda as u8;
+    }
+}
+
+impl OutputMask {
+    /// Returns `true` if the mask contains the [`SentenceType`].
+    #[inline]
+    #[must_use]
+    pub const fn contains_type(self, kind: SentenceType) -> bool { self.contains(kind.mask()) }
+}
+
+/// Which [`SentenceType`]s a GPS module sends,
+/// and which of them the application uses.
+///
+/// The driver records every received message, and marks sentences returned
+/// by the parsing methods as consumed. Sentences the parser does not
+/// support can only be read as raw messages, so the application marks them
+/// with `GenericGps::mark_consumed`.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_generic_gps::{OutputMask, SentenceType, SentenceUsage};
+///
+/// let mut usage = SentenceUsage::NONE;
+/// for kind in [SentenceType::Gga, SentenceType::Rmc, SentenceType::Gsv, SentenceType::Gsa] {
+///     usage.record_received(kind);
+/// }
+/// usage.mark_consumed(SentenceType::Gga);
+/// usage.mark_consumed(SentenceType::Rmc);
+///
+/// assert_eq!(usage.unused(), OutputMask::GSV | OutputMask::GSA);
+/// assert_eq!(usage.recommend_output_mask(), OutputMask::GGA | OutputMask::RMC);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SentenceUsage {
+    received: OutputMask,
+    consumed: OutputMask,
+}
+
+impl SentenceUsage {
+    /// Nothing received or consumed.
+    pub const NONE: Self = Self { received: OutputMask::empty(), consumed: OutputMask::empty() };
+
+    /// Record that a sentence was received.
+    #[inline]
+    pub const fn record_received(&mut self, kind: SentenceType) {
+        self.received = self.received.union(kind.mask());
+    }
+
+    /// Record that a sentence was used by the application.
+    #[inline]
+    pub const fn mark_consumed(&mut self, kind: SentenceType) {
+        self.consumed = self.consumed.union(kind.mask());
+    }
+
+    /// Get the sentence types received.
+    #[inline]
+    #[must_use]
+    pub const fn received(&self) -> OutputMask { self.received }
+
+    /// Get the sentence types used by the application.
+    #[inline]
+    #[must_use]
+    pub const fn consumed(&self) -> OutputMask { self.consumed }
+
+    /// Get the sentence types received, but never used.
+    #[inline]
+    #[must_use]
+    pub const fn unused(&self) -> OutputMask { self.received.difference(self.consumed) }
+
+    /// Get an [`OutputMask`] that only enables the sentence types used by
+    /// the application.
+    ///
+    /// Types that were not used yet are disabled, so only rely on the mask
+    /// once the application has run through every code path that reads
+    /// sentences. Without any consumed sentence the mask is empty, which
+    /// disables every sentence.
+    #[inline]
+    #[must_use]
+    pub const fn recommend_output_mask(&self) -> OutputMask { self.consumed }
+}
+
+impl Default for SentenceUsage {
+    fn default() -> Self { Self::NONE }
+}
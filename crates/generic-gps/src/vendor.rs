@@ -0,0 +1,292 @@
+//! Vendor-specific configuration commands and their acknowledgments.
+
+use crate::{
+    OutputMask, SentenceType,
+    nmea::{BuildError, SentenceBuilder, checksum},
+    ubx,
+};
+
+/// The number of bytes received without an acknowledgment before a
+/// command times out.
+///
+/// This is about two seconds of NMEA output at 9600 baud.
+pub const ACK_TIMEOUT_BYTES: usize = 2048;
+
+/// The manufacturer of a GPS module,
+/// which decides the configuration commands it understands.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Vendor {
+    /// A module that is only read from.
+    #[default]
+    Generic,
+    /// A MediaTek module, configured with `PMTK` sentences.
+    MediaTek,
+    /// A u-blox module, configured with UBX messages.
+    Ublox,
+}
+
+impl Vendor {
+    /// Get the commands that save the current settings,
+    /// or `None` if the vendor has no such command.
+    pub(crate) const fn persist_commands(self) -> Option<&'static [Command]> {
+        match self {
+            // MediaTek modules keep settings in battery-backed RAM,
+            // there is no generic command to write them to flash.
+            Vendor::Generic | Vendor::MediaTek => None,
+            Vendor::Ublox => Some(&[Command::Ubx { id: ubx::ID_CFG_CFG, payload: &UBX_SAVE }]),
+        }
+    }
+
+    /// Get the commands that restore the default settings,
+    /// or `None` if the vendor has no such command.
+    pub(crate) const fn restore_commands(self) -> Option<&'static [Command]> {
+        match self {
+            Vendor::Generic => None,
+            Vendor::MediaTek => Some(&[
+                // Default sentence output
+                Command::Mtk { id: "PMTK314", fields: &["-1"] },
+                // Default update rate of 1 Hz
+                Command::Mtk { id: "PMTK220", fields: &["1000"] },
+            ]),
+            Vendor::Ublox => Some(&[Command::Ubx { id: ubx::ID_CFG_CFG, payload: &UBX_RESTORE }]),
+        }
+    }
+
+    /// Get the commands that only enable the sentences in `mask`,
+    /// or `None` if the vendor has no such command.
+    pub(crate) fn output_mask_commands(
+        self,
+        mask: OutputMask,
+    ) -> Option<impl Iterator<Item = Command>> {
+        let (mtk, pubx): (_, &[SentenceType]) = match self {
+            Vendor::Generic => return None,
+            Vendor::MediaTek => (Some(Command::MtkOutput(mask)), &[]),
+            Vendor::Ublox => (None, &SentenceType::ALL),
+        };
+        let pubx = pubx
+            .iter()
+            .map(move |&kind| Command::PubxRate { kind, enabled: mask.contains_type(kind) });
+        Some(mtk.into_iter().chain(pubx))
+    }
+}
+
+/// Every configuration section known to `CFG-CFG`.
+const UBX_SECTIONS: [u8; 4] = [0xFF, 0xFF, 0x00, 0x00];
+/// Battery-backed RAM, flash, EEPROM and SPI flash.
+const UBX_DEVICES: u8 = 0b0001_0111;
+/// `CFG-CFG` saving every section to every device.
+const UBX_SAVE: [u8; 13] = cfg_cfg([0; 4], UBX_SECTIONS, [0; 4]);
+/// `CFG-CFG` clearing every section and loading the defaults.
+const UBX_RESTORE: [u8; 13] = cfg_cfg(UBX_SECTIONS, [0; 4], UBX_SECTIONS);
+
+/// The `PMTK314` field of each [`SentenceType`].
+///
+/// Fields 6 to 16 are reserved, field 18 is the MediaTek-specific `MCHN`.
+const MTK_OUTPUT_FIELD: [usize; SentenceType::ALL.len()] = [0, 1, 2, 3, 4, 5, 17];
+
+/// Create the 19 `PMTK314` fields sending the sentences in `mask` with every
+/// fix, and disabling every other sentence.
+const fn mtk_output_fields(mask: OutputMask) -> [&'static str; 19] {
+    let mut fields = ["0"; 19];
+    let mut index = 0;
+    while index < SentenceType::ALL.len() {
+        if mask.contains_type(SentenceType::ALL[index]) {
+            fields[MTK_OUTPUT_FIELD[index]] = "1";
+        }
+        index += 1;
+    }
+    fields
+}
+
+/// Create the `PUBX,40` fields setting the rate of a sentence on every port,
+/// sending it with every fix or not at all.
+///
+/// The rates are for the DDC (I2C), UART 1, UART 2, USB and SPI ports,
+/// followed by a reserved field.
+const fn pubx_rate_fields(kind: SentenceType, enabled: bool) -> [&'static str; 8] {
+    let rate = if enabled { "1" } else { "0" };
+    ["40", kind.id(), rate, rate, rate, rate, rate, "0"]
+}
+
+/// Create a `CFG-CFG` payload from its clear, save and load masks.
+const fn cfg_cfg(clear: [u8; 4], save: [u8; 4], load: [u8; 4]) -> [u8; 13] {
+    let mut payload = [UBX_DEVICES; 13];
+    let mut index = 0;
+    while index < 4 {
+        payload[index] = clear[index];
+        payload[4 + index] = save[index];
+        payload[8 + index] = load[index];
+        index += 1;
+    }
+    payload
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A configuration command, usually answered by an acknowledgment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Command {
+    /// A `PMTK` sentence, answered by `PMTK001`.
+    Mtk { id: &'static str, fields: &'static [&'static str] },
+    /// A `PMTK314` sentence only enabling the sentences in the mask,
+    /// answered by `PMTK001`.
+    MtkOutput(OutputMask),
+    /// A UBX `CFG` message, answered by `ACK-ACK` or `ACK-NAK`.
+    Ubx { id: u8, payload: &'static [u8] },
+    /// A `PUBX,40` sentence enabling or disabling a sentence,
+    /// which u-blox modules do not answer.
+    PubxRate { kind: SentenceType, enabled: bool },
+}
+
+/// The longest command, a `PMTK314` sentence setting every field.
+pub(crate) const COMMAND_BUFFER: usize = 51;
+
+impl Command {
+    /// Write the command into `buffer`.
+    pub(crate) fn write(self, buffer: &mut [u8; COMMAND_BUFFER]) -> Result<&[u8], BuildError> {
+        match self {
+            Command::Mtk { id, fields } => SentenceBuilder::proprietary(id, fields).write(buffer),
+            Command::Ubx { id, payload } => ubx::write_frame(ubx::CLASS_CFG, id, payload, buffer),
+            Command::MtkOutput(mask) => {
+                SentenceBuilder::proprietary("PMTK314", &mtk_output_fields(mask)).write(buffer)
+            }
+            Command::PubxRate { kind, enabled } => {
+                SentenceBuilder::proprietary("PUBX", &pubx_rate_fields(kind, enabled)).write(buffer)
+            }
+        }
+    }
+
+    /// Get the acknowledgment answering the command,
+    /// or `None` if it is not answered.
+    pub(crate) const fn expected(self) -> Option<Expected> {
+        match self {
+            Command::Mtk { id, .. } => Some(Expected::Mtk(id)),
+            Command::MtkOutput(_) => Some(Expected::Mtk("PMTK314")),
+            Command::Ubx { id, .. } => Some(Expected::Ubx(id)),
+            Command::PubxRate { .. } => None,
+        }
+    }
+}
+
+/// The acknowledgment answering a [`Command`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Expected {
+    /// `PMTK001` for a `PMTK` sentence.
+    Mtk(&'static str),
+    /// `ACK-ACK` or `ACK-NAK` for a UBX `CFG` message.
+    Ubx(u8),
+}
+
+/// The result of scanning received bytes for an acknowledgment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Ack {
+    /// The command was accepted.
+    Ack,
+    /// The command was rejected.
+    Nak,
+}
+
+/// Scans received bytes for the acknowledgment of a [`Command`],
+/// skipping any other sentences and messages.
+pub(crate) struct AckScanner {
+    expected: Expected,
+    /// The most recent bytes, or the current line of a `PMTK` sentence.
+    window: [u8; 24],
+    len: usize,
+}
+
+impl AckScanner {
+    /// The length of an `ACK-ACK` or `ACK-NAK` frame.
+    const UBX_ACK: usize = ubx::OVERHEAD + 2;
+
+    /// Create a scanner for an acknowledgment.
+    pub(crate) const fn new(expected: Expected) -> Self {
+        Self { expected, window: [0; 24], len: 0 }
+    }
+
+    /// Scan a received byte,
+    /// returning the acknowledgment once it is complete.
+    pub(crate) fn push(&mut self, byte: u8) -> Option<Ack> {
+        match self.expected {
+            Expected::Mtk(id) => self.push_line(id, byte),
+            Expected::Ubx(id) => self.push_frame(id, byte),
+        }
+    }
+
+    /// Scan a byte for `$PMTK001,<command>,<flag>*<checksum>`.
+    ///
+    /// A flag of `3` acknowledges the command, any other flag rejects it.
+    fn push_line(&mut self, id: &str, byte: u8) -> Option<Ack> {
+        match byte {
+            b'$' => self.len = 0,
+            b'\r' | b'\n' => {
+                let line = core::mem::take(&mut self.len);
+                let (body, sum) = self.window[..line].split_at(line.checked_sub(3)?);
+                let sum = core::str::from_utf8(sum).ok()?.strip_prefix('*')?;
+                if u8::from_str_radix(sum, 16).ok()? != checksum(body) {
+                    return None;
+                }
+
+                let fields = body.strip_prefix(b"PMTK001,")?;
+                let (command, flag) = fields.split_at(fields.len().checked_sub(2)?);
+                if command != id.strip_prefix("PMTK")?.as_bytes() || flag[0] != b',' {
+                    return None;
+                }
+                return Some(if flag[1] == b'3' { Ack::Ack } else { Ack::Nak });
+            }
+            _ if self.len < self.window.len() => {
+                self.window[self.len] = byte;
+                self.len += 1;
+            }
+            // Longer lines are never acknowledgments
+            _ => {}
+        }
+        None
+    }
+
+    /// Scan a byte for an `ACK-ACK` or `ACK-NAK` frame for `CFG-<id>`.
+    fn push_frame(&mut self, id: u8, byte: u8) -> Option<Ack> {
+        // Keep the most recent bytes of a frame
+        if self.len == Self::UBX_ACK {
+            self.window.copy_within(1..Self::UBX_ACK, 0);
+            self.len -= 1;
+        }
+        self.window[self.len] = byte;
+        self.len += 1;
+
+        let frame = &self.window[..self.len];
+        let [s0, s1, ubx::CLASS_ACK, kind, 2, 0, ubx::CLASS_CFG, acked, a, b] = *frame else {
+            return None;
+        };
+        if [s0, s1] != ubx::SYNC || acked != id || ubx::checksum(&frame[2..8]) != [a, b] {
+            return None;
+        }
+
+        self.len = 0;
+        match kind {
+            ubx::ID_ACK_ACK => Some(Ack::Ack),
+            ubx::ID_ACK_NAK => Some(Ack::Nak),
+            _ => None,
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// An error that can occur when configuring a GPS module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CommandError<Error> {
+    /// The [`Vendor`] has no command for the request.
+    Unsupported,
+    /// The module rejected the command.
+    Nak,
+    /// The module did not acknowledge the command within
+    /// [`ACK_TIMEOUT_BYTES`].
+    Timeout,
+    /// The command could not be built.
+    Build(BuildError),
+    /// An other error occurred.
+    Other(Error),
+}
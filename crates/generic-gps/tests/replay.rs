@@ -0,0 +1,162 @@
+//! Replay the logs in `tests/data` through the whole receive, parse and
+//! averaging pipeline, however the stream is split up.
+
+use ef_generic_gps::{
+    GenericGps,
+    mode::{Async, Blocking},
+    nmea::{
+        AveragerConfig, GpsFix, Latitude, Longitude, NmeaError, NmeaSentence, NmeaSentenceKind,
+        PositionAverager, parse_sentence,
+    },
+    replay::{ReplayError, ReplayReader, block_on},
+};
+
+/// A cold start, without a fix for three seconds.
+const COLD_START: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/cold_start.nmea"));
+/// A drive heading west.
+const DRIVING: &[u8] =
+    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/driving.nmea"));
+
+/// Positions in the stream at which a read fails, including the middle of
+/// the first sentence and a line ending.
+const ERRORS: &[usize] = &[0, 20, 41, 200];
+
+/// The result of replaying a log.
+#[derive(Debug, PartialEq)]
+struct Replay {
+    /// Every sentence received, in order.
+    sentences: Vec<NmeaSentence>,
+    /// The number of injected errors returned.
+    errors: usize,
+    /// The number of sentences of a type that is not parsed.
+    unknown: usize,
+}
+
+impl Replay {
+    /// Record the result of receiving a sentence.
+    ///
+    /// Returns `false` once the log is exhausted.
+    fn record(&mut self, result: Result<NmeaSentence, NmeaError<ReplayError>>) -> bool {
+        match result {
+            Ok(sentence) => self.sentences.push(sentence),
+            Err(NmeaError::Other(ReplayError::Injected)) => self.errors += 1,
+            Err(NmeaError::Other(ReplayError::Exhausted)) => return false,
+            Err(NmeaError::UnknownType) => self.unknown += 1,
+            Err(err) => panic!("Unexpected error: {err:?}"),
+        }
+        true
+    }
+
+    /// Average the positions of every `GGA` fix.
+    fn average(&self) -> PositionAverager {
+        let mut averager = PositionAverager::new(AveragerConfig::DEFAULT);
+        for fix in self.fixes() {
+            averager.add(fix);
+        }
+        averager
+    }
+
+    /// Get the fixes of every `GGA` sentence.
+    fn fixes(&self) -> impl Iterator<Item = &GpsFix> {
+        self.sentences.iter().filter_map(|sentence| match &sentence.kind {
+            NmeaSentenceKind::GGA(fix) => Some(fix),
+            _ => None,
+        })
+    }
+}
+
+/// Parse every line of a log on its own.
+fn expected(log: &[u8]) -> Replay {
+    let mut replay = Replay { sentences: Vec::new(), errors: 0, unknown: 0 };
+    for line in log.split_inclusive(|&byte| byte == b'\n') {
+        replay.record(parse_sentence(line));
+    }
+    replay
+}
+
+/// Replay a log through a blocking driver.
+fn replay_blocking(reader: ReplayReader<'_>) -> Replay {
+    let mut gps = GenericGps::<_, Blocking, 128>::new(reader);
+    let mut replay = Replay { sentences: Vec::new(), errors: 0, unknown: 0 };
+    while replay.record(gps.receive_sentence()) {}
+    replay
+}
+
+/// Replay a log through an async driver, polling until every read completes.
+fn replay_async(reader: ReplayReader<'_>) -> Replay {
+    let mut gps = GenericGps::<_, Async, 128>::new(reader);
+    let mut replay = Replay { sentences: Vec::new(), errors: 0, unknown: 0 };
+    while replay.record(block_on(gps.receive_sentence())) {}
+    replay
+}
+
+#[test]
+fn replays_every_chunk_size() {
+    for log in [COLD_START, DRIVING] {
+        let expected = expected(log);
+        for chunk_size in 1..=log.len() {
+            let reader = || ReplayReader::new(log).with_chunk_size(chunk_size);
+            assert_eq!(replay_blocking(reader()), expected, "{chunk_size} bytes per read");
+            assert_eq!(
+                replay_async(reader().with_pending(1)),
+                expected,
+                "{chunk_size} bytes per read"
+            );
+        }
+    }
+}
+
+#[test]
+fn replays_through_errors() {
+    for log in [COLD_START, DRIVING] {
+        let expected = Replay { errors: ERRORS.len(), ..expected(log) };
+        for chunk_size in [1, 7, 64, usize::MAX] {
+            let reader = || ReplayReader::new(log).with_chunk_size(chunk_size).with_errors(ERRORS);
+            assert_eq!(replay_blocking(reader()), expected, "{chunk_size} bytes per read");
+            assert_eq!(
+                replay_async(reader().with_pending(2)),
+                expected,
+                "{chunk_size} bytes per read"
+            );
+        }
+    }
+}
+
+#[test]
+fn averages_the_cold_start() {
+    let replay = replay_async(ReplayReader::new(COLD_START).with_chunk_size(5).with_pending(3));
+    assert_eq!(replay.unknown, 6);
+
+    // The first three seconds have no position
+    let result = replay.average().result().unwrap();
+    assert_eq!((result.accepted, result.rejected), (3, 3));
+
+    // Every fix is at the same position
+    let Latitude::North(latitude) = result.latitude else { panic!("Expected a northern latitude") };
+    let Longitude::East(longitude) = result.longitude else {
+        panic!("Expected an eastern longitude")
+    };
+    assert!((latitude - (52.0 + 30.1124 / 60.0)).abs() < 1e-9);
+    assert!((longitude - (13.0 + 23.4480 / 60.0)).abs() < 1e-9);
+}
+
+#[test]
+fn averages_the_drive() {
+    let replay = replay_blocking(ReplayReader::new(DRIVING).with_chunk_size(3));
+    let fixes: Vec<_> = replay.fixes().collect();
+    assert_eq!(fixes.len(), 5);
+
+    // The mean lies on the road driven, between the first and last fix
+    let result = replay.average().result().unwrap();
+    assert_eq!((result.accepted, result.rejected), (5, 0));
+    let west = |fix: &GpsFix| match fix.longitude {
+        Some(Longitude::West(longitude)) => longitude,
+        other => panic!("Expected a western longitude, not {other:?}"),
+    };
+    let Longitude::West(longitude) = result.longitude else {
+        panic!("Expected a western longitude")
+    };
+    assert!(west(fixes[0]) < longitude && longitude < west(fixes[4]));
+    assert_eq!(result.latitude, fixes[0].latitude.unwrap());
+}
@@ -1,73 +1,364 @@
-use core::marker::PhantomData;
-
-use embedded_hal_async::i2c::I2c;
+use ef_driver_common::identify::{AsyncIdentify, DeviceIdentity};
+use embedded_hal::digital::{Error, ErrorKind, OutputPin};
+use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
 
 use crate::{
-    DetectedGesture, DetectedTouch, GT911, GT911Error, GT911Mode, Gesture, GesturePoint, Touch,
-    TouchPoint, register,
+    ClearPolicy, DetectedGesture, DetectedTouch, DeviceInfo, GT911, GT911Address, GT911Config,
+    GT911Error, GT911Mode, Gesture, GesturePoint, GestureTrack, InterruptTrigger, NoiseProfile,
+    OrientedGesture, Touch, TouchFrame, TouchKeys, TouchPoint, TouchReport,
+    config::ConfigPatch,
+    register,
+    reset::{self, ResetStep},
 };
 
-/// A simple macro to enter and exit command mode around a code block.
-macro_rules! command_mode {
-    ($driver:expr, $mode:ty, $block:block) => {
-        command_mode!($driver, $mode, 0, $block)
-    };
-    ($driver:expr, $mode:ty, $code:expr, $block:block) => {{
-        // Enter command mode
-        if $code > 7 {
-            $driver.write_register_async(register::GT911_COMMAND_CHECK, $code).await?;
+impl<I2C: I2c, MODE: GT911Mode, const MAX: usize> GT911<I2C, MODE, MAX> {
+    /// Query the device's [`DeviceInfo`] in a single read.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the identification and resolution registers are only
+    /// read, and the driver keeps no state from them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any I2C operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error};
+    /// use embedded_hal_async::i2c::ErrorKind;
+    /// # use ef_gt911::test_util::{Registers, block_on};
+    ///
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x140..0x14B].copy_from_slice(b"911\0\x60\x10\xE0\x01\x20\x03\x02");
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&registers), 0x5D);
+    ///
+    /// // The same bytes as the blocking driver
+    /// let info = block_on(touch.query_device_info_async())?;
+    /// assert_eq!((info.product_id, info.firmware_version), (*b"911\0", 0x1060));
+    /// assert_eq!((info.x_resolution, info.y_resolution), (480, 800));
+    /// assert_eq!(touch.i2c().writes, [vec![0x81, 0x40]]);
+    ///
+    /// // Reading the status leaves it set
+    /// touch.i2c_mut().registers[0x14E] = 0x81;
+    /// touch.i2c_mut().writes.clear();
+    /// assert_eq!(block_on(touch.query_touch_status_async())?.bits(), 0x81);
+    /// assert_eq!(touch.i2c().writes, [vec![0x81, 0x4E]]);
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    pub async fn query_device_info_async(&mut self) -> Result<DeviceInfo, GT911Error<I2C::Error>> {
+        let mut buf = [0u8; DeviceInfo::LEN];
+        self.read_register_async(register::GT911_PRODUCT_ID1, &mut buf).await?;
+        Ok(DeviceInfo::from_bytes(buf))
+    }
+
+    /// Query the device's product ID and firmware version.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the product ID and firmware version are read again
+    /// by the next call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any I2C operation fails.
+    #[deprecated = "use `GT911::query_device_info_async` instead"]
+    pub async fn device_info_async(&mut self) -> Result<([u8; 4], u16), GT911Error<I2C::Error>> {
+        let info = self.query_device_info_async().await?;
+        Ok((info.product_id, info.firmware_version))
+    }
+
+    /// Clear the status register, letting the device report the next frame
+    /// or gesture.
+    ///
+    /// See [`GT911::clear_status`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the status is either cleared or left set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C operation fails.
+    #[inline]
+    pub async fn clear_status_async(&mut self) -> Result<(), GT911Error<I2C::Error>> {
+        self.write_register_async(MODE::CLEAR_REGISTER, 0).await
+    }
+
+    /// Detect the longest read the I2C bus supports,
+    /// and use it as the maximum transaction length.
+    ///
+    /// See [`GT911::detect_max_transaction`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: only configuration registers are read,
+    /// and the maximum is only changed once detected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a single byte cannot be read.
+    pub async fn detect_max_transaction_async(&mut self) -> Result<usize, GT911Error<I2C::Error>> {
+        let mut buf = [0u8; crate::CONFIG_LEN];
+        let register = register::register_bytes(register::GT911_CONFIG_VERSION);
+
+        // A single byte must always be readable
+        let result = self.i2c.write_read(self.address, &register, &mut buf[..1]).await;
+        result.map_err(GT911Error::I2C)?;
+        if self.i2c.write_read(self.address, &register, &mut buf).await.is_ok() {
+            self.max_transaction = usize::MAX;
+            return Ok(self.max_transaction);
         }
-        $driver.write_register_async(register::GT911_COMMAND, $code).await?;
 
-        // Create a closure and execute the block (preventing early returns)
-        let mut closure = async || $block;
-        let result = (closure)().await;
+        // Find the longest read that succeeds
+        let (mut good, mut bad) = (1, crate::CONFIG_LEN);
+        while bad - good > 1 {
+            let len = good + (bad - good) / 2;
+            if self.i2c.write_read(self.address, &register, &mut buf[..len]).await.is_ok() {
+                good = len;
+            } else {
+                bad = len;
+            }
+        }
+        self.max_transaction = good;
+        Ok(good)
+    }
 
-        // Exit command mode
-        $driver.write_register_async(<$mode>::CLEAR_REGISTER, 0).await?;
+    /// Read the configuration registers.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: only configuration registers are read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored checksum is invalid,
+    /// or if any I2C operation fails.
+    pub async fn read_config_async(&mut self) -> Result<GT911Config, GT911Error<I2C::Error>> {
+        let mut image = [0u8; GT911Config::LEN];
+        self.read_register_async(register::GT911_CONFIG_VERSION, &mut image).await?;
 
-        // Return the result
-        result
-    }};
-}
+        let config = GT911Config::from_bytes(image);
+        let (stored, computed) = (config.stored_checksum(), config.checksum());
+        if stored == computed {
+            Ok(config)
+        } else {
+            Err(GT911Error::ConfigChecksum { stored, computed })
+        }
+    }
 
-impl<I2C: I2c, MODE: GT911Mode> GT911<I2C, MODE> {
-    /// Query the device's product ID and firmware version.
+    /// Write the configuration registers, with a new checksum and the fresh
+    /// flag set, then read back the checksum to verify it was taken.
+    ///
+    /// The device ignores a configuration with a version below its own,
+    /// see [`GT911Config::version`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the configuration is written in a single
+    /// transaction, so it can be written again.
     ///
     /// # Errors
     ///
-    /// Returns an error if any I2C operation fails.
-    pub async fn device_info_async(&mut self) -> Result<([u8; 4], u16), GT911Error<I2C::Error>> {
-        command_mode!(self, MODE, { self.device_info_async_cmd().await })
+    /// Returns an error if the checksum read back does not match,
+    /// or if any I2C operation fails.
+    pub async fn write_config_async(
+        &mut self,
+        config: &GT911Config,
+    ) -> Result<(), GT911Error<I2C::Error>> {
+        let computed = config.checksum();
+        let mut data = [0u8; GT911Config::LEN + 1];
+        data[..GT911Config::LEN].copy_from_slice(config.as_bytes());
+        data[GT911Config::LEN - 1] = computed;
+        data[GT911Config::LEN] = 1;
+        self.write_registers_async(register::GT911_CONFIG_VERSION, &data).await?;
+
+        let mut stored = [0u8; 1];
+        self.read_register_async(register::GT911_CONFIG_CHECKSUM, &mut stored).await?;
+        if stored[0] == computed {
+            Ok(())
+        } else {
+            Err(GT911Error::ConfigChecksum { stored: stored[0], computed })
+        }
     }
 
-    /// Query the device's product ID and firmware version.
+    /// Apply a [`NoiseProfile`] to the device configuration.
+    ///
+    /// See [`GT911::apply_noise_profile`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: the noise registers may be written without the new
+    /// checksum, and the device keeps its previous configuration until the
+    /// next call completes.
+    ///
+    /// # Errors
     ///
-    /// Requires the outer function to be in command mode.
-    async fn device_info_async_cmd(&mut self) -> Result<([u8; 4], u16), GT911Error<I2C::Error>> {
-        // Query the product ID
-        let mut id = [0u8; 4];
-        self.read_register_async(register::GT911_PRODUCT_ID1, &mut id).await?;
-        // Query the firmware version
-        let mut ver = [0u8; 2];
-        self.read_register_async(register::GT911_FIRMWARE_VER_LSB, &mut ver).await?;
-        Ok((id, u16::from_le_bytes(ver)))
+    /// Returns an error if the stored checksum is invalid,
+    /// or if any I2C operation fails.
+    pub async fn apply_noise_profile_async(
+        &mut self,
+        profile: &NoiseProfile,
+    ) -> Result<bool, GT911Error<I2C::Error>> {
+        self.patch_config_async(profile.settings().patches()).await
+    }
+
+    /// Apply [`ConfigPatch`]es to the device configuration asynchronously,
+    /// writing only the patches that change it.
+    ///
+    /// Returns `true` if the configuration was changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored checksum is invalid,
+    /// or if any I2C operation fails.
+    async fn patch_config_async(
+        &mut self,
+        patches: impl IntoIterator<Item = ConfigPatch>,
+    ) -> Result<bool, GT911Error<I2C::Error>> {
+        let mut config = self.read_config_async().await?;
+
+        let mut changed = false;
+        for patch in patches {
+            if patch.apply(config.as_bytes_mut()) {
+                self.write_registers_async(patch.register(), patch.data()).await?;
+                changed = true;
+            }
+        }
+
+        if changed {
+            // Write the new checksum and mark the configuration as updated
+            let checksum = config.checksum();
+            self.write_registers_async(register::GT911_CONFIG_CHECKSUM, &[checksum, 1]).await?;
+        }
+        Ok(changed)
+    }
+
+    /// Put the device to sleep.
+    ///
+    /// See [`GT911::enter_sleep`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the driver considers the device asleep before the
+    /// command is sent, so whether or not the command reached the device,
+    /// reads are refused with [`GT911Error::Asleep`] until it is woken.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::{
+    ///     pin::pin,
+    ///     task::{Context, Waker},
+    /// };
+    ///
+    /// use ef_gt911::{GT911, GT911Error};
+    /// use embedded_hal::digital::{ErrorType, OutputPin};
+    /// use embedded_hal_async::{delay::DelayNs, i2c::ErrorKind};
+    /// # use ef_gt911::test_util::{Registers, block_on};
+    ///
+    /// /// A pin and delay that do nothing.
+    /// struct Noop;
+    ///
+    /// impl ErrorType for Noop {
+    ///     type Error = core::convert::Infallible;
+    /// }
+    ///
+    /// impl OutputPin for Noop {
+    ///     fn set_low(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    ///
+    ///     fn set_high(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// }
+    ///
+    /// impl DelayNs for Noop {
+    ///     async fn delay_ns(&mut self, _: u32) {}
+    /// }
+    ///
+    /// // Cancel the command at every await point
+    /// for polls in 1.. {
+    ///     let mut touch: GT911<_> = GT911::new(Registers::new(&[0u8; 0x200]).with_pending(), 0x5D);
+    ///
+    ///     let completed = {
+    ///         let mut cx = Context::from_waker(Waker::noop());
+    ///         let mut future = pin!(touch.enter_sleep_async());
+    ///         (0..polls).any(|_| future.as_mut().poll(&mut cx).is_ready())
+    ///     };
+    ///
+    ///     // Reads are refused until the device is woken
+    ///     assert!(touch.is_asleep());
+    ///     assert_eq!(block_on(touch.query_touch_status_async()), Err(GT911Error::Asleep));
+    ///     block_on(touch.wake_async(&mut Noop, &mut Noop))?;
+    ///     assert!(block_on(touch.query_touch_status_async()).is_ok());
+    ///
+    ///     if completed {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    pub async fn enter_sleep_async(&mut self) -> Result<(), GT911Error<I2C::Error>> {
+        // Consider the device asleep before the command may reach it
+        self.asleep = true;
+        let result = self.write_register_async(register::GT911_COMMAND, reset::SLEEP_COMMAND).await;
+        self.asleep = result.is_ok();
+        result
+    }
+
+    /// Wake the device from sleep by pulsing its INT pin high.
+    ///
+    /// See [`GT911::wake`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the driver considers the device asleep until a
+    /// call completes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if driving the pin fails.
+    pub async fn wake_async<INT: OutputPin, D: DelayNs>(
+        &mut self,
+        int: &mut INT,
+        delay: &mut D,
+    ) -> Result<(), GT911Error<I2C::Error>> {
+        int.set_high().map_err(|err| GT911Error::Pin(err.kind()))?;
+        delay.delay_us(reset::WAKE_PULSE_US).await;
+        int.set_low().map_err(|err| GT911Error::Pin(err.kind()))?;
+        delay.delay_us(reset::WAKE_SETTLE_US).await;
+        self.asleep = false;
+        Ok(())
     }
 
     /// Read from a register asynchronously.
     ///
+    /// Reads longer than the maximum transaction length are split.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the read operation fails.
+    /// Returns an error if the device is asleep or must be reset,
+    /// or if the read operation fails.
     async fn read_register_async(
         &mut self,
         register: u16,
         buf: &mut [u8],
     ) -> Result<(), GT911Error<I2C::Error>> {
-        self.i2c
-            .write_read(self.address, &register.to_be_bytes(), buf)
-            .await
-            .map_err(GT911Error::I2C)
+        if self.needs_reset {
+            return Err(GT911Error::NeedsReset);
+        } else if self.asleep {
+            return Err(GT911Error::Asleep);
+        }
+        for (register, chunk) in crate::transactions(register, buf, self.max_transaction) {
+            self.i2c
+                .write_read(self.address, &register::register_bytes(register), chunk)
+                .await
+                .map_err(GT911Error::I2C)?;
+        }
+        Ok(())
     }
 
     /// Write to a register asynchronously.
@@ -80,50 +371,136 @@ impl<I2C: I2c, MODE: GT911Mode> GT911<I2C, MODE> {
         register: u16,
         data: u8,
     ) -> Result<(), GT911Error<I2C::Error>> {
-        let buf = [register.to_be_bytes()[0], register.to_be_bytes()[1], data];
+        let [hi, lo] = register::register_bytes(register);
+        let buf = [hi, lo, data];
         self.i2c.write(self.address, &buf).await.map_err(GT911Error::I2C)
     }
+
+    /// Write to consecutive registers in a single transaction asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write operation fails.
+    async fn write_registers_async(
+        &mut self,
+        register: u16,
+        data: &[u8],
+    ) -> Result<(), GT911Error<I2C::Error>> {
+        let mut buf = [0u8; 2 + GT911Config::LEN + 1];
+        buf[..2].copy_from_slice(&register::register_bytes(register));
+        buf[2..2 + data.len()].copy_from_slice(data);
+        self.i2c.write(self.address, &buf[..2 + data.len()]).await.map_err(GT911Error::I2C)
+    }
 }
 
-impl<I2C: I2c> GT911<I2C, Touch> {
+impl<I2C: I2c, const MAX: usize> GT911<I2C, Touch, MAX> {
     /// Initialize the GT911 device.
     ///
+    /// Returns the [`DeviceInfo`], including the configured resolution
+    /// used to scale coordinates.
+    ///
+    /// The product ID must be one of the variants set with
+    /// [`GT911::with_variants`], and the detected variant is kept.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the detected variant is only kept once the product ID
+    /// was read, and the status is never cleared.
+    ///
     /// # Errors
     ///
     /// Returns an error if the device is not ready, if the product ID does not
     /// match, or if any I2C operation fails.
-    pub async fn init_async(&mut self) -> Result<(), GT911Error<I2C::Error>> {
-        command_mode!(self, Touch, {
-            let status = self.status_async_cmd().await?;
-            if !status.is_ready() && status.bits() != 0 {
-                // Return that the device is not ready
-                // NOTE: A `0` most likely indicates the status was written to before.
-                return Err(GT911Error::DeviceNotReady(status));
-            }
+    pub async fn init_async(&mut self) -> Result<DeviceInfo, GT911Error<I2C::Error>> {
+        let status = self.query_touch_status_async().await?;
+        if !status.is_ready() && status.bits() != 0 {
+            // Return that the device is not ready
+            // NOTE: A `0` most likely indicates the status was written to before.
+            return Err(GT911Error::DeviceNotReady(status));
+        }
 
-            let (id, version) = self.device_info_async_cmd().await?;
-            if id == [b'9', b'1', b'1', b'\0'] {
-                Ok(())
-            } else {
-                // Return that the product ID does not match
-                Err(GT911Error::ProductIdMismatch(id, version))
+        let info = self.query_device_info_async().await?;
+        self.accept_variant(&info)?;
+        Ok(info)
+    }
+
+    /// Wait up to `timeout_ms` milliseconds for the device to become ready,
+    /// then initialize it.
+    ///
+    /// See [`GT911::wait_ready`] and [`GT911::init`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the wait only polls the status, and the variant is only
+    /// kept by the final read.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GT911Error::Timeout`] if the device does not become ready in
+    /// time, an error if the product ID does not match, or if any I2C
+    /// operation fails.
+    pub async fn init_with_timeout_async<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+        timeout_ms: u32,
+    ) -> Result<DeviceInfo, GT911Error<I2C::Error>> {
+        self.wait_ready_async(delay, timeout_ms).await?;
+        self.init_async().await
+    }
+
+    /// Poll the status register until the device is ready,
+    /// for up to `timeout_ms` milliseconds.
+    ///
+    /// See [`GT911::wait_ready`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the wait only polls the status, which a new call polls
+    /// again from the start of its timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GT911Error::Timeout`] if the device does not become ready in
+    /// time, or an error if any I2C operation fails.
+    pub async fn wait_ready_async<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+        timeout_ms: u32,
+    ) -> Result<DetectedTouch, GT911Error<I2C::Error>> {
+        let mut waits = reset::ready_backoff(timeout_ms);
+        loop {
+            let status = self.query_touch_status_async().await?;
+            if status.is_ready() {
+                return Ok(status);
             }
-        })
+            delay.delay_ms(waits.next().ok_or(GT911Error::Timeout)?).await;
+        }
     }
 
     /// Query the device's touch status.
     ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the status is read without clearing it.
+    ///
     /// # Errors
     ///
     /// Returns an error if any I2C operation fails.
     pub async fn query_touch_status_async(
         &mut self,
     ) -> Result<DetectedTouch, GT911Error<I2C::Error>> {
-        command_mode!(self, Touch, { self.status_async_cmd().await })
+        // Query the status register
+        let mut status = [0u8; 1];
+        self.read_register_async(register::GT911_STATUS, &mut status).await?;
+        Ok(DetectedTouch::from_bits_truncate(status[0]))
     }
 
     /// Query the number of active touch points.
     ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the count comes from a status read that does not clear it.
+    ///
     /// # Errors
     ///
     /// Returns an error if any I2C operation fails.
@@ -136,194 +513,490 @@ impl<I2C: I2c> GT911<I2C, Touch> {
     ///
     /// Returns `None` if there is no data ready for the point.
     ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the point is read without clearing the status,
+    /// so it is still reported by the next call.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the point index is invalid (>4),
-    /// or if any I2C operation fails.
+    /// Returns an error if the point index is invalid (>= `MAX`, or not
+    /// currently active), or if any I2C operation fails.
     pub async fn query_touch_async(
         &mut self,
         index: u8,
     ) -> Result<Option<TouchPoint>, GT911Error<I2C::Error>> {
-        command_mode!(self, Touch, { self.touch_async_cmd(index).await })
+        let status = self.query_touch_status_async().await?;
+        if !status.is_ready() {
+            // If the device is not ready, return `None`
+            return Ok(None);
+        } else if usize::from(index) >= self.max_points() || index >= status.touch_count() {
+            // If the index is higher than the number of points, return an error
+            return Err(GT911Error::InvalidPoint(index));
+        }
+
+        // Query the touch point register
+        let mut buf = [0u8; 7];
+        self.read_register_async(crate::touch_register(index), &mut buf).await?;
+        Ok(Some(self.transform_touch(TouchPoint::from_bytes(buf))))
     }
 
     /// Query all active touch points.
     ///
+    /// At most `MAX` touch points are read in a single burst,
+    /// any further points are ignored. The status is cleared after the
+    /// points are read, following the [`ClearPolicy`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the status is only cleared after the points are read,
+    /// and a frame that was not cleared is read again by the next call.
+    ///
     /// # Errors
     ///
     /// Returns an error if any I2C operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::{
+    ///     pin::pin,
+    ///     task::{Context, Waker},
+    /// };
+    ///
+    /// use ef_gt911::{GT911, GT911Error};
+    /// use embedded_hal_async::i2c::ErrorKind;
+    /// # use ef_gt911::test_util::{Registers, block_on};
+    ///
+    /// // Cancel the query at every await point
+    /// for polls in 0.. {
+    ///     // A single touch point is ready
+    ///     let mut registers = [0u8; 0x200];
+    ///     registers[0x14E] = 0x81;
+    ///     let mut touch: GT911<_> = GT911::new(Registers::new(&registers).with_pending(), 0x5D);
+    ///
+    ///     let completed = {
+    ///         let mut cx = Context::from_waker(Waker::noop());
+    ///         let mut future = pin!(touch.query_touch_all_async());
+    ///         (0..polls).any(|_| future.as_mut().poll(&mut cx).is_ready())
+    ///     };
+    ///
+    ///     // The next query still reports the point, and no command is sent
+    ///     let report = block_on(touch.query_touch_all_async())?;
+    ///     assert_eq!(report.len(), usize::from(!completed));
+    ///     assert!(touch.i2c().register_writes().iter().all(|(register, _)| *register != 0x8040));
+    ///
+    ///     if completed {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
     pub async fn query_touch_all_async(
         &mut self,
-    ) -> Result<[Option<TouchPoint>; 5], GT911Error<I2C::Error>> {
-        command_mode!(self, Touch, {
-            let status = self.status_async_cmd().await?;
-            let mut points = [None, None, None, None, None];
-            if status.is_ready() {
-                for i in 0..status.touch_count() {
-                    // Determine the register for the touch point
-                    let register = match i {
-                        0 => register::GT911_TOUCH1_TRACK_ID,
-                        1 => register::GT911_TOUCH2_TRACK_ID,
-                        2 => register::GT911_TOUCH3_TRACK_ID,
-                        3 => register::GT911_TOUCH4_TRACK_ID,
-                        4 => register::GT911_TOUCH5_TRACK_ID,
-                        // Maximum 5 touch points (0-4)
-                        #[cfg(feature = "defmt")]
-                        _ => defmt::unreachable!("Point index out of range"),
-                        #[cfg(not(feature = "defmt"))]
-                        _ => unreachable!("Point index out of range"),
-                    };
-
-                    // Query the touch point register
-                    let mut buf = [0u8; 7];
-                    self.read_register_async(register, &mut buf).await?;
-                    points[i as usize] = Some(TouchPoint::from_bytes(buf));
-                }
-            }
-            Ok(points)
-        })
+    ) -> Result<TouchReport<MAX>, GT911Error<I2C::Error>> {
+        let status = self.query_touch_status_async().await?;
+        if !status.is_ready() {
+            return Ok(TouchReport::EMPTY);
+        }
+
+        // Query all touch points that fit in the report
+        let mut buf = [0u8; crate::MAX_TOUCH_POINTS * crate::TOUCH_POINT_STRIDE];
+        let buf = &mut buf[..TouchReport::<MAX>::burst_len(status.touch_count())];
+        if !buf.is_empty() {
+            self.read_register_async(register::GT911_TOUCH1_TRACK_ID, buf).await?;
+        }
+
+        if self.clear_policy == ClearPolicy::AfterRead {
+            self.clear_status_async().await?;
+        }
+        Ok(TouchReport::from_burst(buf, |point| self.transform_touch(point)))
     }
 
-    /// Reset the device.
+    /// Reset the device using its RST and INT pins,
+    /// selecting the I2C address it answers to.
+    ///
+    /// See [`GT911::device_reset`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-resumable: the device may be held in reset, and the driver
+    /// keeps its previous address until the next call completes.
     ///
     /// # Errors
     ///
-    /// Returns an error if any I2C operation fails.
-    #[expect(clippy::unused_async, reason = "WIP")]
-    pub async fn device_reset_async(&mut self) -> Result<(), GT911Error<I2C::Error>> { todo!() }
+    /// Returns an error if driving either pin fails.
+    pub async fn device_reset_async<RST: OutputPin, INT: OutputPin, D: DelayNs>(
+        &mut self,
+        rst: &mut RST,
+        int: &mut INT,
+        delay: &mut D,
+        address: GT911Address,
+    ) -> Result<(), GT911Error<I2C::Error>> {
+        reset(rst, int, delay, address).await.map_err(GT911Error::Pin)?;
+        self.address = address.address();
+        self.asleep = false;
+        self.needs_reset = false;
+        Ok(())
+    }
 
-    /// Enter gesture mode.
+    /// Query the touch status and all touch points in a single read,
+    /// then clear the status.
+    ///
+    /// Unlike [`GT911::query_touch_all`], every point comes from the same
+    /// frame, at the cost of always reading `MAX` point slots. The status is
+    /// only cleared if the frame is ready, following the [`ClearPolicy`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the status is only cleared after the frame is read,
+    /// and a frame that was not cleared is read again by the next call.
     ///
     /// # Errors
     ///
     /// Returns an error if any I2C operation fails.
-    pub async fn gesture_mode_async(
-        mut self,
-    ) -> Result<GT911<I2C, Gesture>, (Self, GT911Error<I2C::Error>)> {
-        if let Err(err) = self.write_register_async(register::GT911_COMMAND_CHECK, 0x8).await {
-            return Err((self, err));
+    pub async fn query_touch_frame_async(&mut self) -> Result<TouchFrame, GT911Error<I2C::Error>> {
+        let mut buf = [0u8; TouchFrame::burst_len(crate::MAX_TOUCH_POINTS)];
+        let buf = &mut buf[..TouchFrame::burst_len(MAX)];
+        self.read_register_async(register::GT911_STATUS, buf).await?;
+
+        let frame = TouchFrame::from_burst(buf, |point| self.transform_touch(point));
+        if frame.status.is_ready() && self.clear_policy == ClearPolicy::AfterRead {
+            self.clear_status_async().await?;
         }
-        if let Err(err) = self.write_register_async(register::GT911_COMMAND, 0x8).await {
-            return Err((self, err));
+        Ok(frame)
+    }
+
+    /// Query which touch keys are pressed.
+    ///
+    /// See [`GT911::query_touch_keys`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the key state is read without clearing the status.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any I2C operation fails.
+    pub async fn query_touch_keys_async(&mut self) -> Result<TouchKeys, GT911Error<I2C::Error>> {
+        let status = self.query_touch_status_async().await?;
+        if !status.is_ready() || !status.has_touch_key() {
+            return Ok(TouchKeys::empty());
         }
 
-        let mut gesture: GT911<I2C, Gesture> =
-            GT911 { i2c: self.i2c, address: self.address, _mode: PhantomData };
+        let mut keys = [0u8; 1];
+        self.read_register_async(register::GT911_KEY_VALUE, &mut keys).await?;
+        Ok(TouchKeys::from_bits_truncate(keys[0]))
+    }
 
-        // Use `init` to verify the mode switch
-        match gesture.init_async().await {
-            Ok(()) => Ok(gesture),
-            Err(err) => {
-                Err((GT911 { i2c: gesture.i2c, address: gesture.address, _mode: PhantomData }, err))
+    /// Wait for the INT pin to signal new touch data, then read the
+    /// [`TouchFrame`] and clear the status.
+    ///
+    /// `trigger` must match the [`GT911Config::interrupt_trigger`] of the
+    /// device. Frames that are not ready are discarded and the pin is waited
+    /// on again.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: cancelling while waiting has no effect, and the frame is
+    /// read as in [`GT911::query_touch_frame_async`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if waiting on the pin or any I2C operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error, InterruptTrigger};
+    /// use embedded_hal::digital::{ErrorKind, ErrorType};
+    /// use embedded_hal_async::{digital::Wait, i2c};
+    /// # use ef_gt911::test_util::{Registers, block_on};
+    ///
+    /// /// An INT pin that records the edge it was waited on.
+    /// #[derive(Default)]
+    /// struct Int(Vec<&'static str>);
+    ///
+    /// impl ErrorType for Int {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl Int {
+    ///     fn record(&mut self, edge: &'static str) -> Result<(), ErrorKind> {
+    ///         self.0.push(edge);
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// impl Wait for Int {
+    ///     async fn wait_for_high(&mut self) -> Result<(), ErrorKind> { self.record("high") }
+    ///
+    ///     async fn wait_for_low(&mut self) -> Result<(), ErrorKind> { self.record("low") }
+    ///
+    ///     async fn wait_for_rising_edge(&mut self) -> Result<(), ErrorKind> { self.record("rising") }
+    ///
+    ///     async fn wait_for_falling_edge(&mut self) -> Result<(), ErrorKind> {
+    ///         self.record("falling")
+    ///     }
+    ///
+    ///     async fn wait_for_any_edge(&mut self) -> Result<(), ErrorKind> { unreachable!() }
+    /// }
+    ///
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x14E] = 0x81;
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&registers), 0x5D);
+    ///
+    /// let mut int = Int::default();
+    /// let frame = block_on(touch.wait_for_touch_async(&mut int, InterruptTrigger::Rising))?;
+    /// assert_eq!((frame.len(), touch.i2c().registers[0x14E]), (1, 0x00));
+    /// assert_eq!(int.0, ["rising"]);
+    /// # Ok::<(), GT911Error<i2c::ErrorKind>>(())
+    /// ```
+    pub async fn wait_for_touch_async<INT: Wait>(
+        &mut self,
+        int: &mut INT,
+        trigger: InterruptTrigger,
+    ) -> Result<TouchFrame, GT911Error<I2C::Error>> {
+        loop {
+            match trigger {
+                InterruptTrigger::Rising => int.wait_for_rising_edge().await,
+                InterruptTrigger::Falling => int.wait_for_falling_edge().await,
+                InterruptTrigger::LowLevel => int.wait_for_low().await,
+                InterruptTrigger::HighLevel => int.wait_for_high().await,
+            }
+            .map_err(|err| GT911Error::Pin(err.kind()))?;
+
+            let frame = self.query_touch_frame_async().await?;
+            if frame.status.is_ready() {
+                return Ok(frame);
             }
         }
     }
 
-    /// Internal function to query the device's touch status.
+    /// Enter gesture mode.
     ///
-    /// Requires the outer function to be in command mode.
-    async fn status_async_cmd(&mut self) -> Result<DetectedTouch, GT911Error<I2C::Error>> {
-        // Query the status register
-        let mut status = [0u8; 1];
-        self.read_register_async(register::GT911_STATUS, &mut status).await?;
-        Ok(DetectedTouch::from_bits_truncate(status[0]))
-    }
-
-    /// Internal function to query a specific touch point's data.
+    /// See [`GT911::gesture_mode`].
     ///
-    /// Requires the outer function to be in command mode.
-    async fn touch_async_cmd(
-        &mut self,
-        index: u8,
-    ) -> Result<Option<TouchPoint>, GT911Error<I2C::Error>> {
-        let status = self.status_async_cmd().await?;
-        if !status.is_ready() {
-            // If the device is not ready, return `None`
-            return Ok(None);
-        } else if index > status.touch_count() {
-            // If the index is higher than the number of points, return an error
-            return Err(GT911Error::InvalidPoint(index));
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: the driver is dropped with the future, and once
+    /// the command was sent the device may be in gesture mode. A new driver
+    /// reports this as a product ID mismatch, and can send the command again
+    /// or reset the device.
+    ///
+    /// # Errors
+    ///
+    /// Returns the driver in touch mode and an error if the device is asleep
+    /// or must be reset, if the command is never acknowledged, or if the
+    /// device does not confirm the switch. Once the command was acknowledged
+    /// the returned driver must be reset before it is used again, see
+    /// [`GT911::needs_reset`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::{
+    ///     pin::pin,
+    ///     task::{Context, Waker},
+    /// };
+    ///
+    /// use ef_gt911::{GT911, GT911Error};
+    /// use embedded_hal_async::{delay::DelayNs, i2c::ErrorKind};
+    /// # use ef_gt911::test_util::{Registers, block_on};
+    ///
+    /// /// A delay that returns immediately.
+    /// struct Noop;
+    ///
+    /// impl DelayNs for Noop {
+    ///     async fn delay_ns(&mut self, _: u32) {}
+    /// }
+    ///
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x140..0x144].copy_from_slice(b"911\0");
+    ///
+    /// // Cancel the switch at every await point
+    /// for polls in 1.. {
+    ///     let mut bus = Registers::new(&registers).with_pending();
+    ///
+    ///     let completed = {
+    ///         let mut cx = Context::from_waker(Waker::noop());
+    ///         let (touch, mut delay): (GT911<_>, _) = (GT911::new(&mut bus, 0x5D), Noop);
+    ///         let mut future = pin!(touch.gesture_mode_async(&mut delay));
+    ///         (0..polls).any(|_| future.as_mut().poll(&mut cx).is_ready())
+    ///     };
+    ///
+    ///     // A new driver finds the device in either mode
+    ///     let mut touch: GT911<_> = GT911::new(&mut bus, 0x5D);
+    ///     match block_on(touch.init_async()) {
+    ///         Ok(info) => assert!(!completed && info.product_id == *b"911\0"),
+    ///         Err(GT911Error::ProductIdMismatch(id, _)) => assert_eq!(id, *b"GEST"),
+    ///         Err(err) => panic!("unexpected error: {err:?}"),
+    ///     }
+    ///
+    ///     // Sending the command again completes the switch
+    ///     let Ok(mut gesture) = block_on(touch.gesture_mode_async(&mut Noop)) else { unreachable!() };
+    ///     assert_eq!(block_on(gesture.init_async())?.product_id, *b"GEST");
+    ///
+    ///     if completed {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    pub async fn gesture_mode_async<D: DelayNs>(
+        mut self,
+        delay: &mut D,
+    ) -> Result<GT911<I2C, Gesture, MAX>, (Self, GT911Error<I2C::Error>)> {
+        if self.needs_reset {
+            return Err((self, GT911Error::NeedsReset));
+        } else if self.asleep {
+            return Err((self, GT911Error::Asleep));
         }
 
-        let register = match index {
-            0 => register::GT911_TOUCH1_TRACK_ID,
-            1 => register::GT911_TOUCH2_TRACK_ID,
-            2 => register::GT911_TOUCH3_TRACK_ID,
-            3 => register::GT911_TOUCH4_TRACK_ID,
-            4 => register::GT911_TOUCH5_TRACK_ID,
-            // Maximum 5 touch points (0-4)
-            #[cfg(feature = "defmt")]
-            _ => defmt::unreachable!("Point index out of range"),
-            #[cfg(not(feature = "defmt"))]
-            _ => unreachable!("Point index out of range"),
+        // Send the command, retrying while it is not acknowledged
+        let mut attempt = 1;
+        loop {
+            let mut result = self
+                .write_register_async(register::GT911_COMMAND_CHECK, reset::GESTURE_COMMAND)
+                .await;
+            if result.is_ok() {
+                result = self
+                    .write_register_async(register::GT911_COMMAND, reset::GESTURE_COMMAND)
+                    .await;
+            }
+            match result {
+                Ok(()) => break,
+                Err(err) if attempt == reset::GESTURE_ATTEMPTS => return Err((self, err)),
+                Err(_) => attempt += 1,
+            }
+            delay.delay_ms(reset::GESTURE_RETRY_MS).await;
+        }
+
+        // Wait for the device to confirm the switch
+        let mut gesture: GT911<I2C, Gesture, MAX> = self.into_mode();
+        let mut waits = reset::ready_backoff(reset::GESTURE_CONFIRM_MS);
+        let err = loop {
+            match gesture.init_async().await {
+                Ok(_) => return Ok(gesture),
+                Err(err @ GT911Error::ProductIdMismatch(..)) => match waits.next() {
+                    Some(wait) => delay.delay_ms(wait).await,
+                    None => break err,
+                },
+                Err(err) => break err,
+            }
         };
 
-        // Query the touch point register
-        let mut buf = [0u8; 7];
-        self.read_register_async(register, &mut buf).await?;
-        Ok(Some(TouchPoint::from_bytes(buf)))
+        // The device may be in either mode, so it must be reset
+        let mut touch: Self = gesture.into_mode();
+        touch.needs_reset = true;
+        Err((touch, err))
     }
 }
 
-impl<I2C: I2c> GT911<I2C, Gesture> {
+impl<I2C: I2c, const MAX: usize> GT911<I2C, Gesture, MAX> {
     /// Initialize the GT911 device.
     ///
+    /// Returns the [`DeviceInfo`], including the configured resolution
+    /// used to scale coordinates.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the product ID is read without changing the mode.
+    ///
     /// # Errors
     ///
     /// Returns an error if the device is not ready, if the product ID does not
     /// match, or if any I2C operation fails.
-    pub async fn init_async(&mut self) -> Result<(), GT911Error<I2C::Error>> {
-        let (id, version) = self.device_info_async().await?;
-        if id == [b'G', b'E', b'S', b'T'] {
-            Ok(())
+    pub async fn init_async(&mut self) -> Result<DeviceInfo, GT911Error<I2C::Error>> {
+        let info = self.query_device_info_async().await?;
+        if info.product_id == *b"GEST" {
+            Ok(info)
         } else {
             // Return that the product ID does not match
-            Err(GT911Error::ProductIdMismatch(id, version))
+            Err(GT911Error::ProductIdMismatch(info.product_id, info.firmware_version))
         }
     }
 
-    /// Reset the device, exiting gesture mode.
+    /// Reset the device using its RST and INT pins, exiting gesture mode.
+    ///
+    /// See [`GT911::device_reset`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-corrupting: the driver is dropped with the future,
+    /// and the device may be held in reset.
     ///
     /// # Errors
     ///
-    /// Returns an error if any I2C operation fails.
-    #[expect(clippy::unused_async, reason = "WIP")]
-    pub async fn device_reset_async(
-        self,
-    ) -> Result<GT911<I2C, Touch>, (Self, GT911Error<I2C::Error>)> {
-        todo!()
+    /// Returns the driver and an error if driving either pin fails.
+    pub async fn device_reset_async<RST: OutputPin, INT: OutputPin, D: DelayNs>(
+        mut self,
+        rst: &mut RST,
+        int: &mut INT,
+        delay: &mut D,
+        address: GT911Address,
+    ) -> Result<GT911<I2C, Touch, MAX>, (Self, GT911Error<I2C::Error>)> {
+        match reset(rst, int, delay, address).await {
+            Ok(()) => {
+                self.address = address.address();
+                self.asleep = false;
+                self.needs_reset = false;
+                Ok(self.into_mode())
+            }
+            Err(err) => Err((self, GT911Error::Pin(err))),
+        }
     }
 
     /// Query the detected gesture.
     ///
+    /// Swipes are remapped by the configured
+    /// [`Orientation`](crate::Orientation), the gesture reported by the
+    /// panel is kept as [`OrientedGesture::raw`]. A detected gesture is
+    /// cleared after it is read, following the [`ClearPolicy`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the gesture is only cleared after it is read,
+    /// and a gesture that was not cleared is read again by the next call.
+    ///
     /// # Errors
     ///
     /// Returns an error if any I2C operation fails.
-    pub async fn query_gesture_async(&mut self) -> Result<DetectedGesture, GT911Error<I2C::Error>> {
-        command_mode!(self, Gesture, {
-            // Query the gesture register
-            let mut buf = [0u8; 1];
-            self.read_register_async(register::GT911_GESTURE_STATUS, &mut buf).await?;
-            Ok(DetectedGesture::from_byte(buf[0]))
-        })
+    pub async fn query_gesture_async(&mut self) -> Result<OrientedGesture, GT911Error<I2C::Error>> {
+        // Query the gesture register
+        let mut buf = [0u8; 1];
+        self.read_register_async(register::GT911_GESTURE_STATUS, &mut buf).await?;
+        let gesture = DetectedGesture::from_byte(buf[0]);
+
+        if gesture.is_any() && self.clear_policy == ClearPolicy::AfterRead {
+            self.clear_status_async().await?;
+        }
+        Ok(OrientedGesture::new(gesture, self.orientation))
     }
 
     /// Query the number of gesture touch points.
     ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the count is read without clearing the gesture.
+    ///
     /// # Errors
     ///
     /// Returns an error if any I2C operation fails.
     pub async fn query_gesture_point_count_async(&mut self) -> Result<u8, GT911Error<I2C::Error>> {
-        command_mode!(self, Gesture, {
-            // Query the gesture point count register
-            let mut buf = [0u8; 1];
-            self.read_register_async(register::GT911_GESTURE_TOUCH_POINTS, &mut buf).await?;
-            Ok(buf[0])
-        })
+        // Query the gesture point count register
+        let mut buf = [0u8; 1];
+        self.read_register_async(register::GT911_GESTURE_TOUCH_POINTS, &mut buf).await?;
+        Ok(buf[0])
     }
 
     /// Query a specific gesture point's data.
     ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the point is read without clearing the gesture,
+    /// so it can be read again.
+    ///
     /// # Errors
     ///
     /// Returns an error if the point index is invalid (>63),
@@ -343,27 +1016,95 @@ impl<I2C: I2c> GT911<I2C, Gesture> {
             _ => unreachable!("Point index out of range"),
         };
 
-        command_mode!(self, Gesture, {
-            // Query the gesture touch point register
-            let mut buf = [0u8; 4];
-            self.read_register_async(register, &mut buf).await?;
-            Ok(GesturePoint::from_bytes(buf))
-        })
+        // Query the gesture touch point register
+        let mut buf = [0u8; 4];
+        self.read_register_async(register, &mut buf).await?;
+        Ok(self.transform_gesture(GesturePoint::from_bytes(buf)))
     }
 
     /// Query all gesture touch points.
     ///
+    /// Prefer [`GT911::read_gesture_track_async`],
+    /// which does not need a buffer for every point.
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: the points are read without clearing the gesture,
+    /// so they can be read again.
+    ///
     /// # Errors
     ///
     /// Returns an error if any I2C operation fails.
     pub async fn query_gesture_point_all_async(
         &mut self,
     ) -> Result<[Option<GesturePoint>; 64], GT911Error<I2C::Error>> {
-        let count = self.query_gesture_point_count_async().await?;
+        let mut buf = [0u8; GestureTrack::BUFFER_LEN];
         let mut points = [None; 64];
-        for i in 0..count {
-            points[i as usize] = Some(self.query_gesture_point_async(i).await?);
+        for (slot, point) in points.iter_mut().zip(self.read_gesture_track_async(&mut buf).await?) {
+            *slot = Some(point);
         }
         Ok(points)
     }
+
+    /// Read the gesture points into `buf` in a single read,
+    /// returning a [`GestureTrack`] that decodes them as it is iterated.
+    ///
+    /// Each point takes [`GestureTrack::POINT_LEN`] bytes. If `buf` is too
+    /// small for every reported point, only the first points are read, see
+    /// [`GestureTrack::is_truncated`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Cancel-safe: `buf` may hold part of the points, but the gesture is
+    /// not cleared, so the next call reads all of them again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any I2C operation fails.
+    pub async fn read_gesture_track_async<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+    ) -> Result<GestureTrack<'b>, GT911Error<I2C::Error>> {
+        let count = self.query_gesture_point_count_async().await?;
+        let len = GestureTrack::read_len(count, buf.len());
+        let data = &mut buf[..len];
+        if !data.is_empty() {
+            self.read_register_async(register::GT911_GESTURE_POINT1_X_LSB, data).await?;
+        }
+        Ok(GestureTrack::new(data, count, self.transform))
+    }
+}
+
+impl<I2C: I2c, MODE: GT911Mode, const MAX: usize> AsyncIdentify for GT911<I2C, MODE, MAX> {
+    type Error = GT911Error<I2C::Error>;
+
+    /// Read the product ID and firmware version,
+    /// see [`GT911::query_device_info_async`].
+    async fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> {
+        let info = self.query_device_info_async().await?;
+        Ok(DeviceIdentity::new("GT911", &info.product_id, Some(info.firmware_version)))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Run the reset sequence selecting `address` asynchronously.
+///
+/// # Errors
+///
+/// Returns an error if driving either pin fails.
+async fn reset<RST: OutputPin, INT: OutputPin, D: DelayNs>(
+    rst: &mut RST,
+    int: &mut INT,
+    delay: &mut D,
+    address: GT911Address,
+) -> Result<(), ErrorKind> {
+    for step in reset::reset_steps(address) {
+        match step {
+            ResetStep::Rst(state) => rst.set_state(state).map_err(|err| err.kind())?,
+            ResetStep::Int(state) => int.set_state(state).map_err(|err| err.kind())?,
+            ResetStep::DelayUs(us) => delay.delay_us(us).await,
+        }
+    }
+    Ok(())
 }
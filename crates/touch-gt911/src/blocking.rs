@@ -1,66 +1,435 @@
-use core::marker::PhantomData;
-
-use embedded_hal::i2c::I2c;
+use ef_driver_common::identify::{DeviceIdentity, Identify};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{Error, ErrorKind, InputPin, OutputPin},
+    i2c::I2c,
+};
 
 use crate::{
-    DetectedGesture, DetectedTouch, GT911, GT911Error, GT911Mode, Gesture, GesturePoint, Touch,
-    TouchPoint, register,
+    ClearPolicy, DetectedGesture, DetectedTouch, DeviceInfo, GT911, GT911Address, GT911Config,
+    GT911Error, GT911Mode, Gesture, GesturePoint, GestureTrack, InterruptTrigger, NoiseProfile,
+    OrientedGesture, Touch, TouchFrame, TouchKeys, TouchPoint, TouchReport,
+    config::ConfigPatch,
+    register,
+    reset::{self, ResetStep},
 };
 
-/// A simple macro to enter and exit command mode around a code block.
-macro_rules! command_mode {
-    ($driver:expr, $mode:ty, $block:block) => {
-        command_mode!($driver, $mode, 0, $block)
-    };
-    ($driver:expr, $mode:ty, $code:expr, $block:block) => {{
-        // Enter command mode
-        if $code > 7 {
-            $driver.write_register(register::GT911_COMMAND_CHECK, $code)?;
+impl<I2C: I2c, MODE: GT911Mode, const MAX: usize> GT911<I2C, MODE, MAX> {
+    /// Query the device's [`DeviceInfo`] in a single read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any I2C operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x140..0x14B].copy_from_slice(b"911\0\x60\x10\xE0\x01\x20\x03\x02");
+    /// registers[0x14E] = 0x81;
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&registers), 0x5D);
+    ///
+    /// // Register addresses are sent most significant byte first,
+    /// // while values are stored least significant byte first
+    /// let info = touch.query_device_info()?;
+    /// assert_eq!((info.product_id, info.firmware_version), (*b"911\0", 0x1060));
+    /// assert_eq!((info.x_resolution, info.y_resolution), (480, 800));
+    /// assert_eq!(touch.i2c().writes, [vec![0x81, 0x40]]);
+    ///
+    /// // Reading the status leaves it set
+    /// touch.i2c_mut().writes.clear();
+    /// assert_eq!(touch.query_touch_status()?.bits(), 0x81);
+    /// assert_eq!(touch.i2c().writes, [vec![0x81, 0x4E]]);
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    pub fn query_device_info(&mut self) -> Result<DeviceInfo, GT911Error<I2C::Error>> {
+        let mut buf = [0u8; DeviceInfo::LEN];
+        self.read_register(register::GT911_PRODUCT_ID1, &mut buf)?;
+        Ok(DeviceInfo::from_bytes(buf))
+    }
+
+    /// Query the device's product ID and firmware version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any I2C operation fails.
+    #[deprecated = "use `GT911::query_device_info` instead"]
+    pub fn device_info(&mut self) -> Result<([u8; 4], u16), GT911Error<I2C::Error>> {
+        let info = self.query_device_info()?;
+        Ok((info.product_id, info.firmware_version))
+    }
+
+    /// Clear the status register, letting the device report the next frame
+    /// or gesture.
+    ///
+    /// Reads clear the status themselves unless the
+    /// [`ClearPolicy`] is [`ClearPolicy::Manual`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{ClearPolicy, GT911, GT911Error};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// // One touch point is ready
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x14E] = 0x81;
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&registers), 0x5D);
+    ///
+    /// // The status, the point and a single write to clear the status
+    /// assert_eq!(touch.query_touch_all()?.len(), 1);
+    /// assert_eq!((touch.i2c().transactions, touch.i2c().registers[0x14E]), (3, 0x00));
+    ///
+    /// // Polling an empty status is a single read
+    /// touch.i2c_mut().transactions = 0;
+    /// assert!(touch.query_touch_all()?.is_empty());
+    /// assert_eq!(touch.i2c().transactions, 1);
+    ///
+    /// // With a manual policy the frame stays until it is cleared
+    /// touch.set_clear_policy(ClearPolicy::Manual);
+    /// touch.i2c_mut().registers[0x14E] = 0x81;
+    /// touch.i2c_mut().transactions = 0;
+    /// assert_eq!(touch.query_touch_all()?.len(), 1);
+    /// assert_eq!(touch.query_touch_frame()?.len(), 1);
+    /// assert_eq!((touch.i2c().transactions, touch.i2c().registers[0x14E]), (3, 0x81));
+    ///
+    /// touch.clear_status()?;
+    /// assert_eq!((touch.i2c().transactions, touch.i2c().registers[0x14E]), (4, 0x00));
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    #[inline]
+    pub fn clear_status(&mut self) -> Result<(), GT911Error<I2C::Error>> {
+        self.write_register(MODE::CLEAR_REGISTER, 0)
+    }
+
+    /// Detect the longest read the I2C bus supports,
+    /// and use it as the maximum transaction length.
+    ///
+    /// Reads of decreasing length are made from the configuration registers,
+    /// which have no side effects. If the whole region can be read at once,
+    /// the maximum is left unlimited.
+    ///
+    /// Returns the detected maximum.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// // Five touch points, on a bus limited to 16 bytes per read
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x14E] = 0x85;
+    /// for index in 0..5 {
+    ///     registers[0x157 + index * 8 + 1] = index as u8;
+    /// }
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&registers).with_max_read(16), 0x5D);
+    ///
+    /// // Without a limit, reading all touch points fails
+    /// assert_eq!(touch.max_transaction(), usize::MAX);
+    /// assert_eq!(touch.query_touch_all(), Err(GT911Error::I2C(ErrorKind::Overrun)));
+    ///
+    /// // The limit is detected, and longer reads are split
+    /// assert_eq!(touch.detect_max_transaction()?, 16);
+    /// touch.i2c_mut().registers[0x14E] = 0x85;
+    /// touch.i2c_mut().reads.clear();
+    /// let report = touch.query_touch_all()?;
+    /// assert_eq!(report.points().iter().map(|p| p.x).collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    /// assert_eq!(touch.i2c().reads, [(0x814E, 1), (0x8157, 16), (0x8167, 16), (0x8177, 7)]);
+    ///
+    /// // A bus without a limit stays unlimited
+    /// touch.i2c_mut().max_read = usize::MAX;
+    /// assert_eq!(touch.detect_max_transaction()?, usize::MAX);
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a single byte cannot be read.
+    pub fn detect_max_transaction(&mut self) -> Result<usize, GT911Error<I2C::Error>> {
+        let mut buf = [0u8; crate::CONFIG_LEN];
+        let mut read = |len: usize| {
+            self.i2c.write_read(
+                self.address,
+                &register::register_bytes(register::GT911_CONFIG_VERSION),
+                &mut buf[..len],
+            )
+        };
+
+        // A single byte must always be readable
+        read(1).map_err(GT911Error::I2C)?;
+        if read(crate::CONFIG_LEN).is_ok() {
+            self.max_transaction = usize::MAX;
+            return Ok(self.max_transaction);
         }
-        $driver.write_register(register::GT911_COMMAND, $code)?;
 
-        // Create a closure and execute the block (preventing early returns)
-        #[allow(unused_mut, reason = "Closure may need to be mutable")]
-        let mut closure = || $block;
-        let result = (closure)();
+        // Find the longest read that succeeds
+        let (mut good, mut bad) = (1, crate::CONFIG_LEN);
+        while bad - good > 1 {
+            let len = good + (bad - good) / 2;
+            if read(len).is_ok() { good = len } else { bad = len }
+        }
+        self.max_transaction = good;
+        Ok(good)
+    }
 
-        // Exit command mode
-        $driver.write_register(<$mode>::CLEAR_REGISTER, 0)?;
+    /// Read the configuration registers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored checksum is invalid,
+    /// or if any I2C operation fails.
+    pub fn read_config(&mut self) -> Result<GT911Config, GT911Error<I2C::Error>> {
+        let mut image = [0u8; GT911Config::LEN];
+        self.read_register(register::GT911_CONFIG_VERSION, &mut image)?;
 
-        // Return the result
-        result
-    }};
-}
+        let config = GT911Config::from_bytes(image);
+        let (stored, computed) = (config.stored_checksum(), config.checksum());
+        if stored == computed {
+            Ok(config)
+        } else {
+            Err(GT911Error::ConfigChecksum { stored, computed })
+        }
+    }
 
-impl<I2C: I2c, MODE: GT911Mode> GT911<I2C, MODE> {
-    /// Query the device's product ID and firmware version.
+    /// Write the configuration registers, with a new checksum and the fresh
+    /// flag set, then read back the checksum to verify it was taken.
+    ///
+    /// The device ignores a configuration with a version below its own,
+    /// see [`GT911Config::version`].
     ///
     /// # Errors
     ///
-    /// Returns an error if any I2C operation fails.
-    pub fn device_info(&mut self) -> Result<([u8; 4], u16), GT911Error<I2C::Error>> {
-        command_mode!(self, MODE, {
-            // Query the product ID
-            let mut id = [0u8; 4];
-            self.read_register(register::GT911_PRODUCT_ID1, &mut id)?;
-            // Query the firmware version
-            let mut ver = [0u8; 2];
-            self.read_register(register::GT911_FIRMWARE_VER_LSB, &mut ver)?;
-            Ok((id, u16::from_le_bytes(ver)))
-        })
+    /// Returns an error if the checksum read back does not match,
+    /// or if any I2C operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error, InterruptTrigger};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// // The start of a captured 480x272 configuration image
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x47..0x57].copy_from_slice(&[
+    ///     0x41, 0xE0, 0x01, 0x10, 0x01, 0x05, 0x0D, 0x00, 0x01, 0x08, 0x28, 0x05, 0x50, 0x32, 0x03,
+    ///     0x05,
+    /// ]);
+    /// registers[0xFF] =
+    ///     registers[0x47..0xFF].iter().fold(0u8, |s, &b| s.wrapping_add(b)).wrapping_neg();
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&registers), 0x5D);
+    ///
+    /// let mut config = touch.read_config()?;
+    /// assert_eq!((config.x_max(), config.y_max()), (480, 272));
+    /// assert_eq!(config.interrupt_trigger(), InterruptTrigger::Falling);
+    ///
+    /// // Writing stores the new checksum and sets the fresh flag
+    /// config.set_interrupt_trigger(InterruptTrigger::Rising);
+    /// touch.write_config(&config)?;
+    /// assert_eq!(touch.i2c().registers[0x4D], 0x0C);
+    /// assert_eq!(touch.i2c().registers[0xFF], 0xFC);
+    /// assert_eq!(touch.i2c().registers[0x100], 0x01);
+    /// assert!(touch.read_config()?.has_valid_checksum());
+    ///
+    /// // A configuration the device did not take is reported
+    /// touch.i2c_mut().read_only = true;
+    /// config.set_x_max(800);
+    /// let result = touch.write_config(&config);
+    /// assert!(matches!(result, Err(GT911Error::ConfigChecksum { stored: 0xFC, .. })));
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    pub fn write_config(&mut self, config: &GT911Config) -> Result<(), GT911Error<I2C::Error>> {
+        let computed = config.checksum();
+        let mut data = [0u8; GT911Config::LEN + 1];
+        data[..GT911Config::LEN].copy_from_slice(config.as_bytes());
+        data[GT911Config::LEN - 1] = computed;
+        data[GT911Config::LEN] = 1;
+        self.write_registers(register::GT911_CONFIG_VERSION, &data)?;
+
+        let mut stored = [0u8; 1];
+        self.read_register(register::GT911_CONFIG_CHECKSUM, &mut stored)?;
+        if stored[0] == computed {
+            Ok(())
+        } else {
+            Err(GT911Error::ConfigChecksum { stored: stored[0], computed })
+        }
+    }
+
+    /// Apply a [`NoiseProfile`] to the device configuration.
+    ///
+    /// The configuration is read and its checksum verified, then only the
+    /// changed registers are written, followed by the new checksum and the
+    /// update flag. Nothing is written if the profile is already applied.
+    ///
+    /// Returns `true` if the configuration was changed.
+    ///
+    /// See [`NoiseProfile`] for an example.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored checksum is invalid,
+    /// or if any I2C operation fails.
+    pub fn apply_noise_profile(
+        &mut self,
+        profile: &NoiseProfile,
+    ) -> Result<bool, GT911Error<I2C::Error>> {
+        self.patch_config(profile.settings().patches())
+    }
+
+    /// Apply [`ConfigPatch`]es to the device configuration,
+    /// writing only the patches that change it.
+    ///
+    /// Returns `true` if the configuration was changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored checksum is invalid,
+    /// or if any I2C operation fails.
+    fn patch_config(
+        &mut self,
+        patches: impl IntoIterator<Item = ConfigPatch>,
+    ) -> Result<bool, GT911Error<I2C::Error>> {
+        let mut config = self.read_config()?;
+
+        let mut changed = false;
+        for patch in patches {
+            if patch.apply(config.as_bytes_mut()) {
+                self.write_registers(patch.register(), patch.data())?;
+                changed = true;
+            }
+        }
+
+        if changed {
+            // Write the new checksum and mark the configuration as updated
+            let checksum = config.checksum();
+            self.write_registers(register::GT911_CONFIG_CHECKSUM, &[checksum, 1])?;
+        }
+        Ok(changed)
+    }
+
+    /// Put the device to sleep.
+    ///
+    /// INT must be driven low while the command is sent, as it is after
+    /// [`GT911::device_reset`]. Reads fail with [`GT911Error::Asleep`]
+    /// until the device is woken with [`GT911::wake`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the I2C operation fails.
+    pub fn enter_sleep(&mut self) -> Result<(), GT911Error<I2C::Error>> {
+        self.write_register(register::GT911_COMMAND, reset::SLEEP_COMMAND)?;
+        self.asleep = true;
+        Ok(())
+    }
+
+    /// Wake the device from sleep by pulsing its INT pin high.
+    ///
+    /// INT is held high for 3 ms, then driven low while the device
+    /// stabilizes for 58 ms. INT is left driven low, release it to an input
+    /// before waiting for touch interrupts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if driving the pin fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error};
+    /// use embedded_hal::{
+    ///     delay::DelayNs,
+    ///     digital::{ErrorKind, ErrorType, OutputPin},
+    ///     i2c,
+    /// };
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// /// A pin and delay that log every change and wait, in microseconds.
+    /// #[derive(Default)]
+    /// struct Log(Vec<(&'static str, u32)>);
+    ///
+    /// impl ErrorType for Log {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl OutputPin for Log {
+    ///     fn set_low(&mut self) -> Result<(), ErrorKind> {
+    ///         self.0.push(("int", 0));
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn set_high(&mut self) -> Result<(), ErrorKind> {
+    ///         self.0.push(("int", 1));
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// impl DelayNs for Log {
+    ///     fn delay_ns(&mut self, ns: u32) { self.0.push(("delay", ns / 1_000)); }
+    /// }
+    ///
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&[0u8; 0x200]), 0x5D);
+    ///
+    /// // The sleep command is written, and reads are refused
+    /// touch.enter_sleep()?;
+    /// assert_eq!(touch.i2c().registers[0x040], 0x05);
+    /// assert_eq!(touch.query_touch_status(), Err(GT911Error::Asleep));
+    ///
+    /// // Waking pulses INT high, then waits for the device to stabilize
+    /// let (mut int, mut delay) = (Log::default(), Log::default());
+    /// touch.wake(&mut int, &mut delay)?;
+    /// assert_eq!(int.0, [("int", 1), ("int", 0)]);
+    /// assert_eq!(delay.0, [("delay", 3_000), ("delay", 58_000)]);
+    /// assert!(!touch.is_asleep() && touch.query_touch_status().is_ok());
+    /// # Ok::<(), GT911Error<i2c::ErrorKind>>(())
+    /// ```
+    pub fn wake<INT: OutputPin, D: DelayNs>(
+        &mut self,
+        int: &mut INT,
+        delay: &mut D,
+    ) -> Result<(), GT911Error<I2C::Error>> {
+        int.set_high().map_err(|err| GT911Error::Pin(err.kind()))?;
+        delay.delay_us(reset::WAKE_PULSE_US);
+        int.set_low().map_err(|err| GT911Error::Pin(err.kind()))?;
+        delay.delay_us(reset::WAKE_SETTLE_US);
+        self.asleep = false;
+        Ok(())
     }
 
     /// Read from a register.
     ///
+    /// Reads longer than the maximum transaction length are split.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the read operation fails.
+    /// Returns an error if the device is asleep or must be reset,
+    /// or if the read operation fails.
     fn read_register(
         &mut self,
         register: u16,
         buf: &mut [u8],
     ) -> Result<(), GT911Error<I2C::Error>> {
-        self.i2c.write_read(self.address, &register.to_be_bytes(), buf).map_err(GT911Error::I2C)
+        if self.needs_reset {
+            return Err(GT911Error::NeedsReset);
+        } else if self.asleep {
+            return Err(GT911Error::Asleep);
+        }
+        for (register, chunk) in crate::transactions(register, buf, self.max_transaction) {
+            self.i2c
+                .write_read(self.address, &register::register_bytes(register), chunk)
+                .map_err(GT911Error::I2C)?;
+        }
+        Ok(())
     }
 
     /// Write to a register.
@@ -69,40 +438,245 @@ impl<I2C: I2c, MODE: GT911Mode> GT911<I2C, MODE> {
     ///
     /// Returns an error if the write operation fails.
     fn write_register(&mut self, register: u16, data: u8) -> Result<(), GT911Error<I2C::Error>> {
-        let buf = [register.to_be_bytes()[0], register.to_be_bytes()[1], data];
+        let [hi, lo] = register::register_bytes(register);
+        let buf = [hi, lo, data];
         self.i2c.write(self.address, &buf).map_err(GT911Error::I2C)
     }
+
+    /// Write to consecutive registers in a single transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write operation fails.
+    fn write_registers(
+        &mut self,
+        register: u16,
+        data: &[u8],
+    ) -> Result<(), GT911Error<I2C::Error>> {
+        let mut buf = [0u8; 2 + GT911Config::LEN + 1];
+        buf[..2].copy_from_slice(&register::register_bytes(register));
+        buf[2..2 + data.len()].copy_from_slice(data);
+        self.i2c.write(self.address, &buf[..2 + data.len()]).map_err(GT911Error::I2C)
+    }
 }
 
-impl<I2C: I2c> GT911<I2C, Touch> {
+impl<I2C: I2c, const MAX: usize> GT911<I2C, Touch, MAX> {
     /// Initialize the GT911 device.
     ///
+    /// Returns the [`DeviceInfo`], including the configured resolution
+    /// used to scale coordinates.
+    ///
+    /// The product ID must be one of the variants set with
+    /// [`GT911::with_variants`], and the detected variant is kept.
+    ///
     /// # Errors
     ///
     /// Returns an error if the device is not ready, if the product ID does not
     /// match, or if any I2C operation fails.
-    pub fn init(&mut self) -> Result<(), GT911Error<I2C::Error>> {
+    ///
+    /// Right after a reset the device is not ready yet, use
+    /// [`GT911::init_with_timeout`] to wait for it instead.
+    pub fn init(&mut self) -> Result<DeviceInfo, GT911Error<I2C::Error>> {
         let status = self.query_touch_status()?;
         if !status.is_ready() {
             // Return that the device is not ready
             return Err(GT911Error::DeviceNotReady(status));
         }
 
-        let (id, version) = self.device_info()?;
-        if id == [b'9', b'1', b'1', b'\0'] {
-            Ok(())
-        } else {
-            // Return that the product ID does not match
-            Err(GT911Error::ProductIdMismatch(id, version))
+        let info = self.query_device_info()?;
+        self.accept_variant(&info)?;
+        Ok(info)
+    }
+
+    /// Wait up to `timeout_ms` milliseconds for the device to become ready,
+    /// then initialize it.
+    ///
+    /// See [`GT911::wait_ready`] and [`GT911::init`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GT911Error::Timeout`] if the device does not become ready in
+    /// time, an error if the product ID does not match, or if any I2C
+    /// operation fails.
+    pub fn init_with_timeout<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+        timeout_ms: u32,
+    ) -> Result<DeviceInfo, GT911Error<I2C::Error>> {
+        self.wait_ready(delay, timeout_ms)?;
+        self.init()
+    }
+
+    /// Poll the status register until the device is ready,
+    /// for up to `timeout_ms` milliseconds.
+    ///
+    /// The device takes around 50 ms after a reset before its coordinates
+    /// are valid. Polls start 1 ms apart, and back off to 8 ms apart.
+    ///
+    /// Returns the ready status, which is not cleared.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GT911Error::Timeout`] if the device does not become ready in
+    /// time, or an error if any I2C operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error};
+    /// use embedded_hal::{delay::DelayNs, i2c::ErrorKind};
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// /// A delay that records every wait, in milliseconds.
+    /// #[derive(Default)]
+    /// struct Clock(Vec<u32>);
+    ///
+    /// impl DelayNs for Clock {
+    ///     fn delay_ns(&mut self, ns: u32) { self.0.push(ns / 1_000_000); }
+    /// }
+    ///
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x140..0x144].copy_from_slice(b"911\0");
+    ///
+    /// // Ready on the fifth poll
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&registers).with_ready_after(5), 0x5D);
+    /// let mut clock = Clock::default();
+    /// assert!(touch.wait_ready(&mut clock, 100)?.is_ready());
+    /// assert_eq!(clock.0, [1, 2, 4, 8]);
+    ///
+    /// // The last wait is cut short by the timeout
+    /// let mut touch: GT911<_> =
+    ///     GT911::new(Registers::new(&registers).with_ready_after(usize::MAX), 0x5D);
+    /// let mut clock = Clock::default();
+    /// assert_eq!(touch.wait_ready(&mut clock, 20), Err(GT911Error::Timeout));
+    /// assert_eq!(clock.0, [1, 2, 4, 8, 5]);
+    ///
+    /// // `init` fails on the first read after a reset, unlike `init_with_timeout`
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&registers).with_ready_after(3), 0x5D);
+    /// assert!(matches!(touch.init(), Err(GT911Error::DeviceNotReady(_))));
+    /// touch.init_with_timeout(&mut Clock::default(), 100)?;
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    pub fn wait_ready<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+        timeout_ms: u32,
+    ) -> Result<DetectedTouch, GT911Error<I2C::Error>> {
+        let mut waits = reset::ready_backoff(timeout_ms);
+        loop {
+            let status = self.query_touch_status()?;
+            if status.is_ready() {
+                return Ok(status);
+            }
+            delay.delay_ms(waits.next().ok_or(GT911Error::Timeout)?);
         }
     }
 
-    /// Reset the device.
+    /// Reset the device using its RST and INT pins,
+    /// selecting the I2C address it answers to.
+    ///
+    /// The driver uses the selected address afterwards. INT is left driven
+    /// low, release it to an input before waiting for touch interrupts.
     ///
     /// # Errors
     ///
-    /// Returns an error if any I2C operation fails.
-    pub fn device_reset(&mut self) -> Result<(), GT911Error<I2C::Error>> { todo!() }
+    /// Returns an error if driving either pin fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Address, GT911Error};
+    /// use embedded_hal::{
+    ///     delay::DelayNs,
+    ///     digital::{ErrorKind, ErrorType, OutputPin},
+    ///     i2c::{self, I2c, Operation},
+    /// };
+    ///
+    /// /// A bus that is never used by the reset.
+    /// struct Bus;
+    ///
+    /// impl i2c::ErrorType for Bus {
+    ///     type Error = i2c::ErrorKind;
+    /// }
+    ///
+    /// impl I2c for Bus {
+    ///     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+    ///         unreachable!()
+    ///     }
+    /// }
+    ///
+    /// /// A log of every pin change and delay, in order.
+    /// type Log = std::rc::Rc<std::cell::RefCell<Vec<(&'static str, u32)>>>;
+    ///
+    /// /// A pin that logs its level under a name.
+    /// struct Pin(&'static str, Log);
+    ///
+    /// impl ErrorType for Pin {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl OutputPin for Pin {
+    ///     fn set_low(&mut self) -> Result<(), ErrorKind> {
+    ///         self.1.borrow_mut().push((self.0, 0));
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn set_high(&mut self) -> Result<(), ErrorKind> {
+    ///         self.1.borrow_mut().push((self.0, 1));
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// /// A delay that logs every wait, in microseconds.
+    /// struct Delay(Log);
+    ///
+    /// impl DelayNs for Delay {
+    ///     fn delay_ns(&mut self, ns: u32) { self.0.borrow_mut().push(("delay", ns / 1_000)); }
+    /// }
+    ///
+    /// let log = Log::default();
+    /// let (mut rst, mut int) = (Pin("rst", log.clone()), Pin("int", log.clone()));
+    /// let mut delay = Delay(log.clone());
+    ///
+    /// // Select the secondary address by holding INT high while RST is released
+    /// let mut touch: GT911<_> = GT911::new(Bus, 0x5D);
+    /// touch.device_reset(&mut rst, &mut int, &mut delay, GT911Address::Secondary)?;
+    /// assert_eq!(touch.address(), 0x14);
+    /// assert_eq!(
+    ///     *log.borrow(),
+    ///     [
+    ///         ("rst", 0),
+    ///         ("int", 0),
+    ///         ("delay", 20_000),
+    ///         ("int", 1),
+    ///         ("delay", 200),
+    ///         ("rst", 1),
+    ///         ("delay", 6_000),
+    ///         ("int", 0),
+    ///         ("delay", 50_000),
+    ///     ]
+    /// );
+    ///
+    /// // The primary address holds INT low instead
+    /// log.borrow_mut().clear();
+    /// touch.device_reset(&mut rst, &mut int, &mut delay, GT911Address::Primary)?;
+    /// assert_eq!(touch.address(), 0x5D);
+    /// assert_eq!(log.borrow()[3], ("int", 0));
+    /// # Ok::<(), GT911Error<i2c::ErrorKind>>(())
+    /// ```
+    pub fn device_reset<RST: OutputPin, INT: OutputPin, D: DelayNs>(
+        &mut self,
+        rst: &mut RST,
+        int: &mut INT,
+        delay: &mut D,
+        address: GT911Address,
+    ) -> Result<(), GT911Error<I2C::Error>> {
+        reset(rst, int, delay, address).map_err(GT911Error::Pin)?;
+        self.address = address.address();
+        self.asleep = false;
+        self.needs_reset = false;
+        Ok(())
+    }
 
     /// Query the device's touch status.
     ///
@@ -110,12 +684,10 @@ impl<I2C: I2c> GT911<I2C, Touch> {
     ///
     /// Returns an error if any I2C operation fails.
     pub fn query_touch_status(&mut self) -> Result<DetectedTouch, GT911Error<I2C::Error>> {
-        command_mode!(self, Touch, {
-            // Query the status register
-            let mut status = [0u8; 1];
-            self.read_register(register::GT911_STATUS, &mut status)?;
-            Ok(DetectedTouch::from_bits_truncate(status[0]))
-        })
+        // Query the status register
+        let mut status = [0u8; 1];
+        self.read_register(register::GT911_STATUS, &mut status)?;
+        Ok(DetectedTouch::from_bits_truncate(status[0]))
     }
 
     /// Query the number of active touch points.
@@ -132,111 +704,451 @@ impl<I2C: I2c> GT911<I2C, Touch> {
     ///
     /// # Errors
     ///
-    /// Returns an error if the point index is invalid (>4),
-    /// or if any I2C operation fails.
+    /// Returns an error if the point index is invalid (>= `MAX`, or not
+    /// currently active), or if any I2C operation fails.
     pub fn query_touch(&mut self, index: u8) -> Result<TouchPoint, GT911Error<I2C::Error>> {
         // If the index is higher than the number of points, return an error
-        if index > self.query_touch_count()? {
+        if usize::from(index) >= self.max_points() || index >= self.query_touch_count()? {
             return Err(GT911Error::InvalidPoint(index));
         }
 
-        let register = match index {
-            0 => register::GT911_TOUCH1_TRACK_ID,
-            1 => register::GT911_TOUCH2_TRACK_ID,
-            2 => register::GT911_TOUCH3_TRACK_ID,
-            3 => register::GT911_TOUCH4_TRACK_ID,
-            4 => register::GT911_TOUCH5_TRACK_ID,
-            // Maximum 5 touch points (0-4)
-            _ => unreachable!("Point index out of range"),
-        };
-
-        command_mode!(self, Touch, {
-            // Query the touch point register
-            let mut buf = [0u8; 7];
-            self.read_register(register, &mut buf)?;
-            Ok(TouchPoint::from_bytes(buf))
-        })
+        // Query the touch point register
+        let mut buf = [0u8; 7];
+        self.read_register(crate::touch_register(index), &mut buf)?;
+        Ok(self.transform_touch(TouchPoint::from_bytes(buf)))
     }
 
     /// Query all active touch points.
     ///
+    /// At most `MAX` touch points are read in a single burst,
+    /// any further points are ignored. The status is cleared after the
+    /// points are read, following the [`ClearPolicy`].
+    ///
     /// # Errors
     ///
     /// Returns an error if any I2C operation fails.
-    pub fn query_touch_all(&mut self) -> Result<[Option<TouchPoint>; 5], GT911Error<I2C::Error>> {
-        let count = self.query_touch_count()?;
-        let mut points = [None, None, None, None, None];
-        for i in 0..count {
-            points[i as usize] = Some(self.query_touch(i)?);
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// // Three touch points at (10, 20), (30, 40), and (50, 60)
+    /// let mut registers = [0u8; 0x200];
+    /// for (index, (x, y)) in [(10u16, 20u16), (30, 40), (50, 60)].into_iter().enumerate() {
+    ///     let start = 0x157 + index * 8;
+    ///     registers[start] = index as u8;
+    ///     registers[start + 1..start + 3].copy_from_slice(&x.to_le_bytes());
+    ///     registers[start + 3..start + 5].copy_from_slice(&y.to_le_bytes());
+    /// }
+    ///
+    /// // A driver for a panel with at most two touch points
+    /// let mut touch = GT911::<_, _, 2>::new(Registers::new(&registers), 0x5D);
+    ///
+    /// // Only two points are reported, and only their registers are read
+    /// touch.i2c_mut().registers[0x14E] = 0x83;
+    /// let report = touch.query_touch_all()?;
+    /// assert_eq!(report.len(), 2);
+    /// assert_eq!(
+    ///     report.points().iter().map(|p| (p.x, p.y)).collect::<Vec<_>>(),
+    ///     [(10, 20), (30, 40)]
+    /// );
+    /// assert_eq!(touch.i2c().reads, [(0x814E, 1), (0x8157, 15)]);
+    ///
+    /// // The third point is out of range for the driver
+    /// assert_eq!(touch.query_touch(2), Err(GT911Error::InvalidPoint(2)));
+    ///
+    /// // Nothing is read while the device is not ready
+    /// touch.i2c_mut().registers[0x14E] = 0x03;
+    /// touch.i2c_mut().reads.clear();
+    /// assert!(touch.query_touch_all()?.is_empty());
+    /// assert_eq!(touch.i2c().reads, [(0x814E, 1)]);
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    pub fn query_touch_all(&mut self) -> Result<TouchReport<MAX>, GT911Error<I2C::Error>> {
+        let status = self.query_touch_status()?;
+        if !status.is_ready() {
+            return Ok(TouchReport::EMPTY);
         }
-        Ok(points)
+
+        // Query all touch points that fit in the report
+        let mut buf = [0u8; crate::MAX_TOUCH_POINTS * crate::TOUCH_POINT_STRIDE];
+        let buf = &mut buf[..TouchReport::<MAX>::burst_len(status.touch_count())];
+        if !buf.is_empty() {
+            self.read_register(register::GT911_TOUCH1_TRACK_ID, buf)?;
+        }
+
+        if self.clear_policy == ClearPolicy::AfterRead {
+            self.clear_status()?;
+        }
+        Ok(TouchReport::from_burst(buf, |point| self.transform_touch(point)))
     }
 
-    /// Enter gesture mode.
+    /// Query the touch status and all touch points in a single read,
+    /// then clear the status.
+    ///
+    /// Unlike [`GT911::query_touch_all`], every point comes from the same
+    /// frame, at the cost of always reading `MAX` point slots. The status is
+    /// only cleared if the frame is ready, following the [`ClearPolicy`].
     ///
     /// # Errors
     ///
     /// Returns an error if any I2C operation fails.
-    #[expect(clippy::type_complexity, reason = "Returning one of two types of `GT911`")]
-    pub fn gesture_mode(mut self) -> Result<GT911<I2C, Gesture>, (Self, GT911Error<I2C::Error>)> {
-        let result = self.write_register(register::GT911_COMMAND_CHECK, 0x8);
-        let result = result.and_then(|()| self.write_register(register::GT911_COMMAND, 0x8));
-        if let Err(err) = result {
-            return Err((self, err));
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error, Rotation, TouchPoint, TouchTransform};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// // Two touch points are ready
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x14E] = 0x82;
+    /// registers[0x14F..0x156].copy_from_slice(&[0, 0x10, 0x00, 0x20, 0x00, 0x08, 0x00]);
+    /// registers[0x157..0x15E].copy_from_slice(&[1, 0x30, 0x01, 0x40, 0x01, 0x0A, 0x00]);
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&registers), 0x5D);
+    ///
+    /// // One read and one write to clear the status
+    /// let frame = touch.query_touch_frame()?;
+    /// assert_eq!(touch.i2c().transactions, 2);
+    /// assert_eq!(touch.i2c().registers[0x14E], 0x00);
+    /// assert_eq!(frame.len(), 2);
+    /// assert_eq!(frame.points[1], Some(TouchPoint { point: 1, x: 0x130, y: 0x140, area: 10 }));
+    /// assert_eq!(frame.points[2], None);
+    ///
+    /// // A frame that is not ready is left for the device to finish
+    /// let frame = touch.query_touch_frame()?;
+    /// assert_eq!(touch.i2c().transactions, 3);
+    /// assert!(!frame.status.is_ready() && frame.is_empty());
+    ///
+    /// // Points are moved onto the display once a transform is set
+    /// touch.i2c_mut().registers[0x14E] = 0x82;
+    /// touch.set_transform(Some(
+    ///     TouchTransform::new((480, 800), (800, 480)).with_rotation(Rotation::Deg90),
+    /// ));
+    /// let frame = touch.query_touch_frame()?;
+    /// assert_eq!(frame.points[1], Some(TouchPoint { point: 1, x: 479, y: 0x130, area: 10 }));
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    pub fn query_touch_frame(&mut self) -> Result<TouchFrame, GT911Error<I2C::Error>> {
+        let mut buf = [0u8; TouchFrame::burst_len(crate::MAX_TOUCH_POINTS)];
+        let buf = &mut buf[..TouchFrame::burst_len(MAX)];
+        self.read_register(register::GT911_STATUS, buf)?;
+
+        let frame = TouchFrame::from_burst(buf, |point| self.transform_touch(point));
+        if frame.status.is_ready() && self.clear_policy == ClearPolicy::AfterRead {
+            self.clear_status()?;
         }
+        Ok(frame)
+    }
 
-        let mut gesture: GT911<I2C, Gesture> =
-            GT911 { i2c: self.i2c, address: self.address, _mode: PhantomData };
+    /// Query which touch keys are pressed.
+    ///
+    /// The `Key_Value` register is only read while the status reports a
+    /// touch key, otherwise no keys are pressed. The status is not cleared,
+    /// so keys can be queried alongside [`GT911::query_touch_all`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any I2C operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error, TouchKeys};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// // The first and third keys are pressed
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x14E] = 0x90;
+    /// registers[0x17F] = 0b0101;
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&registers), 0x5D);
+    /// assert_eq!(touch.query_touch_keys()?, TouchKeys::KEY1 | TouchKeys::KEY3);
+    /// assert_eq!(touch.i2c().registers[0x14E], 0x90);
+    ///
+    /// // A stale key value is ignored once the status reports no keys
+    /// touch.i2c_mut().registers[0x14E] = 0x80;
+    /// assert!(touch.query_touch_keys()?.is_empty());
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    pub fn query_touch_keys(&mut self) -> Result<TouchKeys, GT911Error<I2C::Error>> {
+        let status = self.query_touch_status()?;
+        if !status.is_ready() || !status.has_touch_key() {
+            return Ok(TouchKeys::empty());
+        }
+
+        let mut keys = [0u8; 1];
+        self.read_register(register::GT911_KEY_VALUE, &mut keys)?;
+        Ok(TouchKeys::from_bits_truncate(keys[0]))
+    }
 
-        // Use `init` to verify the mode switch
-        match gesture.init() {
-            Ok(()) => Ok(gesture),
-            Err(err) => {
-                Err((GT911 { i2c: gesture.i2c, address: gesture.address, _mode: PhantomData }, err))
+    /// Wait for the INT pin to signal new touch data, then read the
+    /// [`TouchFrame`] and clear the status.
+    ///
+    /// The pin is polled every millisecond, and `trigger` must match the
+    /// [`GT911Config::interrupt_trigger`] of the device. Frames that are not
+    /// ready are discarded and the pin is polled again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading the pin or any I2C operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error, InterruptTrigger, TouchPoint};
+    /// use embedded_hal::{
+    ///     delay::DelayNs,
+    ///     digital::{ErrorKind, ErrorType, InputPin},
+    ///     i2c,
+    /// };
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// /// An INT pin replaying a list of levels, one per read.
+    /// struct Int(Vec<bool>);
+    ///
+    /// impl ErrorType for Int {
+    ///     type Error = ErrorKind;
+    /// }
+    ///
+    /// impl InputPin for Int {
+    ///     fn is_high(&mut self) -> Result<bool, ErrorKind> { Ok(self.0.remove(0)) }
+    ///
+    ///     fn is_low(&mut self) -> Result<bool, ErrorKind> { self.is_high().map(|high| !high) }
+    /// }
+    ///
+    /// struct NoDelay;
+    ///
+    /// impl DelayNs for NoDelay {
+    ///     fn delay_ns(&mut self, _: u32) {}
+    /// }
+    ///
+    /// // One touch point is ready
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x14E] = 0x81;
+    /// registers[0x14F..0x156].copy_from_slice(&[0, 0x10, 0x00, 0x20, 0x00, 0x08, 0x00]);
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&registers), 0x5D);
+    ///
+    /// // The bus is only used once the falling edge is seen
+    /// let mut int = Int(vec![true, true, true, false]);
+    /// let frame = touch.wait_for_touch(&mut int, &mut NoDelay, InterruptTrigger::Falling)?;
+    /// assert!(int.0.is_empty());
+    /// assert_eq!(touch.i2c().transactions, 2);
+    /// assert_eq!(frame.points[0], Some(TouchPoint { point: 0, x: 0x10, y: 0x20, area: 8 }));
+    /// assert_eq!(touch.i2c().registers[0x14E], 0x00);
+    /// # Ok::<(), GT911Error<i2c::ErrorKind>>(())
+    /// ```
+    pub fn wait_for_touch<INT: InputPin, D: DelayNs>(
+        &mut self,
+        int: &mut INT,
+        delay: &mut D,
+        trigger: InterruptTrigger,
+    ) -> Result<TouchFrame, GT911Error<I2C::Error>> {
+        let mut previous = int.is_high().map_err(|err| GT911Error::Pin(err.kind()))?;
+        loop {
+            delay.delay_ms(1);
+            let level = int.is_high().map_err(|err| GT911Error::Pin(err.kind()))?;
+            if trigger.is_triggered(core::mem::replace(&mut previous, level), level) {
+                let frame = self.query_touch_frame()?;
+                if frame.status.is_ready() {
+                    return Ok(frame);
+                }
             }
         }
     }
+
+    /// Enter gesture mode.
+    ///
+    /// The gesture command is written to the command check register and then
+    /// the command register, retried up to 5 times 10 ms apart. The device
+    /// confirms the switch by reporting the gesture product ID, which is
+    /// polled for up to 50 ms.
+    ///
+    /// Gesture mode is left with [`GT911::device_reset`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the driver in touch mode and an error if the device is asleep
+    /// or must be reset, if the command is never acknowledged, or if the
+    /// device does not confirm the switch.
+    ///
+    /// Once the command was acknowledged the device may be in either mode,
+    /// so reads of the returned driver fail with [`GT911Error::NeedsReset`]
+    /// until it is reset with [`GT911::device_reset`]. Waking the device
+    /// does not clear this.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Address, GT911Error};
+    /// use embedded_hal::{
+    ///     delay::DelayNs,
+    ///     digital::{self, ErrorType, OutputPin},
+    ///     i2c::ErrorKind,
+    /// };
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// /// A delay that records every wait, in milliseconds.
+    /// #[derive(Default)]
+    /// struct Clock(Vec<u32>);
+    ///
+    /// impl DelayNs for Clock {
+    ///     fn delay_ns(&mut self, ns: u32) { self.0.push(ns / 1_000_000); }
+    /// }
+    ///
+    /// /// A pin that ignores every change.
+    /// struct Pin;
+    ///
+    /// impl ErrorType for Pin {
+    ///     type Error = digital::ErrorKind;
+    /// }
+    ///
+    /// impl OutputPin for Pin {
+    ///     fn set_low(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    ///
+    ///     fn set_high(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// }
+    ///
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x140..0x144].copy_from_slice(b"911\0");
+    ///
+    /// // The command is checked, sent and confirmed
+    /// let touch: GT911<_> = GT911::new(Registers::new(&registers), 0x5D);
+    /// let Ok(gesture) = touch.gesture_mode(&mut Clock::default()) else { unreachable!() };
+    /// assert_eq!((gesture.i2c().registers[0x46], gesture.i2c().registers[0x40]), (0x08, 0x08));
+    ///
+    /// // A device that never confirms the switch must be reset first
+    /// let touch: GT911<_> = GT911::new(Registers::new(&registers).without_gestures(), 0x5D);
+    /// let mut clock = Clock::default();
+    /// let Err((mut touch, err)) = touch.gesture_mode(&mut clock) else { unreachable!() };
+    /// assert!(matches!(err, GT911Error::ProductIdMismatch(id, _) if id == *b"911\0"));
+    /// assert_eq!(clock.0.iter().sum::<u32>(), 50);
+    /// assert!(touch.needs_reset() && !touch.is_asleep());
+    /// assert_eq!(touch.query_touch_status(), Err(GT911Error::NeedsReset));
+    ///
+    /// // Waking the device is not enough, only a reset clears it
+    /// touch.wake(&mut Pin, &mut clock)?;
+    /// assert_eq!(touch.query_touch_status(), Err(GT911Error::NeedsReset));
+    /// touch.device_reset(&mut Pin, &mut Pin, &mut clock, GT911Address::Primary)?;
+    /// assert!(!touch.needs_reset() && touch.query_touch_status().is_ok());
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    #[expect(clippy::type_complexity, reason = "Returning one of two types of `GT911`")]
+    pub fn gesture_mode<D: DelayNs>(
+        mut self,
+        delay: &mut D,
+    ) -> Result<GT911<I2C, Gesture, MAX>, (Self, GT911Error<I2C::Error>)> {
+        if self.needs_reset {
+            return Err((self, GT911Error::NeedsReset));
+        } else if self.asleep {
+            return Err((self, GT911Error::Asleep));
+        }
+
+        // Send the command, retrying while it is not acknowledged
+        let mut attempt = 1;
+        loop {
+            let result = self.write_register(register::GT911_COMMAND_CHECK, reset::GESTURE_COMMAND);
+            match result
+                .and_then(|()| self.write_register(register::GT911_COMMAND, reset::GESTURE_COMMAND))
+            {
+                Ok(()) => break,
+                Err(err) if attempt == reset::GESTURE_ATTEMPTS => return Err((self, err)),
+                Err(_) => attempt += 1,
+            }
+            delay.delay_ms(reset::GESTURE_RETRY_MS);
+        }
+
+        // Wait for the device to confirm the switch
+        let mut gesture: GT911<I2C, Gesture, MAX> = self.into_mode();
+        let mut waits = reset::ready_backoff(reset::GESTURE_CONFIRM_MS);
+        let err = loop {
+            match gesture.init() {
+                Ok(_) => return Ok(gesture),
+                Err(err @ GT911Error::ProductIdMismatch(..)) => match waits.next() {
+                    Some(wait) => delay.delay_ms(wait),
+                    None => break err,
+                },
+                Err(err) => break err,
+            }
+        };
+
+        // The device may be in either mode, so it must be reset
+        let mut touch: Self = gesture.into_mode();
+        touch.needs_reset = true;
+        Err((touch, err))
+    }
 }
 
-impl<I2C: I2c> GT911<I2C, Gesture> {
+impl<I2C: I2c, const MAX: usize> GT911<I2C, Gesture, MAX> {
     /// Initialize the GT911 device.
     ///
+    /// Returns the [`DeviceInfo`], including the configured resolution
+    /// used to scale coordinates.
+    ///
     /// # Errors
     ///
     /// Returns an error if the device is not ready, if the product ID does not
     /// match, or if any I2C operation fails.
-    pub fn init(&mut self) -> Result<(), GT911Error<I2C::Error>> {
-        let (id, version) = self.device_info()?;
-        if id == [b'G', b'E', b'S', b'T'] {
-            Ok(())
+    pub fn init(&mut self) -> Result<DeviceInfo, GT911Error<I2C::Error>> {
+        let info = self.query_device_info()?;
+        if info.product_id == *b"GEST" {
+            Ok(info)
         } else {
             // Return that the product ID does not match
-            Err(GT911Error::ProductIdMismatch(id, version))
+            Err(GT911Error::ProductIdMismatch(info.product_id, info.firmware_version))
         }
     }
 
-    /// Reset the device, exiting gesture mode.
+    /// Reset the device using its RST and INT pins, exiting gesture mode.
+    ///
+    /// See [`GT911::device_reset`].
     ///
     /// # Errors
     ///
-    /// Returns an error if any I2C operation fails.
+    /// Returns the driver and an error if driving either pin fails.
     #[expect(clippy::type_complexity, reason = "Returning one of two types of `GT911`")]
-    pub fn device_reset(self) -> Result<GT911<I2C, Touch>, (Self, GT911Error<I2C::Error>)> {
-        todo!()
+    pub fn device_reset<RST: OutputPin, INT: OutputPin, D: DelayNs>(
+        mut self,
+        rst: &mut RST,
+        int: &mut INT,
+        delay: &mut D,
+        address: GT911Address,
+    ) -> Result<GT911<I2C, Touch, MAX>, (Self, GT911Error<I2C::Error>)> {
+        match reset(rst, int, delay, address) {
+            Ok(()) => {
+                self.address = address.address();
+                self.asleep = false;
+                self.needs_reset = false;
+                Ok(self.into_mode())
+            }
+            Err(err) => Err((self, GT911Error::Pin(err))),
+        }
     }
 
     /// Query the detected gesture.
     ///
+    /// Swipes are remapped by the configured
+    /// [`Orientation`](crate::Orientation), the gesture reported by the
+    /// panel is kept as [`OrientedGesture::raw`]. A detected gesture is
+    /// cleared after it is read, following the [`ClearPolicy`].
+    ///
     /// # Errors
     ///
     /// Returns an error if any I2C operation fails.
-    pub fn query_gesture(&mut self) -> Result<DetectedGesture, GT911Error<I2C::Error>> {
-        command_mode!(self, Gesture, {
-            // Query the gesture register
-            let mut buf = [0u8; 1];
-            self.read_register(register::GT911_GESTURE_STATUS, &mut buf)?;
-            Ok(DetectedGesture::from_byte(buf[0]))
-        })
+    pub fn query_gesture(&mut self) -> Result<OrientedGesture, GT911Error<I2C::Error>> {
+        // Query the gesture register
+        let mut buf = [0u8; 1];
+        self.read_register(register::GT911_GESTURE_STATUS, &mut buf)?;
+        let gesture = DetectedGesture::from_byte(buf[0]);
+
+        if gesture.is_any() && self.clear_policy == ClearPolicy::AfterRead {
+            self.clear_status()?;
+        }
+        Ok(OrientedGesture::new(gesture, self.orientation))
     }
 
     /// Query the number of gesture touch points.
@@ -245,12 +1157,10 @@ impl<I2C: I2c> GT911<I2C, Gesture> {
     ///
     /// Returns an error if any I2C operation fails.
     pub fn query_gesture_point_count(&mut self) -> Result<u8, GT911Error<I2C::Error>> {
-        command_mode!(self, Gesture, {
-            // Query the gesture point count register
-            let mut buf = [0u8; 1];
-            self.read_register(register::GT911_GESTURE_TOUCH_POINTS, &mut buf)?;
-            Ok(buf[0])
-        })
+        // Query the gesture point count register
+        let mut buf = [0u8; 1];
+        self.read_register(register::GT911_GESTURE_TOUCH_POINTS, &mut buf)?;
+        Ok(buf[0])
     }
 
     /// Query a specific gesture point's data.
@@ -274,27 +1184,153 @@ impl<I2C: I2c> GT911<I2C, Gesture> {
             _ => unreachable!("Point index out of range"),
         };
 
-        command_mode!(self, Gesture, {
-            // Query the gesture touch point register
-            let mut buf = [0u8; 4];
-            self.read_register(register, &mut buf)?;
-            Ok(GesturePoint::from_bytes(buf))
-        })
+        // Query the gesture touch point register
+        let mut buf = [0u8; 4];
+        self.read_register(register, &mut buf)?;
+        Ok(self.transform_gesture(GesturePoint::from_bytes(buf)))
     }
 
     /// Query all gesture touch points.
     ///
+    /// Prefer [`GT911::read_gesture_track`],
+    /// which does not need a buffer for every point.
+    ///
     /// # Errors
     ///
     /// Returns an error if any I2C operation fails.
     pub fn query_gesture_point_all(
         &mut self,
     ) -> Result<[Option<GesturePoint>; 64], GT911Error<I2C::Error>> {
-        let count = self.query_gesture_point_count()?;
+        let mut buf = [0u8; GestureTrack::BUFFER_LEN];
         let mut points = [None; 64];
-        for i in 0..count {
-            points[i as usize] = Some(self.query_gesture_point(i)?);
+        for (slot, point) in points.iter_mut().zip(self.read_gesture_track(&mut buf)?) {
+            *slot = Some(point);
         }
         Ok(points)
     }
+
+    /// Read the gesture points into `buf` in a single read,
+    /// returning a [`GestureTrack`] that decodes them as it is iterated.
+    ///
+    /// Each point takes [`GestureTrack::POINT_LEN`] bytes. If `buf` is too
+    /// small for every reported point, only the first points are read, see
+    /// [`GestureTrack::is_truncated`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any I2C operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error, GesturePoint, GestureTrack};
+    /// use embedded_hal::{delay::DelayNs, i2c::ErrorKind};
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// struct Delay;
+    ///
+    /// impl DelayNs for Delay {
+    ///     fn delay_ns(&mut self, _: u32) {}
+    /// }
+    ///
+    /// // A track of 40 points along a diagonal
+    /// let mut registers = vec![0u8; 0x1520];
+    /// registers[0x140..0x144].copy_from_slice(b"911\0");
+    /// registers[0x14C] = 40;
+    /// for index in 0..40u16 {
+    ///     let start = 0x1420 + usize::from(index) * 4;
+    ///     registers[start..start + 2].copy_from_slice(&(index * 10).to_le_bytes());
+    ///     registers[start + 2..start + 4].copy_from_slice(&(index * 20).to_le_bytes());
+    /// }
+    ///
+    /// let touch: GT911<_> = GT911::new(Registers::new(&registers), 0x5D);
+    /// let Ok(mut gesture) = touch.gesture_mode(&mut Delay) else { unreachable!() };
+    /// gesture.i2c_mut().reads.clear();
+    ///
+    /// // The points are read at once, and decoded lazily
+    /// let mut buf = [0u8; GestureTrack::BUFFER_LEN];
+    /// let track = gesture.read_gesture_track(&mut buf)?;
+    /// assert_eq!((track.len(), track.is_truncated()), (40, false));
+    /// assert!(
+    ///     track.enumerate().all(|(i, p)| p == GesturePoint { x: i as u16 * 10, y: i as u16 * 20 })
+    /// );
+    /// assert_eq!(gesture.i2c().reads, [(0x814C, 1), (0x9420, 160)]);
+    ///
+    /// // A smaller buffer only holds the first points
+    /// let mut buf = [0u8; 64];
+    /// let track = gesture.read_gesture_track(&mut buf)?;
+    /// assert_eq!((track.reported(), track.len(), track.is_truncated()), (40, 16, true));
+    /// assert_eq!(track.last(), Some(GesturePoint { x: 150, y: 300 }));
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    pub fn read_gesture_track<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+    ) -> Result<GestureTrack<'b>, GT911Error<I2C::Error>> {
+        let count = self.query_gesture_point_count()?;
+        let len = GestureTrack::read_len(count, buf.len());
+        let data = &mut buf[..len];
+        if !data.is_empty() {
+            self.read_register(register::GT911_GESTURE_POINT1_X_LSB, data)?;
+        }
+        Ok(GestureTrack::new(data, count, self.transform))
+    }
+}
+
+impl<I2C: I2c, MODE: GT911Mode, const MAX: usize> Identify for GT911<I2C, MODE, MAX> {
+    type Error = GT911Error<I2C::Error>;
+
+    /// Read the product ID and firmware version,
+    /// see [`GT911::query_device_info`].
+    ///
+    /// A GT911 reports `911\0` in touch mode, and `GEST` in gesture mode.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_driver_common::identify_all;
+    /// use ef_gt911::GT911;
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x140..0x146].copy_from_slice(b"911\0\x60\x10");
+    ///
+    /// // Only the device at the primary address is present
+    /// let bus = Registers::new(&registers).with_address(0x5D);
+    /// let mut primary: GT911<_> = GT911::new(bus.clone(), 0x5D);
+    /// let mut secondary: GT911<_> = GT911::new(bus, 0x14);
+    /// let [primary, secondary] = identify_all!(primary, secondary);
+    ///
+    /// let identity = primary.unwrap();
+    /// assert_eq!((identity.name(), identity.id()), ("GT911", &b"911\0"[..]));
+    /// assert_eq!(identity.version(), Some(0x1060));
+    /// assert!(secondary.is_none());
+    /// ```
+    fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> {
+        let info = self.query_device_info()?;
+        Ok(DeviceIdentity::new("GT911", &info.product_id, Some(info.firmware_version)))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Run the reset sequence selecting `address`.
+///
+/// # Errors
+///
+/// Returns an error if driving either pin fails.
+fn reset<RST: OutputPin, INT: OutputPin, D: DelayNs>(
+    rst: &mut RST,
+    int: &mut INT,
+    delay: &mut D,
+    address: GT911Address,
+) -> Result<(), ErrorKind> {
+    for step in reset::reset_steps(address) {
+        match step {
+            ResetStep::Rst(state) => rst.set_state(state).map_err(|err| err.kind())?,
+            ResetStep::Int(state) => int.set_state(state).map_err(|err| err.kind())?,
+            ResetStep::DelayUs(us) => delay.delay_us(us),
+        }
+    }
+    Ok(())
 }
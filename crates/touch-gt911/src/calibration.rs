@@ -0,0 +1,409 @@
+//! Capturing calibration points and solving for an affine correction.
+
+use crate::TouchPoint;
+
+/// Captures raw touch coordinates for a set of on-screen targets.
+///
+/// Show each target in turn and pass the points of every touch report to
+/// [`CalibrationCapture::record`]. A target is captured once a touch is held
+/// within the jitter radius for the required number of reports, and the next
+/// target is only accepted after the touch is released.
+///
+/// Distances are measured as the largest difference along either axis.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_gt911::{CalibrationCapture, CaptureProgress, TouchPoint};
+///
+/// let targets = [(48, 48), (432, 48), (432, 432), (48, 432), (240, 240)];
+/// let mut capture = CalibrationCapture::new(targets).with_tolerance(100);
+///
+/// // A panel that is stretched, skewed, and offset
+/// let distort = |(x, y): (u16, u16)| {
+///     let (x, y) = (f32::from(x), f32::from(y));
+///     ((1.1 * x + 0.05 * y + 20.0) as u16, (-0.03 * x + 0.95 * y + 10.0) as u16)
+/// };
+/// let touch = |(x, y)| [TouchPoint { point: 0, x, y, area: 20 }];
+///
+/// for (index, target) in targets.into_iter().enumerate() {
+///     assert_eq!(capture.target(), Some(target));
+///
+///     // Hold the touch until it is stable, wobbling by a unit
+///     let (x, y) = distort(target);
+///     for sample in 1..CalibrationCapture::DEFAULT_SAMPLES {
+///         let progress = capture.record(&touch((x + u16::from(sample % 2), y)));
+///         assert_eq!(progress, CaptureProgress::Stabilizing { target: index, samples: sample });
+///     }
+///     let progress = capture.record(&touch((x, y)));
+///     assert_eq!(progress, CaptureProgress::Captured(index));
+///
+///     // Lift the finger before the next target
+///     capture.record(&[]);
+/// }
+/// assert!(capture.is_complete());
+///
+/// // The solved calibration undoes the distortion
+/// let calibration = capture.solve()?;
+/// for target in [(0, 0), (100, 300), (240, 240), (479, 479)] {
+///     let (x, y) = distort(target);
+///     let point = calibration.apply(TouchPoint { point: 0, x, y, area: 20 });
+///     assert!(point.x.abs_diff(target.0) <= 1 && point.y.abs_diff(target.1) <= 1);
+/// }
+/// # Ok::<(), ef_gt911::CalibrationError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CalibrationCapture {
+    expected: [(u16, u16); 5],
+    captured: [(u16, u16); 5],
+    /// The index of the current target.
+    index: usize,
+    /// The maximum distance between a touch and its target.
+    tolerance: u16,
+    /// The maximum distance between stable samples.
+    jitter: u16,
+    /// The number of stable samples required to capture a target.
+    samples: u8,
+    /// The first stable sample and the running totals.
+    stable: Option<StableTouch>,
+    /// Whether the touch must be released before the next target.
+    held: bool,
+}
+
+impl CalibrationCapture {
+    /// The default maximum distance between stable samples.
+    pub const DEFAULT_JITTER: u16 = 4;
+    /// The default number of stable samples required.
+    pub const DEFAULT_SAMPLES: u8 = 5;
+    /// The default maximum distance between a touch and its target.
+    pub const DEFAULT_TOLERANCE: u16 = 64;
+
+    /// Create a new [`CalibrationCapture`] for the given targets.
+    ///
+    /// The targets are in display coordinates, and at least
+    /// three of them must not lie on the same line.
+    #[must_use]
+    pub const fn new(expected_points: [(u16, u16); 5]) -> Self {
+        Self {
+            expected: expected_points,
+            captured: [(0, 0); 5],
+            index: 0,
+            tolerance: Self::DEFAULT_TOLERANCE,
+            jitter: Self::DEFAULT_JITTER,
+            samples: Self::DEFAULT_SAMPLES,
+            stable: None,
+            held: false,
+        }
+    }
+
+    /// Set the maximum distance between a raw touch and its target.
+    ///
+    /// This should be larger than the expected distortion of the panel.
+    #[inline]
+    #[must_use]
+    pub const fn with_tolerance(mut self, tolerance: u16) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Set how many reports within `jitter` of each other make a stable touch.
+    ///
+    /// A `samples` value of `0` is treated as `1`.
+    #[inline]
+    #[must_use]
+    pub const fn with_stability(mut self, jitter: u16, samples: u8) -> Self {
+        self.jitter = jitter;
+        self.samples = if samples == 0 { 1 } else { samples };
+        self
+    }
+
+    /// Get the target that is currently being captured.
+    ///
+    /// Returns `None` once all targets have been captured.
+    #[inline]
+    #[must_use]
+    pub const fn target(&self) -> Option<(u16, u16)> {
+        if self.index < self.expected.len() { Some(self.expected[self.index]) } else { None }
+    }
+
+    /// Get the raw coordinates captured so far, in target order.
+    #[inline]
+    #[must_use]
+    pub fn captured(&self) -> &[(u16, u16)] { &self.captured[..self.index] }
+
+    /// Returns `true` if all targets have been captured.
+    #[inline]
+    #[must_use]
+    pub const fn is_complete(&self) -> bool { self.index == self.expected.len() }
+
+    /// Discard all captured points and start over.
+    pub const fn reset(&mut self) {
+        self.index = 0;
+        self.stable = None;
+        self.held = false;
+    }
+
+    /// Record the points of a touch report, using the first touch point.
+    pub fn record(&mut self, touches: &[TouchPoint]) -> CaptureProgress {
+        let touch = touches.first().copied();
+
+        let Some((x, y)) = self.target() else { return CaptureProgress::Complete };
+        let Some(touch) = touch else {
+            self.stable = None;
+            self.held = false;
+            return CaptureProgress::Waiting(self.index);
+        };
+
+        // Wait for the previous touch to be released.
+        if self.held {
+            return CaptureProgress::Captured(self.index - 1);
+        }
+
+        if touch.x.abs_diff(x).max(touch.y.abs_diff(y)) > self.tolerance {
+            self.stable = None;
+            return CaptureProgress::OutOfRange(self.index);
+        }
+
+        let stable = match self.stable {
+            Some(stable) if stable.is_near(touch, self.jitter) => stable.push(touch),
+            _ => StableTouch::new(touch),
+        };
+
+        if stable.count < self.samples {
+            self.stable = Some(stable);
+            return CaptureProgress::Stabilizing { target: self.index, samples: stable.count };
+        }
+
+        self.captured[self.index] = stable.average();
+        self.index += 1;
+        self.stable = None;
+        self.held = true;
+
+        CaptureProgress::Captured(self.index - 1)
+    }
+
+    /// Solve for the [`AffineCalibration`] mapping raw coordinates to targets.
+    ///
+    /// The transform is fitted with least squares over all five points.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if not all targets have been captured,
+    /// or if the targets or captured points lie on a single line.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{CalibrationCapture, CalibrationError, TouchPoint};
+    ///
+    /// // Targets on a diagonal cannot describe a two-dimensional transform
+    /// let targets = [(0, 0), (100, 100), (200, 200), (300, 300), (400, 400)];
+    /// let mut capture = CalibrationCapture::new(targets).with_stability(0, 1);
+    /// assert_eq!(capture.solve(), Err(CalibrationError::Incomplete(0)));
+    ///
+    /// for (x, y) in targets {
+    ///     capture.record(&[TouchPoint { point: 0, x, y, area: 20 }]);
+    ///     capture.record(&[]);
+    /// }
+    /// assert_eq!(capture.solve(), Err(CalibrationError::Degenerate));
+    /// ```
+    #[expect(clippy::many_single_char_names, reason = "Matches the documented coefficients")]
+    pub fn solve(&self) -> Result<AffineCalibration, CalibrationError> {
+        if !self.is_complete() {
+            return Err(CalibrationError::Incomplete(self.index));
+        }
+
+        let raw = Scatter::new(&self.captured);
+        let expected = Scatter::new(&self.expected);
+        if raw.is_degenerate() || expected.is_degenerate() {
+            return Err(CalibrationError::Degenerate);
+        }
+
+        // Covariances between the centered raw and expected coordinates.
+        let (mut sxu, mut syu, mut sxv, mut syv) = (0f32, 0f32, 0f32, 0f32);
+        for (&(x, y), &(u, v)) in self.captured.iter().zip(&self.expected) {
+            let (x, y) = (f32::from(x) - raw.mean.0, f32::from(y) - raw.mean.1);
+            let (u, v) = (f32::from(u) - expected.mean.0, f32::from(v) - expected.mean.1);
+            sxu += x * u;
+            syu += y * u;
+            sxv += x * v;
+            syv += y * v;
+        }
+
+        // Solve the 2x2 normal equations for each output axis.
+        let det = raw.determinant();
+        let a = (sxu * raw.syy - syu * raw.sxy) / det;
+        let b = (syu * raw.sxx - sxu * raw.sxy) / det;
+        let d = (sxv * raw.syy - syv * raw.sxy) / det;
+        let e = (syv * raw.sxx - sxv * raw.sxy) / det;
+        let c = expected.mean.0 - a * raw.mean.0 - b * raw.mean.1;
+        let f = expected.mean.1 - d * raw.mean.0 - e * raw.mean.1;
+
+        Ok(AffineCalibration::new([a, b, c, d, e, f]))
+    }
+}
+
+/// The progress of a [`CalibrationCapture`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CaptureProgress {
+    /// Waiting for a touch near the target.
+    Waiting(usize),
+    /// The touch was too far away from the target.
+    OutOfRange(usize),
+    /// The touch is being held near the target.
+    Stabilizing {
+        /// The index of the target.
+        target: usize,
+        /// The number of stable samples so far.
+        samples: u8,
+    },
+    /// The target was captured, waiting for the touch to be released.
+    Captured(usize),
+    /// All targets have been captured.
+    ///
+    /// Returned by every report after the last target was captured.
+    Complete,
+}
+
+/// An error that can occur when solving a calibration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CalibrationError {
+    /// Only this many targets have been captured.
+    Incomplete(usize),
+    /// The points lie on a single line, or too close to one.
+    Degenerate,
+}
+
+/// An affine transform from raw touch coordinates to display coordinates.
+///
+/// ```text
+/// x' = a * x + b * y + c
+/// y' = d * x + e * y + f
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AffineCalibration {
+    coefficients: [f32; 6],
+}
+
+impl AffineCalibration {
+    /// A calibration that does not change any coordinates.
+    pub const IDENTITY: Self = Self::new([1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+
+    /// Create an [`AffineCalibration`] from `[a, b, c, d, e, f]`.
+    ///
+    /// Useful for restoring a previously solved calibration.
+    #[inline]
+    #[must_use]
+    pub const fn new(coefficients: [f32; 6]) -> Self { Self { coefficients } }
+
+    /// Get the coefficients as `[a, b, c, d, e, f]`.
+    #[inline]
+    #[must_use]
+    pub const fn coefficients(&self) -> [f32; 6] { self.coefficients }
+
+    /// Apply the calibration to a pair of coordinates.
+    ///
+    /// Results are rounded and clamped to the range of a `u16`.
+    #[must_use]
+    #[expect(clippy::many_single_char_names, reason = "Matches the documented coefficients")]
+    pub fn apply_xy(&self, x: u16, y: u16) -> (u16, u16) {
+        let [a, b, c, d, e, f] = self.coefficients;
+        let (x, y) = (f32::from(x), f32::from(y));
+        (to_coordinate(a * x + b * y + c), to_coordinate(d * x + e * y + f))
+    }
+
+    /// Apply the calibration to a [`TouchPoint`].
+    #[must_use]
+    pub fn apply(&self, point: TouchPoint) -> TouchPoint {
+        let (x, y) = self.apply_xy(point.x, point.y);
+        TouchPoint { x, y, ..point }
+    }
+}
+
+impl Default for AffineCalibration {
+    fn default() -> Self { Self::IDENTITY }
+}
+
+/// Round and clamp a value to a coordinate.
+#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "Clamped")]
+fn to_coordinate(value: f32) -> u16 { (value.clamp(0.0, f32::from(u16::MAX)) + 0.5) as u16 }
+
+// -------------------------------------------------------------------------------------------------
+
+/// A touch that has been held in place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct StableTouch {
+    first: (u16, u16),
+    sum: (u32, u32),
+    count: u8,
+}
+
+impl StableTouch {
+    fn new(touch: TouchPoint) -> Self {
+        Self { first: (touch.x, touch.y), sum: (u32::from(touch.x), u32::from(touch.y)), count: 1 }
+    }
+
+    fn is_near(&self, touch: TouchPoint, jitter: u16) -> bool {
+        touch.x.abs_diff(self.first.0).max(touch.y.abs_diff(self.first.1)) <= jitter
+    }
+
+    fn push(mut self, touch: TouchPoint) -> Self {
+        self.sum.0 += u32::from(touch.x);
+        self.sum.1 += u32::from(touch.y);
+        self.count = self.count.saturating_add(1);
+        self
+    }
+
+    #[expect(clippy::cast_possible_truncation, reason = "The average of `u16` values")]
+    fn average(&self) -> (u16, u16) {
+        let count = u32::from(self.count);
+        (((self.sum.0 + count / 2) / count) as u16, ((self.sum.1 + count / 2) / count) as u16)
+    }
+}
+
+/// The mean and centered second moments of a set of points.
+struct Scatter {
+    mean: (f32, f32),
+    sxx: f32,
+    syy: f32,
+    sxy: f32,
+}
+
+impl Scatter {
+    /// The smallest allowed `sin²` of the spread of the points.
+    const MIN_SPREAD: f32 = 0.01;
+
+    #[expect(clippy::cast_precision_loss, reason = "Only a handful of points")]
+    fn new(points: &[(u16, u16)]) -> Self {
+        let count = points.len() as f32;
+        let (sum_x, sum_y) = points
+            .iter()
+            .fold((0f32, 0f32), |(sx, sy), &(x, y)| (sx + f32::from(x), sy + f32::from(y)));
+        let mean = (sum_x / count, sum_y / count);
+
+        let (mut sxx, mut syy, mut sxy) = (0f32, 0f32, 0f32);
+        for &(x, y) in points {
+            let (x, y) = (f32::from(x) - mean.0, f32::from(y) - mean.1);
+            sxx += x * x;
+            syy += y * y;
+            sxy += x * y;
+        }
+        Self { mean, sxx, syy, sxy }
+    }
+
+    fn determinant(&self) -> f32 { self.sxx * self.syy - self.sxy * self.sxy }
+
+    /// Returns `true` if the points are (close to) collinear.
+    ///
+    /// The determinant is compared relative to the spread along each axis,
+    /// so the check does not depend on the scale of the coordinates.
+    fn is_degenerate(&self) -> bool {
+        let spread = self.sxx * self.syy;
+        spread <= 0.0 || self.determinant() <= spread * Self::MIN_SPREAD
+    }
+}
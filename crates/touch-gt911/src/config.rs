@@ -0,0 +1,310 @@
+//! The configuration registers, from `CONFIG_VERSION` to `CONFIG_CHECKSUM`.
+
+use crate::{CONFIG_LEN, register};
+
+/// A copy of the configuration registers of a GT911.
+///
+/// Read with `GT911::read_config`, changed with the typed accessors and
+/// written back with `GT911::write_config`, which updates the checksum.
+/// Registers without an accessor are kept as read, see
+/// [`GT911Config::as_bytes`].
+///
+/// Offsets and bit fields follow the configuration register table of the
+/// *GT911 Programming Guide*.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_gt911::{GT911Config, InterruptTrigger};
+///
+/// let mut config = GT911Config::from_bytes([0; GT911Config::LEN]);
+/// config.set_x_max(480);
+/// config.set_y_max(272);
+/// config.set_touch_points(5);
+/// config.set_interrupt_trigger(InterruptTrigger::Falling);
+/// config.set_refresh_rate_ms(10);
+///
+/// assert_eq!(config.as_bytes()[1..8], [0xE0, 0x01, 0x10, 0x01, 0x05, 0x01, 0x00]);
+/// assert_eq!((config.x_max(), config.y_max(), config.touch_points()), (480, 272, 5));
+/// assert_eq!(config.interrupt_trigger(), InterruptTrigger::Falling);
+/// assert_eq!(config.refresh_rate_ms(), 10);
+///
+/// // The stored checksum is only updated on request
+/// assert!(!config.has_valid_checksum());
+/// config.update_checksum();
+/// assert!(config.has_valid_checksum());
+/// assert_eq!(config.as_bytes().iter().fold(0u8, |sum, &b| sum.wrapping_add(b)), 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GT911Config {
+    image: [u8; CONFIG_LEN],
+}
+
+impl GT911Config {
+    /// The number of configuration registers, including the checksum.
+    pub const LEN: usize = CONFIG_LEN;
+
+    /// Create a [`GT911Config`] from the registers starting at
+    /// `CONFIG_VERSION` (`0x8047`), ending with the checksum.
+    #[inline]
+    #[must_use]
+    pub const fn from_bytes(image: [u8; CONFIG_LEN]) -> Self { Self { image } }
+
+    /// Get the registers starting at `CONFIG_VERSION` (`0x8047`),
+    /// ending with the checksum.
+    #[inline]
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; CONFIG_LEN] { &self.image }
+
+    /// Get a mutable reference to the registers, for registers without an
+    /// accessor.
+    #[inline]
+    #[must_use]
+    pub const fn as_bytes_mut(&mut self) -> &mut [u8; CONFIG_LEN] { &mut self.image }
+
+    /// Get the configuration version.
+    ///
+    /// The device only accepts a configuration with a version of at least
+    /// its own, or after a reset to `0x00`.
+    #[inline]
+    #[must_use]
+    pub const fn version(&self) -> u8 { self.image[offset(register::GT911_CONFIG_VERSION)] }
+
+    /// Set the configuration version.
+    #[inline]
+    pub const fn set_version(&mut self, version: u8) {
+        self.image[offset(register::GT911_CONFIG_VERSION)] = version;
+    }
+
+    /// Get the maximum X coordinate reported.
+    #[inline]
+    #[must_use]
+    pub const fn x_max(&self) -> u16 { self.get_u16(register::GT911_X_OUTPUT_MAX_LSB) }
+
+    /// Set the maximum X coordinate reported.
+    #[inline]
+    pub const fn set_x_max(&mut self, x_max: u16) {
+        self.set_u16(register::GT911_X_OUTPUT_MAX_LSB, x_max);
+    }
+
+    /// Get the maximum Y coordinate reported.
+    #[inline]
+    #[must_use]
+    pub const fn y_max(&self) -> u16 { self.get_u16(register::GT911_Y_OUTPUT_MAX_LSB) }
+
+    /// Set the maximum Y coordinate reported.
+    #[inline]
+    pub const fn set_y_max(&mut self, y_max: u16) {
+        self.set_u16(register::GT911_Y_OUTPUT_MAX_LSB, y_max);
+    }
+
+    /// Get the maximum number of touch points reported, from `1` to `5`.
+    #[inline]
+    #[must_use]
+    pub const fn touch_points(&self) -> u8 {
+        self.image[offset(register::GT911_TOUCH_POINTS)] & 0x0F
+    }
+
+    /// Set the maximum number of touch points reported,
+    /// clamped to `1` to `5`.
+    #[inline]
+    pub const fn set_touch_points(&mut self, points: u8) {
+        let points = if points < 1 {
+            1
+        } else if points > 5 {
+            5
+        } else {
+            points
+        };
+        let byte = &mut self.image[offset(register::GT911_TOUCH_POINTS)];
+        *byte = (*byte & 0xF0) | points;
+    }
+
+    /// Get how the INT pin signals new touch data.
+    #[inline]
+    #[must_use]
+    pub const fn interrupt_trigger(&self) -> InterruptTrigger {
+        InterruptTrigger::from_bits(self.image[offset(register::GT911_MODULE_SWITCH1)])
+    }
+
+    /// Set how the INT pin signals new touch data.
+    #[inline]
+    pub const fn set_interrupt_trigger(&mut self, trigger: InterruptTrigger) {
+        let byte = &mut self.image[offset(register::GT911_MODULE_SWITCH1)];
+        *byte = (*byte & !InterruptTrigger::MASK) | trigger as u8;
+    }
+
+    /// Get the interval between coordinate reports, in milliseconds.
+    #[inline]
+    #[must_use]
+    pub const fn refresh_rate_ms(&self) -> u8 {
+        5 + (self.image[offset(register::GT911_REFRESH_RATE)] & 0x0F)
+    }
+
+    /// Set the interval between coordinate reports, in milliseconds,
+    /// clamped to `5` to `20`.
+    #[inline]
+    pub const fn set_refresh_rate_ms(&mut self, ms: u8) {
+        let ms = if ms < 5 {
+            5
+        } else if ms > 20 {
+            20
+        } else {
+            ms
+        };
+        let byte = &mut self.image[offset(register::GT911_REFRESH_RATE)];
+        *byte = (*byte & 0xF0) | (ms - 5);
+    }
+
+    /// Get the threshold for a new touch.
+    #[inline]
+    #[must_use]
+    pub const fn touch_threshold(&self) -> u8 {
+        self.image[offset(register::GT911_TOUCH_THRESHOLD)]
+    }
+
+    /// Set the threshold for a new touch.
+    #[inline]
+    pub const fn set_touch_threshold(&mut self, threshold: u8) {
+        self.image[offset(register::GT911_TOUCH_THRESHOLD)] = threshold;
+    }
+
+    /// Get the threshold for a released touch.
+    #[inline]
+    #[must_use]
+    pub const fn release_threshold(&self) -> u8 {
+        self.image[offset(register::GT911_RELEASE_THRESHOLD)]
+    }
+
+    /// Set the threshold for a released touch.
+    #[inline]
+    pub const fn set_release_threshold(&mut self, threshold: u8) {
+        self.image[offset(register::GT911_RELEASE_THRESHOLD)] = threshold;
+    }
+
+    /// Get the checksum stored in the configuration.
+    #[inline]
+    #[must_use]
+    pub const fn stored_checksum(&self) -> u8 { self.image[CONFIG_LEN - 1] }
+
+    /// Calculate the checksum of the configuration.
+    #[inline]
+    #[must_use]
+    pub fn checksum(&self) -> u8 { config_checksum(&self.image) }
+
+    /// Returns `true` if the stored checksum matches the configuration.
+    #[inline]
+    #[must_use]
+    pub fn has_valid_checksum(&self) -> bool { self.stored_checksum() == self.checksum() }
+
+    /// Store the checksum of the configuration.
+    #[inline]
+    pub fn update_checksum(&mut self) { self.image[CONFIG_LEN - 1] = self.checksum(); }
+
+    /// Get a little-endian value starting at `register`.
+    const fn get_u16(&self, register: u16) -> u16 {
+        let index = offset(register);
+        u16::from_le_bytes([self.image[index], self.image[index + 1]])
+    }
+
+    /// Set a little-endian value starting at `register`.
+    const fn set_u16(&mut self, register: u16, value: u16) {
+        let index = offset(register);
+        let [lsb, msb] = value.to_le_bytes();
+        self.image[index] = lsb;
+        self.image[index + 1] = msb;
+    }
+}
+
+/// How the INT pin signals new touch data, bits 1-0 of `Module_Switch1`.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InterruptTrigger {
+    /// A rising edge.
+    Rising = 0b00,
+    /// A falling edge.
+    Falling = 0b01,
+    /// A low level, while touch data is ready.
+    LowLevel = 0b10,
+    /// A high level, while touch data is ready.
+    HighLevel = 0b11,
+}
+
+impl InterruptTrigger {
+    /// The bits of the trigger in `Module_Switch1`.
+    const MASK: u8 = 0b11;
+
+    /// Get the trigger from the bits of `Module_Switch1`.
+    const fn from_bits(bits: u8) -> Self {
+        match bits & Self::MASK {
+            0b00 => Self::Rising,
+            0b01 => Self::Falling,
+            0b10 => Self::LowLevel,
+            _ => Self::HighLevel,
+        }
+    }
+
+    /// Returns `true` if the INT pin changing from `previous` to `level`
+    /// signals new touch data, where `true` is high.
+    pub(crate) const fn is_triggered(self, previous: bool, level: bool) -> bool {
+        match self {
+            Self::Rising => !previous && level,
+            Self::Falling => previous && !level,
+            Self::LowLevel => !level,
+            Self::HighLevel => level,
+        }
+    }
+}
+
+/// Get the offset of a configuration register from `CONFIG_VERSION`.
+const fn offset(register: u16) -> usize { (register - register::GT911_CONFIG_VERSION) as usize }
+
+/// A change to a few consecutive configuration registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ConfigPatch {
+    register: u16,
+    data: [u8; ConfigPatch::MAX_LEN],
+    len: usize,
+}
+
+impl ConfigPatch {
+    /// The longest patch, in bytes.
+    pub(crate) const MAX_LEN: usize = 8;
+
+    /// Create a patch writing `data` at `register`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is longer than [`ConfigPatch::MAX_LEN`].
+    pub(crate) fn new(register: u16, data: &[u8]) -> Self {
+        let mut patch = Self { register, data: [0; Self::MAX_LEN], len: data.len() };
+        patch.data[..data.len()].copy_from_slice(data);
+        patch
+    }
+
+    /// Get the first register of the patch.
+    pub(crate) const fn register(&self) -> u16 { self.register }
+
+    /// Get the bytes written by the patch.
+    pub(crate) fn data(&self) -> &[u8] { &self.data[..self.len] }
+
+    /// Apply the patch to a configuration image read from `CONFIG_VERSION`,
+    /// returning `true` if it changed anything.
+    pub(crate) fn apply(&self, image: &mut [u8; CONFIG_LEN]) -> bool {
+        let start = offset(self.register);
+        let target = &mut image[start..start + self.len];
+        if target == self.data() {
+            return false;
+        }
+        target.copy_from_slice(self.data());
+        true
+    }
+}
+
+/// Calculate the checksum of a configuration image,
+/// the two's complement of the sum of every byte before the checksum.
+pub(crate) fn config_checksum(image: &[u8; CONFIG_LEN]) -> u8 {
+    image[..CONFIG_LEN - 1].iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)).wrapping_neg()
+}
@@ -1,6 +1,9 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
 
+#[cfg(feature = "test-util")]
+extern crate alloc;
+
 use core::marker::PhantomData;
 
 #[cfg(not(feature = "defmt"))]
@@ -10,23 +13,161 @@ use defmt::bitflags;
 
 mod r#async;
 mod blocking;
+mod calibration;
+mod config;
+mod multitouch;
+mod noise;
+mod orientation;
+mod predict;
+#[cfg(feature = "record")]
+pub mod record;
 mod register;
+mod reset;
+mod scale;
+#[cfg(feature = "test-util")]
+#[doc(hidden)]
+pub mod test_util;
+mod tracker;
+mod transform;
+mod variant;
+
+pub use calibration::{AffineCalibration, CalibrationCapture, CalibrationError, CaptureProgress};
+pub use config::{GT911Config, InterruptTrigger};
+pub use multitouch::{MultiTouchEvents, MultiTouchGesture, MultiTouchGestures, SCALE_ONE};
+pub use noise::{NoiseProfile, NoiseSettings};
+pub use orientation::{Orientation, OrientedGesture, Rotation};
+pub use predict::{PredictorConfig, TouchPredictor};
+pub use reset::GT911Address;
+pub use scale::{AxisScale, OutputScale};
+pub use tracker::{TouchEvent, TouchEvents, TouchTracker};
+use transform::PreparedTransform;
+pub use transform::TouchTransform;
+pub use variant::GT911Variant;
 
 /// A driver for a GT911 touch sensor.
-pub struct GT911<I2C, MODE = Touch> {
+///
+/// Reports hold at most `MAX` touch points, any further points detected by
+/// the device are ignored and never read from the bus.
+pub struct GT911<I2C, MODE = Touch, const MAX: usize = MAX_TOUCH_POINTS> {
     i2c: I2C,
     address: u8,
+    orientation: Orientation,
+    transform: Option<PreparedTransform>,
+    max_transaction: usize,
+    clear_policy: ClearPolicy,
+    asleep: bool,
+    needs_reset: bool,
+    variants: &'static [GT911Variant],
+    variant: Option<GT911Variant>,
     _mode: PhantomData<MODE>,
 }
 
-impl<I2C> GT911<I2C, Touch> {
-    /// Create a new [`GT911`] driver in touch mode.
+impl<I2C, const MAX: usize> GT911<I2C, Touch, MAX> {
+    /// Create a new [`GT911`] driver in touch mode,
+    /// reporting at most `MAX` touch points.
+    ///
+    /// `MAX` defaults to all 5 touch points wherever the type is named,
+    /// as in `GT911<I2C>`, and is otherwise inferred from how the driver is
+    /// used.
+    ///
+    /// ```rust
+    /// use ef_gt911::GT911;
+    ///
+    /// let touch: GT911<_> = GT911::new((), 0x5D);
+    /// assert_eq!(touch.max_points(), 5);
+    ///
+    /// let touch = GT911::<_, _, 2>::new((), 0x5D);
+    /// assert_eq!(touch.max_points(), 2);
+    /// ```
+    ///
+    /// `MAX` must be between 1 and 5.
+    ///
+    /// ```rust,compile_fail
+    /// use ef_gt911::GT911;
+    ///
+    /// let touch = GT911::<_, _, 6>::new((), 0x5D);
+    /// ```
     #[inline]
     #[must_use]
-    pub const fn new(i2c: I2C, address: u8) -> Self { Self { i2c, address, _mode: PhantomData } }
+    pub const fn new(i2c: I2C, address: u8) -> Self {
+        const { assert!(MAX > 0 && MAX <= MAX_TOUCH_POINTS, "GT911 supports 1 to 5 touch points") };
+        Self {
+            i2c,
+            address,
+            orientation: Orientation::IDENTITY,
+            transform: None,
+            max_transaction: usize::MAX,
+            clear_policy: ClearPolicy::AfterRead,
+            asleep: false,
+            needs_reset: false,
+            variants: &[GT911Variant::GT911],
+            variant: None,
+            _mode: PhantomData,
+        }
+    }
 }
 
-impl<I2C, MODE> GT911<I2C, MODE> {
+impl<I2C, MODE, const MAX: usize> GT911<I2C, MODE, MAX> {
+    /// Get the maximum number of touch points reported.
+    ///
+    /// This is `MAX`, or less if the [`GT911Variant`] detected by
+    /// `GT911::init` supports fewer touch points.
+    #[inline]
+    #[must_use]
+    pub const fn max_points(&self) -> usize {
+        match self.variant {
+            Some(variant) if variant.max_touch_points() < MAX => variant.max_touch_points(),
+            _ => MAX,
+        }
+    }
+
+    /// Set the [`GT911Variant`]s accepted by `GT911::init`.
+    ///
+    /// Only the [`GT911Variant::GT911`] is accepted by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{GT911, GT911Error, GT911Variant};
+    /// use embedded_hal::i2c::ErrorKind;
+    /// # use ef_gt911::test_util::Registers;
+    ///
+    /// // A ready GT928
+    /// let mut registers = [0u8; 0x200];
+    /// registers[0x140..0x144].copy_from_slice(b"928\0");
+    /// registers[0x14E] = 0x80;
+    ///
+    /// // Only the GT911 is accepted by default
+    /// let mut touch: GT911<_> = GT911::new(Registers::new(&registers), 0x5D);
+    /// assert!(matches!(touch.init(), Err(GT911Error::ProductIdMismatch(id, _)) if id == *b"928\0"));
+    /// assert_eq!(touch.variant(), None);
+    ///
+    /// // Accept every variant
+    /// let mut touch = touch.with_variants(GT911Variant::ALL);
+    /// touch.init()?;
+    /// assert_eq!(touch.variant(), Some(GT911Variant::GT928));
+    /// assert_eq!(touch.max_points(), 5);
+    /// # Ok::<(), GT911Error<ErrorKind>>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn with_variants(mut self, variants: &'static [GT911Variant]) -> Self {
+        self.variants = variants;
+        self
+    }
+
+    /// Get the [`GT911Variant`]s accepted by `GT911::init`.
+    #[inline]
+    #[must_use]
+    pub const fn variants(&self) -> &'static [GT911Variant] { self.variants }
+
+    /// Get the [`GT911Variant`] detected by `GT911::init`.
+    ///
+    /// Returns `None` until the device is initialized in touch mode.
+    #[inline]
+    #[must_use]
+    pub const fn variant(&self) -> Option<GT911Variant> { self.variant }
+
     /// Get the I2C address of the GT911 device.
     #[inline]
     #[must_use]
@@ -46,6 +187,162 @@ impl<I2C, MODE> GT911<I2C, MODE> {
     #[inline]
     #[must_use]
     pub fn release(self) -> I2C { self.i2c }
+
+    /// Set the [`Orientation`] of the panel relative to the display.
+    #[inline]
+    #[must_use]
+    pub const fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Get the [`Orientation`] of the panel relative to the display.
+    #[inline]
+    #[must_use]
+    pub const fn orientation(&self) -> Orientation { self.orientation }
+
+    /// Set the [`Orientation`] of the panel relative to the display.
+    ///
+    /// Swipe gestures are remapped to match the display.
+    #[inline]
+    pub const fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+
+    /// Set the [`TouchTransform`] applied to touch and gesture points.
+    ///
+    /// See [`GT911::set_transform`].
+    #[inline]
+    #[must_use]
+    pub const fn with_transform(mut self, transform: TouchTransform) -> Self {
+        self.set_transform(Some(transform));
+        self
+    }
+
+    /// Get the [`TouchTransform`] applied to touch and gesture points.
+    #[inline]
+    #[must_use]
+    pub const fn transform(&self) -> Option<TouchTransform> {
+        match &self.transform {
+            Some(prepared) => Some(prepared.transform()),
+            None => None,
+        }
+    }
+
+    /// Set the [`TouchTransform`] applied to touch and gesture points,
+    /// or `None` to report the coordinates of the panel.
+    ///
+    /// Touch points and gesture points are transformed as they are read.
+    /// Swipe gestures follow [`GT911::set_orientation`] instead, use
+    /// [`TouchTransform::orientation`] to keep both in agreement.
+    #[inline]
+    pub const fn set_transform(&mut self, transform: Option<TouchTransform>) {
+        self.transform = match transform {
+            Some(transform) => Some(PreparedTransform::new(transform)),
+            None => None,
+        };
+    }
+
+    /// Apply the [`TouchTransform`], if one is set, to a [`TouchPoint`].
+    const fn transform_touch(&self, point: TouchPoint) -> TouchPoint {
+        match &self.transform {
+            Some(prepared) => prepared.touch(point),
+            None => point,
+        }
+    }
+
+    /// Apply the [`TouchTransform`], if one is set, to a [`GesturePoint`].
+    const fn transform_gesture(&self, point: GesturePoint) -> GesturePoint {
+        match &self.transform {
+            Some(prepared) => prepared.gesture(point),
+            None => point,
+        }
+    }
+
+    /// Set the maximum number of bytes read in a single I2C transaction.
+    ///
+    /// See [`GT911::set_max_transaction`].
+    #[inline]
+    #[must_use]
+    pub const fn with_max_transaction(mut self, len: usize) -> Self {
+        self.set_max_transaction(len);
+        self
+    }
+
+    /// Get the maximum number of bytes read in a single I2C transaction.
+    #[inline]
+    #[must_use]
+    pub const fn max_transaction(&self) -> usize { self.max_transaction }
+
+    /// Set the maximum number of bytes read in a single I2C transaction.
+    ///
+    /// Longer reads are split into several transactions, each starting at
+    /// the register following the previous one. Defaults to unlimited,
+    /// a length of `0` is treated as `1`.
+    #[inline]
+    pub const fn set_max_transaction(&mut self, len: usize) {
+        self.max_transaction = if len == 0 { 1 } else { len };
+    }
+
+    /// Set when the status of a consumed frame or gesture is cleared.
+    #[inline]
+    #[must_use]
+    pub const fn with_clear_policy(mut self, policy: ClearPolicy) -> Self {
+        self.clear_policy = policy;
+        self
+    }
+
+    /// Get when the status of a consumed frame or gesture is cleared.
+    #[inline]
+    #[must_use]
+    pub const fn clear_policy(&self) -> ClearPolicy { self.clear_policy }
+
+    /// Set when the status of a consumed frame or gesture is cleared.
+    ///
+    /// Defaults to [`ClearPolicy::AfterRead`].
+    #[inline]
+    pub const fn set_clear_policy(&mut self, policy: ClearPolicy) { self.clear_policy = policy; }
+
+    /// Returns `true` if the device was put to sleep and not woken since.
+    #[inline]
+    #[must_use]
+    pub const fn is_asleep(&self) -> bool { self.asleep }
+
+    /// Returns `true` if the device may be in an unknown mode,
+    /// and must be reset with [`GT911::device_reset`] before it is used.
+    #[inline]
+    #[must_use]
+    pub const fn needs_reset(&self) -> bool { self.needs_reset }
+
+    /// Convert the driver into another mode, keeping its settings.
+    fn into_mode<NEW>(self) -> GT911<I2C, NEW, MAX> {
+        GT911 {
+            i2c: self.i2c,
+            address: self.address,
+            orientation: self.orientation,
+            transform: self.transform,
+            max_transaction: self.max_transaction,
+            clear_policy: self.clear_policy,
+            asleep: self.asleep,
+            needs_reset: self.needs_reset,
+            variants: self.variants,
+            variant: self.variant,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Accept the product ID of a device in touch mode if it is one of the
+    /// allowed variants, storing the detected variant.
+    fn accept_variant<E>(&mut self, info: &DeviceInfo) -> Result<(), GT911Error<E>> {
+        match GT911Variant::from_product_id(info.product_id) {
+            Some(variant) if self.variants.contains(&variant) => {
+                self.variant = Some(variant);
+                Ok(())
+            }
+            // Return that the product ID does not match
+            _ => Err(GT911Error::ProductIdMismatch(info.product_id, info.firmware_version)),
+        }
+    }
 }
 
 /// A marker struct for touch mode.
@@ -66,7 +363,7 @@ impl GT911Mode for Gesture {
 
 /// A marker trait for GT911 operating modes.
 pub trait GT911Mode: sealed::Sealed {
-    /// The register to clear when exiting command mode.
+    /// The status register cleared once a frame or gesture is consumed.
     const CLEAR_REGISTER: u16;
 }
 mod sealed {
@@ -75,6 +372,23 @@ mod sealed {
     impl Sealed for super::Gesture {}
 }
 
+/// When the driver clears the status register of a consumed frame or
+/// gesture.
+///
+/// Reads need no command, but the device only reports a new frame or
+/// gesture once the status register is cleared. Clearing it is a single
+/// write, made by the read itself or by
+/// [`GT911::clear_status`](crate::GT911::clear_status).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ClearPolicy {
+    /// Clear the status after reading a ready frame or a detected gesture.
+    #[default]
+    AfterRead,
+    /// Never clear the status, leaving it to the caller.
+    Manual,
+}
+
 // -------------------------------------------------------------------------------------------------
 
 /// An error that can occur when using the GT911 driver.
@@ -83,14 +397,90 @@ mod sealed {
 pub enum GT911Error<E> {
     /// The device is not ready.
     DeviceNotReady(DetectedTouch),
+    /// The device did not become ready in time.
+    Timeout,
+    /// The device is asleep and must be woken before it can be read.
+    Asleep,
+    /// A mode switch was interrupted, so the device must be reset
+    /// before it can be read.
+    NeedsReset,
     /// An invalid touch or gesture point was requested.
     InvalidPoint(u8),
     /// Unexpected product ID.
     ProductIdMismatch([u8; 4], u16),
+    /// The configuration read from the device has an invalid checksum,
+    /// so it was not patched.
+    ConfigChecksum {
+        /// The checksum stored in the device.
+        stored: u8,
+        /// The checksum calculated from the configuration.
+        computed: u8,
+    },
+    /// The RST pin could not be driven, or the INT pin driven or read.
+    Pin(#[cfg_attr(feature = "defmt", defmt(Debug2Format))] embedded_hal::digital::ErrorKind),
     /// I2C bus error.
     I2C(E),
 }
 
+/// The product information of a GT911, read from `0x8140` to `0x814A`.
+///
+/// Returned by `GT911::query_device_info` and `GT911::init`.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_gt911::DeviceInfo;
+///
+/// let info = DeviceInfo::from_bytes(*b"911\0\x60\x10\xE0\x01\x20\x03\x02");
+/// assert_eq!(info.product_id_str(), Some("911"));
+/// assert_eq!(info.firmware_version, 0x1060);
+/// assert_eq!((info.x_resolution, info.y_resolution), (480, 800));
+/// assert_eq!(info.vendor_id, 0x02);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceInfo {
+    /// The product ID, `911\0` in touch mode and `GEST` in gesture mode.
+    ///
+    /// Other chips of the GT9xx family report their own ID in touch mode,
+    /// see [`GT911Variant`].
+    pub product_id: [u8; 4],
+    /// The firmware version.
+    pub firmware_version: u16,
+    /// The configured X resolution, in pixels.
+    pub x_resolution: u16,
+    /// The configured Y resolution, in pixels.
+    pub y_resolution: u16,
+    /// The vendor ID, identifying the sensor panel.
+    pub vendor_id: u8,
+}
+
+impl DeviceInfo {
+    /// The length of the product information, in bytes.
+    const LEN: usize = 11;
+
+    /// Create the product information from raw data.
+    #[must_use]
+    pub const fn from_bytes(data: [u8; 11]) -> Self {
+        Self {
+            product_id: [data[0], data[1], data[2], data[3]],
+            firmware_version: u16::from_le_bytes([data[4], data[5]]),
+            x_resolution: u16::from_le_bytes([data[6], data[7]]),
+            y_resolution: u16::from_le_bytes([data[8], data[9]]),
+            vendor_id: data[10],
+        }
+    }
+
+    /// Get the product ID as a string, without trailing `NUL` bytes.
+    ///
+    /// Returns `None` if the product ID is not valid UTF-8.
+    #[must_use]
+    pub fn product_id_str(&self) -> Option<&str> {
+        let len = self.product_id.iter().position(|&byte| byte == 0).unwrap_or(4);
+        core::str::from_utf8(&self.product_id[..len]).ok()
+    }
+}
+
 /// A touch point reported by the GT911.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -106,6 +496,9 @@ pub struct TouchPoint {
 }
 
 impl TouchPoint {
+    /// An empty touch point, used to fill unused report slots.
+    const EMPTY: Self = Self { point: 0, x: 0, y: 0, area: 0 };
+
     /// Create a touch point from raw data.
     #[must_use]
     pub const fn from_bytes(data: [u8; 7]) -> Self {
@@ -118,6 +511,172 @@ impl TouchPoint {
     }
 }
 
+/// The maximum number of touch points the GT911 can report.
+const MAX_TOUCH_POINTS: usize = 5;
+/// The distance between touch point registers, in bytes.
+const TOUCH_POINT_STRIDE: usize = 8;
+
+/// The length of the configuration registers, from `CONFIG_VERSION` to
+/// `CONFIG_CHECKSUM`.
+const CONFIG_LEN: usize =
+    (register::GT911_CONFIG_CHECKSUM - register::GT911_CONFIG_VERSION + 1) as usize;
+
+/// Split a transfer starting at `register` into transactions of at most
+/// `max` bytes, each paired with the register it starts at.
+#[expect(clippy::cast_possible_truncation, reason = "Transfers fit in the register space")]
+fn transactions(
+    register: u16,
+    buf: &mut [u8],
+    max: usize,
+) -> impl Iterator<Item = (u16, &mut [u8])> {
+    buf.chunks_mut(max)
+        .enumerate()
+        .map(move |(index, chunk)| (register.wrapping_add((index * max) as u16), chunk))
+}
+
+/// Get the register of the touch point at `index`.
+#[expect(clippy::cast_possible_truncation, reason = "Within bounds")]
+const fn touch_register(index: u8) -> u16 {
+    register::GT911_TOUCH1_TRACK_ID + index as u16 * TOUCH_POINT_STRIDE as u16
+}
+
+/// The active touch points reported by the GT911.
+///
+/// Holds at most `MAX` points, matching the [`GT911`] it was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TouchReport<const MAX: usize = MAX_TOUCH_POINTS> {
+    count: usize,
+    points: [TouchPoint; MAX],
+}
+
+impl<const MAX: usize> TouchReport<MAX> {
+    /// A report without any touch points.
+    pub const EMPTY: Self = Self { count: 0, points: [TouchPoint::EMPTY; MAX] };
+
+    /// The number of bytes to burst read for `count` touch points.
+    ///
+    /// The count is clamped to `MAX`, and the reserved byte after the last
+    /// touch point is not read.
+    fn burst_len(count: u8) -> usize {
+        (usize::from(count).min(MAX) * TOUCH_POINT_STRIDE).saturating_sub(1)
+    }
+
+    /// Create a report from a burst read of the touch point registers.
+    ///
+    /// Every started group of [`TOUCH_POINT_STRIDE`] bytes is a touch point,
+    /// points past `MAX` are ignored. Each point is passed through `map`.
+    fn from_burst(data: &[u8], map: impl Fn(TouchPoint) -> TouchPoint) -> Self {
+        let mut report = Self::EMPTY;
+        for (point, chunk) in report.points.iter_mut().zip(data.chunks(TOUCH_POINT_STRIDE)) {
+            let mut bytes = [0u8; 7];
+            bytes.copy_from_slice(&chunk[..7]);
+            *point = map(TouchPoint::from_bytes(bytes));
+            report.count += 1;
+        }
+        report
+    }
+
+    /// Create a report from touch points, keeping at most `MAX` points.
+    ///
+    /// ```rust
+    /// use ef_gt911::{TouchPoint, TouchReport};
+    ///
+    /// let points: [_; 3] =
+    ///     core::array::from_fn(|i| TouchPoint { point: i as u8, x: 0, y: 0, area: 1 });
+    /// assert_eq!(TouchReport::<5>::from_points(&points).points(), points);
+    /// assert_eq!(TouchReport::<2>::from_points(&points).points(), &points[..2]);
+    /// ```
+    #[must_use]
+    pub fn from_points(points: &[TouchPoint]) -> Self {
+        let mut report = Self::EMPTY;
+        report.count = points.len().min(MAX);
+        report.points[..report.count].copy_from_slice(&points[..report.count]);
+        report
+    }
+
+    /// Get the active touch points.
+    #[inline]
+    #[must_use]
+    pub fn points(&self) -> &[TouchPoint] { &self.points[..self.count] }
+
+    /// Get the number of active touch points.
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> usize { self.count }
+
+    /// Returns `true` if there are no active touch points.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool { self.count == 0 }
+}
+
+impl<const MAX: usize> Default for TouchReport<MAX> {
+    fn default() -> Self { Self::EMPTY }
+}
+
+/// The touch status and points read in a single transaction,
+/// so that all points come from the same frame.
+///
+/// Returned by `GT911::query_touch_frame`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TouchFrame {
+    /// The touch status of the frame.
+    pub status: DetectedTouch,
+    /// The touch point slots, `None` past the number of detected points.
+    pub points: [Option<TouchPoint>; MAX_TOUCH_POINTS],
+}
+
+impl TouchFrame {
+    /// A frame without any touch points, that is not ready.
+    pub const EMPTY: Self =
+        Self { status: DetectedTouch::empty(), points: [None; MAX_TOUCH_POINTS] };
+
+    /// The number of bytes to burst read for a frame with at most `max`
+    /// points, from the status register to the end of the last point slot.
+    const fn burst_len(max: usize) -> usize { 1 + max * TOUCH_POINT_STRIDE }
+
+    /// Create a frame from a burst read starting at the status register.
+    ///
+    /// Only the detected points are kept, slots missing from `data` are
+    /// left empty. Each point is passed through `map`.
+    fn from_burst(data: &[u8], map: impl Fn(TouchPoint) -> TouchPoint) -> Self {
+        let status = DetectedTouch::from_bits_truncate(data[0]);
+        let mut frame = Self { status, points: [None; MAX_TOUCH_POINTS] };
+        if !status.is_ready() {
+            return frame;
+        }
+
+        let count = usize::from(status.touch_count());
+        let slots = data[1..].chunks_exact(TOUCH_POINT_STRIDE).take(count);
+        for (point, chunk) in frame.points.iter_mut().zip(slots) {
+            let mut bytes = [0u8; 7];
+            bytes.copy_from_slice(&chunk[..7]);
+            *point = Some(map(TouchPoint::from_bytes(bytes)));
+        }
+        frame
+    }
+
+    /// Iterate over the detected touch points.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &TouchPoint> { self.points.iter().flatten() }
+
+    /// Get the number of detected touch points.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize { self.iter().count() }
+
+    /// Returns `true` if no touch points were detected.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.points[0].is_none() }
+}
+
+impl Default for TouchFrame {
+    fn default() -> Self { Self::EMPTY }
+}
+
 bitflags! {
     /// Flags representing the current touch status.
     #[cfg_attr(not(feature = "defmt"), derive(Debug, Clone, Copy, PartialEq, Eq))]
@@ -128,7 +687,7 @@ bitflags! {
         const LARGE_TOUCH_MASK = 0b0100_0000;
         /// Whether the proximity sensor is triggered.
         const PROXIMITY_MASK = 0b0010_0000;
-        /// Whether the device is being touched.
+        /// Whether a touch key is pressed.
         const TOUCH_KEY_MASK = 0b0001_0000;
         /// How many touch points are currently detected.
         const TOUCH_POINT_MASK = 0b0000_1111;
@@ -162,6 +721,24 @@ impl DetectedTouch {
     pub const fn is_triggered(self) -> bool { self.contains(DetectedTouch::PROXIMITY_MASK) }
 }
 
+bitflags! {
+    /// The touch keys currently pressed, read from the `Key_Value` register.
+    ///
+    /// Keys are numbered in the order their positions are configured,
+    /// from `Key_1` to `Key_4`.
+    #[cfg_attr(not(feature = "defmt"), derive(Debug, Clone, Copy, PartialEq, Eq))]
+    pub struct TouchKeys: u8 {
+        /// The first touch key.
+        const KEY1 = 0b0000_0001;
+        /// The second touch key.
+        const KEY2 = 0b0000_0010;
+        /// The third touch key.
+        const KEY3 = 0b0000_0100;
+        /// The fourth touch key.
+        const KEY4 = 0b0000_1000;
+    }
+}
+
 /// A gesture detected by the GT911.
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -184,16 +761,81 @@ pub enum DetectedGesture {
 }
 
 impl DetectedGesture {
+    /// The characters recognized by the gesture firmware.
+    ///
+    /// Each is reported by its ASCII code, all other codes are reserved.
+    pub const CHARS: [char; 17] =
+        ['>', '^', 'a', 'b', 'c', 'd', 'e', 'g', 'h', 'm', 'o', 'q', 's', 'v', 'w', 'y', 'z'];
+
     /// Returns `true` if any gesture is detected.
     #[inline]
     #[must_use]
     pub const fn is_any(self) -> bool { !matches!(self, DetectedGesture::None) }
 
+    /// Returns `true` if the gesture can be reported by the firmware,
+    /// which is not the case for a [`DetectedGesture::Char`] of any other
+    /// character than [`DetectedGesture::CHARS`].
+    #[must_use]
+    pub const fn is_valid(self) -> bool {
+        match self {
+            DetectedGesture::Char(char) => {
+                let mut index = 0;
+                while index < Self::CHARS.len() {
+                    if Self::CHARS[index] == char {
+                        return true;
+                    }
+                    index += 1;
+                }
+                false
+            }
+            _ => true,
+        }
+    }
+
     /// Create a `DetectedGesture` from a raw byte.
+    ///
+    /// Reserved codes are reported as [`DetectedGesture::None`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::DetectedGesture;
+    ///
+    /// // Every code maps to a valid gesture, and characters to their ASCII code
+    /// let mut chars = Vec::new();
+    /// for byte in 0..=u8::MAX {
+    ///     let gesture = DetectedGesture::from_byte(byte);
+    ///     assert!(gesture.is_valid(), "{byte:#04X} -> {gesture:?}");
+    ///     if let DetectedGesture::Char(char) = gesture {
+    ///         assert_eq!(u32::from(char), u32::from(byte));
+    ///         chars.push(char);
+    ///     }
+    /// }
+    /// assert_eq!(chars, DetectedGesture::CHARS);
+    ///
+    /// // Reserved codes, including other letters, are not gestures
+    /// for byte in [0x27, b'A', b'f', b'x', 0x7F, 0xAC, 0xFF] {
+    ///     assert_eq!(DetectedGesture::from_byte(byte), DetectedGesture::None);
+    /// }
+    /// assert_eq!(DetectedGesture::from_byte(0xCC), DetectedGesture::DoubleTap);
+    /// assert!(!DetectedGesture::Char('x').is_valid());
+    /// ```
     #[must_use]
     pub const fn from_byte(byte: u8) -> Self {
         match byte {
-            0x27..=0x7F => DetectedGesture::Char(byte as char),
+            b'>'
+            | b'^'
+            | b'a'..=b'e'
+            | b'g'
+            | b'h'
+            | b'm'
+            | b'o'
+            | b'q'
+            | b's'
+            | b'v'
+            | b'w'
+            | b'y'
+            | b'z' => DetectedGesture::Char(byte as char),
             0xAA => DetectedGesture::SwipeRight,
             0xBB => DetectedGesture::SwipeLeft,
             0xAB => DetectedGesture::SwipeDown,
@@ -225,3 +867,83 @@ impl GesturePoint {
         }
     }
 }
+
+/// The gesture points read by `GT911::read_gesture_track`,
+/// decoded as they are iterated.
+///
+/// Points are transformed by the [`TouchTransform`] set when they were read.
+#[derive(Debug, Clone)]
+pub struct GestureTrack<'a> {
+    data: &'a [u8],
+    reported: u8,
+    transform: Option<PreparedTransform>,
+}
+
+impl<'a> GestureTrack<'a> {
+    /// The length of a buffer that holds every gesture point.
+    pub const BUFFER_LEN: usize = Self::MAX_POINTS * Self::POINT_LEN;
+    /// The maximum number of gesture points.
+    pub const MAX_POINTS: usize = 64;
+    /// The number of bytes of a gesture point.
+    pub const POINT_LEN: usize = 4;
+
+    /// Create a [`GestureTrack`] over the points read into `data`.
+    pub(crate) const fn new(
+        data: &'a [u8],
+        reported: u8,
+        transform: Option<PreparedTransform>,
+    ) -> Self {
+        Self { data, reported, transform }
+    }
+
+    /// Get the number of bytes to read for `reported` points
+    /// into a buffer of `available` bytes.
+    pub(crate) const fn read_len(reported: u8, available: usize) -> usize {
+        let points = Self::points(reported);
+        let points =
+            if points * Self::POINT_LEN > available { available / Self::POINT_LEN } else { points };
+        points * Self::POINT_LEN
+    }
+
+    /// Get the number of points held by the device, at most
+    /// [`GestureTrack::MAX_POINTS`].
+    const fn points(reported: u8) -> usize {
+        let reported = reported as usize;
+        if reported > Self::MAX_POINTS { Self::MAX_POINTS } else { reported }
+    }
+
+    /// Get the number of points reported by the device.
+    #[inline]
+    #[must_use]
+    pub const fn reported(&self) -> u8 { self.reported }
+
+    /// Returns `true` if the buffer was too small to hold every reported
+    /// point, so only the first points were read.
+    #[inline]
+    #[must_use]
+    pub const fn is_truncated(&self) -> bool {
+        self.data.len() / Self::POINT_LEN < Self::points(self.reported)
+    }
+}
+
+impl Iterator for GestureTrack<'_> {
+    type Item = GesturePoint;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&point, rest) = self.data.split_first_chunk::<{ GestureTrack::POINT_LEN }>()?;
+        self.data = rest;
+
+        let point = GesturePoint::from_bytes(point);
+        Some(match &self.transform {
+            Some(transform) => transform.gesture(point),
+            None => point,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.data.len() / Self::POINT_LEN;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for GestureTrack<'_> {}
@@ -0,0 +1,342 @@
+//! Recognizing pinch and rotate gestures from two touch points.
+
+use crate::TouchPoint;
+
+/// A scale of one, in the fixed-point units of [`MultiTouchGesture::Pinch`].
+pub const SCALE_ONE: i32 = 1 << 16;
+
+/// π, in milliradians.
+const PI_MRAD: i32 = 3142;
+/// π / 2, in milliradians.
+const HALF_PI_MRAD: i32 = 1571;
+
+/// Recognizes pinch and two-finger rotate gestures from touch reports.
+///
+/// The built-in gestures of the GT911 only work while the display is off,
+/// this recognizes gestures from the coordinates of the touch points instead.
+///
+/// The first two touch points of a report are tracked by their IDs, and the
+/// distance and angle between them are compared with each report. A gesture
+/// starts once the distance or angle changed by its start threshold, and
+/// then reports every change larger than the dead zone. Lifting either
+/// finger ends both gestures.
+///
+/// All math is done with integers, angles are measured in milliradians
+/// clockwise on a display with Y pointing down.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_gt911::{MultiTouchGesture, MultiTouchGestures, SCALE_ONE, TouchPoint};
+///
+/// let touch = |point, (x, y)| TouchPoint { point, x, y, area: 20 };
+/// let mut gestures = MultiTouchGestures::new();
+///
+/// // A single finger is not a gesture
+/// assert_eq!(gestures.record(&[touch(0, (200, 240))]).count(), 0);
+///
+/// // Spread two fingers apart, by two pixels each per report
+/// let mut events = Vec::new();
+/// for step in 0..=20 {
+///     let report = [touch(0, (200 - 2 * step, 240)), touch(1, (280 + 2 * step, 240))];
+///     events.extend(gestures.record(&report));
+/// }
+///
+/// // Nothing is reported until the start threshold, then every report
+/// assert_eq!(events.len(), 17);
+/// assert_eq!(events[0], MultiTouchGesture::Pinch { scale_delta: SCALE_ONE / 5 });
+///
+/// // The deltas add up to doubling the distance
+/// let scale = events.iter().fold(i64::from(SCALE_ONE), |scale, event| {
+///     let MultiTouchGesture::Pinch { scale_delta } = *event else { panic!() };
+///     scale * i64::from(SCALE_ONE + scale_delta) / i64::from(SCALE_ONE)
+/// });
+/// assert!((scale - 2 * i64::from(SCALE_ONE)).abs() < 64);
+///
+/// // Lifting a finger ends the gesture
+/// assert_eq!(gestures.record(&[touch(0, (160, 240))]).count(), 0);
+/// assert!(!gestures.is_tracking());
+///
+/// // Turn two new fingers a quarter turn around the center of the display
+/// let mut turned = 0;
+/// for degrees in (0..=90).step_by(3) {
+///     let (sin, cos) = f32::to_radians(degrees as f32).sin_cos();
+///     let (dx, dy) = ((100.0 * cos).round() as u16, (100.0 * sin).round() as u16);
+///     let report = [touch(2, (240 - dx, 240 - dy)), touch(3, (240 + dx, 240 + dy))];
+///
+///     for event in gestures.record(&report) {
+///         let MultiTouchGesture::Rotate { angle_delta_millirad } = event else { panic!() };
+///         turned += angle_delta_millirad;
+///     }
+/// }
+/// assert!((turned - 1571).abs() <= 5, "{turned}");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MultiTouchGestures {
+    /// The change in distance that starts a pinch.
+    pinch_threshold: u16,
+    /// The change in angle that starts a rotation.
+    rotate_threshold: u16,
+    /// The smallest change in distance reported during a pinch.
+    distance_dead_zone: u16,
+    /// The smallest change in angle reported during a rotation.
+    angle_dead_zone: u16,
+    /// The tracked touch points.
+    pair: Option<ContactPair>,
+}
+
+impl MultiTouchGestures {
+    /// The default smallest change in angle reported during a rotation,
+    /// in milliradians.
+    pub const DEFAULT_ANGLE_DEAD_ZONE: u16 = 10;
+    /// The default smallest change in distance reported during a pinch.
+    pub const DEFAULT_DISTANCE_DEAD_ZONE: u16 = 2;
+    /// The default change in distance that starts a pinch.
+    pub const DEFAULT_PINCH_THRESHOLD: u16 = 16;
+    /// The default change in angle that starts a rotation, in milliradians.
+    pub const DEFAULT_ROTATE_THRESHOLD: u16 = 100;
+
+    /// Create a new [`MultiTouchGestures`] with the default thresholds.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            pinch_threshold: Self::DEFAULT_PINCH_THRESHOLD,
+            rotate_threshold: Self::DEFAULT_ROTATE_THRESHOLD,
+            distance_dead_zone: Self::DEFAULT_DISTANCE_DEAD_ZONE,
+            angle_dead_zone: Self::DEFAULT_ANGLE_DEAD_ZONE,
+            pair: None,
+        }
+    }
+
+    /// Set the change in distance and in angle, in milliradians,
+    /// that start a pinch and a rotation.
+    #[inline]
+    #[must_use]
+    pub const fn with_start_thresholds(mut self, distance: u16, angle_millirad: u16) -> Self {
+        self.pinch_threshold = distance;
+        self.rotate_threshold = angle_millirad;
+        self
+    }
+
+    /// Set the smallest change in distance and in angle, in milliradians,
+    /// reported once a gesture has started.
+    ///
+    /// Smaller changes are collected until they reach the dead zone.
+    #[inline]
+    #[must_use]
+    pub const fn with_dead_zone(mut self, distance: u16, angle_millirad: u16) -> Self {
+        self.distance_dead_zone = distance;
+        self.angle_dead_zone = angle_millirad;
+        self
+    }
+
+    /// Returns `true` if two touch points are being tracked.
+    #[inline]
+    #[must_use]
+    pub const fn is_tracking(&self) -> bool { self.pair.is_some() }
+
+    /// Stop tracking the current touch points.
+    ///
+    /// The next report with two touch points starts a new gesture.
+    #[inline]
+    pub const fn reset(&mut self) { self.pair = None; }
+
+    /// Record the points of a touch report,
+    /// returning the gestures recognized since the previous report.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{MultiTouchGesture, MultiTouchGestures, SCALE_ONE, TouchPoint};
+    ///
+    /// let touch = |point, (x, y)| TouchPoint { point, x, y, area: 20 };
+    /// let mut gestures = MultiTouchGestures::new();
+    ///
+    /// // Opposite corners of the coordinate range do not overflow
+    /// gestures.record(&[touch(0, (0, 0)), touch(1, (u16::MAX, u16::MAX))]);
+    /// let events: Vec<_> = gestures.record(&[touch(0, (0, 0)), touch(1, (u16::MAX, 0))]).collect();
+    /// assert_eq!(
+    ///     events,
+    ///     [
+    ///         MultiTouchGesture::Pinch { scale_delta: -19194 },
+    ///         MultiTouchGesture::Rotate { angle_delta_millirad: -785 },
+    ///     ]
+    /// );
+    ///
+    /// // A third finger is ignored, and keeps the gesture going once a
+    /// // tracked finger lifts, without a jump
+    /// gestures.record(&[touch(0, (0, 0)), touch(1, (100, 0)), touch(2, (0, 200))]);
+    /// assert_eq!(gestures.record(&[touch(0, (0, 0)), touch(2, (0, 200))]).count(), 0);
+    /// assert!(gestures.is_tracking());
+    /// let events: Vec<_> = gestures.record(&[touch(0, (0, 0)), touch(2, (0, 400))]).collect();
+    /// assert_eq!(events, [MultiTouchGesture::Pinch { scale_delta: SCALE_ONE }]);
+    /// ```
+    pub fn record(&mut self, touches: &[TouchPoint]) -> MultiTouchEvents {
+        let contacts = self.pair.and_then(|pair| pair.find(touches));
+        let (Some(pair), Some((first, second))) = (&mut self.pair, contacts) else {
+            // Start over with the first two touch points, if any.
+            self.pair = ContactPair::start(touches);
+            return MultiTouchEvents::default();
+        };
+
+        let (distance, angle) = (distance(first, second), angle(first, second));
+        let mut events = MultiTouchEvents::default();
+
+        let threshold = if pair.pinching { self.distance_dead_zone } else { self.pinch_threshold };
+        if pair.distance == 0 {
+            // Fingers at the same point have no scale to compare with.
+            pair.distance = distance;
+        } else if distance.abs_diff(pair.distance) >= u32::from(threshold.max(1)) {
+            pair.pinching = true;
+            events.pinch = Some(MultiTouchGesture::Pinch {
+                scale_delta: scale_delta(pair.distance, distance),
+            });
+            pair.distance = distance;
+        }
+
+        let threshold = if pair.rotating { self.angle_dead_zone } else { self.rotate_threshold };
+        let turned = wrap_angle(angle - pair.angle);
+        if turned.unsigned_abs() >= u32::from(threshold.max(1)) {
+            pair.rotating = true;
+            events.rotate = Some(MultiTouchGesture::Rotate { angle_delta_millirad: turned });
+            pair.angle = angle;
+        }
+
+        events
+    }
+}
+
+impl Default for MultiTouchGestures {
+    fn default() -> Self { Self::new() }
+}
+
+/// A gesture recognized by [`MultiTouchGestures`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MultiTouchGesture {
+    /// The fingers moved apart (positive) or together (negative).
+    Pinch {
+        /// The change in scale since the previous pinch event,
+        /// in units of [`SCALE_ONE`].
+        ///
+        /// The new scale is `scale * (SCALE_ONE + scale_delta) / SCALE_ONE`.
+        scale_delta: i32,
+    },
+    /// The fingers turned clockwise (positive) or counter-clockwise
+    /// (negative).
+    Rotate {
+        /// The change in angle since the previous rotate event,
+        /// in milliradians.
+        angle_delta_millirad: i32,
+    },
+}
+
+/// The gestures recognized from a single touch report,
+/// a pinch followed by a rotation.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MultiTouchEvents {
+    pinch: Option<MultiTouchGesture>,
+    rotate: Option<MultiTouchGesture>,
+}
+
+impl Iterator for MultiTouchEvents {
+    type Item = MultiTouchGesture;
+
+    fn next(&mut self) -> Option<Self::Item> { self.pinch.take().or_else(|| self.rotate.take()) }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Two tracked touch points and the last reported distance and angle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct ContactPair {
+    ids: [u8; 2],
+    distance: u32,
+    angle: i32,
+    pinching: bool,
+    rotating: bool,
+}
+
+impl ContactPair {
+    /// Start tracking the first two touch points.
+    fn start(touches: &[TouchPoint]) -> Option<Self> {
+        let [first, second, ..] = *touches else { return None };
+        Some(Self {
+            ids: [first.point, second.point],
+            distance: distance(first, second),
+            angle: angle(first, second),
+            pinching: false,
+            rotating: false,
+        })
+    }
+
+    /// Find the tracked touch points in a report.
+    fn find(self, touches: &[TouchPoint]) -> Option<(TouchPoint, TouchPoint)> {
+        let find = |id| touches.iter().find(|touch| touch.point == id).copied();
+        Some((find(self.ids[0])?, find(self.ids[1])?))
+    }
+}
+
+/// Get the distance between two touch points.
+///
+/// Squares are summed as `u64`, so any two coordinates fit.
+fn distance(first: TouchPoint, second: TouchPoint) -> u32 {
+    let dx = u64::from(first.x.abs_diff(second.x));
+    let dy = u64::from(first.y.abs_diff(second.y));
+    u32::try_from((dx * dx + dy * dy).isqrt()).unwrap_or(u32::MAX)
+}
+
+/// Get the angle from the first to the second touch point, in milliradians.
+fn angle(first: TouchPoint, second: TouchPoint) -> i32 {
+    let dx = i32::from(second.x) - i32::from(first.x);
+    let dy = i32::from(second.y) - i32::from(first.y);
+    atan2(dy, dx)
+}
+
+/// Get the change in scale from `from` to `to`, in units of [`SCALE_ONE`].
+fn scale_delta(from: u32, to: u32) -> i32 {
+    let delta = (i64::from(to) - i64::from(from)) * i64::from(SCALE_ONE) / i64::from(from);
+    i32::try_from(delta).unwrap_or(i32::MAX)
+}
+
+/// Wrap an angle difference into `(-π, π]`.
+const fn wrap_angle(angle: i32) -> i32 {
+    if angle > PI_MRAD {
+        angle - 2 * PI_MRAD
+    } else if angle <= -PI_MRAD {
+        angle + 2 * PI_MRAD
+    } else {
+        angle
+    }
+}
+
+/// Calculate `atan2(y, x)` in milliradians, in `(-π, π]`.
+fn atan2(y: i32, x: i32) -> i32 {
+    let (ax, ay) = (x.unsigned_abs(), y.unsigned_abs());
+    if ax == 0 && ay == 0 {
+        return 0;
+    }
+
+    let octant = if ay <= ax { atan_unit(ay, ax) } else { HALF_PI_MRAD - atan_unit(ax, ay) };
+    let half = if x < 0 { PI_MRAD - octant } else { octant };
+    if y < 0 { -half } else { half }
+}
+
+/// Calculate `atan(num / den)` in milliradians, for `num <= den`.
+///
+/// Uses `atan(z) ≈ π/4·z + z·(1 - z)·(0.2447 + 0.0663·z)`, which is within
+/// 1.5 milliradians of the exact value.
+#[expect(clippy::cast_possible_truncation, reason = "At most π/4 in milliradians")]
+fn atan_unit(num: u32, den: u32) -> i32 {
+    /// One, in the fixed-point units of `z`.
+    const ONE: u64 = 1 << 12;
+
+    let z = (u64::from(num) * ONE) / u64::from(den);
+    let tail = 244_700 + 66_300 * z / ONE;
+    let slope = 785_398 + tail * (ONE - z) / ONE;
+    let microradians = slope * z / ONE;
+    ((microradians + 500) / 1000) as i32
+}
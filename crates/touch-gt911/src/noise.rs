@@ -0,0 +1,144 @@
+//! Noise immunity presets for the configuration registers.
+
+use crate::{config::ConfigPatch, register};
+
+/// A set of noise related configuration values, applied with
+/// [`GT911::apply_noise_profile`](crate::GT911::apply_noise_profile).
+///
+/// Register names follow the configuration register table of the
+/// *GT911 Programming Guide*.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoiseSettings {
+    /// `Filter` (`0x8050`): bits 7-6 are the filter of the first touch,
+    /// bits 5-0 the filter of the coordinate window.
+    pub filter: u8,
+    /// `Large_Touch` (`0x8051`): the number of channels covered by a large
+    /// area touch.
+    pub large_touch: u8,
+    /// `Noise_Reduction` (`0x8052`): bits 3-0 are the noise reduction value,
+    /// from `0` to `15`.
+    pub noise_reduction: u8,
+    /// `Screen_Touch_Level` (`0x8053`): the threshold for a new touch.
+    pub touch_threshold: u8,
+    /// `Screen_Leave_Level` (`0x8054`): the threshold for a released touch.
+    pub release_threshold: u8,
+    /// `Hopping_Flag` (`0x807D`): the frequency hopping enables.
+    ///
+    /// The hopping bands are tuned per module, so `None` leaves the
+    /// register untouched.
+    pub hopping: Option<u8>,
+}
+
+impl NoiseSettings {
+    /// Get the configuration patches that apply these settings.
+    pub(crate) fn patches(&self) -> impl Iterator<Item = ConfigPatch> {
+        let thresholds = ConfigPatch::new(
+            register::GT911_FILTER,
+            &[
+                self.filter,
+                self.large_touch,
+                self.noise_reduction & 0x0F,
+                self.touch_threshold,
+                self.release_threshold,
+            ],
+        );
+        let hopping =
+            self.hopping.map(|flags| ConfigPatch::new(register::GT911_FREQ_HOP_FLAGS, &[flags]));
+        core::iter::once(thresholds).chain(hopping)
+    }
+}
+
+/// A noise immunity preset.
+///
+/// The presets are based on the values of the Goodix reference
+/// configuration for 5" modules. Cheap USB chargers inject common-mode noise
+/// that is seen as phantom touches, which the
+/// [`ChargerConnected`](NoiseProfile::ChargerConnected) preset filters with
+/// stronger filtering and higher touch thresholds, at the cost of
+/// sensitivity. Switch profiles from the VBUS detect interrupt.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_gt911::{GT911, GT911Error, NoiseProfile, NoiseSettings};
+/// use embedded_hal::i2c::ErrorKind;
+/// # use ef_gt911::test_util::Registers;
+///
+/// /// The checksum of a configuration image.
+/// fn checksum(registers: &[u8]) -> u8 {
+///     registers[0x47..0xFF].iter().fold(0u8, |sum, &b| sum.wrapping_add(b)).wrapping_neg()
+/// }
+///
+/// // The start of a captured 480x272 configuration image
+/// let mut registers = [0u8; 0x200];
+/// registers[0x47..0x57].copy_from_slice(&[
+///     0x41, 0xE0, 0x01, 0x10, 0x01, 0x05, 0x0D, 0x00, 0x01, 0x08, 0x28, 0x05, 0x50, 0x32, 0x03,
+///     0x05,
+/// ]);
+/// registers[0xFF] = checksum(&registers);
+/// let mut touch: GT911<_> = GT911::new(Registers::new(&registers), 0x5D);
+///
+/// // The image already holds the normal values, nothing is written
+/// assert!(!touch.apply_noise_profile(&NoiseProfile::Normal)?);
+/// assert!(touch.i2c().register_writes().is_empty());
+///
+/// // Switching patches the noise registers, the checksum and the fresh flag
+/// assert!(touch.apply_noise_profile(&NoiseProfile::ChargerConnected)?);
+/// let writes = touch.i2c().register_writes();
+/// assert_eq!(writes.len(), 2);
+/// assert_eq!(writes[0], (0x8050, vec![0x0F, 0x28, 0x0F, 0x78, 0x5A]));
+/// assert_eq!(writes[1], (0x80FF, vec![checksum(&touch.i2c().registers), 0x01]));
+///
+/// // Custom settings may also set the hopping flags
+/// let settings = NoiseSettings { hopping: Some(0x80), ..NoiseProfile::Normal.settings() };
+/// assert!(touch.apply_noise_profile(&NoiseProfile::Custom(settings))?);
+/// assert_eq!(touch.i2c().registers[0x50..0x55], [0x08, 0x28, 0x05, 0x50, 0x32]);
+/// assert_eq!(touch.i2c().registers[0x7D], 0x80);
+/// assert_eq!(touch.i2c().registers[0xFF], checksum(&touch.i2c().registers));
+///
+/// // A configuration with a bad checksum is never patched
+/// touch.i2c_mut().registers[0xFF] ^= 0xFF;
+/// touch.i2c_mut().writes.clear();
+/// let result = touch.apply_noise_profile(&NoiseProfile::Normal);
+/// assert!(matches!(result, Err(GT911Error::ConfigChecksum { .. })));
+/// assert!(touch.i2c().register_writes().is_empty());
+/// # Ok::<(), GT911Error<ErrorKind>>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NoiseProfile {
+    /// The values of the reference configuration.
+    Normal,
+    /// Maximum filtering and noise reduction, with touch and release
+    /// thresholds raised by half.
+    ChargerConnected,
+    /// Module specific settings.
+    Custom(NoiseSettings),
+}
+
+impl NoiseProfile {
+    /// Get the [`NoiseSettings`] of the profile.
+    #[must_use]
+    pub const fn settings(&self) -> NoiseSettings {
+        match self {
+            Self::Normal => NoiseSettings {
+                filter: 0x08,
+                large_touch: 0x28,
+                noise_reduction: 0x05,
+                touch_threshold: 0x50,
+                release_threshold: 0x32,
+                hopping: None,
+            },
+            Self::ChargerConnected => NoiseSettings {
+                filter: 0x0F,
+                large_touch: 0x28,
+                noise_reduction: 0x0F,
+                touch_threshold: 0x78,
+                release_threshold: 0x5A,
+                hopping: None,
+            },
+            Self::Custom(settings) => *settings,
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! The orientation of the touch panel relative to the display.
+
+use crate::DetectedGesture;
+
+/// The orientation of the touch panel relative to the display.
+///
+/// Panel directions are mirrored first, then rotated clockwise.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_gt911::{DetectedGesture::*, Orientation, Rotation};
+///
+/// // Every swipe under every orientation, as `[right, left, down, up]`
+/// let table = [
+///     (Rotation::Deg0, false, false, [SwipeRight, SwipeLeft, SwipeDown, SwipeUp]),
+///     (Rotation::Deg0, false, true, [SwipeRight, SwipeLeft, SwipeUp, SwipeDown]),
+///     (Rotation::Deg0, true, false, [SwipeLeft, SwipeRight, SwipeDown, SwipeUp]),
+///     (Rotation::Deg0, true, true, [SwipeLeft, SwipeRight, SwipeUp, SwipeDown]),
+///     (Rotation::Deg90, false, false, [SwipeDown, SwipeUp, SwipeLeft, SwipeRight]),
+///     (Rotation::Deg90, false, true, [SwipeDown, SwipeUp, SwipeRight, SwipeLeft]),
+///     (Rotation::Deg90, true, false, [SwipeUp, SwipeDown, SwipeLeft, SwipeRight]),
+///     (Rotation::Deg90, true, true, [SwipeUp, SwipeDown, SwipeRight, SwipeLeft]),
+///     (Rotation::Deg180, false, false, [SwipeLeft, SwipeRight, SwipeUp, SwipeDown]),
+///     (Rotation::Deg180, false, true, [SwipeLeft, SwipeRight, SwipeDown, SwipeUp]),
+///     (Rotation::Deg180, true, false, [SwipeRight, SwipeLeft, SwipeUp, SwipeDown]),
+///     (Rotation::Deg180, true, true, [SwipeRight, SwipeLeft, SwipeDown, SwipeUp]),
+///     (Rotation::Deg270, false, false, [SwipeUp, SwipeDown, SwipeRight, SwipeLeft]),
+///     (Rotation::Deg270, false, true, [SwipeUp, SwipeDown, SwipeLeft, SwipeRight]),
+///     (Rotation::Deg270, true, false, [SwipeDown, SwipeUp, SwipeRight, SwipeLeft]),
+///     (Rotation::Deg270, true, true, [SwipeDown, SwipeUp, SwipeLeft, SwipeRight]),
+/// ];
+///
+/// for (rotation, mirror_x, mirror_y, expected) in table {
+///     let orientation =
+///         Orientation::new(rotation).with_mirror_x(mirror_x).with_mirror_y(mirror_y);
+///     let swipes = [SwipeRight, SwipeLeft, SwipeDown, SwipeUp];
+///     assert_eq!(swipes.map(|swipe| orientation.apply_gesture(swipe)), expected);
+///
+///     // Other gestures are not directional
+///     for gesture in [None, DoubleTap, Char('c')] {
+///         assert_eq!(orientation.apply_gesture(gesture), gesture);
+///     }
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Orientation {
+    /// The clockwise rotation, applied after mirroring.
+    pub rotation: Rotation,
+    /// Whether the X axis is mirrored.
+    pub mirror_x: bool,
+    /// Whether the Y axis is mirrored.
+    pub mirror_y: bool,
+}
+
+impl Orientation {
+    /// An orientation that does not change any directions.
+    pub const IDENTITY: Self = Self::new(Rotation::Deg0);
+
+    /// Create a new [`Orientation`] without mirroring.
+    #[inline]
+    #[must_use]
+    pub const fn new(rotation: Rotation) -> Self {
+        Self { rotation, mirror_x: false, mirror_y: false }
+    }
+
+    /// Set whether the X axis is mirrored.
+    #[inline]
+    #[must_use]
+    pub const fn with_mirror_x(mut self, mirror_x: bool) -> Self {
+        self.mirror_x = mirror_x;
+        self
+    }
+
+    /// Set whether the Y axis is mirrored.
+    #[inline]
+    #[must_use]
+    pub const fn with_mirror_y(mut self, mirror_y: bool) -> Self {
+        self.mirror_y = mirror_y;
+        self
+    }
+
+    /// Remap the direction of a swipe gesture from the panel to the display.
+    ///
+    /// Gestures without a direction are returned unchanged.
+    #[must_use]
+    pub const fn apply_gesture(self, gesture: DetectedGesture) -> DetectedGesture {
+        // Directions as clockwise quarter turns from up
+        let mut turns = match gesture {
+            DetectedGesture::SwipeUp => 0,
+            DetectedGesture::SwipeRight => 1,
+            DetectedGesture::SwipeDown => 2,
+            DetectedGesture::SwipeLeft => 3,
+            _ => return gesture,
+        };
+
+        if self.mirror_x {
+            turns = (4 - turns) % 4;
+        }
+        if self.mirror_y {
+            turns = (6 - turns) % 4;
+        }
+
+        match (turns + self.rotation as u8) % 4 {
+            0 => DetectedGesture::SwipeUp,
+            1 => DetectedGesture::SwipeRight,
+            2 => DetectedGesture::SwipeDown,
+            _ => DetectedGesture::SwipeLeft,
+        }
+    }
+}
+
+/// A clockwise rotation, in quarter turns.
+#[repr(u8)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Rotation {
+    /// No rotation.
+    #[default]
+    Deg0 = 0,
+    /// A quarter turn clockwise.
+    Deg90 = 1,
+    /// A half turn.
+    Deg180 = 2,
+    /// A quarter turn counter-clockwise.
+    Deg270 = 3,
+}
+
+/// A gesture remapped by the configured [`Orientation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OrientedGesture {
+    gesture: DetectedGesture,
+    raw: DetectedGesture,
+}
+
+impl OrientedGesture {
+    /// Remap a gesture reported by the panel.
+    #[inline]
+    #[must_use]
+    pub const fn new(raw: DetectedGesture, orientation: Orientation) -> Self {
+        Self { gesture: orientation.apply_gesture(raw), raw }
+    }
+
+    /// Get the gesture, as seen on the display.
+    #[inline]
+    #[must_use]
+    pub const fn gesture(&self) -> DetectedGesture { self.gesture }
+
+    /// Get the gesture as reported by the panel, before remapping.
+    #[inline]
+    #[must_use]
+    pub const fn raw(&self) -> DetectedGesture { self.raw }
+
+    /// Returns `true` if any gesture is detected.
+    #[inline]
+    #[must_use]
+    pub const fn is_any(&self) -> bool { self.gesture.is_any() }
+}
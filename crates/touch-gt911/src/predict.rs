@@ -0,0 +1,359 @@
+//! Predicting touch positions between and after touch reports.
+
+use crate::{MAX_TOUCH_POINTS, TouchEvent};
+
+/// The number of fractional bits of predicted positions.
+const POSITION_FRAC: u32 = 8;
+/// The number of fractional bits of velocities, in pixels per millisecond.
+const VELOCITY_FRAC: u32 = 16;
+
+/// The configuration of a [`TouchPredictor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PredictorConfig {
+    /// The furthest a position is extrapolated past the last sample,
+    /// in microseconds.
+    ///
+    /// Positions further ahead are held, which also bounds the overshoot of
+    /// a contact that stopped moving and no longer produces events.
+    pub max_horizon_us: u32,
+    /// The time over which a prediction error is corrected when the next
+    /// sample arrives, in microseconds.
+    ///
+    /// A value of `0` applies every sample immediately.
+    pub correction_us: u32,
+}
+
+impl PredictorConfig {
+    /// A configuration extrapolating up to 20 ms, two reports at 100 Hz,
+    /// and correcting errors over 10 ms.
+    pub const DEFAULT: Self = Self { max_horizon_us: 20_000, correction_us: 10_000 };
+
+    /// Set the furthest a position is extrapolated past the last sample,
+    /// in microseconds.
+    #[inline]
+    #[must_use]
+    pub const fn with_max_horizon_us(mut self, max_horizon_us: u32) -> Self {
+        self.max_horizon_us = max_horizon_us;
+        self
+    }
+
+    /// Set the time over which a prediction error is corrected,
+    /// in microseconds.
+    #[inline]
+    #[must_use]
+    pub const fn with_correction_us(mut self, correction_us: u32) -> Self {
+        self.correction_us = correction_us;
+        self
+    }
+}
+
+impl Default for PredictorConfig {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// Predicts the positions of touch contacts at arbitrary times from
+/// timestamped [`TouchEvent`]s, such as the vertical sync of a display
+/// that refreshes at a different rate than the GT911 reports.
+///
+/// Each contact is tracked by its ID with a constant-velocity model, using
+/// the velocity between its last two samples. Times before the last sample
+/// are interpolated between the last two samples, later times are
+/// extrapolated up to [`PredictorConfig::max_horizon_us`].
+///
+/// When a new sample arrives, the difference between the predicted and the
+/// real position is faded out over [`PredictorConfig::correction_us`], so
+/// that predicted positions do not jump back.
+///
+/// Timestamps are in microseconds and may wrap around.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_gt911::{PredictorConfig, TouchEvent, TouchPoint, TouchPredictor};
+///
+/// let touch = |x, y| TouchPoint { point: 0, x, y, area: 20 };
+///
+/// // Apply every sample immediately, without correcting over time.
+/// let config = PredictorConfig::DEFAULT.with_correction_us(0);
+/// let mut predictor = TouchPredictor::new(config);
+///
+/// // Move right by 10 pixels every 10 ms.
+/// predictor.update(0, &TouchEvent::Down(touch(100, 100)));
+/// predictor.update(10_000, &TouchEvent::Move(touch(110, 100)));
+///
+/// // Extrapolate half a report ahead, or interpolate between the reports.
+/// assert_eq!(predictor.predict(15_000), Some((115, 100)));
+/// assert_eq!(predictor.predict(5_000), Some((105, 100)));
+///
+/// // Extrapolation is clamped to 20 ms past the last report.
+/// assert_eq!(predictor.predict(50_000), Some((130, 100)));
+///
+/// // Lifting the finger ends the prediction.
+/// predictor.update(20_000, &TouchEvent::Up(touch(120, 100)));
+/// assert_eq!(predictor.predict(25_000), None);
+/// ```
+///
+/// Linear motion sampled with jitter, predicted for a 90 Hz display:
+///
+/// ```rust
+/// use ef_gt911::{PredictorConfig, TouchEvent, TouchPoint, TouchPredictor};
+///
+/// // A finger moving at 0.8 px/ms right and 0.3 px/ms down.
+/// let position = |t: u32| (100.0 + 0.8 * t as f32 / 1000.0, 50.0 + 0.3 * t as f32 / 1000.0);
+/// let touch = |t| {
+///     let (x, y) = position(t);
+///     TouchPoint { point: 3, x: x.round() as u16, y: y.round() as u16, area: 20 }
+/// };
+///
+/// let mut predictor = TouchPredictor::new(PredictorConfig::DEFAULT);
+/// predictor.update(0, &TouchEvent::Down(touch(0)));
+///
+/// // Reports every 10 ms, up to 2 ms late.
+/// let reports = (1..50).map(|i| i * 10_000 + [0, 2_000, 700, 1_300][i as usize % 4]);
+/// let mut frames = (0..).map(|i| i * 11_111).peekable();
+/// let mut worst = 0.0f32;
+/// for report in reports {
+///     predictor.update(report, &TouchEvent::Move(touch(report)));
+///
+///     // Draw every frame until the next report, skipping the first reports.
+///     while let Some(frame) = frames.next_if(|frame| *frame < report + 10_000) {
+///         if report >= 30_000 && frame >= report {
+///             let (x, y) = predictor.predict(frame).unwrap();
+///             let (ex, ey) = position(frame);
+///             worst = worst.max((x as f32 - ex).hypot(y as f32 - ey));
+///         }
+///     }
+/// }
+/// assert!(worst <= 1.5, "{worst}");
+/// ```
+///
+/// Circular motion, where the constant-velocity model lags on the curve:
+///
+/// ```rust
+/// use ef_gt911::{PredictorConfig, TouchEvent, TouchPoint, TouchPredictor};
+///
+/// // A finger circling at a radius of 100 px, once per second.
+/// let position = |t: u32| {
+///     let (sin, cos) = (t as f32 / 1_000_000.0 * core::f32::consts::TAU).sin_cos();
+///     (240.0 + 100.0 * cos, 240.0 + 100.0 * sin)
+/// };
+/// let touch = |t| {
+///     let (x, y) = position(t);
+///     TouchPoint { point: 1, x: x.round() as u16, y: y.round() as u16, area: 20 }
+/// };
+///
+/// let mut predictor = TouchPredictor::new(PredictorConfig::DEFAULT);
+/// predictor.update(0, &TouchEvent::Down(touch(0)));
+///
+/// let mut worst = 0.0f32;
+/// let mut last = None;
+/// for report in (1..100).map(|i| i * 10_000) {
+///     predictor.update(report, &TouchEvent::Move(touch(report)));
+///
+///     // Query every millisecond until the next report, skipping the first reports.
+///     for frame in (report..report + 10_000).step_by(1_000) {
+///         let (x, y) = predictor.predict(frame).unwrap();
+///         if report >= 30_000 {
+///             let (ex, ey) = position(frame);
+///             worst = worst.max((x as f32 - ex).hypot(y as f32 - ey));
+///         }
+///
+///         // The position never jumps, even when a new report arrives.
+///         if let Some((lx, ly)) = last {
+///             assert!(u16::abs_diff(x, lx) <= 2 && u16::abs_diff(y, ly) <= 2);
+///         }
+///         last = Some((x, y));
+///     }
+/// }
+/// assert!(worst <= 4.0, "{worst}");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TouchPredictor {
+    config: PredictorConfig,
+    tracks: [Option<Track>; MAX_TOUCH_POINTS],
+}
+
+impl TouchPredictor {
+    /// Create a new [`TouchPredictor`] without any tracked contacts.
+    #[must_use]
+    pub const fn new(config: PredictorConfig) -> Self {
+        Self { config, tracks: [None; MAX_TOUCH_POINTS] }
+    }
+
+    /// Get the [`PredictorConfig`].
+    #[inline]
+    #[must_use]
+    pub const fn config(&self) -> &PredictorConfig { &self.config }
+
+    /// Record a [`TouchEvent`] received at `at_us`.
+    ///
+    /// Presses start tracking a contact and lifts stop it. Presses beyond
+    /// the number of contacts the GT911 can report are ignored.
+    pub fn update(&mut self, at_us: u32, event: &TouchEvent) {
+        let (id, point) = (event.id(), [event.point().x, event.point().y]);
+        let index = self.tracks.iter().position(|track| track.is_some_and(|track| track.id == id));
+        match (event, index) {
+            (TouchEvent::Up(_), Some(index)) => self.tracks[index] = None,
+            (TouchEvent::Move(_), Some(index)) => {
+                if let Some(track) = &mut self.tracks[index] {
+                    track.sample(self.config, at_us, point);
+                }
+            }
+            (TouchEvent::Down(_), Some(index)) => {
+                self.tracks[index] = Some(Track::new(id, at_us, point));
+            }
+            (TouchEvent::Down(_) | TouchEvent::Move(_), None) => {
+                if let Some(slot) = self.tracks.iter_mut().find(|track| track.is_none()) {
+                    *slot = Some(Track::new(id, at_us, point));
+                }
+            }
+            (TouchEvent::Up(_), None) => {}
+        }
+    }
+
+    /// Predict the position of the contact with the lowest track ID
+    /// at `at_us`.
+    ///
+    /// The GT911 gives new contacts the lowest free ID, so this is usually
+    /// the first finger still down.
+    #[must_use]
+    pub fn predict(&self, at_us: u32) -> Option<(u16, u16)> {
+        let track = self.tracks.iter().flatten().min_by_key(|track| track.id)?;
+        Some(track.predict(self.config, at_us))
+    }
+
+    /// Predict the position of the contact with a track ID at `at_us`.
+    #[must_use]
+    pub fn predict_track(&self, id: u8, at_us: u32) -> Option<(u16, u16)> {
+        let track = self.tracks.iter().flatten().find(|track| track.id == id)?;
+        Some(track.predict(self.config, at_us))
+    }
+
+    /// Iterate over the track IDs of the tracked contacts.
+    #[inline]
+    pub fn tracks(&self) -> impl Iterator<Item = u8> + '_ {
+        self.tracks.iter().flatten().map(|track| track.id)
+    }
+
+    /// Stop tracking every contact.
+    #[inline]
+    pub fn clear(&mut self) { self.tracks = [None; MAX_TOUCH_POINTS]; }
+}
+
+impl Default for TouchPredictor {
+    fn default() -> Self { Self::new(PredictorConfig::DEFAULT) }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// The motion of a single contact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Track {
+    id: u8,
+    /// The time of the last sample.
+    time: u32,
+    /// The time of the sample before it.
+    previous_time: u32,
+    /// The position of the last sample, with [`POSITION_FRAC`] bits.
+    position: [i32; 2],
+    /// The position of the sample before it, with [`POSITION_FRAC`] bits.
+    previous: [i32; 2],
+    /// The velocity between the last two samples, with [`VELOCITY_FRAC`]
+    /// bits in pixels per millisecond.
+    velocity: [i32; 2],
+    /// The prediction error when the last sample arrived,
+    /// with [`POSITION_FRAC`] bits.
+    correction: [i32; 2],
+}
+
+impl Track {
+    /// Start tracking a contact at rest.
+    fn new(id: u8, time: u32, [x, y]: [u16; 2]) -> Self {
+        let position = [i32::from(x) << POSITION_FRAC, i32::from(y) << POSITION_FRAC];
+        Self {
+            id,
+            time,
+            previous_time: time,
+            position,
+            previous: position,
+            velocity: [0; 2],
+            correction: [0; 2],
+        }
+    }
+
+    /// Add a sample, keeping the predicted position where it was.
+    fn sample(&mut self, config: PredictorConfig, time: u32, [x, y]: [u16; 2]) {
+        let position = [i32::from(x) << POSITION_FRAC, i32::from(y) << POSITION_FRAC];
+        let predicted = self.extrapolate(config, time);
+
+        let elapsed = elapsed(self.time, time);
+        if elapsed > 0 {
+            self.velocity = core::array::from_fn(|axis| {
+                let moved = i64::from(position[axis] - self.position[axis]);
+                let velocity = (moved << (VELOCITY_FRAC - POSITION_FRAC)) * 1000 / elapsed;
+                i32::try_from(velocity).unwrap_or(if velocity < 0 { i32::MIN } else { i32::MAX })
+            });
+            self.previous = self.position;
+            self.previous_time = self.time;
+        }
+
+        self.time = time;
+        self.position = position;
+        self.correction = if config.correction_us == 0 {
+            [0; 2]
+        } else {
+            [predicted[0] - position[0], predicted[1] - position[1]]
+        };
+    }
+
+    /// Predict the position at a time, rounded to whole pixels.
+    fn predict(&self, config: PredictorConfig, time: u32) -> (u16, u16) {
+        let position = if elapsed(self.time, time) < 0 {
+            self.interpolate(time)
+        } else {
+            self.extrapolate(config, time)
+        };
+        let [x, y] = position.map(|axis| {
+            let pixel = (axis + (1 << (POSITION_FRAC - 1))) >> POSITION_FRAC;
+            u16::try_from(pixel.max(0)).unwrap_or(u16::MAX)
+        });
+        (x, y)
+    }
+
+    /// Interpolate between the last two samples, at or before the last one.
+    fn interpolate(&self, time: u32) -> [i32; 2] {
+        let span = elapsed(self.previous_time, self.time);
+        let since = elapsed(self.previous_time, time).clamp(0, span);
+        if span == 0 {
+            return self.position;
+        }
+        core::array::from_fn(|axis| {
+            let moved = i64::from(self.position[axis] - self.previous[axis]) * since / span;
+            self.previous[axis] + i32::try_from(moved).unwrap_or_default()
+        })
+    }
+
+    /// Extrapolate past the last sample, fading out the correction.
+    fn extrapolate(&self, config: PredictorConfig, time: u32) -> [i32; 2] {
+        let since = elapsed(self.time, time).max(0);
+        let ahead = since.min(i64::from(config.max_horizon_us));
+        let fade = i64::from(config.correction_us);
+        let remaining = (fade - since).max(0);
+
+        core::array::from_fn(|axis| {
+            let moved = i64::from(self.velocity[axis]) * ahead / 1000;
+            let moved = moved >> (VELOCITY_FRAC - POSITION_FRAC);
+            let correction =
+                if fade == 0 { 0 } else { i64::from(self.correction[axis]) * remaining / fade };
+            let position = i64::from(self.position[axis]) + moved + correction;
+            i32::try_from(position).unwrap_or(if position < 0 { i32::MIN } else { i32::MAX })
+        })
+    }
+}
+
+/// The signed time from `from` to `to`, in microseconds,
+/// allowing the timestamps to wrap around.
+fn elapsed(from: u32, to: u32) -> i64 { i64::from(to.wrapping_sub(from).cast_signed()) }
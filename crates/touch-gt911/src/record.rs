@@ -0,0 +1,295 @@
+//! Recording touch reports and replaying them without hardware.
+//!
+//! A [`ReportRecorder`] writes every [`TouchReport`] with a timestamp into a
+//! compact binary recording, and a [`ReportReplayer`] reads it back. Replayed
+//! reports can be fed into the gesture recognizers the same way as reports
+//! read from a [`GT911`](crate::GT911), to reproduce a touch interaction in a
+//! test or a host simulator.
+//!
+//! # Format
+//!
+//! A recording starts with a header:
+//!
+//! | Bytes | Content                          |
+//! |-------|----------------------------------|
+//! | 4     | The magic bytes `GT9R`           |
+//! | 1     | The format [`VERSION`]           |
+//!
+//! Followed by one record per report, with all numbers in little-endian:
+//!
+//! | Bytes | Content                          |
+//! |-------|----------------------------------|
+//! | 4     | The timestamp, in milliseconds   |
+//! | 1     | The number of touch points (0-5) |
+//! | 7 × n | The ID, X, Y and area of a point |
+//!
+//! Recordings may come from untrusted files, so every length is checked
+//! while replaying and malformed recordings return a [`RecordError`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use ef_gt911::{
+//!     MultiTouchGesture, MultiTouchGestures, TouchPoint, TouchReport,
+//!     record::{ReportRecorder, ReportReplayer},
+//! };
+//!
+//! let touch = |point, x| TouchPoint { point, x, y: 240, area: 20 };
+//!
+//! // Record two fingers spreading apart, one report every 10 ms
+//! let mut recording = Vec::new();
+//! let mut recorder = ReportRecorder::new(|bytes: &[u8]| recording.extend_from_slice(bytes));
+//! for step in 0..=20 {
+//!     let report =
+//!         TouchReport::<5>::from_points(&[touch(0, 200 - 2 * step), touch(1, 280 + 2 * step)]);
+//!     recorder.record(u32::from(step) * 10, &report);
+//! }
+//! recorder.record(210, &TouchReport::<5>::EMPTY);
+//! assert_eq!(recorder.records(), 22);
+//! drop(recorder);
+//!
+//! // Each two-finger report takes 19 bytes, after the 5-byte header
+//! assert_eq!(recording.len(), 5 + 21 * 19 + 5);
+//!
+//! // Replay the recording into the gesture recognizer
+//! let mut gestures = MultiTouchGestures::new();
+//! let mut pinches = 0;
+//! for timed in ReportReplayer::<5>::new(&recording)? {
+//!     let timed = timed?;
+//!     for event in gestures.record(timed.report.points()) {
+//!         assert!(matches!(event, MultiTouchGesture::Pinch { .. }));
+//!         pinches += 1;
+//!     }
+//! }
+//! assert_eq!(pinches, 17);
+//! assert!(!gestures.is_tracking());
+//! # Ok::<(), ef_gt911::record::RecordError>(())
+//! ```
+
+use crate::{MAX_TOUCH_POINTS, TouchPoint, TouchReport};
+
+/// The version of the recording format written by [`ReportRecorder`].
+pub const VERSION: u8 = 1;
+
+/// The magic bytes at the start of every recording.
+const MAGIC: [u8; 4] = *b"GT9R";
+/// The length of the recording header.
+const HEADER_LEN: usize = MAGIC.len() + 1;
+/// The length of a record without any touch points.
+const RECORD_HEADER_LEN: usize = 5;
+/// The length of a single touch point in a record.
+const POINT_LEN: usize = 7;
+/// The length of a record with every touch point.
+const MAX_RECORD_LEN: usize = RECORD_HEADER_LEN + MAX_TOUCH_POINTS * POINT_LEN;
+
+/// Writes [`TouchReport`]s into a recording, see the [module](self) docs.
+///
+/// Every record is passed to the sink in a single call.
+#[derive(Debug)]
+pub struct ReportRecorder<F: FnMut(&[u8])> {
+    sink: F,
+    records: usize,
+}
+
+impl<F: FnMut(&[u8])> ReportRecorder<F> {
+    /// Create a new [`ReportRecorder`], writing the recording header to
+    /// `sink`.
+    #[must_use]
+    pub fn new(mut sink: F) -> Self {
+        let mut header = [0u8; HEADER_LEN];
+        header[..MAGIC.len()].copy_from_slice(&MAGIC);
+        header[MAGIC.len()] = VERSION;
+        sink(&header);
+
+        Self { sink, records: 0 }
+    }
+
+    /// Record a [`TouchReport`], read at `timestamp_ms`.
+    #[expect(clippy::cast_possible_truncation, reason = "Reports hold at most 5 points")]
+    pub fn record<const MAX: usize>(&mut self, timestamp_ms: u32, report: &TouchReport<MAX>) {
+        let points = &report.points()[..report.len().min(MAX_TOUCH_POINTS)];
+
+        let mut record = [0u8; MAX_RECORD_LEN];
+        record[..4].copy_from_slice(&timestamp_ms.to_le_bytes());
+        record[4] = points.len() as u8;
+        for (chunk, point) in record[RECORD_HEADER_LEN..].chunks_exact_mut(POINT_LEN).zip(points) {
+            chunk[0] = point.point;
+            chunk[1..3].copy_from_slice(&point.x.to_le_bytes());
+            chunk[3..5].copy_from_slice(&point.y.to_le_bytes());
+            chunk[5..7].copy_from_slice(&point.area.to_le_bytes());
+        }
+
+        (self.sink)(&record[..RECORD_HEADER_LEN + points.len() * POINT_LEN]);
+        self.records += 1;
+    }
+
+    /// Get the number of reports recorded so far.
+    #[inline]
+    #[must_use]
+    pub const fn records(&self) -> usize { self.records }
+
+    /// Release the sink.
+    #[inline]
+    #[must_use]
+    pub fn release(self) -> F { self.sink }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A [`TouchReport`] replayed from a recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimedReport<const MAX: usize = MAX_TOUCH_POINTS> {
+    /// When the report was read, in milliseconds.
+    pub timestamp_ms: u32,
+    /// The touch points of the report.
+    pub report: TouchReport<MAX>,
+}
+
+/// Reads [`TimedReport`]s from a recording, see the [module](self) docs.
+///
+/// Reports with more than `MAX` touch points keep the first `MAX`, in the
+/// same way as a [`GT911`](crate::GT911) reporting at most `MAX` points.
+///
+/// Iteration stops after the first error.
+///
+/// # Example
+///
+/// Malformed recordings are rejected without panicking.
+///
+/// ```rust
+/// use ef_gt911::record::{RecordError, ReportReplayer, VERSION};
+///
+/// /// Replay a recording, collecting the timestamps.
+/// fn replay(bytes: &[u8]) -> Result<Vec<u32>, RecordError> {
+///     ReportReplayer::<5>::new(bytes)?.map(|timed| Ok(timed?.timestamp_ms)).collect()
+/// }
+///
+/// // A header and two records, with one and zero touch points
+/// let mut valid = vec![b'G', b'T', b'9', b'R', VERSION];
+/// // At 16 ms, point 0 at (480, 240) with an area of 20
+/// valid.extend([0x10, 0x00, 0x00, 0x00, 1, 0, 0xE0, 0x01, 0xF0, 0x00, 0x14, 0x00]);
+/// // At 32 ms, without touch points
+/// valid.extend([0x20, 0x00, 0x00, 0x00, 0]);
+/// assert_eq!(replay(&valid), Ok(vec![0x10, 0x20]));
+///
+/// // Wrong magic bytes and unknown versions
+/// assert_eq!(replay(b"GT9X\x01"), Err(RecordError::InvalidMagic));
+/// assert_eq!(replay(b"GT9R\x02"), Err(RecordError::UnsupportedVersion(2)));
+///
+/// // Cut off anywhere but between records
+/// for length in (0..valid.len()).filter(|length| ![5, 17].contains(length)) {
+///     let expected = if length < 5 { RecordError::InvalidMagic } else { RecordError::Truncated };
+///     assert_eq!(replay(&valid[..length]), Err(expected), "{length} bytes");
+/// }
+/// assert_eq!(replay(&valid[..5]), Ok(vec![]));
+/// assert_eq!(replay(&valid[..17]), Ok(vec![0x10]));
+///
+/// // More touch points than the GT911 can report
+/// let mut invalid = valid.clone();
+/// invalid[9] = 6;
+/// assert_eq!(replay(&invalid), Err(RecordError::TooManyPoints(6)));
+///
+/// // Random bytes after a valid header never panic
+/// let mut seed = 0x2545_F491u32;
+/// for length in 0..256 {
+///     let mut bytes = valid[..5].to_vec();
+///     bytes.extend((0..length).map(|_| {
+///         seed ^= seed << 13;
+///         seed ^= seed >> 17;
+///         seed ^= seed << 5;
+///         seed.to_le_bytes()[0] & 0x0F
+///     }));
+///     let _ = replay(&bytes);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReportReplayer<'a, const MAX: usize = MAX_TOUCH_POINTS> {
+    data: &'a [u8],
+    position: usize,
+    failed: bool,
+}
+
+impl<'a, const MAX: usize> ReportReplayer<'a, MAX> {
+    /// Create a new [`ReportReplayer`], checking the recording header.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the recording does not start with a valid header,
+    /// or if it was written by an unsupported version.
+    pub fn new(data: &'a [u8]) -> Result<Self, RecordError> {
+        let Some((header, _)) = data.split_first_chunk::<HEADER_LEN>() else {
+            return Err(RecordError::InvalidMagic);
+        };
+        if header[..MAGIC.len()] != MAGIC {
+            return Err(RecordError::InvalidMagic);
+        }
+        if header[MAGIC.len()] != VERSION {
+            return Err(RecordError::UnsupportedVersion(header[MAGIC.len()]));
+        }
+
+        Ok(Self { data, position: HEADER_LEN, failed: false })
+    }
+
+    /// Get the number of bytes replayed so far, including the header.
+    #[inline]
+    #[must_use]
+    pub const fn position(&self) -> usize { self.position }
+
+    /// Parse the record at the current position.
+    fn parse(&mut self) -> Result<TimedReport<MAX>, RecordError> {
+        let rest = &self.data[self.position..];
+        let Some((header, rest)) = rest.split_first_chunk::<RECORD_HEADER_LEN>() else {
+            return Err(RecordError::Truncated);
+        };
+
+        let count = header[4];
+        if usize::from(count) > MAX_TOUCH_POINTS {
+            return Err(RecordError::TooManyPoints(count));
+        }
+        let Some(points) = rest.get(..usize::from(count) * POINT_LEN) else {
+            return Err(RecordError::Truncated);
+        };
+
+        let mut buffer = [TouchPoint::EMPTY; MAX_TOUCH_POINTS];
+        for (point, chunk) in buffer.iter_mut().zip(points.chunks_exact(POINT_LEN)) {
+            let mut bytes = [0u8; POINT_LEN];
+            bytes.copy_from_slice(chunk);
+            *point = TouchPoint::from_bytes(bytes);
+        }
+
+        self.position += RECORD_HEADER_LEN + points.len();
+        Ok(TimedReport {
+            timestamp_ms: u32::from_le_bytes([header[0], header[1], header[2], header[3]]),
+            report: TouchReport::from_points(&buffer[..usize::from(count)]),
+        })
+    }
+}
+
+impl<const MAX: usize> Iterator for ReportReplayer<'_, MAX> {
+    type Item = Result<TimedReport<MAX>, RecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.position >= self.data.len() {
+            return None;
+        }
+
+        let result = self.parse();
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+/// An error that can occur when replaying a recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecordError {
+    /// The recording does not start with the magic bytes.
+    InvalidMagic,
+    /// The recording was written by an unsupported format version.
+    UnsupportedVersion(u8),
+    /// The recording ends partway through a record.
+    Truncated,
+    /// A record has more touch points than the GT911 can report.
+    TooManyPoints(u8),
+}
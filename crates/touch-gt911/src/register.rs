@@ -250,3 +250,10 @@ register! {
     GT911_GESTURE_POINT1_Y_MSB: 0x9423,
     // Continues through 0x951F for up to 64 gesture points
 }
+
+/// Get the bytes of a register address as sent on the bus.
+///
+/// The GT911 expects register addresses most significant byte first,
+/// unlike the little-endian values stored in the registers.
+#[inline]
+pub(super) const fn register_bytes(register: u16) -> [u8; 2] { register.to_be_bytes() }
@@ -0,0 +1,108 @@
+//! The reset sequence, which also selects the I2C address,
+//! waiting for the device to become ready afterwards,
+//! waking it from sleep and entering gesture mode.
+
+use embedded_hal::digital::PinState;
+
+/// The longest wait between two status polls, in milliseconds.
+const READY_MAX_BACKOFF_MS: u32 = 8;
+
+/// The command that puts the device to sleep.
+pub(crate) const SLEEP_COMMAND: u8 = 0x05;
+
+/// The command that enters gesture mode.
+pub(crate) const GESTURE_COMMAND: u8 = 0x08;
+
+/// How many times the gesture command is sent before giving up,
+/// as the device may not acknowledge writes while it is scanning.
+pub(crate) const GESTURE_ATTEMPTS: u8 = 5;
+
+/// The wait between attempts to send the gesture command, in milliseconds.
+pub(crate) const GESTURE_RETRY_MS: u32 = 10;
+
+/// How long the device takes to confirm gesture mode, in milliseconds.
+pub(crate) const GESTURE_CONFIRM_MS: u32 = 50;
+
+/// How long INT is driven high to wake the device, in microseconds.
+///
+/// The datasheet asks for a pulse of 2 to 5 ms.
+pub(crate) const WAKE_PULSE_US: u32 = 3_000;
+
+/// How long the device takes to accept commands after waking,
+/// in microseconds.
+pub(crate) const WAKE_SETTLE_US: u32 = 58_000;
+
+/// The I2C address selected by the reset sequence.
+#[repr(u8)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GT911Address {
+    /// Address `0x5D`, selected by holding INT low during the reset.
+    #[default]
+    Primary = 0x5D,
+    /// Address `0x14`, selected by holding INT high during the reset.
+    Secondary = 0x14,
+}
+
+impl GT911Address {
+    /// Get the 7-bit I2C address.
+    #[inline]
+    #[must_use]
+    pub const fn address(self) -> u8 { self as u8 }
+
+    /// Get the state INT is held in while RST is released.
+    const fn int_state(self) -> PinState {
+        match self {
+            Self::Primary => PinState::Low,
+            Self::Secondary => PinState::High,
+        }
+    }
+}
+
+/// A step of the reset sequence, shared by both driver modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ResetStep {
+    /// Drive the RST pin.
+    Rst(PinState),
+    /// Drive the INT pin.
+    Int(PinState),
+    /// Wait for a number of microseconds.
+    DelayUs(u32),
+}
+
+/// Get the reset sequence selecting `address`.
+///
+/// Timings follow the power-on sequence of the GT911 datasheet, with the
+/// margins used by the Linux `goodix` driver:
+///
+/// 1. RST and INT are driven low for more than 10 ms.
+/// 2. INT selects the address for more than 100 µs before RST is released.
+/// 3. INT keeps the address for more than 5 ms after RST is released.
+/// 4. INT is driven low for 50 ms to synchronize the interrupt output.
+pub(crate) const fn reset_steps(address: GT911Address) -> [ResetStep; 9] {
+    [
+        ResetStep::Rst(PinState::Low),
+        ResetStep::Int(PinState::Low),
+        ResetStep::DelayUs(20_000),
+        ResetStep::Int(address.int_state()),
+        ResetStep::DelayUs(200),
+        ResetStep::Rst(PinState::High),
+        ResetStep::DelayUs(6_000),
+        ResetStep::Int(PinState::Low),
+        ResetStep::DelayUs(50_000),
+    ]
+}
+
+/// Get the waits between status polls while waiting for the device to
+/// become ready, in milliseconds.
+///
+/// Waits double from 1 ms up to 8 ms, and add up to `timeout_ms`.
+pub(crate) fn ready_backoff(timeout_ms: u32) -> impl Iterator<Item = u32> {
+    let (mut remaining, mut backoff) = (timeout_ms, 1);
+    core::iter::from_fn(move || {
+        let wait = backoff.min(remaining);
+        remaining -= wait;
+        backoff = (backoff * 2).min(READY_MAX_BACKOFF_MS);
+        (wait > 0).then_some(wait)
+    })
+}
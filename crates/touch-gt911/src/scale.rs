@@ -0,0 +1,135 @@
+//! Scaling coordinates without division.
+
+use crate::TouchPoint;
+
+/// Scales coordinates from `0..=in_max` onto `0..=out_max`.
+///
+/// The ratio is stored as a Q16 fixed-point factor, computed once when the
+/// scale is created, so scaling a coordinate is a single 32-bit multiply and
+/// shift. This matters on cores without a hardware divider, such as the
+/// Cortex-M0, where each division costs hundreds of cycles.
+///
+/// # Error bound
+///
+/// The factor is rounded up, so results are never below the exact
+/// `x * out_max / in_max` rounded down, and exceed it by at most 1. Both
+/// ends of the range are exact, `0` and `in_max` map to `0` and `out_max`.
+///
+/// Coordinates above `in_max` are clamped to it first, which also keeps the
+/// product within 32 bits.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_gt911::AxisScale;
+///
+/// // Every possible input, for panels scaled up, down and not at all
+/// for (in_max, out_max) in
+///     [(479, 479), (1023, 479), (479, 1023), (799, 319), (4095, 1279), (65535, 1), (1, 65535)]
+/// {
+///     let scale = AxisScale::new(in_max, out_max);
+///     for x in 0..=u16::MAX {
+///         let exact = u32::from(x.min(in_max)) * u32::from(out_max) / u32::from(in_max);
+///         let fast = u32::from(scale.apply(x));
+///         assert!(fast == exact || fast == exact + 1, "{x} -> {fast}, expected {exact}");
+///     }
+///     assert_eq!((scale.apply(0), scale.apply(in_max)), (0, out_max));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AxisScale {
+    in_max: u16,
+    factor: u32,
+}
+
+impl AxisScale {
+    /// A scale that does not change any coordinates.
+    pub const IDENTITY: Self = Self::new(u16::MAX, u16::MAX);
+
+    /// Create a new [`AxisScale`] from `0..=in_max` onto `0..=out_max`.
+    ///
+    /// An `in_max` of `0` maps every coordinate to `0`.
+    #[must_use]
+    pub const fn new(in_max: u16, out_max: u16) -> Self {
+        let factor = if in_max == 0 { 0 } else { ((out_max as u32) << 16).div_ceil(in_max as u32) };
+        Self { in_max, factor }
+    }
+
+    /// Get the largest input coordinate.
+    #[inline]
+    #[must_use]
+    pub const fn in_max(&self) -> u16 { self.in_max }
+
+    /// Scale a coordinate.
+    #[inline]
+    #[must_use]
+    pub const fn apply(&self, value: u16) -> u16 {
+        let value = if value > self.in_max { self.in_max } else { value };
+        ((value as u32 * self.factor) >> 16) as u16
+    }
+}
+
+impl Default for AxisScale {
+    fn default() -> Self { Self::IDENTITY }
+}
+
+/// Scales touch points from the panel resolution to an output resolution,
+/// with an [`AxisScale`] per axis.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_gt911::{OutputScale, TouchPoint};
+///
+/// // A 1024x600 panel in front of a 480x272 display
+/// let scale = OutputScale::new((1024, 600), (480, 272));
+///
+/// let point = TouchPoint { point: 0, x: 512, y: 300, area: 20 };
+/// assert_eq!(scale.apply(point), TouchPoint { x: 239, y: 135, ..point });
+/// assert_eq!(scale.apply_xy(1023, 599), (479, 271));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OutputScale {
+    /// The scale of the X axis.
+    pub x: AxisScale,
+    /// The scale of the Y axis.
+    pub y: AxisScale,
+}
+
+impl OutputScale {
+    /// A scale that does not change any coordinates.
+    pub const IDENTITY: Self = Self { x: AxisScale::IDENTITY, y: AxisScale::IDENTITY };
+
+    /// Create a new [`OutputScale`] between two `(width, height)` resolutions.
+    ///
+    /// Coordinates run from `0` to one less than the resolution, so the last
+    /// column and row of the panel map to the last of the output.
+    #[must_use]
+    pub const fn new(panel: (u16, u16), output: (u16, u16)) -> Self {
+        Self {
+            x: AxisScale::new(panel.0.saturating_sub(1), output.0.saturating_sub(1)),
+            y: AxisScale::new(panel.1.saturating_sub(1), output.1.saturating_sub(1)),
+        }
+    }
+
+    /// Scale a pair of coordinates.
+    #[inline]
+    #[must_use]
+    pub const fn apply_xy(&self, x: u16, y: u16) -> (u16, u16) {
+        (self.x.apply(x), self.y.apply(y))
+    }
+
+    /// Scale a [`TouchPoint`].
+    #[inline]
+    #[must_use]
+    pub const fn apply(&self, point: TouchPoint) -> TouchPoint {
+        let (x, y) = self.apply_xy(point.x, point.y);
+        TouchPoint { x, y, ..point }
+    }
+}
+
+impl Default for OutputScale {
+    fn default() -> Self { Self::IDENTITY }
+}
@@ -0,0 +1,208 @@
+//! Mock buses for examples and tests, without a device.
+//!
+//! Enabled with the `test-util` feature.
+
+use alloc::vec::Vec;
+use core::{
+    future::poll_fn,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use embedded_hal::i2c::{self, ErrorKind, ErrorType, NoAcknowledgeSource, Operation};
+use embedded_hal_async::i2c as i2c_async;
+
+/// A bus holding the registers of a single device, starting at `0x8000`.
+///
+/// Register addresses are written most significant byte first, optionally
+/// followed by the values to store from that address on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registers {
+    /// The registers, indexed from `0x8000`.
+    pub registers: Vec<u8>,
+    /// The bytes of every write, including the register address.
+    pub writes: Vec<Vec<u8>>,
+    /// The register and length of every read.
+    pub reads: Vec<(u16, usize)>,
+    /// The number of transactions.
+    pub transactions: usize,
+    /// The longest read accepted, longer reads fail with
+    /// [`ErrorKind::Overrun`].
+    pub max_read: usize,
+    /// The only address acknowledged, if limited.
+    pub address: Option<u8>,
+    /// The number of status reads left before the device is ready,
+    /// if it is still starting up.
+    pub ready_after: Option<usize>,
+    /// Whether register values are ignored, as a device does with a
+    /// configuration version below its own.
+    pub read_only: bool,
+    /// Whether the product ID switches to `GEST` in gesture mode.
+    pub gestures: bool,
+    /// Whether every async transaction waits for one poll first.
+    pub pending: bool,
+    pointer: u16,
+}
+
+impl Registers {
+    /// The command register.
+    const COMMAND: u16 = 0x8040;
+    /// The product ID register.
+    const PRODUCT_ID: usize = 0x140;
+    /// The status register.
+    const STATUS: u16 = 0x814E;
+
+    /// Create a new [`Registers`] holding `registers` from `0x8000` on.
+    #[must_use]
+    pub fn new(registers: &[u8]) -> Self {
+        Self {
+            registers: registers.to_vec(),
+            writes: Vec::new(),
+            reads: Vec::new(),
+            transactions: 0,
+            max_read: usize::MAX,
+            address: None,
+            ready_after: None,
+            read_only: false,
+            gestures: true,
+            pending: false,
+            pointer: 0,
+        }
+    }
+
+    /// Fail reads longer than `max_read` bytes.
+    #[must_use]
+    pub const fn with_max_read(mut self, max_read: usize) -> Self {
+        self.max_read = max_read;
+        self
+    }
+
+    /// Only acknowledge `address`.
+    #[must_use]
+    pub const fn with_address(mut self, address: u8) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /// Report ready only on status read `reads`.
+    #[must_use]
+    pub const fn with_ready_after(mut self, reads: usize) -> Self {
+        self.ready_after = Some(reads);
+        self
+    }
+
+    /// Keep the touch product ID in gesture mode.
+    #[must_use]
+    pub const fn without_gestures(mut self) -> Self {
+        self.gestures = false;
+        self
+    }
+
+    /// Wait for one poll before every async transaction.
+    #[must_use]
+    pub const fn with_pending(mut self) -> Self {
+        self.pending = true;
+        self
+    }
+
+    /// Get every write of register values, as the first register and the
+    /// values written.
+    #[must_use]
+    pub fn register_writes(&self) -> Vec<(u16, Vec<u8>)> {
+        self.writes
+            .iter()
+            .filter_map(|bytes| match bytes.as_slice() {
+                [hi, lo, data @ ..] if !data.is_empty() => {
+                    Some((u16::from_be_bytes([*hi, *lo]), data.to_vec()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Apply the operations of a transaction.
+    fn apply(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        if self.address.is_some_and(|expected| expected != address) {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+        self.transactions += 1;
+
+        for op in ops {
+            match op {
+                Operation::Write(bytes) => {
+                    let [hi, lo, data @ ..] = &**bytes else { return Err(ErrorKind::Other) };
+                    self.pointer = u16::from_be_bytes([*hi, *lo]);
+                    if !data.is_empty() && !self.read_only {
+                        self.store(data);
+                    }
+                    self.writes.push(bytes.to_vec());
+                }
+                Operation::Read(buf) if buf.len() > self.max_read => {
+                    return Err(ErrorKind::Overrun);
+                }
+                Operation::Read(buf) => {
+                    let start = self.index();
+                    if self.pointer == Self::STATUS
+                        && let Some(left) = &mut self.ready_after
+                    {
+                        *left = left.saturating_sub(1);
+                        self.registers[start] = if *left == 0 { 0x80 } else { 0x00 };
+                    }
+                    buf.copy_from_slice(&self.registers[start..start + buf.len()]);
+                    self.reads.push((self.pointer, buf.len()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Store `data` from the current register on.
+    fn store(&mut self, data: &[u8]) {
+        let start = self.index();
+        self.registers[start..start + data.len()].copy_from_slice(data);
+        if self.gestures && self.pointer == Self::COMMAND && data == [0x08] {
+            self.registers[Self::PRODUCT_ID..Self::PRODUCT_ID + 4].copy_from_slice(b"GEST");
+        }
+    }
+
+    /// Get the index of the current register.
+    fn index(&self) -> usize { usize::from(self.pointer - 0x8000) }
+}
+
+impl ErrorType for Registers {
+    type Error = ErrorKind;
+}
+
+impl i2c::I2c for Registers {
+    fn transaction(&mut self, address: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.apply(address, ops)
+    }
+}
+
+impl i2c_async::I2c for Registers {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        ops: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        if self.pending {
+            let mut waited = false;
+            poll_fn(|_| {
+                if core::mem::replace(&mut waited, true) { Poll::Ready(()) } else { Poll::Pending }
+            })
+            .await;
+        }
+        self.apply(address, ops)
+    }
+}
+
+/// Run a future to completion, polling it until it is ready.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
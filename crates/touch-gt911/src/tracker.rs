@@ -0,0 +1,226 @@
+//! Turning touch frames into press, move and release events.
+
+use crate::{MAX_TOUCH_POINTS, TouchFrame, TouchPoint};
+
+/// Tracks touch points across [`TouchFrame`]s, emitting a [`TouchEvent`]
+/// whenever a finger is pressed, moved or lifted.
+///
+/// The GT911 keeps the track ID of a touch point while the finger stays
+/// down, and drops it from the frame once the finger is lifted. Each frame
+/// is compared by ID against the previous one: new IDs are pressed, IDs
+/// that moved are moved, and missing IDs are lifted. An ID that is lifted
+/// and pressed again in a later frame is a new touch.
+///
+/// Frames that are not ready hold no new data and are ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_gt911::{DetectedTouch, TouchEvent, TouchFrame, TouchPoint, TouchTracker};
+///
+/// /// A ready frame with touch points of `(id, x, y)`.
+/// fn frame(points: &[(u8, u16, u16)]) -> TouchFrame {
+///     let mut frame = TouchFrame::EMPTY;
+///     frame.status = DetectedTouch::from_bits_truncate(0x80 | points.len() as u8);
+///     for (slot, &(point, x, y)) in frame.points.iter_mut().zip(points) {
+///         *slot = Some(TouchPoint { point, x, y, area: 20 });
+///     }
+///     frame
+/// }
+///
+/// let touch = |point, x, y| TouchPoint { point, x, y, area: 20 };
+/// let mut tracker = TouchTracker::new();
+///
+/// // A first finger is pressed, then a second one.
+/// let events: Vec<_> = tracker.update(&frame(&[(0, 100, 100)])).collect();
+/// assert_eq!(events, [TouchEvent::Down(touch(0, 100, 100))]);
+/// let events: Vec<_> = tracker.update(&frame(&[(0, 100, 100), (1, 300, 200)])).collect();
+/// assert_eq!(events, [TouchEvent::Down(touch(1, 300, 200))]);
+///
+/// // Fingers that did not move produce no events.
+/// assert_eq!(tracker.update(&frame(&[(0, 100, 100), (1, 300, 200)])).count(), 0);
+///
+/// // Both fingers move.
+/// let events: Vec<_> = tracker.update(&frame(&[(0, 110, 100), (1, 290, 205)])).collect();
+/// assert_eq!(
+///     events,
+///     [TouchEvent::Move(touch(0, 110, 100)), TouchEvent::Move(touch(1, 290, 205))]
+/// );
+///
+/// // The first finger is lifted while a third is pressed in the same frame.
+/// let events: Vec<_> = tracker.update(&frame(&[(1, 290, 205), (2, 50, 60)])).collect();
+/// assert_eq!(events, [TouchEvent::Up(touch(0, 110, 100)), TouchEvent::Down(touch(2, 50, 60))]);
+///
+/// // A frame that is not ready holds no new data.
+/// assert_eq!(tracker.update(&TouchFrame::EMPTY).count(), 0);
+/// assert_eq!(tracker.active().count(), 2);
+///
+/// // Lifting every finger releases both tracks.
+/// let events: Vec<_> = tracker.update(&frame(&[])).collect();
+/// assert_eq!(events, [TouchEvent::Up(touch(1, 290, 205)), TouchEvent::Up(touch(2, 50, 60))]);
+///
+/// // A lifted ID that is reused is a new touch.
+/// let events: Vec<_> = tracker.update(&frame(&[(0, 400, 400)])).collect();
+/// assert_eq!(events, [TouchEvent::Down(touch(0, 400, 400))]);
+///
+/// // Clearing the tracker releases the remaining tracks.
+/// let events: Vec<_> = tracker.clear().collect();
+/// assert_eq!(events, [TouchEvent::Up(touch(0, 400, 400))]);
+/// assert_eq!(tracker.active().count(), 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TouchTracker {
+    active: [Option<TouchPoint>; MAX_TOUCH_POINTS],
+}
+
+impl TouchTracker {
+    /// Create a new [`TouchTracker`] without any active touches.
+    #[must_use]
+    pub const fn new() -> Self { Self { active: [None; MAX_TOUCH_POINTS] } }
+
+    /// Iterate over the touch points that are currently down.
+    #[inline]
+    pub fn active(&self) -> impl Iterator<Item = &TouchPoint> { self.active.iter().flatten() }
+
+    /// Compare a frame against the previous one, returning the events
+    /// between them.
+    ///
+    /// Lifted touches are reported first, followed by pressed and moved
+    /// touches in the order of the frame. Touches that only changed their
+    /// area are not reported.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ef_gt911::{DetectedTouch, TouchEvent, TouchFrame, TouchPoint, TouchTracker};
+    ///
+    /// /// A ready frame with five touch points, with IDs from `first`.
+    /// fn frame(first: u8, x: u16) -> TouchFrame {
+    ///     let mut frame = TouchFrame::EMPTY;
+    ///     frame.status = DetectedTouch::from_bits_truncate(0x85);
+    ///     for (id, slot) in (first..).zip(&mut frame.points) {
+    ///         *slot = Some(TouchPoint { point: id, x, y: u16::from(id) * 50, area: 20 });
+    ///     }
+    ///     frame
+    /// }
+    ///
+    /// let mut tracker = TouchTracker::new();
+    /// assert_eq!(tracker.update(&frame(0, 100)).len(), 5);
+    ///
+    /// // Five fingers are replaced by five new ones between two frames.
+    /// let events: Vec<_> = tracker.update(&frame(5, 200)).collect();
+    /// assert_eq!(events.len(), 10);
+    /// assert!(events[..5].iter().all(|event| matches!(event, TouchEvent::Up(p) if p.x == 100)));
+    /// assert!(events[5..].iter().all(|event| matches!(event, TouchEvent::Down(p) if p.x == 200)));
+    /// assert_eq!(events.iter().map(TouchEvent::id).collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    /// ```
+    pub fn update(&mut self, frame: &TouchFrame) -> TouchEvents {
+        let mut events = TouchEvents::EMPTY;
+        if !frame.status.is_ready() {
+            return events;
+        }
+
+        for previous in self.active() {
+            if !frame.iter().any(|point| point.point == previous.point) {
+                events.push(TouchEvent::Up(*previous));
+            }
+        }
+        for point in frame.iter() {
+            match self.active().find(|previous| previous.point == point.point) {
+                None => events.push(TouchEvent::Down(*point)),
+                Some(previous) if (previous.x, previous.y) != (point.x, point.y) => {
+                    events.push(TouchEvent::Move(*point));
+                }
+                Some(_) => {}
+            }
+        }
+
+        self.active = frame.points;
+        events
+    }
+
+    /// Release every active touch, returning an [`TouchEvent::Up`] for each.
+    ///
+    /// Use this when frames stop arriving, such as when the device is reset
+    /// or switched to gesture mode.
+    pub fn clear(&mut self) -> TouchEvents {
+        let mut events = TouchEvents::EMPTY;
+        for point in self.active.iter_mut().filter_map(Option::take) {
+            events.push(TouchEvent::Up(point));
+        }
+        events
+    }
+}
+
+impl Default for TouchTracker {
+    fn default() -> Self { Self::new() }
+}
+
+/// A touch event emitted by a [`TouchTracker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TouchEvent {
+    /// A finger was pressed.
+    Down(TouchPoint),
+    /// A pressed finger moved.
+    Move(TouchPoint),
+    /// A finger was lifted, at its last known position.
+    Up(TouchPoint),
+}
+
+impl TouchEvent {
+    /// Get the touch point of the event.
+    #[inline]
+    #[must_use]
+    pub const fn point(&self) -> &TouchPoint {
+        match self {
+            Self::Down(point) | Self::Move(point) | Self::Up(point) => point,
+        }
+    }
+
+    /// Get the track ID of the event.
+    #[inline]
+    #[must_use]
+    pub const fn id(&self) -> u8 { self.point().point }
+}
+
+/// The events between two frames, returned by [`TouchTracker::update`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TouchEvents {
+    /// Every touch can be lifted while as many new ones are pressed.
+    events: [Option<TouchEvent>; 2 * MAX_TOUCH_POINTS],
+    len: usize,
+    next: usize,
+}
+
+impl TouchEvents {
+    /// No events.
+    const EMPTY: Self = Self { events: [None; 2 * MAX_TOUCH_POINTS], len: 0, next: 0 };
+
+    /// Add an event, ignoring it if there is no space left.
+    fn push(&mut self, event: TouchEvent) {
+        if let Some(slot) = self.events.get_mut(self.len) {
+            *slot = Some(event);
+            self.len += 1;
+        }
+    }
+}
+
+impl Iterator for TouchEvents {
+    type Item = TouchEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.events.get_mut(self.next)?.take()?;
+        self.next += 1;
+        Some(event)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len - self.next;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for TouchEvents {}
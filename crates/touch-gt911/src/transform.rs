@@ -0,0 +1,201 @@
+//! Transforming coordinates from the panel to the display.
+
+use crate::{GesturePoint, Orientation, OutputScale, Rotation, TouchPoint};
+
+/// Maps coordinates from the touch panel onto the display it is mounted on.
+///
+/// Coordinates are clamped to the panel, mirrored, rotated clockwise and
+/// then scaled to the display resolution, in that order. Mirroring and
+/// rotation follow [`Orientation`], so swipes and coordinates agree when
+/// the driver uses [`TouchTransform::orientation`] as well.
+///
+/// Set on the driver with `GT911::with_transform`, which applies it to every
+/// touch and gesture point read.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_gt911::{Rotation, TouchTransform};
+///
+/// // A 1024x600 panel, mounted rotated on a 480x800 display
+/// let transform = TouchTransform::new((1024, 600), (480, 800)).with_rotation(Rotation::Deg90);
+/// assert_eq!(transform.apply_xy(0, 0), (479, 0));
+/// assert_eq!(transform.apply_xy(1023, 599), (0, 799));
+///
+/// // Coordinates past the panel are clamped, and flipping never underflows
+/// assert_eq!(transform.apply_xy(u16::MAX, u16::MAX), (0, 799));
+/// let flipped = transform.with_mirror_x(true).with_mirror_y(true);
+/// assert_eq!(flipped.apply_xy(u16::MAX, u16::MAX), (479, 0));
+/// ```
+///
+/// Every orientation moves points the way it turns swipes.
+///
+/// ```rust
+/// use ef_gt911::{DetectedGesture, Rotation, TouchTransform};
+///
+/// for rotation in [Rotation::Deg0, Rotation::Deg90, Rotation::Deg180, Rotation::Deg270] {
+///     for (mirror_x, mirror_y) in [(false, false), (false, true), (true, false), (true, true)] {
+///         let transform = TouchTransform::new((200, 100), (200, 100))
+///             .with_rotation(rotation)
+///             .with_mirror_x(mirror_x)
+///             .with_mirror_y(mirror_y);
+///         let transform = TouchTransform { to: transform.rotated_size(), ..transform };
+///
+///         // A swipe right on the panel
+///         let (x0, y0) = transform.apply_xy(50, 50);
+///         let (x1, y1) = transform.apply_xy(60, 50);
+///         let swipe = match (x1.cmp(&x0), y1.cmp(&y0)) {
+///             (core::cmp::Ordering::Greater, _) => DetectedGesture::SwipeRight,
+///             (core::cmp::Ordering::Less, _) => DetectedGesture::SwipeLeft,
+///             (_, core::cmp::Ordering::Greater) => DetectedGesture::SwipeDown,
+///             _ => DetectedGesture::SwipeUp,
+///         };
+///         let orientation = transform.orientation();
+///         assert_eq!(swipe, orientation.apply_gesture(DetectedGesture::SwipeRight));
+///
+///         // Corners stay on the display
+///         let (width, height) = transform.to;
+///         for (x, y) in [(0, 0), (199, 0), (0, 99), (199, 99)] {
+///             let (x, y) = transform.apply_xy(x, y);
+///             assert!(x < width && y < height);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TouchTransform {
+    /// The clockwise rotation, applied after mirroring.
+    pub rotation: Rotation,
+    /// Whether the X axis of the panel is mirrored.
+    pub mirror_x: bool,
+    /// Whether the Y axis of the panel is mirrored.
+    pub mirror_y: bool,
+    /// The `(width, height)` resolution of the panel.
+    pub from: (u16, u16),
+    /// The `(width, height)` resolution of the display.
+    pub to: (u16, u16),
+}
+
+impl TouchTransform {
+    /// Create a new [`TouchTransform`] between two `(width, height)`
+    /// resolutions, without rotation or mirroring.
+    #[inline]
+    #[must_use]
+    pub const fn new(from: (u16, u16), to: (u16, u16)) -> Self {
+        Self { rotation: Rotation::Deg0, mirror_x: false, mirror_y: false, from, to }
+    }
+
+    /// Set the clockwise rotation.
+    #[inline]
+    #[must_use]
+    pub const fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Set whether the X axis of the panel is mirrored.
+    #[inline]
+    #[must_use]
+    pub const fn with_mirror_x(mut self, mirror_x: bool) -> Self {
+        self.mirror_x = mirror_x;
+        self
+    }
+
+    /// Set whether the Y axis of the panel is mirrored.
+    #[inline]
+    #[must_use]
+    pub const fn with_mirror_y(mut self, mirror_y: bool) -> Self {
+        self.mirror_y = mirror_y;
+        self
+    }
+
+    /// Get the [`Orientation`] matching the rotation and mirroring,
+    /// which remaps swipe gestures the same way.
+    #[inline]
+    #[must_use]
+    pub const fn orientation(&self) -> Orientation {
+        Orientation::new(self.rotation).with_mirror_x(self.mirror_x).with_mirror_y(self.mirror_y)
+    }
+
+    /// Get the resolution of the panel after rotation.
+    #[inline]
+    #[must_use]
+    pub const fn rotated_size(&self) -> (u16, u16) {
+        let (width, height) = self.from;
+        match self.rotation {
+            Rotation::Deg0 | Rotation::Deg180 => (width, height),
+            Rotation::Deg90 | Rotation::Deg270 => (height, width),
+        }
+    }
+
+    /// Get the [`OutputScale`] from the rotated panel to the display.
+    #[inline]
+    #[must_use]
+    pub const fn output_scale(&self) -> OutputScale {
+        OutputScale::new(self.rotated_size(), self.to)
+    }
+
+    /// Clamp, mirror and rotate a pair of coordinates, without scaling.
+    #[must_use]
+    pub const fn orient_xy(&self, x: u16, y: u16) -> (u16, u16) {
+        let (max_x, max_y) = (self.from.0.saturating_sub(1), self.from.1.saturating_sub(1));
+        let mut x = if x > max_x { max_x } else { x };
+        let mut y = if y > max_y { max_y } else { y };
+        if self.mirror_x {
+            x = max_x - x;
+        }
+        if self.mirror_y {
+            y = max_y - y;
+        }
+
+        match self.rotation {
+            Rotation::Deg0 => (x, y),
+            Rotation::Deg90 => (max_y - y, x),
+            Rotation::Deg180 => (max_x - x, max_y - y),
+            Rotation::Deg270 => (y, max_x - x),
+        }
+    }
+
+    /// Transform a pair of coordinates from the panel to the display.
+    ///
+    /// The [`OutputScale`] is created on every call, the driver creates it
+    /// once when the transform is set.
+    #[inline]
+    #[must_use]
+    pub const fn apply_xy(&self, x: u16, y: u16) -> (u16, u16) {
+        let (x, y) = self.orient_xy(x, y);
+        self.output_scale().apply_xy(x, y)
+    }
+}
+
+/// A [`TouchTransform`] along with its [`OutputScale`],
+/// so that applying it does not divide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PreparedTransform {
+    transform: TouchTransform,
+    scale: OutputScale,
+}
+
+impl PreparedTransform {
+    /// Prepare a [`TouchTransform`].
+    pub(crate) const fn new(transform: TouchTransform) -> Self {
+        Self { transform, scale: transform.output_scale() }
+    }
+
+    /// Get the [`TouchTransform`].
+    pub(crate) const fn transform(&self) -> TouchTransform { self.transform }
+
+    /// Transform a [`TouchPoint`].
+    pub(crate) const fn touch(&self, point: TouchPoint) -> TouchPoint {
+        let (x, y) = self.transform.orient_xy(point.x, point.y);
+        self.scale.apply(TouchPoint { x, y, ..point })
+    }
+
+    /// Transform a [`GesturePoint`].
+    pub(crate) const fn gesture(&self, point: GesturePoint) -> GesturePoint {
+        let (x, y) = self.transform.orient_xy(point.x, point.y);
+        let (x, y) = self.scale.apply_xy(x, y);
+        GesturePoint { x, y }
+    }
+}
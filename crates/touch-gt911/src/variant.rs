@@ -0,0 +1,66 @@
+//! The chips of the GT9xx family that share the GT911 protocol.
+
+/// A touch controller that shares the register map of the GT911,
+/// identified by its product ID.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_gt911::GT911Variant;
+///
+/// assert_eq!(GT911Variant::from_product_id(*b"911\0"), Some(GT911Variant::GT911));
+/// assert_eq!(GT911Variant::from_product_id(*b"9110"), Some(GT911Variant::GT9110));
+/// assert_eq!(GT911Variant::from_product_id(*b"GEST"), None);
+///
+/// assert_eq!(GT911Variant::GT911.max_touch_points(), 5);
+/// assert_eq!(GT911Variant::GT928.max_touch_points(), 10);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GT911Variant {
+    /// The GT911, with 5 touch points.
+    GT911,
+    /// The GT9110, with 10 touch points.
+    GT9110,
+    /// The GT912, with 5 touch points.
+    GT912,
+    /// The GT928, with 10 touch points.
+    GT928,
+    /// The GT5688, with 10 touch points.
+    GT5688,
+}
+
+impl GT911Variant {
+    /// Every [`GT911Variant`].
+    pub const ALL: &'static [Self] =
+        &[Self::GT911, Self::GT9110, Self::GT912, Self::GT928, Self::GT5688];
+
+    /// Get the product ID reported in touch mode.
+    #[must_use]
+    pub const fn product_id(self) -> [u8; 4] {
+        match self {
+            Self::GT911 => *b"911\0",
+            Self::GT9110 => *b"9110",
+            Self::GT912 => *b"912\0",
+            Self::GT928 => *b"928\0",
+            Self::GT5688 => *b"5688",
+        }
+    }
+
+    /// Get the [`GT911Variant`] reporting a product ID.
+    #[must_use]
+    pub fn from_product_id(product_id: [u8; 4]) -> Option<Self> {
+        Self::ALL.iter().copied().find(|variant| variant.product_id() == product_id)
+    }
+
+    /// Get the maximum number of touch points the chip can report.
+    ///
+    /// The driver reads at most 5 touch points, the layout of the GT911.
+    #[must_use]
+    pub const fn max_touch_points(self) -> usize {
+        match self {
+            Self::GT911 | Self::GT912 => 5,
+            Self::GT9110 | Self::GT928 | Self::GT5688 => 10,
+        }
+    }
+}
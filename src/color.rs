@@ -1,7 +1,6 @@
 //! Marker types and traits for supported color modes.
 
-pub use embedded_graphics_core::pixelcolor::{Rgb565, Rgb666, Rgb888};
-use embedded_graphics_core::prelude::RgbColor;
+pub use embedded_graphics_core::pixelcolor::{Rgb565, Rgb666, Rgb888, RgbColor};
 
 /// A marker trait for supported color modes.
 pub trait DisplayColor: RgbColor + sealed::Sealed {}
@@ -0,0 +1,154 @@
+//! Identifying attached devices.
+//!
+//! Every driver identifies its device differently, from a chip ID register
+//! to a display's read ID commands. [`Identify`] reads whatever the device
+//! offers into a [`DeviceIdentity`], so a board support layer can probe its
+//! optional peripherals uniformly with [`identify_all!`](crate::identify_all).
+//!
+//! Devices without an ID register are identified by reading back registers
+//! with known defaults, which is only a weak check. Each implementation
+//! documents what it reads.
+//!
+//! # Example
+//!
+//! ```rust
+//! use ef_driver_common::{
+//!     identify::{DeviceIdentity, Identify},
+//!     identify_all,
+//! };
+//!
+//! /// A device that may not be fitted.
+//! struct Device(&'static str, Option<u8>);
+//!
+//! impl Identify for Device {
+//!     type Error = ();
+//!
+//!     fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> {
+//!         let id = self.1.ok_or(())?;
+//!         Ok(DeviceIdentity::new(self.0, &[id], None))
+//!     }
+//! }
+//!
+//! let mut accel = Device("ADXL345", Some(0xE5));
+//! let mut touch = Device("GT911", None);
+//!
+//! // Devices that fail to respond are reported as missing
+//! let [accel, touch] = identify_all!(accel, touch);
+//! assert_eq!(accel.map(|identity| (identity.name(), identity.id()[0])), Some(("ADXL345", 0xE5)));
+//! assert!(touch.is_none());
+//! ```
+
+/// The longest ID a [`DeviceIdentity`] holds, in bytes.
+pub const MAX_ID_LEN: usize = 4;
+
+/// The identity of a device, read by [`Identify::identify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceIdentity {
+    name: &'static str,
+    id: [u8; MAX_ID_LEN],
+    len: u8,
+    version: Option<u16>,
+}
+
+impl DeviceIdentity {
+    /// Create a new [`DeviceIdentity`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is longer than [`MAX_ID_LEN`].
+    #[must_use]
+    #[expect(clippy::cast_possible_truncation, reason = "The length is checked")]
+    pub const fn new(name: &'static str, id: &[u8], version: Option<u16>) -> Self {
+        assert!(id.len() <= MAX_ID_LEN, "Device IDs are at most 4 bytes");
+        let mut bytes = [0u8; MAX_ID_LEN];
+        let mut index = 0;
+        while index < id.len() {
+            bytes[index] = id[index];
+            index += 1;
+        }
+        Self { name, id: bytes, len: id.len() as u8, version }
+    }
+
+    /// Get the name of the driver that identified the device.
+    #[inline]
+    #[must_use]
+    pub const fn name(&self) -> &'static str { self.name }
+
+    /// Get the ID read from the device.
+    #[inline]
+    #[must_use]
+    pub fn id(&self) -> &[u8] { &self.id[..usize::from(self.len)] }
+
+    /// Get the version read from the device, if it reports one.
+    #[inline]
+    #[must_use]
+    pub const fn version(&self) -> Option<u16> { self.version }
+}
+
+/// A device that can be identified.
+pub trait Identify {
+    /// The error returned when identifying fails.
+    type Error;
+
+    /// Read the identity of the device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the device fails.
+    fn identify(&mut self) -> Result<DeviceIdentity, Self::Error>;
+}
+
+impl<T: Identify + ?Sized> Identify for &mut T {
+    type Error = T::Error;
+
+    #[inline]
+    fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> { T::identify(self) }
+}
+
+/// An asynchronous device that can be identified.
+#[expect(async_fn_in_trait, reason = "Matches the `embedded-hal-async` traits")]
+pub trait AsyncIdentify {
+    /// The error returned when identifying fails.
+    type Error;
+
+    /// Read the identity of the device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the device fails.
+    async fn identify(&mut self) -> Result<DeviceIdentity, Self::Error>;
+}
+
+impl<T: AsyncIdentify + ?Sized> AsyncIdentify for &mut T {
+    type Error = T::Error;
+
+    #[inline]
+    async fn identify(&mut self) -> Result<DeviceIdentity, Self::Error> { T::identify(self).await }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Identify each device in turn, returning an array with the
+/// [`DeviceIdentity`] of every device that responded.
+///
+/// Devices that fail to identify are `None`, whatever the error.
+/// See the [module](crate::identify) docs.
+#[macro_export]
+macro_rules! identify_all {
+    ($($device:expr),* $(,)?) => {
+        [$($crate::identify::Identify::identify(&mut $device).ok()),*]
+    };
+}
+
+/// Identify each asynchronous device in turn, returning an array with the
+/// [`DeviceIdentity`] of every device that responded.
+///
+/// Devices that fail to identify are `None`, whatever the error.
+/// Must be used in an async context.
+#[macro_export]
+macro_rules! identify_all_async {
+    ($($device:expr),* $(,)?) => {
+        [$($crate::identify::AsyncIdentify::identify(&mut $device).await.ok()),*]
+    };
+}
@@ -0,0 +1,367 @@
+//! Sharing one interrupt line between two devices.
+//!
+//! Boards with few free GPIOs often wire the open-drain interrupt outputs of
+//! several devices, such as an I/O expander and a touch controller, to a
+//! single input. When the line is asserted, any of the devices may have
+//! caused it, and more than one may be pending at once.
+//!
+//! [`SharedInterrupt`] waits for the line and asks each [`InterruptSource`]
+//! whether it asserted it, in a fixed order. Each source that claims the
+//! interrupt handles it, which releases its hold on the line.
+//!
+//! # Level triggering
+//!
+//! The line stays asserted until every pending source was handled, and a
+//! source may assert it again while another is being handled. Waiting for
+//! an edge after handling only one source would miss the other, so
+//! [`SharedInterrupt::wait`] re-checks the level of the line after each
+//! round and asks every source again while it is still asserted.
+//!
+//! If the line stays asserted without any source claiming it, such as when a
+//! device is stuck or a source is missing, [`SharedInterruptError::Stuck`] is
+//! returned after a bounded number of rounds instead of spinning forever.
+//! Assertions that no source claims and that are released on their own,
+//! such as glitches, are ignored.
+//!
+//! # Example
+//!
+//! ```rust
+//! use core::{cell::RefCell, convert::Infallible};
+//!
+//! use ef_driver_common::interrupt::{
+//!     InterruptClaims, InterruptSource, SharedInterrupt, SharedInterruptError,
+//! };
+//! use embedded_hal::digital::{ErrorType, InputPin};
+//! use embedded_hal_async::digital::Wait;
+//!
+//! /// Which devices are holding the line low.
+//! #[derive(Default)]
+//! struct Board {
+//!     pending: [bool; 2],
+//!     /// Devices that assert the line each time the MCU waits for it.
+//!     script: Vec<[bool; 2]>,
+//!     /// Every interrupt that was handled, by device.
+//!     handled: Vec<usize>,
+//! }
+//!
+//! /// The shared, active-low interrupt line.
+//! struct Line<'a>(&'a RefCell<Board>);
+//!
+//! impl ErrorType for Line<'_> {
+//!     type Error = Infallible;
+//! }
+//!
+//! impl InputPin for Line<'_> {
+//!     fn is_high(&mut self) -> Result<bool, Infallible> {
+//!         Ok(!self.0.borrow().pending.iter().any(|&pending| pending))
+//!     }
+//!
+//!     fn is_low(&mut self) -> Result<bool, Infallible> { self.is_high().map(|high| !high) }
+//! }
+//!
+//! impl Wait for Line<'_> {
+//!     async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+//!         let mut board = self.0.borrow_mut();
+//!         let assert = board.script.remove(0);
+//!         for (pending, assert) in board.pending.iter_mut().zip(assert) {
+//!             *pending |= assert;
+//!         }
+//!         Ok(())
+//!     }
+//! #
+//! #   async fn wait_for_high(&mut self) -> Result<(), Infallible> { unimplemented!() }
+//! #   async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> { unimplemented!() }
+//! #   async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> { unimplemented!() }
+//! #   async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> { unimplemented!() }
+//! }
+//!
+//! /// A device on the line, such as the expander or the touch controller.
+//! struct Device<'a>(&'a RefCell<Board>, usize);
+//!
+//! impl InterruptSource for Device<'_> {
+//!     type Error = Infallible;
+//!
+//!     async fn claim(&mut self) -> Result<bool, Infallible> {
+//!         let mut board = self.0.borrow_mut();
+//!         let claimed = core::mem::take(&mut board.pending[self.1]);
+//!         if claimed {
+//!             board.handled.push(self.1);
+//!         }
+//!         Ok(claimed)
+//!     }
+//! }
+//!
+//! # fn block_on<F: Future>(future: F) -> F::Output {
+//! #     let mut future = std::pin::pin!(future);
+//! #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+//! #     loop {
+//! #         if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+//! #             return output;
+//! #         }
+//! #     }
+//! # }
+//! let board = RefCell::new(Board::default());
+//! let mut line = Line(&board);
+//! let mut shared = SharedInterrupt::new(Device(&board, 0), Device(&board, 1));
+//!
+//! // Only one device asserts the line
+//! board.borrow_mut().script = vec![[false, true]];
+//! let claims = block_on(shared.wait(&mut line))?;
+//! assert_eq!(claims, InterruptClaims { first: false, second: true });
+//! assert_eq!(board.borrow().handled, [1]);
+//!
+//! // Both devices assert the line, neither is missed
+//! board.borrow_mut().handled.clear();
+//! board.borrow_mut().script = vec![[true, true]];
+//! let claims = block_on(shared.wait(&mut line))?;
+//! assert_eq!(claims, InterruptClaims { first: true, second: true });
+//! assert_eq!(board.borrow().handled, [0, 1]);
+//!
+//! // A glitch that neither device claims is ignored
+//! board.borrow_mut().handled.clear();
+//! board.borrow_mut().script = vec![[false, false], [true, false]];
+//! let claims = block_on(shared.wait(&mut line))?;
+//! assert_eq!(claims, InterruptClaims { first: true, second: false });
+//! assert_eq!(board.borrow().handled, [0]);
+//! assert!(board.borrow().script.is_empty());
+//! # Ok::<(), SharedInterruptError<Infallible, Infallible, Infallible>>(())
+//! ```
+
+use embedded_hal::digital::InputPin;
+use embedded_hal_async::digital::Wait;
+
+/// A device that can assert a shared interrupt line.
+#[expect(async_fn_in_trait, reason = "Matches the `embedded-hal-async` traits")]
+pub trait InterruptSource {
+    /// The error returned when checking the device fails.
+    type Error;
+
+    /// Check whether the device asserted the interrupt line,
+    /// handling the interrupt if it did.
+    ///
+    /// Handling must release the device's hold on the line, such as by
+    /// reading the changed inputs of an I/O expander or clearing the status
+    /// of a touch controller. Returns `false` if the device did not assert
+    /// the line.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the device fails.
+    async fn claim(&mut self) -> Result<bool, Self::Error>;
+}
+
+impl<T: InterruptSource + ?Sized> InterruptSource for &mut T {
+    type Error = T::Error;
+
+    #[inline]
+    async fn claim(&mut self) -> Result<bool, Self::Error> { T::claim(self).await }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Demultiplexes an interrupt line shared by two devices,
+/// see the [module](self) docs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SharedInterrupt<A, B> {
+    first: A,
+    second: B,
+    active_high: bool,
+    max_rounds: u8,
+}
+
+impl<A, B> SharedInterrupt<A, B> {
+    /// The default number of rounds before the line is considered stuck.
+    pub const DEFAULT_MAX_ROUNDS: u8 = 8;
+
+    /// Create a new [`SharedInterrupt`] for an active-low line,
+    /// asking `first` before `second`.
+    #[inline]
+    #[must_use]
+    pub const fn new(first: A, second: B) -> Self {
+        Self { first, second, active_high: false, max_rounds: Self::DEFAULT_MAX_ROUNDS }
+    }
+
+    /// Set whether the line is asserted high instead of low.
+    #[inline]
+    #[must_use]
+    pub const fn with_active_high(mut self, active_high: bool) -> Self {
+        self.active_high = active_high;
+        self
+    }
+
+    /// Set the number of rounds to ask the devices while the line stays
+    /// asserted, before returning [`SharedInterruptError::Stuck`].
+    ///
+    /// At least one round is always made.
+    #[inline]
+    #[must_use]
+    pub const fn with_max_rounds(mut self, max_rounds: u8) -> Self {
+        self.max_rounds = max_rounds;
+        self
+    }
+
+    /// Get a reference to the device asked first.
+    #[inline]
+    #[must_use]
+    pub const fn first(&self) -> &A { &self.first }
+
+    /// Get a mutable reference to the device asked first.
+    #[inline]
+    #[must_use]
+    pub const fn first_mut(&mut self) -> &mut A { &mut self.first }
+
+    /// Get a reference to the device asked second.
+    #[inline]
+    #[must_use]
+    pub const fn second(&self) -> &B { &self.second }
+
+    /// Get a mutable reference to the device asked second.
+    #[inline]
+    #[must_use]
+    pub const fn second_mut(&mut self) -> &mut B { &mut self.second }
+
+    /// Release both devices.
+    #[inline]
+    #[must_use]
+    pub fn release(self) -> (A, B) { (self.first, self.second) }
+}
+
+impl<A: InterruptSource, B: InterruptSource> SharedInterrupt<A, B> {
+    /// Wait for the line to be asserted and handle every device that
+    /// asserted it, returning which devices claimed the interrupt.
+    ///
+    /// Both devices are asked in every round, and rounds repeat until the
+    /// line is released. Assertions that no device claims are ignored once
+    /// the line is released.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading the line or checking a device fails,
+    /// or if the line stays asserted without being released.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use core::convert::Infallible;
+    ///
+    /// use ef_driver_common::interrupt::{InterruptSource, SharedInterrupt, SharedInterruptError};
+    /// use embedded_hal::digital::{ErrorType, InputPin};
+    /// use embedded_hal_async::digital::Wait;
+    ///
+    /// /// A line that is always held low.
+    /// struct Stuck;
+    ///
+    /// impl ErrorType for Stuck {
+    ///     type Error = Infallible;
+    /// }
+    ///
+    /// impl InputPin for Stuck {
+    ///     fn is_high(&mut self) -> Result<bool, Infallible> { Ok(false) }
+    ///
+    ///     fn is_low(&mut self) -> Result<bool, Infallible> { Ok(true) }
+    /// }
+    ///
+    /// impl Wait for Stuck {
+    ///     async fn wait_for_low(&mut self) -> Result<(), Infallible> { Ok(()) }
+    /// #
+    /// #   async fn wait_for_high(&mut self) -> Result<(), Infallible> { unimplemented!() }
+    /// #   async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> { unimplemented!() }
+    /// #   async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> { unimplemented!() }
+    /// #   async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> { unimplemented!() }
+    /// }
+    ///
+    /// /// A device that never asserts the line, counting how often it was asked.
+    /// struct Quiet(u8);
+    ///
+    /// impl InterruptSource for Quiet {
+    ///     type Error = Infallible;
+    ///
+    ///     async fn claim(&mut self) -> Result<bool, Infallible> {
+    ///         self.0 += 1;
+    ///         Ok(false)
+    ///     }
+    /// }
+    ///
+    /// # fn block_on<F: Future>(future: F) -> F::Output {
+    /// #     let mut future = std::pin::pin!(future);
+    /// #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    /// #     loop {
+    /// #         if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+    /// #             return output;
+    /// #         }
+    /// #     }
+    /// # }
+    /// // Neither device claims the interrupt, so the line is stuck
+    /// let mut shared = SharedInterrupt::new(Quiet(0), Quiet(0)).with_max_rounds(3);
+    /// assert_eq!(block_on(shared.wait(&mut Stuck)), Err(SharedInterruptError::Stuck));
+    /// assert_eq!((shared.first().0, shared.second().0), (3, 3));
+    /// ```
+    pub async fn wait<P: Wait + InputPin>(
+        &mut self,
+        pin: &mut P,
+    ) -> Result<InterruptClaims, SharedInterruptError<A::Error, B::Error, P::Error>> {
+        loop {
+            if self.active_high {
+                pin.wait_for_high().await.map_err(SharedInterruptError::Pin)?;
+            } else {
+                pin.wait_for_low().await.map_err(SharedInterruptError::Pin)?;
+            }
+
+            let mut claims = InterruptClaims::NONE;
+            let mut released = false;
+            for _ in 0..self.max_rounds.max(1) {
+                // Ask both devices every round, as both may be pending
+                claims.first |= self.first.claim().await.map_err(SharedInterruptError::First)?;
+                claims.second |= self.second.claim().await.map_err(SharedInterruptError::Second)?;
+
+                let high = pin.is_high().map_err(SharedInterruptError::Pin)?;
+                if high != self.active_high {
+                    released = true;
+                    break;
+                }
+            }
+
+            if !released {
+                return Err(SharedInterruptError::Stuck);
+            }
+            if claims.any() {
+                return Ok(claims);
+            }
+        }
+    }
+}
+
+/// Which devices claimed an interrupt of a [`SharedInterrupt`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InterruptClaims {
+    /// The device asked first claimed the interrupt.
+    pub first: bool,
+    /// The device asked second claimed the interrupt.
+    pub second: bool,
+}
+
+impl InterruptClaims {
+    /// Neither device claimed the interrupt.
+    pub const NONE: Self = Self { first: false, second: false };
+
+    /// Returns `true` if either device claimed the interrupt.
+    #[inline]
+    #[must_use]
+    pub const fn any(self) -> bool { self.first || self.second }
+}
+
+/// An error that can occur when waiting for a [`SharedInterrupt`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SharedInterruptError<A, B, P> {
+    /// Checking the device asked first failed.
+    First(A),
+    /// Checking the device asked second failed.
+    Second(B),
+    /// Reading the interrupt line failed.
+    Pin(P),
+    /// The line stayed asserted without being released.
+    Stuck,
+}
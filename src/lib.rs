@@ -3,4 +3,10 @@
 
 #[cfg(feature = "color")]
 pub mod color;
+pub mod identify;
+#[cfg(feature = "interrupt")]
+pub mod interrupt;
 pub mod mode;
+#[cfg(feature = "motion")]
+pub mod motion;
+pub mod sync;
@@ -1,6 +1,17 @@
 //! Marker types and traits for driver operation modes.
 
 /// A marker type for asynchronous drivers.
+///
+/// # Cancel safety
+///
+/// Every async driver method documents what happens when its future is
+/// dropped before completing:
+///
+/// - **Cancel-safe**: dropping the future has no effect.
+/// - **Cancel-resumable**: the driver stays usable, and calling the method
+///   again completes the operation.
+/// - **Cancel-corrupting**: the driver stays usable, but the device is left
+///   partway through the operation, which must be redone from its start.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Async;
 impl DriverMode for Async {}
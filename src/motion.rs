@@ -0,0 +1,359 @@
+//! A motion detector combining accelerometer and GPS measurements.
+//!
+//! The [`MotionDetector`] does not access any bus, it only consumes the
+//! measurements it is given. Acceleration magnitudes are compared against
+//! gravity (1000 mg) and GPS speeds are compared against a speed threshold,
+//! each with separate thresholds for starting and stopping movement.
+//!
+//! # Degraded mode
+//!
+//! When only one source is available (it was never fed, or its last
+//! measurement is older than [`MotionConfig::source_timeout_ms`]), decisions
+//! are made using the remaining source alone. Without any available source the
+//! current state is held until new measurements arrive.
+//!
+//! # Example
+//!
+//! Replaying a drive, followed by parking the vehicle:
+//!
+//! ```rust
+//! use ef_driver_common::motion::{MotionConfig, MotionDetector, MotionState};
+//!
+//! let mut detector = MotionDetector::new(MotionConfig::DEFAULT);
+//!
+//! // Driving at ~50 km/h, with the occasional bump
+//! for t in 0..60 {
+//!     let bump = if t % 7 == 0 { 1300 } else { 1010 };
+//!     detector.update_acceleration(t * 1000, bump);
+//!     detector.update_speed(t * 1000, 1400);
+//! }
+//! assert_eq!(detector.state(), MotionState::Moving);
+//!
+//! // Parked, with GPS speed jitter below the moving threshold
+//! let mut transitions = Vec::new();
+//! for t in 60..180 {
+//!     transitions.extend(detector.update_acceleration(t * 1000, 1002));
+//!     transitions.extend(detector.update_speed(t * 1000, if t % 2 == 0 { 60 } else { 20 }));
+//! }
+//! assert_eq!(detector.state(), MotionState::Stationary);
+//!
+//! let states: Vec<_> = transitions.iter().map(|transition| transition.to).collect();
+//! assert_eq!(states, [MotionState::PossiblyStationary, MotionState::Stationary]);
+//! ```
+
+/// The configuration of a [`MotionDetector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MotionConfig {
+    /// The deviation from 1 g, in mg, above which the device is moving.
+    pub accel_moving_mg: u32,
+    /// The deviation from 1 g, in mg, below which the device may be still.
+    pub accel_still_mg: u32,
+    /// The GPS speed, in cm/s, above which the device is moving.
+    pub speed_moving_cm_s: u32,
+    /// The GPS speed, in cm/s, below which the device may be still.
+    pub speed_still_cm_s: u32,
+    /// How long, in ms, the device must be still before it is stationary.
+    pub stationary_after_ms: u32,
+    /// How long, in ms, a stationary device must be moving before it is
+    /// considered to be moving again.
+    pub moving_after_ms: u32,
+    /// How long, in ms, a measurement is used before its source is
+    /// considered unavailable.
+    pub source_timeout_ms: u32,
+}
+
+impl MotionConfig {
+    /// A configuration suitable for vehicles and pedestrians.
+    pub const DEFAULT: Self = Self {
+        accel_moving_mg: 150,
+        accel_still_mg: 40,
+        speed_moving_cm_s: 150,
+        speed_still_cm_s: 80,
+        stationary_after_ms: 30_000,
+        moving_after_ms: 2_000,
+        source_timeout_ms: 5_000,
+    };
+}
+
+impl Default for MotionConfig {
+    fn default() -> Self { Self::DEFAULT }
+}
+
+/// The motion state reported by a [`MotionDetector`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MotionState {
+    /// The device is moving.
+    #[default]
+    Moving,
+    /// The device is still, but not for long enough to be stationary.
+    PossiblyStationary,
+    /// The device has been still for the configured dwell time.
+    Stationary,
+}
+
+/// A transition between two [`MotionState`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MotionTransition {
+    /// The previous state.
+    pub from: MotionState,
+    /// The new state.
+    pub to: MotionState,
+    /// The timestamp of the measurement that caused the transition, in ms.
+    pub timestamp_ms: u32,
+}
+
+/// A motion detector using a hysteresis state machine.
+///
+/// See the [module documentation](self) for more details.
+///
+/// # Example
+///
+/// Walking with only an accelerometer available:
+///
+/// ```rust
+/// use ef_driver_common::motion::{MotionConfig, MotionDetector, MotionState};
+///
+/// let mut detector = MotionDetector::new(MotionConfig::DEFAULT);
+///
+/// // Standing still for a minute, sampled at 10 Hz
+/// for t in 0..600 {
+///     detector.update_acceleration(t * 100, 995);
+/// }
+/// assert_eq!(detector.state(), MotionState::Stationary);
+/// assert!(!detector.has_speed(60_000));
+///
+/// // A single bump is not enough to start moving
+/// detector.update_acceleration(60_000, 1600);
+/// detector.update_acceleration(60_100, 1000);
+/// assert_eq!(detector.state(), MotionState::Stationary);
+///
+/// // Walking, with footsteps every 500 ms
+/// let mut transition = None;
+/// for t in 602..700 {
+///     let magnitude = if t % 5 == 0 { 1400 } else { 1180 };
+///     transition = transition.or(detector.update_acceleration(t * 100, magnitude));
+/// }
+/// assert_eq!(detector.state(), MotionState::Moving);
+/// assert_eq!(transition.map(|transition| transition.timestamp_ms), Some(62_200));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MotionDetector {
+    config: MotionConfig,
+    state: MotionState,
+    /// When the current state was entered, or when activity was first seen
+    /// while [`MotionState::Stationary`].
+    since_ms: Option<u32>,
+    accel: Option<Measurement>,
+    speed: Option<Measurement>,
+}
+
+impl MotionDetector {
+    /// Create a new [`MotionDetector`], starting in [`MotionState::Moving`].
+    #[must_use]
+    pub const fn new(config: MotionConfig) -> Self {
+        Self { config, state: MotionState::Moving, since_ms: None, accel: None, speed: None }
+    }
+
+    /// Get the current [`MotionState`].
+    #[inline]
+    #[must_use]
+    pub const fn state(&self) -> MotionState { self.state }
+
+    /// Get the [`MotionConfig`].
+    #[inline]
+    #[must_use]
+    pub const fn config(&self) -> &MotionConfig { &self.config }
+
+    /// Returns `true` if a measurement from the accelerometer is available.
+    #[must_use]
+    pub const fn has_acceleration(&self, now_ms: u32) -> bool {
+        Measurement::available(self.accel, now_ms, self.config.source_timeout_ms)
+    }
+
+    /// Returns `true` if a measurement from the GPS is available.
+    #[must_use]
+    pub const fn has_speed(&self, now_ms: u32) -> bool {
+        Measurement::available(self.speed, now_ms, self.config.source_timeout_ms)
+    }
+
+    /// Feed an acceleration magnitude, in mg.
+    ///
+    /// See [`magnitude`] for combining the individual axes.
+    pub fn update_acceleration(
+        &mut self,
+        timestamp_ms: u32,
+        magnitude_mg: u32,
+    ) -> Option<MotionTransition> {
+        let deviation = magnitude_mg.abs_diff(1000);
+        let activity =
+            Activity::classify(deviation, self.config.accel_moving_mg, self.config.accel_still_mg);
+        self.accel = Some(Measurement { timestamp_ms, activity });
+        self.evaluate(timestamp_ms)
+    }
+
+    /// Feed a GPS speed, in cm/s.
+    pub fn update_speed(&mut self, timestamp_ms: u32, speed_cm_s: u32) -> Option<MotionTransition> {
+        let activity = Activity::classify(
+            speed_cm_s,
+            self.config.speed_moving_cm_s,
+            self.config.speed_still_cm_s,
+        );
+        self.speed = Some(Measurement { timestamp_ms, activity });
+        self.evaluate(timestamp_ms)
+    }
+
+    /// Reset the detector to [`MotionState::Moving`], forgetting all
+    /// measurements.
+    pub const fn reset(&mut self) { *self = Self::new(self.config); }
+
+    /// Combine all available measurements and advance the state machine.
+    fn evaluate(&mut self, now_ms: u32) -> Option<MotionTransition> {
+        let timeout = self.config.source_timeout_ms;
+        let mut sources = [self.accel, self.speed]
+            .into_iter()
+            .flatten()
+            .filter(|measurement| measurement.is_recent(now_ms, timeout))
+            .map(|measurement| measurement.activity);
+
+        // Any active source means movement, otherwise all sources must be still.
+        let first = sources.next()?;
+        let activity = sources.fold(first, |acc, activity| match (acc, activity) {
+            (Activity::Active, _) | (_, Activity::Active) => Activity::Active,
+            (Activity::Still, Activity::Still) => Activity::Still,
+            _ => Activity::Unknown,
+        });
+
+        let next = match (self.state, activity) {
+            (MotionState::Moving, Activity::Still) => MotionState::PossiblyStationary,
+            (MotionState::PossiblyStationary, Activity::Active) => MotionState::Moving,
+            (MotionState::PossiblyStationary, Activity::Still) => {
+                let since = self.since_ms.unwrap_or(now_ms);
+                if now_ms.wrapping_sub(since) >= self.config.stationary_after_ms {
+                    MotionState::Stationary
+                } else {
+                    MotionState::PossiblyStationary
+                }
+            }
+            (MotionState::Stationary, Activity::Active) => {
+                let since = *self.since_ms.get_or_insert(now_ms);
+                if now_ms.wrapping_sub(since) >= self.config.moving_after_ms {
+                    MotionState::Moving
+                } else {
+                    MotionState::Stationary
+                }
+            }
+            (MotionState::Stationary, Activity::Still) => {
+                // Forget any short burst of activity
+                self.since_ms = None;
+                MotionState::Stationary
+            }
+            (state, _) => state,
+        };
+
+        if next == self.state {
+            return None;
+        }
+
+        let transition = MotionTransition { from: self.state, to: next, timestamp_ms: now_ms };
+        self.state = next;
+        self.since_ms = match next {
+            MotionState::PossiblyStationary => Some(now_ms),
+            MotionState::Moving | MotionState::Stationary => None,
+        };
+        Some(transition)
+    }
+}
+
+/// Calculate the magnitude of an acceleration vector.
+///
+/// # Example
+///
+/// ```rust
+/// use ef_driver_common::motion::magnitude;
+///
+/// assert_eq!(magnitude(0, 0, 1000), 1000);
+/// assert_eq!(magnitude(-600, 0, 800), 1000);
+/// assert_eq!(magnitude(i32::MIN, i32::MIN, i32::MIN), 3_719_550_786);
+/// ```
+#[must_use]
+pub fn magnitude(x: i32, y: i32, z: i32) -> u32 {
+    let sum = [x, y, z].into_iter().map(|axis| u64::from(axis.unsigned_abs()).pow(2)).sum();
+    isqrt(sum)
+}
+
+/// Calculate the integer square root of a value.
+#[expect(clippy::cast_possible_truncation, reason = "The root of a `u64` fits in a `u32`")]
+const fn isqrt(value: u64) -> u32 {
+    let mut root = 0u64;
+    let mut bit = 1u64 << 62;
+    let mut remainder = value;
+
+    while bit > value {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if remainder >= root + bit {
+            remainder -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+
+    root as u32
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A classified measurement from a single source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Measurement {
+    timestamp_ms: u32,
+    activity: Activity,
+}
+
+impl Measurement {
+    /// Returns `true` if the measurement is recent enough to be used.
+    const fn is_recent(self, now_ms: u32, timeout_ms: u32) -> bool {
+        now_ms.wrapping_sub(self.timestamp_ms) <= timeout_ms
+    }
+
+    /// Returns `true` if a recent measurement is available.
+    const fn available(measurement: Option<Self>, now_ms: u32, timeout_ms: u32) -> bool {
+        match measurement {
+            Some(measurement) => measurement.is_recent(now_ms, timeout_ms),
+            None => false,
+        }
+    }
+}
+
+/// The activity indicated by a measurement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+enum Activity {
+    /// Above the moving threshold.
+    Active,
+    /// Below the still threshold.
+    Still,
+    /// Between both thresholds.
+    Unknown,
+}
+
+impl Activity {
+    /// Classify a value using the moving and still thresholds.
+    const fn classify(value: u32, moving: u32, still: u32) -> Self {
+        if value > moving {
+            Activity::Active
+        } else if value < still {
+            Activity::Still
+        } else {
+            Activity::Unknown
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Starting armed devices back-to-back.
+//!
+//! Drivers that support a synchronized start split starting a capture into
+//! two steps. Arming configures the device but leaves it stopped, and
+//! starting releases it with a single register write. [`SyncStart`] starts
+//! two armed devices directly after each other, first to second.
+//!
+//! # Skew
+//!
+//! The second device starts one bus transaction after the first. Writing a
+//! single I2C register takes 29 clock cycles (start, address, register, value
+//! and stop), so the skew between the two starts is about:
+//!
+//! | Bus speed | Skew    |
+//! |-----------|---------|
+//! | 100 kHz   | ~290 µs |
+//! | 400 kHz   | ~73 µs  |
+//! | 1 MHz     | ~29 µs  |
+//!
+//! The time the bus driver takes between transactions is added on top, as is
+//! any interrupt that preempts the caller. Start the devices from a high
+//! priority task, or with interrupts masked, to keep the skew bounded. Since
+//! the order is fixed the skew can also be subtracted from the second
+//! device's timestamps afterwards.
+//!
+//! The skew does not include how long each device takes to produce its
+//! first sample after starting, which is listed in its datasheet.
+//!
+//! Devices that share a hardware trigger line, such as a GPIO connected to
+//! the start input of several devices, can implement [`Start`] by pulsing
+//! the line, which starts every device on it at once.
+//!
+//! # Example
+//!
+//! ```rust
+//! use core::cell::RefCell;
+//!
+//! use ef_driver_common::sync::{Start, SyncStart, SyncStartError};
+//!
+//! /// A device that records when it was started.
+//! struct Device<'a>(&'static str, &'a RefCell<Vec<&'static str>>, bool);
+//!
+//! impl Start for Device<'_> {
+//!     type Error = &'static str;
+//!
+//!     fn start(&mut self) -> Result<(), Self::Error> {
+//!         if self.2 {
+//!             return Err(self.0);
+//!         }
+//!         self.1.borrow_mut().push(self.0);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let log = RefCell::new(Vec::new());
+//! let mut accel = Device("accel", &log, false);
+//! let mut adc = Device("adc", &log, false);
+//!
+//! // The first device is always started first
+//! SyncStart::new(&mut accel, &mut adc).start()?;
+//! assert_eq!(*log.borrow(), ["accel", "adc"]);
+//!
+//! // More devices can be started by nesting
+//! log.borrow_mut().clear();
+//! let mut mic = Device("mic", &log, false);
+//! let mut nested = SyncStart::new(SyncStart::new(&mut adc, &mut accel), &mut mic);
+//! assert_eq!(nested.start(), Ok(()));
+//! assert_eq!(*log.borrow(), ["adc", "accel", "mic"]);
+//!
+//! // A failed first start leaves the second device stopped
+//! log.borrow_mut().clear();
+//! let mut broken = Device("broken", &log, true);
+//! let result = SyncStart::new(&mut broken, &mut adc).start();
+//! assert_eq!(result, Err(SyncStartError::First("broken")));
+//! assert!(log.borrow().is_empty());
+//! # Ok::<(), SyncStartError<&str, &str>>(())
+//! ```
+
+/// A device that was armed and can be started with a single bus transaction.
+pub trait Start {
+    /// The error returned when starting fails.
+    type Error;
+
+    /// Start the armed device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the device fails.
+    fn start(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: Start + ?Sized> Start for &mut T {
+    type Error = T::Error;
+
+    #[inline]
+    fn start(&mut self) -> Result<(), Self::Error> { T::start(self) }
+}
+
+/// An asynchronous device that was armed and can be started with a single
+/// bus transaction.
+#[expect(async_fn_in_trait, reason = "Matches the `embedded-hal-async` traits")]
+pub trait AsyncStart {
+    /// The error returned when starting fails.
+    type Error;
+
+    /// Start the armed device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if communication with the device fails.
+    async fn start(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: AsyncStart + ?Sized> AsyncStart for &mut T {
+    type Error = T::Error;
+
+    #[inline]
+    async fn start(&mut self) -> Result<(), Self::Error> { T::start(self).await }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Starts two armed devices back-to-back, see the [module](self) docs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SyncStart<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> SyncStart<A, B> {
+    /// Create a new [`SyncStart`], starting `first` before `second`.
+    #[inline]
+    #[must_use]
+    pub const fn new(first: A, second: B) -> Self { Self { first, second } }
+
+    /// Get a reference to the device started first.
+    #[inline]
+    #[must_use]
+    pub const fn first(&self) -> &A { &self.first }
+
+    /// Get a reference to the device started second.
+    #[inline]
+    #[must_use]
+    pub const fn second(&self) -> &B { &self.second }
+
+    /// Release both devices.
+    #[inline]
+    #[must_use]
+    pub fn release(self) -> (A, B) { (self.first, self.second) }
+}
+
+impl<A: Start, B: Start> Start for SyncStart<A, B> {
+    type Error = SyncStartError<A::Error, B::Error>;
+
+    /// Start both devices, the second directly after the first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either device fails to start.
+    /// If the first device fails, the second is not started.
+    fn start(&mut self) -> Result<(), Self::Error> {
+        self.first.start().map_err(SyncStartError::First)?;
+        self.second.start().map_err(SyncStartError::Second)
+    }
+}
+
+impl<A: AsyncStart, B: AsyncStart> AsyncStart for SyncStart<A, B> {
+    type Error = SyncStartError<A::Error, B::Error>;
+
+    /// Start both devices, the second directly after the first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either device fails to start.
+    /// If the first device fails, the second is not started.
+    async fn start(&mut self) -> Result<(), Self::Error> {
+        self.first.start().await.map_err(SyncStartError::First)?;
+        self.second.start().await.map_err(SyncStartError::Second)
+    }
+}
+
+/// An error that can occur when starting a [`SyncStart`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SyncStartError<A, B> {
+    /// The first device failed to start, the second was not started.
+    First(A),
+    /// The second device failed to start, after the first was started.
+    Second(B),
+}